@@ -0,0 +1,3 @@
+//! Host-side helpers for PSP projects, for use from build scripts.
+
+pub mod pak;
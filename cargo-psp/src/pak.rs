@@ -0,0 +1,153 @@
+//! Host-side builder for `psp::pak` asset archives.
+//!
+//! Build scripts use this to pack assets at build time; the game opens the
+//! result with `psp::pak::PakReader`. The output is byte-for-byte what
+//! `psp::pak::PakBuilder` writes on the PSP for the same files.
+//!
+//! ```ignore
+//! // build.rs
+//! let mut pak = cargo_psp::pak::PakBuilder::new();
+//! pak.add_dir("assets")?;
+//! pak.write(std::path::Path::new(&std::env::var("OUT_DIR")?).join("assets.pak"))?;
+//! println!("cargo:rerun-if-changed=assets");
+//! ```
+
+use anyhow::{Context, Result, bail};
+use std::{fs, path::Path};
+
+const MAGIC: &[u8; 4] = b"PPAK";
+const VERSION: u32 = 1;
+const HEADER_SIZE: usize = 16;
+const ENTRY_SIZE: usize = 24;
+
+/// 64-bit FNV-1a, the path hash the archive's entry table is sorted by.
+fn fnv1a_64(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Normalize an asset path the way `psp::pak::normalize_path` does:
+/// backslashes become forward slashes and leading slashes are removed.
+pub fn normalize_path(path: &str) -> String {
+    path.trim_start_matches(['/', '\\']).replace('\\', "/")
+}
+
+/// Builds a pak archive in memory.
+///
+/// Paths are normalized with [`normalize_path`]. Adding a path twice, or
+/// two paths whose hashes collide, is an error.
+#[derive(Default)]
+pub struct PakBuilder {
+    files: Vec<(u64, String, Vec<u8>)>,
+}
+
+impl PakBuilder {
+    /// Create an empty builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `data` at `path`.
+    pub fn add(&mut self, path: &str, data: impl Into<Vec<u8>>) -> Result<()> {
+        let path = normalize_path(path);
+        if path.len() > u16::MAX as usize {
+            bail!("pak path too long: {:?}", path);
+        }
+        let hash = fnv1a_64(path.as_bytes());
+        match self.files.binary_search_by_key(&hash, |(h, _, _)| *h) {
+            Ok(index) => {
+                let existing = &self.files[index].1;
+                if *existing == path {
+                    bail!("duplicate pak path {:?}", path);
+                }
+                bail!("pak paths {:?} and {:?} have the same hash", existing, path);
+            },
+            Err(index) => {
+                self.files.insert(index, (hash, path, data.into()));
+                Ok(())
+            },
+        }
+    }
+
+    /// Add every file under `dir`, each at its path relative to `dir`.
+    pub fn add_dir(&mut self, dir: impl AsRef<Path>) -> Result<()> {
+        let dir = dir.as_ref();
+        self.add_dir_inner(dir, dir)
+    }
+
+    fn add_dir_inner(&mut self, root: &Path, dir: &Path) -> Result<()> {
+        let entries =
+            fs::read_dir(dir).with_context(|| format!("failed to read {}", dir.display()))?;
+        for entry in entries {
+            let path = entry?.path();
+            if path.is_dir() {
+                self.add_dir_inner(root, &path)?;
+                continue;
+            }
+            let name = path
+                .strip_prefix(root)?
+                .to_str()
+                .with_context(|| format!("non-UTF-8 asset path {}", path.display()))?
+                .to_owned();
+            let data =
+                fs::read(&path).with_context(|| format!("failed to read {}", path.display()))?;
+            self.add(&name, data)?;
+        }
+        Ok(())
+    }
+
+    /// Number of files added so far.
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Whether no files have been added.
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Serialize the archive.
+    pub fn finish(self) -> Result<Vec<u8>> {
+        let names_size: usize = self.files.iter().map(|(_, path, _)| path.len()).sum();
+        let data_size: usize = self.files.iter().map(|(_, _, data)| data.len()).sum();
+        let data_start = HEADER_SIZE + self.files.len() * ENTRY_SIZE + names_size;
+        let total = data_start + data_size;
+        if u32::try_from(total).is_err() {
+            bail!("pak archive too large ({} bytes)", total);
+        }
+
+        let mut out = Vec::with_capacity(total);
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&VERSION.to_le_bytes());
+        out.extend_from_slice(&(self.files.len() as u32).to_le_bytes());
+        out.extend_from_slice(&(names_size as u32).to_le_bytes());
+
+        let mut name_offset = 0;
+        let mut data_offset = data_start;
+        for (hash, path, data) in &self.files {
+            out.extend_from_slice(&hash.to_le_bytes());
+            out.extend_from_slice(&(data_offset as u32).to_le_bytes());
+            out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            out.extend_from_slice(&(name_offset as u32).to_le_bytes());
+            out.extend_from_slice(&(path.len() as u16).to_le_bytes());
+            out.extend_from_slice(&0u16.to_le_bytes());
+            name_offset += path.len();
+            data_offset += data.len();
+        }
+        for (_, path, _) in &self.files {
+            out.extend_from_slice(path.as_bytes());
+        }
+        for (_, _, data) in &self.files {
+            out.extend_from_slice(data);
+        }
+        Ok(out)
+    }
+
+    /// Serialize the archive and write it to `path`.
+    pub fn write(self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        fs::write(path, self.finish()?)
+            .with_context(|| format!("failed to write {}", path.display()))
+    }
+}
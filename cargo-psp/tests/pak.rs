@@ -0,0 +1,51 @@
+use cargo_psp::pak::PakBuilder;
+use std::{env, fs};
+
+/// Archive the PSP-side tests read back with `psp::pak::PakReader`.
+const FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../ci/tests/assets/host.pak");
+
+fn fixture_archive() -> Vec<u8> {
+    let mut builder = PakBuilder::new();
+    builder.add("sprites/hero.png", b"hero".as_slice()).unwrap();
+    builder
+        .add("\\levels\\1.bin", [1, 2, 3].as_slice())
+        .unwrap();
+    builder.add("empty", Vec::new()).unwrap();
+    builder.finish().unwrap()
+}
+
+#[test]
+fn matches_psp_fixture() {
+    let data = fixture_archive();
+    // Set PAK_FIXTURE_UPDATE=1 to rewrite the fixture after a format change.
+    if env::var_os("PAK_FIXTURE_UPDATE").is_some() {
+        fs::write(FIXTURE, &data).unwrap();
+    }
+    assert_eq!(data, fs::read(FIXTURE).unwrap());
+}
+
+#[test]
+fn duplicate_path() {
+    let mut builder = PakBuilder::new();
+    builder.add("a/b", b"1".as_slice()).unwrap();
+    assert!(builder.add("/a/b", b"2".as_slice()).is_err());
+    assert_eq!(builder.len(), 1);
+}
+
+#[test]
+fn add_dir_uses_relative_paths() {
+    let dir = env::temp_dir().join(format!("cargo-psp-pak-{}", std::process::id()));
+    fs::create_dir_all(dir.join("sprites")).unwrap();
+    fs::write(dir.join("sprites/hero.png"), b"hero").unwrap();
+    fs::write(dir.join("levels.bin"), b"").unwrap();
+
+    let mut from_dir = PakBuilder::new();
+    let result = from_dir.add_dir(&dir);
+    fs::remove_dir_all(&dir).unwrap();
+    result.unwrap();
+
+    let mut by_hand = PakBuilder::new();
+    by_hand.add("sprites/hero.png", b"hero".as_slice()).unwrap();
+    by_hand.add("levels.bin", Vec::new()).unwrap();
+    assert_eq!(from_dir.finish().unwrap(), by_hand.finish().unwrap());
+}
@@ -0,0 +1,48 @@
+use alloc::vec::Vec;
+use psp::test_runner::TestRunner;
+
+pub fn test_main(test_runner: &mut TestRunner) {
+    let before = psp::alloc_stats();
+    let small = Vec::<u8>::with_capacity(1000);
+    let during = psp::alloc_stats();
+    drop(small);
+    let after = psp::alloc_stats();
+
+    test_runner.check(
+        "small_alloc_count",
+        during.allocation_count,
+        before.allocation_count + 1,
+    );
+    test_runner.check_true(
+        "small_alloc_used",
+        during.used_bytes >= before.used_bytes + 1000,
+    );
+    test_runner.check_true(
+        "small_alloc_from_arena",
+        during.free_bytes < before.free_bytes,
+    );
+    test_runner.check_true("peak_tracks_used", during.peak_bytes >= during.used_bytes);
+    test_runner.check("dealloc_used", after.used_bytes, before.used_bytes);
+    test_runner.check(
+        "dealloc_count",
+        after.allocation_count,
+        before.allocation_count,
+    );
+
+    // Large allocations bypass the arena but still count.
+    let large = Vec::<u8>::with_capacity(512 * 1024);
+    let with_large = psp::alloc_stats();
+    drop(large);
+    test_runner.check_true(
+        "large_alloc_used",
+        with_large.used_bytes >= before.used_bytes + 512 * 1024,
+    );
+    test_runner.check_true(
+        "large_alloc_peak",
+        psp::alloc_stats().peak_bytes >= with_large.used_bytes,
+    );
+
+    psp::reset_peak();
+    let reset = psp::alloc_stats();
+    test_runner.check("reset_peak", reset.peak_bytes, reset.used_bytes);
+}
@@ -0,0 +1,96 @@
+use psp::audio_mixer::{
+    ChannelConfig, ChannelHandle, EchoSend, Effect, Mixer, DEFAULT_SAMPLE_COUNT, MAX_CHANNELS,
+};
+use psp::test_runner::TestRunner;
+
+use alloc::format;
+use alloc::vec;
+use alloc::vec::Vec;
+
+const FRAMES: usize = DEFAULT_SAMPLE_COUNT as usize;
+
+/// A full-scale sawtooth, so no stage can skip work on silence.
+static SAW: [i16; FRAMES * 2] = saw();
+
+const fn saw() -> [i16; FRAMES * 2] {
+    let mut samples = [0; FRAMES * 2];
+    let mut i = 0;
+    while i < FRAMES * 2 {
+        samples[i] = ((i / 2 % 64) as i32 * 1000 - 32000) as i16;
+        i += 1;
+    }
+    samples
+}
+
+const ITERATIONS: usize = 50;
+
+/// Microseconds of audio in one buffer at 44.1 kHz.
+const BUFFER_US: u64 = FRAMES as u64 * 1_000_000 / 44_100;
+
+/// Average microseconds to mix one buffer.
+fn mix_cost(mixer: &Mixer, out: &mut [i16]) -> u64 {
+    psp::benchmark(|| mixer.mix_into(out), ITERATIONS).as_micros() as u64
+}
+
+pub fn test_main(test_runner: &mut TestRunner) {
+    let mixer = Mixer::new(DEFAULT_SAMPLE_COUNT).unwrap();
+    let channels: Vec<ChannelHandle> = (0..MAX_CHANNELS)
+        .map(|_| {
+            let ch = mixer
+                .alloc_channel(ChannelConfig {
+                    looping: true,
+                    ..Default::default()
+                })
+                .unwrap();
+            unsafe { mixer.submit_samples(ch, &SAW).unwrap() };
+            ch
+        })
+        .collect();
+    let mut out = vec![0i16; FRAMES * 2];
+
+    let dry = mix_cost(&mixer, &mut out);
+
+    for &ch in &channels {
+        mixer
+            .set_channel_effect(
+                ch,
+                Some(Effect::LowPass {
+                    cutoff_normalized: 0.05,
+                }),
+            )
+            .unwrap();
+    }
+    let low_pass = mix_cost(&mixer, &mut out).saturating_sub(dry);
+    for &ch in &channels {
+        mixer.set_channel_effect(ch, None).unwrap();
+    }
+
+    mixer.set_echo(Some(EchoSend {
+        delay_samples: FRAMES * 4,
+        feedback: 0.5,
+        mix: 0.5,
+    }));
+    let echo_bus = mix_cost(&mixer, &mut out).saturating_sub(dry);
+    for &ch in &channels {
+        mixer.set_echo_send(ch, 0x4000).unwrap();
+    }
+    let echo_sends = mix_cost(&mixer, &mut out).saturating_sub(dry);
+    mixer.set_echo(None);
+
+    test_runner.dbg(
+        "audio_mixer_bench",
+        &format!(
+            "{} frames x {} channels: dry {} us, low-pass on all +{} us, echo bus +{} us, \
+             echo bus with all sending +{} us, buffer period {} us",
+            FRAMES, MAX_CHANNELS, dry, low_pass, echo_bus, echo_sends, BUFFER_US
+        ),
+    );
+
+    // The budgets the `Effect` docs promise.
+    test_runner.check_true("low_pass_all_channels", low_pass * 25 < BUFFER_US);
+    test_runner.check_true("echo_all_channels", echo_sends * 25 < BUFFER_US);
+
+    for ch in channels {
+        mixer.free_channel(ch).unwrap();
+    }
+}
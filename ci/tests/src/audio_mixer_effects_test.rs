@@ -0,0 +1,124 @@
+use psp::audio_mixer::{ChannelConfig, EchoSend, Effect, Mixer};
+use psp::test_runner::TestRunner;
+
+use alloc::vec::Vec;
+
+/// Constant 10000 on both sides.
+static DC: [i16; 128] = [10000; 128];
+/// Alternating +10000 / -10000 frames, the highest frequency there is.
+static NYQUIST: [i16; 128] = nyquist();
+/// One frame of 10000, then silence.
+static IMPULSE: [i16; 2] = [10000; 2];
+
+const fn nyquist() -> [i16; 128] {
+    let mut samples = [0; 128];
+    let mut i = 0;
+    while i < 128 {
+        samples[i] = if i / 2 % 2 == 0 { 10000 } else { -10000 };
+        i += 1;
+    }
+    samples
+}
+
+const LOW_PASS: Effect = Effect::LowPass {
+    cutoff_normalized: 0.05,
+};
+
+/// Peak left-side level of a looping channel playing `samples` once the
+/// filter has settled.
+fn settled_peak(mixer: &Mixer, samples: &'static [i16], effect: Option<Effect>) -> i16 {
+    let ch = mixer
+        .alloc_channel(ChannelConfig {
+            looping: true,
+            effect,
+            ..Default::default()
+        })
+        .unwrap();
+    unsafe { mixer.submit_samples(ch, samples).unwrap() };
+    let mut out = [0i16; 128];
+    for _ in 0..4 {
+        mixer.mix_into(&mut out);
+    }
+    mixer.free_channel(ch).unwrap();
+    out.iter()
+        .step_by(2)
+        .map(|s| s.saturating_abs())
+        .max()
+        .unwrap()
+}
+
+pub fn test_main(test_runner: &mut TestRunner) {
+    let mixer = Mixer::new(64).unwrap();
+
+    // DC passes, Nyquist is cut to about a/(2-a) = 16% at this cutoff.
+    let dc = settled_peak(&mixer, &DC, Some(LOW_PASS));
+    test_runner.check_true("low_pass_dc", (9990..=10000).contains(&dc));
+    let nyquist = settled_peak(&mixer, &NYQUIST, Some(LOW_PASS));
+    test_runner.check_true("low_pass_nyquist", (1000..2500).contains(&nyquist));
+    test_runner.check(
+        "unfiltered_nyquist",
+        settled_peak(&mixer, &NYQUIST, None),
+        10000,
+    );
+
+    // A reallocated channel's filter starts from silence, not from the
+    // last sound's level.
+    let ch = mixer
+        .alloc_channel(ChannelConfig {
+            effect: Some(LOW_PASS),
+            ..Default::default()
+        })
+        .unwrap();
+    unsafe { mixer.submit_samples(ch, &DC).unwrap() };
+    let mut out = [0i16; 128];
+    mixer.mix_into(&mut out);
+    test_runner.check_true("low_pass_reset", out[0] > 0 && out[0] < 5000);
+    mixer.free_channel(ch).unwrap();
+
+    // The impulse repeats after 100 frames, at half level 100 frames later.
+    let mixer = Mixer::new(64).unwrap();
+    mixer.set_echo(Some(EchoSend {
+        delay_samples: 100,
+        feedback: 0.5,
+        mix: 1.0,
+    }));
+    test_runner.check(
+        "echo_delay",
+        mixer.echo().map(|e| e.delay_samples),
+        Some(100),
+    );
+    let ch = mixer
+        .alloc_channel(ChannelConfig {
+            echo_send: 0x8000,
+            ..Default::default()
+        })
+        .unwrap();
+    unsafe { mixer.submit_samples(ch, &IMPULSE).unwrap() };
+    let mut left = Vec::new();
+    for _ in 0..4 {
+        mixer.mix_into(&mut out);
+        left.extend(out.iter().step_by(2).copied());
+    }
+    test_runner.check("echo_dry", left[0], 10000);
+    test_runner.check("echo_first_repeat", left[100], 10000);
+    test_runner.check("echo_second_repeat", left[200], 5000);
+    test_runner.check(
+        "echo_silent_between",
+        left.iter().filter(|&&s| s != 0).count(),
+        3,
+    );
+
+    // Delays shorter than a buffer are lengthened to one.
+    mixer.set_echo(Some(EchoSend {
+        delay_samples: 10,
+        feedback: 0.0,
+        mix: 1.0,
+    }));
+    test_runner.check(
+        "echo_min_delay",
+        mixer.echo().map(|e| e.delay_samples),
+        Some(64),
+    );
+    mixer.set_echo(None);
+    test_runner.check("echo_removed", mixer.echo(), None);
+}
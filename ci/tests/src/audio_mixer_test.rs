@@ -0,0 +1,161 @@
+use psp::audio_mixer::{ChannelConfig, ChannelState, Mixer, MixerError, StealPolicy, MAX_CHANNELS};
+use psp::test_runner::TestRunner;
+
+/// Two stereo frames of silence.
+static SHORT: [i16; 4] = [0; 4];
+/// Long enough to still be playing after one mix pass.
+static LONG: [i16; 4096] = [0; 4096];
+/// 40 stereo frames whose left sample is the frame index.
+static RAMP: [i16; 80] = ramp();
+
+const fn ramp() -> [i16; 80] {
+    let mut samples = [0; 80];
+    let mut i = 0;
+    while i < 40 {
+        samples[i * 2] = i as i16;
+        samples[i * 2 + 1] = -(i as i16);
+        i += 1;
+    }
+    samples
+}
+
+pub fn test_main(test_runner: &mut TestRunner) {
+    let mixer = Mixer::new(64).unwrap();
+    let mut out = [0i16; 128];
+
+    // A one-shot returns its channel to Free once the buffer is consumed.
+    let sfx = mixer.play_one_shot(&SHORT, 0x8000, 0.0).unwrap();
+    test_runner.check(
+        "one_shot_playing",
+        mixer.channel_state(sfx),
+        Ok(ChannelState::Playing),
+    );
+    mixer.mix_into(&mut out);
+    test_runner.check(
+        "one_shot_freed",
+        mixer.channel_state(sfx),
+        Ok(ChannelState::Free),
+    );
+
+    // Fill every channel, then steal the oldest one-shot.
+    let first = mixer.play_one_shot_tagged(&LONG, 0x8000, 0.0, 7).unwrap();
+    for _ in 1..MAX_CHANNELS {
+        mixer.play_one_shot(&LONG, 0x8000, 0.0).unwrap();
+    }
+    let stolen = mixer.play_one_shot(&LONG, 0x8000, 0.0).unwrap();
+    test_runner.check("steal_oldest", stolen, first);
+    test_runner.check("steal_resets_tag", mixer.channel_tag(stolen), Ok(0));
+
+    mixer.set_steal_policy(StealPolicy::Refuse);
+    test_runner.check(
+        "steal_refuse",
+        mixer.play_one_shot(&LONG, 0x8000, 0.0),
+        Err(MixerError::NoFreeChannels),
+    );
+
+    mixer.stop_all();
+    test_runner.check(
+        "stop_all_frees_one_shots",
+        mixer.channel_state(stolen),
+        Ok(ChannelState::Free),
+    );
+
+    // Tagged stop leaves other groups alone; allocated channels go idle.
+    let music = mixer.alloc_channel(ChannelConfig::default()).unwrap();
+    unsafe { mixer.submit_samples(music, &LONG).unwrap() };
+    mixer.set_channel_tag(music, 2).unwrap();
+    let step = mixer.play_one_shot_tagged(&LONG, 0x8000, 0.0, 1).unwrap();
+    test_runner.check("stop_tag_count", mixer.stop_tag(1), 1);
+    test_runner.check(
+        "stop_tag_frees",
+        mixer.channel_state(step),
+        Ok(ChannelState::Free),
+    );
+    test_runner.check(
+        "stop_tag_other_group",
+        mixer.channel_state(music),
+        Ok(ChannelState::Playing),
+    );
+    mixer.stop_all();
+    test_runner.check(
+        "stop_all_keeps_allocated",
+        mixer.channel_state(music),
+        Ok(ChannelState::Idle),
+    );
+
+    // With only allocated channels playing, the oldest non-looping one is
+    // lent to the one-shot and comes back to its owner afterwards.
+    mixer.set_steal_policy(StealPolicy::StealOldest);
+    unsafe { mixer.submit_samples(music, &LONG).unwrap() };
+    let others: [_; MAX_CHANNELS - 1] = core::array::from_fn(|_| {
+        let ch = mixer.alloc_channel(ChannelConfig::default()).unwrap();
+        unsafe { mixer.submit_samples(ch, &LONG).unwrap() };
+        ch
+    });
+    let lent = mixer.play_one_shot_tagged(&SHORT, 0x8000, 0.0, 5).unwrap();
+    test_runner.check("steal_allocated", lent, music);
+    test_runner.check("steal_allocated_tag", mixer.channel_tag(music), Ok(5));
+    mixer.mix_into(&mut out);
+    test_runner.check(
+        "steal_allocated_returned",
+        mixer.channel_state(music),
+        Ok(ChannelState::Idle),
+    );
+    test_runner.check("steal_allocated_owner_tag", mixer.channel_tag(music), Ok(2));
+    for ch in others {
+        mixer.free_channel(ch).unwrap();
+    }
+
+    // Streaming: a queued buffer follows the current one without a gap.
+    test_runner.check("queue_idle", mixer.queued(music), Ok(0));
+    unsafe { mixer.queue_samples(music, &SHORT).unwrap() };
+    unsafe { mixer.queue_samples(music, &LONG).unwrap() };
+    test_runner.check("queue_two", mixer.queued(music), Ok(2));
+    test_runner.check(
+        "queue_full",
+        unsafe { mixer.queue_samples(music, &LONG) },
+        Err(MixerError::QueueFull),
+    );
+    mixer.mix_into(&mut out);
+    test_runner.check("queue_advanced", mixer.queued(music), Ok(1));
+
+    mixer.pause(music).unwrap();
+    test_runner.check("pause", mixer.is_paused(music), Ok(true));
+    mixer.resume(music).unwrap();
+    test_runner.check("resume", mixer.is_paused(music), Ok(false));
+    mixer.stop(music).unwrap();
+    test_runner.check("stop_clears_queue", mixer.queued(music), Ok(0));
+    mixer.free_channel(music).unwrap();
+
+    // Loop region: frames 0..10 play once, then 10..30 repeat. 64 output
+    // frames cross the seam twice.
+    let looped = mixer
+        .alloc_channel(ChannelConfig {
+            looping: true,
+            loop_start: 10,
+            loop_end: 30,
+            ..Default::default()
+        })
+        .unwrap();
+    unsafe { mixer.submit_samples(looped, &RAMP).unwrap() };
+    mixer.mix_into(&mut out);
+    let expected = (0..64).map(|i| if i < 30 { i } else { 10 + (i - 30) % 20 });
+    test_runner.check_true(
+        "loop_region_continuity",
+        out.chunks(2)
+            .zip(expected)
+            .all(|(frame, i)| frame[0] == i && frame[1] == -i),
+    );
+    // The next pass picks up right after the last frame mixed.
+    let last = out[126];
+    mixer.mix_into(&mut out);
+    let next = if last == 29 { 10 } else { last + 1 };
+    test_runner.check("loop_region_across_buffers", out[0], next);
+
+    // An end of 0 loops to the end of the buffer.
+    mixer.set_loop_region(looped, 30, 0).unwrap();
+    unsafe { mixer.submit_samples(looped, &RAMP).unwrap() };
+    mixer.mix_into(&mut out);
+    test_runner.check("loop_to_end_seam", (out[78], out[80]), (39, 30));
+    mixer.free_channel(looped).unwrap();
+}
@@ -0,0 +1,123 @@
+extern crate alloc;
+
+use alloc::vec::Vec;
+use psp::audio::{LoopedStream, PcmSource, StreamSource};
+use psp::test_runner::TestRunner;
+
+/// Frames in the test track.
+const FRAMES: usize = 40;
+
+/// Interleaved stereo frames whose samples are `(i, -i)` for frame `i`.
+fn ramp() -> Vec<i16> {
+    (0..FRAMES as i16).flat_map(|i| [i, -i]).collect()
+}
+
+/// A source that can't seek and hands out odd-sized blocks, like a
+/// decoder whose frames don't line up with the loop points.
+struct Blocks {
+    samples: Vec<i16>,
+    position: usize,
+}
+
+impl StreamSource for Blocks {
+    fn next_block(&mut self) -> &[i16] {
+        let start = self.position;
+        self.position = (start + 7 * 2).min(self.samples.len());
+        &self.samples[start..self.position]
+    }
+
+    fn rewind(&mut self) -> bool {
+        self.position = 0;
+        true
+    }
+}
+
+/// Fill `total` frames from `stream` in uneven pieces and return the left
+/// samples.
+fn play<S: StreamSource>(stream: &mut LoopedStream<S>, total: usize) -> Vec<i16> {
+    let mut out = Vec::new();
+    let mut buf = [0i16; 26];
+    let mut sizes = [6, 26, 2, 18].iter().cycle();
+    while out.len() < total {
+        let size = *sizes.next().unwrap();
+        let len = stream.fill(&mut buf[..size]);
+        for frame in buf[..len].chunks(2) {
+            if frame[1] != -frame[0] {
+                // Mark a torn frame so the comparison fails.
+                out.push(i16::MIN);
+            } else {
+                out.push(frame[0]);
+            }
+        }
+        if len < size {
+            break;
+        }
+    }
+    out.truncate(total);
+    out
+}
+
+/// Frame indices of an intro up to `start` followed by `start..end`
+/// repeating.
+fn expected(start: usize, end: usize, total: usize) -> Vec<i16> {
+    (0..total)
+        .map(|i| {
+            if i < end {
+                i
+            } else {
+                start + (i - end) % (end - start)
+            }
+        })
+        .map(|i| i as i16)
+        .collect()
+}
+
+pub fn test_main(test_runner: &mut TestRunner) {
+    // Played once, the stream ends with the source.
+    let mut once = LoopedStream::new(PcmSource::new(ramp()));
+    test_runner.check_large_collection(
+        "play_once",
+        &play(&mut once, 100),
+        &expected(0, FRAMES, FRAMES),
+    );
+    test_runner.check_true("play_once_finished", once.is_finished());
+
+    // Intro, then a region ending before the source does.
+    let mut region = LoopedStream::new(PcmSource::new(ramp())).with_loop(10, Some(30));
+    test_runner.check_large_collection(
+        "loop_region_seekable",
+        &play(&mut region, 150),
+        &expected(10, 30, 150),
+    );
+
+    // Looping to the end of the source.
+    let mut to_end = LoopedStream::new(PcmSource::new(ramp())).with_loop(25, None);
+    test_runner.check_large_collection(
+        "loop_to_end",
+        &play(&mut to_end, 150),
+        &expected(25, FRAMES, 150),
+    );
+
+    // Without seek support the stream rewinds and skips to the loop start,
+    // with block boundaries falling inside the region.
+    let blocks = Blocks {
+        samples: ramp(),
+        position: 0,
+    };
+    let mut decoded = LoopedStream::new(blocks).with_loop(10, Some(30));
+    test_runner.check_large_collection(
+        "loop_region_rewind",
+        &play(&mut decoded, 150),
+        &expected(10, 30, 150),
+    );
+    test_runner.check_true("loop_not_finished", !decoded.is_finished());
+
+    // A loop start past the end of the source can't loop.
+    let mut past_end = LoopedStream::new(PcmSource::new(ramp())).with_loop(50, None);
+    test_runner.check_large_collection(
+        "loop_start_past_end",
+        &play(&mut past_end, 150),
+        &expected(0, FRAMES, FRAMES),
+    );
+    test_runner.check_true("loop_start_past_end_finished", past_end.is_finished());
+}
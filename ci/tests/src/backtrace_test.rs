@@ -0,0 +1,32 @@
+use psp::debug::backtrace;
+use psp::test_runner::TestRunner;
+
+#[inline(never)]
+fn outer() -> alloc::vec::Vec<u32> {
+    inner()
+}
+
+#[inline(never)]
+fn inner() -> alloc::vec::Vec<u32> {
+    backtrace(16)
+}
+
+pub fn test_main(test_runner: &mut TestRunner) {
+    let frames = outer();
+    test_runner.check_true("backtrace_has_callers", frames.len() >= 2);
+    test_runner.check_true("backtrace_respects_max", frames.len() <= 16);
+    test_runner.check_true(
+        "backtrace_aligned",
+        frames.iter().all(|&addr| addr % 4 == 0),
+    );
+
+    // Whether or not `inner` and `outer` tail-call, some frame returns
+    // into this function, just after its first call.
+    let here = test_main as fn(&mut TestRunner) as usize as u32;
+    test_runner.check_true(
+        "backtrace_reaches_test_main",
+        frames.iter().any(|&ra| ra > here && ra - here < 0x400),
+    );
+
+    test_runner.check("backtrace_zero_frames", backtrace(0).len(), 0);
+}
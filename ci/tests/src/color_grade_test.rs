@@ -0,0 +1,66 @@
+use psp::framebuffer::PixelSurface;
+use psp::gu_ext::ColorGrade;
+use psp::sys::DisplayPixelFormat;
+use psp::test_runner::TestRunner;
+
+pub fn test_main(test_runner: &mut TestRunner) {
+    let identity = ColorGrade::new();
+    test_runner.check_true(
+        "identity_tables",
+        identity
+            .tables()
+            .iter()
+            .all(|table| table.iter().enumerate().all(|(i, &v)| v as usize == i)),
+    );
+    test_runner.check(
+        "identity_color",
+        identity.map_color(0x8012_3456),
+        0x8012_3456,
+    );
+
+    let bright = ColorGrade::new().brightness(0.5);
+    test_runner.check("brightness_black", bright.tables()[0][0], 128);
+    test_runner.check("brightness_clamped", bright.tables()[0][255], 255);
+    test_runner.check(
+        "contrast_flat",
+        ColorGrade::new().contrast(0.0).tables()[1][10],
+        128,
+    );
+    test_runner.check("gamma", ColorGrade::new().gamma(2.0).tables()[2][64], 128);
+
+    // Alpha is kept, each channel scaled on its own.
+    let tint = ColorGrade::new().tint(1.0, 0.5, 0.0);
+    test_runner.check("tint", tint.map_color(0x40FF_FFFF), 0x4000_80FF);
+
+    // Fully desaturated red is its luma, 0.299.
+    let gray = ColorGrade::new().saturation(0.0);
+    test_runner.check("desaturate", gray.map_color(0xFF00_00FF), 0xFF4D_4D4D);
+
+    test_runner.check_true("sepia_mixes", ColorGrade::sepia().mixes_channels());
+    test_runner.check_true("sepia_cpu_only", !ColorGrade::sepia().ge_supported());
+    test_runner.check_true(
+        "gamma_cpu_only",
+        !ColorGrade::new().gamma(1.5).ge_supported(),
+    );
+    test_runner.check_true(
+        "linear_on_ge",
+        ColorGrade::new()
+            .brightness(-0.1)
+            .contrast(1.5)
+            .tint(1.0, 0.9, 0.8)
+            .ge_supported(),
+    );
+    test_runner.check_true("night_cpu_only", !ColorGrade::night().ge_supported());
+
+    let half_red = ColorGrade::new().tint(0.5, 1.0, 1.0);
+    let mut pixels = [0x12FF_FFFFu32, 0xFF00_0000];
+    let mut surface = PixelSurface::from_slice(&mut pixels, 2, 1, 2, DisplayPixelFormat::Psm8888);
+    half_red.apply_cpu(&mut surface);
+    test_runner.check_large_collection("apply_8888", &pixels, &[0x12FF_FF80, 0xFF00_0000]);
+
+    // Two white 5650 pixels packed into one word.
+    let mut packed = [0xFFFF_FFFFu32];
+    let mut surface = PixelSurface::from_slice(&mut packed, 2, 1, 2, DisplayPixelFormat::Psm5650);
+    half_red.apply_cpu(&mut surface);
+    test_runner.check("apply_5650", packed[0], 0xFFF0_FFF0);
+}
@@ -0,0 +1,62 @@
+use psp::config::{Config, ConfigError, ConfigValue};
+use psp::test_runner::TestRunner;
+
+/// A version 1 file (no checksum) holding `volume = I32(7)`, `name =
+/// Str("psp")`.
+const V1_FILE: &[u8] = b"RCFG\x01\x00\x02\x00\
+    \x06volume\x01\x04\x00\x07\x00\x00\x00\
+    \x04name\x04\x03\x00psp";
+
+fn sample() -> Config {
+    let mut config = Config::new();
+    config.set("volume", ConfigValue::I32(7));
+    config.set("name", ConfigValue::Str("psp".into()));
+    config
+}
+
+fn matches_sample(config: &Config) -> bool {
+    config.len() == 2
+        && config.get_i32("volume") == Some(7)
+        && config.get_str("name") == Some("psp")
+}
+
+pub fn test_main(test_runner: &mut TestRunner) {
+    let bytes = sample().to_bytes().unwrap();
+    test_runner.check("config_v2_version", &bytes[4..6], &[2u8, 0][..]);
+    test_runner.check(
+        "config_v2_checksum",
+        &bytes[bytes.len() - 4..],
+        &psp::hash::crc32(&bytes[..bytes.len() - 4]).to_le_bytes()[..],
+    );
+    test_runner.check_true(
+        "config_v2_roundtrip",
+        Config::from_bytes(&bytes).is_ok_and(|c| matches_sample(&c)),
+    );
+
+    let mut corrupt = bytes.clone();
+    corrupt[10] ^= 0x20;
+    test_runner.check_true(
+        "config_v2_corrupt",
+        matches!(
+            Config::from_bytes(&corrupt),
+            Err(ConfigError::ChecksumMismatch)
+        ),
+    );
+    test_runner.check_true(
+        "config_v2_truncated",
+        Config::from_bytes(&bytes[..bytes.len() - 1]).is_err(),
+    );
+
+    let old = Config::from_bytes(V1_FILE);
+    test_runner.check_true("config_v1_load", old.as_ref().is_ok_and(matches_sample));
+    // A loaded version 1 config is written back as version 2.
+    let upgraded = old.unwrap().to_bytes().unwrap();
+    test_runner.check("config_v1_upgrade", upgraded, bytes);
+
+    let mut future = V1_FILE.to_vec();
+    future[4] = 3;
+    test_runner.check_true(
+        "config_unknown_version",
+        matches!(Config::from_bytes(&future), Err(ConfigError::InvalidFormat)),
+    );
+}
@@ -0,0 +1,129 @@
+use psp::config::{Config, ConfigSchema, ConfigValue, Kind, Rule, Violation};
+use psp::test_runner::TestRunner;
+
+extern crate alloc;
+use alloc::string::String;
+use alloc::vec;
+
+fn schema() -> ConfigSchema {
+    ConfigSchema::new(3)
+        .key("volume", Kind::I32, Rule::Range(0..=100), 80)
+        .key("player_name", Kind::Str, Rule::MaxLen(8), "Player")
+        .key("gamma", Kind::F32, Rule::FloatRange(0.5..=2.0), 1.0f32)
+        // Release 1 called the volume "vol".
+        .migration(1, |cfg| {
+            if let Some(v) = cfg.remove("vol") {
+                cfg.set("volume", v);
+            }
+        })
+        // Release 2 stored the volume out of 10.
+        .migration(2, |cfg| {
+            if let Some(v) = cfg.get_i32("volume") {
+                cfg.set("volume", ConfigValue::I32(v * 10));
+            }
+        })
+}
+
+fn key(name: &str) -> String {
+    String::from(name)
+}
+
+pub fn test_main(test_runner: &mut TestRunner) {
+    let schema = schema();
+
+    let mut cfg = Config::new();
+    cfg.set("volume", ConfigValue::I32(150));
+    cfg.set("player_name", ConfigValue::I32(1));
+    cfg.set("extra", ConfigValue::Bool(true));
+    cfg.set_app_version(3);
+    test_runner.check(
+        "schema_validate",
+        schema.validate(&cfg),
+        vec![
+            Violation::OutOfRange { key: key("volume") },
+            Violation::WrongType {
+                key: key("player_name"),
+                expected: Kind::Str,
+                found: Kind::I32,
+            },
+            Violation::Missing { key: key("gamma") },
+            Violation::UnknownKey { key: key("extra") },
+        ],
+    );
+
+    let fixed = schema.apply_defaults(&mut cfg);
+    test_runner.check("schema_defaults_fixed", fixed.len(), 3);
+    test_runner.check("schema_defaults_volume", cfg.get_i32("volume"), Some(80));
+    test_runner.check(
+        "schema_defaults_name",
+        cfg.get_str("player_name"),
+        Some("Player"),
+    );
+    test_runner.check("schema_defaults_gamma", cfg.get_f32("gamma"), Some(1.0));
+    test_runner.check(
+        "schema_defaults_keeps_unknown",
+        schema.validate(&cfg),
+        vec![Violation::UnknownKey { key: key("extra") }],
+    );
+
+    let mut long_name = Config::new();
+    long_name.set("player_name", ConfigValue::Str("ninechars".into()));
+    test_runner.check_true(
+        "schema_max_len",
+        schema
+            .validate(&long_name)
+            .contains(&Violation::OutOfRange {
+                key: key("player_name"),
+            }),
+    );
+
+    let mut nan = Config::new();
+    nan.set("gamma", ConfigValue::F32(f32::NAN));
+    test_runner.check_true(
+        "schema_nan_out_of_range",
+        schema
+            .validate(&nan)
+            .contains(&Violation::OutOfRange { key: key("gamma") }),
+    );
+
+    // A release 1 file runs both migrations.
+    let mut v1 = Config::new();
+    v1.set("vol", ConfigValue::I32(7));
+    v1.set_app_version(1);
+    schema.upgrade(&mut v1);
+    test_runner.check("schema_upgrade_v1", v1.get_i32("volume"), Some(70));
+    test_runner.check("schema_upgrade_version", v1.app_version(), Some(3));
+    test_runner.check("schema_upgrade_clean", schema.validate(&v1), vec![]);
+
+    // A release 2 file only runs the second.
+    let mut v2 = Config::new();
+    v2.set("volume", ConfigValue::I32(5));
+    v2.set_app_version(2);
+    schema.upgrade(&mut v2);
+    test_runner.check("schema_upgrade_v2", v2.get_i32("volume"), Some(50));
+
+    // An up-to-date file isn't migrated again.
+    let before = v2.get_i32("volume");
+    schema.upgrade(&mut v2);
+    test_runner.check("schema_upgrade_idempotent", v2.get_i32("volume"), before);
+
+    // No stored version counts as release 0.
+    let mut unversioned = Config::new();
+    unversioned.set("vol", ConfigValue::I32(3));
+    let fixed = schema.upgrade(&mut unversioned);
+    test_runner.check(
+        "schema_upgrade_unversioned",
+        unversioned.get_i32("volume"),
+        Some(30),
+    );
+    test_runner.check(
+        "schema_upgrade_reports_fixed",
+        fixed,
+        vec![
+            Violation::Missing {
+                key: key("player_name"),
+            },
+            Violation::Missing { key: key("gamma") },
+        ],
+    );
+}
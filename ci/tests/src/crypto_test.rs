@@ -0,0 +1,53 @@
+use psp::crypto::{chacha20_block, chacha20_xor, fill_random};
+use psp::test_runner::TestRunner;
+
+/// RFC 8439 section 2.3.2 keystream block.
+const BLOCK: [u8; 64] = [
+    0x10, 0xf1, 0xe7, 0xe4, 0xd1, 0x3b, 0x59, 0x15, 0x50, 0x0f, 0xdd, 0x1f, 0xa3, 0x20, 0x71, 0xc4,
+    0xc7, 0xd1, 0xf4, 0xc7, 0x33, 0xc0, 0x68, 0x03, 0x04, 0x22, 0xaa, 0x9a, 0xc3, 0xd4, 0x6c, 0x4e,
+    0xd2, 0x82, 0x64, 0x46, 0x07, 0x9f, 0xaa, 0x09, 0x14, 0xc2, 0xd7, 0x05, 0xd9, 0x8b, 0x02, 0xa2,
+    0xb5, 0x12, 0x9c, 0xd1, 0xde, 0x16, 0x4e, 0xb9, 0xcb, 0xd0, 0x83, 0xe8, 0xa2, 0x50, 0x3c, 0x4e,
+];
+
+/// RFC 8439 section 2.4.2 plaintext.
+const SUNSCREEN: &[u8] = b"Ladies and Gentlemen of the class of '99: If I could offer you \
+only one tip for the future, sunscreen would be it.";
+
+/// First and last 16 bytes of the section 2.4.2 ciphertext.
+const SUNSCREEN_HEAD: [u8; 16] = [
+    0x6e, 0x2e, 0x35, 0x9a, 0x25, 0x68, 0xf9, 0x80, 0x41, 0xba, 0x07, 0x28, 0xdd, 0x0d, 0x69, 0x81,
+];
+const SUNSCREEN_TAIL: [u8; 16] = [
+    0x0b, 0xbf, 0x74, 0xa3, 0x5b, 0xe6, 0xb4, 0x0b, 0x8e, 0xed, 0xf2, 0x78, 0x5e, 0x42, 0x87, 0x4d,
+];
+
+pub fn test_main(test_runner: &mut TestRunner) {
+    let key: [u8; 32] = core::array::from_fn(|i| i as u8);
+
+    let nonce = [0, 0, 0, 0x09, 0, 0, 0, 0x4a, 0, 0, 0, 0];
+    test_runner.check_large_collection("chacha20_block", &chacha20_block(&key, &nonce, 1), &BLOCK);
+
+    let nonce = [0, 0, 0, 0, 0, 0, 0, 0x4a, 0, 0, 0, 0];
+    let mut data = [0u8; 114];
+    data.copy_from_slice(SUNSCREEN);
+    chacha20_xor(&key, &nonce, 1, &mut data);
+    test_runner.check_large_collection("chacha20_head", &data[..16], &SUNSCREEN_HEAD);
+    test_runner.check_large_collection("chacha20_tail", &data[98..], &SUNSCREEN_TAIL);
+
+    chacha20_xor(&key, &nonce, 1, &mut data);
+    test_runner.check_true("chacha20_roundtrip", data[..] == *SUNSCREEN);
+
+    // Reseeding the game's generator doesn't repeat nonces.
+    let mut first = [0u8; 12];
+    let mut second = [0u8; 12];
+    psp::rand::seed_global(1);
+    fill_random(&mut first);
+    psp::rand::seed_global(1);
+    fill_random(&mut second);
+    test_runner.check_true("fill_random_unseeded", first != second);
+
+    // A length that isn't a whole number of words.
+    let mut odd = [0u8; 7];
+    fill_random(&mut odd);
+    test_runner.check_true("fill_random_odd_len", odd != [0; 7]);
+}
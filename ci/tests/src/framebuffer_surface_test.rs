@@ -0,0 +1,72 @@
+use psp::framebuffer::PixelSurface;
+use psp::sys::DisplayPixelFormat;
+use psp::test_runner::TestRunner;
+
+const RED: u32 = 0xFF00_00FF;
+const BLUE: u32 = 0xFFFF_0000;
+
+pub fn test_main(test_runner: &mut TestRunner) {
+    // 8888: 6x4 pixels in rows of 8.
+    let mut pixels = [0u32; 8 * 4];
+    let mut surface = PixelSurface::from_slice(&mut pixels, 6, 4, 8, DisplayPixelFormat::Psm8888);
+    surface.set_pixel(5, 3, RED);
+    test_runner.check("get_pixel", surface.get_pixel(5, 3), Some(RED));
+    test_runner.check("get_pixel_outside", surface.get_pixel(6, 0), None);
+    test_runner.check("get_pixel_negative", surface.get_pixel(-1, 0), None);
+    // Past the width but within the stride: must not be written.
+    surface.set_pixel(6, 0, RED);
+
+    // A fill hanging off the top-left corner is clipped.
+    surface.fill_rect(-2, -2, 4, 4, BLUE);
+    test_runner.check("fill_inside", surface.get_pixel(1, 1), Some(BLUE));
+    test_runner.check("fill_clipped", surface.get_pixel(2, 0), Some(0));
+    drop(surface);
+    test_runner.check("set_pixel_stride_padding", pixels[6], 0);
+    test_runner.check("set_pixel_raw", pixels[3 * 8 + 5], RED);
+
+    // 5650 packs two pixels per word and drops low color bits.
+    let mut packed = [0u32; 4];
+    let mut small = PixelSurface::from_slice(&mut packed, 4, 2, 4, DisplayPixelFormat::Psm5650);
+    small.set_pixel(0, 0, RED);
+    small.set_pixel(1, 0, 0x0080_8080);
+    test_runner.check("rgb565_red", small.get_pixel(0, 0), Some(RED));
+    test_runner.check(
+        "rgb565_round_trip",
+        small.get_pixel(1, 0),
+        Some(0xFF84_8284),
+    );
+
+    // Blitting converts between formats and clips to both surfaces.
+    let mut src_pixels = [BLUE; 3 * 3];
+    let src = PixelSurface::from_slice(&mut src_pixels, 3, 3, 3, DisplayPixelFormat::Psm8888);
+    small.blit(&src, 0, 0, 2, -1, 3, 3);
+    test_runner.check("blit_converted", small.get_pixel(3, 1), Some(BLUE));
+    test_runner.check(
+        "blit_clipped_left",
+        small.get_pixel(1, 1),
+        Some(0xFF00_0000),
+    );
+    test_runner.check("blit_kept", small.get_pixel(0, 0), Some(RED));
+    drop(small);
+    test_runner.check("rgb565_raw", packed[0] & 0xFFFF, 0x001F);
+
+    // Sizes whose byte count overflows are refused, not wrapped.
+    let mut tiny = [0u32; 4];
+    test_runner.check_true(
+        "oversized_refused",
+        PixelSurface::try_from_slice(&mut tiny, 1, 65536, 65536, DisplayPixelFormat::Psm8888)
+            .is_none(),
+    );
+    test_runner.check_true(
+        "too_small_refused",
+        PixelSurface::try_from_slice(&mut tiny, 4, 2, 4, DisplayPixelFormat::Psm8888).is_none(),
+    );
+    test_runner.check_true(
+        "width_past_stride_refused",
+        PixelSurface::try_from_slice(&mut tiny, 3, 1, 2, DisplayPixelFormat::Psm8888).is_none(),
+    );
+    test_runner.check_true(
+        "exact_fit_accepted",
+        PixelSurface::try_from_slice(&mut tiny, 4, 2, 4, DisplayPixelFormat::Psm5650).is_some(),
+    );
+}
@@ -0,0 +1,38 @@
+use psp::gu_ext::{AlignedBuf, CallList};
+use psp::test_runner::TestRunner;
+
+pub fn test_main(test_runner: &mut TestRunner) {
+    let mut buf = AlignedBuf::new(100);
+    test_runner.check("buf_capacity_rounded", buf.capacity(), 112);
+    test_runner.check("buf_aligned", buf.as_ptr() as usize % 16, 0);
+    test_runner.check_true("buf_starts_empty", buf.is_empty());
+
+    let first = buf.alloc(20).unwrap();
+    test_runner.check(
+        "alloc_first_at_start",
+        first as usize,
+        buf.as_ptr() as usize,
+    );
+    test_runner.check("alloc_rounded", buf.len(), 32);
+    let second = buf.alloc(16).unwrap();
+    test_runner.check("alloc_second_offset", second as usize - first as usize, 32);
+    test_runner.check("buf_remaining", buf.remaining(), 64);
+
+    test_runner.check_true("alloc_too_large", buf.alloc(65).is_none());
+    test_runner.check("failed_alloc_keeps_len", buf.len(), 48);
+    test_runner.check_true("alloc_exact_fit", buf.alloc(64).is_some());
+    test_runner.check("buf_full", buf.remaining(), 0);
+
+    buf.clear();
+    test_runner.check_true("clear_empties", buf.is_empty());
+    test_runner.check(
+        "clear_reuses_start",
+        buf.alloc(1).unwrap() as usize,
+        buf.as_ptr() as usize,
+    );
+
+    let list = CallList::new(4096);
+    test_runner.check_true("call_list_starts_empty", list.is_empty());
+    test_runner.check("call_list_capacity", list.capacity(), 4096);
+    test_runner.check_true("call_list_not_recording", !list.is_recording());
+}
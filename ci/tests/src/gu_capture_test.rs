@@ -0,0 +1,124 @@
+use psp::gu_ext::capture::{
+    command_name, CaptureError, CaptureHeader, CapturedList, GeWord, ReplayList, HEADER_SIZE,
+};
+use psp::gu_ext::{dump_list, validate_list, ListIssue};
+use psp::test_runner::TestRunner;
+
+extern crate alloc;
+use alloc::vec;
+
+pub fn test_main(test_runner: &mut TestRunner) {
+    test_runner.check("name_prim", command_name(0x04), Some("PRIM"));
+    test_runner.check("name_vaddr", command_name(0x01), Some("VADDR"));
+    test_runner.check("name_tme", command_name(0x1e), Some("TME"));
+    test_runner.check("name_tbp3", command_name(0xa3), Some("TBP3"));
+    test_runner.check("name_finish", command_name(0x0f), Some("FINISH"));
+    test_runner.check("name_unknown", command_name(0xff), None);
+
+    let word = GeWord::decode(0x0401_0006);
+    test_runner.check("decode_cmd", word.cmd, 0x04);
+    test_runner.check("decode_arg", word.arg, 0x01_0006);
+    test_runner.check("encode_roundtrip", word.encode(), 0x0401_0006);
+
+    let list = CapturedList {
+        header: CaptureHeader {
+            list_size: 16,
+            list_addr: 0x0880_0000,
+            fbp: 0,
+            fbw: 512,
+            psm: 3,
+        },
+        words: vec![0x1e00_0001, 0x0401_0006, 0x0f00_0000, 0x0c00_0000],
+    };
+    let bytes = list.to_bytes();
+    test_runner.check("dump_len", bytes.len(), HEADER_SIZE + 16);
+    test_runner.check(
+        "dump_roundtrip",
+        CapturedList::from_bytes(&bytes),
+        Ok(list.clone()),
+    );
+    test_runner.check(
+        "dump_truncated",
+        CapturedList::from_bytes(&bytes[..HEADER_SIZE + 8]),
+        Err(CaptureError::Truncated),
+    );
+    test_runner.check(
+        "dump_bad_magic",
+        CapturedList::from_bytes(&[0; HEADER_SIZE]),
+        Err(CaptureError::BadMagic),
+    );
+
+    let text = list.disassemble();
+    test_runner.check_true("text_has_tme", text.contains("TME"));
+    test_runner.check_true("text_has_prim_count", text.contains("count=6"));
+
+    // FINISH/END become RET/NOP so the copy returns to the caller.
+    let replay = ReplayList::new(&list).unwrap();
+    test_runner.check_large_collection(
+        "replay_rewrites_finish",
+        replay.words(),
+        &[0x1e00_0001, 0x0401_0006, 0x0b00_0000, 0x0000_0000],
+    );
+
+    // VTYPE (16-bit index, float positions), IADDR, VADDR, PRIM, FINISH, END.
+    let good = vec![
+        0x1200_1180,
+        0x0200_0000,
+        0x0100_0000,
+        0x0403_0006,
+        0x0f00_0000,
+        0x0c00_0000,
+    ];
+    test_runner.check("validate_good", validate_list(&good), vec![]);
+    test_runner.check("dump_list_len", dump_list(&good).len(), 6);
+    test_runner.check(
+        "dump_list_prim",
+        dump_list(&good)[3],
+        GeWord {
+            cmd: 0x04,
+            arg: 0x03_0006,
+        },
+    );
+
+    // TME on, then a zero-count draw with nothing bound, an unknown
+    // command, and no FINISH/END.
+    let bad = vec![0x1e00_0001, 0x0403_0000, 0xff00_0000];
+    test_runner.check(
+        "validate_bad",
+        validate_list(&bad),
+        vec![
+            ListIssue::DrawWithoutVertexType { index: 1 },
+            ListIssue::DrawWithoutVertices { index: 1 },
+            ListIssue::EmptyDraw { index: 1 },
+            ListIssue::TextureWithoutImage { index: 1 },
+            ListIssue::UnknownCommand {
+                index: 2,
+                cmd: 0xff,
+            },
+            ListIssue::Unterminated,
+        ],
+    );
+
+    let unindexed = vec![0x1200_1180, 0x0100_0000, 0x0403_0006, 0x0c00_0000];
+    test_runner.check(
+        "validate_missing_indices_and_finish",
+        validate_list(&unindexed),
+        vec![
+            ListIssue::DrawWithoutIndices { index: 2 },
+            ListIssue::MissingFinish { index: 3 },
+        ],
+    );
+
+    // BASE + JUMP over two words of inline data, as sceGuGetMemory emits.
+    let mut inline = vec![0u32; 6];
+    let addr = inline.as_ptr() as u32 & 0x0fff_ffff;
+    let target = addr + 16;
+    inline[0] = 0x1000_0000 | ((target >> 8) & 0x000f_0000);
+    inline[1] = 0x0800_0000 | (target & 0x00ff_ffff);
+    inline[2] = 0xffff_ffff;
+    inline[3] = 0xffff_ffff;
+    inline[4] = 0x0f00_0000;
+    inline[5] = 0x0c00_0000;
+    test_runner.check("dump_list_skips_inline", dump_list(&inline).len(), 4);
+    test_runner.check("validate_skips_inline", validate_list(&inline), vec![]);
+}
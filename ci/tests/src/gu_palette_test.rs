@@ -0,0 +1,121 @@
+use psp::gu_ext::{Clut, Texture, TextureError};
+use psp::image::{DecodedImage, PixelFormat};
+use psp::sys::TexturePixelFormat;
+use psp::test_runner::TestRunner;
+
+pub fn test_main(test_runner: &mut TestRunner) {
+    let small = Clut::new(&[0xff00_00ff, 0xff00_ff00, 0xffff_0000]).unwrap();
+    test_runner.check("clut_small_size", small.size(), 16);
+    test_runner.check("clut_small_entry", small.entry(2), Some(0xffff_0000));
+    test_runner.check("clut_small_padding", small.entry(3), Some(0));
+
+    let colors: [u32; 17] = core::array::from_fn(|i| i as u32);
+    let large = Clut::new(&colors).unwrap();
+    test_runner.check("clut_large_size", large.size(), 256);
+    test_runner.check("clut_large_entry", large.entry(16), Some(16));
+    test_runner.check("clut_large_out_of_range", large.entry(256), None);
+
+    let full: [u32; 256] = core::array::from_fn(|i| i as u32);
+    test_runner.check(
+        "clut_full_size",
+        Clut::new(&full).map(|c| c.size()),
+        Some(256),
+    );
+    let too_many: [u32; 257] = core::array::from_fn(|i| i as u32);
+    test_runner.check_true("clut_too_many", Clut::new(&too_many).is_none());
+
+    // A new palette is dirty in full; set_entry dirties up to the entry.
+    let mut clut = Clut::new_4bit(&core::array::from_fn(|i| i as u32));
+    test_runner.check("clut_new_dirty", clut.take_dirty(), 16);
+    test_runner.check("clut_clean", clut.take_dirty(), 0);
+    clut.set_entry(5, 0xff);
+    test_runner.check("clut_set_entry_dirty", clut.take_dirty(), 6);
+    test_runner.check("clut_set_entry_value", clut.entry(5), Some(0xff));
+    clut.set_entry(16, 0xff);
+    test_runner.check("clut_set_entry_out_of_range", clut.take_dirty(), 0);
+
+    // Cycling rotates towards the end and wraps the last entries around.
+    clut.cycle(1..5, 1);
+    test_runner.check_large_collection("clut_cycle", &clut.entries()[..6], &[0, 4, 1, 2, 3, 0xff]);
+    test_runner.check("clut_cycle_dirty", clut.take_dirty(), 5);
+    clut.cycle(1..5, 7);
+    test_runner.check_large_collection(
+        "clut_cycle_wrap_offset",
+        &clut.entries()[..6],
+        &[0, 1, 2, 3, 4, 0xff],
+    );
+    clut.take_dirty();
+
+    // Ranges past the end are clamped; empty ones change nothing.
+    clut.cycle(14..100, 1);
+    test_runner.check_large_collection("clut_cycle_clamped", &clut.entries()[14..], &[15, 14]);
+    test_runner.check("clut_cycle_clamped_dirty", clut.take_dirty(), 16);
+    clut.cycle(3..3, 1);
+    clut.cycle(20..30, 1);
+    test_runner.check("clut_cycle_empty_dirty", clut.take_dirty(), 0);
+    test_runner.check_large_collection(
+        "clut_cycle_empty_unchanged",
+        &clut.entries()[..6],
+        &[0, 1, 2, 3, 4, 0xff],
+    );
+
+    // 3x2 T8: rows padded to 16 pixels.
+    let t8 = Texture::from_indexed(&[1, 2, 3, 4, 5, 6], 3, 2, TexturePixelFormat::PsmT8).unwrap();
+    test_runner.check("t8_buf_width", t8.buf_width(), 16);
+    let data = unsafe { core::slice::from_raw_parts(t8.as_ptr() as *const u8, 32) };
+    test_runner.check_large_collection("t8_row0", &data[..4], &[1, 2, 3, 0]);
+    test_runner.check_large_collection("t8_row1", &data[16..20], &[4, 5, 6, 0]);
+
+    // 3x2 T4: two bytes per source row, rows padded to 32 pixels.
+    let t4 =
+        Texture::from_indexed(&[0x21, 0x03, 0x54, 0x06], 3, 2, TexturePixelFormat::PsmT4).unwrap();
+    test_runner.check("t4_buf_width", t4.buf_width(), 32);
+    let data = unsafe { core::slice::from_raw_parts(t4.as_ptr() as *const u8, 32) };
+    test_runner.check_large_collection("t4_row1", &data[16..18], &[0x54, 0x06]);
+
+    test_runner.check(
+        "indexed_short_data",
+        Texture::from_indexed(&[0; 5], 3, 2, TexturePixelFormat::PsmT8).err(),
+        Some(TextureError::InvalidData),
+    );
+    test_runner.check(
+        "indexed_wrong_format",
+        Texture::from_indexed(&[0; 24], 3, 2, TexturePixelFormat::Psm8888).err(),
+        Some(TextureError::InvalidData),
+    );
+    // Decoded images must hold exactly width * height pixels.
+    let rgb = DecodedImage {
+        width: 3,
+        height: 2,
+        format: PixelFormat::Rgb888,
+        data: [0x80; 18].to_vec(),
+    };
+    test_runner.check(
+        "image_exact_size",
+        Texture::from_image(&rgb).map(|t| t.buf_width()).ok(),
+        Some(4),
+    );
+    let short = DecodedImage {
+        data: [0x80; 17].to_vec(),
+        ..rgb
+    };
+    test_runner.check(
+        "image_short_data",
+        Texture::from_image(&short).err(),
+        Some(TextureError::InvalidData),
+    );
+    let mislabeled = DecodedImage {
+        format: PixelFormat::Rgba8888,
+        ..short
+    };
+    test_runner.check(
+        "image_wrong_format",
+        Texture::from_image(&mislabeled).err(),
+        Some(TextureError::InvalidData),
+    );
+    test_runner.check(
+        "indexed_zero_size",
+        Texture::from_indexed(&[], 0, 2, TexturePixelFormat::PsmT8).err(),
+        Some(TextureError::InvalidSize),
+    );
+}
@@ -0,0 +1,95 @@
+use psp::gu_ext::{screen_ray, unproject};
+use psp::simd::{
+    mat4_inverse, mat4_multiply, mat4_transform, vec4_distance, vec4_dot, vec4_scale, vec4_sub,
+    Mat4, Vec4,
+};
+use psp::test_runner::TestRunner;
+
+const NEAR: f32 = 1.0;
+const FAR: f32 = 100.0;
+
+/// `sceGumPerspective(90.0, 480.0 / 272.0, NEAR, FAR)`.
+fn perspective() -> Mat4 {
+    let mut m = Mat4::ZERO;
+    m.0[0][0] = 272.0 / 480.0;
+    m.0[1][1] = 1.0;
+    m.0[2][2] = (FAR + NEAR) / (NEAR - FAR);
+    m.0[2][3] = -1.0;
+    m.0[3][2] = 2.0 * FAR * NEAR / (NEAR - FAR);
+    m
+}
+
+/// A camera at `z` on the Z axis looking towards -Z.
+fn camera_at(z: f32) -> Mat4 {
+    let mut m = Mat4::IDENTITY;
+    m.0[3][2] = -z;
+    m
+}
+
+fn close(a: &Vec4, b: &Vec4, tolerance: f32) -> bool {
+    vec4_distance(a, b) < tolerance
+}
+
+pub fn test_main(test_runner: &mut TestRunner) {
+    let proj = perspective();
+    let view = camera_at(5.0);
+
+    let m = mat4_multiply(&proj, &view);
+    let identity = mat4_inverse(&m).map(|inv| mat4_multiply(&m, &inv));
+    test_runner.check_true(
+        "inverse",
+        identity.is_some_and(|id| {
+            (0..4).all(|c| close(&Vec4(id.0[c]), &Vec4(Mat4::IDENTITY.0[c]), 1e-5))
+        }),
+    );
+    test_runner.check("inverse_singular", mat4_inverse(&Mat4::ZERO), None);
+
+    // The screen center looks straight down the view axis.
+    let (origin, dir) = screen_ray(240.0, 136.0, &view, &proj);
+    test_runner.check_true(
+        "center_origin",
+        close(&origin, &Vec4::new(0.0, 0.0, 5.0 - NEAR, 1.0), 1e-4),
+    );
+    test_runner.check_true(
+        "center_dir",
+        close(&dir, &Vec4::new(0.0, 0.0, -1.0, 0.0), 1e-4),
+    );
+
+    // The top right corner of the near plane, with a 90 degree field of
+    // view vertically.
+    let corner = unproject(480.0, 0.0, 0.0, &view, &proj);
+    test_runner.check_true(
+        "corner",
+        close(&corner, &Vec4::new(480.0 / 272.0, 1.0, 4.0, 1.0), 1e-4),
+    );
+
+    // Project a point to the screen and back.
+    let point = Vec4::new(2.0, -1.0, -10.0, 1.0);
+    let clip = mat4_transform(&m, &point);
+    let ndc = vec4_scale(&clip, 1.0 / clip.w());
+    let screen_x = (ndc.x() + 1.0) / 2.0 * 480.0;
+    let screen_y = (1.0 - ndc.y()) / 2.0 * 272.0;
+    let depth = (ndc.z() + 1.0) / 2.0;
+    test_runner.check_true(
+        "roundtrip",
+        close(
+            &unproject(screen_x, screen_y, depth, &view, &proj),
+            &point,
+            1e-2,
+        ),
+    );
+
+    // The ray through that pixel passes through the point.
+    let (origin, dir) = screen_ray(screen_x, screen_y, &view, &proj);
+    let t = vec4_dot(&vec4_sub(&point, &origin), &dir);
+    let nearest = Vec4::new(
+        origin.x() + dir.x() * t,
+        origin.y() + dir.y() * t,
+        origin.z() + dir.z() * t,
+        1.0,
+    );
+    test_runner.check_true("ray_hits_point", close(&nearest, &point, 1e-2));
+
+    let (origin, dir) = screen_ray(0.0, 0.0, &view, &Mat4::ZERO);
+    test_runner.check_true("singular_ray", origin.x().is_nan() && dir.x().is_nan());
+}
@@ -0,0 +1,62 @@
+use psp::gu_ext::TransformStack;
+use psp::simd::{mat4_transform, Mat4, Vec4};
+use psp::test_runner::TestRunner;
+
+fn translation(x: f32, y: f32, z: f32) -> Mat4 {
+    let mut m = Mat4::IDENTITY;
+    m.0[3] = [x, y, z, 1.0];
+    m
+}
+
+fn scale(s: f32) -> Mat4 {
+    let mut m = Mat4::IDENTITY;
+    m.0[0][0] = s;
+    m.0[1][1] = s;
+    m.0[2][2] = s;
+    m
+}
+
+fn origin(stack: &TransformStack<2>) -> Vec4 {
+    mat4_transform(stack.current(), &Vec4::new(0.0, 0.0, 0.0, 1.0))
+}
+
+pub fn test_main(test_runner: &mut TestRunner) {
+    let mut stack = TransformStack::<2>::default();
+    test_runner.check("root_identity", *stack.current(), Mat4::IDENTITY);
+    test_runner.check("root_pop", stack.pop(), None);
+    test_runner.check("root_depth", stack.depth(), 0);
+
+    // The child is scaled by its parent, then moved with it.
+    test_runner.check_true("push", stack.push(&translation(10.0, 0.0, 0.0)));
+    test_runner.check_true("push_to_capacity", stack.push(&scale(2.0)));
+    let tip = mat4_transform(stack.current(), &Vec4::new(1.0, 0.0, 0.0, 1.0));
+    test_runner.check("composed", tip, Vec4::new(12.0, 0.0, 0.0, 1.0));
+    test_runner.check("depth", stack.depth(), 2);
+
+    // A full stack refuses further pushes and keeps its top matrix.
+    test_runner.check_true("push_full", !stack.push(&translation(0.0, 1.0, 0.0)));
+    test_runner.check("push_full_depth", stack.depth(), 2);
+    test_runner.check(
+        "push_full_unchanged",
+        mat4_transform(stack.current(), &Vec4::new(1.0, 0.0, 0.0, 1.0)),
+        tip,
+    );
+    let mut ran = false;
+    test_runner.check("with_full", stack.with(&scale(3.0), |_| ran = true), None);
+    test_runner.check_true("with_full_skips_closure", !ran);
+
+    test_runner.check("pop_returns_top", stack.pop().map(|m| m.0[0][0]), Some(2.0));
+    test_runner.check(
+        "pop_to_parent",
+        origin(&stack),
+        Vec4::new(10.0, 0.0, 0.0, 1.0),
+    );
+
+    // `with` pops what it pushed.
+    let inner = stack.with(&translation(0.0, 5.0, 0.0), |stack| origin(stack));
+    test_runner.check("with_inner", inner, Some(Vec4::new(10.0, 5.0, 0.0, 1.0)));
+    test_runner.check("with_restores", stack.depth(), 1);
+
+    stack.reset();
+    test_runner.check("reset", *stack.current(), Mat4::IDENTITY);
+}
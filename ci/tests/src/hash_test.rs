@@ -0,0 +1,75 @@
+use psp::hash::{crc32, fnv1a_64, sha1, siphash24, strip_crc32, Crc32};
+use psp::test_runner::TestRunner;
+
+pub fn test_main(test_runner: &mut TestRunner) {
+    test_runner.check("crc32_empty", crc32(b""), 0);
+    test_runner.check("crc32_check_value", crc32(b"123456789"), 0xCBF4_3926);
+    test_runner.check(
+        "crc32_fox",
+        crc32(b"The quick brown fox jumps over the lazy dog"),
+        0x414F_A339,
+    );
+
+    let mut crc = Crc32::new();
+    crc.update(b"1234");
+    crc.update(b"");
+    crc.update(b"56789");
+    test_runner.check("crc32_incremental", crc.finish(), 0xCBF4_3926);
+
+    let framed = b"123456789\x26\x39\xF4\xCB";
+    test_runner.check("crc32_strip", strip_crc32(framed), Some(&b"123456789"[..]));
+    test_runner.check(
+        "crc32_strip_corrupt",
+        strip_crc32(b"123456780\x26\x39\xF4\xCB"),
+        None,
+    );
+    test_runner.check("crc32_strip_short", strip_crc32(b"abc"), None);
+
+    test_runner.check("fnv1a_64_empty", fnv1a_64(b""), 0xcbf2_9ce4_8422_2325);
+    test_runner.check("fnv1a_64_a", fnv1a_64(b"a"), 0xaf63_dc4c_8601_ec8c);
+    test_runner.check(
+        "fnv1a_64_foobar",
+        fnv1a_64(b"foobar"),
+        0x8594_4171_f739_67e8,
+    );
+
+    // Reference vectors: key 00..0f, message 00..(n-1).
+    let key: [u8; 16] = core::array::from_fn(|i| i as u8);
+    let msg: [u8; 15] = core::array::from_fn(|i| i as u8);
+    test_runner.check(
+        "siphash24_empty",
+        siphash24(&key, b""),
+        0x726f_db47_dd0e_0e31,
+    );
+    test_runner.check("siphash24_15", siphash24(&key, &msg), 0xa129_ca61_49be_45e5);
+    test_runner.check_true(
+        "siphash24_keyed",
+        siphash24(&[0; 16], &msg) != siphash24(&key, &msg),
+    );
+
+    test_runner.check_large_collection(
+        "sha1_empty",
+        &sha1(b""),
+        &[
+            0xda, 0x39, 0xa3, 0xee, 0x5e, 0x6b, 0x4b, 0x0d, 0x32, 0x55, 0xbf, 0xef, 0x95, 0x60,
+            0x18, 0x90, 0xaf, 0xd8, 0x07, 0x09,
+        ],
+    );
+    test_runner.check_large_collection(
+        "sha1_abc",
+        &sha1(b"abc"),
+        &[
+            0xa9, 0x99, 0x3e, 0x36, 0x47, 0x06, 0x81, 0x6a, 0xba, 0x3e, 0x25, 0x71, 0x78, 0x50,
+            0xc2, 0x6c, 0x9c, 0xd0, 0xd8, 0x9d,
+        ],
+    );
+    // 56 bytes: the length no longer fits the first padding block.
+    test_runner.check_large_collection(
+        "sha1_two_block_padding",
+        &sha1(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"),
+        &[
+            0x84, 0x98, 0x3e, 0x44, 0x1c, 0x3b, 0xd2, 0x6e, 0xba, 0xae, 0x4a, 0xa1, 0xf9, 0x51,
+            0x29, 0xe5, 0xe5, 0x46, 0x70, 0xf1,
+        ],
+    );
+}
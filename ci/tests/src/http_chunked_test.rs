@@ -0,0 +1,130 @@
+use alloc::vec::Vec;
+use psp::http::{
+    content_range, decode_chunked, is_chunked, ChunkedDecoder, ContentRange, HttpError,
+    HTTP_ERROR_BAD_CHUNK,
+};
+use psp::test_runner::TestRunner;
+
+pub fn test_main(test_runner: &mut TestRunner) {
+    test_runner.check_true(
+        "http_is_chunked",
+        is_chunked(b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n"),
+    );
+    test_runner.check_true(
+        "http_is_chunked_case",
+        is_chunked(b"transfer-encoding:  CHUNKED\r\n"),
+    );
+    test_runner.check_true(
+        "http_is_chunked_last_coding",
+        is_chunked(b"Transfer-Encoding: gzip, chunked\r\n"),
+    );
+    test_runner.check_true(
+        "http_not_chunked",
+        !is_chunked(b"Content-Length: 5\r\nX-Note: chunked\r\n"),
+    );
+
+    let body = b"4\r\nWiki\r\n5\r\npedia\r\nE\r\n in\r\n\r\nchunks.\r\n0\r\n\r\n";
+    test_runner.check(
+        "http_decode_chunked",
+        decode_chunked(body).as_deref(),
+        Ok(&b"Wikipedia in\r\n\r\nchunks."[..]),
+    );
+
+    let body = b"3;name=value\r\nabc\r\n0\r\nExpires: never\r\n\r\n";
+    test_runner.check(
+        "http_decode_chunked_ext_trailer",
+        decode_chunked(body).as_deref(),
+        Ok(&b"abc"[..]),
+    );
+
+    test_runner.check(
+        "http_decode_chunked_empty",
+        decode_chunked(b"0\r\n\r\n").as_deref(),
+        Ok(&b""[..]),
+    );
+
+    let bad = Some(HttpError(HTTP_ERROR_BAD_CHUNK));
+    test_runner.check(
+        "http_decode_chunked_truncated",
+        decode_chunked(b"a\r\nshort").err(),
+        bad,
+    );
+    test_runner.check(
+        "http_decode_chunked_no_terminator",
+        decode_chunked(b"3\r\nabc\r\n").err(),
+        bad,
+    );
+    test_runner.check(
+        "http_decode_chunked_bad_size",
+        decode_chunked(b"zz\r\nabc\r\n0\r\n\r\n").err(),
+        bad,
+    );
+
+    // Feeding the body one byte at a time must decode the same as all at once.
+    let body = b"4\r\nWiki\r\n5;x=y\r\npedia\r\n0\r\n\r\n";
+    let mut decoder = ChunkedDecoder::new();
+    let mut out = Vec::new();
+    let mut ok = true;
+    for byte in body.chunks(1) {
+        ok &= decoder
+            .feed(byte, |data| {
+                out.extend_from_slice(data);
+                Ok(())
+            })
+            .is_ok();
+    }
+    test_runner.check_true("http_chunked_decoder_bytewise_ok", ok);
+    test_runner.check("http_chunked_decoder_bytewise", &out[..], &b"Wikipedia"[..]);
+    test_runner.check_true("http_chunked_decoder_done", decoder.finish().is_ok());
+
+    let mut decoder = ChunkedDecoder::new();
+    let _ = decoder.feed(b"5\r\nab", |_| Ok(()));
+    test_runner.check(
+        "http_chunked_decoder_cut_short",
+        decoder.finish().err(),
+        bad,
+    );
+
+    // Sink errors pass straight through.
+    let mut decoder = ChunkedDecoder::new();
+    test_runner.check(
+        "http_chunked_decoder_sink_error",
+        decoder.feed(b"3\r\nabc\r\n", |_| Err(HttpError(-99))).err(),
+        Some(HttpError(-99)),
+    );
+
+    test_runner.check(
+        "http_content_range",
+        content_range(b"HTTP/1.1 206 Partial Content\r\nContent-Range: bytes 100-199/1000\r\n"),
+        Some(ContentRange {
+            range: Some((100, 199)),
+            total: Some(1000),
+        }),
+    );
+    test_runner.check(
+        "http_content_range_unknown_total",
+        content_range(b"content-range: bytes 0-9/*\r\n"),
+        Some(ContentRange {
+            range: Some((0, 9)),
+            total: None,
+        }),
+    );
+    test_runner.check(
+        "http_content_range_unsatisfied",
+        content_range(b"Content-Range: bytes */1000\r\n"),
+        Some(ContentRange {
+            range: None,
+            total: Some(1000),
+        }),
+    );
+    test_runner.check(
+        "http_content_range_missing",
+        content_range(b"Content-Length: 5\r\n"),
+        None,
+    );
+    test_runner.check(
+        "http_content_range_malformed",
+        content_range(b"Content-Range: bytes 9-1/10\r\n"),
+        None,
+    );
+}
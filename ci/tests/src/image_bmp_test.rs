@@ -0,0 +1,135 @@
+use psp::image::bmp::{self, BmpError};
+use psp::image::{DecodedImage, PixelFormat};
+use psp::rand::Rng;
+use psp::test_runner::TestRunner;
+
+pub fn test_main(test_runner: &mut TestRunner) {
+    // 3x2 so 24-bit rows need padding.
+    let image = DecodedImage {
+        width: 3,
+        height: 2,
+        format: PixelFormat::Rgba8888,
+        data: [
+            [255, 0, 0, 255],
+            [0, 255, 0, 255],
+            [0, 0, 255, 255],
+            [10, 20, 30, 40],
+            [50, 60, 70, 80],
+            [90, 100, 110, 120],
+        ]
+        .concat(),
+    };
+
+    let encoded = bmp::encode(&image);
+    test_runner.check(
+        "bmp_encoded_len",
+        encoded.len(),
+        bmp::HEADER_SIZE + 3 * 2 * 4,
+    );
+    let decoded = bmp::decode(&encoded).unwrap();
+    test_runner.check("bmp_roundtrip_width", decoded.width, 3);
+    test_runner.check("bmp_roundtrip_height", decoded.height, 2);
+    test_runner.check_large_collection("bmp_roundtrip_pixels", &decoded.data, &image.data);
+
+    // Same pixels as a top-down 24-bit file.
+    let mut bmp24 = encoded[..bmp::HEADER_SIZE].to_vec();
+    bmp24[22..26].copy_from_slice(&(-2i32).to_le_bytes());
+    bmp24[28] = 24;
+    for row in image.data.chunks(12) {
+        for px in row.chunks(4) {
+            bmp24.extend_from_slice(&[px[2], px[1], px[0]]);
+        }
+        bmp24.extend_from_slice(&[0, 0, 0]);
+    }
+    let decoded = bmp::decode(&bmp24).unwrap();
+    test_runner.check_true(
+        "bmp_24bit_top_down",
+        decoded
+            .data
+            .chunks(4)
+            .zip(image.data.chunks(4))
+            .all(|(d, s)| d[..3] == s[..3] && d[3] == 255),
+    );
+
+    // 2x1 palettized: indices 1, 0 into a two-color table.
+    let mut bmp8 = encoded[..bmp::HEADER_SIZE].to_vec();
+    bmp8[10..14].copy_from_slice(&((bmp::HEADER_SIZE + 8) as u32).to_le_bytes());
+    bmp8[18..22].copy_from_slice(&2u32.to_le_bytes());
+    bmp8[22..26].copy_from_slice(&1u32.to_le_bytes());
+    bmp8[28] = 8;
+    bmp8[46..50].copy_from_slice(&2u32.to_le_bytes());
+    bmp8.extend_from_slice(&[0, 0, 255, 0, 255, 0, 0, 0]); // red, blue (BGR0)
+    bmp8.extend_from_slice(&[1, 0, 0, 0]);
+    let decoded = bmp::decode(&bmp8).unwrap();
+    test_runner.check_large_collection(
+        "bmp_8bit_palette",
+        &decoded.data,
+        &[0, 0, 255, 255, 255, 0, 0, 255],
+    );
+
+    test_runner.check(
+        "bmp_truncated",
+        bmp::decode(&encoded[..encoded.len() - 1]).err(),
+        Some(BmpError::Truncated),
+    );
+
+    // A header claiming a huge image is rejected before anything is
+    // allocated for it.
+    let mut huge = encoded.clone();
+    huge[18..22].copy_from_slice(&0x4000_0000u32.to_le_bytes());
+    huge[22..26].copy_from_slice(&0x4000_0000u32.to_le_bytes());
+    test_runner.check(
+        "bmp_huge_dimensions",
+        bmp::decode(&huge).err(),
+        Some(BmpError::InvalidDimensions),
+    );
+    huge[18..22].copy_from_slice(&4096u32.to_le_bytes());
+    huge[22..26].copy_from_slice(&4096u32.to_le_bytes());
+    test_runner.check(
+        "bmp_larger_than_data",
+        bmp::decode(&huge).err(),
+        Some(BmpError::Truncated),
+    );
+
+    // A DIB header size that puts the color table past the end of the
+    // address space is refused rather than wrapping around.
+    for (dib_size, expected) in [
+        (u32::MAX, BmpError::UnsupportedHeader),
+        (u32::MAX - 14, BmpError::BadPalette),
+        (u32::MAX - 20, BmpError::BadPalette),
+    ] {
+        let mut bad = bmp8.clone();
+        bad[14..18].copy_from_slice(&dib_size.to_le_bytes());
+        test_runner.check(
+            "bmp_dib_size_overflow",
+            bmp::decode(&bad).err(),
+            Some(expected),
+        );
+    }
+
+    // Random header fields never panic, and anything accepted decodes to
+    // as many pixels as it claims.
+    let mut rng = Rng::new(0x0b4d_b3a7);
+    let mut consistent = true;
+    for i in 0..2000 {
+        let mut fuzzed = if i % 2 == 0 {
+            bmp8.clone()
+        } else {
+            bmp24.clone()
+        };
+        for _ in 0..1 + rng.below(4) {
+            let at = rng.below(bmp::HEADER_SIZE as u32) as usize;
+            let value = rng.next_u32();
+            // Whole fields as often as single bytes, to reach the extremes.
+            if at + 4 <= bmp::HEADER_SIZE && rng.below(2) == 0 {
+                fuzzed[at..at + 4].copy_from_slice(&value.to_le_bytes());
+            } else {
+                fuzzed[at] = value as u8;
+            }
+        }
+        if let Ok(image) = bmp::decode(&fuzzed) {
+            consistent &= image.data.len() == (image.width * image.height * 4) as usize;
+        }
+    }
+    test_runner.check_true("bmp_fuzzed_headers", consistent);
+}
@@ -0,0 +1,58 @@
+use psp::image::{CollisionMask, DecodedImage, PixelFormat};
+use psp::test_runner::TestRunner;
+
+extern crate alloc;
+use alloc::vec;
+
+pub fn test_main(test_runner: &mut TestRunner) {
+    // 40 wide so rows span two words; a solid 4x4 square at (34, 1).
+    let (width, height) = (40u32, 8u32);
+    let mut data = vec![0u8; (width * height * 4) as usize];
+    for y in 1..5 {
+        for x in 34..38 {
+            data[((y * width + x) * 4 + 3) as usize] = 255;
+        }
+    }
+    // Faint pixel, below the threshold.
+    data[3] = 64;
+    let image = DecodedImage {
+        width,
+        height,
+        format: PixelFormat::Rgba8888,
+        data,
+    };
+    let mask = CollisionMask::from_image(&image, 127);
+
+    test_runner.check_true("point_solid", mask.test_point(34, 1));
+    test_runner.check_true("point_solid_far_corner", mask.test_point(37, 4));
+    test_runner.check_true("point_empty", !mask.test_point(33, 1));
+    test_runner.check_true("point_below_threshold", !mask.test_point(0, 0));
+    test_runner.check_true("point_out_of_bounds", !mask.test_point(-1, 1));
+    test_runner.check_true(
+        "threshold_zero",
+        CollisionMask::from_image(&image, 0).test_point(0, 0),
+    );
+
+    let mut dot = CollisionMask::empty(1, 1);
+    dot.set(0, 0, true);
+    test_runner.check_true("overlap_hit", mask.overlaps(&dot, 35, 2));
+    test_runner.check_true("overlap_miss_beside", !mask.overlaps(&dot, 38, 2));
+    test_runner.check_true("overlap_outside", !mask.overlaps(&dot, 40, 2));
+    test_runner.check_true("overlap_reverse", dot.overlaps(&mask, -37, -4));
+    test_runner.check_true("overlap_reverse_miss", !dot.overlaps(&mask, -33, -4));
+
+    // Two squares touching only across a word boundary.
+    test_runner.check_true("overlap_self_shifted", mask.overlaps(&mask, 3, 3));
+    test_runner.check_true("overlap_self_apart", !mask.overlaps(&mask, 4, 0));
+
+    let opaque = DecodedImage {
+        width: 2,
+        height: 1,
+        format: PixelFormat::Rgb888,
+        data: vec![0; 6],
+    };
+    test_runner.check_true(
+        "rgb_opaque",
+        CollisionMask::from_image(&opaque, 255).test_point(1, 0),
+    );
+}
@@ -0,0 +1,180 @@
+use psp::config::Config;
+use psp::input::{ActionMap, Axis, Binding, Conflict, InputSnapshot};
+use psp::sys::CtrlButtons;
+use psp::test_runner::TestRunner;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Action {
+    Jump,
+    Menu,
+    Special,
+    Move,
+}
+
+fn name(action: Action) -> &'static str {
+    match action {
+        Action::Jump => "jump",
+        Action::Menu => "menu",
+        Action::Special => "special",
+        Action::Move => "move",
+    }
+}
+
+fn snapshot(held: CtrlButtons, previous: CtrlButtons, x: f32) -> InputSnapshot {
+    InputSnapshot {
+        held,
+        previous,
+        x,
+        y: 0.0,
+    }
+}
+
+fn defaults() -> ActionMap<Action> {
+    let mut actions = ActionMap::new();
+    actions.set_deadzone(0.25);
+    actions.bind(Action::Jump, CtrlButtons::CROSS);
+    actions.bind(Action::Jump, CtrlButtons::UP);
+    actions.bind(Action::Menu, CtrlButtons::START);
+    actions.bind(
+        Action::Special,
+        CtrlButtons::LTRIGGER | CtrlButtons::RTRIGGER,
+    );
+    actions.bind_analog(Action::Move, Axis::X, 1.0);
+    actions.bind(Action::Move, CtrlButtons::RIGHT);
+    actions
+}
+
+pub fn test_main(test_runner: &mut TestRunner) {
+    let none = CtrlButtons::empty();
+    let actions = defaults();
+
+    // Either binding triggers the action.
+    let cross = snapshot(CtrlButtons::CROSS, none, 0.0);
+    let up = snapshot(CtrlButtons::UP, none, 0.0);
+    test_runner.check_true(
+        "action_pressed_first_binding",
+        actions.is_action_pressed(Action::Jump, &cross),
+    );
+    test_runner.check_true(
+        "action_pressed_second_binding",
+        actions.is_action_pressed(Action::Jump, &up),
+    );
+    test_runner.check_true(
+        "action_not_pressed",
+        !actions.is_action_pressed(Action::Menu, &cross),
+    );
+
+    // Edge detection: only the first frame of a hold.
+    let held = snapshot(CtrlButtons::CROSS, CtrlButtons::CROSS, 0.0);
+    test_runner.check_true(
+        "action_was_pressed",
+        actions.was_action_pressed(Action::Jump, &cross),
+    );
+    test_runner.check_true(
+        "action_held_not_was_pressed",
+        !actions.was_action_pressed(Action::Jump, &held),
+    );
+
+    // A chord needs every button, and fires when the last one goes down.
+    let l = CtrlButtons::LTRIGGER;
+    let lr = CtrlButtons::LTRIGGER | CtrlButtons::RTRIGGER;
+    test_runner.check_true(
+        "chord_partial",
+        !actions.is_action_pressed(Action::Special, &snapshot(l, none, 0.0)),
+    );
+    test_runner.check_true(
+        "chord_completed",
+        actions.was_action_pressed(Action::Special, &snapshot(lr, l, 0.0)),
+    );
+    test_runner.check_true(
+        "chord_held",
+        !actions.was_action_pressed(Action::Special, &snapshot(lr, lr, 0.0)),
+    );
+
+    // Analog value after the 0.25 deadzone; a held button is 1.0.
+    test_runner.check(
+        "action_value_deadzone",
+        actions.action_value(Action::Move, &snapshot(none, none, 0.1)),
+        0.0,
+    );
+    test_runner.check(
+        "action_value_analog",
+        actions.action_value(Action::Move, &snapshot(none, none, 0.625)),
+        0.5,
+    );
+    test_runner.check(
+        "action_value_left",
+        actions.action_value(Action::Move, &snapshot(none, none, -1.0)),
+        -1.0,
+    );
+    test_runner.check(
+        "action_value_button",
+        actions.action_value(Action::Move, &snapshot(CtrlButtons::RIGHT, none, 0.625)),
+        1.0,
+    );
+    test_runner.check(
+        "action_value_inactive",
+        actions.action_value(Action::Jump, &snapshot(none, none, 1.0)),
+        0.0,
+    );
+
+    // Conflicts between different actions sharing a chord.
+    test_runner.check("conflicts_none", actions.conflicts().len(), 0);
+    let mut clashing = defaults();
+    clashing.bind(Action::Menu, CtrlButtons::CROSS);
+    clashing.bind(Action::Menu, CtrlButtons::CROSS);
+    test_runner.check(
+        "conflicts_found",
+        clashing.conflicts(),
+        alloc::vec![Conflict {
+            first: Action::Jump,
+            second: Action::Menu,
+            buttons: CtrlButtons::CROSS,
+        }],
+    );
+
+    // Remapping persists through a Config round trip.
+    let mut remapped = defaults();
+    remapped.rebind(
+        Action::Jump,
+        &[Binding::Buttons(CtrlButtons::CIRCLE | CtrlButtons::SQUARE)],
+    );
+    remapped.rebind(Action::Move, &[Binding::Analog(Axis::Y, -0.5)]);
+    let mut config = Config::new();
+    remapped.save_to_config(&mut config, "input.", name);
+    test_runner.check_true("config_has_entry", config.get("input.jump").is_some());
+
+    let mut loaded = defaults();
+    test_runner.check(
+        "config_loaded_count",
+        loaded.load_from_config(&config, "input.", name),
+        4,
+    );
+    test_runner.check(
+        "config_jump_bindings",
+        loaded
+            .bindings(Action::Jump)
+            .collect::<alloc::vec::Vec<_>>(),
+        alloc::vec![Binding::Buttons(CtrlButtons::CIRCLE | CtrlButtons::SQUARE)],
+    );
+    test_runner.check(
+        "config_move_bindings",
+        loaded
+            .bindings(Action::Move)
+            .collect::<alloc::vec::Vec<_>>(),
+        alloc::vec![Binding::Analog(Axis::Y, -0.5)],
+    );
+
+    // Missing entries keep the defaults.
+    let mut fresh = defaults();
+    test_runner.check(
+        "config_empty_keeps_defaults",
+        fresh.load_from_config(&Config::new(), "input.", name),
+        0,
+    );
+    test_runner.check(
+        "config_defaults_kept",
+        fresh.bindings(Action::Jump).count(),
+        2,
+    );
+}
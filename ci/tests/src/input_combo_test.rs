@@ -0,0 +1,66 @@
+use psp::input::ComboDetector;
+use psp::sys::CtrlButtons;
+use psp::test_runner::TestRunner;
+
+const DOWN: CtrlButtons = CtrlButtons::DOWN;
+const RIGHT: CtrlButtons = CtrlButtons::RIGHT;
+const PUNCH: CtrlButtons = CtrlButtons::SQUARE;
+
+pub fn test_main(test_runner: &mut TestRunner) {
+    let mut combos = ComboDetector::new();
+    let fireball = combos.register(&[DOWN, DOWN | RIGHT, RIGHT | PUNCH], 200);
+    let jab = combos.register(&[PUNCH], 1000);
+
+    // down, down-forward, forward + punch, 100 ms apart.
+    test_runner.check("combo_step_1", combos.feed(DOWN, DOWN, 0), None);
+    test_runner.check(
+        "combo_idle_frame",
+        combos.feed(DOWN, CtrlButtons::empty(), 50_000),
+        None,
+    );
+    test_runner.check(
+        "combo_step_2",
+        combos.feed(DOWN | RIGHT, RIGHT, 100_000),
+        None,
+    );
+    // Both combos end on the punch; the longer one wins.
+    test_runner.check(
+        "combo_complete",
+        combos.feed(RIGHT | PUNCH, PUNCH, 200_000),
+        Some(fireball),
+    );
+    test_runner.check(
+        "combo_single_step",
+        combos.feed(PUNCH, PUNCH, 300_000),
+        Some(jab),
+    );
+
+    // Too slow between steps.
+    combos.feed(DOWN, DOWN, 1_000_000);
+    combos.feed(DOWN | RIGHT, RIGHT, 1_100_000);
+    test_runner.check(
+        "combo_timeout",
+        combos.feed(RIGHT | PUNCH, PUNCH, 1_400_000),
+        Some(jab),
+    );
+
+    // A stray press restarts the sequence.
+    combos.feed(DOWN, DOWN, 2_000_000);
+    combos.feed(DOWN | CtrlButtons::CROSS, CtrlButtons::CROSS, 2_050_000);
+    combos.feed(DOWN | RIGHT, RIGHT, 2_100_000);
+    test_runner.check(
+        "combo_stray_press",
+        combos.feed(RIGHT | PUNCH, PUNCH, 2_150_000),
+        Some(jab),
+    );
+
+    // Holding a step's buttons without a new press doesn't advance.
+    combos.reset();
+    combos.feed(DOWN, DOWN, 3_000_000);
+    combos.feed(DOWN | RIGHT, CtrlButtons::empty(), 3_050_000);
+    test_runner.check(
+        "combo_needs_edge",
+        combos.feed(RIGHT | PUNCH, PUNCH, 3_100_000),
+        Some(jab),
+    );
+}
@@ -0,0 +1,39 @@
+use psp::input::stick_to_dpad;
+use psp::sys::CtrlButtons;
+use psp::test_runner::TestRunner;
+
+pub fn test_main(test_runner: &mut TestRunner) {
+    test_runner.check(
+        "dpad_centered",
+        stick_to_dpad(0.0, 0.0, 0.5),
+        CtrlButtons::empty(),
+    );
+    test_runner.check(
+        "dpad_below_threshold",
+        stick_to_dpad(0.4, -0.5, 0.5),
+        CtrlButtons::empty(),
+    );
+    test_runner.check(
+        "dpad_right",
+        stick_to_dpad(0.8, 0.1, 0.5),
+        CtrlButtons::RIGHT,
+    );
+    test_runner.check(
+        "dpad_left",
+        stick_to_dpad(-0.8, 0.0, 0.5),
+        CtrlButtons::LEFT,
+    );
+    // Down is positive Y.
+    test_runner.check("dpad_up", stick_to_dpad(0.0, -1.0, 0.5), CtrlButtons::UP);
+    test_runner.check("dpad_down", stick_to_dpad(0.0, 1.0, 0.5), CtrlButtons::DOWN);
+    test_runner.check(
+        "dpad_diagonal",
+        stick_to_dpad(-0.7, 0.7, 0.5),
+        CtrlButtons::LEFT | CtrlButtons::DOWN,
+    );
+    test_runner.check(
+        "dpad_zero_threshold",
+        stick_to_dpad(0.01, 0.0, 0.0),
+        CtrlButtons::RIGHT,
+    );
+}
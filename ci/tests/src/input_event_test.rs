@@ -0,0 +1,82 @@
+use psp::input::{EventQueue, InputEvent, InputSnapshot};
+use psp::sys::CtrlButtons;
+use psp::test_runner::TestRunner;
+
+fn frame(previous: CtrlButtons, held: CtrlButtons, x: f32, y: f32) -> InputSnapshot {
+    InputSnapshot {
+        held,
+        previous,
+        x,
+        y,
+    }
+}
+
+pub fn test_main(test_runner: &mut TestRunner) {
+    let none = CtrlButtons::empty();
+    let cross = CtrlButtons::CROSS;
+    let up = CtrlButtons::UP;
+
+    let mut events = EventQueue::new();
+    events.feed(&frame(none, cross | up, 0.0, 0.0));
+    test_runner.check("event_down_count", events.len(), 2);
+    test_runner.check(
+        "event_down_first",
+        events.pop(),
+        Some(InputEvent::ButtonDown(up)),
+    );
+    test_runner.check(
+        "event_down_second",
+        events.pop(),
+        Some(InputEvent::ButtonDown(cross)),
+    );
+
+    // Held buttons and a centered stick produce nothing.
+    events.feed(&frame(cross | up, cross | up, 0.05, 0.0));
+    test_runner.check("event_idle", events.is_empty(), true);
+
+    // Releases come before presses in the same frame.
+    events.feed(&frame(cross | up, up | CtrlButtons::CIRCLE, 0.0, 0.0));
+    let drained: [Option<InputEvent>; 3] = {
+        let mut it = events.drain();
+        [it.next(), it.next(), it.next()]
+    };
+    test_runner.check(
+        "event_up_first",
+        drained[0],
+        Some(InputEvent::ButtonUp(cross)),
+    );
+    test_runner.check(
+        "event_then_down",
+        drained[1],
+        Some(InputEvent::ButtonDown(CtrlButtons::CIRCLE)),
+    );
+    test_runner.check("event_drained", drained[2], None);
+
+    // Stick: full right, a tiny wobble, then back to center.
+    events.set_deadzone(0.0);
+    events.feed(&frame(none, none, 1.0, 0.0));
+    test_runner.check(
+        "event_analog_moved",
+        events.pop(),
+        Some(InputEvent::AnalogMoved(1.0, 0.0)),
+    );
+    events.feed(&frame(none, none, 0.99, 0.0));
+    test_runner.check("event_analog_threshold", events.pop(), None);
+    events.feed(&frame(none, none, 0.0, 0.0));
+    test_runner.check(
+        "event_analog_center",
+        events.pop(),
+        Some(InputEvent::AnalogMoved(0.0, 0.0)),
+    );
+
+    // A full queue drops the oldest events.
+    let mut small = EventQueue::with_capacity(2);
+    small.feed(&frame(none, up | CtrlButtons::DOWN | cross, 0.0, 0.0));
+    test_runner.check("event_capacity_len", small.len(), 2);
+    test_runner.check("event_capacity_dropped", small.dropped(), 1);
+    test_runner.check(
+        "event_capacity_oldest",
+        small.pop(),
+        Some(InputEvent::ButtonDown(CtrlButtons::DOWN)),
+    );
+}
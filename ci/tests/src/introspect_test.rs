@@ -0,0 +1,61 @@
+use psp::introspect;
+use psp::sync::{EventFlag, Semaphore};
+use psp::sys::{EventFlagAttributes, SceSysMemPartitionId};
+use psp::test_runner::TestRunner;
+
+pub fn test_main(test_runner: &mut TestRunner) {
+    let me = psp::thread::current_thread_id();
+    let own = introspect::threads().unwrap().find(|t| t.uid == me);
+    test_runner.check_true("threads_has_current", own.is_some());
+    test_runner.check_true(
+        "thread_stack_free",
+        own.is_some_and(|t| t.stack_free.is_some_and(|free| free < t.stack_size)),
+    );
+
+    let sema = Semaphore::new(b"introspect_sema\0", 2, 5).unwrap();
+    let info = introspect::semaphores()
+        .unwrap()
+        .find(|s| s.uid == sema.id());
+    test_runner.check(
+        "semaphore_name",
+        info.as_ref().map(|s| s.name.as_str()),
+        Some("introspect_sema"),
+    );
+    test_runner.check(
+        "semaphore_counts",
+        info.as_ref()
+            .map(|s| (s.current_count, s.max_count, s.waiting_threads)),
+        Some((2, 5, 0)),
+    );
+    let sema_id = sema.id();
+    drop(sema);
+    test_runner.check_true(
+        "semaphore_deleted",
+        introspect::semaphores().unwrap().all(|s| s.uid != sema_id),
+    );
+
+    let flag = EventFlag::new(
+        b"introspect_flag\0",
+        EventFlagAttributes::WAIT_MULTIPLE,
+        0x5,
+    )
+    .unwrap();
+    let info = introspect::event_flags()
+        .unwrap()
+        .find(|f| f.name == "introspect_flag");
+    test_runner.check(
+        "event_flag_pattern",
+        info.map(|f| (f.init_pattern, f.current_pattern)),
+        Some((0x5, 0x5)),
+    );
+    drop(flag);
+
+    let partitions = introspect::memory_partitions();
+    let user = partitions
+        .iter()
+        .find(|p| p.id == SceSysMemPartitionId::SceKernelPrimaryUserPartition as i32);
+    test_runner.check_true(
+        "user_partition_free",
+        user.is_some_and(|p| p.free > 0 && p.max_free <= p.free),
+    );
+}
@@ -0,0 +1,92 @@
+use psp::io::{CachedFile, IoError, ReadAt};
+use psp::test_runner::TestRunner;
+
+/// In-memory source that counts reads, standing in for the memory stick.
+struct MockReader {
+    data: alloc::vec::Vec<u8>,
+    reads: usize,
+}
+
+impl ReadAt for MockReader {
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> Result<usize, IoError> {
+        self.reads += 1;
+        let start = (offset as usize).min(self.data.len());
+        let n = buf.len().min(self.data.len() - start);
+        buf[..n].copy_from_slice(&self.data[start..start + n]);
+        Ok(n)
+    }
+}
+
+fn mock(len: usize) -> MockReader {
+    MockReader {
+        data: (0..len).map(|i| (i % 251) as u8).collect(),
+        reads: 0,
+    }
+}
+
+fn expected(offset: usize, len: usize) -> alloc::vec::Vec<u8> {
+    (offset..offset + len).map(|i| (i % 251) as u8).collect()
+}
+
+pub fn test_main(test_runner: &mut TestRunner) {
+    // 4 blocks of 16 bytes over a 100-byte file (last block is short).
+    let mut file = CachedFile::with_blocks(mock(100), 4, 16);
+
+    // Read straddling blocks 0 and 1.
+    let mut buf = [0u8; 8];
+    let n = file.read_at(12, &mut buf).unwrap();
+    test_runner.check("cached_straddle_len", n, 8);
+    test_runner.check_large_collection("cached_straddle_data", &buf, &expected(12, 8));
+    test_runner.check("cached_straddle_misses", file.stats().misses, 2);
+    test_runner.check("cached_straddle_reads", file.get_ref().reads, 2);
+
+    // Same blocks again are served from the cache.
+    file.read_at(0, &mut buf).unwrap();
+    file.read_at(20, &mut buf).unwrap();
+    test_runner.check("cached_hits", file.stats().hits, 2);
+    test_runner.check("cached_hit_reads", file.get_ref().reads, 2);
+
+    // Reads past EOF are truncated.
+    let mut tail = [0u8; 16];
+    let n = file.read_at(92, &mut tail).unwrap();
+    test_runner.check("cached_eof_len", n, 8);
+    test_runner.check_large_collection("cached_eof_data", &tail[..8], &expected(92, 8));
+    test_runner.check(
+        "cached_past_eof_len",
+        file.read_at(200, &mut tail).unwrap(),
+        0,
+    );
+
+    // With two slots, touching block 0 makes block 1 the LRU, so loading
+    // block 2 evicts it.
+    let mut file = CachedFile::with_blocks(mock(100), 2, 16);
+    file.read_at(0, &mut buf).unwrap();
+    file.read_at(16, &mut buf).unwrap();
+    file.read_at(0, &mut buf).unwrap();
+    file.read_at(32, &mut buf).unwrap();
+    test_runner.check("cached_evict_misses", file.stats().misses, 3);
+    file.read_at(0, &mut buf).unwrap();
+    test_runner.check("cached_lru_kept", file.stats().hits, 2);
+    file.read_at(16, &mut buf).unwrap();
+    test_runner.check("cached_lru_evicted", file.stats().misses, 4);
+    test_runner.check_large_collection("cached_reload_data", &buf, &expected(16, 8));
+
+    // Prefetch loads blocks without counting them, so later reads hit.
+    let mut file = CachedFile::with_blocks(mock(100), 4, 16);
+    file.prefetch(40, 30).unwrap();
+    test_runner.check("cached_prefetch_reads", file.get_ref().reads, 3);
+    test_runner.check("cached_prefetch_uncounted", file.stats().misses, 0);
+    let mut buf = [0u8; 30];
+    file.read_at(40, &mut buf).unwrap();
+    test_runner.check_large_collection("cached_prefetch_data", &buf, &expected(40, 30));
+    test_runner.check("cached_prefetch_hits", file.stats().hits, 3);
+
+    // Prefetch never loads more blocks than the cache holds.
+    let mut file = CachedFile::with_blocks(mock(1000), 4, 16);
+    file.prefetch(0, 1000).unwrap();
+    test_runner.check("cached_prefetch_capped", file.get_ref().reads, 4);
+
+    file.invalidate();
+    file.read_at(0, &mut buf[..4]).unwrap();
+    test_runner.check("cached_invalidate_miss", file.stats().misses, 1);
+}
@@ -0,0 +1,75 @@
+extern crate alloc;
+
+use alloc::vec::Vec;
+use psp::io::{Change, ChangedPath, WatchError, WatchId, Watcher};
+use psp::test_runner::TestRunner;
+use psp::time::Duration;
+
+const PATH: &str = "host0:/io_watch_test.txt";
+const OTHER: &str = "host0:/io_watch_test_other.txt";
+
+fn changes<'a>(changed: impl Iterator<Item = ChangedPath<'a>>) -> Vec<(WatchId, Change)> {
+    changed.map(|c| (c.id, c.change)).collect()
+}
+
+pub fn test_main(test_runner: &mut TestRunner) {
+    let _ = psp::io::remove_file(PATH);
+    let _ = psp::io::remove_file(OTHER);
+    psp::io::write_bytes(PATH, b"one").unwrap();
+
+    // A long interval so only `poll_now` and the first `poll` check.
+    let mut watcher = Watcher::<2>::default().with_interval(Duration::from_secs(3600));
+    let file = watcher.watch(PATH).unwrap();
+    let other = watcher.watch(OTHER).unwrap();
+    test_runner.check("watch_len", watcher.len(), 2);
+    test_runner.check("watch_path", watcher.path(file), Some(PATH));
+    test_runner.check("watch_full", watcher.watch(PATH), Err(WatchError::Full));
+
+    // The first poll checks, but nothing changed since `watch`.
+    test_runner.check("first_poll_empty", changes(watcher.poll()).len(), 0);
+
+    // Sizes differ so the change shows even within one mtime second.
+    psp::io::write_bytes(PATH, b"three").unwrap();
+    psp::io::write_bytes(OTHER, b"new").unwrap();
+    test_runner.check("poll_within_interval", changes(watcher.poll()).len(), 0);
+    test_runner.check_large_collection(
+        "modified_and_added",
+        &changes(watcher.poll_now()),
+        &[(file, Change::Modified), (other, Change::Added)],
+    );
+    test_runner.check("reported_once", changes(watcher.poll_now()).len(), 0);
+
+    // Removed is reported once, then Added when the file comes back.
+    psp::io::remove_file(PATH).unwrap();
+    test_runner.check_large_collection(
+        "removed",
+        &changes(watcher.poll_now()),
+        &[(file, Change::Removed)],
+    );
+    test_runner.check("removed_once", changes(watcher.poll_now()).len(), 0);
+    psp::io::write_bytes(PATH, b"back").unwrap();
+
+    // A change left unconsumed is returned by the next poll, even between
+    // checks.
+    drop(watcher.poll_now());
+    test_runner.check_large_collection(
+        "readded",
+        &changes(watcher.poll()),
+        &[(file, Change::Added)],
+    );
+
+    test_runner.check_true("unwatch", watcher.unwatch(other));
+    test_runner.check_true("unwatch_twice", !watcher.unwatch(other));
+    test_runner.check("unwatch_path", watcher.path(other), None);
+    test_runner.check("unwatch_len", watcher.len(), 1);
+
+    // The freed slot gets a new id, so the stale one stays dead.
+    let again = watcher.watch(OTHER).unwrap();
+    test_runner.check_true("rewatch_new_id", again != other);
+    test_runner.check("rewatch_stale_path", watcher.path(other), None);
+    test_runner.check_true("rewatch_stale_unwatch", !watcher.unwatch(other));
+    test_runner.check("rewatch_path", watcher.path(again), Some(OTHER));
+
+    let _ = psp::io::remove_file(PATH);
+    let _ = psp::io::remove_file(OTHER);
+}
@@ -0,0 +1,82 @@
+use psp::kvstore::{Batch, KvError, KvStore};
+use psp::test_runner::TestRunner;
+
+const PATH: &str = "host0:/kvstore_test.kv";
+
+pub fn test_main(test_runner: &mut TestRunner) {
+    let _ = psp::io::remove_file(PATH);
+
+    let mut store = KvStore::open(PATH).unwrap();
+    test_runner.check("open_empty", store.len(), 0);
+
+    store.put("best", &1200u32.to_le_bytes()).unwrap();
+    store.put("name", b"AAA").unwrap();
+    store.put("best", &1500u32.to_le_bytes()).unwrap();
+    test_runner.check(
+        "get_latest",
+        store.get("best"),
+        Some(&1500u32.to_le_bytes()[..]),
+    );
+    test_runner.check("remove_existing", store.remove("name"), Ok(true));
+    test_runner.check("remove_missing", store.remove("name"), Ok(false));
+    test_runner.check(
+        "apply_batch",
+        store.apply(Batch::new().put("level", &[3]).put("coins", &[9, 0])),
+        Ok(()),
+    );
+    test_runner.check(
+        "key_too_long",
+        store.put(&"k".repeat(256), b""),
+        Err(KvError::KeyTooLong),
+    );
+    test_runner.check_true("has_garbage", store.garbage_bytes() > 0);
+
+    // Reopening replays the log.
+    let log_len = store.log_len();
+    drop(store);
+    let mut store = KvStore::open(PATH).unwrap();
+    test_runner.check("reopen_len", store.len(), 3);
+    test_runner.check("reopen_log_len", store.log_len(), log_len);
+    test_runner.check("reopen_removed", store.get("name"), None);
+    test_runner.check("reopen_batch", store.get("coins"), Some(&[9u8, 0][..]));
+
+    store.compact().unwrap();
+    test_runner.check("compact_garbage", store.garbage_bytes(), 0);
+    test_runner.check_true("compact_shrinks", store.log_len() < log_len);
+    drop(store);
+
+    // A torn trailing record is dropped on open.
+    let mut data = psp::io::read_to_vec(PATH).unwrap();
+    data.extend_from_slice(&[20, 0, 0, 0, 0, 1]);
+    psp::io::write_bytes(PATH, &data).unwrap();
+    let store = KvStore::open(PATH).unwrap();
+    test_runner.check("torn_len", store.len(), 3);
+    test_runner.check(
+        "torn_best",
+        store.get("best"),
+        Some(&1500u32.to_le_bytes()[..]),
+    );
+    test_runner.check("torn_repaired", store.log_len() < data.len(), true);
+
+    // A write that fails partway leaves the store usable: the next update
+    // isn't appended after the torn bytes, so it survives a reopen.
+    let mut store = store;
+    let short_write = store.apply_with(Batch::new().put("lost", b"xyz"), |file, record| {
+        file.write(&record[..record.len() / 2])?;
+        Err(psp::io::IoError(-1))
+    });
+    test_runner.check_true("short_write_fails", short_write.is_err());
+    test_runner.check("short_write_not_applied", store.get("lost"), None);
+    store.put("after", b"ok").unwrap();
+    drop(store);
+    let store = KvStore::open(PATH).unwrap();
+    test_runner.check("short_write_lost", store.get("lost"), None);
+    test_runner.check(
+        "short_write_next_kept",
+        store.get("after"),
+        Some(&b"ok"[..]),
+    );
+    drop(store);
+
+    let _ = psp::io::remove_file(PATH);
+}
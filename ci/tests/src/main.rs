@@ -1,14 +1,63 @@
 #![no_std]
 #![no_main]
-#![feature(asm_experimental_arch)]
+#![feature(allocator_api, asm_experimental_arch)]
 
 #[cfg(not(feature = "stub-only"))]
 extern crate alloc;
 
 use psp::test_runner::TestRunner;
 
+mod alloc_stats_test;
+mod audio_mixer_bench_test;
+mod audio_mixer_effects_test;
+mod audio_mixer_test;
+mod audio_stream_test;
+mod backtrace_test;
 mod bmp_screenshot_test;
+mod color_grade_test;
+mod config_format_test;
+mod config_schema_test;
+mod crypto_test;
+mod framebuffer_surface_test;
+mod gu_call_list_test;
+mod gu_capture_test;
+mod gu_palette_test;
+mod gu_picking_test;
+mod gu_transform_test;
+mod hash_test;
+mod http_chunked_test;
+mod image_bmp_test;
+mod image_collision_test;
+mod input_action_test;
+mod input_combo_test;
+mod input_dpad_test;
+mod input_event_test;
+mod introspect_test;
+mod io_cached_test;
+mod io_watch_test;
+mod kvstore_test;
 mod math_test;
+mod mpeg_pmf_test;
+mod net_ntp_test;
+mod net_websocket_test;
+mod osk_inline_test;
+mod pak_test;
+mod particles_test;
+mod partition_allocator_test;
+mod power_clock_test;
+mod rand_test;
+mod rtc_countdown_test;
+mod savedata_codec_test;
+mod simd_spline_test;
+mod skinning_test;
+mod sync_rwlock_test;
+mod task_test;
+mod thread_list_test;
+mod thread_stack_test;
+mod time_test;
+mod transition_test;
+mod ui_cursor_test;
+mod vertex_format_test;
 mod vfpu_test;
 mod vram_test;
 
@@ -16,8 +65,57 @@ psp::module!("ci_tests", 1, 1);
 
 fn psp_main() {
     let tests = &[
+        alloc_stats_test::test_main,
+        audio_mixer_bench_test::test_main,
+        audio_mixer_effects_test::test_main,
+        audio_mixer_test::test_main,
+        audio_stream_test::test_main,
+        backtrace_test::test_main,
         bmp_screenshot_test::test_main,
+        color_grade_test::test_main,
+        config_format_test::test_main,
+        config_schema_test::test_main,
+        crypto_test::test_main,
+        framebuffer_surface_test::test_main,
+        gu_call_list_test::test_main,
+        gu_capture_test::test_main,
+        gu_palette_test::test_main,
+        gu_picking_test::test_main,
+        gu_transform_test::test_main,
+        hash_test::test_main,
+        http_chunked_test::test_main,
+        image_bmp_test::test_main,
+        image_collision_test::test_main,
+        input_action_test::test_main,
+        input_combo_test::test_main,
+        input_dpad_test::test_main,
+        input_event_test::test_main,
+        introspect_test::test_main,
+        io_cached_test::test_main,
+        io_watch_test::test_main,
+        kvstore_test::test_main,
         math_test::test_main,
+        mpeg_pmf_test::test_main,
+        net_ntp_test::test_main,
+        net_websocket_test::test_main,
+        osk_inline_test::test_main,
+        pak_test::test_main,
+        particles_test::test_main,
+        partition_allocator_test::test_main,
+        power_clock_test::test_main,
+        rand_test::test_main,
+        rtc_countdown_test::test_main,
+        savedata_codec_test::test_main,
+        simd_spline_test::test_main,
+        skinning_test::test_main,
+        sync_rwlock_test::test_main,
+        task_test::test_main,
+        thread_list_test::test_main,
+        thread_stack_test::test_main,
+        time_test::test_main,
+        transition_test::test_main,
+        ui_cursor_test::test_main,
+        vertex_format_test::test_main,
         vfpu_test::test_main,
         vram_test::test_main,
     ];
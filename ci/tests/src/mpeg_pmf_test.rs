@@ -0,0 +1,104 @@
+use psp::mpeg::{MpegError, PmfInfo};
+use psp::test_runner::TestRunner;
+use psp::time::Duration;
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// `sceMpegQueryStreamOffset`'s error for a header that isn't PSMF.
+const ERROR_INVALID_VALUE: i32 = 0x8061_01FE_u32 as i32;
+
+/// A 2048-byte PSMF header listing `streams` as (stream id, sub-id).
+fn header(streams: &[(u8, u8)]) -> Vec<u8> {
+    let mut h = vec![0u8; 2048];
+    h[..8].copy_from_slice(b"PSMF0015");
+    h[0x08..0x0C].copy_from_slice(&0x800u32.to_be_bytes());
+    h[0x0C..0x10].copy_from_slice(&0x12_3400u32.to_be_bytes());
+    // 48-bit timestamps: 1 s and 11.5 s at 90 kHz.
+    h[0x54..0x5A].copy_from_slice(&90_000u64.to_be_bytes()[2..]);
+    h[0x5A..0x60].copy_from_slice(&1_035_000u64.to_be_bytes()[2..]);
+    h[0x80..0x82].copy_from_slice(&(streams.len() as u16).to_be_bytes());
+    for (i, &(id, sub_id)) in streams.iter().enumerate() {
+        let entry = &mut h[0x82 + i * 16..0x82 + (i + 1) * 16];
+        entry[0] = id;
+        entry[1] = sub_id;
+        // Size in macroblocks.
+        entry[12] = 30;
+        entry[13] = 17;
+    }
+    h
+}
+
+pub fn test_main(test_runner: &mut TestRunner) {
+    let info = PmfInfo::parse(&header(&[(0xE0, 0x00), (0xBD, 0x00)]));
+    test_runner.check(
+        "video_and_audio",
+        info,
+        Ok(PmfInfo {
+            stream_offset: 0x800,
+            stream_size: 0x12_3400,
+            first_pts: 90_000,
+            last_pts: 1_035_000,
+            width: 480,
+            height: 272,
+            video_streams: 1,
+            audio_streams: 1,
+        }),
+    );
+    test_runner.check(
+        "duration",
+        info.map(|info| info.duration()),
+        Ok(Duration::from_millis(10_500)),
+    );
+
+    let video_only = PmfInfo::parse(&header(&[(0xE0, 0x00)]));
+    test_runner.check(
+        "video_only",
+        video_only.map(|info| (info.video_streams, info.audio_streams)),
+        Ok((1, 0)),
+    );
+
+    // Linear PCM (sub-id 0x40) isn't ATRAC3plus, and a second video
+    // stream doesn't change the size.
+    let mut two_videos = header(&[(0xE0, 0x00), (0xE1, 0x00), (0xBD, 0x40)]);
+    two_videos[0x82 + 16 + 12] = 15;
+    let info = PmfInfo::parse(&two_videos);
+    test_runner.check(
+        "pcm_and_second_video",
+        info.map(|info| (info.video_streams, info.audio_streams, info.width)),
+        Ok((2, 0, 480)),
+    );
+
+    let no_streams = PmfInfo::parse(&header(&[]));
+    test_runner.check(
+        "no_streams",
+        no_streams.map(|info| (info.video_streams, info.width, info.height)),
+        Ok((0, 0, 0)),
+    );
+
+    let mut bad_magic = header(&[(0xE0, 0x00)]);
+    bad_magic[0] = b'X';
+    test_runner.check(
+        "bad_magic",
+        PmfInfo::parse(&bad_magic),
+        Err(MpegError(ERROR_INVALID_VALUE)),
+    );
+
+    // The stream table runs past the end of the buffer.
+    let full = header(&[(0xE0, 0x00), (0xBD, 0x00)]);
+    test_runner.check(
+        "truncated_table",
+        PmfInfo::parse(&full[..0x82 + 16]),
+        Err(MpegError(ERROR_INVALID_VALUE)),
+    );
+    test_runner.check(
+        "truncated_header",
+        PmfInfo::parse(&full[..0x40]),
+        Err(MpegError(ERROR_INVALID_VALUE)),
+    );
+    test_runner.check(
+        "just_the_table",
+        PmfInfo::parse(&full[..0x82 + 32]).map(|info| info.audio_streams),
+        Ok(1),
+    );
+}
@@ -0,0 +1,80 @@
+use psp::net::ntp::{
+    build_request, compute_offset, parse_response, tick_to_timestamp, timestamp_to_tick, Reply,
+    NTP_EPOCH_TICK, PACKET_SIZE,
+};
+use psp::rtc::Tick;
+use psp::test_runner::TestRunner;
+
+/// 1970-01-01 00:00:00 UTC.
+const UNIX_EPOCH_TICK: u64 = 62_135_596_800_000_000;
+const UNIX_EPOCH_NTP_SECS: u32 = 2_208_988_800;
+
+pub fn test_main(test_runner: &mut TestRunner) {
+    test_runner.check(
+        "ntp_unix_epoch",
+        timestamp_to_tick(UNIX_EPOCH_NTP_SECS, 0),
+        Tick(UNIX_EPOCH_TICK),
+    );
+    test_runner.check(
+        "ntp_half_second",
+        timestamp_to_tick(UNIX_EPOCH_NTP_SECS, 0x8000_0000),
+        Tick(UNIX_EPOCH_TICK + 500_000),
+    );
+    // Era 1 starts when the 32-bit seconds wrap in 2036.
+    test_runner.check(
+        "ntp_era_1",
+        timestamp_to_tick(0, 0),
+        Tick(NTP_EPOCH_TICK + (1 << 32) * 1_000_000),
+    );
+    test_runner.check(
+        "ntp_to_timestamp",
+        tick_to_timestamp(Tick(UNIX_EPOCH_TICK + 500_000)),
+        (UNIX_EPOCH_NTP_SECS, 0x8000_0000),
+    );
+    let tick = Tick(UNIX_EPOCH_TICK + 1_700_000_000_123_456);
+    let (secs, frac) = tick_to_timestamp(tick);
+    test_runner.check_true(
+        "ntp_roundtrip",
+        timestamp_to_tick(secs, frac).0.abs_diff(tick.0) <= 1,
+    );
+
+    test_runner.check(
+        "ntp_offset",
+        compute_offset(Tick(100), Tick(1100), Tick(1200), Tick(400)),
+        900,
+    );
+    test_runner.check(
+        "ntp_offset_ahead",
+        compute_offset(Tick(1000), Tick(500), Tick(500), Tick(1000)),
+        -500,
+    );
+
+    let sent = Tick(UNIX_EPOCH_TICK + 10_000_000);
+    let request = build_request(sent);
+    test_runner.check("ntp_request_header", request[0], 0x23);
+
+    let mut reply = [0u8; PACKET_SIZE];
+    reply[0] = 0x24; // LI 0, version 4, server
+    reply[1] = 2;
+    reply[24..32].copy_from_slice(&request[40..48]);
+    reply[32..36].copy_from_slice(&(UNIX_EPOCH_NTP_SECS + 20).to_be_bytes());
+    reply[40..44].copy_from_slice(&(UNIX_EPOCH_NTP_SECS + 21).to_be_bytes());
+    test_runner.check(
+        "ntp_parse_reply",
+        parse_response(&reply, sent),
+        Ok(Reply {
+            receive: Tick(UNIX_EPOCH_TICK + 20_000_000),
+            transmit: Tick(UNIX_EPOCH_TICK + 21_000_000),
+        }),
+    );
+    test_runner.check_true(
+        "ntp_reject_other_request",
+        parse_response(&reply, Tick(sent.0 + 1_000_000)).is_err(),
+    );
+    test_runner.check_true(
+        "ntp_reject_short",
+        parse_response(&reply[..40], sent).is_err(),
+    );
+    reply[1] = 0; // kiss-o'-death
+    test_runner.check_true("ntp_reject_kod", parse_response(&reply, sent).is_err());
+}
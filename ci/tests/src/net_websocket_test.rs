@@ -0,0 +1,213 @@
+use psp::net::websocket::{
+    accept_key, apply_mask, encode_frame, verify_handshake, FrameHeader, MessageAssembler, Opcode,
+    WsError, WsMessage,
+};
+use psp::test_runner::TestRunner;
+
+extern crate alloc;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// An unmasked data frame, final or not.
+fn frame(fin: bool, opcode: Opcode, payload: &[u8]) -> (FrameHeader, Vec<u8>) {
+    let mut bytes = encode_frame(opcode, payload, None);
+    if !fin {
+        bytes[0] &= 0x7f;
+    }
+    let header = FrameHeader::parse(&bytes).unwrap().unwrap();
+    (header, bytes[header.header_len..].to_vec())
+}
+
+/// Push `frames` through a fresh assembler with a `max`-byte limit,
+/// returning what each push gave.
+fn assemble(
+    max: usize,
+    frames: &[(bool, Opcode, &[u8])],
+) -> Vec<Result<Option<WsMessage>, WsError>> {
+    let mut assembler = MessageAssembler::new(max);
+    frames
+        .iter()
+        .map(|&(fin, opcode, payload)| {
+            let (header, payload) = frame(fin, opcode, payload);
+            assembler.push(&header, payload)
+        })
+        .collect()
+}
+
+pub fn test_main(test_runner: &mut TestRunner) {
+    // RFC 6455 section 1.3.
+    test_runner.check(
+        "accept_key_rfc",
+        accept_key("dGhlIHNhbXBsZSBub25jZQ==").as_str(),
+        "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=",
+    );
+    let head = "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\n\
+                Connection: Upgrade\r\nsec-websocket-accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\r\n\r\n";
+    test_runner.check(
+        "handshake_ok",
+        verify_handshake(head, "dGhlIHNhbXBsZSBub25jZQ=="),
+        Ok(()),
+    );
+    test_runner.check(
+        "handshake_wrong_key",
+        verify_handshake(head, "AAAAAAAAAAAAAAAAAAAAAA=="),
+        Err(WsError::Handshake),
+    );
+    test_runner.check(
+        "handshake_not_101",
+        verify_handshake("HTTP/1.1 200 OK\r\n\r\n", "dGhlIHNhbXBsZSBub25jZQ=="),
+        Err(WsError::Handshake),
+    );
+
+    // RFC 6455 section 5.7: unmasked and masked "Hello".
+    let unmasked = encode_frame(Opcode::Text, b"Hello", None);
+    test_runner.check_large_collection(
+        "encode_unmasked",
+        &unmasked,
+        &[0x81, 0x05, 0x48, 0x65, 0x6c, 0x6c, 0x6f],
+    );
+    let masked = encode_frame(Opcode::Text, b"Hello", Some([0x37, 0xfa, 0x21, 0x3d]));
+    test_runner.check_large_collection(
+        "encode_masked",
+        &masked,
+        &[
+            0x81, 0x85, 0x37, 0xfa, 0x21, 0x3d, 0x7f, 0x9f, 0x4d, 0x51, 0x58,
+        ],
+    );
+
+    let header = FrameHeader::parse(&masked).unwrap().unwrap();
+    test_runner.check("parse_fin", header.fin, true);
+    test_runner.check("parse_opcode", header.opcode, Opcode::Text);
+    test_runner.check("parse_mask", header.mask, Some([0x37, 0xfa, 0x21, 0x3d]));
+    test_runner.check("parse_len", header.payload_len, 5);
+    test_runner.check("parse_header_len", header.header_len, 6);
+    let mut payload = masked[header.header_len..].to_vec();
+    apply_mask(&mut payload, header.mask.unwrap());
+    test_runner.check_large_collection("unmask", &payload, b"Hello");
+
+    test_runner.check("parse_partial", FrameHeader::parse(&masked[..4]), Ok(None));
+    test_runner.check("parse_empty", FrameHeader::parse(&[]), Ok(None));
+
+    // 16-bit and 64-bit extended lengths.
+    let medium = encode_frame(Opcode::Binary, &vec![0; 256], None);
+    test_runner.check_large_collection("encode_len16", &medium[..4], &[0x82, 126, 0x01, 0x00]);
+    let header = FrameHeader::parse(&medium).unwrap().unwrap();
+    test_runner.check("parse_len16", header.payload_len, 256);
+    test_runner.check("parse_len16_header", header.header_len, 4);
+    let large = encode_frame(Opcode::Binary, &vec![0; 65536], None);
+    let header = FrameHeader::parse(&large).unwrap().unwrap();
+    test_runner.check("parse_len64", header.payload_len, 65536);
+    test_runner.check("parse_len64_header", header.header_len, 10);
+
+    // First fragment of an unmasked "Hel" text message.
+    let header = FrameHeader::parse(&[0x01, 0x03, 0x48, 0x65, 0x6c])
+        .unwrap()
+        .unwrap();
+    test_runner.check("parse_fragment_fin", header.fin, false);
+
+    test_runner.check(
+        "parse_reserved_bits",
+        FrameHeader::parse(&[0xc1, 0x00]),
+        Err(WsError::Protocol),
+    );
+    test_runner.check(
+        "parse_bad_opcode",
+        FrameHeader::parse(&[0x83, 0x00]),
+        Err(WsError::Protocol),
+    );
+    test_runner.check(
+        "parse_fragmented_ping",
+        FrameHeader::parse(&[0x09, 0x00]),
+        Err(WsError::Protocol),
+    );
+    test_runner.check(
+        "parse_long_ping",
+        FrameHeader::parse(&[0x89, 126, 0x00, 0x80]),
+        Err(WsError::Protocol),
+    );
+
+    // Fragmented messages come out whole, on their final frame.
+    let text = assemble(
+        64,
+        &[
+            (false, Opcode::Text, b"Hel"),
+            (false, Opcode::Continuation, b""),
+            (true, Opcode::Continuation, b"lo"),
+        ],
+    );
+    test_runner.check(
+        "reassemble_text",
+        text,
+        vec![
+            Ok(None),
+            Ok(None),
+            Ok(Some(WsMessage::Text(String::from("Hello")))),
+        ],
+    );
+    let binary = assemble(
+        64,
+        &[
+            (false, Opcode::Binary, &[1, 2]),
+            (true, Opcode::Continuation, &[3]),
+            (true, Opcode::Binary, &[4]),
+        ],
+    );
+    test_runner.check(
+        "reassemble_binary",
+        binary,
+        vec![
+            Ok(None),
+            Ok(Some(WsMessage::Binary(vec![1, 2, 3]))),
+            Ok(Some(WsMessage::Binary(vec![4]))),
+        ],
+    );
+    // A UTF-8 sequence may be split across fragments.
+    let split = assemble(
+        64,
+        &[
+            (false, Opcode::Text, &[0xc3]),
+            (true, Opcode::Continuation, &[0xa9]),
+        ],
+    );
+    test_runner.check(
+        "reassemble_split_utf8",
+        split,
+        vec![Ok(None), Ok(Some(WsMessage::Text(String::from("\u{e9}"))))],
+    );
+
+    test_runner.check(
+        "reassemble_orphan_continuation",
+        assemble(64, &[(true, Opcode::Continuation, b"x")]),
+        vec![Err(WsError::Protocol)],
+    );
+    test_runner.check(
+        "reassemble_interleaved",
+        assemble(
+            64,
+            &[(false, Opcode::Text, b"a"), (true, Opcode::Binary, b"b")],
+        ),
+        vec![Ok(None), Err(WsError::Protocol)],
+    );
+    test_runner.check(
+        "reassemble_control",
+        assemble(64, &[(true, Opcode::Ping, b"")]),
+        vec![Err(WsError::Protocol)],
+    );
+    test_runner.check(
+        "reassemble_too_large",
+        assemble(
+            4,
+            &[
+                (false, Opcode::Binary, &[0; 3]),
+                (true, Opcode::Continuation, &[0; 2]),
+            ],
+        ),
+        vec![Ok(None), Err(WsError::MessageTooLarge { len: 5, max: 4 })],
+    );
+    test_runner.check(
+        "reassemble_bad_utf8",
+        assemble(64, &[(true, Opcode::Text, &[0xff])]),
+        vec![Err(WsError::InvalidUtf8)],
+    );
+}
@@ -0,0 +1,49 @@
+use psp::osk::inline::{cell_for_stick, InlineKeyboard, KeyEvent, KeyInput, Layer};
+use psp::sys::CtrlButtons;
+use psp::test_runner::TestRunner;
+
+pub fn test_main(test_runner: &mut TestRunner) {
+    test_runner.check_list(&[
+        ("osk_inline_cell_center", cell_for_stick(0.0, 0.0), 4),
+        ("osk_inline_cell_top_left", cell_for_stick(-1.0, -1.0), 0),
+        ("osk_inline_cell_bottom_right", cell_for_stick(1.0, 1.0), 8),
+        ("osk_inline_cell_right", cell_for_stick(0.9, 0.1), 5),
+    ]);
+
+    let mut kb = InlineKeyboard::new(3);
+
+    // Top-left cell, Triangle -> 'a'.
+    let ev = kb.handle(press(CtrlButtons::TRIANGLE, -1.0, -1.0));
+    test_runner.check("osk_inline_type_a", ev, Some(KeyEvent::Char('a')));
+
+    // Shift, then top-left cell Cross -> 'D'.
+    kb.handle(press(CtrlButtons::RTRIGGER, 0.0, 0.0));
+    test_runner.check("osk_inline_shift_layer", kb.layer(), Layer::Upper);
+    let ev = kb.handle(press(CtrlButtons::CROSS, -1.0, -1.0));
+    test_runner.check("osk_inline_type_upper", ev, Some(KeyEvent::Char('D')));
+
+    kb.handle(press(CtrlButtons::SELECT, 0.0, 0.0));
+    test_runner.check("osk_inline_symbol_layer", kb.layer(), Layer::Symbols);
+    let ev = kb.handle(press(CtrlButtons::TRIANGLE, -1.0, -1.0));
+    test_runner.check("osk_inline_type_digit", ev, Some(KeyEvent::Char('1')));
+
+    // Buffer is full at 3 characters.
+    let ev = kb.handle(press(CtrlButtons::SQUARE, -1.0, -1.0));
+    test_runner.check("osk_inline_max_len", ev, None);
+    test_runner.check("osk_inline_text", kb.current_text(), "aD1");
+
+    let ev = kb.handle(press(CtrlButtons::LTRIGGER, 0.0, 0.0));
+    test_runner.check("osk_inline_backspace", ev, Some(KeyEvent::Backspace));
+    test_runner.check("osk_inline_text_after_backspace", kb.current_text(), "aD");
+
+    let ev = kb.handle(press(CtrlButtons::START, 0.0, 0.0));
+    test_runner.check("osk_inline_enter", ev, Some(KeyEvent::Enter));
+}
+
+fn press(buttons: CtrlButtons, stick_x: f32, stick_y: f32) -> KeyInput {
+    KeyInput {
+        pressed: buttons,
+        stick_x,
+        stick_y,
+    }
+}
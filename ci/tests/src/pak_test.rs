@@ -0,0 +1,186 @@
+use alloc::vec::Vec;
+use psp::io::{IoError, ReadAt};
+use psp::pak::{normalize_path, PakBuilder, PakError, PakReader};
+use psp::test_runner::TestRunner;
+
+/// Written by `cargo_psp::pak::PakBuilder`; see `cargo-psp/tests/pak.rs`.
+const HOST_PAK: &[u8] = include_bytes!("../assets/host.pak");
+
+/// In-memory archive that counts reads.
+struct MemPak {
+    data: Vec<u8>,
+    reads: usize,
+    /// Report the length through `len_hint`.
+    known_len: bool,
+}
+
+impl ReadAt for MemPak {
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> Result<usize, IoError> {
+        self.reads += 1;
+        let start = (offset as usize).min(self.data.len());
+        let n = buf.len().min(self.data.len() - start);
+        buf[..n].copy_from_slice(&self.data[start..start + n]);
+        Ok(n)
+    }
+
+    fn len_hint(&mut self) -> Result<Option<u64>, IoError> {
+        Ok(self.known_len.then_some(self.data.len() as u64))
+    }
+}
+
+fn mem_pak(data: Vec<u8>, known_len: bool) -> MemPak {
+    MemPak {
+        data,
+        reads: 0,
+        known_len,
+    }
+}
+
+/// A header for `count` entries and `names_size` bytes of names, with
+/// nothing after it.
+fn bare_header(count: u32, names_size: u32) -> Vec<u8> {
+    let mut data = b"PPAK".to_vec();
+    data.extend_from_slice(&1u32.to_le_bytes());
+    data.extend_from_slice(&count.to_le_bytes());
+    data.extend_from_slice(&names_size.to_le_bytes());
+    data
+}
+
+pub fn test_main(test_runner: &mut TestRunner) {
+    test_runner.check(
+        "pak_normalize",
+        &*normalize_path("\\sprites\\hero.png"),
+        "sprites/hero.png",
+    );
+
+    let mut builder = PakBuilder::new();
+    builder.add("sprites/hero.png", b"hero").unwrap();
+    builder.add("levels/1.bin", &[1, 2, 3]).unwrap();
+    builder.add("empty", b"").unwrap();
+    test_runner.check(
+        "pak_duplicate",
+        builder.add("/sprites/hero.png", b"again"),
+        Err(PakError::DuplicatePath("sprites/hero.png".into())),
+    );
+    test_runner.check("pak_builder_len", builder.len(), 3);
+    let data = builder.finish().unwrap();
+
+    let mut pak = PakReader::new(mem_pak(data.clone(), true)).unwrap();
+    test_runner.check("pak_len", pak.len(), 3);
+    test_runner.check_true("pak_exists", pak.exists("levels/1.bin"));
+    test_runner.check_true("pak_exists_normalized", pak.exists("\\levels\\1.bin"));
+    test_runner.check_true("pak_missing", !pak.exists("levels/2.bin"));
+    test_runner.check("pak_size", pak.size("sprites/hero.png"), Some(4));
+
+    let mut names: Vec<&str> = pak.entries().map(|e| e.name).collect();
+    names.sort_unstable();
+    test_runner.check_large_collection(
+        "pak_entries",
+        &names,
+        &["empty", "levels/1.bin", "sprites/hero.png"],
+    );
+
+    test_runner.check(
+        "pak_read",
+        pak.read("sprites/hero.png").unwrap(),
+        b"hero".to_vec(),
+    );
+    let mut buf = [0u8; 8];
+    test_runner.check(
+        "pak_read_into",
+        pak.read_into("levels/1.bin", &mut buf),
+        Ok(3),
+    );
+    test_runner.check_large_collection("pak_read_into_data", &buf[..3], &[1, 2, 3]);
+    test_runner.check(
+        "pak_read_into_small",
+        pak.read_into("sprites/hero.png", &mut buf[..2]),
+        Err(PakError::BufferTooSmall { needed: 4 }),
+    );
+    test_runner.check(
+        "pak_read_missing",
+        pak.read("nope"),
+        Err(PakError::NotFound),
+    );
+    // Header and table reads, then one read per successful request.
+    test_runner.check("pak_one_read_per_asset", pak.into_inner().reads, 4);
+
+    // The same files packed on the host by cargo-psp.
+    test_runner.check_large_collection("pak_host_bytes", &data, HOST_PAK);
+    let mut host = PakReader::new(mem_pak(HOST_PAK.to_vec(), true)).unwrap();
+    test_runner.check(
+        "pak_host_read",
+        host.read("levels/1.bin").unwrap(),
+        [1, 2, 3].to_vec(),
+    );
+    test_runner.check("pak_host_size", host.size("empty"), Some(0));
+
+    test_runner.check_true(
+        "pak_bad_magic",
+        matches!(
+            PakReader::new(mem_pak(b"NOPE0000000000000000".to_vec(), true)),
+            Err(PakError::BadMagic)
+        ),
+    );
+
+    // Headers claiming tables far larger than the archive fail before
+    // anything is allocated for them, whether or not the reader knows
+    // its length.
+    for known_len in [true, false] {
+        test_runner.check_true(
+            "pak_table_past_end",
+            matches!(
+                PakReader::new(mem_pak(bare_header(0x0AAA_AAAA, 0), known_len)),
+                Err(PakError::Corrupt)
+            ),
+        );
+        test_runner.check_true(
+            "pak_names_past_end",
+            matches!(
+                PakReader::new(mem_pak(bare_header(0, u32::MAX), known_len)),
+                Err(PakError::Corrupt)
+            ),
+        );
+        test_runner.check(
+            "pak_unknown_len",
+            PakReader::new(mem_pak(data.clone(), known_len)).map(|pak| pak.len()),
+            Ok(3),
+        );
+    }
+
+    // The first entry's data and name fields, after the 16-byte header and
+    // its 8-byte hash.
+    let with_entry = |offset: u32, size: u32, name_offset: u32| {
+        let mut bad = data.clone();
+        bad[24..28].copy_from_slice(&offset.to_le_bytes());
+        bad[28..32].copy_from_slice(&size.to_le_bytes());
+        bad[32..36].copy_from_slice(&name_offset.to_le_bytes());
+        bad
+    };
+    let data_start = le_u32(&data[24..]);
+    let name_offset = le_u32(&data[32..]);
+    let bad_entries = [
+        (
+            "pak_data_past_end",
+            with_entry(data_start, 0x1000, name_offset),
+        ),
+        ("pak_data_overflow", with_entry(u32::MAX, 2, name_offset)),
+        ("pak_data_in_table", with_entry(0, 1, name_offset)),
+        ("pak_name_overflow", with_entry(data_start, 0, u32::MAX)),
+    ];
+    for (name, bad) in bad_entries {
+        test_runner.check_true(
+            name,
+            matches!(PakReader::new(mem_pak(bad, true)), Err(PakError::Corrupt)),
+        );
+    }
+    test_runner.check(
+        "pak_empty_archive",
+        PakReader::new(mem_pak(bare_header(0, 0), false)).map(|pak| pak.len()),
+        Ok(0),
+    );
+}
+
+fn le_u32(b: &[u8]) -> u32 {
+    u32::from_le_bytes([b[0], b[1], b[2], b[3]])
+}
@@ -0,0 +1,102 @@
+use psp::gu_ext::{EmitterConfig, ParticleSystem};
+use psp::test_runner::TestRunner;
+
+/// Ten particles per second, each living just over a second and moving
+/// right at a fixed speed while falling.
+fn steady_config() -> EmitterConfig {
+    EmitterConfig {
+        spawn_rate: 10.0,
+        lifetime: (1.05, 1.05),
+        velocity_min: (10.0, 0.0),
+        velocity_max: (10.0, 0.0),
+        gravity: (0.0, 20.0),
+        start_color: 0xFF00_00FF,
+        end_color: 0x00FF_0000,
+        start_size: 8.0,
+        end_size: 2.0,
+    }
+}
+
+fn near(a: f32, b: f32) -> bool {
+    (a - b).abs() < 1e-4
+}
+
+pub fn test_main(test_runner: &mut TestRunner) {
+    let mut system = ParticleSystem::<16>::new(steady_config(), 1);
+    test_runner.check("starts_empty", system.live_count(), 0);
+    for _ in 0..5 {
+        system.update(0.1);
+    }
+    test_runner.check("spawns_at_rate", system.live_count(), 5);
+    for _ in 0..25 {
+        system.update(0.1);
+    }
+    // Particles spawned in the last 11 ticks are still alive.
+    test_runner.check("steady_state_occupancy", system.live_count(), 11);
+    let ordered = system.particles().windows(2).all(|w| w[0].age >= w[1].age);
+    test_runner.check_true("particles_oldest_first", ordered);
+
+    let mut full = ParticleSystem::<8>::new(steady_config(), 1);
+    for _ in 0..30 {
+        full.update(0.1);
+    }
+    test_runner.check("pool_saturates", full.live_count(), 8);
+    test_runner.check("burst_when_full", full.burst(4), 0);
+
+    let mut single = ParticleSystem::<4>::new(
+        EmitterConfig {
+            spawn_rate: 0.0,
+            ..steady_config()
+        },
+        1,
+    );
+    single.set_position(100.0, 50.0);
+    test_runner.check("burst_spawns", single.burst(1), 1);
+    single.update(0.1);
+    let p = single.particles()[0];
+    test_runner.check_true("semi_implicit_x", near(p.x, 101.0));
+    test_runner.check_true("semi_implicit_y", near(p.y, 50.2));
+    test_runner.check_true("semi_implicit_vy", near(p.vy, 2.0));
+    single.update(0.1);
+    test_runner.check_true("second_step_y", near(single.particles()[0].y, 50.6));
+
+    let mut fresh = ParticleSystem::<4>::new(steady_config(), 1);
+    fresh.burst(1);
+    let p = fresh.particles()[0];
+    test_runner.check("color_at_birth", fresh.color_of(&p), 0xFF00_00FF);
+    test_runner.check("size_at_birth", fresh.size_of(&p), 8.0);
+    let dying = psp::gu_ext::Particle {
+        age: p.lifetime,
+        ..p
+    };
+    test_runner.check("color_at_death", fresh.color_of(&dying), 0x00FF_0000);
+    test_runner.check("size_at_death", fresh.size_of(&dying), 2.0);
+
+    let mut stopped = ParticleSystem::<16>::new(steady_config(), 1);
+    for _ in 0..10 {
+        stopped.update(0.1);
+    }
+    stopped.set_emitting(false);
+    for _ in 0..12 {
+        stopped.update(0.1);
+    }
+    test_runner.check("all_die_after_stop", stopped.live_count(), 0);
+
+    let spread = EmitterConfig {
+        spawn_rate: 60.0,
+        lifetime: (0.5, 2.0),
+        velocity_min: (-50.0, -50.0),
+        velocity_max: (50.0, 50.0),
+        ..steady_config()
+    };
+    let mut a = ParticleSystem::<64>::new(spread, 7);
+    let mut b = ParticleSystem::<64>::new(spread, 7);
+    let mut c = ParticleSystem::<64>::new(spread, 8);
+    for _ in 0..90 {
+        a.update(1.0 / 60.0);
+        b.update(1.0 / 60.0);
+        c.update(1.0 / 60.0);
+    }
+    test_runner.check_true("same_seed_same_particles", a.particles() == b.particles());
+    test_runner.check_true("different_seed_differs", a.particles() != c.particles());
+}
@@ -0,0 +1,52 @@
+use alloc::vec::Vec;
+use core::alloc::{Allocator, Layout};
+use psp::mem::PartitionAllocator;
+use psp::sys::SceSysMemPartitionId;
+use psp::test_runner::TestRunner;
+
+pub fn test_main(test_runner: &mut TestRunner) {
+    let user = PartitionAllocator::USER;
+    test_runner.check_true(
+        "user_partition",
+        user.partition() == SceSysMemPartitionId::SceKernelPrimaryUserPartition,
+    );
+
+    let mut values: Vec<u32, _> = Vec::with_capacity_in(1024, user);
+    values.extend(0..1024);
+    test_runner.check("vec_len", values.len(), 1024);
+    test_runner.check("vec_sum", values.iter().sum::<u32>(), 1023 * 1024 / 2);
+    // Growing reallocates into a fresh block.
+    values.extend(0..1024);
+    test_runner.check("vec_grown_last", values[2047], 1023);
+    drop(values);
+
+    let before = psp::alloc_stats();
+    let buf = Vec::<u8, _>::with_capacity_in(64 * 1024, user);
+    test_runner.check(
+        "bypasses_global_heap",
+        psp::alloc_stats().used_bytes,
+        before.used_bytes,
+    );
+    drop(buf);
+
+    let mut aligned = true;
+    for align in [1, 4, 16, 64, 256, 4096] {
+        let layout = Layout::from_size_align(100, align).unwrap();
+        let Ok(ptr) = user.allocate(layout) else {
+            aligned = false;
+            continue;
+        };
+        let data = ptr.cast::<u8>();
+        aligned &= data.as_ptr() as usize % align == 0 && ptr.len() == 100;
+        // SAFETY: Just allocated with `layout`.
+        unsafe {
+            data.as_ptr().write_bytes(0xAB, 100);
+            user.deallocate(data, layout);
+        }
+    }
+    test_runner.check_true("allocations_aligned", aligned);
+
+    let zst = Layout::from_size_align(0, 8).unwrap();
+    let empty = user.allocate(zst);
+    test_runner.check("zero_size_len", empty.map(|p| p.len()).ok(), Some(0));
+}
@@ -0,0 +1,54 @@
+use core::sync::atomic::{AtomicI32, Ordering};
+
+use psp::power::{self, ClockChange, ClockRequest};
+use psp::test_runner::TestRunner;
+
+static OLD_MHZ: AtomicI32 = AtomicI32::new(0);
+static NEW_MHZ: AtomicI32 = AtomicI32::new(0);
+
+fn cpu_mhz() -> i32 {
+    power::get_clock().cpu_mhz
+}
+
+pub fn test_main(test_runner: &mut TestRunner) {
+    let start = power::get_clock();
+
+    power::set_clock(111, 55).unwrap();
+    test_runner.check("no_floor", power::clock_floor(), None);
+    test_runner.check("unraised", cpu_mhz(), 111);
+
+    let watch = power::on_clock_change(|change: ClockChange| {
+        OLD_MHZ.store(change.old_mhz, Ordering::Release);
+        NEW_MHZ.store(change.new_mhz, Ordering::Release);
+    })
+    .unwrap();
+
+    // A request raises the clock right away.
+    let low = ClockRequest::minimum(222);
+    test_runner.check("floor_222", power::clock_floor(), Some(222));
+    test_runner.check("raised_222", cpu_mhz(), 222);
+
+    psp::thread::sleep_ms(power::CLOCK_POLL_MS * 3);
+    test_runner.check("notified_old", OLD_MHZ.load(Ordering::Acquire), 111);
+    test_runner.check("notified_new", NEW_MHZ.load(Ordering::Acquire), 222);
+
+    // Asking for less keeps the floor, asking for more goes above it.
+    power::set_clock(111, 55).unwrap();
+    test_runner.check("held_at_floor", cpu_mhz(), 222);
+    power::set_clock(266, 133).unwrap();
+    test_runner.check("above_floor", cpu_mhz(), 266);
+    power::set_clock(111, 55).unwrap();
+
+    // The highest of two requests wins until it's dropped.
+    let high = ClockRequest::minimum(333);
+    test_runner.check("floor_333", power::clock_floor(), Some(333));
+    test_runner.check("raised_333", cpu_mhz(), 333);
+    drop(high);
+    test_runner.check("back_to_222", cpu_mhz(), 222);
+    drop(low);
+    test_runner.check("released_floor", power::clock_floor(), None);
+    test_runner.check("released", cpu_mhz(), 111);
+
+    drop(watch);
+    power::set_clock(start.cpu_mhz, start.bus_mhz).unwrap();
+}
@@ -0,0 +1,95 @@
+use psp::rand::{self, Rng};
+use psp::test_runner::TestRunner;
+
+const SAMPLES: usize = 100_000;
+
+pub fn test_main(test_runner: &mut TestRunner) {
+    let mut rng = Rng::new(1);
+    test_runner.check("xoshiro_seed_1_first", rng.next_u32(), 2146930148);
+    test_runner.check("xoshiro_seed_1_second", rng.next_u32(), 2585199205);
+    test_runner.check("xoshiro_seed_1_third", rng.next_u32(), 3670091704);
+
+    let mut zero = Rng::new(0);
+    let varied = (0..16).any(|_| zero.next_u32() != 0);
+    test_runner.check_true("zero_seed_not_stuck", varied);
+
+    let mut a = Rng::new(0xDEAD_BEEF);
+    let mut b = Rng::new(0xDEAD_BEEF);
+    let same = (0..64).all(|_| a.next_u32() == b.next_u32());
+    test_runner.check_true("same_seed_same_sequence", same);
+
+    // Uniform [0, 1) has mean 1/2 and variance 1/12.
+    let mut rng = Rng::new(42);
+    let mut sum = 0.0f64;
+    let mut sum_sq = 0.0f64;
+    let mut in_range = true;
+    for _ in 0..SAMPLES {
+        let v = rng.next_f32();
+        in_range &= (0.0..1.0).contains(&v);
+        sum += v as f64;
+        sum_sq += (v as f64) * (v as f64);
+    }
+    let mean = sum / SAMPLES as f64;
+    let variance = sum_sq / SAMPLES as f64 - mean * mean;
+    test_runner.check_true("next_f32_in_unit_range", in_range);
+    test_runner.check_true("next_f32_mean", (mean - 0.5).abs() < 0.01);
+    test_runner.check_true("next_f32_variance", (variance - 1.0 / 12.0).abs() < 0.005);
+
+    // Each face of a die comes up about a sixth of the time.
+    let mut counts = [0usize; 6];
+    let mut die_in_range = true;
+    for _ in 0..SAMPLES {
+        let face = rng.range(1..7);
+        die_in_range &= (1..7).contains(&face);
+        if (1..7).contains(&face) {
+            counts[(face - 1) as usize] += 1;
+        }
+    }
+    let expected = SAMPLES / 6;
+    test_runner.check_true("range_in_bounds", die_in_range);
+    test_runner.check_true(
+        "range_uniform",
+        counts.iter().all(|&c| c.abs_diff(expected) < expected / 20),
+    );
+    test_runner.check("range_empty", rng.range(5..5), 5);
+    let wide = rng.range(i32::MIN..i32::MAX);
+    test_runner.check_true("range_full_width", wide < i32::MAX);
+
+    let hits = (0..SAMPLES).filter(|_| rng.chance(0.25)).count();
+    test_runner.check_true("chance_rate", hits.abs_diff(SAMPLES / 4) < SAMPLES / 100);
+    test_runner.check_true("chance_zero", !(0..1000).any(|_| rng.chance(0.0)));
+
+    let ranged = (0..1000).all(|_| {
+        let v = rng.range_f32(-3.0, 5.0);
+        (-3.0..5.0).contains(&v)
+    });
+    test_runner.check_true("range_f32_in_range", ranged);
+    test_runner.check("range_f32_empty", rng.range_f32(2.5, 2.5), 2.5);
+    test_runner.check("below_zero", rng.below(0), 0);
+
+    let mut deck = [0u8; 52];
+    for (i, card) in deck.iter_mut().enumerate() {
+        *card = i as u8;
+    }
+    rng.shuffle(&mut deck);
+    let mut sorted = deck;
+    sorted.sort_unstable();
+    test_runner.check_true(
+        "shuffle_is_permutation",
+        sorted.iter().enumerate().all(|(i, &c)| c == i as u8),
+    );
+    test_runner.check_true("shuffle_moves_cards", sorted != deck);
+
+    let items = [10, 20, 30];
+    let picked = (0..100).all(|_| rng.pick(&items).is_some_and(|v| items.contains(v)));
+    test_runner.check_true("pick_from_items", picked);
+    test_runner.check("pick_empty", rng.pick::<u8>(&[]), None);
+
+    rand::seed_global(7);
+    let first = rand::random_u32();
+    rand::seed_global(7);
+    test_runner.check("global_reseed_repeats", rand::random_u32(), first);
+    test_runner.check("global_matches_local", first, Rng::new(7).next_u32());
+    let r = rand::random_range(-10..10);
+    test_runner.check_true("global_range", (-10..10).contains(&r));
+}
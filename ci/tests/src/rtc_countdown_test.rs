@@ -0,0 +1,42 @@
+use psp::rtc::Countdown;
+use psp::test_runner::TestRunner;
+
+pub fn test_main(test_runner: &mut TestRunner) {
+    let zero = Countdown::new(0);
+    test_runner.check_true("countdown_zero_expired", zero.is_expired());
+    test_runner.check("countdown_zero_remaining", zero.remaining_us(), 0);
+
+    let mut round = Countdown::new(10_000_000);
+    test_runner.check_true("countdown_running", !round.is_expired());
+    test_runner.check_true("countdown_not_paused", !round.is_paused());
+
+    // Time spent paused doesn't count.
+    round.pause();
+    let frozen = round.remaining_us();
+    psp::thread::sleep_ms(5);
+    test_runner.check("countdown_paused_frozen", round.remaining_us(), frozen);
+    test_runner.check_true("countdown_paused", round.is_paused());
+
+    round.resume();
+    psp::thread::sleep_ms(5);
+    test_runner.check_true("countdown_resumed", round.remaining_us() < frozen);
+    test_runner.check_true(
+        "countdown_resumed_from_frozen",
+        round.remaining_us() > frozen - 1_000_000,
+    );
+
+    let mut short = Countdown::new(1_000);
+    psp::thread::sleep_ms(5);
+    test_runner.check_true("countdown_expires", short.is_expired());
+    short.pause();
+    short.resume();
+    test_runner.check_true("countdown_stays_expired", short.is_expired());
+
+    // Extending an expired countdown counts from now.
+    short.extend(10_000_000);
+    test_runner.check_true("countdown_extend_expired", short.remaining_us() > 9_000_000);
+
+    short.pause();
+    short.restart();
+    test_runner.check("countdown_restart_paused", short.remaining_us(), 1_000);
+}
@@ -0,0 +1,120 @@
+use psp::savedata::{decode_typed, encode_typed, CodecError, SaveCodec, SaveField, SaveReader};
+use psp::test_runner::TestRunner;
+
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+#[derive(Debug, PartialEq)]
+struct Progress {
+    level: u16,
+    score: i64,
+    speed: f32,
+    name: String,
+    items: Vec<u8>,
+    best: Option<u32>,
+    unlocked: [bool; 3],
+}
+
+impl SaveCodec for Progress {
+    const VERSION: u32 = 2;
+
+    fn encode(&self, buf: &mut Vec<u8>) {
+        self.level.write(buf);
+        self.score.write(buf);
+        self.speed.write(buf);
+        self.name.write(buf);
+        self.items.write(buf);
+        self.best.write(buf);
+        self.unlocked.write(buf);
+    }
+
+    fn decode(buf: &[u8]) -> Result<Self, CodecError> {
+        let mut r = SaveReader::new(buf);
+        let progress = Self {
+            level: r.read()?,
+            score: r.read()?,
+            speed: r.read()?,
+            name: r.read()?,
+            items: r.read()?,
+            best: r.read()?,
+            unlocked: r.read()?,
+        };
+        r.finish()?;
+        Ok(progress)
+    }
+}
+
+/// Version 1 saves only had a level.
+struct Level(u16);
+
+impl SaveCodec for Level {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        self.0.write(buf);
+    }
+
+    fn decode(buf: &[u8]) -> Result<Self, CodecError> {
+        let mut r = SaveReader::new(buf);
+        let level = Self(r.read()?);
+        r.finish()?;
+        Ok(level)
+    }
+}
+
+pub fn test_main(test_runner: &mut TestRunner) {
+    let progress = Progress {
+        level: 7,
+        score: -12345,
+        speed: 1.5,
+        name: String::from("Kirby"),
+        items: vec![1, 2, 3],
+        best: Some(999),
+        unlocked: [true, false, true],
+    };
+    let data = encode_typed(&progress);
+    test_runner.check("header", &data[..8], b"PST1\x02\0\0\0".as_slice());
+    test_runner.check("roundtrip", decode_typed::<Progress>(&data), Ok(progress));
+
+    test_runner.check(
+        "truncated",
+        decode_typed::<Progress>(&data[..data.len() - 1]).err(),
+        Some(CodecError::Truncated),
+    );
+    let mut trailing = data.clone();
+    trailing.push(0);
+    test_runner.check(
+        "trailing",
+        decode_typed::<Progress>(&trailing).err(),
+        Some(CodecError::TrailingData),
+    );
+    test_runner.check(
+        "bad_magic",
+        decode_typed::<Progress>(&data[1..]).err(),
+        Some(CodecError::BadMagic),
+    );
+
+    // The default decode_version rejects other versions.
+    let old = encode_typed(&Level(3));
+    test_runner.check(
+        "old_version",
+        decode_typed::<Progress>(&old).err(),
+        Some(CodecError::UnsupportedVersion(1)),
+    );
+
+    let mut buf = Vec::new();
+    2u8.write(&mut buf);
+    test_runner.check(
+        "invalid_bool",
+        SaveReader::new(&buf).read::<bool>(),
+        Err(CodecError::Invalid),
+    );
+
+    // A corrupt length runs out of data instead of allocating.
+    let mut buf = Vec::new();
+    u32::MAX.write(&mut buf);
+    test_runner.check(
+        "huge_length",
+        SaveReader::new(&buf).read::<Vec<u32>>(),
+        Err(CodecError::Truncated),
+    );
+}
@@ -0,0 +1,50 @@
+use psp::simd::{catmull_rom, cubic_bezier, spline_tangent, SplineKind, Vec4};
+use psp::test_runner::TestRunner;
+
+fn near(a: Vec4, b: Vec4) -> bool {
+    a.0.iter()
+        .zip(b.0.iter())
+        .all(|(x, y)| (x - y).abs() < 1e-5)
+}
+
+pub fn test_main(test_runner: &mut TestRunner) {
+    let p0 = Vec4::new(0.0, 0.0, 0.0, 1.0);
+    let p1 = Vec4::new(1.0, 2.0, 0.0, 1.0);
+    let p2 = Vec4::new(3.0, 2.0, 0.0, 1.0);
+    let p3 = Vec4::new(4.0, 0.0, 0.0, 1.0);
+
+    test_runner.check("bezier_start", cubic_bezier(&p0, &p1, &p2, &p3, 0.0), p0);
+    test_runner.check("bezier_end", cubic_bezier(&p0, &p1, &p2, &p3, 1.0), p3);
+    // (p0 + 3 p1 + 3 p2 + p3) / 8
+    test_runner.check_true(
+        "bezier_mid",
+        near(
+            cubic_bezier(&p0, &p1, &p2, &p3, 0.5),
+            Vec4::new(2.0, 1.5, 0.0, 1.0),
+        ),
+    );
+
+    test_runner.check(
+        "catmull_rom_start",
+        catmull_rom(&p0, &p1, &p2, &p3, 0.0),
+        p1,
+    );
+    test_runner.check("catmull_rom_end", catmull_rom(&p0, &p1, &p2, &p3, 1.0), p2);
+
+    // Bezier tangents at the ends point along the control polygon.
+    test_runner.check_true(
+        "bezier_tangent_start",
+        near(
+            spline_tangent(SplineKind::CubicBezier, &p0, &p1, &p2, &p3, 0.0),
+            Vec4::new(3.0, 6.0, 0.0, 0.0),
+        ),
+    );
+    // Catmull-Rom tangent at p1 is (p2 - p0) / 2.
+    test_runner.check_true(
+        "catmull_rom_tangent_start",
+        near(
+            spline_tangent(SplineKind::CatmullRom, &p0, &p1, &p2, &p3, 0.0),
+            Vec4::new(1.5, 1.0, 0.0, 0.0),
+        ),
+    );
+}
@@ -0,0 +1,123 @@
+use core::f32::consts::FRAC_1_SQRT_2;
+use core::mem::size_of;
+use psp::gu_ext::{
+    morphed_vertex_type, set_bone_matrices, set_bone_matrix, set_morph_weights,
+    skinned_vertex_type, vertex_stride, weight_count, ColoredVertex, Morphed, SkinnedColoredVertex,
+    SkinnedMesh, SkinnedNormalVertex, SkinnedVertex, SkinningError, VertexFormat, WeightFormat,
+    MAX_BONES,
+};
+use psp::simd::{mat4_from_quat_translation, mat4_transform, Mat4, Vec4};
+use psp::sys::{GuPrimitive, ScePspFMatrix4, VertexType};
+use psp::test_runner::TestRunner;
+
+fn matches<V: VertexFormat>() -> bool {
+    size_of::<V>() == vertex_stride(V::VERTEX_TYPE)
+}
+
+fn near(a: Vec4, b: Vec4) -> bool {
+    a.0.iter()
+        .zip(b.0.iter())
+        .all(|(x, y)| (x - y).abs() < 1e-5)
+}
+
+pub fn test_main(test_runner: &mut TestRunner) {
+    // Two float weights, then a float position.
+    let skinned = skinned_vertex_type(VertexType::VERTEX_32BITF, 2, WeightFormat::F32);
+    test_runner.check_true(
+        "skinned_flags",
+        skinned.contains(VertexType::WEIGHT_32BITF | VertexType::WEIGHTS2),
+    );
+    test_runner.check("skinned_stride_f32", vertex_stride(skinned), 20);
+    // Four byte weights, then a 16-bit position padded to 2 bytes.
+    test_runner.check(
+        "skinned_stride_u8",
+        vertex_stride(skinned_vertex_type(
+            VertexType::VERTEX_16BIT,
+            4,
+            WeightFormat::U8,
+        )),
+        10,
+    );
+    test_runner.check(
+        "morphed_stride",
+        vertex_stride(morphed_vertex_type(VertexType::VERTEX_32BITF, 3)),
+        36,
+    );
+
+    let m = Mat4([
+        [1.0, 2.0, 3.0, 4.0],
+        [5.0, 6.0, 7.0, 8.0],
+        [9.0, 10.0, 11.0, 12.0],
+        [13.0, 14.0, 15.0, 16.0],
+    ]);
+    let ge = ScePspFMatrix4::from(m);
+    test_runner.check("fmatrix_column_x", (ge.x.x, ge.x.w), (1.0, 4.0));
+    test_runner.check("fmatrix_column_w", (ge.w.x, ge.w.w), (13.0, 16.0));
+    test_runner.check("fmatrix_round_trip", Mat4::from(ge), m);
+
+    let identity = Vec4::new(0.0, 0.0, 0.0, 1.0);
+    test_runner.check(
+        "quat_identity",
+        mat4_from_quat_translation(&identity, &Vec4::ZERO),
+        Mat4::IDENTITY,
+    );
+
+    // 90 degrees about Z, then a translation along X.
+    let rot_z = Vec4::new(0.0, 0.0, FRAC_1_SQRT_2, FRAC_1_SQRT_2);
+    let bone = mat4_from_quat_translation(&rot_z, &Vec4::new(5.0, 0.0, 0.0, 0.0));
+    test_runner.check_true(
+        "quat_rotate_translate",
+        near(
+            mat4_transform(&bone, &Vec4::new(1.0, 0.0, 0.0, 1.0)),
+            Vec4::new(5.0, 1.0, 0.0, 1.0),
+        ),
+    );
+
+    test_runner.check_true("skinned_vertex_1_layout", matches::<SkinnedVertex<1>>());
+    test_runner.check_true("skinned_vertex_8_layout", matches::<SkinnedVertex<8>>());
+    test_runner.check_true(
+        "skinned_colored_vertex_layout",
+        matches::<SkinnedColoredVertex<3>>(),
+    );
+    test_runner.check_true(
+        "skinned_normal_vertex_layout",
+        matches::<SkinnedNormalVertex<2>>(),
+    );
+    test_runner.check_true("morphed_layout", matches::<Morphed<ColoredVertex, 3>>());
+    test_runner.check(
+        "skinned_weight_count",
+        weight_count(SkinnedColoredVertex::<3>::VERTEX_TYPE),
+        3,
+    );
+    test_runner.check(
+        "unskinned_weight_count",
+        weight_count(ColoredVertex::VERTEX_TYPE),
+        0,
+    );
+
+    // Out-of-range uploads fail before reaching the GE.
+    test_runner.check(
+        "bone_index_out_of_range",
+        unsafe { set_bone_matrix(MAX_BONES as u8, &Mat4::IDENTITY) },
+        Err(SkinningError::BoneIndex(MAX_BONES)),
+    );
+    test_runner.check(
+        "too_many_bones",
+        unsafe { set_bone_matrices(&[Mat4::IDENTITY; MAX_BONES + 1]) },
+        Err(SkinningError::TooMany(MAX_BONES + 1)),
+    );
+    test_runner.check(
+        "too_many_morph_weights",
+        unsafe { set_morph_weights(&[0.0; MAX_BONES + 1]) },
+        Err(SkinningError::TooMany(MAX_BONES + 1)),
+    );
+    let mesh = SkinnedMesh::<SkinnedVertex<3>>::new(GuPrimitive::Triangles);
+    test_runner.check(
+        "mesh_too_few_bones",
+        unsafe { mesh.draw(&[Mat4::IDENTITY; 2]) },
+        Err(SkinningError::TooFewBones {
+            bones: 2,
+            weights: 3,
+        }),
+    );
+}
@@ -0,0 +1,366 @@
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use psp::sync::{ReadGuard, SpinRwLock, UpgradableReadGuard, WriteGuard};
+use psp::test_runner::TestRunner;
+use psp::thread::{self, ThreadBuilder};
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Reader iterations the writer may take to get in.
+const STARVATION_BOUND: u32 = 10;
+
+/// Lock state as seen from one thread: plain readers, an upgradable
+/// reader, and a writer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Model {
+    readers: usize,
+    upgradable: bool,
+    writer: bool,
+}
+
+/// Every state one thread can reach with up to two plain readers.
+const STATES: [Model; 7] = [
+    Model::new(0, false, false),
+    Model::new(1, false, false),
+    Model::new(2, false, false),
+    Model::new(0, true, false),
+    Model::new(1, true, false),
+    Model::new(2, true, false),
+    Model::new(0, false, true),
+];
+
+#[derive(Debug, Clone, Copy)]
+enum Op {
+    Read,
+    ReadUpgradable,
+    Write,
+    DropRead,
+    DropUpgradable,
+    DropWrite,
+    TryUpgrade,
+    Upgrade,
+}
+
+const OPS: [Op; 8] = [
+    Op::Read,
+    Op::ReadUpgradable,
+    Op::Write,
+    Op::DropRead,
+    Op::DropUpgradable,
+    Op::DropWrite,
+    Op::TryUpgrade,
+    Op::Upgrade,
+];
+
+impl Model {
+    const fn new(readers: usize, upgradable: bool, writer: bool) -> Self {
+        Self {
+            readers,
+            upgradable,
+            writer,
+        }
+    }
+
+    /// Whether `op` should succeed, or `None` if it doesn't apply here
+    /// (nothing to drop, or an upgrade that would spin forever).
+    fn apply(&mut self, op: Op) -> Option<bool> {
+        let ok = match op {
+            Op::Read => !self.writer,
+            Op::ReadUpgradable => !self.writer && !self.upgradable,
+            Op::Write => self.readers == 0 && !self.upgradable && !self.writer,
+            Op::DropRead if self.readers > 0 => true,
+            Op::DropUpgradable if self.upgradable => true,
+            Op::DropWrite if self.writer => true,
+            Op::TryUpgrade if self.upgradable => self.readers == 0,
+            Op::Upgrade if self.upgradable && self.readers == 0 => true,
+            _ => return None,
+        };
+        if ok {
+            match op {
+                Op::Read => self.readers += 1,
+                Op::ReadUpgradable => self.upgradable = true,
+                Op::Write => self.writer = true,
+                Op::DropRead => self.readers -= 1,
+                Op::DropUpgradable => self.upgradable = false,
+                Op::DropWrite => self.writer = false,
+                Op::TryUpgrade | Op::Upgrade => {
+                    self.upgradable = false;
+                    self.writer = true;
+                },
+            }
+        }
+        Some(ok)
+    }
+}
+
+/// The guards one thread holds on a lock.
+struct Held<'a> {
+    readers: Vec<ReadGuard<'a, u32>>,
+    upgradable: Option<UpgradableReadGuard<'a, u32>>,
+    writer: Option<WriteGuard<'a, u32>>,
+}
+
+impl<'a> Held<'a> {
+    /// Take the guards of `state` on a fresh `lock`.
+    fn enter(lock: &'a SpinRwLock<u32>, state: Model) -> Self {
+        Self {
+            readers: (0..state.readers).map(|_| lock.read()).collect(),
+            upgradable: state.upgradable.then(|| lock.read_upgradable()),
+            writer: state.writer.then(|| lock.write()),
+        }
+    }
+
+    /// Perform `op` on the real lock, returning whether it succeeded, or
+    /// `None` where [`Model::apply`] says it doesn't apply.
+    fn apply(&mut self, lock: &'a SpinRwLock<u32>, op: Op) -> Option<bool> {
+        Some(match op {
+            Op::Read => lock.try_read().map(|g| self.readers.push(g)).is_some(),
+            Op::ReadUpgradable => lock
+                .try_read_upgradable()
+                .map(|g| self.upgradable = Some(g))
+                .is_some(),
+            Op::Write => lock.try_write().map(|g| self.writer = Some(g)).is_some(),
+            Op::DropRead => self.readers.pop().is_some(),
+            Op::DropUpgradable => self.upgradable.take().is_some(),
+            Op::DropWrite => self.writer.take().is_some(),
+            Op::TryUpgrade => match self.upgradable.take()?.try_upgrade() {
+                Ok(w) => {
+                    self.writer = Some(w);
+                    true
+                },
+                Err(up) => {
+                    self.upgradable = Some(up);
+                    false
+                },
+            },
+            Op::Upgrade => {
+                self.writer = Some(self.upgradable.take()?.upgrade());
+                true
+            },
+        })
+    }
+}
+
+/// Run every pair of operations from every state against the model,
+/// returning the number of mismatches and a description of the first.
+fn check_transitions() -> (usize, String) {
+    let mut failures = 0;
+    let mut first = String::new();
+    for start in STATES {
+        for op1 in OPS {
+            for op2 in OPS {
+                let mut model = start;
+                let Some(expected1) = model.apply(op1) else {
+                    continue;
+                };
+                let expected2 = model.apply(op2);
+                if expected2.is_none() {
+                    continue;
+                }
+
+                let lock = SpinRwLock::new(0u32);
+                let mut held = Held::enter(&lock, start);
+                let got1 = held.apply(&lock, op1);
+                let got2 = held.apply(&lock, op2);
+                if got1 != Some(expected1) || got2 != expected2 {
+                    if failures == 0 {
+                        first = format!(
+                            "{:?} then {:?} from {:?}: got {:?}, {:?}",
+                            op1, op2, start, got1, got2
+                        );
+                    }
+                    failures += 1;
+                }
+                drop(held);
+                // Every guard gone leaves the lock free.
+                if lock.try_write().is_none() {
+                    if failures == 0 {
+                        first = format!("{:?} then {:?} from {:?}: left locked", op1, op2, start);
+                    }
+                    failures += 1;
+                }
+            }
+        }
+    }
+    (failures, first)
+}
+
+/// Sleep until `flag` is set, for at most `ms` milliseconds.
+fn wait_for(flag: &AtomicBool, ms: u32) -> bool {
+    for _ in 0..ms {
+        if flag.load(Ordering::Acquire) {
+            return true;
+        }
+        thread::sleep_ms(1);
+    }
+    flag.load(Ordering::Acquire)
+}
+
+pub fn test_main(test_runner: &mut TestRunner) {
+    let lock = SpinRwLock::new(0u32);
+
+    // Readers share; writers are shut out.
+    let r1 = lock.try_read();
+    let r2 = lock.try_read();
+    test_runner.check("rw_two_readers", r1.is_some() && r2.is_some(), true);
+    test_runner.check(
+        "rw_write_blocked_by_readers",
+        lock.try_write().is_none(),
+        true,
+    );
+
+    // One upgradable reader joins the plain readers.
+    let up = lock.try_read_upgradable();
+    test_runner.check("rw_upgradable_with_readers", up.is_some(), true);
+    test_runner.check(
+        "rw_one_upgradable",
+        lock.try_read_upgradable().is_none(),
+        true,
+    );
+    test_runner.check("rw_read_beside_upgradable", lock.try_read().is_some(), true);
+    test_runner.check(
+        "rw_write_blocked_by_upgradable",
+        lock.try_write().is_none(),
+        true,
+    );
+
+    // Upgrading waits for the plain readers.
+    let up = match up.unwrap().try_upgrade() {
+        Ok(_) => {
+            test_runner.check("rw_upgrade_waits_for_readers", false, true);
+            return;
+        },
+        Err(up) => up,
+    };
+    drop(r1);
+    drop(r2);
+    {
+        let mut w = up.upgrade();
+        *w += 1;
+        test_runner.check("rw_read_blocked_by_writer", lock.try_read().is_none(), true);
+        test_runner.check(
+            "rw_upgradable_blocked_by_writer",
+            lock.try_read_upgradable().is_none(),
+            true,
+        );
+        test_runner.check(
+            "rw_write_blocked_by_writer",
+            lock.try_write().is_none(),
+            true,
+        );
+    }
+    test_runner.check("rw_upgrade_wrote", *lock.read(), 1);
+
+    // Dropping an upgradable guard without upgrading releases it.
+    drop(lock.read_upgradable());
+    let w = lock.try_write();
+    test_runner.check("rw_released", w.is_some(), true);
+    drop(w);
+    let up = lock.read_upgradable();
+    test_runner.check("rw_try_upgrade_alone", up.try_upgrade().is_ok(), true);
+    test_runner.check("rw_unlocked", lock.try_write().is_some(), true);
+
+    // Starvation regression: this thread keeps a read lock held almost
+    // all the time, re-taking it as soon as it lets go. A lower-priority
+    // writer only runs while the reader sleeps; it must still get in.
+    let lock = SpinRwLock::new(0u32);
+    let written = AtomicBool::new(false);
+    let mut iterations = 0;
+    thread::scope(|s| {
+        ThreadBuilder::new(b"rw_writer\0")
+            .priority(48)
+            .spawn_scoped(s, || {
+                *lock.write() += 1;
+                written.store(true, Ordering::Release);
+                0
+            })
+            .unwrap();
+
+        while !written.load(Ordering::Acquire) && iterations < STARVATION_BOUND * 10 {
+            iterations += 1;
+            match lock.try_read() {
+                Some(guard) => {
+                    thread::sleep_ms(1);
+                    drop(guard);
+                },
+                // A writer is waiting; step aside.
+                None => thread::sleep_ms(1),
+            }
+        }
+    })
+    .unwrap();
+    test_runner.check_true("rw_writer_not_starved", iterations <= STARVATION_BOUND);
+    test_runner.check("rw_writer_wrote", *lock.read(), 1);
+
+    // Every single-thread transition matches the model.
+    let (failures, first) = check_transitions();
+    if failures > 0 {
+        test_runner.dbg("rw_transitions", &first);
+    }
+    test_runner.check("rw_transitions", failures, 0);
+
+    // Writer preference: once a lower-priority writer is waiting behind a
+    // reader, new plain and upgradable readers are turned away, and it gets
+    // in as soon as the reader leaves.
+    let lock = SpinRwLock::new(0u32);
+    let done = AtomicBool::new(false);
+    thread::scope(|s| {
+        let reader = lock.read();
+        ThreadBuilder::new(b"rw_waiter\0")
+            .priority(48)
+            .spawn_scoped(s, || {
+                *lock.write() += 1;
+                done.store(true, Ordering::Release);
+                0
+            })
+            .unwrap();
+        // Let the writer run until it is spinning.
+        thread::sleep_ms(2);
+        test_runner.check_true("rw_waiting_blocks_read", lock.try_read().is_none());
+        test_runner.check_true(
+            "rw_waiting_blocks_upgradable",
+            lock.try_read_upgradable().is_none(),
+        );
+        test_runner.check_true("rw_waiting_blocks_write", lock.try_write().is_none());
+        test_runner.check_true("rw_waiting_writer_held_off", !done.load(Ordering::Acquire));
+        drop(reader);
+        test_runner.check_true("rw_waiting_writer_runs", wait_for(&done, 100));
+    })
+    .unwrap();
+    test_runner.check("rw_waiting_writer_wrote", *lock.read(), 1);
+    test_runner.check_true("rw_waiting_released", lock.try_write().is_some());
+
+    // An upgradable reader that was in first upgrades ahead of a waiting
+    // writer, and plain readers stay out meanwhile.
+    let lock = SpinRwLock::new(0u32);
+    let done = AtomicBool::new(false);
+    thread::scope(|s| {
+        let up = lock.read_upgradable();
+        ThreadBuilder::new(b"rw_waiter\0")
+            .priority(48)
+            .spawn_scoped(s, || {
+                let mut w = lock.write();
+                *w = *w * 10 + 2;
+                done.store(true, Ordering::Release);
+                0
+            })
+            .unwrap();
+        thread::sleep_ms(2);
+        test_runner.check_true(
+            "rw_upgradable_waiting_blocks_read",
+            lock.try_read().is_none(),
+        );
+        match up.try_upgrade() {
+            Ok(mut w) => {
+                *w = *w * 10 + 1;
+                test_runner.check_true("rw_upgrade_beats_waiter", true);
+            },
+            Err(_) => test_runner.check_true("rw_upgrade_beats_waiter", false),
+        }
+        test_runner.check_true("rw_upgraded_waiter_runs", wait_for(&done, 100));
+    })
+    .unwrap();
+    test_runner.check("rw_upgrade_order", *lock.read(), 12);
+}
@@ -0,0 +1,51 @@
+use core::cell::Cell;
+use psp::task::{next_frame, oneshot, wait_frames, Executor, RecvError};
+use psp::test_runner::TestRunner;
+
+pub fn test_main(test_runner: &mut TestRunner) {
+    let steps = Cell::new(0);
+    let received = Cell::new(None);
+    let mut executor = Executor::new();
+    executor.spawn(async {
+        steps.set(1);
+        next_frame().await;
+        steps.set(2);
+        wait_frames(3).await;
+        steps.set(3);
+    });
+
+    test_runner.check("task_not_polled_on_spawn", steps.get(), 0);
+    test_runner.check("task_first_poll_running", executor.run_once(), 1);
+    test_runner.check("task_runs_to_first_await", steps.get(), 1);
+    executor.run_once();
+    test_runner.check("task_next_frame", steps.get(), 2);
+    executor.run_once();
+    executor.run_once();
+    test_runner.check("task_wait_frames_pending", steps.get(), 2);
+    test_runner.check("task_wait_frames_done", executor.run_once(), 0);
+    test_runner.check("task_finished", steps.get(), 3);
+    test_runner.check_true("executor_empty", executor.is_empty());
+
+    let (tx, rx) = oneshot();
+    executor.spawn(async {
+        received.set(Some(rx.await));
+    });
+    executor.spawn(async move {
+        wait_frames(2).await;
+        tx.send(42);
+    });
+    for _ in 0..3 {
+        executor.run_once();
+    }
+    test_runner.check("oneshot_pending", received.get(), None);
+    executor.run_once();
+    test_runner.check("oneshot_received", received.get(), Some(Ok(42)));
+
+    let (tx, rx) = oneshot::<u32>();
+    drop(tx);
+    executor.spawn(async {
+        received.set(Some(rx.await));
+    });
+    executor.run_once();
+    test_runner.check("oneshot_closed", received.get(), Some(Err(RecvError)));
+}
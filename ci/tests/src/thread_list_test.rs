@@ -0,0 +1,57 @@
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use psp::test_runner::TestRunner;
+use psp::thread::{self, ThreadBuilder, ThreadStatus};
+
+static RELEASE: AtomicBool = AtomicBool::new(false);
+
+pub fn test_main(test_runner: &mut TestRunner) {
+    let handle = ThreadBuilder::new(b"list_sleeper\0")
+        .priority(40)
+        .stack_size(8 * 1024)
+        .spawn(|| {
+            while !RELEASE.load(Ordering::Acquire) {
+                thread::sleep_ms(1);
+            }
+            0
+        })
+        .unwrap();
+    // Let it reach its first sleep.
+    thread::sleep_ms(5);
+
+    let threads = thread::list().unwrap();
+    let me = thread::current_thread_id();
+    let own = threads.iter().find(|t| t.uid == me);
+    test_runner.check_true("list_has_current", own.is_some());
+    test_runner.check(
+        "current_is_running",
+        own.map(|t| t.status),
+        Some(ThreadStatus::Running),
+    );
+
+    let sleeper = threads.iter().find(|t| t.uid == handle.id());
+    test_runner.check(
+        "sleeper_name",
+        sleeper.map(|t| t.name.as_str()),
+        Some("list_sleeper"),
+    );
+    test_runner.check(
+        "sleeper_waiting",
+        sleeper.map(|t| t.status),
+        Some(ThreadStatus::Waiting),
+    );
+    test_runner.check("sleeper_priority", sleeper.map(|t| t.priority), Some(40));
+    test_runner.check_true(
+        "sleeper_stack",
+        sleeper.is_some_and(|t| t.stack_size >= 8 * 1024),
+    );
+
+    // Joining deletes the thread.
+    let sleeper_id = handle.id();
+    RELEASE.store(true, Ordering::Release);
+    handle.join().unwrap();
+    test_runner.check_true(
+        "joined_not_listed",
+        thread::list().unwrap().iter().all(|t| t.uid != sleeper_id),
+    );
+}
@@ -0,0 +1,41 @@
+use psp::test_runner::TestRunner;
+use psp::thread::{self, ThreadBuilder};
+
+/// Touch `bytes` of stack in one frame.
+#[inline(never)]
+fn use_stack(bytes: usize) -> u32 {
+    let mut buf = [0u8; 8 * 1024];
+    let n = bytes.min(buf.len());
+    for (i, b) in buf[..n].iter_mut().enumerate() {
+        *b = i as u8;
+    }
+    core::hint::black_box(&buf);
+    buf[..n].iter().map(|&b| b as u32).sum()
+}
+
+pub fn test_main(test_runner: &mut TestRunner) {
+    let handle = ThreadBuilder::new(b"stack_canary\0")
+        .stack_size(32 * 1024)
+        .stack_canary(true)
+        .spawn(|| {
+            use_stack(8 * 1024);
+            let own = thread::check_stack_headroom();
+            own.map_or(-1, |usage| usage.used as i32)
+        })
+        .unwrap();
+    let (seen_inside, usage) = handle.join_with_stack_usage().unwrap();
+    let usage = usage.unwrap();
+
+    test_runner.check_true("canary_size", usage.size >= 32 * 1024);
+    test_runner.check_true("canary_used_buffer", usage.used >= 8 * 1024);
+    test_runner.check_true("canary_headroom", usage.headroom() >= 16 * 1024);
+    test_runner.check("canary_guard_intact", usage.guard_intact, true);
+    test_runner.check_true("canary_seen_inside", seen_inside >= 8 * 1024);
+
+    // Without the canary there is nothing to measure.
+    let handle = thread::spawn(b"no_canary\0", || {
+        thread::check_stack_headroom().is_none() as i32
+    })
+    .unwrap();
+    test_runner.check("unpainted_none", handle.join(), Ok(1));
+}
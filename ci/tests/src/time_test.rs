@@ -0,0 +1,31 @@
+use psp::test_runner::TestRunner;
+use psp::time::{Cooldown, Duration, Stopwatch, Timeout};
+
+pub fn test_main(test_runner: &mut TestRunner) {
+    let sw = Stopwatch::start();
+    psp::thread::sleep_ms(5);
+    test_runner.check_true("stopwatch_elapsed", sw.elapsed_us() >= 5_000);
+
+    let mut cooldown = Cooldown::new(1000);
+    test_runner.check_true("cooldown_ready_initially", cooldown.is_ready());
+    test_runner.check_true("cooldown_first_trigger", cooldown.trigger());
+    test_runner.check_true("cooldown_rearmed", !cooldown.trigger());
+    test_runner.check_true("cooldown_remaining", cooldown.remaining() > Duration::ZERO);
+    cooldown.reset();
+    test_runner.check_true("cooldown_reset", cooldown.trigger());
+
+    let expired = Timeout::from_millis(0);
+    test_runner.check_true("timeout_zero_expired", expired.expired());
+    test_runner.check(
+        "timeout_zero_remaining",
+        expired.remaining(),
+        Duration::ZERO,
+    );
+
+    let pending = Timeout::from_millis(10_000);
+    test_runner.check_true("timeout_pending", !pending.expired());
+    test_runner.check_true(
+        "timeout_remaining",
+        pending.remaining() > Duration::from_millis(9_000),
+    );
+}
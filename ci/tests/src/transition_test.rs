@@ -0,0 +1,55 @@
+use psp::gu_ext::{Transition, TransitionKind, TransitionState, WipeDirection};
+use psp::test_runner::TestRunner;
+
+pub fn test_main(test_runner: &mut TestRunner) {
+    let mut fade = Transition::fade_out(0xFF00_0000, 4);
+    test_runner.check("transition_start_progress", fade.progress(), 0.0);
+    test_runner.check(
+        "transition_start_state",
+        fade.state(),
+        TransitionState::Running(0.0),
+    );
+    test_runner.check_true("transition_start_not_finished", !fade.is_finished());
+
+    // ease_in_out_quad(0.5) is exactly 0.5.
+    fade.update();
+    test_runner.check(
+        "transition_half",
+        fade.update(),
+        TransitionState::Running(0.5),
+    );
+    fade.update();
+    test_runner.check(
+        "transition_finished",
+        fade.update(),
+        TransitionState::Finished,
+    );
+    test_runner.check_true("transition_is_finished", fade.is_finished());
+    test_runner.check("transition_fade_out_covered", fade.coverage(), 1.0);
+
+    // Updating past the end stays finished and fully covered.
+    fade.update();
+    test_runner.check("transition_past_end", fade.progress(), 1.0);
+
+    let mut fade_in = Transition::fade_in(0xFF00_0000, 2);
+    test_runner.check("transition_fade_in_start", fade_in.coverage(), 1.0);
+    fade_in.update();
+    fade_in.update();
+    test_runner.check("transition_fade_in_end", fade_in.coverage(), 0.0);
+
+    let wipe = Transition::wipe_out(WipeDirection::LeftToRight, 0xFF00_00FF, 10);
+    test_runner.check(
+        "transition_wipe_kind",
+        wipe.kind(),
+        TransitionKind::WipeOut(WipeDirection::LeftToRight),
+    );
+    test_runner.check_true("transition_wipe_out_covers", wipe.kind().covers());
+    test_runner.check_true(
+        "transition_wipe_in_uncovers",
+        !TransitionKind::WipeIn(WipeDirection::TopToBottom).covers(),
+    );
+
+    let instant = Transition::fade_in(0, 0);
+    test_runner.check_true("transition_zero_duration", instant.is_finished());
+    test_runner.check("transition_zero_duration_coverage", instant.coverage(), 0.0);
+}
@@ -0,0 +1,42 @@
+use psp::sys::CtrlButtons;
+use psp::test_runner::TestRunner;
+use psp::ui::Cursor;
+
+pub fn test_main(test_runner: &mut TestRunner) {
+    let mut cursor = Cursor::new();
+    test_runner.check("starts_centered", cursor.position(), (240.0, 136.0));
+
+    // Full right deflection for half a second at the default 240 px/s.
+    cursor.feed(1.0, 0.0, CtrlButtons::empty(), 0.5);
+    test_runner.check("moves_right", cursor.position(), (360.0, 136.0));
+
+    // Inside the radial deadzone, even on a diagonal.
+    cursor.feed(0.1, 0.1, CtrlButtons::empty(), 1.0);
+    test_runner.check("deadzone_holds", cursor.position(), (360.0, 136.0));
+
+    cursor.feed(1.0, -1.0, CtrlButtons::empty(), 10.0);
+    test_runner.check("clamped", cursor.position(), (479.0, 0.0));
+
+    cursor.set_position(100.0, 100.0);
+    cursor.feed(-1.0, 1.0, CtrlButtons::empty(), 0.25);
+    let (x, y) = cursor.position();
+    test_runner.check_true(
+        "diagonal_equal_axes",
+        ((100.0 - x) - (y - 100.0)).abs() < 0.001,
+    );
+    test_runner.check_true("diagonal_not_faster", 100.0 - x < 60.0 && 100.0 - x > 40.0);
+
+    cursor.feed(0.0, 0.0, CtrlButtons::CROSS, 0.0);
+    test_runner.check("clicked_on_press", cursor.clicked(), true);
+    test_runner.check("held_on_press", cursor.held(), true);
+    cursor.feed(0.0, 0.0, CtrlButtons::CROSS, 0.0);
+    test_runner.check("not_clicked_while_held", cursor.clicked(), false);
+    cursor.feed(0.0, 0.0, CtrlButtons::empty(), 0.0);
+    test_runner.check("released", cursor.released(), true);
+
+    cursor.set_button(CtrlButtons::CIRCLE);
+    cursor.feed(0.0, 0.0, CtrlButtons::CROSS, 0.0);
+    test_runner.check("other_button_ignored", cursor.clicked(), false);
+    cursor.feed(0.0, 0.0, CtrlButtons::CIRCLE, 0.0);
+    test_runner.check("remapped_click", cursor.clicked(), true);
+}
@@ -0,0 +1,92 @@
+use core::mem::size_of;
+use psp::gu_ext::{
+    vertex_stride, ColorVertex, ColoredVertex, NormalVertex, SpriteVertex, TexturedNormalVertex,
+    TexturedVertex, VertexBuffer, VertexFormat,
+};
+use psp::sys::VertexType;
+use psp::test_runner::TestRunner;
+
+fn stride_of(bits: &[VertexType]) -> usize {
+    vertex_stride(VertexType::from_bits_truncate(
+        bits.iter().fold(0, |a, b| a | b.bits()),
+    ))
+}
+
+fn matches<V: VertexFormat>() -> bool {
+    size_of::<V>() == vertex_stride(V::VERTEX_TYPE)
+}
+
+pub fn test_main(test_runner: &mut TestRunner) {
+    test_runner.check_true("sprite_vertex_layout", matches::<SpriteVertex>());
+    test_runner.check_true("color_vertex_layout", matches::<ColorVertex>());
+    test_runner.check_true("textured_vertex_layout", matches::<TexturedVertex>());
+    test_runner.check_true("colored_vertex_layout", matches::<ColoredVertex>());
+    test_runner.check_true("normal_vertex_layout", matches::<NormalVertex>());
+    test_runner.check_true(
+        "textured_normal_vertex_layout",
+        matches::<TexturedNormalVertex>(),
+    );
+
+    test_runner.check(
+        "stride_sprite",
+        stride_of(&[
+            VertexType::TEXTURE_32BITF,
+            VertexType::COLOR_8888,
+            VertexType::VERTEX_32BITF,
+        ]),
+        24,
+    );
+    // 4 bytes of uv, 4 of color, 6 of position padded to 16.
+    test_runner.check(
+        "stride_16bit_padded",
+        stride_of(&[
+            VertexType::TEXTURE_16BIT,
+            VertexType::COLOR_8888,
+            VertexType::VERTEX_16BIT,
+        ]),
+        16,
+    );
+    test_runner.check(
+        "stride_5650",
+        stride_of(&[VertexType::COLOR_5650, VertexType::VERTEX_16BIT]),
+        8,
+    );
+    test_runner.check(
+        "stride_8bit",
+        stride_of(&[VertexType::TEXTURE_8BIT, VertexType::VERTEX_8BIT]),
+        5,
+    );
+    test_runner.check(
+        "stride_weights",
+        stride_of(&[
+            VertexType::WEIGHT_32BITF,
+            VertexType::WEIGHTS2,
+            VertexType::VERTEX_32BITF,
+        ]),
+        20,
+    );
+    test_runner.check(
+        "stride_morph",
+        stride_of(&[VertexType::VERTEX_32BITF, VertexType::VERTICES2]),
+        24,
+    );
+    test_runner.check(
+        "stride_no_position",
+        stride_of(&[VertexType::COLOR_8888]),
+        0,
+    );
+
+    let mut buffer = VertexBuffer::<ColoredVertex>::new();
+    test_runner.check_true("buffer_starts_empty", buffer.is_empty());
+    buffer.push(ColoredVertex {
+        color: 0xFFFF_FFFF,
+        x: 1.0,
+        y: 2.0,
+        z: 3.0,
+    });
+    buffer.extend_from_slice(&[ColoredVertex::default(); 2]);
+    test_runner.check("buffer_len", buffer.len(), 3);
+    test_runner.check("buffer_first_x", buffer.as_slice()[0].x, 1.0);
+    buffer.clear();
+    test_runner.check_true("buffer_cleared", buffer.is_empty());
+}
@@ -0,0 +1,106 @@
+//! Show `host0:/background.bmp` and reload it whenever it changes on the
+//! host.
+//!
+//! Run through psplink, then overwrite `background.bmp` in the directory
+//! psplink serves as `host0:`. The screen turns dark red while the file is
+//! missing or can't be decoded.
+
+#![no_std]
+#![no_main]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use psp::framebuffer::PixelSurface;
+use psp::image::{DecodedImage, PixelFormat};
+use psp::io::{Change, Watcher};
+use psp::sys::{self, DisplayPixelFormat};
+use psp::{BUF_WIDTH, SCREEN_HEIGHT, SCREEN_WIDTH};
+
+psp::module!("asset_reload_example", 1, 1);
+
+const PATH: &str = "host0:/background.bmp";
+const BACKDROP: u32 = 0xff10_1010;
+const MISSING: u32 = 0xff00_0060;
+
+/// Pixels of `image` as `0xAABBGGRR` words.
+fn to_pixels(image: &DecodedImage) -> Vec<u32> {
+    match image.format {
+        PixelFormat::Rgba8888 => image
+            .data
+            .chunks_exact(4)
+            .map(|p| u32::from_le_bytes([p[0], p[1], p[2], p[3]]))
+            .collect(),
+        PixelFormat::Rgb888 => image
+            .data
+            .chunks_exact(3)
+            .map(|p| u32::from_le_bytes([p[0], p[1], p[2], 0xff]))
+            .collect(),
+    }
+}
+
+/// Load the background and draw it centered, or fill the screen with
+/// [`MISSING`] if it can't be loaded.
+fn show(screen: &mut PixelSurface<'_>) {
+    let image = match psp::image::load(PATH) {
+        Ok(image) => image,
+        Err(_) => {
+            screen.clear(MISSING);
+            return;
+        },
+    };
+    let mut pixels = to_pixels(&image);
+    let src = PixelSurface::from_slice(
+        &mut pixels,
+        image.width,
+        image.height,
+        image.width,
+        DisplayPixelFormat::Psm8888,
+    );
+    let x = (SCREEN_WIDTH as i32 - image.width as i32) / 2;
+    let y = (SCREEN_HEIGHT as i32 - image.height as i32) / 2;
+    screen.clear(BACKDROP);
+    screen.blit(&src, 0, 0, x, y, image.width, image.height);
+}
+
+fn psp_main() {
+    psp::callback::setup_exit_callback().unwrap();
+
+    let mut screen = unsafe {
+        sys::sceDisplaySetMode(
+            sys::DisplayMode::Lcd,
+            SCREEN_WIDTH as usize,
+            SCREEN_HEIGHT as usize,
+        );
+        // Cache-through address
+        let vram = (0x4000_0000u32 | sys::sceGeEdramGetAddr() as u32) as *mut u8;
+        sys::sceDisplaySetFrameBuf(
+            vram,
+            BUF_WIDTH as usize,
+            DisplayPixelFormat::Psm8888,
+            sys::DisplaySetBufSync::NextFrame,
+        );
+        PixelSurface::from_raw(
+            vram,
+            SCREEN_WIDTH,
+            SCREEN_HEIGHT,
+            BUF_WIDTH,
+            DisplayPixelFormat::Psm8888,
+        )
+    };
+
+    let mut watcher = Watcher::new();
+    watcher.watch(PATH).unwrap();
+    show(&mut screen);
+
+    loop {
+        // Stats the file at most every 500 ms, so this is cheap per frame.
+        if let Some(changed) = watcher.poll().last() {
+            match changed.change {
+                Change::Removed => screen.clear(MISSING),
+                Change::Modified | Change::Added => show(&mut screen),
+            }
+        }
+        unsafe { sys::sceDisplayWaitVblankStart() };
+    }
+}
@@ -1,9 +1,13 @@
+//! Play a 440 Hz tone, pausing and resuming from the headphone remote's
+//! play/pause button.
+
 #![no_std]
 #![no_main]
 
 use core::f32::consts::PI;
 
 use psp::audio::{AudioChannel, AudioFormat};
+use psp::hprm::{HprmButtons, Remote};
 use psp::sys::AUDIO_VOLUME_MAX;
 
 psp::module!("audio_tone_example", 1, 1);
@@ -31,13 +35,44 @@ fn psp_main() {
         channel.channel_id()
     );
 
+    // Models without a remote connector just play straight through.
+    let mut remote = match Remote::new() {
+        Ok(remote) => {
+            psp::dprintln!("Press play/pause on the headphone remote to pause");
+            Some(remote)
+        },
+        Err(e) => {
+            psp::dprintln!("No remote: {}", e);
+            None
+        },
+    };
+
     let aligned_count = channel.sample_count() as usize;
     let mut buf = [0i16; 2048]; // stereo pairs: 1024 * 2
+    let silence = [0i16; 2048];
     let mut phase: f32 = 0.0;
     let phase_inc = 2.0 * PI * TONE_HZ / SAMPLE_RATE;
     let total_buffers = (SAMPLE_RATE as u32 * PLAY_SECONDS) / aligned_count as u32;
+    let mut played = 0;
+    let mut paused = false;
+
+    while played < total_buffers {
+        if let Some(remote) = &mut remote {
+            remote.update();
+            if remote.is_pressed(HprmButtons::PLAY_PAUSE) {
+                paused = !paused;
+                psp::dprintln!("{}", if paused { "Paused" } else { "Resumed" });
+            }
+        }
+        if paused {
+            // Keep the channel fed so playback resumes without a gap.
+            if let Err(e) = channel.output_blocking(0, &silence) {
+                psp::dprintln!("Audio output error: {:?}", e);
+                return;
+            }
+            continue;
+        }
 
-    for _ in 0..total_buffers {
         for i in 0..aligned_count {
             let sample = unsafe { (psp::math::sinf(phase) * 16000.0) as i16 };
             buf[i * 2] = sample; // left
@@ -52,6 +87,7 @@ fn psp_main() {
             psp::dprintln!("Audio output error: {:?}", e);
             return;
         }
+        played += 1;
     }
 
     // Channel is released on drop
@@ -0,0 +1,33 @@
+//! Button remapper plugin that swaps Cross and Circle in every game.
+//!
+//! Build the PRX, copy it to `ms0:/seplugins/` and add a line such as
+//! `ms0:/seplugins/button_remap.prx 1` to `game.txt` (or `PLUGINS.TXT` on
+//! ARK-4). Requires custom firmware; in PPSSPP the hooks fail to install
+//! and the plugin does nothing.
+//!
+//! To test: start any game whose menus confirm with Cross (most western
+//! releases) and check that Circle now confirms and Cross cancels.
+
+#![no_std]
+#![no_main]
+
+use psp::input::kernel::intercept_buttons;
+use psp::sys::{CtrlButtons, SceCtrlData};
+
+psp::module_kernel!("ButtonRemap", 1, 0);
+
+fn swap_cross_circle(pad: &mut SceCtrlData) {
+    let cross = pad.buttons.contains(CtrlButtons::CROSS);
+    let circle = pad.buttons.contains(CtrlButtons::CIRCLE);
+    pad.buttons.set(CtrlButtons::CROSS, circle);
+    pad.buttons.set(CtrlButtons::CIRCLE, cross);
+}
+
+fn psp_main() {
+    // The hooks live on after `psp_main` returns, for as long as the
+    // plugin stays loaded.
+    // SAFETY: This is a kernel-mode module.
+    if let Err(e) = unsafe { intercept_buttons(swap_cross_circle) } {
+        psp::dprintln!("button-remap: {}", e);
+    }
+}
@@ -0,0 +1,111 @@
+//! Compare 1000 random 64-byte reads through `File` and `CachedFile`.
+
+#![no_std]
+#![no_main]
+
+use psp::io::{CachedFile, File, ReadAt};
+use psp::sys::IoOpenFlags;
+use psp::timer::VTimer;
+
+psp::module!("cached_io_example", 1, 1);
+
+const PATH: &str = "ms0:/cached_io_test.bin";
+const FILE_SIZE: usize = 2 * 1024 * 1024;
+const READS: usize = 1000;
+const READ_SIZE: usize = 64;
+/// Reads cluster in a window this big, like records near their index.
+const WINDOW: u64 = 48 * 1024;
+
+/// Small xorshift PRNG so both runs see the same offsets.
+struct Rng(u32);
+
+impl Rng {
+    fn next(&mut self) -> u32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        self.0
+    }
+
+    /// Offsets within a window that moves every 100 reads.
+    fn offsets(seed: u32) -> impl Iterator<Item = u64> {
+        let mut rng = Rng(seed);
+        let mut base = 0;
+        (0..READS).map(move |i| {
+            if i % 100 == 0 {
+                base = rng.next() as u64 % (FILE_SIZE as u64 - WINDOW);
+            }
+            base + rng.next() as u64 % (WINDOW - READ_SIZE as u64)
+        })
+    }
+}
+
+fn create_test_file() -> Result<(), psp::io::IoError> {
+    let file = File::create(PATH)?;
+    let mut chunk = [0u8; 4096];
+    for (i, byte) in chunk.iter_mut().enumerate() {
+        *byte = i as u8;
+    }
+    for _ in 0..FILE_SIZE / chunk.len() {
+        file.write(&chunk)?;
+    }
+    Ok(())
+}
+
+/// Run the benchmark over `reader`, returning elapsed microseconds.
+fn bench(timer: &VTimer, reader: &mut impl FnMut(u64, &mut [u8])) -> i64 {
+    let mut buf = [0u8; READ_SIZE];
+    let start = timer.time_us();
+    for offset in Rng::offsets(0x1234_5678) {
+        reader(offset, &mut buf);
+    }
+    timer.time_us() - start
+}
+
+fn psp_main() {
+    psp::callback::setup_exit_callback().unwrap();
+
+    psp::dprintln!("Writing {} KB test file...", FILE_SIZE / 1024);
+    if let Err(e) = create_test_file() {
+        psp::dprintln!("Failed to create test file: {:?}", e);
+        return;
+    }
+
+    let timer = match VTimer::new(b"cached_io\0") {
+        Ok(t) => t,
+        Err(e) => {
+            psp::dprintln!("Failed to create VTimer: {:?}", e);
+            return;
+        },
+    };
+    timer.start().unwrap();
+
+    let mut file = match File::open(PATH, IoOpenFlags::RD_ONLY) {
+        Ok(f) => f,
+        Err(e) => {
+            psp::dprintln!("Failed to open test file: {:?}", e);
+            return;
+        },
+    };
+    let uncached = bench(&timer, &mut |offset, buf| {
+        let _ = file.read_at(offset, buf);
+    });
+
+    let mut cached = CachedFile::new(file);
+    let cached_us = bench(&timer, &mut |offset, buf| {
+        let _ = cached.read_at(offset, buf);
+    });
+    let stats = cached.stats();
+
+    psp::dprintln!("{} random {}-byte reads:", READS, READ_SIZE);
+    psp::dprintln!("  File:       {} us", uncached);
+    psp::dprintln!("  CachedFile: {} us", cached_us);
+    psp::dprintln!(
+        "  hits {} / misses {} ({:.1}% hit rate)",
+        stats.hits,
+        stats.misses,
+        stats.hit_rate() * 100.0
+    );
+
+    let _ = psp::io::remove_file(PATH);
+}
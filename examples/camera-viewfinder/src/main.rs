@@ -0,0 +1,125 @@
+//! Live viewfinder for the Go!Cam camera.
+//!
+//! Each frame goes through the whole chain: the camera delivers a JPEG,
+//! the hardware decoder turns it into pixels, and the GE draws them as a
+//! full-screen texture. Plug the camera into the top port before starting.
+
+#![no_std]
+#![no_main]
+
+use core::ffi::c_void;
+
+use psp::camera::{Camera, CameraError};
+use psp::gu_ext::{BlendMode, Texture, blit_texture, clear, set_blend_mode, setup_2d};
+use psp::image::JpegDecoder;
+use psp::sys::{
+    self, ClearBuffer, DisplayPixelFormat, GuContextType, GuState, GuSyncBehavior, GuSyncMode,
+    TexturePixelFormat, UsbCamFrameRate, UsbCamResolution,
+};
+use psp::vram_alloc::get_vram_allocator;
+use psp::{BUF_WIDTH, SCREEN_HEIGHT, SCREEN_WIDTH};
+
+psp::module!("camera_viewfinder_example", 1, 1);
+
+const FRAME_PIXELS: usize = (SCREEN_WIDTH * SCREEN_HEIGHT) as usize;
+
+static mut LIST: psp::Align16<[u32; 0x40000]> = psp::Align16([0; 0x40000]);
+static mut FRAME: psp::Align16<[u32; FRAME_PIXELS]> = psp::Align16([0; FRAME_PIXELS]);
+
+/// The camera driver writes frames by DMA, so give them whole cache lines.
+#[repr(C, align(64))]
+struct JpegBuf([u8; 32 * 1024]);
+
+static mut JPEG: JpegBuf = JpegBuf([0; 32 * 1024]);
+
+fn psp_main() {
+    psp::callback::setup_exit_callback().unwrap();
+
+    let mut camera = match Camera::new(UsbCamResolution::Px480_272, UsbCamFrameRate::Fps15) {
+        Ok(camera) => camera,
+        Err(CameraError::NotConnected) => {
+            psp::dprintln!("No camera found. Plug the Go!Cam into the top port and restart.");
+            return;
+        },
+        Err(e) => {
+            psp::dprintln!("Failed to open the camera: {}", e);
+            return;
+        },
+    };
+    let mut decoder = JpegDecoder::new(SCREEN_WIDTH as i32, SCREEN_HEIGHT as i32).unwrap();
+
+    let allocator = get_vram_allocator().unwrap();
+    let fbp0 = allocator
+        .alloc_texture_pixels(BUF_WIDTH, SCREEN_HEIGHT, TexturePixelFormat::Psm8888)
+        .unwrap()
+        .as_mut_ptr_from_zero();
+    let fbp1 = allocator
+        .alloc_texture_pixels(BUF_WIDTH, SCREEN_HEIGHT, TexturePixelFormat::Psm8888)
+        .unwrap()
+        .as_mut_ptr_from_zero();
+
+    unsafe {
+        sys::sceGuInit();
+        sys::sceGuStart(GuContextType::Direct, &raw mut LIST as *mut c_void);
+        sys::sceGuDrawBuffer(DisplayPixelFormat::Psm8888, fbp0 as _, BUF_WIDTH as i32);
+        sys::sceGuDispBuffer(
+            SCREEN_WIDTH as i32,
+            SCREEN_HEIGHT as i32,
+            fbp1 as _,
+            BUF_WIDTH as i32,
+        );
+        sys::sceGuOffset(2048 - (SCREEN_WIDTH / 2), 2048 - (SCREEN_HEIGHT / 2));
+        sys::sceGuViewport(2048, 2048, SCREEN_WIDTH as i32, SCREEN_HEIGHT as i32);
+        sys::sceGuScissor(0, 0, SCREEN_WIDTH as i32, SCREEN_HEIGHT as i32);
+        sys::sceGuEnable(GuState::ScissorTest);
+        sys::sceGuFinish();
+        sys::sceGuSync(GuSyncMode::Finish, GuSyncBehavior::Wait);
+        sys::sceDisplayWaitVblankStart();
+        sys::sceGuDisplay(true);
+    }
+
+    // SAFETY: the statics are only used from this thread.
+    let (jpeg, frame) = unsafe { (&mut (*(&raw mut JPEG)).0, &mut (*(&raw mut FRAME)).0) };
+    // SAFETY: `frame` is 16-byte aligned, lives forever and is filled by
+    // the decoder straight in memory, where the GE reads it.
+    let texture = unsafe {
+        Texture::from_raw(
+            frame.as_ptr() as *const c_void,
+            SCREEN_WIDTH,
+            SCREEN_HEIGHT,
+            SCREEN_WIDTH,
+            TexturePixelFormat::Psm8888,
+        )
+    };
+
+    loop {
+        // Blocks until the next frame, which paces the loop at 15 FPS.
+        let len = match camera.capture_frame(jpeg) {
+            Ok(len) => len,
+            Err(_) => continue,
+        };
+        // The decoder writes to memory directly; drop any cached lines
+        // first so they can't be written back over the new frame.
+        unsafe {
+            sys::sceKernelDcacheWritebackInvalidateRange(
+                frame.as_ptr() as *const c_void,
+                core::mem::size_of_val(frame) as u32,
+            );
+        }
+        if decoder.decode_into(&jpeg[..len], frame).is_err() {
+            continue;
+        }
+
+        unsafe {
+            sys::sceGuStart(GuContextType::Direct, &raw mut LIST as *mut c_void);
+            clear(0xff000000, ClearBuffer::COLOR_BUFFER_BIT);
+            setup_2d();
+            set_blend_mode(BlendMode::None);
+            blit_texture(&texture, 0.0, 0.0);
+            sys::sceGuFinish();
+            sys::sceGuSync(GuSyncMode::Finish, GuSyncBehavior::Wait);
+            sys::sceDisplayWaitVblankStart();
+            sys::sceGuSwapBuffers();
+        }
+    }
+}
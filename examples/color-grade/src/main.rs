@@ -0,0 +1,104 @@
+//! Cycle color grading presets over a test scene with the D-pad.
+//!
+//! Left and right switch between the presets. The scene is redrawn and
+//! graded on the CPU only when the preset changes, since grading every
+//! pixel takes a good part of a frame.
+
+#![no_std]
+#![no_main]
+
+use psp::framebuffer::PixelSurface;
+use psp::gu_ext::ColorGrade;
+use psp::input::Controller;
+use psp::sys::{self, CtrlButtons, DisplayPixelFormat};
+use psp::{BUF_WIDTH, SCREEN_HEIGHT, SCREEN_WIDTH};
+
+psp::module!("color_grade_example", 1, 1);
+
+const PRESETS: [fn() -> ColorGrade; 5] = [
+    ColorGrade::new,
+    ColorGrade::sepia,
+    ColorGrade::night,
+    ColorGrade::underwater,
+    || ColorGrade::new().contrast(1.3).saturation(1.5).gamma(1.1),
+];
+
+/// A fully saturated `0xAABBGGRR` color for `hue` in 0..1536.
+fn hue_color(hue: u32) -> u32 {
+    let x = hue % 256;
+    let (r, g, b) = match hue / 256 {
+        0 => (255, x, 0),
+        1 => (255 - x, 255, 0),
+        2 => (0, 255, x),
+        3 => (0, 255 - x, 255),
+        4 => (x, 0, 255),
+        _ => (255, 0, 255 - x),
+    };
+    0xff00_0000 | r | g << 8 | b << 16
+}
+
+/// Hue bands fading to black at the top, over a gray ramp at the bottom.
+fn draw_scene(screen: &mut PixelSurface<'_>) {
+    let (width, height) = (screen.width(), screen.height());
+    let bands = height * 3 / 4;
+    for x in 0..width {
+        let color = hue_color(x * 1536 / width);
+        for y in 0..bands {
+            let shade = y * 255 / bands;
+            let [r, g, b, _] = color.to_le_bytes().map(|c| c as u32 * shade / 255);
+            screen.set_pixel(x as i32, y as i32, 0xff00_0000 | r | g << 8 | b << 16);
+        }
+        let gray = x * 255 / width;
+        let gray = 0xff00_0000 | gray * 0x0001_0101;
+        screen.fill_rect(x as i32, bands as i32, 1, height - bands, gray);
+    }
+}
+
+fn psp_main() {
+    psp::callback::setup_exit_callback().unwrap();
+
+    let mut screen = unsafe {
+        sys::sceDisplaySetMode(
+            sys::DisplayMode::Lcd,
+            SCREEN_WIDTH as usize,
+            SCREEN_HEIGHT as usize,
+        );
+        // Cache-through address
+        let vram = (0x4000_0000u32 | sys::sceGeEdramGetAddr() as u32) as *mut u8;
+        sys::sceDisplaySetFrameBuf(
+            vram,
+            BUF_WIDTH as usize,
+            DisplayPixelFormat::Psm8888,
+            sys::DisplaySetBufSync::NextFrame,
+        );
+        PixelSurface::from_raw(
+            vram,
+            SCREEN_WIDTH,
+            SCREEN_HEIGHT,
+            BUF_WIDTH,
+            DisplayPixelFormat::Psm8888,
+        )
+    };
+
+    let mut ctrl = Controller::new();
+    let mut preset = 0;
+    let mut dirty = true;
+
+    loop {
+        ctrl.update();
+        if ctrl.is_pressed(CtrlButtons::RIGHT) {
+            preset = (preset + 1) % PRESETS.len();
+            dirty = true;
+        }
+        if ctrl.is_pressed(CtrlButtons::LEFT) {
+            preset = (preset + PRESETS.len() - 1) % PRESETS.len();
+            dirty = true;
+        }
+        if dirty {
+            draw_scene(&mut screen);
+            PRESETS[preset]().apply_cpu(&mut screen);
+            dirty = false;
+        }
+        unsafe { sys::sceDisplayWaitVblankStart() };
+    }
+}
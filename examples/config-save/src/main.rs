@@ -1,51 +1,73 @@
-//! Save and load key-value settings using the Config module.
+//! Save and load key-value settings using the Config module, checked
+//! against a schema that upgrades files written by older releases.
 
 #![no_std]
 #![no_main]
 
-use psp::config::{Config, ConfigValue};
+use psp::config::{Config, ConfigSchema, ConfigValue, Kind, Rule};
 
 psp::module!("config_save_example", 1, 1);
 
+/// The settings this release expects. Release 1 stored the volume under
+/// "vol"; the migration renames it.
+fn schema() -> ConfigSchema {
+    ConfigSchema::new(2)
+        .key("fullscreen", Kind::Bool, Rule::Any, true)
+        .key("volume", Kind::I32, Rule::Range(0..=100), 80)
+        .key("gamma", Kind::F32, Rule::FloatRange(0.5..=2.0), 1.0f32)
+        .key("player_name", Kind::Str, Rule::MaxLen(16), "PSP_User")
+        .migration(1, |cfg| {
+            if let Some(v) = cfg.remove("vol") {
+                cfg.set("volume", v);
+            }
+        })
+}
+
 fn psp_main() {
     psp::callback::setup_exit_callback().unwrap();
 
-    // Create a config and populate it.
-    let mut cfg = Config::new();
-    cfg.set("fullscreen", ConfigValue::Bool(true));
-    cfg.set("volume", ConfigValue::I32(80));
-    cfg.set("gamma", ConfigValue::F32(1.2));
-    cfg.set("player_name", ConfigValue::Str("PSP_User".into()));
+    let schema = schema();
 
-    psp::dprintln!("Created config with {} entries", cfg.len());
+    // Write a config the way release 1 did: old key name, an
+    // out-of-range gamma, and no player name.
+    let mut old = Config::new();
+    old.set_app_version(1);
+    old.set("fullscreen", ConfigValue::Bool(false));
+    old.set("vol", ConfigValue::I32(40));
+    old.set("gamma", ConfigValue::F32(9.0));
 
-    // Save to file.
     let path = "host0:/test_config.rcfg";
-    match cfg.save(path) {
-        Ok(()) => psp::dprintln!("Saved config to {}", path),
+    match old.save(path) {
+        Ok(()) => psp::dprintln!("Saved release 1 config to {}", path),
         Err(e) => {
             psp::dprintln!("Failed to save: {:?}", e);
             return;
         },
     }
 
-    // Load it back.
-    let loaded = match Config::load(path) {
-        Ok(c) => c,
+    // Load it back through the schema: migrate, then fill in defaults.
+    let (cfg, fixed) = match Config::load_with_schema(path, &schema) {
+        Ok(loaded) => loaded,
         Err(e) => {
             psp::dprintln!("Failed to load: {:?}", e);
             return;
         },
     };
-
-    psp::dprintln!("Loaded {} entries:", loaded.len());
-    if let Some(v) = loaded.get_bool("fullscreen") {
-        psp::dprintln!("  fullscreen = {}", v);
+    for violation in &fixed {
+        psp::dprintln!("  fixed: {}", violation);
     }
-    if let Some(v) = loaded.get_i32("volume") {
-        psp::dprintln!("  volume = {}", v);
+
+    psp::dprintln!(
+        "Loaded {} entries (release {:?}):",
+        cfg.len(),
+        cfg.app_version()
+    );
+    for (key, value) in cfg.iter() {
+        psp::dprintln!("  {} = {:?}", key, value);
     }
-    if let Some(v) = loaded.get_str("player_name") {
-        psp::dprintln!("  player_name = {}", v);
+
+    // Save the upgraded config so the migration only runs once.
+    if let Err(e) = cfg.save(path) {
+        psp::dprintln!("Failed to save: {:?}", e);
     }
 }
@@ -0,0 +1,162 @@
+//! A scripted cutscene written as async functions on `psp::task`.
+//!
+//! The hero walks across the screen, pauses, speaks two lines of dialog
+//! (each dismissed with Cross), then signals a second character to walk
+//! in over a oneshot channel. The whole script is `cutscene()` below;
+//! the game loop only updates the controller, runs the executor and
+//! draws.
+
+#![no_std]
+#![no_main]
+
+use core::cell::{Cell, RefCell};
+use core::ffi::c_void;
+
+use psp::gu_ext::{draw_rect_filled, setup_2d};
+use psp::input::Controller;
+use psp::sys::{
+    self, ClearBuffer, CtrlButtons, DisplayPixelFormat, GuContextType, GuState, GuSyncBehavior,
+    GuSyncMode, TexturePixelFormat,
+};
+use psp::task::{
+    Executor, Receiver, Sender, next_frame, oneshot, wait_button, wait_frames, wait_ms,
+};
+use psp::vram_alloc::get_vram_allocator;
+use psp::{BUF_WIDTH, SCREEN_HEIGHT, SCREEN_WIDTH};
+
+psp::module!("cutscene_example", 1, 1);
+
+static mut LIST: psp::Align16<[u32; 0x40000]> = psp::Align16([0; 0x40000]);
+
+const GROUND: f32 = 200.0;
+const WALK_SPEED: f32 = 2.0;
+
+/// State shared between the script and the renderer.
+struct Stage {
+    hero: Cell<f32>,
+    friend: Cell<f32>,
+    friend_visible: Cell<bool>,
+    dialog: Cell<Option<&'static [u8]>>,
+}
+
+/// Move `actor` towards `target` by `WALK_SPEED` pixels per frame.
+async fn walk_to(actor: &Cell<f32>, target: f32) {
+    while actor.get() != target {
+        let x = actor.get();
+        let step = (target - x).clamp(-WALK_SPEED, WALK_SPEED);
+        actor.set(x + step);
+        next_frame().await;
+    }
+}
+
+/// Show a NUL-terminated line of dialog until Cross is pressed.
+async fn say(stage: &Stage, controller: &RefCell<Controller>, line: &'static [u8]) {
+    stage.dialog.set(Some(line));
+    wait_button(controller, CtrlButtons::CROSS).await;
+    stage.dialog.set(None);
+}
+
+async fn cutscene(stage: &Stage, controller: &RefCell<Controller>, cue: Sender<()>) {
+    walk_to(&stage.hero, 240.0).await;
+    wait_frames(60).await;
+    say(stage, controller, b"Where did everyone go?\0").await;
+    wait_ms(500).await;
+    say(stage, controller, b"Hello? Anyone?\0").await;
+    cue.send(());
+    walk_to(&stage.hero, 200.0).await;
+}
+
+async fn friend_enters(stage: &Stage, cue: Receiver<()>) {
+    if cue.await.is_err() {
+        return;
+    }
+    stage.friend_visible.set(true);
+    walk_to(&stage.friend, 280.0).await;
+}
+
+unsafe fn draw(stage: &Stage) {
+    unsafe {
+        draw_rect_filled(0.0, GROUND, SCREEN_WIDTH as f32, 72.0, 0xff20_6020);
+        draw_rect_filled(
+            stage.hero.get() - 8.0,
+            GROUND - 32.0,
+            16.0,
+            32.0,
+            0xff20_20e0,
+        );
+        if stage.friend_visible.get() {
+            let x = stage.friend.get();
+            draw_rect_filled(x - 8.0, GROUND - 32.0, 16.0, 32.0, 0xffe0_8020);
+        }
+        if let Some(line) = stage.dialog.get() {
+            draw_rect_filled(20.0, 20.0, 440.0, 48.0, 0xc000_0000);
+            sys::sceGuDebugPrint(32, 32, 0xffff_ffff, line.as_ptr());
+            sys::sceGuDebugPrint(32, 48, 0xff80_8080, b"(X)\0".as_ptr());
+            sys::sceGuDebugFlush();
+        }
+    }
+}
+
+fn psp_main() {
+    psp::callback::setup_exit_callback().unwrap();
+
+    let allocator = get_vram_allocator().unwrap();
+    let fbp0 = allocator
+        .alloc_texture_pixels(BUF_WIDTH, SCREEN_HEIGHT, TexturePixelFormat::Psm8888)
+        .unwrap()
+        .as_mut_ptr_from_zero();
+    let fbp1 = allocator
+        .alloc_texture_pixels(BUF_WIDTH, SCREEN_HEIGHT, TexturePixelFormat::Psm8888)
+        .unwrap()
+        .as_mut_ptr_from_zero();
+
+    unsafe {
+        sys::sceGuInit();
+        sys::sceGuStart(GuContextType::Direct, &raw mut LIST as *mut c_void);
+        sys::sceGuDrawBuffer(DisplayPixelFormat::Psm8888, fbp0 as _, BUF_WIDTH as i32);
+        sys::sceGuDispBuffer(
+            SCREEN_WIDTH as i32,
+            SCREEN_HEIGHT as i32,
+            fbp1 as _,
+            BUF_WIDTH as i32,
+        );
+        sys::sceGuOffset(2048 - (SCREEN_WIDTH / 2), 2048 - (SCREEN_HEIGHT / 2));
+        sys::sceGuViewport(2048, 2048, SCREEN_WIDTH as i32, SCREEN_HEIGHT as i32);
+        sys::sceGuScissor(0, 0, SCREEN_WIDTH as i32, SCREEN_HEIGHT as i32);
+        sys::sceGuEnable(GuState::ScissorTest);
+        sys::sceGuFinish();
+        sys::sceGuSync(GuSyncMode::Finish, GuSyncBehavior::Wait);
+        sys::sceDisplayWaitVblankStart();
+        sys::sceGuDisplay(true);
+    }
+
+    let stage = Stage {
+        hero: Cell::new(40.0),
+        friend: Cell::new(SCREEN_WIDTH as f32),
+        friend_visible: Cell::new(false),
+        dialog: Cell::new(None),
+    };
+    let controller = RefCell::new(Controller::new());
+    let (cue_tx, cue_rx) = oneshot();
+
+    let mut executor = Executor::new();
+    executor.spawn(cutscene(&stage, &controller, cue_tx));
+    executor.spawn(friend_enters(&stage, cue_rx));
+
+    loop {
+        controller.borrow_mut().update();
+        executor.run_once();
+
+        unsafe {
+            sys::sceGuStart(GuContextType::Direct, &raw mut LIST as *mut c_void);
+            sys::sceGuClearColor(0xff40_2010);
+            sys::sceGuClear(ClearBuffer::COLOR_BUFFER_BIT);
+            setup_2d();
+            draw(&stage);
+            sys::sceGuFinish();
+            sys::sceGuSync(GuSyncMode::Finish, GuSyncBehavior::Wait);
+            sys::sceDisplayWaitVblankStart();
+            sys::sceGuSwapBuffers();
+        }
+    }
+}
@@ -0,0 +1,68 @@
+//! Restoring the display after suspend/resume with DoubleBuffer.
+//!
+//! Animates a bar across a double-buffered screen. A power callback
+//! records every resume, and the main loop calls `DoubleBuffer::reinit()`
+//! when one happened, then flashes the screen green for a second to show
+//! the recovery ran.
+//!
+//! PPSSPP can't simulate suspend, so test this on hardware:
+//!
+//! 1. Start the example; the blue bar should be moving.
+//! 2. Slide the power switch up briefly to suspend, wait for the power
+//!    LED to blink off, then slide it up again to resume.
+//! 3. The screen should flash green and the bar keep moving. Without the
+//!    `reinit()` call the screen stays black after resume.
+//! 4. Repeat a few times; each resume flashes once.
+
+#![no_std]
+#![no_main]
+
+use psp::framebuffer::{BUF_WIDTH, DoubleBuffer, SCREEN_HEIGHT, SCREEN_WIDTH};
+use psp::sys::DisplayPixelFormat;
+
+psp::module!("display_resume_example", 1, 1);
+
+const BACKGROUND: u32 = 0xFF20_2020;
+const BAR: u32 = 0xFFFF_8000;
+const FLASH: u32 = 0xFF00_C000;
+const BAR_WIDTH: u32 = 32;
+/// Frames to show the green flash for after a resume.
+const FLASH_FRAMES: u32 = 60;
+
+fn psp_main() {
+    psp::callback::setup_exit_callback().unwrap();
+    let _resume = psp::power::watch_resume().unwrap();
+
+    let mut db = DoubleBuffer::new(DisplayPixelFormat::Psm8888, true);
+    db.init();
+
+    let mut x = 0;
+    let mut flash = 0;
+    loop {
+        if db.needs_reinit() {
+            db.reinit();
+            flash = FLASH_FRAMES;
+        }
+
+        let background = if flash > 0 {
+            flash -= 1;
+            FLASH
+        } else {
+            BACKGROUND
+        };
+        let buf = db.draw_buffer() as *mut u32;
+        for y in 0..SCREEN_HEIGHT {
+            for col in 0..SCREEN_WIDTH {
+                let color = if col >= x && col < x + BAR_WIDTH {
+                    BAR
+                } else {
+                    background
+                };
+                unsafe { *buf.add((y * BUF_WIDTH + col) as usize) = color };
+            }
+        }
+
+        x = (x + 4) % (SCREEN_WIDTH - BAR_WIDTH);
+        db.swap();
+    }
+}
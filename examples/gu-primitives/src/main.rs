@@ -0,0 +1,103 @@
+//! Draw the analog stick position as a crosshair with a fading trail using
+//! the immediate-mode primitive helpers in `psp::gu_ext`.
+
+#![no_std]
+#![no_main]
+
+use core::ffi::c_void;
+
+use psp::gu_ext::{
+    clear, draw_circle, draw_line, draw_polyline, draw_rect_filled, draw_rect_outline, setup_2d,
+};
+use psp::input::{self, Controller};
+use psp::sys::{
+    self, ClearBuffer, DisplayPixelFormat, GuContextType, GuState, GuSyncBehavior, GuSyncMode,
+    TexturePixelFormat,
+};
+use psp::vram_alloc::get_vram_allocator;
+use psp::{BUF_WIDTH, SCREEN_HEIGHT, SCREEN_WIDTH};
+
+psp::module!("gu_primitives_example", 1, 1);
+
+static mut LIST: psp::Align16<[u32; 0x40000]> = psp::Align16([0; 0x40000]);
+
+const TRAIL_LEN: usize = 32;
+const CENTER_X: f32 = SCREEN_WIDTH as f32 / 2.0;
+const CENTER_Y: f32 = SCREEN_HEIGHT as f32 / 2.0;
+const RADIUS: f32 = 100.0;
+
+fn psp_main() {
+    psp::callback::setup_exit_callback().unwrap();
+    input::enable_analog();
+
+    let allocator = get_vram_allocator().unwrap();
+    let fbp0 = allocator
+        .alloc_texture_pixels(BUF_WIDTH, SCREEN_HEIGHT, TexturePixelFormat::Psm8888)
+        .unwrap()
+        .as_mut_ptr_from_zero();
+    let fbp1 = allocator
+        .alloc_texture_pixels(BUF_WIDTH, SCREEN_HEIGHT, TexturePixelFormat::Psm8888)
+        .unwrap()
+        .as_mut_ptr_from_zero();
+
+    unsafe {
+        sys::sceGuInit();
+        sys::sceGuStart(GuContextType::Direct, &raw mut LIST as *mut c_void);
+        sys::sceGuDrawBuffer(DisplayPixelFormat::Psm8888, fbp0 as _, BUF_WIDTH as i32);
+        sys::sceGuDispBuffer(
+            SCREEN_WIDTH as i32,
+            SCREEN_HEIGHT as i32,
+            fbp1 as _,
+            BUF_WIDTH as i32,
+        );
+        sys::sceGuOffset(2048 - (SCREEN_WIDTH / 2), 2048 - (SCREEN_HEIGHT / 2));
+        sys::sceGuViewport(2048, 2048, SCREEN_WIDTH as i32, SCREEN_HEIGHT as i32);
+        sys::sceGuScissor(0, 0, SCREEN_WIDTH as i32, SCREEN_HEIGHT as i32);
+        sys::sceGuEnable(GuState::ScissorTest);
+        sys::sceGuFinish();
+        sys::sceGuSync(GuSyncMode::Finish, GuSyncBehavior::Wait);
+        sys::sceDisplayWaitVblankStart();
+        sys::sceGuDisplay(true);
+    }
+
+    let mut ctrl = Controller::new();
+    let mut trail = [(CENTER_X, CENTER_Y); TRAIL_LEN];
+
+    loop {
+        ctrl.update();
+        let x = CENTER_X + ctrl.analog_x_f32(0.1) * RADIUS;
+        let y = CENTER_Y + ctrl.analog_y_f32(0.1) * RADIUS;
+        trail.copy_within(1.., 0);
+        trail[TRAIL_LEN - 1] = (x, y);
+
+        unsafe {
+            sys::sceGuStart(GuContextType::Direct, &raw mut LIST as *mut c_void);
+            clear(0xff202020, ClearBuffer::COLOR_BUFFER_BIT);
+
+            setup_2d();
+
+            // Stick range and deadzone.
+            draw_circle(CENTER_X, CENTER_Y, RADIUS, 48, 0xff606060);
+            draw_rect_outline(
+                CENTER_X - RADIUS,
+                CENTER_Y - RADIUS,
+                RADIUS * 2.0,
+                RADIUS * 2.0,
+                0xff404040,
+            );
+            draw_circle(CENTER_X, CENTER_Y, RADIUS * 0.1, 16, 0xff404080);
+
+            draw_polyline(&trail, 0x8000c0ff);
+
+            // Crosshair.
+            draw_line(x - 8.0, y, x + 8.0, y, 0xff00ff00);
+            draw_line(x, y - 8.0, x, y + 8.0, 0xff00ff00);
+            draw_rect_filled(x - 1.0, y - 1.0, 3.0, 3.0, 0xffffffff);
+
+            sys::sceGuFinish();
+            sys::sceGuSync(GuSyncMode::Finish, GuSyncBehavior::Wait);
+            sys::sceDisplayWaitVblankStart();
+            sys::sceGuSwapBuffers();
+        }
+    }
+}
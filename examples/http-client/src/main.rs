@@ -1,4 +1,4 @@
-//! High-level HTTP GET using psp::http::HttpClient.
+//! High-level HTTPS GET using psp::http::HttpClient.
 //!
 //! Requires a real PSP with WiFi configured in network settings slot 1.
 //! Will not work in PPSSPP emulator.
@@ -29,19 +29,19 @@ fn psp_main() {
     }
     psp::dprintln!("WiFi connected.");
 
-    // Create an HTTP client (initializes sceHttp subsystem).
-    let client = match HttpClient::new() {
+    // Create an HTTPS-capable client (initializes sceSsl/sceHttp/sceHttps).
+    let client = match HttpClient::new_https() {
         Ok(c) => c,
         Err(e) => {
-            psp::dprintln!("HttpClient::new failed: {:?}", e);
+            psp::dprintln!("HttpClient::new_https failed: {:?}", e);
             net::term();
             return;
         },
     };
 
     // Perform a GET request (URL must be null-terminated).
-    psp::dprintln!("Fetching http://example.com/ ...");
-    match client.get(b"http://example.com/\0") {
+    psp::dprintln!("Fetching https://example.com/ ...");
+    match client.get(b"https://example.com/\0") {
         Ok(resp) => {
             psp::dprintln!("Status: {}", resp.status_code);
             if let Some(len) = resp.content_length {
@@ -55,7 +55,7 @@ fn psp_main() {
         Err(e) => psp::dprintln!("GET failed: {:?}", e),
     }
 
-    // Client cleans up sceHttp on drop.
+    // Client cleans up sceHttps/sceHttp/sceSsl on drop.
     drop(client);
     net::term();
     psp::dprintln!("Done.");
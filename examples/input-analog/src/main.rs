@@ -1,37 +1,54 @@
-//! Controller input with analog deadzone normalization.
+//! Controller input through an action map, with analog deadzone
+//! normalization.
 
 #![no_std]
 #![no_main]
 
-use psp::input::{self, Controller};
+use psp::input::{self, ActionMap, Axis, Controller};
 use psp::sys::CtrlButtons;
 
 psp::module!("input_analog_example", 1, 1);
 
 const DEADZONE: f32 = 0.2;
 
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Action {
+    Confirm,
+    Exit,
+    MoveX,
+    MoveY,
+}
+
 fn psp_main() {
     psp::callback::setup_exit_callback().unwrap();
     input::enable_analog();
 
     let mut ctrl = Controller::new();
 
+    let mut actions = ActionMap::new();
+    actions.set_deadzone(DEADZONE);
+    actions.bind(Action::Confirm, CtrlButtons::CROSS);
+    actions.bind(Action::Exit, CtrlButtons::START);
+    actions.bind_analog(Action::MoveX, Axis::X, 1.0);
+    actions.bind_analog(Action::MoveY, Axis::Y, 1.0);
+
     psp::dprintln!("Move the analog stick or press CROSS. START exits.");
 
     loop {
         ctrl.update();
+        let input = ctrl.snapshot();
 
-        if ctrl.is_pressed(CtrlButtons::START) {
+        if actions.was_action_pressed(Action::Exit, &input) {
             psp::dprintln!("START pressed, exiting.");
             break;
         }
 
-        if ctrl.is_pressed(CtrlButtons::CROSS) {
+        if actions.was_action_pressed(Action::Confirm, &input) {
             psp::dprintln!("CROSS pressed!");
         }
 
-        let x = ctrl.analog_x_f32(DEADZONE);
-        let y = ctrl.analog_y_f32(DEADZONE);
+        let x = actions.action_value(Action::MoveX, &input);
+        let y = actions.action_value(Action::MoveY, &input);
 
         if x != 0.0 || y != 0.0 {
             // Scale to integer display since PSP debug print has no float formatting
@@ -18,19 +18,15 @@ fn psp_main() {
         let me_freq = psp::sys::scePowerGetMeClockFrequency();
         psp::dprintln!("ME clock: {}MHz", me_freq);
 
-        // 2. Volatile memory (extra 4MB RAM on PSP-2000+)
-        let mut addr: *mut u8 = core::ptr::null_mut();
-        let mut size: i32 = 0;
-        let ret = psp::sys::sceKernelVolatileMemLock(
-            0,
-            &mut addr as *mut _ as *mut *mut core::ffi::c_void,
-            &mut size,
-        );
-        if ret == 0 {
-            psp::dprintln!("Volatile mem: {:p}, {} bytes", addr, size);
-            psp::sys::sceKernelVolatileMemUnlock(0);
-        } else {
-            psp::dprintln!("Volatile mem lock failed: {}", ret);
+        // 2. Volatile memory (extra 4MB RAM), unlocked when `region` drops
+        match psp::volatile_mem::try_lock() {
+            Ok(region) => {
+                psp::dprintln!("Volatile mem: {:p}, {} bytes", region.base(), region.size());
+                if let Ok(buf) = region.alloc_bytes(64 * 1024, 64) {
+                    psp::dprintln!("Allocated {} bytes at {:p}", buf.len(), buf.as_ptr());
+                }
+            },
+            Err(e) => psp::dprintln!("Volatile mem lock failed: {}", e),
         }
 
         // 3. NAND flash info
@@ -52,5 +48,14 @@ fn psp_main() {
         let gpio_reg = psp::hw::Register::<u32>::new(psp::hw::GPIO_PORT_READ);
         let gpio_val2 = gpio_reg.read();
         psp::dprintln!("GPIO port (via Register): 0x{:08X}", gpio_val2);
+
+        // 6. Blink the WLAN LED through the GPIO set/clear registers
+        for _ in 0..3 {
+            psp::hw::led_set(psp::hw::Led::Wlan, true).unwrap();
+            psp::sys::sceKernelDelayThread(200_000);
+            psp::hw::led_set(psp::hw::Led::Wlan, false).unwrap();
+            psp::sys::sceKernelDelayThread(200_000);
+        }
+        psp::dprintln!("Blinked the WLAN LED");
     }
 }
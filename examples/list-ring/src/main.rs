@@ -0,0 +1,196 @@
+//! Draw thousands of sprites split across many display lists, either as
+//! `Direct` lists finished and synced one at a time or through a
+//! `psp::gu_ext::ListRing`, and show the frame time of each, taken from
+//! the GE's finish interrupt. Press CROSS to switch modes.
+
+#![no_std]
+#![no_main]
+
+extern crate alloc;
+
+use core::ffi::c_void;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use psp::gu_ext::{ListRing, SpriteBatch, setup_2d};
+use psp::input::Controller;
+use psp::rand::Rng;
+use psp::sys::{
+    self, ClearBuffer, CtrlButtons, DisplayPixelFormat, GuCallbackId, GuContextType, GuState,
+    GuSyncBehavior, GuSyncMode, TexturePixelFormat,
+};
+use psp::vram_alloc::get_vram_allocator;
+use psp::{BUF_WIDTH, SCREEN_HEIGHT, SCREEN_WIDTH};
+
+psp::module!("list_ring_example", 1, 1);
+
+static mut LIST: psp::Align16<[u32; 0x10000]> = psp::Align16([0; 0x10000]);
+
+const SPRITES: usize = 8000;
+const PER_LIST: usize = 500;
+/// Room for `PER_LIST` sprites (48 bytes each) plus state commands.
+const LIST_BYTES: usize = 32 * 1024;
+/// Frames averaged per displayed timing.
+const WINDOW: u32 = 60;
+
+/// Low 32 bits of the system time, in microseconds, when the GE last
+/// reached the FINISH command at the end of a list.
+static GE_FINISHED: AtomicU32 = AtomicU32::new(0);
+
+/// GU finish callback. Runs in interrupt context as each list ends.
+extern "C" fn on_finish(_id: i32, _arg: *mut c_void) {
+    GE_FINISHED.store(
+        unsafe { sys::sceKernelGetSystemTimeLow() },
+        Ordering::Relaxed,
+    );
+}
+
+struct Sprite {
+    x: f32,
+    y: f32,
+    dx: f32,
+    dy: f32,
+    color: u32,
+}
+
+fn psp_main() {
+    psp::callback::setup_exit_callback().unwrap();
+
+    let allocator = get_vram_allocator().unwrap();
+    let fbp0 = allocator
+        .alloc_texture_pixels(BUF_WIDTH, SCREEN_HEIGHT, TexturePixelFormat::Psm8888)
+        .unwrap()
+        .as_mut_ptr_from_zero();
+    let fbp1 = allocator
+        .alloc_texture_pixels(BUF_WIDTH, SCREEN_HEIGHT, TexturePixelFormat::Psm8888)
+        .unwrap()
+        .as_mut_ptr_from_zero();
+
+    unsafe {
+        sys::sceGuInit();
+        sys::sceGuSetCallback(GuCallbackId::Finish, Some(on_finish));
+        sys::sceGuStart(GuContextType::Direct, &raw mut LIST as *mut c_void);
+        sys::sceGuDrawBuffer(DisplayPixelFormat::Psm8888, fbp0 as _, BUF_WIDTH as i32);
+        sys::sceGuDispBuffer(
+            SCREEN_WIDTH as i32,
+            SCREEN_HEIGHT as i32,
+            fbp1 as _,
+            BUF_WIDTH as i32,
+        );
+        sys::sceGuOffset(2048 - (SCREEN_WIDTH / 2), 2048 - (SCREEN_HEIGHT / 2));
+        sys::sceGuViewport(2048, 2048, SCREEN_WIDTH as i32, SCREEN_HEIGHT as i32);
+        sys::sceGuScissor(0, 0, SCREEN_WIDTH as i32, SCREEN_HEIGHT as i32);
+        sys::sceGuEnable(GuState::ScissorTest);
+        sys::sceGuFinish();
+        sys::sceGuSync(GuSyncMode::Finish, GuSyncBehavior::Wait);
+        sys::sceDisplayWaitVblankStart();
+        sys::sceGuDisplay(true);
+    }
+
+    let mut rng = Rng::new(0x5eed);
+    let mut sprites: alloc::vec::Vec<Sprite> = (0..SPRITES)
+        .map(|_| Sprite {
+            x: rng.range_f32(0.0, SCREEN_WIDTH as f32),
+            y: rng.range_f32(0.0, SCREEN_HEIGHT as f32),
+            dx: rng.range_f32(-60.0, 60.0),
+            dy: rng.range_f32(-60.0, 60.0),
+            color: 0x8000_0000 | (rng.next_u32() & 0x00ff_ffff),
+        })
+        .collect();
+
+    let mut ring = ListRing::new(3, LIST_BYTES);
+    let mut batch = SpriteBatch::new(PER_LIST);
+    let mut ctrl = Controller::new();
+    let mut use_ring = true;
+
+    let mut frames = 0;
+    let mut total_us = 0;
+    let mut label = alloc::string::String::from("measuring...\0");
+
+    loop {
+        ctrl.update();
+        if ctrl.is_pressed(CtrlButtons::CROSS) {
+            use_ring = !use_ring;
+            frames = 0;
+            total_us = 0;
+        }
+
+        for s in &mut sprites {
+            s.x = wrap(s.x + s.dx / 60.0, SCREEN_WIDTH as f32);
+            s.y = wrap(s.y + s.dy / 60.0, SCREEN_HEIGHT as f32);
+        }
+
+        // Time from starting to record until the GE finishes the last
+        // list, as stamped by its finish interrupt rather than whenever
+        // the CPU gets round to checking. The swap below doesn't wait for
+        // vblank.
+        let frame_start = unsafe { sys::sceKernelGetSystemTimeLow() };
+        let chunks = sprites.len().div_ceil(PER_LIST);
+        for (i, chunk) in sprites.chunks(PER_LIST).enumerate() {
+            unsafe {
+                if use_ring {
+                    ring.begin();
+                } else {
+                    sys::sceGuStart(GuContextType::Direct, &raw mut LIST as *mut c_void);
+                }
+
+                if i == 0 {
+                    sys::sceGuClearColor(0xff101010);
+                    sys::sceGuClear(ClearBuffer::COLOR_BUFFER_BIT);
+                }
+                setup_2d();
+                sys::sceGuDisable(GuState::Texture2D);
+
+                batch.reserve(chunk.len());
+                for s in chunk {
+                    batch.draw_colored_rect(s.x, s.y, 4.0, 4.0, s.color);
+                }
+                batch.flush();
+
+                if i == chunks - 1 {
+                    sys::sceGuDebugPrint(8, 8, 0xffffffff, label.as_ptr());
+                    sys::sceGuDebugFlush();
+                }
+
+                if use_ring {
+                    ring.submit().unwrap();
+                } else {
+                    sys::sceGuFinish();
+                    sys::sceGuSync(GuSyncMode::Finish, GuSyncBehavior::Wait);
+                }
+            }
+        }
+        ring.sync_all();
+        total_us += GE_FINISHED
+            .load(Ordering::Relaxed)
+            .wrapping_sub(frame_start) as u64;
+        frames += 1;
+
+        if frames == WINDOW {
+            let avg_us = total_us / WINDOW as u64;
+            label = alloc::format!(
+                "{}: {} sprites in {} lists, {}.{} ms/frame (X to switch)\0",
+                if use_ring { "ring" } else { "direct" },
+                SPRITES,
+                chunks,
+                avg_us / 1000,
+                avg_us % 1000 / 100,
+            );
+            frames = 0;
+            total_us = 0;
+        }
+
+        unsafe {
+            sys::sceGuSwapBuffers();
+        }
+    }
+}
+
+fn wrap(v: f32, max: f32) -> f32 {
+    if v < 0.0 {
+        v + max
+    } else if v >= max {
+        v - max
+    } else {
+        v
+    }
+}
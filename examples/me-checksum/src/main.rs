@@ -0,0 +1,91 @@
+//! Checksum a block of data on the Media Engine and verify it on the CPU.
+//!
+//! The ME task computes an Adler-32 of its work buffer and flips every
+//! byte in place. The CPU checks both against its own computation, which
+//! shows the data crossed the CPU/ME boundary intact in each direction.
+//!
+//! Requires custom firmware.
+
+#![no_std]
+#![no_main]
+
+extern crate alloc;
+
+use psp::me::MeExecutor;
+
+psp::module_kernel!("MeChecksum", 1, 0);
+
+const DATA_LEN: usize = 64 * 1024;
+
+/// Adler-32, written without anything that could panic: the ME can't
+/// report one.
+fn adler32(data: *const u8, len: usize) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for i in 0..len {
+        a = (a + unsafe { *data.add(i) } as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+/// Runs on the ME: checksum the input, then invert it.
+unsafe extern "C" fn checksum_task(data: *mut u8, len: usize) -> i32 {
+    let sum = adler32(data, len);
+    for i in 0..len {
+        unsafe { *data.add(i) = !*data.add(i) };
+    }
+    sum as i32
+}
+
+fn psp_main() {
+    psp::callback::setup_exit_callback().unwrap();
+
+    // Deterministic pseudo-random input (xorshift32).
+    let mut input = alloc::vec![0u8; DATA_LEN];
+    let mut state = 0x2545_F491u32;
+    for byte in input.iter_mut() {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        *byte = state as u8;
+    }
+
+    let mut executor = match MeExecutor::with_buffer(4096, DATA_LEN) {
+        Ok(executor) => executor,
+        Err(e) => {
+            psp::dprintln!("MeExecutor failed: {:#010x}", e as u32);
+            return;
+        },
+    };
+
+    let job = match unsafe { executor.submit_buffer_task(checksum_task, &input) } {
+        Ok(job) => job,
+        Err(e) => {
+            psp::dprintln!("Submit failed: {}", e);
+            return;
+        },
+    };
+    let (me_sum, output) = match executor.wait_job(&job) {
+        Ok(done) => done,
+        Err(e) => {
+            psp::dprintln!("Job failed: {}", e);
+            return;
+        },
+    };
+
+    let cpu_sum = adler32(input.as_ptr(), input.len());
+    psp::dprintln!("ME  adler32: {:#010x}", me_sum as u32);
+    psp::dprintln!("CPU adler32: {:#010x}", cpu_sum);
+
+    let mismatched = input.iter().zip(output).filter(|&(i, o)| *o != !*i).count();
+    if me_sum as u32 == cpu_sum && mismatched == 0 {
+        psp::dprintln!("OK: {} bytes verified in both directions", output.len());
+    } else {
+        psp::dprintln!(
+            "FAILED: {} of {} output bytes wrong",
+            mismatched,
+            output.len()
+        );
+    }
+    executor.reset();
+}
@@ -0,0 +1,75 @@
+//! Record three seconds from the microphone and play them back.
+//!
+//! Doubles as the hardware test for `psp::audio::Recorder`: plug in the
+//! headset (PSP-2000/3000) or use the built-in microphone (PSP Go), speak
+//! while "Recording" is shown, then listen for the playback.
+
+#![no_std]
+#![no_main]
+
+extern crate alloc;
+
+use alloc::vec;
+
+use psp::audio::{AudioChannel, AudioFormat, RecordError, Recorder};
+use psp::sys::AUDIO_VOLUME_MAX;
+
+psp::module!("mic_record_example", 1, 1);
+
+const SAMPLE_RATE: u32 = 22050;
+const RECORD_SECONDS: u32 = 3;
+const SAMPLE_COUNT: i32 = 1024;
+
+fn psp_main() {
+    psp::callback::setup_exit_callback().unwrap();
+
+    let recorder = match Recorder::new(SAMPLE_RATE, 0) {
+        Ok(rec) => rec,
+        Err(RecordError::NoMicrophone) => {
+            psp::dprintln!("This console has no microphone input");
+            return;
+        },
+        Err(e) => {
+            psp::dprintln!("Failed to open microphone: {}", e);
+            return;
+        },
+    };
+
+    let mut samples = vec![0i16; (SAMPLE_RATE * RECORD_SECONDS) as usize];
+    psp::dprintln!("Recording {}s at {} Hz...", RECORD_SECONDS, SAMPLE_RATE);
+    if let Err(e) = recorder.read_blocking(&mut samples) {
+        psp::dprintln!("Recording failed: {}", e);
+        return;
+    }
+    drop(recorder);
+
+    let peak = samples.iter().map(|s| s.unsigned_abs()).max().unwrap_or(0);
+    psp::dprintln!("Recorded {} samples, peak level {}", samples.len(), peak);
+
+    // The channel plays at 44.1 kHz, so each recorded sample is output
+    // twice to keep the original pitch.
+    let channel = match AudioChannel::reserve(SAMPLE_COUNT, AudioFormat::Mono) {
+        Ok(ch) => ch,
+        Err(e) => {
+            psp::dprintln!("Failed to reserve audio channel: {:?}", e);
+            return;
+        },
+    };
+    let half = channel.sample_count() as usize / 2;
+    let mut buf = vec![0i16; channel.sample_count() as usize];
+
+    psp::dprintln!("Playing back...");
+    for chunk in samples.chunks(half) {
+        buf.fill(0);
+        for (i, &s) in chunk.iter().enumerate() {
+            buf[i * 2] = s;
+            buf[i * 2 + 1] = s;
+        }
+        if let Err(e) = channel.output_blocking(AUDIO_VOLUME_MAX as i32, &buf) {
+            psp::dprintln!("Audio output error: {:?}", e);
+            return;
+        }
+    }
+
+    psp::dprintln!("Playback complete");
+}
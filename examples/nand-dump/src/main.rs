@@ -0,0 +1,109 @@
+//! Dump the internal NAND flash to the Memory Stick, one file per erase
+//! block.
+//!
+//! Blocks are written to `ms0:/nand/blockNNNN.bin`. A block whose file
+//! already exists at full size is skipped, so an interrupted dump resumes
+//! where it stopped. Bad blocks are listed in `ms0:/nand/bad_blocks.txt`
+//! instead of being dumped.
+//!
+//! Requires custom firmware. Nothing is ever written to the flash.
+
+#![no_std]
+#![no_main]
+
+extern crate alloc;
+
+use alloc::format;
+use alloc::string::String;
+use psp::nand;
+
+psp::module_kernel!("NandDump", 1, 0);
+
+const OUT_DIR: &str = "ms0:/nand";
+
+fn psp_main() {
+    psp::callback::setup_exit_callback().unwrap();
+
+    let geo = match nand::geometry() {
+        Ok(geo) => geo,
+        Err(e) => {
+            psp::dprintln!("Can't query NAND: {}", e);
+            return;
+        },
+    };
+    psp::dprintln!(
+        "NAND: {} blocks x {} pages x {} bytes ({} KiB)",
+        geo.total_blocks,
+        geo.pages_per_block,
+        geo.page_size,
+        geo.total_bytes() / 1024
+    );
+
+    // Fails harmlessly if the directory is already there.
+    let _ = psp::io::create_dir(OUT_DIR);
+
+    let block_size = geo.block_size();
+    let mut buf = alloc::vec![0u8; block_size];
+    let mut bad = String::new();
+    let (mut dumped, mut skipped, mut failed) = (0u32, 0u32, 0u32);
+
+    for block in 0..geo.total_blocks {
+        let path = format!("{}/block{:04}.bin", OUT_DIR, block);
+
+        // Progress stays on one line below the header.
+        psp::debug::set_cursor(1, 0);
+        psp::dprint!(
+            "Block {:4}/{}  dumped {} skipped {} bad {} failed {}   ",
+            block + 1,
+            geo.total_blocks,
+            dumped,
+            skipped,
+            bad.lines().count(),
+            failed
+        );
+
+        if psp::io::stat(&path).is_ok_and(|st| st.st_size == block_size as i64) {
+            skipped += 1;
+            continue;
+        }
+        match nand::is_bad_block(block) {
+            Ok(true) => {
+                bad.push_str(&format!("{}\n", block));
+                continue;
+            },
+            Ok(false) => {},
+            Err(_) => {
+                failed += 1;
+                continue;
+            },
+        }
+        let saved = nand::read_block(block, &mut buf)
+            .map_err(|e| format!("{}", e))
+            .and_then(|()| psp::io::write_bytes(&path, &buf).map_err(|e| format!("{:?}", e)));
+        match saved {
+            Ok(()) => dumped += 1,
+            Err(e) => {
+                failed += 1;
+                // A partial file would be mistaken for a dumped block.
+                let _ = psp::io::remove_file(&path);
+                psp::debug::set_cursor(3, 0);
+                psp::dprint!("Block {}: {}", block, e);
+            },
+        }
+    }
+
+    // Bad blocks are rechecked on every run, so the list is always whole.
+    let list = format!("{}/bad_blocks.txt", OUT_DIR);
+    if let Err(e) = psp::io::write_bytes(&list, bad.as_bytes()) {
+        psp::dprintln!("\nFailed to write {}: {:?}", list, e);
+    }
+
+    psp::debug::set_cursor(5, 0);
+    psp::dprintln!(
+        "Done: {} dumped, {} already present, {} bad, {} failed",
+        dumped,
+        skipped,
+        bad.lines().count(),
+        failed
+    );
+}
@@ -0,0 +1,63 @@
+//! Connect to WiFi, query an NTP server, and compare its time with the
+//! local clock.
+//!
+//! Requires a real PSP with WiFi configured in network settings slot 1.
+
+#![no_std]
+#![no_main]
+
+use psp::net::{self, ntp};
+use psp::rtc::{self, Tick};
+
+psp::module!("ntp_time_example", 1, 1);
+
+const SERVER: &str = "pool.ntp.org";
+
+fn print_tick(label: &str, tick: &Tick) {
+    match rtc::format_rfc3339(tick, 0) {
+        Ok(buf) => {
+            let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+            let text = core::str::from_utf8(&buf[..len]).unwrap_or("?");
+            psp::dprintln!("{}: {}", label, text);
+        },
+        Err(e) => psp::dprintln!("{}: format failed: {:?}", label, e),
+    }
+}
+
+fn psp_main() {
+    psp::callback::setup_exit_callback().unwrap();
+
+    if let Err(e) = net::init(256 * 1024) {
+        psp::dprintln!("net::init failed: {:?}", e);
+        return;
+    }
+
+    psp::dprintln!("Connecting to WiFi...");
+    if let Err(e) = net::connect_ap(1) {
+        psp::dprintln!("connect_ap failed: {:?}", e);
+        net::term();
+        return;
+    }
+
+    match ntp::query(SERVER, 3000) {
+        Ok(server_time) => {
+            if let Ok(local) = Tick::now() {
+                print_tick("Local", &local);
+            }
+            print_tick("NTP  ", &server_time);
+        },
+        Err(e) => psp::dprintln!("NTP query failed: {}", e),
+    }
+
+    match ntp::sync_offset(SERVER, 3000) {
+        Ok(offset) => {
+            psp::dprintln!("Local clock is off by {} ms", offset / 1000);
+            if let Ok(corrected) = rtc::corrected_now() {
+                print_tick("Fixed", &corrected);
+            }
+        },
+        Err(e) => psp::dprintln!("NTP sync failed: {}", e),
+    }
+
+    net::term();
+}
@@ -0,0 +1,85 @@
+//! Compare loading 100 small assets from loose files and from a pak.
+
+#![no_std]
+#![no_main]
+
+extern crate alloc;
+
+use alloc::format;
+use alloc::vec::Vec;
+
+use psp::pak::{PakBuilder, PakReader};
+use psp::time::Instant;
+
+psp::module!("pak_assets_example", 1, 1);
+
+const DIR: &str = "ms0:/pak_assets";
+const PAK_PATH: &str = "ms0:/pak_assets.pak";
+const ASSETS: usize = 100;
+const ASSET_SIZE: usize = 512;
+
+fn asset(i: usize) -> Vec<u8> {
+    (0..ASSET_SIZE).map(|j| (i + j) as u8).collect()
+}
+
+/// Write every asset as a loose file and into one pak.
+fn create_assets() -> Result<(), &'static str> {
+    let _ = psp::io::create_dir(DIR);
+    let mut builder = PakBuilder::new();
+    for i in 0..ASSETS {
+        let name = format!("asset_{:03}.bin", i);
+        psp::io::write_bytes(&format!("{}/{}", DIR, name), &asset(i))
+            .map_err(|_| "failed to write loose file")?;
+        builder
+            .add(&name, &asset(i))
+            .map_err(|_| "failed to add pak entry")?;
+    }
+    let pak = builder.finish().map_err(|_| "failed to build pak")?;
+    psp::io::write_bytes(PAK_PATH, &pak).map_err(|_| "failed to write pak")
+}
+
+fn psp_main() {
+    psp::callback::setup_exit_callback().unwrap();
+
+    psp::dprintln!("Writing {} assets...", ASSETS);
+    if let Err(e) = create_assets() {
+        psp::dprintln!("{}", e);
+        return;
+    }
+
+    let start = Instant::now();
+    let mut loose_bytes = 0;
+    for i in 0..ASSETS {
+        match psp::io::read_to_vec(&format!("{}/asset_{:03}.bin", DIR, i)) {
+            Ok(data) => loose_bytes += data.len(),
+            Err(e) => psp::dprintln!("loose read failed: {:?}", e),
+        }
+    }
+    let loose = start.elapsed();
+
+    let start = Instant::now();
+    let mut pak_bytes = 0;
+    match PakReader::open(PAK_PATH) {
+        Ok(mut pak) => {
+            for i in 0..ASSETS {
+                match pak.read(&format!("asset_{:03}.bin", i)) {
+                    Ok(data) => pak_bytes += data.len(),
+                    Err(e) => psp::dprintln!("pak read failed: {}", e),
+                }
+            }
+        },
+        Err(e) => psp::dprintln!("pak open failed: {}", e),
+    }
+    let packed = start.elapsed();
+
+    psp::dprintln!(
+        "Loose files: {} bytes in {} ms",
+        loose_bytes,
+        loose.as_millis()
+    );
+    psp::dprintln!(
+        "Pak file:    {} bytes in {} ms",
+        pak_bytes,
+        packed.as_millis()
+    );
+}
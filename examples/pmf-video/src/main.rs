@@ -0,0 +1,105 @@
+//! Play `host0:/video.pmf` full screen with its audio.
+//!
+//! Run it through psplink with a PMF file, such as one made with the UMD
+//! Stream Composer, in the directory psplink serves as `host0:`. The
+//! header is printed first; a file without an audio stream plays
+//! silently. Press Cross to stop early.
+
+#![no_std]
+#![no_main]
+
+use core::ffi::c_void;
+
+use psp::gu_ext::{BlendMode, blit_texture, clear, set_blend_mode, setup_2d};
+use psp::input::Controller;
+use psp::mpeg::MpegPlayer;
+use psp::sys::{
+    self, ClearBuffer, CtrlButtons, DisplayPixelFormat, GuContextType, GuState, GuSyncBehavior,
+    GuSyncMode, TexturePixelFormat,
+};
+use psp::vram_alloc::get_vram_allocator;
+use psp::{BUF_WIDTH, SCREEN_HEIGHT, SCREEN_WIDTH};
+
+psp::module!("pmf_video_example", 1, 1);
+
+const PATH: &str = "host0:/video.pmf";
+
+static mut LIST: psp::Align16<[u32; 0x40000]> = psp::Align16([0; 0x40000]);
+
+fn psp_main() {
+    psp::callback::setup_exit_callback().unwrap();
+
+    let mut player = match MpegPlayer::open(PATH) {
+        Ok(player) => player,
+        Err(e) => {
+            psp::dprintln!("Failed to open {}: {}", PATH, e);
+            return;
+        },
+    };
+    let info = *player.info();
+    psp::dprintln!(
+        "{}x{}, {} ms, {} video and {} audio stream(s)",
+        info.width,
+        info.height,
+        info.duration().as_millis(),
+        info.video_streams,
+        info.audio_streams,
+    );
+
+    let allocator = get_vram_allocator().unwrap();
+    let fbp0 = allocator
+        .alloc_texture_pixels(BUF_WIDTH, SCREEN_HEIGHT, TexturePixelFormat::Psm8888)
+        .unwrap()
+        .as_mut_ptr_from_zero();
+    let fbp1 = allocator
+        .alloc_texture_pixels(BUF_WIDTH, SCREEN_HEIGHT, TexturePixelFormat::Psm8888)
+        .unwrap()
+        .as_mut_ptr_from_zero();
+
+    unsafe {
+        sys::sceGuInit();
+        sys::sceGuStart(GuContextType::Direct, &raw mut LIST as *mut c_void);
+        sys::sceGuDrawBuffer(DisplayPixelFormat::Psm8888, fbp0 as _, BUF_WIDTH as i32);
+        sys::sceGuDispBuffer(
+            SCREEN_WIDTH as i32,
+            SCREEN_HEIGHT as i32,
+            fbp1 as _,
+            BUF_WIDTH as i32,
+        );
+        sys::sceGuOffset(2048 - (SCREEN_WIDTH / 2), 2048 - (SCREEN_HEIGHT / 2));
+        sys::sceGuViewport(2048, 2048, SCREEN_WIDTH as i32, SCREEN_HEIGHT as i32);
+        sys::sceGuScissor(0, 0, SCREEN_WIDTH as i32, SCREEN_HEIGHT as i32);
+        sys::sceGuEnable(GuState::ScissorTest);
+        sys::sceGuFinish();
+        sys::sceGuSync(GuSyncMode::Finish, GuSyncBehavior::Wait);
+        sys::sceDisplayWaitVblankStart();
+        sys::sceGuDisplay(true);
+    }
+
+    let mut controller = Controller::new();
+    while !player.is_finished() {
+        controller.update();
+        if controller.is_pressed(CtrlButtons::CROSS) {
+            break;
+        }
+        if let Err(e) = player.update() {
+            psp::dprintln!("Playback failed: {}", e);
+            break;
+        }
+
+        unsafe {
+            sys::sceGuStart(GuContextType::Direct, &raw mut LIST as *mut c_void);
+            clear(0xff000000, ClearBuffer::COLOR_BUFFER_BIT);
+            setup_2d();
+            // The decoder leaves the alpha channel undefined.
+            set_blend_mode(BlendMode::None);
+            blit_texture(player.frame(), 0.0, 0.0);
+            sys::sceGuFinish();
+            sys::sceGuSync(GuSyncMode::Finish, GuSyncBehavior::Wait);
+            sys::sceDisplayWaitVblankStart();
+            sys::sceGuSwapBuffers();
+        }
+    }
+
+    psp::dprintln!("Decoded {} frames", player.frames_decoded());
+}
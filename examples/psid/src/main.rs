@@ -0,0 +1,37 @@
+//! Print the console's OpenPSID and the short device hash derived from it.
+
+#![no_std]
+#![no_main]
+
+use psp::ident;
+
+psp::module!("psid_example", 1, 1);
+
+const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+fn psp_main() {
+    psp::callback::setup_exit_callback().unwrap();
+
+    let psid = match ident::open_psid() {
+        Ok(psid) => psid,
+        Err(e) => {
+            psp::dprintln!("{}", e);
+            return;
+        },
+    };
+
+    let mut hex = [0u8; 32];
+    for (i, byte) in psid.iter().enumerate() {
+        hex[i * 2] = HEX_DIGITS[(byte >> 4) as usize];
+        hex[i * 2 + 1] = HEX_DIGITS[(byte & 0xf) as usize];
+    }
+    psp::dprintln!("OpenPSID: {}", core::str::from_utf8(&hex).unwrap());
+
+    if let Ok(hash) = ident::device_hash() {
+        psp::dprintln!("Device hash: {:016x}", hash);
+    }
+
+    if !ident::is_unique() {
+        psp::dprintln!("Running under PPSSPP: this ID is shared by every install.");
+    }
+}
@@ -1,6 +1,7 @@
 #![no_std]
 #![no_main]
 
+use psp::framebuffer::PixelSurface;
 use psp::sys;
 use psp::{BUF_WIDTH, SCREEN_HEIGHT, SCREEN_WIDTH};
 
@@ -28,14 +29,18 @@ fn psp_main() {
             sys::DisplaySetBufSync::NextFrame,
         );
 
+        let mut screen = PixelSurface::from_raw(
+            vram as *mut u8,
+            SCREEN_WIDTH,
+            SCREEN_HEIGHT,
+            BUF_WIDTH,
+            sys::DisplayPixelFormat::Psm8888,
+        );
+
         loop {
             sys::sceDisplayWaitVblankStart();
             for pos in 0..255 {
-                let color = wheel(pos);
-
-                for i in 0..(BUF_WIDTH * SCREEN_HEIGHT) {
-                    *vram.add(i as usize) = color;
-                }
+                screen.clear(wheel(pos));
             }
         }
     }
@@ -58,6 +58,21 @@ fn psp_main() {
         Err(e) => psp::dprintln!("DST error: {:?}", e),
     }
 
+    match system_param::button_assignment() {
+        Ok(button) => psp::dprintln!("Confirm button: {:?}", button),
+        Err(e) => psp::dprintln!("Button assignment error: {:?}", e),
+    }
+
+    match system_param::wlan_power_save() {
+        Ok(on) => psp::dprintln!("WLAN power save: {}", if on { "on" } else { "off" }),
+        Err(e) => psp::dprintln!("WLAN power save error: {:?}", e),
+    }
+
+    match system_param::adhoc_channel() {
+        Ok(channel) => psp::dprintln!("Ad hoc channel: {:?}", channel),
+        Err(e) => psp::dprintln!("Ad hoc channel error: {:?}", e),
+    }
+
     // --- RTC operations ---
     psp::dprintln!("\n=== RTC ===");
 
@@ -2,6 +2,9 @@
 //!
 //! The savedata utility renders via the GE, so GU must be initialized
 //! before calling save/load.
+//!
+//! Writes a plain slot and an encrypted slot side by side, then shows that
+//! the encrypted one only loads with the right key.
 
 #![no_std]
 #![no_main]
@@ -15,6 +18,9 @@ use psp::sys::{
 
 psp::module!("savedata_example", 1, 1);
 
+/// Key for the encrypted slot. A real game would pick its own.
+const PAYLOAD_KEY: [u8; 16] = *b"rust-psp-example";
+
 static mut LIST: psp::Align16<[u32; 262144]> = psp::Align16([0; 262144]);
 
 unsafe fn setup_gu() {
@@ -71,6 +77,42 @@ fn psp_main() {
         Err(e) => psp::dprintln!("Load failed: {:?}", e),
     }
 
+    // The same data in a second slot, encrypted with PAYLOAD_KEY.
+    let secret_name = b"SAVE1\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0";
+    let encrypted = Savedata::new(game_name)
+        .title("Rust PSP Encrypted Save")
+        .detail("Example save data, encrypted")
+        .encrypt_with(&PAYLOAD_KEY);
+
+    psp::dprintln!("Saving encrypted...");
+    if let Err(e) = encrypted.save(secret_name, save_data) {
+        psp::dprintln!("Encrypted save failed: {:?}", e);
+        return;
+    }
+
+    // The raw file is unreadable without the key...
+    if let Ok(raw) = Savedata::new(game_name).load(secret_name, 1024) {
+        psp::dprintln!("Raw encrypted file: {} bytes", raw.len());
+    }
+
+    // ...loads normally with it...
+    match encrypted.load(secret_name, 1024) {
+        Ok(data) => {
+            let text = core::str::from_utf8(&data).unwrap_or("<binary>");
+            psp::dprintln!("Decrypted {} bytes: {}", data.len(), text);
+        },
+        Err(e) => psp::dprintln!("Encrypted load failed: {:?}", e),
+    }
+
+    // ...and is rejected with the wrong one.
+    match Savedata::new(game_name)
+        .encrypt_with(b"some-other-key!!")
+        .load(secret_name, 1024)
+    {
+        Err(e) if e.is_wrong_key() => psp::dprintln!("Wrong key rejected: {}", e),
+        other => psp::dprintln!("Unexpected result with wrong key: {:?}", other),
+    }
+
     unsafe {
         sys::sceKernelExitGame();
     }
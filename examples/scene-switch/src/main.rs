@@ -0,0 +1,155 @@
+//! Alternate between two colored scenes, switching with each kind of
+//! `psp::gu_ext::Transition` in turn: a fade, then a wipe in every
+//! direction.
+
+#![no_std]
+#![no_main]
+
+use core::ffi::c_void;
+
+use psp::gu_ext::{Transition, WipeDirection, draw_circle_filled, draw_rect_filled, setup_2d};
+use psp::sys::{
+    self, ClearBuffer, DisplayPixelFormat, GuContextType, GuState, GuSyncBehavior, GuSyncMode,
+    TexturePixelFormat,
+};
+use psp::vram_alloc::get_vram_allocator;
+use psp::{BUF_WIDTH, SCREEN_HEIGHT, SCREEN_WIDTH};
+
+psp::module!("scene_switch_example", 1, 1);
+
+static mut LIST: psp::Align16<[u32; 0x40000]> = psp::Align16([0; 0x40000]);
+
+/// Frames each scene is shown before switching.
+const HOLD_FRAMES: u32 = 90;
+/// Frames each half of a transition takes.
+const TRANSITION_FRAMES: u32 = 30;
+/// Color the screen passes through between scenes.
+const COVER_COLOR: u32 = 0xff00_0000;
+
+/// The out/in pairs cycled through, as `None` for a fade or the wipe
+/// direction.
+const EFFECTS: [Option<WipeDirection>; 5] = [
+    None,
+    Some(WipeDirection::LeftToRight),
+    Some(WipeDirection::RightToLeft),
+    Some(WipeDirection::TopToBottom),
+    Some(WipeDirection::BottomToTop),
+];
+
+enum Phase {
+    Hold(u32),
+    Out(Transition),
+    In(Transition),
+}
+
+fn transition_out(effect: Option<WipeDirection>) -> Transition {
+    match effect {
+        None => Transition::fade_out(COVER_COLOR, TRANSITION_FRAMES),
+        Some(direction) => Transition::wipe_out(direction, COVER_COLOR, TRANSITION_FRAMES),
+    }
+}
+
+fn transition_in(effect: Option<WipeDirection>) -> Transition {
+    match effect {
+        None => Transition::fade_in(COVER_COLOR, TRANSITION_FRAMES),
+        Some(direction) => Transition::wipe_in(direction, COVER_COLOR, TRANSITION_FRAMES),
+    }
+}
+
+unsafe fn draw_scene(scene: usize) {
+    let w = SCREEN_WIDTH as f32;
+    let h = SCREEN_HEIGHT as f32;
+    unsafe {
+        if scene == 0 {
+            draw_rect_filled(0.0, 0.0, w, h, 0xffc0_6020);
+            draw_circle_filled(w / 2.0, h / 2.0, 60.0, 32, 0xff20_c0ff);
+        } else {
+            draw_rect_filled(0.0, 0.0, w, h, 0xff30_a030);
+            draw_rect_filled(w / 2.0 - 60.0, h / 2.0 - 60.0, 120.0, 120.0, 0xffff_ffff);
+        }
+    }
+}
+
+fn psp_main() {
+    psp::callback::setup_exit_callback().unwrap();
+
+    let allocator = get_vram_allocator().unwrap();
+    let fbp0 = allocator
+        .alloc_texture_pixels(BUF_WIDTH, SCREEN_HEIGHT, TexturePixelFormat::Psm8888)
+        .unwrap()
+        .as_mut_ptr_from_zero();
+    let fbp1 = allocator
+        .alloc_texture_pixels(BUF_WIDTH, SCREEN_HEIGHT, TexturePixelFormat::Psm8888)
+        .unwrap()
+        .as_mut_ptr_from_zero();
+
+    unsafe {
+        sys::sceGuInit();
+        sys::sceGuStart(GuContextType::Direct, &raw mut LIST as *mut c_void);
+        sys::sceGuDrawBuffer(DisplayPixelFormat::Psm8888, fbp0 as _, BUF_WIDTH as i32);
+        sys::sceGuDispBuffer(
+            SCREEN_WIDTH as i32,
+            SCREEN_HEIGHT as i32,
+            fbp1 as _,
+            BUF_WIDTH as i32,
+        );
+        sys::sceGuOffset(2048 - (SCREEN_WIDTH / 2), 2048 - (SCREEN_HEIGHT / 2));
+        sys::sceGuViewport(2048, 2048, SCREEN_WIDTH as i32, SCREEN_HEIGHT as i32);
+        sys::sceGuScissor(0, 0, SCREEN_WIDTH as i32, SCREEN_HEIGHT as i32);
+        sys::sceGuEnable(GuState::ScissorTest);
+        sys::sceGuFinish();
+        sys::sceGuSync(GuSyncMode::Finish, GuSyncBehavior::Wait);
+        sys::sceDisplayWaitVblankStart();
+        sys::sceGuDisplay(true);
+    }
+
+    let mut scene = 0;
+    let mut effect = 0;
+    let mut phase = Phase::Hold(0);
+
+    loop {
+        phase = match phase {
+            Phase::Hold(frames) if frames >= HOLD_FRAMES => {
+                Phase::Out(transition_out(EFFECTS[effect]))
+            },
+            Phase::Hold(frames) => Phase::Hold(frames + 1),
+            Phase::Out(mut out) => {
+                out.update();
+                if out.is_finished() {
+                    // Fully covered: swap scenes behind the cover.
+                    scene = 1 - scene;
+                    Phase::In(transition_in(EFFECTS[effect]))
+                } else {
+                    Phase::Out(out)
+                }
+            },
+            Phase::In(mut fade_in) => {
+                fade_in.update();
+                if fade_in.is_finished() {
+                    effect = (effect + 1) % EFFECTS.len();
+                    Phase::Hold(0)
+                } else {
+                    Phase::In(fade_in)
+                }
+            },
+        };
+
+        unsafe {
+            sys::sceGuStart(GuContextType::Direct, &raw mut LIST as *mut c_void);
+            sys::sceGuClearColor(0xff00_0000);
+            sys::sceGuClear(ClearBuffer::COLOR_BUFFER_BIT);
+
+            setup_2d();
+            draw_scene(scene);
+            match &phase {
+                Phase::Out(transition) | Phase::In(transition) => transition.draw(),
+                Phase::Hold(_) => {},
+            }
+
+            sys::sceGuFinish();
+            sys::sceGuSync(GuSyncMode::Finish, GuSyncBehavior::Wait);
+            sys::sceDisplayWaitVblankStart();
+            sys::sceGuSwapBuffers();
+        }
+    }
+}
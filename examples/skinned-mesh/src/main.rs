@@ -0,0 +1,139 @@
+//! A bar bending at its middle joint, skinned by the GE from two bones.
+
+#![no_std]
+#![no_main]
+
+use psp::gu_ext::{SkinnedColoredVertex, SkinnedMesh};
+use psp::math::{cosf, sinf};
+use psp::simd::{Vec4, mat4_from_quat_translation, mat4_identity};
+use psp::sys::{
+    self, ClearBuffer, DepthFunc, DisplayPixelFormat, GuContextType, GuPrimitive, GuState,
+    GuSyncBehavior, GuSyncMode, ScePspFVector3, ShadingModel, TexturePixelFormat,
+};
+use psp::vram_alloc::get_vram_allocator;
+use psp::{Align16, BUF_WIDTH, SCREEN_HEIGHT, SCREEN_WIDTH};
+
+psp::module!("skinned_mesh_example", 1, 1);
+
+static mut LIST: Align16<[u32; 0x40000]> = Align16([0; 0x40000]);
+
+/// Rows of vertices along the bar, bottom to top.
+const SEGMENTS: usize = 8;
+const HALF_WIDTH: f32 = 0.2;
+
+type Vertex = SkinnedColoredVertex<2>;
+
+/// Build the bar as a triangle strip. The bottom follows bone 0, the top
+/// bone 1, with a smooth blend around the joint at y = 0.
+fn build_bar(mesh: &mut SkinnedMesh<Vertex>) {
+    for i in 0..=SEGMENTS {
+        let t = i as f32 / SEGMENTS as f32;
+        let y = t * 2.0 - 1.0;
+        // 0 below y = -0.5, 1 above y = 0.5, smoothstep in between.
+        let s = (y + 0.5).clamp(0.0, 1.0);
+        let upper = s * s * (3.0 - 2.0 * s);
+        let weights = [1.0 - upper, upper];
+        let shade = (0x80 + (0x7f as f32 * t) as u32) & 0xff;
+        let color = 0xff00_0000 | (shade << 8) | (0xff - shade);
+        for x in [-HALF_WIDTH, HALF_WIDTH] {
+            mesh.vertices_mut().push(Vertex {
+                weights,
+                color,
+                x,
+                y,
+                z: 0.0,
+            });
+        }
+    }
+}
+
+fn psp_main() {
+    psp::callback::setup_exit_callback().unwrap();
+
+    let mut bar = SkinnedMesh::<Vertex>::new(GuPrimitive::TriangleStrip);
+    build_bar(&mut bar);
+
+    unsafe {
+        let allocator = get_vram_allocator().unwrap();
+        let fbp0 = allocator
+            .alloc_texture_pixels(BUF_WIDTH, SCREEN_HEIGHT, TexturePixelFormat::Psm8888)
+            .unwrap();
+        let fbp1 = allocator
+            .alloc_texture_pixels(BUF_WIDTH, SCREEN_HEIGHT, TexturePixelFormat::Psm8888)
+            .unwrap();
+        let zbp = allocator
+            .alloc_texture_pixels(BUF_WIDTH, SCREEN_HEIGHT, TexturePixelFormat::Psm4444)
+            .unwrap();
+
+        sys::sceGuInit();
+        sys::sceGuStart(GuContextType::Direct, &raw mut LIST.0 as *mut _);
+        sys::sceGuDrawBuffer(
+            DisplayPixelFormat::Psm8888,
+            fbp0.as_mut_ptr_from_zero() as _,
+            BUF_WIDTH as i32,
+        );
+        sys::sceGuDispBuffer(
+            SCREEN_WIDTH as i32,
+            SCREEN_HEIGHT as i32,
+            fbp1.as_mut_ptr_from_zero() as _,
+            BUF_WIDTH as i32,
+        );
+        sys::sceGuDepthBuffer(zbp.as_mut_ptr_from_zero() as _, BUF_WIDTH as i32);
+        sys::sceGuOffset(2048 - (SCREEN_WIDTH / 2), 2048 - (SCREEN_HEIGHT / 2));
+        sys::sceGuViewport(2048, 2048, SCREEN_WIDTH as i32, SCREEN_HEIGHT as i32);
+        sys::sceGuDepthRange(65535, 0);
+        sys::sceGuScissor(0, 0, SCREEN_WIDTH as i32, SCREEN_HEIGHT as i32);
+        sys::sceGuEnable(GuState::ScissorTest);
+        sys::sceGuDepthFunc(DepthFunc::GreaterOrEqual);
+        sys::sceGuEnable(GuState::DepthTest);
+        sys::sceGuShadeModel(ShadingModel::Smooth);
+        sys::sceGuEnable(GuState::ClipPlanes);
+        sys::sceGuFinish();
+        sys::sceGuSync(GuSyncMode::Finish, GuSyncBehavior::Wait);
+        sys::sceDisplayWaitVblankStart();
+        sys::sceGuDisplay(true);
+    }
+
+    let mut frame = 0u32;
+    loop {
+        // Swing the upper half up to 60 degrees either way around Z.
+        let angle = unsafe { sinf(frame as f32 * 0.04) } * core::f32::consts::FRAC_PI_3;
+        let half = angle * 0.5;
+        let rotation = unsafe { Vec4::new(0.0, 0.0, sinf(half), cosf(half)) };
+        let bones = [
+            mat4_identity(),
+            mat4_from_quat_translation(&rotation, &Vec4::ZERO),
+        ];
+
+        unsafe {
+            sys::sceGuStart(GuContextType::Direct, &raw mut LIST.0 as *mut _);
+            sys::sceGuClearColor(0xff40_3020);
+            sys::sceGuClearDepth(0);
+            sys::sceGuClear(ClearBuffer::COLOR_BUFFER_BIT | ClearBuffer::DEPTH_BUFFER_BIT);
+
+            sys::sceGumMatrixMode(sys::MatrixMode::Projection);
+            sys::sceGumLoadIdentity();
+            sys::sceGumPerspective(75.0, 16.0 / 9.0, 0.5, 1000.0);
+            sys::sceGumMatrixMode(sys::MatrixMode::View);
+            sys::sceGumLoadIdentity();
+            sys::sceGumMatrixMode(sys::MatrixMode::Model);
+            sys::sceGumLoadIdentity();
+            sys::sceGumTranslate(&ScePspFVector3 {
+                x: 0.0,
+                y: 0.0,
+                z: -3.0,
+            });
+            // SkinnedMesh draws with sceGuDrawArray, so flush the sceGum
+            // matrices to the GE first.
+            sys::sceGumUpdateMatrix();
+
+            bar.draw(&bones).unwrap();
+
+            sys::sceGuFinish();
+            sys::sceGuSync(GuSyncMode::Finish, GuSyncBehavior::Wait);
+            sys::sceDisplayWaitVblankStart();
+            sys::sceGuSwapBuffers();
+        }
+        frame = frame.wrapping_add(1);
+    }
+}
@@ -0,0 +1,149 @@
+//! Scroll a line of text inside a rounded-rect panel, clipped to the
+//! panel's shape with `psp::gu_ext::StencilMask`.
+//!
+//! The draw buffer is `Psm8888`, whose alpha bits double as the 8-bit
+//! stencil buffer. The panel background is drawn before the mask because
+//! unclipped draws overwrite the stencil with their color alpha.
+
+#![no_std]
+#![no_main]
+
+use core::ffi::c_void;
+
+use psp::font::{FontLib, FontRenderer};
+use psp::gu_ext::{StencilMask, draw_circle_filled, draw_rect_filled, setup_2d};
+use psp::sys::{
+    self, ClearBuffer, DisplayPixelFormat, GuContextType, GuState, GuSyncBehavior, GuSyncMode,
+    SceFontFamilyCode, SceFontLanguageCode, SceFontStyleCode, TexturePixelFormat,
+};
+use psp::vram_alloc::get_vram_allocator;
+use psp::{BUF_WIDTH, SCREEN_HEIGHT, SCREEN_WIDTH};
+
+psp::module!("stencil_clip_example", 1, 1);
+
+static mut LIST: psp::Align16<[u32; 0x40000]> = psp::Align16([0; 0x40000]);
+
+const PANEL_X: f32 = 90.0;
+const PANEL_Y: f32 = 96.0;
+const PANEL_W: f32 = 300.0;
+const PANEL_H: f32 = 80.0;
+const PANEL_RADIUS: f32 = 24.0;
+
+const MESSAGE: &str = "Stencil clipping on the PSP: this text only appears inside the panel";
+
+/// Fill a rounded rectangle from two rects and four corner circles.
+unsafe fn rounded_rect(x: f32, y: f32, w: f32, h: f32, r: f32, color: u32) {
+    unsafe {
+        draw_rect_filled(x + r, y, w - 2.0 * r, h, color);
+        draw_rect_filled(x, y + r, w, h - 2.0 * r, color);
+        draw_circle_filled(x + r, y + r, r, 16, color);
+        draw_circle_filled(x + w - r, y + r, r, 16, color);
+        draw_circle_filled(x + r, y + h - r, r, 16, color);
+        draw_circle_filled(x + w - r, y + h - r, r, 16, color);
+    }
+}
+
+fn psp_main() {
+    psp::callback::setup_exit_callback().unwrap();
+
+    let allocator = get_vram_allocator().unwrap();
+    let fbp0 = allocator
+        .alloc_texture_pixels(BUF_WIDTH, SCREEN_HEIGHT, TexturePixelFormat::Psm8888)
+        .unwrap()
+        .as_mut_ptr_from_zero();
+    let fbp1 = allocator
+        .alloc_texture_pixels(BUF_WIDTH, SCREEN_HEIGHT, TexturePixelFormat::Psm8888)
+        .unwrap()
+        .as_mut_ptr_from_zero();
+    let atlas_vram = allocator
+        .alloc_texture_pixels(512, 512, TexturePixelFormat::PsmT8)
+        .unwrap()
+        .as_mut_ptr_direct_to_vram();
+
+    unsafe {
+        sys::sceGuInit();
+        sys::sceGuStart(GuContextType::Direct, &raw mut LIST as *mut c_void);
+        sys::sceGuDrawBuffer(DisplayPixelFormat::Psm8888, fbp0 as _, BUF_WIDTH as i32);
+        sys::sceGuDispBuffer(
+            SCREEN_WIDTH as i32,
+            SCREEN_HEIGHT as i32,
+            fbp1 as _,
+            BUF_WIDTH as i32,
+        );
+        sys::sceGuOffset(2048 - (SCREEN_WIDTH / 2), 2048 - (SCREEN_HEIGHT / 2));
+        sys::sceGuViewport(2048, 2048, SCREEN_WIDTH as i32, SCREEN_HEIGHT as i32);
+        sys::sceGuScissor(0, 0, SCREEN_WIDTH as i32, SCREEN_HEIGHT as i32);
+        sys::sceGuEnable(GuState::ScissorTest);
+        sys::sceGuFinish();
+        sys::sceGuSync(GuSyncMode::Finish, GuSyncBehavior::Wait);
+        sys::sceDisplayWaitVblankStart();
+        sys::sceGuDisplay(true);
+    }
+
+    let fontlib = match FontLib::new(4) {
+        Ok(fl) => fl,
+        Err(e) => {
+            psp::dprintln!("FontLib::new failed: {:?}", e);
+            return;
+        },
+    };
+    let font = match fontlib.find_optimum(
+        SceFontFamilyCode::SansSerif,
+        SceFontStyleCode::Regular,
+        SceFontLanguageCode::Latin,
+    ) {
+        Ok(f) => f,
+        Err(e) => {
+            psp::dprintln!("find_optimum failed: {:?}", e);
+            return;
+        },
+    };
+    let mut renderer = FontRenderer::new(&font, atlas_vram, 24.0);
+    let text_width = renderer.measure_text(MESSAGE);
+    let text_y = PANEL_Y + (PANEL_H + renderer.line_height()) / 2.0;
+
+    let mut stencil = StencilMask::new();
+    let mut scroll = PANEL_X + PANEL_W;
+
+    loop {
+        scroll -= 1.5;
+        if scroll < PANEL_X - text_width {
+            scroll = PANEL_X + PANEL_W;
+        }
+
+        unsafe {
+            sys::sceGuStart(GuContextType::Direct, &raw mut LIST as *mut c_void);
+            sys::sceGuClearColor(0xff302010);
+            sys::sceGuClearStencil(0);
+            sys::sceGuClear(ClearBuffer::COLOR_BUFFER_BIT | ClearBuffer::STENCIL_BUFFER_BIT);
+
+            setup_2d();
+
+            // Unclipped content first: it writes alpha, i.e. the stencil.
+            rounded_rect(
+                PANEL_X - 4.0,
+                PANEL_Y - 4.0,
+                PANEL_W + 8.0,
+                PANEL_H + 8.0,
+                PANEL_RADIUS + 4.0,
+                0xffc0c0c0,
+            );
+            rounded_rect(PANEL_X, PANEL_Y, PANEL_W, PANEL_H, PANEL_RADIUS, 0xff603018);
+
+            // Mark the panel's interior in the stencil buffer.
+            stencil.begin_mask();
+            rounded_rect(PANEL_X, PANEL_Y, PANEL_W, PANEL_H, PANEL_RADIUS, 0xffffffff);
+            stencil.end_mask();
+
+            stencil.begin_clipped();
+            renderer.draw_text(scroll, text_y, 0xffffffff, MESSAGE);
+            renderer.flush();
+            stencil.end_clipped();
+
+            sys::sceGuFinish();
+            sys::sceGuSync(GuSyncMode::Finish, GuSyncBehavior::Wait);
+            sys::sceDisplayWaitVblankStart();
+            sys::sceGuSwapBuffers();
+        }
+    }
+}
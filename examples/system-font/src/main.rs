@@ -88,6 +88,9 @@ fn psp_main() {
 
     let mut renderer = FontRenderer::new(&font, atlas_vram, 16.0);
 
+    // Render the glyphs up front so the first frame doesn't stall on them.
+    renderer.prewarm_ascii();
+
     // Render loop.
     unsafe {
         loop {
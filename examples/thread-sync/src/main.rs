@@ -1,4 +1,4 @@
-//! Spawn threads sharing a SpinMutex counter.
+//! Spawn scoped threads sharing a stack-local SpinMutex counter.
 
 #![no_std]
 #![no_main]
@@ -8,8 +8,6 @@ use psp::thread;
 
 psp::module!("thread_sync_example", 1, 1);
 
-static COUNTER: SpinMutex<u32> = SpinMutex::new(0);
-
 const THREAD_COUNT: usize = 4;
 const INCREMENTS: u32 = 100;
 
@@ -22,34 +20,30 @@ fn psp_main() {
         INCREMENTS
     );
 
-    let mut handles = [const { None }; THREAD_COUNT];
+    // Scoped threads may borrow this local instead of needing a static.
+    let counter = SpinMutex::new(0u32);
     let names: [&[u8]; THREAD_COUNT] = [b"worker_0\0", b"worker_1\0", b"worker_2\0", b"worker_3\0"];
 
-    for i in 0..THREAD_COUNT {
-        match thread::spawn(names[i], || {
-            for _ in 0..INCREMENTS {
-                *COUNTER.lock() += 1;
-            }
-            0
-        }) {
-            Ok(h) => handles[i] = Some(h),
-            Err(e) => {
+    let result = thread::scope(|s| {
+        for (i, name) in names.into_iter().enumerate() {
+            let spawned = s.spawn(name, || {
+                for _ in 0..INCREMENTS {
+                    *counter.lock() += 1;
+                }
+                0
+            });
+            if let Err(e) = spawned {
                 psp::dprintln!("Failed to spawn thread {}: {:?}", i, e);
-                return;
-            },
-        }
-    }
-
-    for (i, slot) in handles.into_iter().enumerate() {
-        if let Some(h) = slot {
-            match h.join() {
-                Ok(code) => psp::dprintln!("Thread {} exited with code {}", i, code),
-                Err(e) => psp::dprintln!("Thread {} join failed: {:?}", i, e),
             }
         }
+        // All threads are joined when the scope ends.
+    });
+
+    if let Err(e) = result {
+        psp::dprintln!("A worker thread failed: {:?}", e);
     }
 
-    let total = *COUNTER.lock();
+    let total = *counter.lock();
     psp::dprintln!(
         "Final counter value: {} (expected {})",
         total,
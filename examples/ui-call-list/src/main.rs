@@ -0,0 +1,165 @@
+//! Draw a static UI panel of a few thousand widgets either by re-batching
+//! it every frame or by replaying a `psp::gu_ext::CallList` recorded once,
+//! and show the CPU time each frame's list takes to build. Press CROSS to
+//! switch modes.
+
+#![no_std]
+#![no_main]
+
+extern crate alloc;
+
+use core::ffi::c_void;
+
+use psp::gu_ext::{AlignedBuf, CallList, SpriteBatch, setup_2d};
+use psp::input::Controller;
+use psp::sys::{
+    self, ClearBuffer, CtrlButtons, DisplayPixelFormat, GuContextType, GuState, GuSyncBehavior,
+    GuSyncMode, TexturePixelFormat,
+};
+use psp::time::Stopwatch;
+use psp::vram_alloc::get_vram_allocator;
+use psp::{BUF_WIDTH, SCREEN_HEIGHT, SCREEN_WIDTH};
+
+psp::module!("ui_call_list_example", 1, 1);
+
+static mut LIST: psp::Align16<[u32; 0x40000]> = psp::Align16([0; 0x40000]);
+
+/// Panel grid: 4x4 pixel widgets on a 5 pixel pitch.
+const COLUMNS: usize = 88;
+const ROWS: usize = 40;
+const WIDGETS: usize = COLUMNS * ROWS;
+/// 48 bytes of vertices per widget.
+const VERTEX_BYTES: usize = WIDGETS * 48;
+/// Frames averaged per displayed timing.
+const WINDOW: u32 = 60;
+
+fn psp_main() {
+    psp::callback::setup_exit_callback().unwrap();
+
+    let allocator = get_vram_allocator().unwrap();
+    let fbp0 = allocator
+        .alloc_texture_pixels(BUF_WIDTH, SCREEN_HEIGHT, TexturePixelFormat::Psm8888)
+        .unwrap()
+        .as_mut_ptr_from_zero();
+    let fbp1 = allocator
+        .alloc_texture_pixels(BUF_WIDTH, SCREEN_HEIGHT, TexturePixelFormat::Psm8888)
+        .unwrap()
+        .as_mut_ptr_from_zero();
+
+    unsafe {
+        sys::sceGuInit();
+        sys::sceGuStart(GuContextType::Direct, &raw mut LIST as *mut c_void);
+        sys::sceGuDrawBuffer(DisplayPixelFormat::Psm8888, fbp0 as _, BUF_WIDTH as i32);
+        sys::sceGuDispBuffer(
+            SCREEN_WIDTH as i32,
+            SCREEN_HEIGHT as i32,
+            fbp1 as _,
+            BUF_WIDTH as i32,
+        );
+        sys::sceGuOffset(2048 - (SCREEN_WIDTH / 2), 2048 - (SCREEN_HEIGHT / 2));
+        sys::sceGuViewport(2048, 2048, SCREEN_WIDTH as i32, SCREEN_HEIGHT as i32);
+        sys::sceGuScissor(0, 0, SCREEN_WIDTH as i32, SCREEN_HEIGHT as i32);
+        sys::sceGuEnable(GuState::ScissorTest);
+        sys::sceGuFinish();
+        sys::sceGuSync(GuSyncMode::Finish, GuSyncBehavior::Wait);
+        sys::sceDisplayWaitVblankStart();
+        sys::sceGuDisplay(true);
+    }
+
+    let mut batch = SpriteBatch::new(WIDGETS);
+
+    // Record the panel once. Its vertices live in `vertices`, so the list
+    // itself is only a handful of commands.
+    let mut vertices = AlignedBuf::new(VERTEX_BYTES);
+    let mut panel = CallList::new(1024);
+    unsafe {
+        panel.begin();
+        draw_panel(&mut batch);
+        assert!(batch.flush_to_buffer(&mut vertices));
+        panel.end();
+    }
+
+    let mut ctrl = Controller::new();
+    let mut replay = true;
+
+    let mut frames = 0;
+    let mut total_us = 0;
+    let mut label = alloc::string::String::from("measuring...\0");
+    let mut cursor = 0.0f32;
+
+    loop {
+        ctrl.update();
+        if ctrl.is_pressed(CtrlButtons::CROSS) {
+            replay = !replay;
+            frames = 0;
+            total_us = 0;
+        }
+        cursor = (cursor + 2.0) % SCREEN_WIDTH as f32;
+
+        unsafe {
+            // Time building the list only, not drawing it.
+            let build = Stopwatch::start();
+            sys::sceGuStart(GuContextType::Direct, &raw mut LIST as *mut c_void);
+            sys::sceGuClearColor(0xff101010);
+            sys::sceGuClear(ClearBuffer::COLOR_BUFFER_BIT);
+
+            if replay {
+                panel.call();
+            } else {
+                draw_panel(&mut batch);
+                batch.flush();
+            }
+            total_us += build.elapsed_us();
+
+            // Something that changes every frame, drawn over the panel.
+            setup_2d();
+            sys::sceGuDisable(GuState::Texture2D);
+            batch.draw_colored_rect(cursor, 220.0, 16.0, 16.0, 0xff00ffff);
+            batch.flush();
+
+            sys::sceGuDebugPrint(8, 248, 0xffffffff, label.as_ptr());
+            sys::sceGuDebugFlush();
+            sys::sceGuFinish();
+            sys::sceGuSync(GuSyncMode::Finish, GuSyncBehavior::Wait);
+        }
+        frames += 1;
+
+        if frames == WINDOW {
+            let avg_us = total_us / WINDOW as u64;
+            label = alloc::format!(
+                "{}: {} widgets, {} us/frame to build (X to switch)\0",
+                if replay { "replay" } else { "re-batch" },
+                WIDGETS,
+                avg_us,
+            );
+            frames = 0;
+            total_us = 0;
+        }
+
+        unsafe {
+            sys::sceDisplayWaitVblankStart();
+            sys::sceGuSwapBuffers();
+        }
+    }
+}
+
+/// Queue every widget of the panel, with the 2D state it's drawn with.
+unsafe fn draw_panel(batch: &mut SpriteBatch) {
+    unsafe {
+        setup_2d();
+        sys::sceGuDisable(GuState::Texture2D);
+    }
+    for row in 0..ROWS {
+        for column in 0..COLUMNS {
+            let shade = ((row * 5 + column * 3) % 64) as u32 * 2 + 64;
+            let color = 0xff00_0000 | (shade << 16) | ((shade / 2) << 8) | 0x20;
+            batch.draw_colored_rect(
+                12.0 + column as f32 * 5.0,
+                12.0 + row as f32 * 5.0,
+                4.0,
+                4.0,
+                color,
+            );
+        }
+    }
+}
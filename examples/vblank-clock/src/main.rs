@@ -0,0 +1,57 @@
+//! A clock overlay redrawn exactly once per vblank by
+//! `psp::display::on_vblank`, while the main thread sleeps.
+
+#![no_std]
+#![no_main]
+
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use psp::time::DateTime;
+
+psp::module!("vblank_clock", 1, 1);
+
+/// Vblanks handled so far.
+static FRAMES: AtomicU32 = AtomicU32::new(0);
+
+fn draw_clock() {
+    let frame = FRAMES.fetch_add(1, Ordering::Relaxed) + 1;
+    psp::debug::set_cursor(2, 0);
+    match DateTime::now() {
+        Ok(now) => psp::dprint!(
+            "{:02}:{:02}:{:02}  frame {:>6}",
+            now.hour(),
+            now.minute(),
+            now.second(),
+            frame
+        ),
+        Err(_) => psp::dprint!("--:--:--  frame {:>6}", frame),
+    }
+}
+
+fn psp_main() {
+    psp::callback::setup_exit_callback().unwrap();
+    psp::dprintln!("Clock drawn from a vblank handler");
+
+    // SAFETY: this module isn't built with `feature = "kernel"`, so
+    // `draw_clock` runs on the handler thread, not in interrupt context.
+    let vblank = match unsafe { psp::display::on_vblank(draw_clock) } {
+        Ok(vblank) => vblank,
+        Err(e) => {
+            psp::dprintln!("on_vblank failed: {}", e);
+            return;
+        },
+    };
+
+    // The main thread does nothing but sleep; the clock keeps ticking.
+    let start = psp::display::vblank_count();
+    psp::thread::sleep_ms(30_000);
+    drop(vblank);
+
+    let elapsed = psp::display::vblank_count().wrapping_sub(start);
+    psp::debug::set_cursor(4, 0);
+    psp::dprintln!(
+        "Handled {} of {} vblanks",
+        FRAMES.load(Ordering::Relaxed),
+        elapsed
+    );
+}
@@ -0,0 +1,84 @@
+//! Connect to WiFi, open a WebSocket to a public echo server and check
+//! that text and binary messages come back unchanged.
+//!
+//! Uses `ws://echo.websocket.org/`, which greets each connection with a
+//! "Request served by ..." text message and then echoes every message.
+//! Any other plain `ws://` echo server works by changing `HOST`, `PORT`
+//! and `PATH`.
+//!
+//! Requires a real PSP with WiFi configured in network settings slot 1.
+//! Will not work in PPSSPP emulator.
+
+#![no_std]
+#![no_main]
+
+extern crate alloc;
+
+use psp::net::{
+    self,
+    websocket::{WsClient, WsMessage},
+};
+
+psp::module!("websocket_echo_example", 1, 1);
+
+const HOST: &str = "echo.websocket.org";
+const PORT: u16 = 80;
+const PATH: &str = "/";
+
+fn psp_main() {
+    psp::callback::setup_exit_callback().unwrap();
+
+    if let Err(e) = net::init(256 * 1024) {
+        psp::dprintln!("net::init failed: {:?}", e);
+        return;
+    }
+    psp::dprintln!("Connecting to WiFi...");
+    if let Err(e) = net::connect_ap(1) {
+        psp::dprintln!("connect_ap failed: {:?}", e);
+        net::term();
+        return;
+    }
+
+    run();
+
+    net::term();
+    psp::dprintln!("Done.");
+}
+
+fn run() {
+    psp::dprintln!("Opening ws://{}:{}{}", HOST, PORT, PATH);
+    let mut ws = match WsClient::connect(HOST, PORT, PATH) {
+        Ok(ws) => ws,
+        Err(e) => {
+            psp::dprintln!("connect failed: {}", e);
+            return;
+        },
+    };
+    if let Err(e) = ws.set_read_timeout(5000) {
+        psp::dprintln!("set_read_timeout failed: {}", e);
+    }
+
+    // The server's greeting.
+    if let Ok(WsMessage::Text(text)) = ws.recv() {
+        psp::dprintln!("Server says: {}", text);
+    }
+
+    let text = "Hello from rust-psp!";
+    let binary = [0u8, 1, 2, 3, 0xfe, 0xff];
+    let result = ws
+        .send_text(text)
+        .and_then(|()| ws.recv())
+        .map(|reply| reply == WsMessage::Text(text.into()))
+        .and_then(|text_ok| {
+            ws.send_binary(&binary)?;
+            let reply = ws.recv()?;
+            Ok(text_ok && reply == WsMessage::Binary(binary.to_vec()))
+        });
+    match result {
+        Ok(true) => psp::dprintln!("Text and binary echoed correctly."),
+        Ok(false) => psp::dprintln!("Echo didn't match what was sent."),
+        Err(e) => psp::dprintln!("Echo failed: {}", e),
+    }
+
+    // Dropping the client performs the close handshake.
+}
@@ -28,6 +28,7 @@
 
 use crate::sys::{self, SceSysMemBlockTypes, SceSysMemPartitionId, SceUid};
 use alloc::alloc::{GlobalAlloc, Layout};
+use core::sync::atomic::{AtomicUsize, Ordering};
 use core::{mem, ptr};
 use linked_list_allocator::Heap;
 use spin::Mutex;
@@ -93,7 +94,8 @@ const TAG_KERNEL: u32 = 0xC0DE_BABE;
 
 /// Per-allocation header. Lives immediately before the alignment
 /// padding. Stores the routing tag plus the size or kernel block id
-/// `dealloc` needs to free the block.
+/// `dealloc` needs to free the block, and the total size for
+/// [`alloc_stats`].
 ///
 /// Layout:
 ///
@@ -105,11 +107,13 @@ const TAG_KERNEL: u32 = 0xC0DE_BABE;
 /// For arena blocks, `size_or_id` is the total bytes allocated from
 /// the linked-list heap (header + padding + user data). For kernel
 /// blocks it's the `SceUid` cast to `u32` so we can call
-/// `sceKernelFreePartitionMemory`.
+/// `sceKernelFreePartitionMemory`. `total` is the number of bytes
+/// the block occupies in either case.
 #[repr(C)]
 struct AllocHeader {
     tag: u32,
     size_or_id: u32,
+    total: u32,
 }
 
 const HEADER_SIZE: usize = mem::size_of::<AllocHeader>();
@@ -178,6 +182,67 @@ fn ensure_heap_init(heap: &mut Heap) -> bool {
     true
 }
 
+/// Bytes currently allocated, including per-allocation overhead.
+static USED_BYTES: AtomicUsize = AtomicUsize::new(0);
+/// High-water mark of `USED_BYTES` since start or `reset_peak`.
+static PEAK_BYTES: AtomicUsize = AtomicUsize::new(0);
+/// Number of live allocations.
+static ALLOCATION_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// Snapshot of global allocator usage, from [`alloc_stats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AllocStats {
+    /// Bytes held by live allocations, including the allocator's
+    /// per-allocation header and alignment padding. Counts both arena
+    /// allocations and large allocations served by dedicated kernel
+    /// blocks.
+    pub used_bytes: usize,
+    /// Bytes still free in the heap arena. Allocations that don't fit
+    /// fall back to kernel blocks, so running out of arena is not yet
+    /// out of memory; see `sceKernelMaxFreeMemSize` for the partition.
+    pub free_bytes: usize,
+    /// Highest `used_bytes` seen since startup or the last
+    /// [`reset_peak`].
+    pub peak_bytes: usize,
+    /// Number of live allocations.
+    pub allocation_count: usize,
+}
+
+/// Current usage of the global allocator, shared by `alloc` and (with
+/// the `std` feature) std's `System` allocator.
+///
+/// Counters are updated with atomics outside the heap lock, so the
+/// fields may be momentarily inconsistent with each other while other
+/// threads allocate.
+pub fn alloc_stats() -> AllocStats {
+    AllocStats {
+        used_bytes: USED_BYTES.load(Ordering::Relaxed),
+        free_bytes: {
+            let h = lock_heap();
+            if h.size() == 0 { HEAP_SIZE } else { h.free() }
+        },
+        peak_bytes: PEAK_BYTES.load(Ordering::Relaxed),
+        allocation_count: ALLOCATION_COUNT.load(Ordering::Relaxed),
+    }
+}
+
+/// Reset [`AllocStats::peak_bytes`] to the current usage, e.g. before
+/// loading a level to measure that level's high-water mark.
+pub fn reset_peak() {
+    PEAK_BYTES.store(USED_BYTES.load(Ordering::Relaxed), Ordering::Relaxed);
+}
+
+fn record_alloc(total: usize) {
+    let used = USED_BYTES.fetch_add(total, Ordering::Relaxed) + total;
+    PEAK_BYTES.fetch_max(used, Ordering::Relaxed);
+    ALLOCATION_COUNT.fetch_add(1, Ordering::Relaxed);
+}
+
+fn record_dealloc(total: usize) {
+    USED_BYTES.fetch_sub(total, Ordering::Relaxed);
+    ALLOCATION_COUNT.fetch_sub(1, Ordering::Relaxed);
+}
+
 /// Free heap memory in bytes (current capacity minus used). Useful
 /// for diagnostics — embedders can call this to check arena pressure.
 pub fn heap_free() -> usize {
@@ -245,6 +310,9 @@ unsafe impl GlobalAlloc for SystemAlloc {
         let pad_len = *ptr.sub(1) as usize;
         let header_ptr = ptr.sub(pad_len).sub(HEADER_SIZE);
         let header = ptr::read(header_ptr.cast::<AllocHeader>());
+        if matches!(header.tag, TAG_ARENA | TAG_KERNEL) {
+            record_dealloc(header.total as usize);
+        }
         match header.tag {
             TAG_ARENA => {
                 let heap_layout = Layout::from_size_align_unchecked(
@@ -318,9 +386,17 @@ unsafe fn write_header_and_align(
     }
     let pad_len = 1 + offset;
     debug_assert!(pad_len <= MAX_ALIGN);
-    ptr::write(raw.cast::<AllocHeader>(), AllocHeader { tag, size_or_id });
+    ptr::write(
+        raw.cast::<AllocHeader>(),
+        AllocHeader {
+            tag,
+            size_or_id,
+            total: total as u32,
+        },
+    );
     let user_ptr = after_header.add(pad_len);
     *user_ptr.sub(1) = pad_len as u8;
+    record_alloc(total);
     user_ptr
 }
 
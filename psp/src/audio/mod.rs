@@ -4,7 +4,11 @@
 //! channels, and [`SrcChannel`] for the global Sample Rate Conversion (SRC)
 //! channel. The SRC channel is a singleton separate from the 8 PCM channels,
 //! making it ideal for background audio in plugins that must not conflict with
-//! game audio.
+//! game audio. [`Recorder`] captures mono samples from the microphone.
+//!
+//! [`StreamPlayer`] streams an MP3 or in-memory PCM to a channel from a
+//! worker thread, optionally playing an intro once and then looping the
+//! rest without a gap (see [`LoopedStream`]).
 //!
 //! # Example
 //!
@@ -16,8 +20,15 @@
 //! // Channel is released on drop.
 //! ```
 
+#[cfg(not(feature = "stub-only"))]
+mod stream;
+
+#[cfg(not(feature = "stub-only"))]
+pub use stream::{LoopedStream, PcmSource, StreamError, StreamPlayer, StreamSource};
+
 use core::ffi::c_void;
 use core::marker::PhantomData;
+use core::sync::atomic::{AtomicBool, Ordering};
 
 /// Audio output format.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -304,3 +315,133 @@ impl Drop for SrcChannel {
         }
     }
 }
+
+// ---------------------------------------------------------------------------
+// Microphone input
+// ---------------------------------------------------------------------------
+
+/// Sample rates the microphone hardware supports, in Hz.
+pub const INPUT_SAMPLE_RATES: [u32; 3] = [44100, 22050, 11025];
+
+/// Whether a [`Recorder`] currently exists.
+static RECORDER_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Error from a [`Recorder`] operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordError {
+    /// The sample rate is not one of [`INPUT_SAMPLE_RATES`].
+    UnsupportedRate(u32),
+    /// The console has no microphone input (PSP-1000).
+    NoMicrophone,
+    /// Another [`Recorder`] is already open.
+    Busy,
+    /// An audio input syscall failed.
+    Audio(AudioError),
+}
+
+impl core::fmt::Display for RecordError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::UnsupportedRate(rate) => write!(f, "unsupported input sample rate {} Hz", rate),
+            Self::NoMicrophone => f.write_str("no microphone input on this console"),
+            Self::Busy => f.write_str("a recorder is already open"),
+            Self::Audio(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<AudioError> for RecordError {
+    fn from(e: AudioError) -> Self {
+        Self::Audio(e)
+    }
+}
+
+/// An RAII handle to the microphone input.
+///
+/// The PSP-2000 and later take a microphone through the headset remote
+/// port (the PSP Go has one built in); the PSP-1000's remote port has no
+/// microphone line. Only one recorder can be open at a time. Input stops
+/// when it is dropped.
+///
+/// Emulators report whatever their host provides, which may be silence.
+///
+/// # Example
+///
+/// ```ignore
+/// use psp::audio::Recorder;
+///
+/// let rec = Recorder::new(22050, 0)?;
+/// let mut samples = [0i16; 22050];
+/// rec.read_blocking(&mut samples)?; // one second of audio
+/// ```
+pub struct Recorder {
+    sample_rate: u32,
+    _marker: PhantomData<*const ()>, // !Send + !Sync
+}
+
+impl Recorder {
+    /// Open the microphone at `sample_rate` Hz with input `gain`.
+    ///
+    /// `sample_rate` must be one of [`INPUT_SAMPLE_RATES`]. `gain` is
+    /// passed to `sceAudioInputInit` unchanged; 0 is a safe default.
+    pub fn new(sample_rate: u32, gain: i32) -> Result<Self, RecordError> {
+        input_frequency(sample_rate)?;
+        if crate::model::detect() == Ok(crate::model::PspModel::Psp1000) {
+            return Err(RecordError::NoMicrophone);
+        }
+        if RECORDER_ACTIVE.swap(true, Ordering::AcqRel) {
+            return Err(RecordError::Busy);
+        }
+        let ret = unsafe { crate::sys::sceAudioInputInit(0, gain, 0) };
+        if ret < 0 {
+            RECORDER_ACTIVE.store(false, Ordering::Release);
+            return Err(RecordError::Audio(AudioError(ret)));
+        }
+        Ok(Self {
+            sample_rate,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Fill `buf` with mono samples, blocking until it is full.
+    ///
+    /// At 44100 Hz a 1024-sample buffer takes about 23 ms to fill.
+    pub fn read_blocking(&self, buf: &mut [i16]) -> Result<(), RecordError> {
+        for chunk in buf.chunks_mut(crate::sys::AUDIO_SAMPLE_MAX as usize) {
+            let ret = unsafe {
+                crate::sys::sceAudioInputBlocking(
+                    chunk.len() as i32,
+                    input_frequency(self.sample_rate)?,
+                    chunk.as_mut_ptr() as *mut c_void,
+                )
+            };
+            if ret < 0 {
+                return Err(RecordError::Audio(AudioError(ret)));
+            }
+        }
+        Ok(())
+    }
+
+    /// The sample rate in Hz.
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        unsafe {
+            crate::sys::sceAudioWaitInputEnd();
+        }
+        RECORDER_ACTIVE.store(false, Ordering::Release);
+    }
+}
+
+fn input_frequency(sample_rate: u32) -> Result<crate::sys::AudioInputFrequency, RecordError> {
+    match sample_rate {
+        44100 => Ok(crate::sys::AudioInputFrequency::Khz44_1),
+        22050 => Ok(crate::sys::AudioInputFrequency::Khz22_05),
+        11025 => Ok(crate::sys::AudioInputFrequency::Khz11_025),
+        _ => Err(RecordError::UnsupportedRate(sample_rate)),
+    }
+}
@@ -0,0 +1,409 @@
+//! Streaming playback with an intro and a gapless loop.
+
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicBool, AtomicI32, AtomicU8, Ordering};
+
+use super::{AudioChannel, AudioError, AudioFormat};
+use crate::mp3::Mp3Decoder;
+use crate::thread::{self, JoinHandle, ThreadBuilder, ThreadError};
+
+/// Stereo frames [`PcmSource`] hands out per block.
+const PCM_BLOCK_FRAMES: usize = 1152;
+
+/// How long a paused worker sleeps between checks.
+const POLL_MS: u32 = 10;
+
+/// Worker start-up states, in [`Shared::status`].
+const STARTING: u8 = 0;
+const RUNNING: u8 = 1;
+const FINISHED: u8 = 2;
+
+/// Interleaved stereo PCM, decoded a block at a time.
+pub trait StreamSource: Send {
+    /// The next block of interleaved stereo samples. An empty block ends
+    /// the stream.
+    fn next_block(&mut self) -> &[i16];
+
+    /// Go back to the first frame, returning `false` if the source can't.
+    fn rewind(&mut self) -> bool;
+
+    /// Jump straight to stereo frame `frame`, returning `false` if the
+    /// source can't seek.
+    ///
+    /// The default can't. [`LoopedStream`] then rewinds and decodes its way
+    /// to the frame instead.
+    fn seek(&mut self, frame: usize) -> bool {
+        let _ = frame;
+        false
+    }
+}
+
+/// Decodes frame by frame. A decode error ends the stream. The decoder
+/// can't seek, so looping back to a loop start past the beginning decodes
+/// everything before it again.
+impl StreamSource for Mp3Decoder {
+    fn next_block(&mut self) -> &[i16] {
+        self.decode_frame().unwrap_or(&[])
+    }
+
+    fn rewind(&mut self) -> bool {
+        self.reset().is_ok()
+    }
+}
+
+/// Interleaved stereo samples already in memory.
+pub struct PcmSource {
+    samples: Vec<i16>,
+    /// Index into `samples` of the next block.
+    position: usize,
+}
+
+impl PcmSource {
+    /// Wrap interleaved stereo `samples`. A trailing odd sample is
+    /// ignored.
+    pub fn new(mut samples: Vec<i16>) -> Self {
+        samples.truncate(samples.len() & !1);
+        Self {
+            samples,
+            position: 0,
+        }
+    }
+
+    /// Length in stereo frames.
+    pub fn frames(&self) -> usize {
+        self.samples.len() / 2
+    }
+}
+
+impl StreamSource for PcmSource {
+    fn next_block(&mut self) -> &[i16] {
+        let start = self.position;
+        self.position = (start + PCM_BLOCK_FRAMES * 2).min(self.samples.len());
+        &self.samples[start..self.position]
+    }
+
+    fn rewind(&mut self) -> bool {
+        self.position = 0;
+        true
+    }
+
+    fn seek(&mut self, frame: usize) -> bool {
+        self.position = frame.saturating_mul(2).min(self.samples.len());
+        true
+    }
+}
+
+/// A [`StreamSource`] played once or with a loop region, into buffers of
+/// any size.
+///
+/// With a loop set, the frames before the loop start play once as an
+/// intro, then the region repeats. The frame just before the loop end is
+/// followed directly by the loop start, in the same output buffer, so the
+/// seam adds no silence or repeated frame. Decoded blocks rarely line up
+/// with the loop points or the output buffer, so what's left of a block
+/// is kept for the next [`fill()`](Self::fill).
+///
+/// [`StreamPlayer`] plays one on a thread; `fill` can also feed a
+/// [`Mixer`](crate::audio_mixer::Mixer) channel or custom output.
+pub struct LoopedStream<S> {
+    source: S,
+    looping: bool,
+    loop_start: usize,
+    /// `None` loops at the end of the source.
+    loop_end: Option<usize>,
+    /// Source frame of the next sample `fill` hands out.
+    position: usize,
+    /// After rewinding a source that can't seek, frames before this are
+    /// decoded and dropped.
+    skip_to: usize,
+    /// The current block, from `carry_pos` on not yet handed out.
+    carry: Vec<i16>,
+    carry_pos: usize,
+    finished: bool,
+}
+
+impl<S: StreamSource> LoopedStream<S> {
+    /// Play `source` once, from the start.
+    pub fn new(source: S) -> Self {
+        Self {
+            source,
+            looping: false,
+            loop_start: 0,
+            loop_end: None,
+            position: 0,
+            skip_to: 0,
+            carry: Vec::new(),
+            carry_pos: 0,
+            finished: false,
+        }
+    }
+
+    /// Loop the stereo frames `start..end` after playing the frames before
+    /// `start` once. `None` for `end` loops at the end of the source, as
+    /// does an `end` past it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `end` isn't after `start`.
+    pub fn with_loop(mut self, start: usize, end: Option<usize>) -> Self {
+        assert!(
+            end.is_none_or(|end| end > start),
+            "loop end must be after loop start"
+        );
+        self.looping = true;
+        self.loop_start = start;
+        self.loop_end = end;
+        self
+    }
+
+    /// Fill `out` with interleaved stereo samples and return how many
+    /// were written: all of `out` (rounded down to whole frames) unless the
+    /// stream ended.
+    pub fn fill(&mut self, out: &mut [i16]) -> usize {
+        let want = out.len() & !1;
+        let mut len = 0;
+        while len < want && !self.finished {
+            let loop_end = self.loop_end.filter(|_| self.looping);
+            if loop_end.is_some_and(|end| self.position >= end) {
+                self.wrap();
+                continue;
+            }
+            if self.carry_pos == self.carry.len() {
+                self.pull();
+                continue;
+            }
+            let mut n = (self.carry.len() - self.carry_pos).min(want - len);
+            if let Some(end) = loop_end {
+                n = n.min((end - self.position) * 2);
+            }
+            out[len..len + n].copy_from_slice(&self.carry[self.carry_pos..self.carry_pos + n]);
+            len += n;
+            self.carry_pos += n;
+            self.position += n / 2;
+        }
+        len
+    }
+
+    /// Source frame of the next sample [`fill()`](Self::fill) hands out.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Whether the stream has ended: it doesn't loop and reached the end
+    /// of the source, or its source failed to rewind.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Replace `carry` with the source's next block.
+    fn pull(&mut self) {
+        self.carry.clear();
+        self.carry_pos = 0;
+        let block = self.source.next_block();
+        let block = &block[..block.len() & !1];
+        if block.is_empty() {
+            // Nothing played since the last wrap means the loop region is
+            // empty; give up rather than spin.
+            if self.looping && self.position > self.loop_start {
+                self.wrap();
+            } else {
+                self.finished = true;
+            }
+            return;
+        }
+        let skip = self
+            .skip_to
+            .saturating_sub(self.position)
+            .min(block.len() / 2);
+        self.position += skip;
+        self.carry.extend_from_slice(&block[skip * 2..]);
+    }
+
+    /// Go back to the loop start.
+    fn wrap(&mut self) {
+        self.carry.clear();
+        self.carry_pos = 0;
+        if self.source.seek(self.loop_start) {
+            self.position = self.loop_start;
+            self.skip_to = 0;
+        } else if self.source.rewind() {
+            self.position = 0;
+            self.skip_to = self.loop_start;
+        } else {
+            self.finished = true;
+        }
+    }
+}
+
+/// Error from starting a [`StreamPlayer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamError {
+    /// No hardware audio channel could be reserved.
+    Audio(AudioError),
+    /// The playback thread couldn't be started.
+    Thread(ThreadError),
+}
+
+impl core::fmt::Display for StreamError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Audio(e) => write!(f, "no audio channel for stream: {}", e),
+            Self::Thread(e) => write!(f, "stream thread failed: {}", e),
+        }
+    }
+}
+
+/// State shared between the player and its worker thread.
+struct Shared {
+    /// `STARTING`, `RUNNING` or `FINISHED`.
+    status: AtomicU8,
+    /// Error code from reserving the channel, if that failed.
+    error: AtomicI32,
+    paused: AtomicBool,
+    /// Output volume (0..=0x8000).
+    volume: AtomicI32,
+    quit: AtomicBool,
+}
+
+/// Plays a [`LoopedStream`] through its own [`AudioChannel`], decoding on a
+/// worker thread.
+///
+/// Unlike [`MusicPlayer`](crate::audio_mixer::MusicPlayer) this takes a
+/// hardware channel instead of sharing the mixer, and the loop can start
+/// after an intro. Output is 44.1 kHz with no resampling, so the source
+/// should be encoded at that rate. Dropping the player stops playback and
+/// releases the channel.
+///
+/// A source that can't seek, like [`Mp3Decoder`], decodes the intro again
+/// on every loop, while the previous buffer is still playing. Decoding is
+/// much faster than real time, but a very long intro can still underrun;
+/// keep loop starts within a few seconds or loop from decoded PCM.
+///
+/// # Example
+///
+/// ```ignore
+/// use psp::audio::{LoopedStream, StreamPlayer};
+/// use psp::mp3::Mp3Decoder;
+///
+/// let data = psp::io::read_to_vec("ms0:/music/boss.mp3")?;
+/// // A 2.5 second intro at 44.1 kHz, then the rest of the track forever.
+/// let stream = LoopedStream::new(Mp3Decoder::new(&data)?).with_loop(110_250, None);
+/// let player = StreamPlayer::new(stream, 1024)?;
+/// player.set_volume(0x6000);
+/// ```
+pub struct StreamPlayer {
+    shared: Arc<Shared>,
+    worker: Option<JoinHandle>,
+}
+
+impl StreamPlayer {
+    /// Reserve a stereo channel of `sample_count` frames per output call
+    /// and start playing `stream` on it.
+    pub fn new<S: StreamSource + 'static>(
+        mut stream: LoopedStream<S>,
+        sample_count: i32,
+    ) -> Result<Self, StreamError> {
+        let shared = Arc::new(Shared {
+            status: AtomicU8::new(STARTING),
+            error: AtomicI32::new(0),
+            paused: AtomicBool::new(false),
+            volume: AtomicI32::new(0x8000),
+            quit: AtomicBool::new(false),
+        });
+        let worker_shared = shared.clone();
+        let worker = ThreadBuilder::new(b"stream_player\0")
+            .priority(crate::DEFAULT_THREAD_PRIORITY - 2)
+            .spawn(move || {
+                // `AudioChannel` isn't `Send`, so it's reserved here.
+                let shared = worker_shared;
+                match AudioChannel::reserve(sample_count, AudioFormat::Stereo) {
+                    Ok(channel) => {
+                        shared.status.store(RUNNING, Ordering::Release);
+                        play(&shared, &mut stream, &channel);
+                    },
+                    Err(e) => shared.error.store(e.0, Ordering::Relaxed),
+                }
+                shared.status.store(FINISHED, Ordering::Release);
+                0
+            })
+            .map_err(StreamError::Thread)?;
+
+        while shared.status.load(Ordering::Acquire) == STARTING {
+            thread::sleep_ms(1);
+        }
+        let error = shared.error.load(Ordering::Relaxed);
+        if error != 0 {
+            let _ = worker.join();
+            return Err(StreamError::Audio(AudioError(error)));
+        }
+        Ok(Self {
+            shared,
+            worker: Some(worker),
+        })
+    }
+
+    /// Stop output where it is. The rest of the current buffer still
+    /// plays.
+    pub fn pause(&self) {
+        self.shared.paused.store(true, Ordering::Relaxed);
+    }
+
+    /// Continue after [`pause()`](Self::pause).
+    pub fn resume(&self) {
+        self.shared.paused.store(false, Ordering::Relaxed);
+    }
+
+    /// Whether playback is paused.
+    pub fn is_paused(&self) -> bool {
+        self.shared.paused.load(Ordering::Relaxed)
+    }
+
+    /// Set the output volume (0..=0x8000).
+    pub fn set_volume(&self, volume: i32) {
+        self.shared
+            .volume
+            .store(volume.clamp(0, 0x8000), Ordering::Relaxed);
+    }
+
+    /// The output volume.
+    pub fn volume(&self) -> i32 {
+        self.shared.volume.load(Ordering::Relaxed)
+    }
+
+    /// Whether the stream played to its end or output failed. A looping
+    /// stream only finishes if its source fails.
+    pub fn is_finished(&self) -> bool {
+        self.shared.status.load(Ordering::Acquire) == FINISHED
+    }
+}
+
+impl Drop for StreamPlayer {
+    fn drop(&mut self) {
+        self.shared.quit.store(true, Ordering::Release);
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// The worker's output loop.
+fn play<S: StreamSource>(shared: &Shared, stream: &mut LoopedStream<S>, channel: &AudioChannel) {
+    let mut buf = alloc::vec![0i16; channel.sample_count() as usize * 2];
+    while !shared.quit.load(Ordering::Acquire) {
+        if shared.paused.load(Ordering::Relaxed) {
+            thread::sleep_ms(POLL_MS);
+            continue;
+        }
+        let len = stream.fill(&mut buf);
+        if len == 0 {
+            break;
+        }
+        // The last buffer is padded with silence.
+        buf[len..].fill(0);
+        let volume = shared.volume.load(Ordering::Relaxed);
+        if channel.output_blocking(volume, &buf).is_err() {
+            break;
+        }
+    }
+}
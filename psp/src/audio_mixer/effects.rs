@@ -0,0 +1,240 @@
+//! Per-channel low-pass filter and the shared echo bus.
+//!
+//! Both run inside the mix loop in fixed point; see [`Effect`] for what
+//! they cost.
+
+#[cfg(not(feature = "stub-only"))]
+use alloc::vec::Vec;
+
+/// Fixed-point fractional bits of the low-pass coefficient and the echo
+/// levels.
+const Q15_SHIFT: u32 = 15;
+
+/// 1.0 in Q15.
+const Q15_ONE: i32 = 1 << Q15_SHIFT;
+
+/// Extra fractional bits kept in the filter state, so slow filters still
+/// settle on the input instead of stopping a few samples short.
+const STATE_SHIFT: u32 = 8;
+
+/// Highest echo feedback, so the tail always decays.
+const MAX_FEEDBACK: f32 = 0.99;
+
+/// An effect applied to one channel's samples before they are mixed.
+///
+/// A filter's state is cleared when its channel is allocated, so a
+/// reused channel doesn't start with the tail of the previous sound.
+///
+/// # Cost
+///
+/// The `audio_mixer_bench` CI test measures both effects on the default
+/// 1024-frame buffer (23.2 ms of audio at 44.1 kHz) with all
+/// [`MAX_CHANNELS`](super::MAX_CHANNELS) channels playing, and logs the
+/// time each adds per buffer. It fails if filtering every channel, or
+/// running the echo bus with every channel sending to it, adds more than
+/// 4% of the buffer period.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Effect {
+    /// One-pole low-pass filter, for sounds behind walls or underwater.
+    ///
+    /// `cutoff_normalized` is the cutoff frequency as a fraction of the
+    /// sample rate, from 0.0 (silence) to 0.5 (Nyquist, nearly
+    /// unfiltered). At 44.1 kHz, 0.02 is about 880 Hz. The response falls
+    /// off at 6 dB per octave above the cutoff.
+    LowPass { cutoff_normalized: f32 },
+}
+
+/// Settings for the mixer's shared echo bus, set with
+/// [`Mixer::set_echo`](super::Mixer::set_echo).
+///
+/// Channels feed the bus at their
+/// [`ChannelConfig::echo_send`](super::ChannelConfig::echo_send) level.
+/// The bus repeats what it receives after `delay_samples`, and feeds a
+/// `feedback` share of each repeat back in for further repeats.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EchoSend {
+    /// Delay between repeats in stereo frames. Raised to at least the
+    /// mixer's [`sample_count`](super::Mixer::sample_count).
+    pub delay_samples: usize,
+    /// Share of each repeat fed back into the delay, 0.0..=0.99.
+    pub feedback: f32,
+    /// Level of the repeats in the output, 0.0..=1.0.
+    pub mix: f32,
+}
+
+/// Convert a 0.0..=1.0 level to Q15.
+fn q15(level: f32) -> i32 {
+    (level.clamp(0.0, 1.0) * Q15_ONE as f32) as i32
+}
+
+/// One-pole low-pass filter state for a stereo channel.
+#[derive(Clone, Copy)]
+pub(super) struct LowPass {
+    /// Smoothing coefficient in Q15.
+    coeff: i32,
+    /// Last output per side, with [`STATE_SHIFT`] fractional bits.
+    state: [i32; 2],
+}
+
+impl LowPass {
+    /// Filter state for `effect`, starting from silence.
+    pub(super) fn new(effect: Option<Effect>) -> Option<Self> {
+        let Effect::LowPass { cutoff_normalized } = effect?;
+        Some(Self {
+            coeff: Self::coeff(cutoff_normalized),
+            state: [0; 2],
+        })
+    }
+
+    /// Change the effect of a channel that may be playing. A filter that
+    /// stays a filter keeps its state, so moving the cutoff doesn't click.
+    pub(super) fn update(filter: &mut Option<Self>, effect: Option<Effect>) {
+        match (filter.as_mut(), effect) {
+            (Some(filter), Some(Effect::LowPass { cutoff_normalized })) => {
+                filter.coeff = Self::coeff(cutoff_normalized);
+            },
+            _ => *filter = Self::new(effect),
+        }
+    }
+
+    /// `1 - e^(-2π fc)`, which puts the -3 dB point near `fc` for cutoffs
+    /// well below Nyquist.
+    fn coeff(cutoff_normalized: f32) -> i32 {
+        let fc = cutoff_normalized.clamp(0.0, 0.5);
+        q15(1.0 - libm::expf(-2.0 * core::f32::consts::PI * fc))
+    }
+
+    /// Filter one stereo frame.
+    #[inline]
+    pub(super) fn process(&mut self, left: i32, right: i32) -> (i32, i32) {
+        (self.step(0, left), self.step(1, right))
+    }
+
+    #[inline]
+    fn step(&mut self, side: usize, x: i32) -> i32 {
+        let y = self.state[side];
+        let delta = ((x << STATE_SHIFT) - y) as i64 * self.coeff as i64;
+        let y = y + (delta >> Q15_SHIFT) as i32;
+        self.state[side] = y;
+        y >> STATE_SHIFT
+    }
+}
+
+/// The echo delay line, owned by the [`Mixer`](super::Mixer).
+#[cfg(not(feature = "stub-only"))]
+pub(super) struct EchoBus {
+    settings: EchoSend,
+    /// Interleaved stereo frames, `delay_samples` long.
+    ring: Vec<i32>,
+    /// Frame of `ring` that lines up with the next output frame.
+    pos: usize,
+}
+
+#[cfg(not(feature = "stub-only"))]
+impl EchoBus {
+    /// A silent delay line for `settings`, at least `min_delay` frames
+    /// long.
+    pub(super) fn new(settings: EchoSend, min_delay: usize) -> Self {
+        let delay = settings.delay_samples.max(min_delay).max(1);
+        Self {
+            settings: EchoSend {
+                delay_samples: delay,
+                feedback: settings.feedback.clamp(0.0, MAX_FEEDBACK),
+                mix: settings.mix.clamp(0.0, 1.0),
+            },
+            ring: alloc::vec![0; delay * 2],
+            pos: 0,
+        }
+    }
+
+    /// The settings in effect, after clamping.
+    pub(super) fn settings(&self) -> EchoSend {
+        self.settings
+    }
+
+    /// Change levels without clearing the echoes already in the line.
+    /// Returns `false` if the delay differs, which needs a new line.
+    pub(super) fn retune(&mut self, settings: EchoSend, min_delay: usize) -> bool {
+        let replacement = Self::new(settings, min_delay);
+        if replacement.settings.delay_samples != self.settings.delay_samples {
+            return false;
+        }
+        self.settings = replacement.settings;
+        true
+    }
+
+    /// Borrow the line for one [`mix_channels`](super::mix_channels) call.
+    pub(super) fn line(&mut self) -> EchoLine<'_> {
+        EchoLine {
+            ring: &mut self.ring,
+            pos: &mut self.pos,
+            feedback: q15(self.settings.feedback),
+            mix: q15(self.settings.mix),
+        }
+    }
+}
+
+/// An echo delay line as [`mix_channels`](super::mix_channels) uses it.
+///
+/// Each mix first plays the repeats due in the buffer and scales them
+/// down by the feedback, then channels add their sends to the same
+/// frames, which come round again one delay later. This needs the delay
+/// to be at least one buffer long.
+#[cfg_attr(feature = "stub-only", allow(dead_code))]
+pub(super) struct EchoLine<'a> {
+    ring: &'a mut [i32],
+    pos: &'a mut usize,
+    feedback: i32,
+    mix: i32,
+}
+
+#[cfg_attr(feature = "stub-only", allow(dead_code))]
+impl EchoLine<'_> {
+    /// Whether a buffer of `frames` output frames fits in the delay, so
+    /// no frame of it is sent before it has been played.
+    pub(super) fn fits(&self, frames: usize) -> bool {
+        frames <= self.ring.len() / 2
+    }
+
+    /// Index in `ring` of the left sample for output frame `frame`, which
+    /// [`fits`](Self::fits) the delay.
+    #[inline]
+    fn index(&self, frame: usize) -> usize {
+        let len = self.ring.len() / 2;
+        let frame = *self.pos + frame;
+        // Avoid a division per sample: both terms are below `len`.
+        let frame = if frame >= len { frame - len } else { frame };
+        frame * 2
+    }
+
+    /// Add the repeats due in `output` at `master_vol`, and keep the
+    /// feedback share of them in the line.
+    pub(super) fn play(&mut self, output: &mut [i16], master_vol: i32) {
+        for (frame, out) in output.chunks_exact_mut(2).enumerate() {
+            let index = self.index(frame);
+            for (side, out) in out.iter_mut().enumerate() {
+                let delayed = self.ring[index + side];
+                let wet =
+                    ((delayed as i64 * self.mix as i64) >> Q15_SHIFT) * master_vol as i64 / 0x8000;
+                *out = out.saturating_add(wet.clamp(i16::MIN as i64, i16::MAX as i64) as i16);
+                self.ring[index + side] =
+                    ((delayed as i64 * self.feedback as i64) >> Q15_SHIFT) as i32;
+            }
+        }
+    }
+
+    /// Add a channel's output frame `frame` to the line at `level`
+    /// (0..=0x8000).
+    #[inline]
+    pub(super) fn send(&mut self, frame: usize, left: i32, right: i32, level: i32) {
+        let index = self.index(frame);
+        let ring = &mut self.ring[index..index + 2];
+        ring[0] = ring[0].saturating_add((left as i64 * level as i64 / 0x8000) as i32);
+        ring[1] = ring[1].saturating_add((right as i64 * level as i64 / 0x8000) as i32);
+    }
+
+    /// Move past the `frames` just mixed.
+    pub(super) fn advance(&mut self, frames: usize) {
+        *self.pos = (*self.pos + frames) % (self.ring.len() / 2);
+    }
+}
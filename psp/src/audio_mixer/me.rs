@@ -0,0 +1,311 @@
+//! Media Engine offload for [`Mixer::mix_into`].
+//!
+//! The channel array and two output buffers live in one uncached block of
+//! ME-accessible memory. Access to the channels is arbitrated by an owner
+//! flag in the same block instead of the mixer's [`SpinMutex`]:
+//!
+//! - CPU threads take the channels by swapping the flag from `FREE` to
+//!   `CPU`, and put it back to `FREE` when done.
+//! - The audio thread hands the channels to the ME by swapping `FREE` to
+//!   `ME` before submitting a mix job; the ME sets it back to `FREE` when
+//!   the job finishes.
+//!
+//! Only the CPU ever performs the compare-and-swap, and the ME only writes
+//! the flag while it owns it, so no atomic read-modify-write is needed
+//! across the two cores.
+
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use super::{Channel, MAX_CHANNELS, Mixer, MixerError, mix_channels};
+use crate::me::{MeExecutor, MeHandle};
+use crate::sync::SpinMutex;
+
+/// No one holds the shared channels.
+const OWNER_FREE: u32 = 0;
+/// A CPU thread holds the shared channels.
+const OWNER_CPU: u32 = 1;
+/// The ME is mixing with the shared channels.
+const OWNER_ME: u32 = 2;
+
+/// State shared with the ME mix task, in uncached memory.
+#[repr(C, align(64))]
+struct MeMixState {
+    /// `OWNER_*` value.
+    owner: AtomicU32,
+    /// Master volume for the job being mixed.
+    master_volume: i32,
+    /// Output buffer the job mixes into.
+    target: *mut i16,
+    /// Length of each output buffer in `i16`s.
+    output_len: usize,
+    channels: [Channel; MAX_CHANNELS],
+}
+
+/// CPU-side handle to the offload state of a [`Mixer`].
+pub(super) struct MeOffload {
+    /// Uncached pointer to the shared state.
+    state: *mut MeMixState,
+    /// Block holding the shared state and both output buffers.
+    block: crate::sys::SceUid,
+    executor: *mut MeExecutor,
+    /// Uncached output buffers, mixed into alternately.
+    outputs: [*mut i16; 2],
+    output_len: usize,
+    /// The job in flight and the index of the buffer it mixes into.
+    pending: SpinMutex<Option<(MeHandle, usize)>>,
+}
+
+/// Exclusive CPU access to the shared channels. Releases the owner flag
+/// when dropped.
+pub(super) struct SharedGuard<'a> {
+    offload: &'a MeOffload,
+}
+
+impl core::ops::Deref for SharedGuard<'_> {
+    type Target = [Channel; MAX_CHANNELS];
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: We hold the owner flag.
+        unsafe { &(*self.offload.state).channels }
+    }
+}
+
+impl core::ops::DerefMut for SharedGuard<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        // SAFETY: We hold the owner flag exclusively.
+        unsafe { &mut (*self.offload.state).channels }
+    }
+}
+
+impl Drop for SharedGuard<'_> {
+    fn drop(&mut self) {
+        self.offload.owner().store(OWNER_FREE, Ordering::Release);
+    }
+}
+
+impl MeOffload {
+    fn owner(&self) -> &AtomicU32 {
+        // SAFETY: `state` stays allocated for the lifetime of `self`.
+        unsafe { &(*self.state).owner }
+    }
+
+    /// Take the shared channels for the calling CPU thread, spinning while
+    /// another thread or the ME holds them.
+    pub(super) fn lock(&self) -> SharedGuard<'_> {
+        while self
+            .owner()
+            .compare_exchange_weak(OWNER_FREE, OWNER_CPU, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            core::hint::spin_loop();
+        }
+        SharedGuard { offload: self }
+    }
+
+    /// Hand out the buffer the ME mixed during the previous call and queue
+    /// the next one.
+    ///
+    /// Mixes on the CPU instead when no ME buffer is ready, either because
+    /// the executor was busy with another task or because `output` doesn't
+    /// match the buffer size.
+    pub(super) fn mix_into(&self, output: &mut [i16], master_vol: i32) {
+        let mut pending = self.pending.lock();
+        if output.len() != self.output_len {
+            self.finish(pending.take());
+            mix_channels(&mut self.lock(), master_vol, output, None);
+            return;
+        }
+
+        let next = match self.finish(pending.take()) {
+            Some(index) => {
+                // SAFETY: The job that wrote this buffer has finished, and
+                // the lengths match.
+                unsafe {
+                    core::ptr::copy_nonoverlapping(
+                        self.outputs[index],
+                        output.as_mut_ptr(),
+                        self.output_len,
+                    );
+                }
+                index ^ 1
+            },
+            None => {
+                mix_channels(&mut self.lock(), master_vol, output, None);
+                0
+            },
+        };
+        *pending = self.submit(next, master_vol);
+    }
+
+    /// Wait for `job` to finish and return the index of its buffer.
+    fn finish(&self, job: Option<(MeHandle, usize)>) -> Option<usize> {
+        let (handle, index) = job?;
+        // SAFETY: See `Mixer::enable_me_offload`.
+        let executor = unsafe { &mut *self.executor };
+        executor.wait(&handle);
+        executor.reset();
+        Some(index)
+    }
+
+    /// Start mixing into output buffer `index` on the ME, unless the
+    /// executor or the channels are busy.
+    fn submit(&self, index: usize, master_vol: i32) -> Option<(MeHandle, usize)> {
+        // SAFETY: See `Mixer::enable_me_offload`.
+        let executor = unsafe { &mut *self.executor };
+        if !executor.is_idle() {
+            return None;
+        }
+        // Don't wait for a control method holding the channels; the next
+        // buffer is mixed on the CPU instead.
+        self.owner()
+            .compare_exchange(OWNER_FREE, OWNER_ME, Ordering::Acquire, Ordering::Relaxed)
+            .ok()?;
+
+        // SAFETY: The ME task is not running and we own the channels, so
+        // nothing else touches the shared state. `submit` writes back the
+        // data cache, which makes queued sample buffers visible to the ME.
+        unsafe {
+            core::ptr::write_volatile(&raw mut (*self.state).master_volume, master_vol);
+            core::ptr::write_volatile(&raw mut (*self.state).target, self.outputs[index]);
+            let handle = executor.submit(psp_audio_mixer_me_task, self.state as i32);
+            Some((handle, index))
+        }
+    }
+}
+
+/// Mix job run on the Media Engine.
+///
+/// Makes no syscalls and only touches uncached memory: the channel array
+/// is copied in and out with volatile accesses, and sample data is read
+/// through uncached addresses so stale lines in the ME's cache can't be
+/// observed.
+#[unsafe(no_mangle)]
+unsafe extern "C" fn psp_audio_mixer_me_task(state_addr: i32) -> i32 {
+    let state = state_addr as *mut MeMixState;
+    unsafe {
+        let mut channels = core::ptr::read_volatile(&raw const (*state).channels);
+        let master_vol = core::ptr::read_volatile(&raw const (*state).master_volume);
+        let target = core::ptr::read_volatile(&raw const (*state).target);
+        let output_len = core::ptr::read_volatile(&raw const (*state).output_len);
+
+        for ch in channels.iter_mut() {
+            ch.buffer = remap(ch.buffer, crate::me::to_uncached);
+            ch.next = remap(ch.next, crate::me::to_uncached);
+        }
+
+        mix_channels(
+            &mut channels,
+            master_vol,
+            core::slice::from_raw_parts_mut(target, output_len),
+            None,
+        );
+
+        // Hand the CPU cached slices again. A queued buffer may have
+        // become the current one, so map whatever is there now.
+        for ch in channels.iter_mut() {
+            ch.buffer = remap(ch.buffer, crate::me::to_cached);
+            ch.next = remap(ch.next, crate::me::to_cached);
+        }
+
+        core::ptr::write_volatile(&raw mut (*state).channels, channels);
+        (*state).owner.store(OWNER_FREE, Ordering::Release);
+    }
+    0
+}
+
+/// Rebuild `samples` at the address `map` gives its start, to switch
+/// between the cached and uncached views. Empty slices are left alone.
+unsafe fn remap(samples: &'static [i16], map: fn(*mut i16) -> *mut i16) -> &'static [i16] {
+    if samples.is_empty() {
+        return samples;
+    }
+    unsafe { core::slice::from_raw_parts(map(samples.as_ptr() as *mut i16), samples.len()) }
+}
+
+impl Mixer {
+    /// Offload mixing to the Media Engine.
+    ///
+    /// Moves the channel state into uncached ME-accessible memory along
+    /// with two output buffers of [`sample_count`](Self::sample_count)
+    /// stereo frames. From then on [`mix_into`](Self::mix_into) mixes one
+    /// buffer ahead on the ME and falls back to the CPU whenever
+    /// `executor` is busy with another task, so audio never stalls. All
+    /// channel control methods keep working as before.
+    ///
+    /// The executor's stack must hold a copy of the channel array on top
+    /// of the mix loop's own frame; 8 KiB is plenty.
+    ///
+    /// # Safety
+    ///
+    /// - `executor` must not be moved or dropped until offload is turned
+    ///   off with [`disable_me_offload`](Self::disable_me_offload) or the
+    ///   mixer is dropped.
+    /// - Other code may submit tasks to `executor` between `mix_into`
+    ///   calls, but must collect them and call [`MeExecutor::reset`]
+    ///   before the next `mix_into`, and must never touch the executor
+    ///   while a mix job is running.
+    pub unsafe fn enable_me_offload(
+        &mut self,
+        executor: &mut MeExecutor,
+    ) -> Result<(), MixerError> {
+        if self.me.is_some() || !executor.is_idle() {
+            return Err(MixerError::AlreadyRunning);
+        }
+
+        let output_len = self.sample_count as usize * 2;
+        let state_size = core::mem::size_of::<MeMixState>();
+        let size = state_size + 2 * output_len * core::mem::size_of::<i16>();
+        // SAFETY: Kernel mode is required by the `kernel` feature.
+        let (ptr, block) = unsafe { crate::me::me_alloc(size as u32, b"MixerMeState\0".as_ptr()) }
+            .map_err(MixerError::MeOffload)?;
+
+        let state = ptr as *mut MeMixState;
+        // SAFETY: The block is large enough for the state followed by both
+        // buffers, and partition blocks are suitably aligned.
+        let outputs = unsafe {
+            let first = ptr.add(state_size) as *mut i16;
+            [first, first.add(output_len)]
+        };
+        unsafe {
+            state.write(MeMixState {
+                owner: AtomicU32::new(OWNER_FREE),
+                master_volume: 0,
+                target: outputs[0],
+                output_len,
+                channels: *self.channels.lock(),
+            });
+        }
+
+        self.me = Some(MeOffload {
+            state,
+            block,
+            executor,
+            outputs,
+            output_len,
+            pending: SpinMutex::new(None),
+        });
+        Ok(())
+    }
+
+    /// Return mixing to the CPU, undoing
+    /// [`enable_me_offload`](Self::enable_me_offload).
+    ///
+    /// Waits for an in-flight mix job and moves the channel state back.
+    /// The buffer the ME mixed ahead is discarded. Does nothing if offload
+    /// is not enabled.
+    pub fn disable_me_offload(&mut self) {
+        let Some(offload) = self.me.take() else {
+            return;
+        };
+        offload.finish(offload.pending.lock().take());
+        *self.channels.lock() = *offload.lock();
+        // SAFETY: The ME is idle and nothing references the block anymore.
+        unsafe {
+            crate::sys::sceKernelFreePartitionMemory(offload.block);
+        }
+    }
+
+    /// Whether mixing is offloaded to the Media Engine.
+    pub fn is_me_offloaded(&self) -> bool {
+        self.me.is_some()
+    }
+}
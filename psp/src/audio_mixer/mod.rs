@@ -0,0 +1,1104 @@
+//! Audio mixing engine for the PSP.
+//!
+//! Provides a multi-channel PCM audio mixer that can run on the main CPU
+//! or (in kernel mode) offload mixing to the Media Engine. The mixer
+//! accepts PCM streams from multiple sources, handles volume, panning,
+//! and fade in/out, and writes mixed output to the PSP audio hardware.
+//!
+//! # Architecture
+//!
+//! The mixer uses a double-buffered approach:
+//! 1. The main CPU submits audio data to channels
+//! 2. The mixing callback reads all active channels, mixes them, and
+//!    writes to the output buffer
+//! 3. The output buffer is submitted to the PSP audio hardware via
+//!    `sceAudioOutputBlocking`
+//!
+//! # Example
+//!
+//! ```ignore
+//! use psp::audio_mixer::{Mixer, ChannelConfig};
+//!
+//! let mut mixer = Mixer::new(1024).unwrap();
+//!
+//! let ch = mixer.alloc_channel(ChannelConfig {
+//!     volume_left: 0x6000,
+//!     volume_right: 0x6000,
+//!     ..Default::default()
+//! }).unwrap();
+//!
+//! mixer.submit_samples(ch, &pcm_data);
+//! mixer.start();
+//! ```
+//!
+//! # Music
+//!
+//! [`MusicPlayer`] decodes an MP3 on a worker thread and streams it into a
+//! channel through [`Mixer::queue_samples`], so a background track plays
+//! alongside sound effects.
+//!
+//! A looping channel can repeat just part of its buffer: with
+//! [`ChannelConfig::loop_start`] and [`ChannelConfig::loop_end`] set (or
+//! [`Mixer::set_loop_region`]), the frames before `loop_start` play once
+//! as an intro and the region after it repeats without a gap. Tracks too
+//! long to keep decoded in memory can do the same with
+//! [`StreamPlayer`](crate::audio::StreamPlayer).
+//!
+//! # Sound effects
+//!
+//! Short sounds don't need a channel of their own:
+//! [`Mixer::play_one_shot`] grabs a free channel, plays the buffer once
+//! and returns the channel to [`ChannelState::Free`] when it finishes.
+//! When every channel is busy the oldest non-looping sound is stolen (see
+//! [`StealPolicy`]). Tags let game code stop a group of sounds at once.
+//!
+//! ```ignore
+//! const TAG_FOOTSTEPS: u32 = 1;
+//!
+//! mixer.play_one_shot_tagged(&STEP_PCM, 0x6000, -0.5, TAG_FOOTSTEPS)?;
+//! // ... later, when the player stops walking:
+//! mixer.stop_tag(TAG_FOOTSTEPS);
+//! ```
+//!
+//! # Effects
+//!
+//! Each channel can run an [`Effect`] on its samples, such as a low-pass
+//! filter to muffle a sound behind a wall, and send part of its output to
+//! an echo bus shared by all channels ([`Mixer::set_echo`]). Both are
+//! cheap enough to use on every channel; see [`Effect`] for the costs.
+//!
+//! ```ignore
+//! use psp::audio_mixer::{ChannelConfig, EchoSend, Effect};
+//!
+//! mixer.set_echo(Some(EchoSend { delay_samples: 11025, feedback: 0.4, mix: 0.5 }));
+//! let ch = mixer.alloc_channel(ChannelConfig {
+//!     effect: Some(Effect::LowPass { cutoff_normalized: 0.02 }),
+//!     echo_send: 0x4000,
+//!     ..Default::default()
+//! })?;
+//! ```
+//!
+//! # Media Engine offload
+//!
+//! In kernel mode, [`Mixer::enable_me_offload`] moves the mixing work to
+//! the Media Engine. The audio thread keeps calling [`Mixer::mix_into`]
+//! and [`Mixer::output_blocking`] as before, but `mix_into` now only copies
+//! a buffer the ME mixed ahead of time and queues the next one, so the
+//! CPU spends its time blocked in `sceAudioOutputPannedBlocking` rather
+//! than mixing. This adds one buffer of latency to channel changes.
+//!
+//! ```ignore
+//! let mut executor = psp::me::MeExecutor::new(8192).unwrap();
+//! unsafe { mixer.enable_me_offload(&mut executor) }.unwrap();
+//!
+//! let mut buf = [0i16; DEFAULT_SAMPLE_COUNT as usize * 2];
+//! loop {
+//!     mixer.mix_into(&mut buf);
+//!     mixer.output_blocking(&buf).unwrap();
+//! }
+//! ```
+
+mod effects;
+#[cfg(all(target_os = "psp", feature = "kernel"))]
+mod me;
+#[cfg(not(feature = "stub-only"))]
+mod music;
+
+pub use effects::{EchoSend, Effect};
+#[cfg(not(feature = "stub-only"))]
+pub use music::{MusicError, MusicPlayer, PlayerState};
+
+use crate::sync::{SpinGuard, SpinMutex};
+use core::sync::atomic::{AtomicI32, AtomicU8, AtomicU32, Ordering};
+#[cfg(not(feature = "stub-only"))]
+use effects::EchoBus;
+use effects::{EchoLine, LowPass};
+
+/// Maximum number of mixer channels.
+pub const MAX_CHANNELS: usize = 8;
+
+/// Default sample count per audio output call (must be 64-aligned).
+pub const DEFAULT_SAMPLE_COUNT: i32 = 1024;
+
+/// Channel state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ChannelState {
+    /// Channel is free and can be allocated. One-shot channels return
+    /// here automatically when they finish.
+    Free = 0,
+    /// Channel is allocated but has no data queued.
+    Idle = 1,
+    /// Channel is actively playing audio.
+    Playing = 2,
+    /// Channel is fading out and will become idle when done.
+    FadingOut = 3,
+}
+
+/// Configuration for a mixer channel.
+#[derive(Debug, Clone, Copy)]
+pub struct ChannelConfig {
+    /// Left channel volume (0..=0x8000).
+    pub volume_left: i32,
+    /// Right channel volume (0..=0x8000).
+    pub volume_right: i32,
+    /// Whether to loop when the buffer runs out.
+    pub looping: bool,
+    /// Stereo frame the loop jumps back to. Playback always starts at
+    /// frame 0, so everything before this plays once as an intro.
+    pub loop_start: usize,
+    /// Frame at which a looping channel jumps back to
+    /// [`loop_start`](Self::loop_start), exclusive. 0 means the end of the
+    /// buffer.
+    pub loop_end: usize,
+    /// Effect applied to the channel's samples before volume and fades.
+    pub effect: Option<Effect>,
+    /// Level sent to the echo bus set up with [`Mixer::set_echo`]
+    /// (0..=0x8000), after volume and fades. 0 sends nothing.
+    pub echo_send: i32,
+}
+
+impl ChannelConfig {
+    /// The loop region `(start, end)` within a buffer of `frames` stereo
+    /// frames. An end past the buffer is clamped to it, and a start at or
+    /// past the end loops the whole buffer.
+    fn loop_region(&self, frames: usize) -> (usize, usize) {
+        let end = match self.loop_end {
+            0 => frames,
+            end => end.min(frames),
+        };
+        let start = if self.loop_start < end {
+            self.loop_start
+        } else {
+            0
+        };
+        (start, end)
+    }
+}
+
+impl Default for ChannelConfig {
+    fn default() -> Self {
+        Self {
+            volume_left: 0x8000,
+            volume_right: 0x8000,
+            looping: false,
+            loop_start: 0,
+            loop_end: 0,
+            effect: None,
+            echo_send: 0,
+        }
+    }
+}
+
+/// A handle to a mixer channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChannelHandle(pub u8);
+
+/// What [`Mixer::play_one_shot`] does when no channel is free.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(u8)]
+pub enum StealPolicy {
+    /// Cut off the non-looping sound that started longest ago and reuse
+    /// its channel. Looping channels and channels with a buffer
+    /// [queued](Mixer::queue_samples) are never stolen.
+    ///
+    /// A channel from [`Mixer::alloc_channel`] is only lent: it stays
+    /// allocated to its owner, and when the one-shot ends (or the owner
+    /// submits new samples) its own config and tag come back and it is
+    /// `Idle`.
+    #[default]
+    StealOldest = 0,
+    /// Fail with [`MixerError::NoFreeChannels`].
+    Refuse = 1,
+}
+
+/// Full-volume value for the fade multiplier.
+const FADE_MAX: i32 = 256;
+
+/// Fixed-point fractional bits for fade arithmetic (16.16).
+const FADE_FP_SHIFT: i32 = 16;
+
+/// Full volume in fixed-point representation (`256 << 16`).
+const FADE_MAX_FP: i32 = FADE_MAX << FADE_FP_SHIFT;
+
+/// Per-channel state stored in the mixer.
+#[derive(Clone, Copy)]
+struct Channel {
+    state: ChannelState,
+    config: ChannelConfig,
+    /// State of the [`Effect::LowPass`] in `config`, if any.
+    filter: Option<LowPass>,
+    /// PCM sample buffer (interleaved stereo i16: L, R, L, R, ...)
+    buffer: &'static [i16],
+    /// Buffer queued to play once `buffer` runs out (empty = none).
+    next: &'static [i16],
+    /// Skipped by the mixer without advancing while set.
+    paused: bool,
+    /// Current read position in the buffer (in samples, not bytes).
+    position: usize,
+    /// Fade volume multiplier in 16.16 fixed-point (0..=FADE_MAX_FP).
+    fade_level: i32,
+    /// Fade step per output frame in 16.16 fixed-point (negative = fade out).
+    fade_step: i32,
+    /// Started by `play_one_shot`; freed automatically when done.
+    one_shot: bool,
+    /// Owner's config and tag while an allocated channel is lent to a
+    /// stolen one-shot.
+    lent: Option<(ChannelConfig, u32)>,
+    /// User tag for grouping channels (0 = untagged).
+    tag: u32,
+    /// Value of `Mixer::play_seq` when playback started, for picking the
+    /// oldest voice to steal.
+    started: u32,
+}
+
+impl Channel {
+    const fn new() -> Self {
+        Self {
+            state: ChannelState::Free,
+            config: ChannelConfig {
+                volume_left: 0x8000,
+                volume_right: 0x8000,
+                looping: false,
+                loop_start: 0,
+                loop_end: 0,
+                effect: None,
+                echo_send: 0,
+            },
+            filter: None,
+            buffer: &[],
+            next: &[],
+            paused: false,
+            position: 0,
+            fade_level: FADE_MAX_FP,
+            fade_step: 0,
+            one_shot: false,
+            lent: None,
+            tag: 0,
+            started: 0,
+        }
+    }
+
+    /// Stop playback. One-shots are released, other channels stay
+    /// allocated and become idle.
+    fn stop(&mut self) {
+        if self.one_shot {
+            *self = Self::new();
+        } else {
+            self.reclaim();
+            self.state = ChannelState::Idle;
+            self.position = 0;
+            self.next = &[];
+        }
+    }
+
+    /// Give a lent channel back to its owner's config and tag.
+    fn reclaim(&mut self) {
+        if let Some((config, tag)) = self.lent.take() {
+            self.config = config;
+            self.filter = LowPass::new(config.effect);
+            self.tag = tag;
+            self.fade_level = FADE_MAX_FP;
+            self.fade_step = 0;
+        }
+    }
+
+    /// Whether [`StealPolicy::StealOldest`] may cut this channel off.
+    fn stealable(&self) -> bool {
+        matches!(self.state, ChannelState::Playing | ChannelState::FadingOut)
+            && !self.config.looping
+            && self.next.is_empty()
+    }
+}
+
+/// Multi-channel PCM audio mixer.
+///
+/// Manages up to [`MAX_CHANNELS`] concurrent audio streams and mixes
+/// them into a single stereo output buffer for the PSP audio hardware.
+pub struct Mixer {
+    channels: SpinMutex<[Channel; MAX_CHANNELS]>,
+    /// Number of samples per output call (64-aligned).
+    sample_count: i32,
+    /// Hardware channel ID from sceAudioChReserve.
+    hw_channel: AtomicI32,
+    /// Master volume (0..=0x8000).
+    master_volume: AtomicU32,
+    /// [`StealPolicy`] as `u8`.
+    steal_policy: AtomicU8,
+    /// Incremented each time playback starts on a channel.
+    play_seq: AtomicU32,
+    /// Delay line for [`set_echo`](Self::set_echo). Locked before
+    /// `channels` when both are needed.
+    #[cfg(not(feature = "stub-only"))]
+    echo: SpinMutex<Option<EchoBus>>,
+    /// Shared state with the Media Engine while mixing is offloaded. The
+    /// channels then live there instead of in `channels`.
+    #[cfg(all(target_os = "psp", feature = "kernel"))]
+    me: Option<me::MeOffload>,
+}
+
+// SAFETY: Mixer uses internal synchronization (SpinMutex, the ME owner
+// flag, and atomics).
+unsafe impl Sync for Mixer {}
+unsafe impl Send for Mixer {}
+
+/// Error type for mixer operations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MixerError {
+    /// No free channels available.
+    NoFreeChannels,
+    /// The specified channel handle is invalid.
+    InvalidChannel,
+    /// The PSP audio hardware returned an error.
+    AudioError(i32),
+    /// The mixer is already running.
+    AlreadyRunning,
+    /// Memory for Media Engine offload could not be allocated.
+    MeOffload(i32),
+    /// The channel already has a buffer queued behind the playing one.
+    QueueFull,
+}
+
+/// Exclusive access to the channel array, wherever it currently lives.
+enum ChannelsGuard<'a> {
+    /// The mixer's own array, mixed on the CPU.
+    Local(SpinGuard<'a, [Channel; MAX_CHANNELS]>),
+    /// The array in memory shared with the Media Engine.
+    #[cfg(all(target_os = "psp", feature = "kernel"))]
+    Shared(me::SharedGuard<'a>),
+}
+
+impl core::ops::Deref for ChannelsGuard<'_> {
+    type Target = [Channel; MAX_CHANNELS];
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Self::Local(guard) => guard,
+            #[cfg(all(target_os = "psp", feature = "kernel"))]
+            Self::Shared(guard) => guard,
+        }
+    }
+}
+
+impl core::ops::DerefMut for ChannelsGuard<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match self {
+            Self::Local(guard) => guard,
+            #[cfg(all(target_os = "psp", feature = "kernel"))]
+            Self::Shared(guard) => guard,
+        }
+    }
+}
+
+impl Mixer {
+    /// Create a new mixer with the given sample count per output call.
+    ///
+    /// `sample_count` must be between 64 and 65472, aligned to 64.
+    /// Use [`DEFAULT_SAMPLE_COUNT`] (1024) for a good balance of
+    /// latency and efficiency.
+    pub fn new(sample_count: i32) -> Result<Self, MixerError> {
+        let sample_count = crate::sys::audio_sample_align(sample_count);
+
+        Ok(Self {
+            channels: SpinMutex::new([const { Channel::new() }; MAX_CHANNELS]),
+            sample_count,
+            hw_channel: AtomicI32::new(-1),
+            master_volume: AtomicU32::new(0x8000),
+            steal_policy: AtomicU8::new(StealPolicy::StealOldest as u8),
+            play_seq: AtomicU32::new(0),
+            #[cfg(not(feature = "stub-only"))]
+            echo: SpinMutex::new(None),
+            #[cfg(all(target_os = "psp", feature = "kernel"))]
+            me: None,
+        })
+    }
+
+    /// Lock the channel array for the calling CPU thread.
+    fn lock_channels(&self) -> ChannelsGuard<'_> {
+        #[cfg(all(target_os = "psp", feature = "kernel"))]
+        if let Some(offload) = &self.me {
+            return ChannelsGuard::Shared(offload.lock());
+        }
+        ChannelsGuard::Local(self.channels.lock())
+    }
+
+    /// Allocate a mixer channel with the given configuration.
+    ///
+    /// Returns a [`ChannelHandle`] for submitting samples and controlling
+    /// the channel.
+    pub fn alloc_channel(&self, config: ChannelConfig) -> Result<ChannelHandle, MixerError> {
+        let mut channels = self.lock_channels();
+        for (i, ch) in channels.iter_mut().enumerate() {
+            if ch.state == ChannelState::Free {
+                ch.state = ChannelState::Idle;
+                ch.config = config;
+                ch.filter = LowPass::new(config.effect);
+                ch.buffer = &[];
+                ch.next = &[];
+                ch.paused = false;
+                ch.position = 0;
+                ch.fade_level = FADE_MAX_FP;
+                ch.fade_step = 0;
+                ch.one_shot = false;
+                ch.lent = None;
+                ch.tag = 0;
+                return Ok(ChannelHandle(i as u8));
+            }
+        }
+        Err(MixerError::NoFreeChannels)
+    }
+
+    /// Free a mixer channel.
+    pub fn free_channel(&self, handle: ChannelHandle) -> Result<(), MixerError> {
+        let mut channels = self.lock_channels();
+        let ch = channels
+            .get_mut(handle.0 as usize)
+            .ok_or(MixerError::InvalidChannel)?;
+        *ch = Channel::new();
+        Ok(())
+    }
+
+    /// Submit PCM samples to a channel, replacing anything playing or
+    /// queued on it.
+    ///
+    /// `samples` must be interleaved stereo i16 data (L, R, L, R, ...).
+    /// The buffer must live for at least as long as the channel is playing
+    /// (use `'static` lifetime or ensure it's pinned).
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure `samples` remains valid for the lifetime of
+    /// playback. Passing stack-allocated data will cause use-after-free.
+    pub unsafe fn submit_samples(
+        &self,
+        handle: ChannelHandle,
+        samples: &'static [i16],
+    ) -> Result<(), MixerError> {
+        // Buffer must have an even number of elements (interleaved stereo pairs).
+        if samples.len() % 2 != 0 {
+            return Err(MixerError::AudioError(-1));
+        }
+        let mut channels = self.lock_channels();
+        let ch = channels
+            .get_mut(handle.0 as usize)
+            .ok_or(MixerError::InvalidChannel)?;
+        if ch.state == ChannelState::Free {
+            return Err(MixerError::InvalidChannel);
+        }
+        ch.reclaim();
+        ch.buffer = samples;
+        ch.next = &[];
+        ch.position = 0;
+        ch.state = ChannelState::Playing;
+        ch.started = self.play_seq.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Queue PCM samples to play on a channel without a gap after the
+    /// current buffer, for streaming decoded audio.
+    ///
+    /// An idle channel starts playing `samples` right away. A playing
+    /// channel holds one queued buffer behind the current one; while that
+    /// slot is taken this fails with [`MixerError::QueueFull`]. Poll
+    /// [`queued`](Self::queued) to see when a buffer has been consumed and
+    /// can be refilled. Looping channels never reach a queued buffer.
+    ///
+    /// # Safety
+    ///
+    /// Same as [`submit_samples`](Self::submit_samples): `samples` must
+    /// stay valid until the channel has finished with it.
+    pub unsafe fn queue_samples(
+        &self,
+        handle: ChannelHandle,
+        samples: &'static [i16],
+    ) -> Result<(), MixerError> {
+        if samples.len() % 2 != 0 {
+            return Err(MixerError::AudioError(-1));
+        }
+        let mut channels = self.lock_channels();
+        let ch = channels
+            .get_mut(handle.0 as usize)
+            .ok_or(MixerError::InvalidChannel)?;
+        if ch.lent.is_some() {
+            // Cut off the one-shot borrowing the channel.
+            ch.stop();
+        }
+        match ch.state {
+            ChannelState::Free => Err(MixerError::InvalidChannel),
+            ChannelState::Idle => {
+                ch.buffer = samples;
+                ch.position = 0;
+                ch.state = ChannelState::Playing;
+                ch.started = self.play_seq.fetch_add(1, Ordering::Relaxed);
+                Ok(())
+            },
+            ChannelState::Playing | ChannelState::FadingOut if ch.next.is_empty() => {
+                ch.next = samples;
+                Ok(())
+            },
+            ChannelState::Playing | ChannelState::FadingOut => Err(MixerError::QueueFull),
+        }
+    }
+
+    /// Number of buffers a channel still has to play: 0 when idle, 1 while
+    /// playing, 2 with a buffer [queued](Self::queue_samples) behind it.
+    pub fn queued(&self, handle: ChannelHandle) -> Result<usize, MixerError> {
+        let channels = self.lock_channels();
+        let ch = channels
+            .get(handle.0 as usize)
+            .ok_or(MixerError::InvalidChannel)?;
+        Ok(match ch.state {
+            ChannelState::Playing | ChannelState::FadingOut => 1 + !ch.next.is_empty() as usize,
+            ChannelState::Free | ChannelState::Idle => 0,
+        })
+    }
+
+    /// Hold a channel where it is: it stays `Playing` but is left out of
+    /// the mix, and its position and fades don't advance until
+    /// [`resume`](Self::resume).
+    pub fn pause(&self, handle: ChannelHandle) -> Result<(), MixerError> {
+        self.set_paused(handle, true)
+    }
+
+    /// Continue a channel held by [`pause`](Self::pause).
+    pub fn resume(&self, handle: ChannelHandle) -> Result<(), MixerError> {
+        self.set_paused(handle, false)
+    }
+
+    /// Whether a channel is held by [`pause`](Self::pause).
+    pub fn is_paused(&self, handle: ChannelHandle) -> Result<bool, MixerError> {
+        let channels = self.lock_channels();
+        let ch = channels
+            .get(handle.0 as usize)
+            .ok_or(MixerError::InvalidChannel)?;
+        Ok(ch.paused)
+    }
+
+    fn set_paused(&self, handle: ChannelHandle, paused: bool) -> Result<(), MixerError> {
+        let mut channels = self.lock_channels();
+        let ch = channels
+            .get_mut(handle.0 as usize)
+            .ok_or(MixerError::InvalidChannel)?;
+        if ch.state == ChannelState::Free {
+            return Err(MixerError::InvalidChannel);
+        }
+        ch.paused = paused;
+        Ok(())
+    }
+
+    /// Play a sound effect once on any available channel.
+    ///
+    /// `volume` is 0..=0x8000 and `pan` ranges from -1.0 (left) through
+    /// 0.0 (center) to 1.0 (right). The channel is released automatically
+    /// when the buffer finishes, so the returned handle is only useful
+    /// for stopping or adjusting the sound while it plays.
+    ///
+    /// If every channel is busy, the [`StealPolicy`] decides whether the
+    /// oldest non-looping sound is cut off or [`MixerError::NoFreeChannels`]
+    /// is returned. A handle is only a channel index: once its sound has
+    /// ended or been stolen, it refers to whatever plays there next.
+    pub fn play_one_shot(
+        &self,
+        samples: &'static [i16],
+        volume: i32,
+        pan: f32,
+    ) -> Result<ChannelHandle, MixerError> {
+        self.play_one_shot_tagged(samples, volume, pan, 0)
+    }
+
+    /// [`play_one_shot`](Self::play_one_shot) with a user tag for
+    /// [`stop_tag`](Self::stop_tag).
+    pub fn play_one_shot_tagged(
+        &self,
+        samples: &'static [i16],
+        volume: i32,
+        pan: f32,
+        tag: u32,
+    ) -> Result<ChannelHandle, MixerError> {
+        if samples.is_empty() || samples.len() % 2 != 0 {
+            return Err(MixerError::AudioError(-1));
+        }
+        let steal = self.steal_policy() == StealPolicy::StealOldest;
+
+        let mut channels = self.lock_channels();
+        let index = match channels
+            .iter()
+            .position(|ch| ch.state == ChannelState::Free)
+        {
+            Some(i) => i,
+            None if steal => {
+                // Age relative to the current sequence number, so the
+                // comparison survives wrap-around.
+                let now = self.play_seq.load(Ordering::Relaxed);
+                channels
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, ch)| ch.stealable())
+                    .max_by_key(|(_, ch)| now.wrapping_sub(ch.started))
+                    .map(|(i, _)| i)
+                    .ok_or(MixerError::NoFreeChannels)?
+            },
+            None => return Err(MixerError::NoFreeChannels),
+        };
+
+        let volume = volume.clamp(0, 0x8000);
+        let pan = pan.clamp(-1.0, 1.0);
+        let ch = &mut channels[index];
+        // An allocated channel keeps its owner's settings for later, and
+        // a lent one still has them from the first steal.
+        let lent = match ch.state {
+            ChannelState::Free => None,
+            _ if ch.one_shot => None,
+            _ => ch.lent.or(Some((ch.config, ch.tag))),
+        };
+        *ch = Channel::new();
+        ch.state = ChannelState::Playing;
+        ch.config = ChannelConfig {
+            volume_left: (volume as f32 * (1.0 - pan).min(1.0)) as i32,
+            volume_right: (volume as f32 * (1.0 + pan).min(1.0)) as i32,
+            ..ChannelConfig::default()
+        };
+        ch.buffer = samples;
+        ch.one_shot = lent.is_none();
+        ch.lent = lent;
+        ch.tag = tag;
+        ch.started = self.play_seq.fetch_add(1, Ordering::Relaxed);
+        Ok(ChannelHandle(index as u8))
+    }
+
+    /// Set what [`play_one_shot`](Self::play_one_shot) does when every
+    /// channel is busy.
+    pub fn set_steal_policy(&self, policy: StealPolicy) {
+        self.steal_policy.store(policy as u8, Ordering::Relaxed);
+    }
+
+    /// Get the current voice-stealing policy.
+    pub fn steal_policy(&self) -> StealPolicy {
+        match self.steal_policy.load(Ordering::Relaxed) {
+            1 => StealPolicy::Refuse,
+            _ => StealPolicy::StealOldest,
+        }
+    }
+
+    /// Set the user tag for a channel.
+    pub fn set_channel_tag(&self, handle: ChannelHandle, tag: u32) -> Result<(), MixerError> {
+        let mut channels = self.lock_channels();
+        let ch = channels
+            .get_mut(handle.0 as usize)
+            .ok_or(MixerError::InvalidChannel)?;
+        if ch.state == ChannelState::Free {
+            return Err(MixerError::InvalidChannel);
+        }
+        ch.tag = tag;
+        Ok(())
+    }
+
+    /// Get the user tag of a channel (0 if never set).
+    pub fn channel_tag(&self, handle: ChannelHandle) -> Result<u32, MixerError> {
+        let channels = self.lock_channels();
+        let ch = channels
+            .get(handle.0 as usize)
+            .ok_or(MixerError::InvalidChannel)?;
+        Ok(ch.tag)
+    }
+
+    /// Get the state of a channel.
+    pub fn channel_state(&self, handle: ChannelHandle) -> Result<ChannelState, MixerError> {
+        let channels = self.lock_channels();
+        let ch = channels
+            .get(handle.0 as usize)
+            .ok_or(MixerError::InvalidChannel)?;
+        Ok(ch.state)
+    }
+
+    /// Stop playback on a channel.
+    ///
+    /// One-shot channels are freed; channels from
+    /// [`alloc_channel`](Self::alloc_channel) stay allocated and become
+    /// `Idle`.
+    pub fn stop(&self, handle: ChannelHandle) -> Result<(), MixerError> {
+        let mut channels = self.lock_channels();
+        let ch = channels
+            .get_mut(handle.0 as usize)
+            .ok_or(MixerError::InvalidChannel)?;
+        if ch.state != ChannelState::Free {
+            ch.stop();
+        }
+        Ok(())
+    }
+
+    /// Stop every channel carrying `tag`. Returns how many were stopped.
+    pub fn stop_tag(&self, tag: u32) -> usize {
+        let mut stopped = 0;
+        for ch in self.lock_channels().iter_mut() {
+            if ch.state != ChannelState::Free && ch.tag == tag {
+                ch.stop();
+                stopped += 1;
+            }
+        }
+        stopped
+    }
+
+    /// Stop all channels (see [`stop`](Self::stop)).
+    pub fn stop_all(&self) {
+        for ch in self.lock_channels().iter_mut() {
+            if ch.state != ChannelState::Free {
+                ch.stop();
+            }
+        }
+    }
+
+    /// Set the volume for a channel.
+    pub fn set_channel_volume(
+        &self,
+        handle: ChannelHandle,
+        left: i32,
+        right: i32,
+    ) -> Result<(), MixerError> {
+        let mut channels = self.lock_channels();
+        let ch = channels
+            .get_mut(handle.0 as usize)
+            .ok_or(MixerError::InvalidChannel)?;
+        ch.config.volume_left = left;
+        ch.config.volume_right = right;
+        Ok(())
+    }
+
+    /// Change a channel's [`Effect`], or remove it with `None`.
+    ///
+    /// Moving a low-pass cutoff while the channel plays keeps the filter
+    /// running, so it can follow the game smoothly (e.g. muffling a sound
+    /// as a door closes).
+    pub fn set_channel_effect(
+        &self,
+        handle: ChannelHandle,
+        effect: Option<Effect>,
+    ) -> Result<(), MixerError> {
+        let mut channels = self.lock_channels();
+        let ch = channels
+            .get_mut(handle.0 as usize)
+            .ok_or(MixerError::InvalidChannel)?;
+        if ch.state == ChannelState::Free {
+            return Err(MixerError::InvalidChannel);
+        }
+        ch.config.effect = effect;
+        LowPass::update(&mut ch.filter, effect);
+        Ok(())
+    }
+
+    /// Set how much of a channel goes to the echo bus (0..=0x8000).
+    pub fn set_echo_send(&self, handle: ChannelHandle, level: i32) -> Result<(), MixerError> {
+        let mut channels = self.lock_channels();
+        let ch = channels
+            .get_mut(handle.0 as usize)
+            .ok_or(MixerError::InvalidChannel)?;
+        if ch.state == ChannelState::Free {
+            return Err(MixerError::InvalidChannel);
+        }
+        ch.config.echo_send = level.clamp(0, 0x8000);
+        Ok(())
+    }
+
+    /// Set up the echo bus shared by all channels, or remove it with
+    /// `None`.
+    ///
+    /// Channels opt in with [`ChannelConfig::echo_send`]. Changing only
+    /// the feedback or mix keeps the echoes already sounding; a new delay
+    /// starts from silence. The bus is mixed on the CPU only, so while
+    /// [Media Engine offload](Self::enable_me_offload) is enabled, sends
+    /// are ignored.
+    #[cfg(not(feature = "stub-only"))]
+    pub fn set_echo(&self, echo: Option<EchoSend>) {
+        let min_delay = self.sample_count as usize;
+        if let (Some(settings), Some(bus)) = (echo, self.echo.lock().as_mut())
+            && bus.retune(settings, min_delay)
+        {
+            return;
+        }
+        // Allocate, and free the old line, without holding the lock the
+        // audio thread mixes under.
+        let bus = echo.map(|settings| EchoBus::new(settings, min_delay));
+        let old = core::mem::replace(&mut *self.echo.lock(), bus);
+        drop(old);
+    }
+
+    /// The echo bus settings, after clamping, or `None` without one.
+    #[cfg(not(feature = "stub-only"))]
+    pub fn echo(&self) -> Option<EchoSend> {
+        self.echo.lock().as_ref().map(EchoBus::settings)
+    }
+
+    /// Make a channel loop the stereo frames `start..end` of its buffer,
+    /// after playing the frames before `start` once. An `end` of 0 loops
+    /// to the end of the buffer.
+    ///
+    /// Takes effect immediately: a channel already past `end` jumps back
+    /// to `start` on the next frame it mixes.
+    pub fn set_loop_region(
+        &self,
+        handle: ChannelHandle,
+        start: usize,
+        end: usize,
+    ) -> Result<(), MixerError> {
+        let mut channels = self.lock_channels();
+        let ch = channels
+            .get_mut(handle.0 as usize)
+            .ok_or(MixerError::InvalidChannel)?;
+        ch.config.looping = true;
+        ch.config.loop_start = start;
+        ch.config.loop_end = end;
+        Ok(())
+    }
+
+    /// Start a fade-out on a channel.
+    ///
+    /// `frames` is the number of output frames over which to fade.
+    /// After the fade completes, the channel transitions to `Idle`.
+    pub fn fade_out(&self, handle: ChannelHandle, frames: u16) -> Result<(), MixerError> {
+        let mut channels = self.lock_channels();
+        let ch = channels
+            .get_mut(handle.0 as usize)
+            .ok_or(MixerError::InvalidChannel)?;
+        if frames == 0 {
+            ch.fade_level = 0;
+            ch.stop();
+        } else {
+            ch.fade_step = -(FADE_MAX_FP / frames as i32);
+            ch.state = ChannelState::FadingOut;
+        }
+        Ok(())
+    }
+
+    /// Start a fade-in on a channel.
+    pub fn fade_in(&self, handle: ChannelHandle, frames: u16) -> Result<(), MixerError> {
+        let mut channels = self.lock_channels();
+        let ch = channels
+            .get_mut(handle.0 as usize)
+            .ok_or(MixerError::InvalidChannel)?;
+        if frames == 0 {
+            ch.fade_level = FADE_MAX_FP;
+        } else {
+            ch.fade_level = 0;
+            ch.fade_step = FADE_MAX_FP / frames as i32;
+        }
+        Ok(())
+    }
+
+    /// Set master volume (0..=0x8000).
+    pub fn set_master_volume(&self, volume: u32) {
+        self.master_volume
+            .store(volume.min(0x8000), Ordering::Relaxed);
+    }
+
+    /// Get master volume.
+    pub fn master_volume(&self) -> u32 {
+        self.master_volume.load(Ordering::Relaxed)
+    }
+
+    /// Mix all active channels into the output buffer.
+    ///
+    /// `output` must have space for `sample_count * 2` i16 values
+    /// (interleaved stereo). With [Media Engine
+    /// offload](Self::enable_me_offload) enabled, this returns the buffer
+    /// the ME mixed during the previous call and starts mixing the next.
+    pub fn mix_into(&self, output: &mut [i16]) {
+        let master_vol = self.master_volume.load(Ordering::Relaxed) as i32;
+
+        #[cfg(all(target_os = "psp", feature = "kernel"))]
+        if let Some(offload) = &self.me {
+            offload.mix_into(output, master_vol);
+            return;
+        }
+
+        #[cfg(not(feature = "stub-only"))]
+        let mut echo = self.echo.lock();
+        #[cfg(not(feature = "stub-only"))]
+        let echo = echo.as_mut().map(EchoBus::line);
+        #[cfg(feature = "stub-only")]
+        let echo = None;
+        mix_channels(&mut self.lock_channels(), master_vol, output, echo);
+    }
+
+    /// Reserve a hardware audio channel.
+    ///
+    /// Must be called before [`output_blocking`](Self::output_blocking).
+    pub fn reserve_hw_channel(&self) -> Result<(), MixerError> {
+        let ch = unsafe {
+            crate::sys::sceAudioChReserve(
+                crate::sys::AUDIO_NEXT_CHANNEL,
+                self.sample_count,
+                crate::sys::AudioFormat::Stereo,
+            )
+        };
+        if ch < 0 {
+            return Err(MixerError::AudioError(ch));
+        }
+        self.hw_channel.store(ch, Ordering::Release);
+        Ok(())
+    }
+
+    /// Release the hardware audio channel.
+    pub fn release_hw_channel(&self) {
+        let ch = self.hw_channel.swap(-1, Ordering::AcqRel);
+        if ch >= 0 {
+            unsafe {
+                crate::sys::sceAudioChRelease(ch);
+            }
+        }
+    }
+
+    /// Output the given buffer to the audio hardware (blocking).
+    ///
+    /// The buffer must contain at least `sample_count * 2` i16 samples
+    /// (interleaved stereo). Returns [`MixerError::AudioError`] if the
+    /// buffer is too small. This call blocks until the hardware is
+    /// ready for the next buffer.
+    pub fn output_blocking(&self, buffer: &[i16]) -> Result<(), MixerError> {
+        let required = self.sample_count as usize * 2; // stereo
+        if buffer.len() < required {
+            return Err(MixerError::AudioError(-1));
+        }
+        let ch = self.hw_channel.load(Ordering::Acquire);
+        if ch < 0 {
+            return Err(MixerError::AudioError(-1));
+        }
+        let ret = unsafe {
+            crate::sys::sceAudioOutputPannedBlocking(
+                ch,
+                0x8000, // full left
+                0x8000, // full right
+                buffer.as_ptr() as *mut core::ffi::c_void,
+            )
+        };
+        if ret < 0 {
+            Err(MixerError::AudioError(ret))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Get the configured sample count per output call.
+    pub fn sample_count(&self) -> i32 {
+        self.sample_count
+    }
+}
+
+/// Mix the playing `channels` into `output`, advancing their positions and
+/// fades, along with the repeats from `echo` and the sends to it.
+///
+/// Runs on the CPU or, with offload enabled, on the Media Engine, so it
+/// must not make syscalls.
+fn mix_channels(
+    channels: &mut [Channel; MAX_CHANNELS],
+    master_vol: i32,
+    output: &mut [i16],
+    echo: Option<EchoLine<'_>>,
+) {
+    // A buffer longer than the delay would send into frames it hasn't
+    // played yet. `Mixer::set_echo` sizes the delay for `sample_count`.
+    let mut echo = echo.filter(|echo| echo.fits(output.len() / 2));
+    // Clear the output buffer
+    for sample in output.iter_mut() {
+        *sample = 0;
+    }
+    if let Some(echo) = &mut echo {
+        echo.play(output, master_vol);
+    }
+
+    for ch in channels.iter_mut() {
+        if ch.state != ChannelState::Playing && ch.state != ChannelState::FadingOut || ch.paused {
+            continue;
+        }
+
+        if ch.buffer.is_empty() {
+            ch.stop();
+            continue;
+        }
+
+        let vol_l = ch.config.volume_left;
+        let vol_r = ch.config.volume_right;
+        let fade = ch.fade_level >> FADE_FP_SHIFT;
+        let send = ch.config.echo_send;
+
+        let mut frames = ch.buffer.len() / 2;
+        let (mut loop_start, mut loop_end) = ch.config.loop_region(frames);
+
+        // Mix this channel's samples into the output
+        let stereo_samples = output.len() / 2;
+        for i in 0..stereo_samples {
+            if ch.position >= frames && !ch.next.is_empty() {
+                ch.buffer = core::mem::take(&mut ch.next);
+                ch.position = 0;
+                frames = ch.buffer.len() / 2;
+                (loop_start, loop_end) = ch.config.loop_region(frames);
+            } else if ch.config.looping && ch.position >= loop_end {
+                // Wrap before reading, so the frame at `loop_end - 1` is
+                // followed directly by the one at `loop_start`.
+                ch.position = loop_start;
+            } else if ch.position >= frames {
+                ch.stop();
+                break;
+            }
+            let buf_pos = ch.position * 2; // stereo pairs
+
+            let mut src_l = ch.buffer[buf_pos] as i32;
+            let mut src_r = ch.buffer[buf_pos + 1] as i32;
+            if let Some(filter) = &mut ch.filter {
+                (src_l, src_r) = filter.process(src_l, src_r);
+            }
+
+            // Apply channel volume and fade, send to the echo bus, then
+            // apply master volume. Use i64 intermediates to prevent
+            // overflow when src ~ 32000 and vol = 0x8000.
+            let chan_l = src_l as i64 * vol_l as i64 / 0x8000 * fade as i64 / 256;
+            let chan_r = src_r as i64 * vol_r as i64 / 0x8000 * fade as i64 / 256;
+            if send > 0
+                && let Some(echo) = &mut echo
+            {
+                echo.send(i, chan_l as i32, chan_r as i32, send);
+            }
+            let mixed_l = (chan_l * master_vol as i64 / 0x8000)
+                .clamp(i16::MIN as i64, i16::MAX as i64) as i16;
+            let mixed_r = (chan_r * master_vol as i64 / 0x8000)
+                .clamp(i16::MIN as i64, i16::MAX as i64) as i16;
+
+            // Saturating add to output
+            let out_idx = i * 2;
+            output[out_idx] = output[out_idx].saturating_add(mixed_l);
+            output[out_idx + 1] = output[out_idx + 1].saturating_add(mixed_r);
+
+            ch.position += 1;
+        }
+
+        // Update fade. A channel that finished above has already been
+        // stopped (and, for one-shots, reset), so it is skipped here.
+        if ch.state == ChannelState::FadingOut {
+            let new_fade = ch.fade_level + ch.fade_step;
+            if new_fade <= 0 {
+                ch.fade_level = 0;
+                ch.stop();
+            } else {
+                ch.fade_level = new_fade;
+            }
+        } else if ch.fade_step > 0 {
+            let new_fade = ch.fade_level + ch.fade_step;
+            if new_fade >= FADE_MAX_FP {
+                ch.fade_level = FADE_MAX_FP;
+                ch.fade_step = 0;
+            } else {
+                ch.fade_level = new_fade;
+            }
+        }
+    }
+
+    if let Some(echo) = &mut echo {
+        echo.advance(output.len() / 2);
+    }
+}
+
+impl Drop for Mixer {
+    fn drop(&mut self) {
+        #[cfg(all(target_os = "psp", feature = "kernel"))]
+        self.disable_me_offload();
+        self.release_hw_channel();
+    }
+}
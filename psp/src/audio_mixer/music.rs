@@ -0,0 +1,372 @@
+//! Background music: an [`Mp3Decoder`] streamed into a mixer channel.
+
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicBool, AtomicI32, AtomicU8, Ordering};
+
+use super::{ChannelConfig, ChannelHandle, Mixer, MixerError};
+use crate::mp3::{Mp3Decoder, Mp3Error};
+use crate::thread::{self, JoinHandle, ThreadBuilder, ThreadError};
+
+/// Stereo frames per streaming buffer: four MP3 frames, about 100 ms at
+/// 44.1 kHz.
+const BUFFER_FRAMES: usize = 4 * 1152;
+
+/// Buffers cycled between the decoder and the mixer: one playing, one
+/// queued behind it, one being decoded into.
+const BUFFER_COUNT: usize = 3;
+
+/// How long the worker sleeps between checks, well under one buffer.
+const POLL_MS: u32 = 10;
+
+/// `Shared::requested` when there is no request waiting.
+const NO_REQUEST: u8 = u8::MAX;
+
+/// Playback state of a [`MusicPlayer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum PlayerState {
+    /// Not playing; [`play()`](MusicPlayer::play) starts from the
+    /// beginning.
+    Stopped = 0,
+    /// Decoding and playing.
+    Playing = 1,
+    /// Held in place; [`play()`](MusicPlayer::play) continues from here.
+    Paused = 2,
+    /// The track played to the end, or decoding failed.
+    Finished = 3,
+}
+
+impl PlayerState {
+    fn from_u8(v: u8) -> Self {
+        match v {
+            1 => Self::Playing,
+            2 => Self::Paused,
+            3 => Self::Finished,
+            _ => Self::Stopped,
+        }
+    }
+}
+
+/// Error from creating a [`MusicPlayer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MusicError {
+    /// The MP3 decoder failed to start.
+    Mp3(Mp3Error),
+    /// No mixer channel was available.
+    Mixer(MixerError),
+    /// The decoding thread couldn't be started.
+    Thread(ThreadError),
+}
+
+impl core::fmt::Display for MusicError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Mp3(e) => write!(f, "music decoder failed: {}", e),
+            Self::Mixer(e) => write!(f, "no mixer channel for music: {:?}", e),
+            Self::Thread(e) => write!(f, "music thread failed: {}", e),
+        }
+    }
+}
+
+/// State shared between the player and its worker thread.
+struct Shared {
+    /// A [`PlayerState`] the caller asked for that the worker hasn't
+    /// picked up yet, or `NO_REQUEST`. Only the caller stores requests;
+    /// the worker takes them, so none is lost to a worker update.
+    requested: AtomicU8,
+    /// The [`PlayerState`] the worker has reached.
+    state: AtomicU8,
+    looping: AtomicBool,
+    /// Channel volume (0..=0x8000), applied by the worker.
+    volume: AtomicI32,
+    quit: AtomicBool,
+}
+
+/// Plays an MP3 through a [`Mixer`] channel, decoding on a worker thread.
+///
+/// The worker decodes a few frames ahead into a small ring of buffers and
+/// [queues](Mixer::queue_samples) each one behind the buffer that's
+/// playing, so the track streams without gaps while sound effects share
+/// the mixer. The mixer's own output loop must be running for anything to
+/// be heard.
+///
+/// The mixer plays at 44.1 kHz and doesn't resample, so the MP3 should be
+/// encoded at that rate. Dropping the player stops the music and frees
+/// its channel.
+///
+/// See the [`mp3`](crate::mp3) module for why this is for one track at a
+/// time rather than switching songs often.
+///
+/// # Example
+///
+/// ```ignore
+/// use alloc::sync::Arc;
+/// use psp::audio_mixer::{Mixer, MusicPlayer};
+///
+/// let mixer = Arc::new(Mixer::new(DEFAULT_SAMPLE_COUNT)?);
+/// // ... start a thread that calls mixer.mix_into / output_blocking ...
+///
+/// let data = psp::io::read_to_vec("ms0:/music/theme.mp3")?;
+/// let music = MusicPlayer::new(mixer.clone(), &data)?;
+/// music.set_looping(true);
+/// music.set_volume(0x5000);
+/// music.play();
+/// ```
+pub struct MusicPlayer {
+    shared: Arc<Shared>,
+    worker: Option<JoinHandle>,
+}
+
+impl MusicPlayer {
+    /// Start a decoder for `data` and a worker feeding a new channel of
+    /// `mixer`. The player starts [`Stopped`](PlayerState::Stopped).
+    pub fn new(mixer: Arc<Mixer>, data: &[u8]) -> Result<Self, MusicError> {
+        let decoder = Mp3Decoder::new(data).map_err(MusicError::Mp3)?;
+        let channel = mixer
+            .alloc_channel(ChannelConfig::default())
+            .map_err(MusicError::Mixer)?;
+
+        let shared = Arc::new(Shared {
+            requested: AtomicU8::new(NO_REQUEST),
+            state: AtomicU8::new(PlayerState::Stopped as u8),
+            looping: AtomicBool::new(false),
+            volume: AtomicI32::new(0x8000),
+            quit: AtomicBool::new(false),
+        });
+        let mut worker = Worker {
+            shared: shared.clone(),
+            mixer: mixer.clone(),
+            channel,
+            decoder,
+            buffers: (0..BUFFER_COUNT)
+                .map(|_| alloc::vec![0i16; BUFFER_FRAMES * 2].into_boxed_slice())
+                .collect(),
+            free: (0..BUFFER_COUNT).collect(),
+            in_flight: Vec::with_capacity(BUFFER_COUNT),
+            carry: Vec::new(),
+            volume: 0x8000,
+            decoded_all: false,
+        };
+        let handle = ThreadBuilder::new(b"music_player\0")
+            .priority(crate::DEFAULT_THREAD_PRIORITY - 2)
+            .spawn(move || {
+                worker.run();
+                0
+            });
+        match handle {
+            Ok(handle) => Ok(Self {
+                shared,
+                worker: Some(handle),
+            }),
+            Err(e) => {
+                // The closure, and with it the worker, was dropped, but
+                // the channel is still allocated.
+                let _ = mixer.free_channel(channel);
+                Err(MusicError::Thread(e))
+            },
+        }
+    }
+
+    /// Start playing, or continue after [`pause()`](Self::pause). After
+    /// [`stop()`](Self::stop) or the end of the track, playback restarts
+    /// from the beginning.
+    pub fn play(&self) {
+        self.request(PlayerState::Playing);
+    }
+
+    /// Hold playback where it is.
+    pub fn pause(&self) {
+        if self.state() == PlayerState::Playing {
+            self.request(PlayerState::Paused);
+        }
+    }
+
+    /// Stop playback and rewind to the beginning.
+    pub fn stop(&self) {
+        self.request(PlayerState::Stopped);
+    }
+
+    /// Set the music volume (0..=0x8000), independent of the mixer's
+    /// master volume.
+    pub fn set_volume(&self, volume: i32) {
+        self.shared
+            .volume
+            .store(volume.clamp(0, 0x8000), Ordering::Relaxed);
+    }
+
+    /// The music volume.
+    pub fn volume(&self) -> i32 {
+        self.shared.volume.load(Ordering::Relaxed)
+    }
+
+    /// Whether the track starts over when it ends. Off by default.
+    pub fn set_looping(&self, looping: bool) {
+        self.shared.looping.store(looping, Ordering::Relaxed);
+    }
+
+    /// The playback state the worker has reached. Requests take effect
+    /// within a few milliseconds.
+    pub fn state(&self) -> PlayerState {
+        PlayerState::from_u8(self.shared.state.load(Ordering::Acquire))
+    }
+
+    fn request(&self, state: PlayerState) {
+        self.shared.requested.store(state as u8, Ordering::Release);
+    }
+}
+
+impl Drop for MusicPlayer {
+    fn drop(&mut self) {
+        self.shared.quit.store(true, Ordering::Release);
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Everything the worker thread owns.
+struct Worker {
+    shared: Arc<Shared>,
+    mixer: Arc<Mixer>,
+    channel: ChannelHandle,
+    decoder: Mp3Decoder,
+    buffers: Vec<Box<[i16]>>,
+    /// Indices of buffers the mixer is done with.
+    free: Vec<usize>,
+    /// Indices and lengths of queued buffers, oldest (playing) first.
+    in_flight: Vec<(usize, usize)>,
+    /// Decoded samples that didn't fit in the last buffer.
+    carry: Vec<i16>,
+    /// Volume last applied to the channel.
+    volume: i32,
+    /// The decoder has returned its last frame.
+    decoded_all: bool,
+}
+
+impl Worker {
+    fn run(&mut self) {
+        let mut state = PlayerState::Stopped;
+        let mut requested = PlayerState::Stopped;
+        while !self.shared.quit.load(Ordering::Acquire) {
+            let volume = self.shared.volume.load(Ordering::Relaxed);
+            if volume != self.volume {
+                self.volume = volume;
+                let _ = self.mixer.set_channel_volume(self.channel, volume, volume);
+            }
+
+            match self.shared.requested.swap(NO_REQUEST, Ordering::AcqRel) {
+                NO_REQUEST => {},
+                request => requested = PlayerState::from_u8(request),
+            }
+            state = match (state, requested) {
+                (PlayerState::Playing, PlayerState::Paused) => {
+                    let _ = self.mixer.pause(self.channel);
+                    PlayerState::Paused
+                },
+                (PlayerState::Paused, PlayerState::Playing) => {
+                    let _ = self.mixer.resume(self.channel);
+                    PlayerState::Playing
+                },
+                (PlayerState::Stopped | PlayerState::Finished, PlayerState::Playing) => {
+                    self.rewind();
+                    PlayerState::Playing
+                },
+                (PlayerState::Playing | PlayerState::Paused, PlayerState::Stopped) => {
+                    self.rewind();
+                    PlayerState::Stopped
+                },
+                (PlayerState::Playing, _) => self.pump(),
+                (current, _) => current,
+            };
+            self.shared.state.store(state as u8, Ordering::Release);
+            if state == PlayerState::Finished {
+                // Don't restart until asked to again.
+                requested = PlayerState::Finished;
+            }
+            thread::sleep_ms(POLL_MS);
+        }
+        // Free the channel before the buffers it may point at go away.
+        let _ = self.mixer.free_channel(self.channel);
+    }
+
+    /// Stop the channel and go back to the start of the track.
+    fn rewind(&mut self) {
+        let _ = self.mixer.stop(self.channel);
+        let _ = self.mixer.resume(self.channel);
+        self.free
+            .extend(self.in_flight.drain(..).map(|(index, _)| index));
+        self.carry.clear();
+        self.decoded_all = self.decoder.reset().is_err();
+    }
+
+    /// Recycle played buffers and queue newly decoded ones. Returns the
+    /// new state.
+    fn pump(&mut self) -> PlayerState {
+        let queued = self.mixer.queued(self.channel).unwrap_or(0);
+        while self.in_flight.len() > queued {
+            let (index, _) = self.in_flight.remove(0);
+            self.free.push(index);
+        }
+
+        while self.in_flight.len() < 2 && !self.decoded_all {
+            let Some(index) = self.free.pop() else { break };
+            let len = self.fill(index);
+            if len == 0 {
+                self.free.push(index);
+                break;
+            }
+            // SAFETY: The buffer stays allocated and untouched until the
+            // channel has played it: it only returns to `free` once
+            // `queued` drops, and the channel is freed before `buffers`.
+            let samples: &'static [i16] =
+                unsafe { core::slice::from_raw_parts(self.buffers[index].as_ptr(), len) };
+            if unsafe { self.mixer.queue_samples(self.channel, samples) }.is_err() {
+                self.free.push(index);
+                break;
+            }
+            self.in_flight.push((index, len));
+        }
+
+        if self.decoded_all && self.in_flight.is_empty() {
+            PlayerState::Finished
+        } else {
+            PlayerState::Playing
+        }
+    }
+
+    /// Decode into buffer `index`, returning how many samples it holds.
+    fn fill(&mut self, index: usize) -> usize {
+        let buffer = &mut self.buffers[index];
+        let mut len = self.carry.len().min(buffer.len());
+        buffer[..len].copy_from_slice(&self.carry[..len]);
+        self.carry.drain(..len);
+
+        // Only loop once per buffer, so a track that decodes to nothing
+        // can't spin here.
+        let mut rewound = false;
+        while len < buffer.len() {
+            let frame = match self.decoder.decode_frame() {
+                Ok(frame) if !frame.is_empty() => frame,
+                // End of the track, or a decode error that ends it.
+                _ => {
+                    if !rewound
+                        && self.shared.looping.load(Ordering::Relaxed)
+                        && self.decoder.reset().is_ok()
+                    {
+                        rewound = true;
+                        continue;
+                    }
+                    self.decoded_all = true;
+                    break;
+                },
+            };
+            let n = frame.len().min(buffer.len() - len);
+            buffer[len..len + n].copy_from_slice(&frame[..n]);
+            self.carry.extend_from_slice(&frame[n..]);
+            len += n;
+        }
+        len & !1
+    }
+}
@@ -32,8 +32,9 @@
 //!
 //! # Requirements
 //!
-//! - **User mode**: Call `sceUtilityLoadModule(PSP_MODULE_AV_AVCODEC)` (and
-//!   `PSP_MODULE_AV_MPEGBASE` for some codecs) before creating a decoder.
+//! - **User mode**: Call
+//!   [`utility::load_module(UtilityModule::AvCodec)`](crate::utility::load_module)
+//!   (and `AvMpegBase` for some codecs) before creating a decoder.
 //! - **Kernel mode**: The codec modules are typically loaded by the game.
 //!   Source/destination buffers should be in user-accessible memory since the
 //!   codec validates pointer ranges.
@@ -0,0 +1,259 @@
+//! Go!Cam (Chotto Shot) camera capture.
+//!
+//! The PSP camera is a USB accessory plugged into the top port. Opening a
+//! [`Camera`] loads the USB camera modules, starts the bus, accessory and
+//! camera drivers, waits for the camera to connect and starts video
+//! capture. Frames arrive as MJPEG: each [`Camera::capture_frame`] call
+//! returns one complete JPEG image, which
+//! [`JpegDecoder`](crate::image::JpegDecoder) turns into pixels for
+//! display.
+//!
+//! Still pictures use a separate mode of the driver that can't run
+//! alongside video; see `sceUsbCamSetupStill` and
+//! `sceUsbCamStillInputBlocking` in [`crate::sys`].
+//!
+//! # Example
+//!
+//! ```ignore
+//! use psp::camera::{Camera, CameraError};
+//! use psp::sys::{UsbCamFrameRate, UsbCamResolution};
+//!
+//! let mut camera = match Camera::new(UsbCamResolution::Px480_272, UsbCamFrameRate::Fps15) {
+//!     Ok(camera) => camera,
+//!     Err(CameraError::NotConnected) => return show_message("Plug in the camera"),
+//!     Err(e) => panic!("{e}"),
+//! };
+//! let mut jpeg = vec![0u8; camera.frame_capacity()];
+//! let len = camera.capture_frame(&mut jpeg)?;
+//! ```
+
+use crate::sys::{
+    USB_CAM_PID, UsbCamEffectMode, UsbCamEvLevel, UsbCamFrameRate, UsbCamResolution,
+    UsbCamSetupVideoParam, UsbCamWb, UsbState,
+};
+use crate::utility::{self, ModuleError, UtilityModule};
+use alloc::vec::Vec;
+use core::ffi::c_void;
+
+/// How long [`Camera::new`] waits for the camera to connect.
+pub const CONNECT_TIMEOUT_MS: u32 = 2000;
+
+/// Size of the driver's work area, as the firmware's sample uses.
+const WORK_AREA_SIZE: usize = 68 * 1024;
+
+/// Drivers started for the camera, in start order.
+const DRIVERS: [&[u8]; 4] = [
+    b"USBBusDriver\0",
+    b"USBAccBaseDriver\0",
+    b"USBCamDriver\0",
+    b"USBCamMicDriver\0",
+];
+
+/// Error from a camera operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CameraError {
+    /// No camera connected within [`CONNECT_TIMEOUT_MS`].
+    NotConnected,
+    /// Loading the USB camera modules failed.
+    Module(ModuleError),
+    /// Starting a USB driver failed with this SCE error code.
+    Usb(i32),
+    /// A `sceUsbCam*` call failed with this SCE error code.
+    Camera(i32),
+}
+
+impl core::fmt::Display for CameraError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::NotConnected => f.write_str("camera not connected"),
+            Self::Module(e) => write!(f, "{e}"),
+            Self::Usb(code) => write!(f, "USB error {:#010x}", *code as u32),
+            Self::Camera(code) => write!(f, "camera error {:#010x}", *code as u32),
+        }
+    }
+}
+
+impl From<ModuleError> for CameraError {
+    fn from(e: ModuleError) -> Self {
+        Self::Module(e)
+    }
+}
+
+/// Width and height of `resolution` in pixels.
+pub fn resolution_size(resolution: UsbCamResolution) -> (u32, u32) {
+    match resolution {
+        UsbCamResolution::Px160_120 => (160, 120),
+        UsbCamResolution::Px176_144 => (176, 144),
+        UsbCamResolution::Px320_240 => (320, 240),
+        UsbCamResolution::Px352_288 => (352, 288),
+        UsbCamResolution::Px640_480 => (640, 480),
+        UsbCamResolution::Px1024_768 => (1024, 768),
+        UsbCamResolution::Px1280_960 => (1280, 960),
+        UsbCamResolution::Px480_272 => (480, 272),
+        UsbCamResolution::Px360_272 => (360, 272),
+    }
+}
+
+/// The USB setup done so far, undone in reverse on drop.
+struct UsbSetup {
+    module_loaded: bool,
+    drivers_started: usize,
+    activated: bool,
+}
+
+impl UsbSetup {
+    fn start() -> Result<Self, CameraError> {
+        let mut setup = Self {
+            module_loaded: false,
+            drivers_started: 0,
+            activated: false,
+        };
+        utility::load_module(UtilityModule::UsbCam)?;
+        setup.module_loaded = true;
+
+        for driver in DRIVERS {
+            let ret = unsafe { crate::sys::sceUsbStart(driver.as_ptr(), 0, core::ptr::null_mut()) };
+            if ret < 0 {
+                return Err(CameraError::Usb(ret));
+            }
+            setup.drivers_started += 1;
+        }
+
+        let ret = unsafe { crate::sys::sceUsbActivate(USB_CAM_PID as u32) };
+        if ret < 0 {
+            return Err(CameraError::Usb(ret));
+        }
+        setup.activated = true;
+
+        let mut waited = 0;
+        while !crate::usb::state().contains(UsbState::ESTABLISHED) {
+            if waited >= CONNECT_TIMEOUT_MS {
+                return Err(CameraError::NotConnected);
+            }
+            crate::thread::sleep_ms(50);
+            waited += 50;
+        }
+        Ok(setup)
+    }
+}
+
+impl Drop for UsbSetup {
+    fn drop(&mut self) {
+        unsafe {
+            if self.activated {
+                crate::sys::sceUsbDeactivate(USB_CAM_PID as u32);
+            }
+            for driver in DRIVERS[..self.drivers_started].iter().rev() {
+                crate::sys::sceUsbStop(driver.as_ptr(), 0, core::ptr::null_mut::<c_void>());
+            }
+        }
+        if self.module_loaded {
+            let _ = utility::unload_module(UtilityModule::UsbCam);
+        }
+    }
+}
+
+/// An open camera capturing video.
+///
+/// Video stops and the USB drivers and modules are released on drop.
+pub struct Camera {
+    resolution: UsbCamResolution,
+    frame_capacity: usize,
+    /// Handed to the driver by `sceUsbCamSetupVideo` and used until video
+    /// stops.
+    _work_area: Vec<u8>,
+    // Declared last so the drivers stop after video and the work area.
+    _usb: UsbSetup,
+}
+
+impl Camera {
+    /// Connect to the camera and start capturing `resolution` video at
+    /// `framerate`.
+    ///
+    /// Fails with [`CameraError::NotConnected`] if no camera shows up
+    /// within [`CONNECT_TIMEOUT_MS`], leaving nothing set up.
+    pub fn new(
+        resolution: UsbCamResolution,
+        framerate: UsbCamFrameRate,
+    ) -> Result<Self, CameraError> {
+        let usb = UsbSetup::start()?;
+
+        let (width, height) = resolution_size(resolution);
+        // A quarter byte per pixel leaves room for the least compressed
+        // frames.
+        let frame_capacity = ((width * height / 4) as usize).max(16 * 1024);
+        let mut work_area = alloc::vec![0u8; WORK_AREA_SIZE];
+        let mut param = UsbCamSetupVideoParam {
+            size: core::mem::size_of::<UsbCamSetupVideoParam>() as i32,
+            resolution,
+            framerate,
+            white_balance: UsbCamWb::Auto,
+            saturation: 125,
+            brightness: 100,
+            contrast: 64,
+            sharpness: 0,
+            effect_mode: UsbCamEffectMode::Normal,
+            frame_size: frame_capacity as i32,
+            unk: 0,
+            evl_evel: UsbCamEvLevel::Zero,
+        };
+        let ret = unsafe {
+            crate::sys::sceUsbCamSetupVideo(
+                &mut param,
+                work_area.as_mut_ptr() as *mut c_void,
+                WORK_AREA_SIZE as i32,
+            )
+        };
+        if ret < 0 {
+            return Err(CameraError::Camera(ret));
+        }
+        unsafe { crate::sys::sceUsbCamAutoImageReverseSW(1) };
+
+        let ret = unsafe { crate::sys::sceUsbCamStartVideo() };
+        if ret < 0 {
+            return Err(CameraError::Camera(ret));
+        }
+
+        Ok(Self {
+            resolution,
+            frame_capacity,
+            _work_area: work_area,
+            _usb: usb,
+        })
+    }
+
+    /// Wait for the next frame and copy it into `buf` as a JPEG image,
+    /// returning its length in bytes.
+    ///
+    /// `buf` should hold [`frame_capacity`](Self::frame_capacity) bytes.
+    pub fn capture_frame(&mut self, buf: &mut [u8]) -> Result<usize, CameraError> {
+        let ret =
+            unsafe { crate::sys::sceUsbCamReadVideoFrameBlocking(buf.as_mut_ptr(), buf.len()) };
+        if ret < 0 {
+            Err(CameraError::Camera(ret))
+        } else {
+            Ok(ret as usize)
+        }
+    }
+
+    /// Largest JPEG frame the driver delivers, in bytes.
+    pub fn frame_capacity(&self) -> usize {
+        self.frame_capacity
+    }
+
+    /// The capture resolution.
+    pub fn resolution(&self) -> UsbCamResolution {
+        self.resolution
+    }
+
+    /// Width and height of captured frames in pixels.
+    pub fn size(&self) -> (u32, u32) {
+        resolution_size(self.resolution)
+    }
+}
+
+impl Drop for Camera {
+    fn drop(&mut self) {
+        unsafe { crate::sys::sceUsbCamStopVideo() };
+    }
+}
@@ -8,7 +8,7 @@
 //!
 //! ```text
 //! Magic: b"RCFG" (4 bytes)
-//! Version: 1 (u16 LE)
+//! Version: 2 (u16 LE)
 //! Count: N (u16 LE)
 //! Entry[N]:
 //!   key_len: u8
@@ -16,13 +16,30 @@
 //!   value_type: u8 (0=Bool, 1=I32, 2=U32, 3=F32, 4=Str, 5=Bytes)
 //!   value_len: u16 LE
 //!   value: [u8; value_len]
+//! Checksum: CRC-32 of all preceding bytes (u32 LE)
 //! ```
+//!
+//! Version 1 files are identical except that they have no checksum. They
+//! still load, and are written back as version 2 on the next save.
+//!
+//! The [`schema`] module validates loaded values, fills in defaults and
+//! migrates files written by older releases of a game.
 
 use alloc::string::String;
 use alloc::vec::Vec;
 
+pub mod schema;
+
+pub use schema::{ConfigSchema, Kind, Migration, Rule, Violation};
+
+/// Key under which [`Config::set_app_version()`] stores the release of the
+/// app that wrote a config.
+pub const APP_VERSION_KEY: &str = "app_version";
+
 const MAGIC: &[u8; 4] = b"RCFG";
-const VERSION: u16 = 1;
+const VERSION: u16 = 2;
+/// The original format, without a trailing checksum.
+const VERSION_1: u16 = 1;
 const MAX_FILE_SIZE: usize = 64 * 1024;
 
 /// Error from a config operation.
@@ -37,6 +54,9 @@ pub enum ConfigError {
     TooLarge,
     /// A key exceeds 255 bytes.
     KeyTooLong,
+    /// The file's checksum doesn't match its contents (it was corrupted
+    /// on the storage medium or truncated).
+    ChecksumMismatch,
 }
 
 impl core::fmt::Debug for ConfigError {
@@ -47,6 +67,7 @@ impl core::fmt::Debug for ConfigError {
             Self::KeyNotFound => write!(f, "ConfigError::KeyNotFound"),
             Self::TooLarge => write!(f, "ConfigError::TooLarge"),
             Self::KeyTooLong => write!(f, "ConfigError::KeyTooLong"),
+            Self::ChecksumMismatch => write!(f, "ConfigError::ChecksumMismatch"),
         }
     }
 }
@@ -59,6 +80,7 @@ impl core::fmt::Display for ConfigError {
             Self::KeyNotFound => write!(f, "config key not found"),
             Self::TooLarge => write!(f, "config file too large"),
             Self::KeyTooLong => write!(f, "config key too long"),
+            Self::ChecksumMismatch => write!(f, "config checksum mismatch"),
         }
     }
 }
@@ -93,6 +115,62 @@ impl core::fmt::Debug for ConfigValue {
     }
 }
 
+impl ConfigValue {
+    /// The value's type.
+    pub fn kind(&self) -> Kind {
+        match self {
+            Self::Bool(_) => Kind::Bool,
+            Self::I32(_) => Kind::I32,
+            Self::U32(_) => Kind::U32,
+            Self::F32(_) => Kind::F32,
+            Self::Str(_) => Kind::Str,
+            Self::Bytes(_) => Kind::Bytes,
+        }
+    }
+}
+
+impl From<bool> for ConfigValue {
+    fn from(v: bool) -> Self {
+        Self::Bool(v)
+    }
+}
+
+impl From<i32> for ConfigValue {
+    fn from(v: i32) -> Self {
+        Self::I32(v)
+    }
+}
+
+impl From<u32> for ConfigValue {
+    fn from(v: u32) -> Self {
+        Self::U32(v)
+    }
+}
+
+impl From<f32> for ConfigValue {
+    fn from(v: f32) -> Self {
+        Self::F32(v)
+    }
+}
+
+impl From<&str> for ConfigValue {
+    fn from(v: &str) -> Self {
+        Self::Str(String::from(v))
+    }
+}
+
+impl From<String> for ConfigValue {
+    fn from(v: String) -> Self {
+        Self::Str(v)
+    }
+}
+
+impl From<Vec<u8>> for ConfigValue {
+    fn from(v: Vec<u8>) -> Self {
+        Self::Bytes(v)
+    }
+}
+
 const TYPE_BOOL: u8 = 0;
 const TYPE_I32: u8 = 1;
 const TYPE_U32: u8 = 2;
@@ -103,6 +181,9 @@ const TYPE_BYTES: u8 = 5;
 /// Key-value configuration store.
 pub struct Config {
     entries: Vec<(String, ConfigValue)>,
+    /// Modification time of the file at the last load, as a
+    /// [`mtime_key`].
+    loaded_mtime: Option<u64>,
 }
 
 impl Config {
@@ -110,21 +191,67 @@ impl Config {
     pub fn new() -> Self {
         Self {
             entries: Vec::new(),
+            loaded_mtime: None,
         }
     }
 
     /// Load a configuration from a file.
     pub fn load(path: &str) -> Result<Self, ConfigError> {
+        // Stat before reading so an edit made in between is picked up by
+        // the next `reload_if_changed`.
+        let mtime = mtime_key(&crate::io::stat(path)?.st_mtime);
+        let mut config = Self::read(path)?;
+        config.loaded_mtime = Some(mtime);
+        Ok(config)
+    }
+
+    /// Load a configuration and [upgrade](ConfigSchema::upgrade) it to
+    /// `schema`, returning it with the violations that were fixed.
+    ///
+    /// The upgraded config is not saved; call [`save()`](Self::save) to
+    /// keep the migrated values.
+    pub fn load_with_schema(
+        path: &str,
+        schema: &ConfigSchema,
+    ) -> Result<(Self, Vec<Violation>), ConfigError> {
+        let mut config = Self::load(path)?;
+        let fixed = schema.upgrade(&mut config);
+        Ok((config, fixed))
+    }
+
+    fn read(path: &str) -> Result<Self, ConfigError> {
         let data = crate::io::read_to_vec(path)?;
         if data.len() > MAX_FILE_SIZE {
             return Err(ConfigError::TooLarge);
         }
-        Self::deserialize(&data)
+        Self::from_bytes(&data)
+    }
+
+    /// Re-read the file at `path` if it was modified since it was last
+    /// loaded, returning whether a reload happened.
+    ///
+    /// Only the file's modification time is checked when nothing changed,
+    /// so this is cheap enough to call every frame. A config that was not
+    /// created by [`load()`](Self::load) is always reloaded the first
+    /// time. If the new contents fail to parse, the current values are
+    /// kept and the error is returned; the file is not re-parsed until it
+    /// changes again.
+    ///
+    /// Saving with [`save()`](Self::save) also updates the modification
+    /// time, so the next call after a save reloads the just-written file.
+    pub fn reload_if_changed(&mut self, path: &str) -> Result<bool, ConfigError> {
+        let mtime = mtime_key(&crate::io::stat(path)?.st_mtime);
+        if self.loaded_mtime.is_some_and(|loaded| mtime <= loaded) {
+            return Ok(false);
+        }
+        self.loaded_mtime = Some(mtime);
+        self.entries = Self::read(path)?.entries;
+        Ok(true)
     }
 
     /// Save the configuration to a file.
     pub fn save(&self, path: &str) -> Result<(), ConfigError> {
-        let data = self.serialize()?;
+        let data = self.to_bytes()?;
         crate::io::write_bytes(path, &data)?;
         Ok(())
     }
@@ -189,6 +316,50 @@ impl Config {
         }
     }
 
+    /// Get an `i32`, or `default` if the key is missing or not an `I32`.
+    pub fn get_i32_or(&self, key: &str, default: i32) -> i32 {
+        self.get_i32(key).unwrap_or(default)
+    }
+
+    /// Get an `f32` clamped to `lo..=hi`, or `default` if the key is
+    /// missing, not an `F32`, or NaN.
+    ///
+    /// Panics if `lo > hi`, like [`f32::clamp`].
+    pub fn get_f32_clamped(&self, key: &str, default: f32, lo: f32, hi: f32) -> f32 {
+        match self.get_f32(key) {
+            Some(v) if !v.is_nan() => v.clamp(lo, hi),
+            _ => default,
+        }
+    }
+
+    /// Get an `I32` value converted to an enum (or any `TryFrom<i32>`
+    /// type), or `default` if the key is missing or the value is out of
+    /// range.
+    ///
+    /// ```ignore
+    /// #[derive(TryFromPrimitive)]
+    /// #[repr(i32)]
+    /// enum Difficulty { Easy, Normal, Hard }
+    ///
+    /// let d = cfg.get_enum("difficulty", Difficulty::Normal);
+    /// ```
+    pub fn get_enum<T: TryFrom<i32>>(&self, key: &str, default: T) -> T {
+        self.get_i32(key)
+            .and_then(|v| T::try_from(v).ok())
+            .unwrap_or(default)
+    }
+
+    /// The app release that wrote this config, stored under
+    /// [`APP_VERSION_KEY`].
+    pub fn app_version(&self) -> Option<u32> {
+        self.get_u32(APP_VERSION_KEY)
+    }
+
+    /// Record the app release writing this config.
+    pub fn set_app_version(&mut self, version: u32) {
+        self.set(APP_VERSION_KEY, ConfigValue::U32(version));
+    }
+
     /// Iterate over all entries.
     pub fn iter(&self) -> impl Iterator<Item = (&str, &ConfigValue)> {
         self.entries.iter().map(|(k, v)| (k.as_str(), v))
@@ -204,7 +375,9 @@ impl Config {
         self.entries.is_empty()
     }
 
-    fn serialize(&self) -> Result<Vec<u8>, ConfigError> {
+    /// Serialize to the binary RCFG format written by
+    /// [`save()`](Self::save).
+    pub fn to_bytes(&self) -> Result<Vec<u8>, ConfigError> {
         if self.entries.len() > u16::MAX as usize {
             return Err(ConfigError::TooLarge);
         }
@@ -263,13 +436,18 @@ impl Config {
             }
         }
 
+        let checksum = crate::hash::crc32(&buf);
+        buf.extend_from_slice(&checksum.to_le_bytes());
+
         if buf.len() > MAX_FILE_SIZE {
             return Err(ConfigError::TooLarge);
         }
         Ok(buf)
     }
 
-    fn deserialize(data: &[u8]) -> Result<Self, ConfigError> {
+    /// Parse the binary RCFG format, accepting both checksummed (version 2)
+    /// and legacy (version 1) files.
+    pub fn from_bytes(data: &[u8]) -> Result<Self, ConfigError> {
         if data.len() < 8 {
             return Err(ConfigError::InvalidFormat);
         }
@@ -277,7 +455,12 @@ impl Config {
             return Err(ConfigError::InvalidFormat);
         }
         let version = u16::from_le_bytes([data[4], data[5]]);
-        if version != VERSION {
+        let data = match version {
+            VERSION => crate::hash::strip_crc32(data).ok_or(ConfigError::ChecksumMismatch)?,
+            VERSION_1 => data,
+            _ => return Err(ConfigError::InvalidFormat),
+        };
+        if data.len() < 8 {
             return Err(ConfigError::InvalidFormat);
         }
         let count = u16::from_le_bytes([data[6], data[7]]) as usize;
@@ -364,10 +547,20 @@ impl Config {
             entries.push((String::from(key), value));
         }
 
-        Ok(Self { entries })
+        Ok(Self {
+            entries,
+            loaded_mtime: None,
+        })
     }
 }
 
+/// Pack a timestamp into an integer that orders chronologically.
+fn mtime_key(t: &crate::sys::ScePspDateTime) -> u64 {
+    let date = (t.year as u64 * 13 + t.month as u64) * 32 + t.day as u64;
+    let secs = ((date * 24 + t.hour as u64) * 60 + t.minutes as u64) * 60 + t.seconds as u64;
+    secs * 1_000_000 + t.microseconds as u64
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self::new()
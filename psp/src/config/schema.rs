@@ -0,0 +1,298 @@
+//! Schema validation and versioned migration for [`Config`].
+//!
+//! Config files outlive the code that wrote them: a file saved by an older
+//! release may lack keys, use old names, or hold values a newer release
+//! rejects. A [`ConfigSchema`] describes the keys the current release
+//! expects, reports what's wrong with a loaded config and fills in
+//! defaults, and runs migrations on files written by older releases.
+//!
+//! The release that wrote a file is stored in the config itself under
+//! [`APP_VERSION_KEY`](super::APP_VERSION_KEY), separate from the RCFG
+//! format version.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use psp::config::{Config, ConfigSchema, Kind, Rule};
+//!
+//! let schema = ConfigSchema::new(2)
+//!     .key("volume", Kind::I32, Rule::Range(0..=100), 80)
+//!     .key("player_name", Kind::Str, Rule::MaxLen(16), "Player")
+//!     // Release 1 stored the volume as "vol".
+//!     .migration(1, |cfg| {
+//!         if let Some(v) = cfg.remove("vol") {
+//!             cfg.set("volume", v);
+//!         }
+//!     });
+//!
+//! let (cfg, fixed) = Config::load_with_schema("ms0:/game.rcfg", &schema)?;
+//! ```
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ops::RangeInclusive;
+
+use super::{Config, ConfigValue};
+
+/// The type of a config value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Bool,
+    I32,
+    U32,
+    F32,
+    Str,
+    Bytes,
+}
+
+/// A constraint on a value's contents.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Rule {
+    /// Any value of the right kind.
+    Any,
+    /// An `I32` or `U32` within the range.
+    Range(RangeInclusive<i64>),
+    /// An `F32` within the range. NaN is out of range.
+    FloatRange(RangeInclusive<f32>),
+    /// A `Str` of at most this many characters, or `Bytes` of at most
+    /// this many bytes.
+    MaxLen(usize),
+}
+
+impl Rule {
+    fn allows(&self, value: &ConfigValue) -> bool {
+        match (self, value) {
+            (Self::Any, _) => true,
+            (Self::Range(r), ConfigValue::I32(v)) => r.contains(&(*v as i64)),
+            (Self::Range(r), ConfigValue::U32(v)) => r.contains(&(*v as i64)),
+            (Self::FloatRange(r), ConfigValue::F32(v)) => r.contains(v),
+            (Self::MaxLen(n), ConfigValue::Str(s)) => s.chars().count() <= *n,
+            (Self::MaxLen(n), ConfigValue::Bytes(b)) => b.len() <= *n,
+            _ => false,
+        }
+    }
+
+    fn applies_to(&self, kind: Kind) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Range(_) => matches!(kind, Kind::I32 | Kind::U32),
+            Self::FloatRange(_) => kind == Kind::F32,
+            Self::MaxLen(_) => matches!(kind, Kind::Str | Kind::Bytes),
+        }
+    }
+}
+
+/// A problem found by [`ConfigSchema::validate()`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Violation {
+    /// A schema key is absent.
+    Missing { key: String },
+    /// A schema key holds a value of another kind.
+    WrongType {
+        key: String,
+        expected: Kind,
+        found: Kind,
+    },
+    /// A schema key's value breaks its [`Rule`].
+    OutOfRange { key: String },
+    /// The config has a key the schema doesn't know.
+    UnknownKey { key: String },
+}
+
+impl Violation {
+    /// The key the violation is about.
+    pub fn key(&self) -> &str {
+        match self {
+            Self::Missing { key }
+            | Self::WrongType { key, .. }
+            | Self::OutOfRange { key }
+            | Self::UnknownKey { key } => key,
+        }
+    }
+}
+
+impl core::fmt::Display for Violation {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Missing { key } => write!(f, "config key {key:?} is missing"),
+            Self::WrongType {
+                key,
+                expected,
+                found,
+            } => write!(
+                f,
+                "config key {key:?} should be {expected:?}, found {found:?}"
+            ),
+            Self::OutOfRange { key } => write!(f, "config key {key:?} is out of range"),
+            Self::UnknownKey { key } => write!(f, "unknown config key {key:?}"),
+        }
+    }
+}
+
+/// Upgrades a config from one app release to the next.
+pub type Migration = fn(&mut Config);
+
+struct KeySpec {
+    name: &'static str,
+    kind: Kind,
+    rule: Rule,
+    default: ConfigValue,
+}
+
+/// The keys a config should have, and how to upgrade older files.
+pub struct ConfigSchema {
+    version: u32,
+    keys: Vec<KeySpec>,
+    /// Sorted by the version each migration upgrades from.
+    migrations: Vec<(u32, Migration)>,
+}
+
+impl ConfigSchema {
+    /// Create an empty schema for app release `version`.
+    pub fn new(version: u32) -> Self {
+        Self {
+            version,
+            keys: Vec::new(),
+            migrations: Vec::new(),
+        }
+    }
+
+    /// The app release this schema describes.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Add a key, replacing any earlier definition of the same name.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rule` doesn't apply to `kind`, or `default` isn't a
+    /// valid value for the key. Both are mistakes in the schema itself.
+    pub fn key(
+        mut self,
+        name: &'static str,
+        kind: Kind,
+        rule: Rule,
+        default: impl Into<ConfigValue>,
+    ) -> Self {
+        let default = default.into();
+        assert!(
+            rule.applies_to(kind),
+            "rule {rule:?} can't apply to {kind:?}"
+        );
+        assert!(
+            default.kind() == kind && rule.allows(&default),
+            "invalid default for config key {name:?}"
+        );
+        self.keys.retain(|spec| spec.name != name);
+        self.keys.push(KeySpec {
+            name,
+            kind,
+            rule,
+            default,
+        });
+        self
+    }
+
+    /// Register a migration from release `from_version` to the next one.
+    ///
+    /// When a config written by release `v` is upgraded, every migration
+    /// with `from_version >= v` (and below this schema's version) runs, in
+    /// ascending order. Registering the same `from_version` twice replaces
+    /// the earlier migration.
+    pub fn migration(mut self, from_version: u32, migrate: Migration) -> Self {
+        match self
+            .migrations
+            .binary_search_by_key(&from_version, |&(v, _)| v)
+        {
+            Ok(i) => self.migrations[i].1 = migrate,
+            Err(i) => self.migrations.insert(i, (from_version, migrate)),
+        }
+        self
+    }
+
+    /// The default value for `key`, if it's in the schema.
+    pub fn default_of(&self, key: &str) -> Option<&ConfigValue> {
+        self.spec(key).map(|spec| &spec.default)
+    }
+
+    /// Check `config` against the schema.
+    ///
+    /// Schema keys are reported in definition order, then unknown keys in
+    /// config order. [`APP_VERSION_KEY`](super::APP_VERSION_KEY) is never
+    /// reported as unknown.
+    pub fn validate(&self, config: &Config) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        for spec in &self.keys {
+            if let Some(violation) = spec.check(config.get(spec.name)) {
+                violations.push(violation);
+            }
+        }
+        for (key, _) in config.iter() {
+            if key != super::APP_VERSION_KEY && self.spec(key).is_none() {
+                violations.push(Violation::UnknownKey {
+                    key: String::from(key),
+                });
+            }
+        }
+        violations
+    }
+
+    /// Replace every missing, wrongly typed or out-of-range schema key
+    /// with its default, returning the violations that were fixed.
+    ///
+    /// Unknown keys are left alone; remove them in a migration once the
+    /// release that used them is gone.
+    pub fn apply_defaults(&self, config: &mut Config) -> Vec<Violation> {
+        let mut fixed = Vec::new();
+        for spec in &self.keys {
+            if let Some(violation) = spec.check(config.get(spec.name)) {
+                config.set(spec.name, spec.default.clone());
+                fixed.push(violation);
+            }
+        }
+        fixed
+    }
+
+    /// Bring a config written by any earlier release up to this schema.
+    ///
+    /// Runs the migrations newer than the config's stored
+    /// [`app_version()`](Config::app_version) (a config without one is
+    /// treated as release 0), stamps it with this schema's version, and
+    /// then applies defaults. Returns the violations
+    /// [`apply_defaults()`](Self::apply_defaults) fixed.
+    ///
+    /// A config from a newer release is only defaulted, never downgraded.
+    pub fn upgrade(&self, config: &mut Config) -> Vec<Violation> {
+        let stored = config.app_version().unwrap_or(0);
+        if stored < self.version {
+            for &(from, migrate) in &self.migrations {
+                if from >= stored && from < self.version {
+                    migrate(config);
+                }
+            }
+            config.set_app_version(self.version);
+        }
+        self.apply_defaults(config)
+    }
+
+    fn spec(&self, key: &str) -> Option<&KeySpec> {
+        self.keys.iter().find(|spec| spec.name == key)
+    }
+}
+
+impl KeySpec {
+    fn check(&self, value: Option<&ConfigValue>) -> Option<Violation> {
+        let key = || String::from(self.name);
+        match value {
+            None => Some(Violation::Missing { key: key() }),
+            Some(v) if v.kind() != self.kind => Some(Violation::WrongType {
+                key: key(),
+                expected: self.kind,
+                found: v.kind(),
+            }),
+            Some(v) if !self.rule.allows(v) => Some(Violation::OutOfRange { key: key() }),
+            Some(_) => None,
+        }
+    }
+}
@@ -0,0 +1,140 @@
+//! ChaCha20 stream cipher.
+//!
+//! A small, table-free implementation of ChaCha20 as specified in
+//! RFC 8439 (256-bit key, 96-bit nonce, 32-bit block counter), used by
+//! [`Savedata::encrypt_with`](crate::savedata::Savedata::encrypt_with)
+//! to keep save files from being read or edited with a hex editor.
+//!
+//! The cipher itself is sound, but a key compiled into a game can be
+//! pulled out of the binary, so anything built on it is anti-tamper
+//! rather than real protection. Pair it with a MAC such as
+//! [`crate::hash::siphash24`] to detect modified ciphertext: on its own a
+//! stream cipher lets flipped bits through undetected. [`fill_random`]
+//! makes nonces.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use psp::crypto;
+//!
+//! let mut data = *b"attack at dawn";
+//! crypto::chacha20_xor(&KEY, &nonce, 1, &mut data);
+//! // ... and the same call again to decrypt.
+//! crypto::chacha20_xor(&KEY, &nonce, 1, &mut data);
+//! ```
+
+use core::sync::atomic::{AtomicU32, Ordering};
+
+/// Size of a ChaCha20 key in bytes.
+pub const KEY_SIZE: usize = 32;
+
+/// Size of a ChaCha20 nonce in bytes.
+pub const NONCE_SIZE: usize = 12;
+
+/// Size of one keystream block in bytes.
+pub const BLOCK_SIZE: usize = 64;
+
+/// Calls to [`fill_random`] so far, mixed into each seed.
+static RANDOM_CALLS: AtomicU32 = AtomicU32::new(0);
+
+/// "expand 32-byte k"
+const CONSTANTS: [u32; 4] = [0x6170_7865, 0x3320_646e, 0x7962_2d32, 0x6b20_6574];
+
+#[inline(always)]
+fn quarter_round(s: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    s[a] = s[a].wrapping_add(s[b]);
+    s[d] = (s[d] ^ s[a]).rotate_left(16);
+    s[c] = s[c].wrapping_add(s[d]);
+    s[b] = (s[b] ^ s[c]).rotate_left(12);
+    s[a] = s[a].wrapping_add(s[b]);
+    s[d] = (s[d] ^ s[a]).rotate_left(8);
+    s[c] = s[c].wrapping_add(s[d]);
+    s[b] = (s[b] ^ s[c]).rotate_left(7);
+}
+
+fn le_words<const N: usize>(bytes: &[u8]) -> [u32; N] {
+    core::array::from_fn(|i| {
+        u32::from_le_bytes([
+            bytes[i * 4],
+            bytes[i * 4 + 1],
+            bytes[i * 4 + 2],
+            bytes[i * 4 + 3],
+        ])
+    })
+}
+
+/// The keystream block for `counter`.
+pub fn chacha20_block(
+    key: &[u8; KEY_SIZE],
+    nonce: &[u8; NONCE_SIZE],
+    counter: u32,
+) -> [u8; BLOCK_SIZE] {
+    let key: [u32; 8] = le_words(key);
+    let nonce: [u32; 3] = le_words(nonce);
+    let mut input = [0u32; 16];
+    input[..4].copy_from_slice(&CONSTANTS);
+    input[4..12].copy_from_slice(&key);
+    input[12] = counter;
+    input[13..].copy_from_slice(&nonce);
+
+    let mut s = input;
+    for _ in 0..10 {
+        quarter_round(&mut s, 0, 4, 8, 12);
+        quarter_round(&mut s, 1, 5, 9, 13);
+        quarter_round(&mut s, 2, 6, 10, 14);
+        quarter_round(&mut s, 3, 7, 11, 15);
+        quarter_round(&mut s, 0, 5, 10, 15);
+        quarter_round(&mut s, 1, 6, 11, 12);
+        quarter_round(&mut s, 2, 7, 8, 13);
+        quarter_round(&mut s, 3, 4, 9, 14);
+    }
+
+    let mut out = [0u8; BLOCK_SIZE];
+    for (i, word) in s.iter().enumerate() {
+        out[i * 4..i * 4 + 4].copy_from_slice(&word.wrapping_add(input[i]).to_le_bytes());
+    }
+    out
+}
+
+/// Encrypt or decrypt `data` in place, starting at block `counter`.
+///
+/// XORs `data` with the keystream, so the same call undoes itself. Never
+/// reuse a nonce with the same key: two messages encrypted under one
+/// (key, nonce) pair leak their XOR. RFC 8439 starts at counter 1,
+/// keeping block 0 for deriving a one-time MAC key.
+pub fn chacha20_xor(key: &[u8; KEY_SIZE], nonce: &[u8; NONCE_SIZE], counter: u32, data: &mut [u8]) {
+    for (i, chunk) in data.chunks_mut(BLOCK_SIZE).enumerate() {
+        let block = chacha20_block(key, nonce, counter.wrapping_add(i as u32));
+        for (b, k) in chunk.iter_mut().zip(block) {
+            *b ^= k;
+        }
+    }
+}
+
+/// Fill `buf` with random bytes for nonces and masking keys.
+///
+/// The generator behind [`crate::rand`] can be reseeded by the game, e.g.
+/// for replays, which would repeat nonces. This source can't be: each
+/// call seeds the kernel's `sceKernelUtilsMt19937` generator afresh from
+/// the system timer, the RTC tick and a call counter, so even calls in
+/// the same microsecond get different seeds. That keeps nonces from
+/// repeating, but with 32-bit seeds it is no source of key material.
+pub fn fill_random(buf: &mut [u8]) {
+    let call = RANDOM_CALLS.fetch_add(1, Ordering::Relaxed);
+    let mut tick = 0u64;
+    let mut ctx = crate::sys::SceKernelUtilsMt19937Context {
+        count: 0,
+        state: [0; 624],
+    };
+    // SAFETY: `tick` and `ctx` are valid for the calls.
+    unsafe {
+        crate::sys::sceRtcGetCurrentTick(&mut tick);
+        let time = crate::sys::sceKernelGetSystemTimeWide() as u64;
+        let seed = (time ^ tick ^ (tick >> 32)) as u32 ^ call.wrapping_mul(0x9E37_79B9);
+        crate::sys::sceKernelUtilsMt19937Init(&mut ctx, seed);
+        for chunk in buf.chunks_mut(4) {
+            let word = crate::sys::sceKernelUtilsMt19937UInt(&mut ctx).to_le_bytes();
+            chunk.copy_from_slice(&word[..chunk.len()]);
+        }
+    }
+}
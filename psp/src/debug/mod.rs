@@ -0,0 +1,536 @@
+//! Debug support.
+//!
+//! You should use the `dprintln!` and `dprint!` macros.
+//!
+//! Thread-safe: access to the character buffer is protected by a spinlock.
+//!
+//! [`configure()`] confines the console to a region of the screen with its
+//! own colors, optionally drawn over a GU-rendered app's framebuffer;
+//! [`clear()`] and [`set_cursor()`] control where the next output goes.
+//!
+//! [`backtrace()`] returns the calling thread's stack of return addresses
+//! for crash reports. The [`remote`] submodule serves a command console
+//! over TCP.
+
+pub mod remote;
+
+use alloc::vec::Vec;
+
+use crate::sync::SpinMutex;
+use crate::sys;
+use core::fmt;
+
+/// Like `println!`, but prints to the PSP screen.
+#[macro_export]
+macro_rules! dprintln {
+    () => {
+        $crate::dprint!("\n")
+    };
+    ($($arg:tt)*) => {{
+        $crate::dprint!($($arg)*);
+        $crate::dprint!("\n");
+    }};
+}
+
+/// Like `print!`, but prints to the PSP screen.
+#[macro_export]
+macro_rules! dprint {
+    ($($arg:tt)*) => {{
+        $crate::debug::print_args(core::format_args!($($arg)*))
+    }}
+}
+
+/// Placement and colors of the debug console.
+///
+/// By default the console covers the whole screen in white on black and
+/// shows its own framebuffer. Confining it to a region with
+/// [`overlay`](Self::overlay) set keeps a GU-rendered app visible around
+/// it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DebugScreenConfig {
+    /// Text color, ABGR.
+    pub fg_color: u32,
+    /// Color the region is cleared to before drawing, ABGR.
+    pub bg_color: u32,
+    /// Left edge of the region in pixels.
+    pub origin_x: usize,
+    /// Top edge of the region in pixels.
+    pub origin_y: usize,
+    /// Region width in pixels, rounded down to whole characters.
+    pub width: usize,
+    /// Region height in pixels, rounded down to whole lines.
+    pub height: usize,
+    /// Continue long lines on the next line instead of cutting them off.
+    pub wrap: bool,
+    /// Draw over whatever 32-bit framebuffer is on screen instead of
+    /// switching the display to the console's own. Falls back to the
+    /// console's framebuffer when the display shows none.
+    pub overlay: bool,
+}
+
+impl DebugScreenConfig {
+    /// The whole screen, white on black, on the console's own framebuffer.
+    pub const fn new() -> Self {
+        Self {
+            fg_color: 0xffff_ffff,
+            bg_color: 0,
+            origin_x: 0,
+            origin_y: 0,
+            width: DISPLAY_WIDTH,
+            height: DISPLAY_HEIGHT,
+            wrap: true,
+            overlay: false,
+        }
+    }
+}
+
+impl Default for DebugScreenConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+static CHARS: SpinMutex<CharBuffer> = SpinMutex::new(CharBuffer::new());
+
+/// Set the console's region and colors.
+///
+/// The region is clipped to the screen and holds at least one character.
+/// Reconfiguring clears the text; the screen is updated by the next print
+/// or [`clear()`].
+pub fn configure(config: DebugScreenConfig) {
+    let origin_x = config.origin_x.min(DISPLAY_WIDTH - MsxFont::CHAR_WIDTH);
+    let origin_y = config.origin_y.min(DISPLAY_HEIGHT - MsxFont::CHAR_HEIGHT);
+    let config = DebugScreenConfig {
+        origin_x,
+        origin_y,
+        width: config
+            .width
+            .clamp(MsxFont::CHAR_WIDTH, DISPLAY_WIDTH - origin_x),
+        height: config
+            .height
+            .clamp(MsxFont::CHAR_HEIGHT, DISPLAY_HEIGHT - origin_y),
+        ..config
+    };
+    *CHARS.lock() = CharBuffer::with_config(config);
+}
+
+/// The current console configuration.
+pub fn config() -> DebugScreenConfig {
+    CHARS.lock().config
+}
+
+/// Erase the console's text, move the cursor to the top left and redraw.
+pub fn clear() {
+    let mut chars = CHARS.lock();
+    chars.clear();
+    update(&chars);
+}
+
+/// Move the cursor to `row` and `col` of the console (both from 0),
+/// clamped to its size. Later output overwrites the text there.
+pub fn set_cursor(row: usize, col: usize) {
+    let mut chars = CHARS.lock();
+    chars.set_cursor(row, col);
+}
+
+/// Draw the console again without printing anything.
+///
+/// With [`overlay`](DebugScreenConfig::overlay) set, a GU app calls this
+/// after `sceGuSwapBuffers` to put the text back on the buffer now being
+/// shown.
+pub fn redraw() {
+    update(&CHARS.lock());
+}
+
+/// Update the screen.
+fn update(chars: &CharBuffer) {
+    let config = &chars.config;
+    unsafe {
+        let target = target(config.overlay);
+        target.fill(
+            config.origin_x,
+            config.origin_y,
+            config.width,
+            config.height,
+            config.bg_color,
+        );
+
+        for (i, line) in chars.lines().iter().enumerate() {
+            target.put_str::<MsxFont>(
+                &line.chars[0..line.len],
+                config.origin_x,
+                config.origin_y + i * MsxFont::CHAR_HEIGHT,
+                config.fg_color,
+            )
+        }
+    }
+}
+
+trait Font {
+    const CHAR_WIDTH: usize;
+    const CHAR_HEIGHT: usize;
+
+    fn put_char(target: &Target, x: usize, y: usize, color: u32, c: u8);
+}
+
+struct MsxFont;
+
+impl Font for MsxFont {
+    const CHAR_HEIGHT: usize = 10;
+    const CHAR_WIDTH: usize = 6;
+
+    fn put_char(target: &Target, x: usize, y: usize, color: u32, c: u8) {
+        debug_assert!((c as usize) < 256, "font index out of bounds");
+
+        unsafe {
+            let mut ptr = target.base.add(x + y * target.stride);
+
+            for i in 0..8 {
+                for j in 0..8 {
+                    if MSX_FONT[c as usize * 8 + i] & (0b1000_0000 >> j) != 0 {
+                        *ptr = color;
+                    }
+
+                    ptr = ptr.offset(1);
+                }
+
+                ptr = ptr.add(target.stride - 8);
+            }
+        }
+    }
+}
+
+use crate::constants::{SCREEN_HEIGHT, SCREEN_WIDTH, VRAM_BASE_UNCACHED, VRAM_BUFFER_WIDTH};
+
+const BUFFER_WIDTH: usize = VRAM_BUFFER_WIDTH as usize;
+const DISPLAY_HEIGHT: usize = SCREEN_HEIGHT as usize;
+const DISPLAY_WIDTH: usize = SCREEN_WIDTH as usize;
+
+/// A 32-bit framebuffer to draw the console into.
+struct Target {
+    base: *mut u32,
+    /// Buffer width in pixels.
+    stride: usize,
+}
+
+impl Target {
+    unsafe fn fill(&self, x: usize, y: usize, w: usize, h: usize, color: u32) {
+        for row in y..y + h {
+            for col in x..x + w {
+                unsafe {
+                    *self.base.add(col + row * self.stride) = color;
+                }
+            }
+        }
+    }
+
+    unsafe fn put_str<T: Font>(&self, s: &[u8], x: usize, y: usize, color: u32) {
+        if y + T::CHAR_HEIGHT > DISPLAY_HEIGHT {
+            return;
+        }
+
+        for (i, c) in s.iter().enumerate() {
+            if T::CHAR_WIDTH * (i + 1) + x > DISPLAY_WIDTH {
+                break;
+            }
+
+            if *c != b'\0' {
+                T::put_char(self, T::CHAR_WIDTH * i + x, y, color, *c);
+            }
+        }
+    }
+}
+
+/// The framebuffer on screen when overlaying one, or the console's own.
+unsafe fn target(overlay: bool) -> Target {
+    if overlay
+        && let Some(fb) = crate::display::current_framebuffer()
+        && matches!(fb.pixel_format, sys::DisplayPixelFormat::Psm8888)
+    {
+        return Target {
+            base: (fb.top_addr as u32 | VRAM_BASE_UNCACHED) as *mut u32,
+            stride: fb.buf_width,
+        };
+    }
+    unsafe { init() }
+}
+
+unsafe fn init() -> Target {
+    // The OR operation here specifies the address bypasses cache.
+    let base = (VRAM_BASE_UNCACHED | unsafe { sys::sceGeEdramGetAddr() } as u32) as *mut u32;
+
+    unsafe {
+        sys::sceDisplaySetMode(sys::DisplayMode::Lcd, DISPLAY_WIDTH, DISPLAY_HEIGHT);
+        sys::sceDisplaySetFrameBuf(
+            base as *const u8,
+            BUFFER_WIDTH,
+            sys::DisplayPixelFormat::Psm8888,
+            sys::DisplaySetBufSync::NextFrame,
+        );
+    }
+    Target {
+        base,
+        stride: BUFFER_WIDTH,
+    }
+}
+
+#[doc(hidden)]
+pub fn print_args(arguments: core::fmt::Arguments<'_>) {
+    use fmt::Write;
+
+    let mut guard = CHARS.lock();
+    let _ = write!(*guard, "{}", arguments);
+    update(&guard);
+}
+
+/// Return addresses on the calling thread's stack, innermost first.
+///
+/// Collects up to `max_frames` addresses. Each is the instruction after a
+/// call, so subtract 8 (the call and its delay slot) to get the call site,
+/// and look them up in the ELF with `addr2line` or `objdump` after
+/// subtracting the module's text address.
+///
+/// Rust code on the PSP has no frame pointer or unwind tables at runtime,
+/// so frames are found by reading each function's prologue: scanning back
+/// from the current address to the `addiu $sp, $sp, -N` that allocated
+/// the frame and the `sw $ra, off($sp)` that saved the return address.
+/// The walk stops early at a function that doesn't follow that pattern
+/// (large frames set up through a register, hand-written assembly) or at
+/// an address outside RAM. Frames of inlined functions don't appear.
+#[inline(never)]
+pub fn backtrace(max_frames: usize) -> Vec<u32> {
+    #[cfg(target_os = "psp")]
+    {
+        let mut frames = Vec::new();
+        let mut pc: u32;
+        let mut sp: u32;
+        unsafe {
+            core::arch::asm!(
+                "1:",
+                "la {pc}, 1b",
+                "move {sp}, $sp",
+                pc = out(reg) pc,
+                sp = out(reg) sp,
+                options(nomem, nostack),
+            );
+        }
+
+        while frames.len() < max_frames {
+            let Some((frame_size, ra_offset)) = (unsafe { find_prologue(pc) }) else {
+                break;
+            };
+            let ra_slot = sp.wrapping_add(ra_offset);
+            if !is_ram_address(ra_slot) {
+                break;
+            }
+            let ra = unsafe { core::ptr::read_volatile(ra_slot as *const u32) };
+            if !is_ram_address(ra) {
+                break;
+            }
+            frames.push(ra);
+            pc = ra;
+            sp = sp.wrapping_add(frame_size);
+        }
+        frames
+    }
+
+    #[cfg(not(target_os = "psp"))]
+    {
+        let _ = max_frames;
+        Vec::new()
+    }
+}
+
+/// How far back [`find_prologue`] scans for a function's start, in
+/// instructions.
+#[cfg(target_os = "psp")]
+const MAX_PROLOGUE_SCAN: u32 = 4096;
+
+/// Find the frame size and return address slot of the function containing
+/// `pc` by scanning back to its prologue.
+#[cfg(target_os = "psp")]
+unsafe fn find_prologue(pc: u32) -> Option<(u32, u32)> {
+    // addiu $sp, $sp, imm
+    const ADDIU_SP: u32 = 0x27BD_0000;
+    // sw $ra, imm($sp)
+    const SW_RA: u32 = 0xAFBF_0000;
+
+    let mut ra_offset = None;
+    let mut addr = pc & !3;
+    for _ in 0..MAX_PROLOGUE_SCAN {
+        addr = addr.wrapping_sub(4);
+        if !is_ram_address(addr) {
+            return None;
+        }
+        let insn = unsafe { core::ptr::read_volatile(addr as *const u32) };
+        let imm = insn as u16 as i16;
+        if insn & 0xFFFF_0000 == SW_RA {
+            ra_offset = Some(imm as u32);
+        } else if insn & 0xFFFF_0000 == ADDIU_SP && imm < 0 {
+            // The epilogue's `addiu $sp, $sp, N` frees the frame; only
+            // the negative one allocates it.
+            return ra_offset.map(|offset| ((-(imm as i32)) as u32, offset));
+        }
+    }
+    None
+}
+
+/// End of RAM, or 0 until [`ram_end`] has asked [`crate::model`].
+#[cfg(target_os = "psp")]
+static RAM_END: core::sync::atomic::AtomicU32 = core::sync::atomic::AtomicU32::new(0);
+
+/// End of RAM: 32 MB above its start, or 64 MB on the models with
+/// [extra RAM](crate::model::has_extra_ram).
+#[cfg(target_os = "psp")]
+fn ram_end() -> u32 {
+    use core::sync::atomic::Ordering;
+
+    match RAM_END.load(Ordering::Relaxed) {
+        0 => {
+            let end = if crate::model::has_extra_ram() {
+                0x0C00_0000
+            } else {
+                0x0A00_0000
+            };
+            RAM_END.store(end, Ordering::Relaxed);
+            end
+        },
+        end => end,
+    }
+}
+
+/// Whether `addr` is a word-aligned address in RAM the caller may read.
+#[cfg(target_os = "psp")]
+fn is_ram_address(addr: u32) -> bool {
+    #[cfg(feature = "kernel")]
+    const RAM_START: u32 = 0x0800_0000;
+    #[cfg(not(feature = "kernel"))]
+    const RAM_START: u32 = 0x0880_0000;
+
+    #[cfg(not(feature = "kernel"))]
+    if addr >= 0x4000_0000 {
+        return false;
+    }
+    addr % 4 == 0 && (RAM_START..ram_end()).contains(&(addr & 0x1FFF_FFFF))
+}
+
+const ROWS: usize = DISPLAY_HEIGHT / MsxFont::CHAR_HEIGHT;
+const COLS: usize = DISPLAY_WIDTH / MsxFont::CHAR_WIDTH;
+
+#[derive(Copy, Clone)]
+struct Line {
+    chars: [u8; COLS],
+    len: usize,
+}
+
+impl Line {
+    const fn new() -> Self {
+        Self {
+            chars: [0; COLS],
+            len: 0,
+        }
+    }
+}
+
+/// The console's text, one [`Line`] per row on screen.
+struct CharBuffer {
+    lines: [Line; ROWS],
+    config: DebugScreenConfig,
+    rows: usize,
+    cols: usize,
+    row: usize,
+    col: usize,
+    /// A newline was printed; move down before the next character, so a
+    /// final newline doesn't scroll the last line away.
+    advance_next: bool,
+}
+
+impl CharBuffer {
+    const fn new() -> Self {
+        Self::with_config(DebugScreenConfig::new())
+    }
+
+    const fn with_config(config: DebugScreenConfig) -> Self {
+        Self {
+            lines: [Line::new(); ROWS],
+            config,
+            rows: config.height / MsxFont::CHAR_HEIGHT,
+            cols: config.width / MsxFont::CHAR_WIDTH,
+            row: 0,
+            col: 0,
+            advance_next: false,
+        }
+    }
+
+    fn clear(&mut self) {
+        *self = Self::with_config(self.config);
+    }
+
+    fn set_cursor(&mut self, row: usize, col: usize) {
+        self.row = row.min(self.rows - 1);
+        self.col = col.min(self.cols - 1);
+        self.advance_next = false;
+    }
+
+    fn advance(&mut self) {
+        self.col = 0;
+        if self.row + 1 < self.rows {
+            self.row += 1;
+        } else {
+            self.lines[..self.rows].rotate_left(1);
+            self.lines[self.rows - 1] = Line::new();
+        }
+    }
+
+    fn add(&mut self, c: u8) {
+        if self.advance_next {
+            self.advance_next = false;
+            self.advance();
+        }
+
+        match c {
+            b'\n' => self.advance_next = true,
+            b'\t' => {
+                for _ in 0..4 {
+                    self.add(b' ');
+                }
+            },
+
+            _ => {
+                if self.col == self.cols {
+                    if !self.config.wrap {
+                        return;
+                    }
+                    self.advance();
+                }
+
+                let line = &mut self.lines[self.row];
+                line.chars[self.col] = c;
+                self.col += 1;
+                line.len = line.len.max(self.col);
+            },
+        }
+    }
+
+    fn lines(&self) -> &[Line] {
+        &self.lines[..self.rows]
+    }
+}
+
+impl fmt::Write for CharBuffer {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            match c as u32 {
+                0..=255 => self.add(c as u8),
+                _ => self.add(0),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Raw MSX font.
+///
+/// This is an 8bit x 256 black and white image.
+const MSX_FONT: [u8; 2048] = *include_bytes!("msxfont.bin");
@@ -0,0 +1,423 @@
+//! A line-based command console over TCP.
+//!
+//! [`start()`] spawns a thread that listens on a port and serves one client
+//! at a time, e.g. `nc <psp-ip> 2323`. Each line is a command followed by
+//! whitespace-separated arguments. The built-in commands are:
+//!
+//! - `help`: list the built-in and registered commands
+//! - `mem`: heap, kernel partition and VRAM usage
+//! - `threads`: every thread's name, priority and status
+//! - `screenshot [path]`: save a BMP, to `ms0:/remote_NNN.bmp` by default
+//! - `quit`: close the connection
+//!
+//! Games add their own with [`register()`]. [`stop()`] closes the
+//! console again, e.g. before [`net::term()`].
+//!
+//! # Handler threading
+//!
+//! Handlers run on the console thread, concurrently with the game loop.
+//! Anything that touches game state should not do so directly; push a
+//! request onto a queue the game drains once per frame instead, e.g. a
+//! static [`SpscQueue`](crate::sync::SpscQueue) with the handler as its
+//! only producer:
+//!
+//! ```ignore
+//! use psp::debug::remote;
+//! use psp::sync::SpscQueue;
+//!
+//! static REQUESTS: SpscQueue<u32, 16> = SpscQueue::new();
+//!
+//! remote::register("give_item", |args, out| match args.first().and_then(|a| a.parse().ok()) {
+//!     Some(id) if REQUESTS.push(id).is_ok() => out.push_str("queued\n"),
+//!     Some(_) => out.push_str("queue full\n"),
+//!     None => out.push_str("usage: give_item <id>\n"),
+//! });
+//! remote::start(2323)?;
+//!
+//! loop {
+//!     while let Some(id) = REQUESTS.pop() {
+//!         inventory.add(id);
+//!     }
+//!     // ...
+//! }
+//! ```
+
+use alloc::format;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt::Write as _;
+use core::sync::atomic::{AtomicBool, AtomicI32, AtomicU32, Ordering};
+
+use crate::net::{self, NetError, TcpListener, TcpStream};
+use crate::sync::SpinMutex;
+use crate::sys;
+use crate::thread::{JoinHandle, ThreadBuilder, ThreadError};
+
+/// Error starting the remote console.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemoteError {
+    /// [`net::init()`] hasn't been called, or [`net::term()`] has.
+    NetNotInitialized,
+    /// The console is already running.
+    AlreadyRunning,
+    /// Listening on the port failed.
+    Net(NetError),
+    /// The console thread couldn't be created.
+    Thread(ThreadError),
+}
+
+impl core::fmt::Display for RemoteError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::NetNotInitialized => f.write_str("networking is not initialized"),
+            Self::AlreadyRunning => f.write_str("remote console is already running"),
+            Self::Net(e) => write!(f, "remote console listen failed: {e}"),
+            Self::Thread(e) => write!(f, "remote console thread failed: {e}"),
+        }
+    }
+}
+
+type Handler = dyn Fn(&[&str], &mut String) + Send + Sync;
+
+static COMMANDS: SpinMutex<Vec<(&'static str, Arc<Handler>)>> = SpinMutex::new(Vec::new());
+
+static RUNNING: AtomicBool = AtomicBool::new(false);
+/// Set by [`stop()`] for the console thread to notice between polls.
+static STOP: AtomicBool = AtomicBool::new(false);
+static CONSOLE: SpinMutex<Option<JoinHandle>> = SpinMutex::new(None);
+
+/// Listen result reported by the console thread: a `NetError` code,
+/// `LISTENING`, or `PENDING` until the thread gets that far.
+static STATUS: AtomicI32 = AtomicI32::new(PENDING);
+const PENDING: i32 = i32::MIN;
+const LISTENING: i32 = i32::MAX;
+
+/// Numbers the default screenshot paths.
+static SCREENSHOTS: AtomicU32 = AtomicU32::new(0);
+
+const THREAD_NAME: &[u8] = b"remote_console\0";
+const STACK_SIZE: i32 = 16 * 1024;
+
+/// How long the console thread blocks on a socket before checking
+/// [`STOP`], which bounds how long [`stop()`] waits.
+const POLL_MS: u32 = 250;
+
+/// Longest accepted command line; longer lines are discarded.
+const MAX_LINE: usize = 512;
+
+const BUILTINS: [&str; 5] = ["help", "mem", "threads", "screenshot", "quit"];
+
+/// Add a command, replacing any registered command of the same name.
+///
+/// `handler` receives the arguments after the command name and appends
+/// its reply to the output string. It runs on the console thread; see the
+/// [module documentation](self) for passing requests to the game.
+/// Registered commands can't shadow the built-ins.
+pub fn register<F>(name: &'static str, handler: F)
+where
+    F: Fn(&[&str], &mut String) + Send + Sync + 'static,
+{
+    let handler: Arc<Handler> = Arc::new(handler);
+    let mut commands = COMMANDS.lock();
+    match commands.iter_mut().find(|(n, _)| *n == name) {
+        Some(entry) => entry.1 = handler,
+        None => commands.push((name, handler)),
+    }
+}
+
+/// Start the console, listening on `port` on all interfaces.
+///
+/// Returns once the listener is up. Fails with
+/// [`RemoteError::NetNotInitialized`] rather than touching the network
+/// stack when [`net::init()`] hasn't been called, so games can call this
+/// unconditionally in debug builds.
+pub fn start(port: u16) -> Result<(), RemoteError> {
+    if !net::is_initialized() {
+        return Err(RemoteError::NetNotInitialized);
+    }
+    if RUNNING.swap(true, Ordering::AcqRel) {
+        return Err(RemoteError::AlreadyRunning);
+    }
+
+    STATUS.store(PENDING, Ordering::Release);
+    STOP.store(false, Ordering::Release);
+    let handle = ThreadBuilder::new(THREAD_NAME)
+        .stack_size(STACK_SIZE)
+        .spawn(move || console_thread(port))
+        .map_err(|e| {
+            RUNNING.store(false, Ordering::Release);
+            RemoteError::Thread(e)
+        })?;
+
+    // Sockets can't leave the thread that created them, so the listener
+    // is bound on the console thread; wait for it to report.
+    loop {
+        match STATUS.load(Ordering::Acquire) {
+            PENDING => unsafe {
+                sys::sceKernelDelayThread(1000);
+            },
+            LISTENING => {
+                *CONSOLE.lock() = Some(handle);
+                return Ok(());
+            },
+            code => {
+                let _ = handle.join();
+                RUNNING.store(false, Ordering::Release);
+                return Err(RemoteError::Net(NetError(code)));
+            },
+        }
+    }
+}
+
+/// Stop the console, closing the listener and any connected client.
+///
+/// Returns once the console thread has exited, which takes up to a
+/// quarter of a second plus the rest of any command it is running.
+/// Does nothing if the console isn't running.
+pub fn stop() {
+    let Some(handle) = CONSOLE.lock().take() else {
+        return;
+    };
+    STOP.store(true, Ordering::Release);
+    let _ = handle.join();
+}
+
+/// Whether the console is running.
+pub fn is_running() -> bool {
+    RUNNING.load(Ordering::Acquire)
+}
+
+fn stopping() -> bool {
+    STOP.load(Ordering::Acquire)
+}
+
+fn console_thread(port: u16) -> i32 {
+    let listener = match TcpListener::bind(port) {
+        Ok(listener) => listener,
+        Err(e) => {
+            STATUS.store(e.0, Ordering::Release);
+            return 0;
+        },
+    };
+    let status = match listener.set_accept_timeout(POLL_MS) {
+        Ok(()) => LISTENING,
+        Err(e) => e.0,
+    };
+    STATUS.store(status, Ordering::Release);
+    if status != LISTENING {
+        return 0;
+    }
+
+    while !stopping() {
+        match listener.accept() {
+            Ok((stream, _, _)) => {
+                // A dropped connection only ends that session.
+                let _ = serve(&stream);
+            },
+            Err(e) if e.is_timeout() => {},
+            Err(_) => break,
+        }
+    }
+    RUNNING.store(false, Ordering::Release);
+    0
+}
+
+fn serve(stream: &TcpStream) -> Result<(), NetError> {
+    stream.set_read_timeout(POLL_MS)?;
+    write_all(stream, b"PSP remote console, type `help` for commands\n> ")?;
+
+    let mut line = Vec::new();
+    let mut overflow = false;
+    let mut buf = [0u8; 256];
+    loop {
+        if stopping() {
+            return write_all(stream, b"\nremote console stopped\n");
+        }
+        let n = match stream.read(&mut buf) {
+            Err(e) if e.is_timeout() => continue,
+            result => result?,
+        };
+        if n == 0 {
+            return Ok(());
+        }
+        for &byte in &buf[..n] {
+            if byte != b'\n' {
+                if line.len() < MAX_LINE {
+                    line.push(byte);
+                } else {
+                    overflow = true;
+                }
+                continue;
+            }
+
+            let mut out = String::new();
+            let quit = if overflow {
+                out.push_str("line too long\n");
+                false
+            } else {
+                let text = String::from_utf8_lossy(&line);
+                let words: Vec<&str> = text.split_whitespace().collect();
+                match words.split_first() {
+                    Some((name, args)) => execute(name, args, &mut out),
+                    None => false,
+                }
+            };
+            line.clear();
+            overflow = false;
+
+            if quit {
+                return write_all(stream, out.as_bytes());
+            }
+            out.push_str("> ");
+            write_all(stream, out.as_bytes())?;
+        }
+    }
+}
+
+/// Run one command, returning whether the client asked to disconnect.
+fn execute(name: &str, args: &[&str], out: &mut String) -> bool {
+    match name {
+        "help" => {
+            out.push_str("commands:");
+            for builtin in BUILTINS {
+                out.push(' ');
+                out.push_str(builtin);
+            }
+            for (name, _) in COMMANDS.lock().iter() {
+                out.push(' ');
+                out.push_str(name);
+            }
+            out.push('\n');
+        },
+        "mem" => mem(out),
+        "threads" => threads(out),
+        "screenshot" => screenshot(args.first().copied(), out),
+        "quit" => {
+            out.push_str("bye\n");
+            return true;
+        },
+        _ => {
+            // Clone the handler out so it runs without holding the lock,
+            // letting it register commands itself.
+            let handler = COMMANDS
+                .lock()
+                .iter()
+                .find(|(n, _)| *n == name)
+                .map(|(_, handler)| handler.clone());
+            match handler {
+                Some(handler) => handler(args, out),
+                None => {
+                    let _ = writeln!(out, "unknown command: {name}");
+                },
+            }
+        },
+    }
+    false
+}
+
+fn mem(out: &mut String) {
+    let heap = crate::alloc_stats();
+    let (total_free, max_free) = unsafe {
+        (
+            sys::sceKernelTotalFreeMemSize(),
+            sys::sceKernelMaxFreeMemSize(),
+        )
+    };
+    let _ = writeln!(
+        out,
+        "heap: {} used, {} free, {} peak, {} allocations",
+        heap.used_bytes, heap.free_bytes, heap.peak_bytes, heap.allocation_count
+    );
+    let _ = writeln!(
+        out,
+        "partition: {total_free} free, {max_free} largest block"
+    );
+    let _ = writeln!(
+        out,
+        "vram: {} used of {}",
+        crate::vram_alloc::vram_used(),
+        crate::vram_alloc::vram_total()
+    );
+}
+
+fn threads(out: &mut String) {
+    let mut ids = [sys::SceUid(0); 64];
+    let mut count = 0;
+    let ret = unsafe {
+        sys::sceKernelGetThreadmanIdList(
+            sys::SceKernelIdListType::Thread,
+            ids.as_mut_ptr(),
+            ids.len() as i32,
+            &mut count,
+        )
+    };
+    if ret < 0 {
+        let _ = writeln!(out, "thread list failed: {:#010x}", ret as u32);
+        return;
+    }
+
+    for &id in &ids[..(count as usize).min(ids.len())] {
+        let mut info = core::mem::MaybeUninit::<sys::SceKernelThreadInfo>::uninit();
+        let ret = unsafe {
+            (&raw mut (*info.as_mut_ptr()).size)
+                .write(core::mem::size_of::<sys::SceKernelThreadInfo>());
+            sys::sceKernelReferThreadStatus(id, info.as_mut_ptr())
+        };
+        if ret < 0 {
+            continue;
+        }
+        // SAFETY: the kernel filled in the structure.
+        let info = unsafe { info.assume_init() };
+        let len = info.name.iter().position(|&b| b == 0).unwrap_or(32);
+        let _ = writeln!(
+            out,
+            "{:#010x} {:<32} prio {:3} {}",
+            id.0,
+            String::from_utf8_lossy(&info.name[..len]),
+            info.current_priority,
+            status_name(info.status)
+        );
+    }
+}
+
+fn status_name(status: i32) -> &'static str {
+    match status {
+        s if s & 0x01 != 0 => "running",
+        s if s & 0x02 != 0 => "ready",
+        s if s & 0x04 != 0 => "waiting",
+        s if s & 0x08 != 0 => "suspended",
+        s if s & 0x10 != 0 => "dormant",
+        s if s & 0x20 != 0 => "dead",
+        _ => "unknown",
+    }
+}
+
+fn screenshot(path: Option<&str>, out: &mut String) {
+    let path = match path {
+        Some(path) => String::from(path),
+        None => format!(
+            "ms0:/remote_{:03}.bmp",
+            SCREENSHOTS.fetch_add(1, Ordering::Relaxed)
+        ),
+    };
+    match crate::io::write_bytes(&path, &crate::screenshot_bmp()) {
+        Ok(()) => {
+            let _ = writeln!(out, "saved {path}");
+        },
+        Err(e) => {
+            let _ = writeln!(out, "screenshot failed: {e}");
+        },
+    }
+}
+
+fn write_all(stream: &TcpStream, mut data: &[u8]) -> Result<(), NetError> {
+    while !data.is_empty() {
+        let n = stream.write(data)?;
+        if n == 0 {
+            return Err(NetError(-1));
+        }
+        data = &data[n..];
+    }
+    Ok(())
+}
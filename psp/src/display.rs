@@ -3,12 +3,19 @@
 //! Wraps the common `sceDisplay*` syscalls into ergonomic functions.
 //! Every graphics application needs vblank sync — this module removes
 //! the need to call raw syscalls directly.
+//!
+//! [`on_vblank()`] runs a function once per vertical blank, for animation
+//! or overlays that update outside the main loop. [`set_brightness()`]
+//! changes the backlight level (kernel mode).
 
 use core::ffi::c_void;
+#[cfg(not(feature = "stub-only"))]
+use core::sync::atomic::{AtomicBool, Ordering};
 
 use crate::sys::{DisplayPixelFormat, DisplaySetBufSync};
 
 /// Information about the current framebuffer configuration.
+#[derive(Debug, Clone, Copy)]
 pub struct FrameBufInfo {
     /// Pointer to the top-left pixel of the framebuffer.
     pub top_addr: *mut u8,
@@ -77,3 +84,307 @@ pub fn get_framebuf(sync: DisplaySetBufSync) -> FrameBufInfo {
         pixel_format,
     }
 }
+
+/// The framebuffer the display is showing right now, or `None` if no
+/// framebuffer is set (e.g. the display is off).
+///
+/// Overlay plugins can draw into the returned buffer to composite over a
+/// running game, typically from a `psp::hook::SyscallHook` (kernel feature)
+/// on `sceDisplaySetFrameBuf` or once per vblank. The buffer belongs to
+/// the game; it may switch to another buffer at the next flip.
+pub fn current_framebuffer() -> Option<FrameBufInfo> {
+    let mut top_addr: *mut c_void = core::ptr::null_mut();
+    let mut buf_width: usize = 0;
+    let mut pixel_format = DisplayPixelFormat::Psm8888;
+    let ret = unsafe {
+        crate::sys::sceDisplayGetFrameBuf(
+            &mut top_addr,
+            &mut buf_width,
+            &mut pixel_format,
+            DisplaySetBufSync::Immediate,
+        )
+    };
+    if ret < 0 || top_addr.is_null() || buf_width == 0 {
+        return None;
+    }
+    Some(FrameBufInfo {
+        top_addr: top_addr as *mut u8,
+        buf_width,
+        pixel_format,
+    })
+}
+
+// ── Brightness ──────────────────────────────────────────────────────
+
+/// Error from a brightness or vblank handler operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisplayError {
+    /// A `sceDisplay*` or interrupt manager call failed with this SCE
+    /// error code.
+    Kernel(i32),
+    /// Brightness control needs `feature = "kernel"`.
+    KernelRequired,
+    /// [`Brightness::Max`] is only available on AC power.
+    AcPowerRequired,
+    /// All [`MAX_VBLANK_HANDLERS`] handlers are registered.
+    NoFreeSlot,
+}
+
+impl core::fmt::Display for DisplayError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Kernel(code) => write!(f, "display error {:#010x}", *code as u32),
+            Self::KernelRequired => f.write_str("brightness control requires kernel mode"),
+            Self::AcPowerRequired => f.write_str("maximum brightness requires AC power"),
+            Self::NoFreeSlot => f.write_str("too many vblank handlers"),
+        }
+    }
+}
+
+/// Backlight brightness levels, as offered by the system menu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Brightness {
+    Level1,
+    Level2,
+    Level3,
+    Level4,
+    /// The extra level available while on AC power.
+    Max,
+}
+
+impl Brightness {
+    /// Every level, dimmest first.
+    pub const ALL: [Brightness; 5] = [
+        Self::Level1,
+        Self::Level2,
+        Self::Level3,
+        Self::Level4,
+        Self::Max,
+    ];
+
+    /// The backlight value (0-100) the display driver uses for this level.
+    pub const fn percent(self) -> i32 {
+        match self {
+            Self::Level1 => 44,
+            Self::Level2 => 60,
+            Self::Level3 => 72,
+            Self::Level4 => 84,
+            Self::Max => 100,
+        }
+    }
+
+    /// The level closest to a backlight value.
+    pub fn from_percent(percent: i32) -> Self {
+        Self::ALL
+            .into_iter()
+            .min_by_key(|level| (level.percent() - percent).abs())
+            .unwrap_or(Self::Level1)
+    }
+}
+
+/// Set the backlight brightness.
+///
+/// [`Brightness::Max`] fails with [`DisplayError::AcPowerRequired`] on
+/// battery. Without `feature = "kernel"` there is no way to change the
+/// brightness, and this returns [`DisplayError::KernelRequired`].
+pub fn set_brightness(level: Brightness) -> Result<(), DisplayError> {
+    if level == Brightness::Max && !crate::power::is_ac_power() {
+        return Err(DisplayError::AcPowerRequired);
+    }
+    #[cfg(feature = "kernel")]
+    {
+        let ret = unsafe { crate::sys::sceDisplaySetBrightness(level.percent(), 0) };
+        if ret < 0 {
+            return Err(DisplayError::Kernel(ret));
+        }
+        Ok(())
+    }
+    #[cfg(not(feature = "kernel"))]
+    {
+        Err(DisplayError::KernelRequired)
+    }
+}
+
+/// The current backlight brightness, rounded to the nearest level.
+///
+/// Returns [`DisplayError::KernelRequired`] without `feature = "kernel"`.
+pub fn brightness() -> Result<Brightness, DisplayError> {
+    #[cfg(feature = "kernel")]
+    {
+        let (mut level, mut unk) = (0, 0);
+        let ret = unsafe { crate::sys::sceDisplayGetBrightness(&mut level, &mut unk) };
+        if ret < 0 {
+            return Err(DisplayError::Kernel(ret));
+        }
+        Ok(Brightness::from_percent(level))
+    }
+    #[cfg(not(feature = "kernel"))]
+    {
+        Err(DisplayError::KernelRequired)
+    }
+}
+
+// ── Vblank handlers ─────────────────────────────────────────────────
+
+/// Maximum number of [`on_vblank()`] handlers registered at once.
+pub const MAX_VBLANK_HANDLERS: usize = 4;
+
+#[cfg(not(feature = "stub-only"))]
+static VBLANK_SLOTS: [AtomicBool; MAX_VBLANK_HANDLERS] =
+    [const { AtomicBool::new(false) }; MAX_VBLANK_HANDLERS];
+
+/// First sub-interrupt number used for the vblank interrupt; slot `n`
+/// registers as `VBLANK_SUB_BASE + n`.
+#[cfg(feature = "kernel")]
+const VBLANK_SUB_BASE: i32 = 16;
+
+/// Set by a [`VblankHandler`]'s drop to end its thread.
+#[cfg(all(not(feature = "kernel"), not(feature = "stub-only")))]
+static VBLANK_STOP: [AtomicBool; MAX_VBLANK_HANDLERS] =
+    [const { AtomicBool::new(false) }; MAX_VBLANK_HANDLERS];
+
+/// Call `handler` once at the start of every vertical blank, until the
+/// returned [`VblankHandler`] is dropped.
+///
+/// There are two backends with the same API:
+///
+/// - With `feature = "kernel"`, `handler` is a sub-interrupt handler on
+///   the vblank interrupt and runs in interrupt context.
+/// - Otherwise a dedicated high-priority thread loops on
+///   `sceDisplayWaitVblankStart` and calls `handler`. A handler that
+///   takes longer than a frame (about 16.7 ms) misses vblanks, and it
+///   runs concurrently with the rest of the program.
+///
+/// At most [`MAX_VBLANK_HANDLERS`] handlers can be registered at once.
+///
+/// # Safety
+///
+/// With `feature = "kernel"`, `handler` must be safe to run in interrupt
+/// context: it must not block, sleep, allocate, take locks the
+/// interrupted code might hold, or call anything but interrupt-safe
+/// syscalls, and it must not use the FPU or VFPU, whose registers aren't
+/// saved. Keep it to a few atomic stores (for example, bumping a counter
+/// a thread waits on). Handlers like that suit both backends; the thread
+/// backend alone runs any handler soundly.
+///
+/// # Example
+///
+/// ```ignore
+/// use core::sync::atomic::{AtomicU32, Ordering};
+///
+/// static FRAMES: AtomicU32 = AtomicU32::new(0);
+///
+/// fn tick() {
+///     FRAMES.fetch_add(1, Ordering::Relaxed);
+/// }
+///
+/// // SAFETY: `tick` only does an atomic add.
+/// let _vblank = unsafe { psp::display::on_vblank(tick)? };
+/// ```
+#[cfg(not(feature = "stub-only"))]
+pub unsafe fn on_vblank(handler: fn()) -> Result<VblankHandler, DisplayError> {
+    let slot = VBLANK_SLOTS
+        .iter()
+        .position(|used| {
+            used.compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+                .is_ok()
+        })
+        .ok_or(DisplayError::NoFreeSlot)?;
+    match start_vblank(slot, handler) {
+        Ok(handler) => Ok(handler),
+        Err(e) => {
+            VBLANK_SLOTS[slot].store(false, Ordering::Release);
+            Err(e)
+        },
+    }
+}
+
+/// A registered [`on_vblank()`] handler. Dropping it unregisters the
+/// handler; once the drop returns, it won't be called again.
+#[cfg(not(feature = "stub-only"))]
+#[must_use = "the vblank handler is unregistered when this is dropped"]
+pub struct VblankHandler {
+    slot: usize,
+    /// The thread calling the handler, until the drop joins it.
+    #[cfg(not(feature = "kernel"))]
+    thread: Option<crate::thread::JoinHandle>,
+}
+
+#[cfg(all(feature = "kernel", not(feature = "stub-only")))]
+fn start_vblank(slot: usize, handler: fn()) -> Result<VblankHandler, DisplayError> {
+    use crate::sys::{SubInterrupt, sceKernelEnableSubIntr, sceKernelRegisterSubIntrHandler};
+
+    unsafe extern "C" fn vblank_interrupt(_sub_intr: i32, arg: *mut c_void) -> i32 {
+        // SAFETY: `arg` is the `fn()` passed to `on_vblank`.
+        let handler: fn() = unsafe { core::mem::transmute(arg) };
+        handler();
+        -1
+    }
+
+    let sub = VBLANK_SUB_BASE + slot as i32;
+    let ret = unsafe {
+        sceKernelRegisterSubIntrHandler(
+            SubInterrupt::Display as i32,
+            sub,
+            vblank_interrupt as *mut c_void,
+            handler as *mut c_void,
+        )
+    };
+    if ret < 0 {
+        return Err(DisplayError::Kernel(ret));
+    }
+    let ret = unsafe { sceKernelEnableSubIntr(SubInterrupt::Display as i32, sub) };
+    if ret < 0 {
+        unsafe { crate::sys::sceKernelReleaseSubIntrHandler(SubInterrupt::Display as i32, sub) };
+        return Err(DisplayError::Kernel(ret));
+    }
+    Ok(VblankHandler { slot })
+}
+
+#[cfg(all(not(feature = "kernel"), not(feature = "stub-only")))]
+fn start_vblank(slot: usize, handler: fn()) -> Result<VblankHandler, DisplayError> {
+    VBLANK_STOP[slot].store(false, Ordering::Relaxed);
+    let thread = crate::thread::ThreadBuilder::new(b"vblank_handler\0")
+        // Above the usual 32 so the handler runs as the vblank starts.
+        .priority(16)
+        .stack_size(0x4000)
+        .spawn(move || {
+            let stop = &VBLANK_STOP[slot];
+            loop {
+                unsafe { crate::sys::sceDisplayWaitVblankStart() };
+                if stop.load(Ordering::Acquire) {
+                    return 0;
+                }
+                handler();
+            }
+        })
+        .map_err(|e| DisplayError::Kernel(e.0))?;
+    Ok(VblankHandler {
+        slot,
+        thread: Some(thread),
+    })
+}
+
+#[cfg(not(feature = "stub-only"))]
+impl Drop for VblankHandler {
+    fn drop(&mut self) {
+        #[cfg(feature = "kernel")]
+        unsafe {
+            let sub = VBLANK_SUB_BASE + self.slot as i32;
+            crate::sys::sceKernelDisableSubIntr(crate::sys::SubInterrupt::Display as i32, sub);
+            crate::sys::sceKernelReleaseSubIntrHandler(
+                crate::sys::SubInterrupt::Display as i32,
+                sub,
+            );
+        }
+        #[cfg(not(feature = "kernel"))]
+        {
+            // The thread sees the flag after at most one more vblank.
+            VBLANK_STOP[self.slot].store(true, Ordering::Release);
+            if let Some(thread) = self.thread.take() {
+                let _ = thread.join();
+            }
+        }
+        VBLANK_SLOTS[self.slot].store(false, Ordering::Release);
+    }
+}
@@ -5,6 +5,10 @@
 //! - [`Font`]: Open PGF font handle. RAII.
 //! - [`FontRenderer`]: High-level text renderer with glyph atlas caching
 //!   and sprite-batched drawing via [`crate::gu_ext::SpriteBatch`].
+//!
+//! Rendering a glyph the first time it is drawn is slow enough that a
+//! dialog full of new characters can drop a frame; use
+//! [`FontRenderer::prewarm()`] and friends to render them ahead of time.
 
 use alloc::vec::Vec;
 use core::alloc::Layout;
@@ -295,6 +299,7 @@ struct AtlasRow {
     lru_stamp: u32,
 }
 
+#[derive(Clone, Copy)]
 struct CachedGlyph {
     char_code: u32,
     atlas_x: u32,
@@ -445,6 +450,16 @@ impl GlyphAtlas {
 
 // ── FontRenderer ─────────────────────────────────────────────────────
 
+/// Outcome of looking a character up in the atlas.
+enum PreparedGlyph {
+    /// The glyph is in the atlas.
+    Cached(CachedGlyph),
+    /// Nothing to draw, but the cursor still advances.
+    Advance(f32),
+    /// The font has no such character.
+    Missing,
+}
+
 /// High-level text renderer with VRAM glyph atlas and sprite batching.
 ///
 /// Renders glyphs to a PsmT8 atlas in VRAM on cache miss, then draws
@@ -459,11 +474,7 @@ pub struct FontRenderer<'a> {
 }
 
 /// CLUT for PsmT8: maps index i to RGBA(0xFF, 0xFF, 0xFF, i).
-/// 256 entries × 4 bytes = 1024 bytes. Must be 16-byte aligned.
-#[repr(align(16))]
-struct ClutTable([u32; 256]);
-
-static ALPHA_CLUT: ClutTable = {
+static ALPHA_CLUT: crate::gu_ext::Clut = {
     let mut table = [0u32; 256];
     let mut i = 0u32;
     while i < 256 {
@@ -471,7 +482,7 @@ static ALPHA_CLUT: ClutTable = {
         table[i as usize] = (i << 24) | 0x00FFFFFF;
         i += 1;
     }
-    ClutTable(table)
+    crate::gu_ext::Clut::new_8bit(&table)
 };
 
 const ATLAS_WIDTH: u32 = 512;
@@ -523,100 +534,154 @@ impl<'a> FontRenderer<'a> {
                 continue;
             }
 
-            let char_code = c as u32;
-
-            // Check cache first.
-            if let Some(cached) = self.atlas.find_cached(char_code) {
-                let gx = cursor_x + cached.metrics.bearing_x;
-                let gy = baseline - cached.metrics.bearing_y;
-                let u0 = cached.atlas_x as f32;
-                let v0 = cached.atlas_y as f32;
-                let u1 = (cached.atlas_x + cached.atlas_w) as f32;
-                let v1 = (cached.atlas_y + cached.atlas_h) as f32;
-                self.batch.draw_rect(
-                    gx,
-                    gy,
-                    cached.atlas_w as f32,
-                    cached.atlas_h as f32,
-                    u0,
-                    v0,
-                    u1,
-                    v1,
-                    color,
-                );
-                cursor_x += cached.metrics.advance_x;
-                continue;
+            match self.prepare_glyph(c) {
+                PreparedGlyph::Cached(cached) => {
+                    let gx = cursor_x + cached.metrics.bearing_x;
+                    let gy = baseline - cached.metrics.bearing_y;
+                    let u0 = cached.atlas_x as f32;
+                    let v0 = cached.atlas_y as f32;
+                    let u1 = (cached.atlas_x + cached.atlas_w) as f32;
+                    let v1 = (cached.atlas_y + cached.atlas_h) as f32;
+                    self.batch.draw_rect(
+                        gx,
+                        gy,
+                        cached.atlas_w as f32,
+                        cached.atlas_h as f32,
+                        u0,
+                        v0,
+                        u1,
+                        v1,
+                        color,
+                    );
+                    cursor_x += cached.metrics.advance_x;
+                },
+                PreparedGlyph::Advance(advance) => cursor_x += advance,
+                PreparedGlyph::Missing => {},
             }
+        }
+    }
 
-            // Cache miss — render glyph.
-            let Ok(metrics) = self.font.char_info(c) else {
-                continue;
-            };
+    /// Render `chars` into the atlas without drawing anything, so that
+    /// a later [`draw_text()`](Self::draw_text) doesn't stall on them.
+    ///
+    /// Call this during a loading screen with the text that is about to
+    /// appear. Returns the number of characters processed, which is all
+    /// of them. Glyphs evicted from a full atlas have to be rendered
+    /// again, so don't prewarm more text than fits in the 512x512 atlas.
+    pub fn prewarm(&mut self, chars: impl Iterator<Item = char>) -> usize {
+        let mut processed = 0;
+        for c in chars {
+            self.prewarm_char(c);
+            processed += 1;
+        }
+        processed
+    }
 
-            if metrics.width == 0 || metrics.height == 0 {
-                cursor_x += metrics.advance_x;
-                continue;
+    /// Like [`prewarm()`](Self::prewarm), but stops once `max_micros`
+    /// have passed, to spread the work across idle frames.
+    ///
+    /// Returns the number of characters processed; skip that many next
+    /// frame to resume. At least one character is processed per call, so
+    /// the caller always makes progress.
+    ///
+    /// ```ignore
+    /// let mut done = 0;
+    /// while done < text.chars().count() {
+    ///     done += renderer.prewarm_budgeted(text.chars().skip(done), 2000);
+    ///     // ... draw the loading screen and wait for vblank ...
+    /// }
+    /// ```
+    pub fn prewarm_budgeted(
+        &mut self,
+        chars: impl Iterator<Item = char>,
+        max_micros: u32,
+    ) -> usize {
+        let start = unsafe { crate::sys::sceKernelGetSystemTimeWide() };
+        let mut processed = 0;
+        for c in chars {
+            if processed > 0 {
+                let elapsed = unsafe { crate::sys::sceKernelGetSystemTimeWide() } - start;
+                if elapsed >= max_micros as i64 {
+                    break;
+                }
             }
+            self.prewarm_char(c);
+            processed += 1;
+        }
+        processed
+    }
 
-            let gw = metrics.width;
-            let gh = metrics.height;
-            let staging_size = (gw * gh) as usize;
-            if staging_size > self.staging.len() {
-                self.staging.resize(staging_size, 0);
-            }
+    /// Prewarm the printable ASCII characters.
+    pub fn prewarm_ascii(&mut self) -> usize {
+        self.prewarm((0x21u8..=0x7e).map(char::from))
+    }
 
-            // Clear staging buffer.
-            for b in self.staging[..staging_size].iter_mut() {
-                *b = 0;
-            }
+    /// Prewarm the characters of `text`, e.g. the next dialog's lines.
+    pub fn prewarm_text(&mut self, text: &str) -> usize {
+        self.prewarm(text.chars())
+    }
 
-            let mut glyph_image = SceFontGlyphImage {
-                pixel_format: SceFontPixelFormatCode::Format8,
-                x_pos_64: 0,
-                y_pos_64: 0,
-                buf_width: gw as u16,
-                buf_height: gh as u16,
-                bytes_per_line: gw as u16,
-                pad: 0,
-                buffer_ptr: self.staging.as_mut_ptr() as u32,
-            };
-
-            let ret =
-                unsafe { sceFontGetCharGlyphImage(self.font.handle, char_code, &mut glyph_image) };
-            if ret < 0 {
-                cursor_x += metrics.advance_x;
-                continue;
-            }
+    fn prewarm_char(&mut self, c: char) {
+        if c != ' ' && !c.is_control() {
+            self.prepare_glyph(c);
+        }
+    }
 
-            // Insert into atlas.
-            if let Some(cached) = self.atlas.insert(
-                char_code,
-                gw,
-                gh,
-                metrics,
-                &self.staging[..staging_size],
-                gw,
-            ) {
-                let gx = cursor_x + cached.metrics.bearing_x;
-                let gy = baseline - cached.metrics.bearing_y;
-                let u0 = cached.atlas_x as f32;
-                let v0 = cached.atlas_y as f32;
-                let u1 = (cached.atlas_x + cached.atlas_w) as f32;
-                let v1 = (cached.atlas_y + cached.atlas_h) as f32;
-                self.batch.draw_rect(
-                    gx,
-                    gy,
-                    cached.atlas_w as f32,
-                    cached.atlas_h as f32,
-                    u0,
-                    v0,
-                    u1,
-                    v1,
-                    color,
-                );
-            }
+    /// Look `c` up in the atlas, rendering it on a cache miss.
+    fn prepare_glyph(&mut self, c: char) -> PreparedGlyph {
+        let char_code = c as u32;
+        if let Some(cached) = self.atlas.find_cached(char_code) {
+            return PreparedGlyph::Cached(*cached);
+        }
+
+        let Ok(metrics) = self.font.char_info(c) else {
+            return PreparedGlyph::Missing;
+        };
+
+        if metrics.width == 0 || metrics.height == 0 {
+            return PreparedGlyph::Advance(metrics.advance_x);
+        }
+
+        let gw = metrics.width;
+        let gh = metrics.height;
+        let staging_size = (gw * gh) as usize;
+        if staging_size > self.staging.len() {
+            self.staging.resize(staging_size, 0);
+        }
 
-            cursor_x += metrics.advance_x;
+        // Clear staging buffer.
+        for b in self.staging[..staging_size].iter_mut() {
+            *b = 0;
+        }
+
+        let mut glyph_image = SceFontGlyphImage {
+            pixel_format: SceFontPixelFormatCode::Format8,
+            x_pos_64: 0,
+            y_pos_64: 0,
+            buf_width: gw as u16,
+            buf_height: gh as u16,
+            bytes_per_line: gw as u16,
+            pad: 0,
+            buffer_ptr: self.staging.as_mut_ptr() as u32,
+        };
+
+        let ret =
+            unsafe { sceFontGetCharGlyphImage(self.font.handle, char_code, &mut glyph_image) };
+        if ret < 0 {
+            return PreparedGlyph::Advance(metrics.advance_x);
+        }
+
+        // Insert into atlas.
+        match self.atlas.insert(
+            char_code,
+            gw,
+            gh,
+            metrics,
+            &self.staging[..staging_size],
+            gw,
+        ) {
+            Some(cached) => PreparedGlyph::Cached(*cached),
+            None => PreparedGlyph::Advance(metrics.advance_x),
         }
     }
 
@@ -649,14 +714,67 @@ impl<'a> FontRenderer<'a> {
     ///
     /// Must be called within an active GU display list.
     pub unsafe fn flush(&mut self) {
+        // The current context is always open.
+        let _ = unsafe { self.flush_into(crate::sys::current_context()) };
+    }
+
+    /// Like [`flush()`](Self::flush), but records the texture state and
+    /// glyph sprites into `context`'s list, which may be one the current
+    /// list was started from. See
+    /// [`SpriteBatch::flush_into()`](crate::gu_ext::SpriteBatch::flush_into).
+    ///
+    /// Returns `false`, leaving the text queued, if `context` has no open
+    /// display list.
+    ///
+    /// # Safety
+    ///
+    /// Must be called while GU display lists are being recorded.
+    #[must_use]
+    pub unsafe fn flush_into(&mut self, context: crate::sys::GuContextType) -> bool {
+        if self.batch.count() == 0 {
+            return true;
+        }
+        unsafe {
+            crate::sys::with_context(context, || {
+                self.bind_atlas();
+                self.batch.flush_into(context)
+            })
+            .unwrap_or(false)
+        }
+    }
+
+    /// Like [`flush()`](Self::flush), but with the glyph vertices copied
+    /// into `storage`, so a [`CallList`](crate::gu_ext::CallList) recorded
+    /// with it can be replayed across frames. Returns `false`, leaving the
+    /// text queued, if `storage` is full.
+    ///
+    /// The atlas must keep the queued glyphs while the list may run, so
+    /// don't draw new text into this renderer, or clear its atlas, between
+    /// replays.
+    ///
+    /// # Safety
+    ///
+    /// Must be called within an active GU display list, and `storage` must
+    /// not be cleared or dropped while a list that draws from it may run.
+    pub unsafe fn flush_to_buffer(&mut self, storage: &mut crate::gu_ext::AlignedBuf) -> bool {
         if self.batch.count() == 0 {
-            return;
+            return true;
+        }
+        let bytes = self.batch.count() * 2 * core::mem::size_of::<crate::gu_ext::SpriteVertex>();
+        if bytes.next_multiple_of(16) > storage.remaining() {
+            return false;
         }
+        unsafe {
+            self.bind_atlas();
+            self.batch.flush_to_buffer(storage)
+        }
+    }
 
+    /// Set up the CLUT and texture state for the atlas.
+    unsafe fn bind_atlas(&self) {
         unsafe {
             // Set up CLUT: alpha-ramp lookup table.
-            crate::sys::sceGuClutMode(crate::sys::ClutPixelFormat::Psm8888, 0, 0xFF, 0);
-            crate::sys::sceGuClutLoad(256 / 8, ALPHA_CLUT.0.as_ptr() as *const c_void);
+            ALPHA_CLUT.upload();
 
             // Bind atlas texture as PsmT8.
             crate::sys::sceGuTexMode(crate::sys::TexturePixelFormat::PsmT8, 0, 0, 0);
@@ -673,8 +791,6 @@ impl<'a> FontRenderer<'a> {
                 crate::sys::TextureEffect::Modulate,
                 crate::sys::TextureColorComponent::Rgba,
             );
-
-            self.batch.flush();
         }
     }
 
@@ -7,6 +7,8 @@
 //! - [`DirtyRect`]: Track modified regions to minimize VRAM writes
 //! - [`LayerCompositor`]: Compose multiple layers (background, content,
 //!   overlay) with DMA-driven blits
+//! - [`PixelSurface`]: Bounds-checked pixel access, fills and blits over
+//!   a framebuffer or off-screen buffer
 //!
 //! # PSP Display Model
 //!
@@ -16,6 +18,8 @@
 //! `512 * 272 * 4 = 557,056` bytes (~544 KiB). Two framebuffers fit
 //! comfortably in VRAM with room for textures.
 
+use core::marker::PhantomData;
+
 use crate::sys::{DisplayPixelFormat, DisplaySetBufSync};
 
 /// PSP screen width in pixels.
@@ -49,16 +53,29 @@ pub const fn framebuffer_size(fmt: DisplayPixelFormat) -> u32 {
 /// pointer is updated to the newly drawn buffer (optionally synced to
 /// vsync to avoid tearing).
 ///
+/// # Suspend and resume
+///
+/// The display controller forgets its mode and framebuffer address when
+/// the PSP suspends, leaving a black or garbled screen after resume. Call
+/// [`power::watch_resume`](crate::power::watch_resume) once at startup (or
+/// [`power::notify_resumed`](crate::power::notify_resumed) from your own
+/// power callback), then check [`needs_reinit`](Self::needs_reinit) each
+/// frame and call [`reinit`](Self::reinit) when it returns `true`.
+///
 /// # Example
 ///
 /// ```ignore
 /// use psp::framebuffer::DoubleBuffer;
 /// use psp::sys::DisplayPixelFormat;
 ///
+/// let _resume = psp::power::watch_resume().unwrap();
 /// let mut db = DoubleBuffer::new(DisplayPixelFormat::Psm8888, true);
 /// db.init();
 ///
 /// loop {
+///     if db.needs_reinit() {
+///         db.reinit();
+///     }
 ///     let buf = db.draw_buffer();
 ///     // ... draw into buf ...
 ///     db.swap();
@@ -73,6 +90,8 @@ pub struct DoubleBuffer {
     format: DisplayPixelFormat,
     /// Whether to sync swaps to vsync.
     vsync: bool,
+    /// [`crate::power::resume_count`] when the display was last set up.
+    resume_seen: u32,
 }
 
 impl DoubleBuffer {
@@ -93,6 +112,7 @@ impl DoubleBuffer {
             display_buf: 0,
             format,
             vsync,
+            resume_seen: crate::power::resume_count(),
         }
     }
 
@@ -108,6 +128,26 @@ impl DoubleBuffer {
         }
     }
 
+    /// Whether the system resumed from suspend since this buffer was
+    /// created or last reinitialized, so the display must be set up again.
+    ///
+    /// Only reports resumes recorded by
+    /// [`power::notify_resumed`](crate::power::notify_resumed).
+    pub fn needs_reinit(&self) -> bool {
+        crate::power::resume_count() != self.resume_seen
+    }
+
+    /// Restore the display mode and the currently displayed framebuffer
+    /// after a resume, and clear [`needs_reinit`](Self::needs_reinit).
+    ///
+    /// Only display controller state is restored. Code drawing with the
+    /// GU must also rerun its `sceGuInit` setup, since the GE loses its
+    /// state too.
+    pub fn reinit(&mut self) {
+        self.resume_seen = crate::power::resume_count();
+        self.init();
+    }
+
     /// Get a mutable pointer to the draw buffer (the one NOT being displayed).
     ///
     /// Returns a pointer to uncached VRAM suitable for direct pixel writes.
@@ -121,6 +161,25 @@ impl DoubleBuffer {
         self.vram_ptr(self.display_buf) as *const u8
     }
 
+    /// A [`PixelSurface`] over the draw buffer, for software rendering.
+    ///
+    /// It borrows the double buffer so it can't outlive the next
+    /// [`swap()`](Self::swap).
+    pub fn draw_surface(&mut self) -> PixelSurface<'_> {
+        // SAFETY: the draw buffer is a full framebuffer of `self.format`
+        // in VRAM, which is always mapped, and nothing else in `self`
+        // writes to it while the surface borrows `self`.
+        unsafe {
+            PixelSurface::from_raw(
+                self.draw_buffer(),
+                SCREEN_WIDTH,
+                SCREEN_HEIGHT,
+                BUF_WIDTH,
+                self.format,
+            )
+        }
+    }
+
     /// Get the VRAM offset of the draw buffer.
     pub fn draw_buffer_offset(&self) -> u32 {
         let draw_idx = 1 - self.display_buf;
@@ -417,3 +476,341 @@ impl LayerCompositor {
         self.format
     }
 }
+
+// ── PixelSurface ────────────────────────────────────────────────────
+
+/// A bounds-checked view of a framebuffer, or any buffer laid out like
+/// one, for software rendering.
+///
+/// Colors are `0xAABBGGRR`, the same as the GU uses, whatever the surface
+/// format: writes to 16-bit surfaces drop the low bits of each channel,
+/// and reads expand them back, with alpha `0xFF` for
+/// [`Psm5650`](DisplayPixelFormat::Psm5650). Anything outside the surface
+/// is clipped away, so sprites can be drawn partly off screen.
+///
+/// # Example
+///
+/// ```ignore
+/// use psp::framebuffer::{DoubleBuffer, PixelSurface};
+/// use psp::sys::DisplayPixelFormat;
+///
+/// let mut db = DoubleBuffer::new(DisplayPixelFormat::Psm5650, true);
+/// db.init();
+/// let mut sprite_pixels = [0u32; 16 * 16];
+/// let sprite = PixelSurface::from_slice(&mut sprite_pixels, 16, 16, 16, DisplayPixelFormat::Psm8888);
+///
+/// loop {
+///     let mut screen = db.draw_surface();
+///     screen.clear(0xFF00_0000);
+///     screen.fill_rect(10, 10, 100, 20, 0xFF00_00FF);
+///     screen.blit(&sprite, 0, 0, x, y, 16, 16);
+///     db.swap();
+/// }
+/// ```
+pub struct PixelSurface<'a> {
+    ptr: *mut u8,
+    width: u32,
+    height: u32,
+    /// Row length in pixels.
+    stride: u32,
+    format: DisplayPixelFormat,
+    _buf: PhantomData<&'a mut [u8]>,
+}
+
+impl<'a> PixelSurface<'a> {
+    /// A surface over `buf`, which holds `height` rows of `stride` pixels
+    /// in `format`. 16-bit formats pack two pixels into each `u32`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` exceeds `stride` or `buf` is too small; see
+    /// [`try_from_slice`](Self::try_from_slice) for a checked version.
+    pub fn from_slice(
+        buf: &'a mut [u32],
+        width: u32,
+        height: u32,
+        stride: u32,
+        format: DisplayPixelFormat,
+    ) -> Self {
+        Self::try_from_slice(buf, width, height, stride, format)
+            .expect("surface dimensions don't fit the buffer")
+    }
+
+    /// [`from_slice`](Self::from_slice), returning `None` instead of
+    /// panicking if `width` exceeds `stride` or `buf` is smaller than
+    /// `stride * height` pixels (including when that size overflows).
+    pub fn try_from_slice(
+        buf: &'a mut [u32],
+        width: u32,
+        height: u32,
+        stride: u32,
+        format: DisplayPixelFormat,
+    ) -> Option<Self> {
+        let needed = (stride as usize)
+            .checked_mul(height as usize)?
+            .checked_mul(bytes_per_pixel(format) as usize)?;
+        if width > stride || needed > core::mem::size_of_val(buf) {
+            return None;
+        }
+        // SAFETY: `buf` is large enough, aligned for any pixel format, and
+        // borrowed mutably for `'a`.
+        Some(unsafe { Self::from_raw(buf.as_mut_ptr() as *mut u8, width, height, stride, format) })
+    }
+
+    /// A surface over raw memory, such as a VRAM framebuffer.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be aligned to the pixel size and valid for reads and
+    /// writes of `stride * height` pixels of `format` for `'a`, with no
+    /// other access to that memory meanwhile except through the GE.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` exceeds `stride`.
+    pub unsafe fn from_raw(
+        ptr: *mut u8,
+        width: u32,
+        height: u32,
+        stride: u32,
+        format: DisplayPixelFormat,
+    ) -> Self {
+        assert!(width <= stride, "surface width exceeds its stride");
+        Self {
+            ptr,
+            width,
+            height,
+            stride,
+            format,
+            _buf: PhantomData,
+        }
+    }
+
+    /// Width in pixels.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Height in pixels.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Row length in pixels.
+    pub fn stride(&self) -> u32 {
+        self.stride
+    }
+
+    /// Get the pixel format.
+    pub fn format(&self) -> DisplayPixelFormat {
+        self.format
+    }
+
+    /// Set the pixel at `(x, y)`. Does nothing outside the surface.
+    pub fn set_pixel(&mut self, x: i32, y: i32, color: u32) {
+        if self.contains(x, y) {
+            let raw = encode(self.format, color);
+            // SAFETY: `(x, y)` is within the surface.
+            unsafe { self.write(x as u32, y as u32, raw) };
+        }
+    }
+
+    /// The pixel at `(x, y)`, or `None` outside the surface.
+    pub fn get_pixel(&self, x: i32, y: i32) -> Option<u32> {
+        // SAFETY: `(x, y)` is within the surface.
+        self.contains(x, y)
+            .then(|| decode(self.format, unsafe { self.read(x as u32, y as u32) }))
+    }
+
+    /// Fill a `w` x `h` rectangle with its top-left corner at `(x, y)`,
+    /// clipped to the surface.
+    pub fn fill_rect(&mut self, x: i32, y: i32, w: u32, h: u32, color: u32) {
+        let Some((_, x, w)) = clip(0, x, w, u32::MAX, self.width) else {
+            return;
+        };
+        let Some((_, y, h)) = clip(0, y, h, u32::MAX, self.height) else {
+            return;
+        };
+        let raw = encode(self.format, color);
+        for row in y..y + h {
+            for col in x..x + w {
+                // SAFETY: clipped to the surface.
+                unsafe { self.write(col, row, raw) };
+            }
+        }
+    }
+
+    /// Fill the whole surface with `color`.
+    pub fn clear(&mut self, color: u32) {
+        self.fill_rect(0, 0, self.width, self.height, color);
+    }
+
+    /// Copy the `w` x `h` rectangle at `(sx, sy)` in `src` to `(dx, dy)`
+    /// in this surface, clipped to both. Pixels are converted if the
+    /// formats differ.
+    #[allow(clippy::too_many_arguments)]
+    pub fn blit(
+        &mut self,
+        src: &PixelSurface<'_>,
+        sx: i32,
+        sy: i32,
+        dx: i32,
+        dy: i32,
+        w: u32,
+        h: u32,
+    ) {
+        let Some((sx, dx, w)) = clip(sx, dx, w, src.width, self.width) else {
+            return;
+        };
+        let Some((sy, dy, h)) = clip(sy, dy, h, src.height, self.height) else {
+            return;
+        };
+
+        if src.format == self.format {
+            let bpp = bytes_per_pixel(self.format) as usize;
+            let row_bytes = w as usize * bpp;
+            let copy_row = |row: u32| {
+                // SAFETY: both rows are clipped to their surfaces.
+                // `ptr::copy` allows the two surfaces to share memory.
+                unsafe {
+                    core::ptr::copy(
+                        src.ptr.add(src.offset(sx, sy + row)),
+                        self.ptr.add(self.offset(dx, dy + row)),
+                        row_bytes,
+                    );
+                }
+            };
+            // Copy bottom-up when moving down within shared memory, so
+            // rows aren't overwritten before they're read.
+            if dy > sy {
+                (0..h).rev().for_each(copy_row);
+            } else {
+                (0..h).for_each(copy_row);
+            }
+        } else {
+            for row in 0..h {
+                for col in 0..w {
+                    // SAFETY: clipped to both surfaces.
+                    unsafe {
+                        let color = decode(src.format, src.read(sx + col, sy + row));
+                        self.write(dx + col, dy + row, encode(self.format, color));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Replace every pixel with `f` of its raw value, in `format`'s
+    /// layout. 16-bit pixels are passed and returned in the low half.
+    pub(crate) fn map_raw(&mut self, mut f: impl FnMut(u32) -> u32) {
+        let width = self.width as usize;
+        for row in 0..self.height {
+            // SAFETY: each row holds `width` pixels within the surface.
+            unsafe {
+                let ptr = self.ptr.add(self.offset(0, row));
+                match self.format {
+                    DisplayPixelFormat::Psm8888 => {
+                        for pixel in core::slice::from_raw_parts_mut(ptr as *mut u32, width) {
+                            *pixel = f(*pixel);
+                        }
+                    },
+                    _ => {
+                        for pixel in core::slice::from_raw_parts_mut(ptr as *mut u16, width) {
+                            *pixel = f(*pixel as u32) as u16;
+                        }
+                    },
+                }
+            }
+        }
+    }
+
+    fn contains(&self, x: i32, y: i32) -> bool {
+        x >= 0 && y >= 0 && (x as u32) < self.width && (y as u32) < self.height
+    }
+
+    /// Byte offset of pixel `(x, y)`.
+    fn offset(&self, x: u32, y: u32) -> usize {
+        (y as usize * self.stride as usize + x as usize) * bytes_per_pixel(self.format) as usize
+    }
+
+    /// Read the raw pixel at `(x, y)`, which must be within the surface.
+    unsafe fn read(&self, x: u32, y: u32) -> u32 {
+        unsafe {
+            let ptr = self.ptr.add(self.offset(x, y));
+            match self.format {
+                DisplayPixelFormat::Psm8888 => *(ptr as *const u32),
+                _ => *(ptr as *const u16) as u32,
+            }
+        }
+    }
+
+    /// Write the raw pixel at `(x, y)`, which must be within the surface.
+    unsafe fn write(&mut self, x: u32, y: u32, raw: u32) {
+        unsafe {
+            let ptr = self.ptr.add(self.offset(x, y));
+            match self.format {
+                DisplayPixelFormat::Psm8888 => *(ptr as *mut u32) = raw,
+                _ => *(ptr as *mut u16) = raw as u16,
+            }
+        }
+    }
+}
+
+/// Clip a span of `len` pixels starting at `src` in a source `src_len`
+/// long and at `dst` in a destination `dst_len` long, returning the
+/// visible part as `(src, dst, len)`.
+fn clip(src: i32, dst: i32, len: u32, src_len: u32, dst_len: u32) -> Option<(u32, u32, u32)> {
+    let (mut src, mut dst, mut len) = (src as i64, dst as i64, len as i64);
+    let skip = (-src).max(-dst).max(0);
+    src += skip;
+    dst += skip;
+    len -= skip;
+    len = len.min(src_len as i64 - src).min(dst_len as i64 - dst);
+    (len > 0).then_some((src as u32, dst as u32, len as u32))
+}
+
+/// Convert a `0xAABBGGRR` color to `format`'s pixel layout.
+pub(crate) fn encode(format: DisplayPixelFormat, color: u32) -> u32 {
+    let [r, g, b, a] = color.to_le_bytes().map(u32::from);
+    match format {
+        DisplayPixelFormat::Psm8888 => color,
+        DisplayPixelFormat::Psm5650 => (r >> 3) | ((g >> 2) << 5) | ((b >> 3) << 11),
+        DisplayPixelFormat::Psm5551 => {
+            (r >> 3) | ((g >> 3) << 5) | ((b >> 3) << 10) | ((a >> 7) << 15)
+        },
+        DisplayPixelFormat::Psm4444 => {
+            (r >> 4) | ((g >> 4) << 4) | ((b >> 4) << 8) | ((a >> 4) << 12)
+        },
+    }
+}
+
+/// Convert a pixel in `format`'s layout to `0xAABBGGRR`, replicating the
+/// high bits of each channel into the low ones.
+pub(crate) fn decode(format: DisplayPixelFormat, raw: u32) -> u32 {
+    let expand = |value: u32, bits: u32| {
+        let value = value & ((1 << bits) - 1);
+        (value << (8 - bits)) | (value >> (2 * bits - 8))
+    };
+    let (r, g, b, a) = match format {
+        DisplayPixelFormat::Psm8888 => return raw,
+        DisplayPixelFormat::Psm5650 => (
+            expand(raw, 5),
+            expand(raw >> 5, 6),
+            expand(raw >> 11, 5),
+            0xFF,
+        ),
+        DisplayPixelFormat::Psm5551 => (
+            expand(raw, 5),
+            expand(raw >> 5, 5),
+            expand(raw >> 10, 5),
+            if raw & 0x8000 != 0 { 0xFF } else { 0 },
+        ),
+        DisplayPixelFormat::Psm4444 => (
+            expand(raw, 4),
+            expand(raw >> 4, 4),
+            expand(raw >> 8, 4),
+            expand(raw >> 12, 4),
+        ),
+    };
+    r | (g << 8) | (b << 16) | (a << 24)
+}
@@ -42,6 +42,10 @@ impl core::fmt::Display for GpioError {
 pub mod pins {
     /// LCD backlight control. Toggling this pin turns off the screen.
     pub const LCD_BACKLIGHT: u32 = 3;
+    /// Memory Stick access LED.
+    pub const MS_LED: u32 = 6;
+    /// WLAN activity LED.
+    pub const WLAN_LED: u32 = 7;
     /// USB PHY transceiver. Disrupts USB communication if toggled.
     pub const USB_PHY: u32 = 19;
     /// USB VBUS MOSFET gate. Controls 5V power output on the USB port.
@@ -0,0 +1,270 @@
+//! Display lists recorded once and replayed every frame.
+//!
+//! A static UI panel costs the CPU the same to re-batch every frame as it
+//! did the first time. A [`CallList`] records it once into a `Call`-context
+//! list and replays it with `sceGuCallList`, which costs one command in the
+//! frame's list no matter how much the panel draws.
+//!
+//! Vertices the recording allocates with `sceGuGetMemory` are embedded in
+//! the call list itself, so they stay valid as long as the list does.
+//! [`SpriteBatch::flush_to_buffer()`](super::SpriteBatch::flush_to_buffer)
+//! puts them in a separate [`AlignedBuf`] instead, which keeps the list
+//! small and lets several lists share one vertex pool.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use psp::gu_ext::{AlignedBuf, CallList, SpriteBatch};
+//!
+//! let mut panel = CallList::new(4096);
+//! let mut vertices = AlignedBuf::new(16 * 1024);
+//! unsafe {
+//!     panel.begin();
+//!     for w in &widgets {
+//!         batch.draw_colored_rect(w.x, w.y, w.w, w.h, w.color);
+//!     }
+//!     batch.flush_to_buffer(&mut vertices);
+//!     panel.end();
+//! }
+//! loop {
+//!     unsafe {
+//!         sys::sceGuStart(GuContextType::Direct, list);
+//!         draw_scene();
+//!         panel.call();
+//!         sys::sceGuFinish();
+//!     }
+//! }
+//! ```
+
+use alloc::alloc::{Layout, alloc_zeroed, dealloc};
+use core::ffi::c_void;
+
+use crate::sys::{self, GuContextType};
+
+/// Display lists and GE vertex data must be 16-byte aligned.
+const ALIGN: usize = 16;
+
+/// A fixed-size, 16-byte aligned heap buffer for GE data that must outlive
+/// a single frame's display list.
+///
+/// Space is handed out by [`alloc()`](Self::alloc) from the front and only
+/// reclaimed all at once by [`clear()`](Self::clear).
+pub struct AlignedBuf {
+    ptr: *mut u8,
+    capacity: usize,
+    used: usize,
+}
+
+impl AlignedBuf {
+    /// Allocate a zeroed buffer of `bytes` bytes, rounded up to a multiple
+    /// of 16.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bytes` is zero.
+    pub fn new(bytes: usize) -> Self {
+        assert!(bytes > 0, "aligned buffer must be non-empty");
+        let capacity = bytes.next_multiple_of(ALIGN);
+        let layout = Self::layout(capacity);
+        // SAFETY: the layout has a non-zero size.
+        let ptr = unsafe { alloc_zeroed(layout) };
+        if ptr.is_null() {
+            alloc::alloc::handle_alloc_error(layout);
+        }
+        // Display lists are written through the uncached mirror. Push out
+        // any dirty lines first so a later eviction can't overwrite them.
+        unsafe {
+            sys::sceKernelDcacheWritebackInvalidateRange(ptr as *const c_void, capacity as u32);
+        }
+        Self {
+            ptr,
+            capacity,
+            used: 0,
+        }
+    }
+
+    fn layout(capacity: usize) -> Layout {
+        Layout::from_size_align(capacity, ALIGN).unwrap()
+    }
+
+    /// Claim `bytes` bytes, rounded up to a multiple of 16, returning a
+    /// 16-byte aligned pointer to them, or `None` if the buffer is full.
+    ///
+    /// Data written through the pointer must be flushed from the data
+    /// cache (`sceKernelDcacheWritebackRange`) before the GE reads it.
+    pub fn alloc(&mut self, bytes: usize) -> Option<*mut u8> {
+        let size = bytes.next_multiple_of(ALIGN);
+        if size > self.remaining() {
+            return None;
+        }
+        // SAFETY: `used + size` is within the allocation.
+        let ptr = unsafe { self.ptr.add(self.used) };
+        self.used += size;
+        Some(ptr)
+    }
+
+    /// Bytes claimed by [`alloc()`](Self::alloc) since the last
+    /// [`clear()`](Self::clear).
+    pub fn len(&self) -> usize {
+        self.used
+    }
+
+    /// Whether nothing has been claimed.
+    pub fn is_empty(&self) -> bool {
+        self.used == 0
+    }
+
+    /// Size of the buffer in bytes.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Bytes still free.
+    pub fn remaining(&self) -> usize {
+        self.capacity - self.used
+    }
+
+    /// Reclaim all space. Lists that still reference the old contents
+    /// will draw whatever is written over them.
+    pub fn clear(&mut self) {
+        self.used = 0;
+    }
+
+    /// Start of the buffer.
+    pub fn as_ptr(&self) -> *const u8 {
+        self.ptr
+    }
+}
+
+impl Drop for AlignedBuf {
+    fn drop(&mut self) {
+        // SAFETY: allocated in `new` with the same layout.
+        unsafe { dealloc(self.ptr, Self::layout(self.capacity)) };
+    }
+}
+
+/// A `Call`-context display list recorded once and replayed with
+/// `sceGuCallList`.
+///
+/// See the [module documentation](self).
+pub struct CallList {
+    buf: AlignedBuf,
+    /// Size of the recorded list in bytes; zero if nothing is recorded.
+    size: usize,
+    recording: bool,
+}
+
+impl CallList {
+    /// Allocate a list buffer of `list_bytes` bytes.
+    ///
+    /// The buffer must hold the whole list, including any vertices
+    /// allocated with `sceGuGetMemory` while recording; the GU doesn't
+    /// check for overflow.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `list_bytes` is less than 16.
+    pub fn new(list_bytes: usize) -> Self {
+        assert!(list_bytes >= ALIGN, "display list buffer too small");
+        Self {
+            buf: AlignedBuf::new(list_bytes),
+            size: 0,
+            recording: false,
+        }
+    }
+
+    /// Start recording, replacing the previous recording.
+    ///
+    /// # Safety
+    ///
+    /// The GU must be initialized, and no list that calls this one may be
+    /// queued or drawing. Every `sceGu*` call up to [`end()`](Self::end) is
+    /// recorded into this list.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this list is already recording.
+    pub unsafe fn begin(&mut self) {
+        assert!(!self.recording, "call list is already recording");
+        self.size = 0;
+        self.recording = true;
+        unsafe {
+            sys::sceGuStart(GuContextType::Call, self.buf.as_ptr() as *mut c_void);
+        }
+    }
+
+    /// Finish the recording started by [`begin()`](Self::begin) and return
+    /// its size in bytes. Recording resumes in the list that was active
+    /// before `begin`.
+    ///
+    /// # Safety
+    ///
+    /// Every list started since `begin` must have been finished, so the
+    /// call list is the one `sceGuFinish` closes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this list isn't recording, or if the recording overflowed
+    /// the buffer.
+    pub unsafe fn end(&mut self) -> usize {
+        assert!(self.recording, "call list is not recording");
+        self.recording = false;
+        let size = unsafe { sys::sceGuFinish() } as usize;
+        assert!(
+            size <= self.buf.capacity(),
+            "display list overflowed its {} byte buffer",
+            self.buf.capacity()
+        );
+        self.size = size;
+        size
+    }
+
+    /// Emit a call to the recorded list into the current display list.
+    /// Does nothing if nothing is recorded.
+    ///
+    /// # Safety
+    ///
+    /// Must be called within an active GU display list, and this
+    /// `CallList`, along with everything the recording references
+    /// (textures, [`AlignedBuf`] vertices), must stay valid and unchanged
+    /// until that list has been drawn.
+    pub unsafe fn call(&self) {
+        if self.size == 0 || self.recording {
+            return;
+        }
+        unsafe {
+            sys::sceGuCallList(self.buf.as_ptr() as *const c_void);
+        }
+    }
+
+    /// Size of the recorded list in bytes.
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    /// Whether nothing is recorded.
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Size of the list buffer in bytes.
+    pub fn capacity(&self) -> usize {
+        self.buf.capacity()
+    }
+
+    /// Whether the list is between [`begin()`](Self::begin) and
+    /// [`end()`](Self::end).
+    pub fn is_recording(&self) -> bool {
+        self.recording
+    }
+}
+
+impl Drop for CallList {
+    fn drop(&mut self) {
+        if self.recording {
+            unsafe {
+                sys::sceGuFinish();
+            }
+        }
+    }
+}
@@ -0,0 +1,710 @@
+//! Display list capture, disassembly and replay for debugging.
+//!
+//! [`CapturedList::capture_last()`] copies the display list most recently
+//! finished on the direct context into the heap, together with the draw
+//! buffer configuration. The capture can be saved to the memory stick as
+//! a binary dump ([`CapturedList::save()`]) or as readable text
+//! ([`CapturedList::save_text()`]), and a saved dump can be resubmitted
+//! with [`replay_list()`] for A/B testing.
+//!
+//! For a quick look at a list without capturing it, [`dump_list()`]
+//! decodes it into [`GeWord`]s and [`validate_list()`] flags common
+//! mistakes such as draws without a vertex buffer or a missing `END`.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use psp::gu_ext::capture::CapturedList;
+//!
+//! sceGuFinish();
+//! sceGuSync(GuSyncMode::Finish, GuSyncBehavior::Wait);
+//! if ctrl.is_held(CtrlButtons::LTRIGGER | CtrlButtons::RTRIGGER)
+//!     && ctrl.is_pressed(CtrlButtons::SELECT)
+//! {
+//!     if let Some(list) = unsafe { CapturedList::capture_last() } {
+//!         list.save("ms0:/frame.gels")?;
+//!         list.save_text("ms0:/frame.txt")?;
+//!     }
+//! }
+//! ```
+//!
+//! # File format
+//!
+//! All fields are little-endian `u32`s: the magic `"GELS"`, the format
+//! version, the list size in bytes, the list's original address, the draw
+//! buffer address, width and pixel format, followed by the command words.
+
+use crate::io::IoError;
+use crate::sys::GuContextType;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ffi::c_void;
+use core::fmt::Write;
+
+/// Magic bytes at the start of a dump.
+pub const MAGIC: [u8; 4] = *b"GELS";
+
+/// Current dump format version.
+pub const VERSION: u32 = 1;
+
+/// Size of the dump header in bytes.
+pub const HEADER_SIZE: usize = 28;
+
+/// Mask for the 28-bit addresses the GE works with.
+const ADDR_MASK: u32 = 0x0fff_ffff;
+
+// ── Command decoding ────────────────────────────────────────────────
+
+/// GE command ids used when decoding and relocating lists.
+const CMD_NOP: u8 = 0x00;
+const CMD_VADDR: u8 = 0x01;
+const CMD_IADDR: u8 = 0x02;
+const CMD_PRIM: u8 = 0x04;
+const CMD_JUMP: u8 = 0x08;
+const CMD_BJUMP: u8 = 0x09;
+const CMD_CALL: u8 = 0x0a;
+const CMD_RET: u8 = 0x0b;
+const CMD_END: u8 = 0x0c;
+const CMD_FINISH: u8 = 0x0f;
+const CMD_BASE: u8 = 0x10;
+
+/// Mnemonic for a GE command id, or `None` if it isn't in the table.
+pub fn command_name(cmd: u8) -> Option<&'static str> {
+    const LTE: [&str; 4] = ["LTE0", "LTE1", "LTE2", "LTE3"];
+    const TBP: [&str; 8] = [
+        "TBP0", "TBP1", "TBP2", "TBP3", "TBP4", "TBP5", "TBP6", "TBP7",
+    ];
+    const TBW: [&str; 8] = [
+        "TBW0", "TBW1", "TBW2", "TBW3", "TBW4", "TBW5", "TBW6", "TBW7",
+    ];
+    const TSIZE: [&str; 8] = [
+        "TSIZE0", "TSIZE1", "TSIZE2", "TSIZE3", "TSIZE4", "TSIZE5", "TSIZE6", "TSIZE7",
+    ];
+    const DTH: [&str; 4] = ["DTH0", "DTH1", "DTH2", "DTH3"];
+
+    let name = match cmd {
+        0x00 => "NOP",
+        0x01 => "VADDR",
+        0x02 => "IADDR",
+        0x04 => "PRIM",
+        0x05 => "BEZIER",
+        0x06 => "SPLINE",
+        0x07 => "BBOX",
+        0x08 => "JUMP",
+        0x09 => "BJUMP",
+        0x0a => "CALL",
+        0x0b => "RET",
+        0x0c => "END",
+        0x0e => "SIGNAL",
+        0x0f => "FINISH",
+        0x10 => "BASE",
+        0x12 => "VTYPE",
+        0x13 => "OFFSETADDR",
+        0x14 => "ORIGIN",
+        0x15 => "REGION1",
+        0x16 => "REGION2",
+        0x17 => "LTE",
+        0x18..=0x1b => LTE[(cmd - 0x18) as usize],
+        0x1c => "CLE",
+        0x1d => "BCE",
+        0x1e => "TME",
+        0x1f => "FGE",
+        0x20 => "DTE",
+        0x21 => "ABE",
+        0x22 => "ATE",
+        0x23 => "ZTE",
+        0x24 => "STE",
+        0x25 => "AAE",
+        0x26 => "PCE",
+        0x27 => "CTE",
+        0x28 => "LOE",
+        0x2a => "BOFS",
+        0x2b => "BONE",
+        0x3a => "WMS",
+        0x3b => "WORLD",
+        0x3c => "VMS",
+        0x3d => "VIEW",
+        0x3e => "PMS",
+        0x3f => "PROJ",
+        0x40 => "TMS",
+        0x41 => "TMATRIX",
+        0x42 => "XSCALE",
+        0x43 => "YSCALE",
+        0x44 => "ZSCALE",
+        0x45 => "XPOS",
+        0x46 => "YPOS",
+        0x47 => "ZPOS",
+        0x48 => "USCALE",
+        0x49 => "VSCALE",
+        0x4a => "UOFFSET",
+        0x4b => "VOFFSET",
+        0x4c => "OFFSETX",
+        0x4d => "OFFSETY",
+        0x50 => "SHADE",
+        0x51 => "RNORM",
+        0x53 => "CMAT",
+        0x54 => "EMC",
+        0x55 => "AMC",
+        0x56 => "DMC",
+        0x57 => "SMC",
+        0x58 => "AMA",
+        0x5b => "SPOW",
+        0x5c => "ALC",
+        0x5d => "ALA",
+        0x5e => "LMODE",
+        0x9b => "CULL",
+        0x9c => "FBP",
+        0x9d => "FBW",
+        0x9e => "ZBP",
+        0x9f => "ZBW",
+        0xa0..=0xa7 => TBP[(cmd - 0xa0) as usize],
+        0xa8..=0xaf => TBW[(cmd - 0xa8) as usize],
+        0xb0 => "CBP",
+        0xb1 => "CBW",
+        0xb2 => "TRXSBP",
+        0xb3 => "TRXSBW",
+        0xb4 => "TRXDBP",
+        0xb5 => "TRXDBW",
+        0xb8..=0xbf => TSIZE[(cmd - 0xb8) as usize],
+        0xc0 => "TMAP",
+        0xc1 => "TEXSHADE",
+        0xc2 => "TMODE",
+        0xc3 => "TPSM",
+        0xc4 => "CLOAD",
+        0xc5 => "CMODE",
+        0xc6 => "TFLT",
+        0xc7 => "TWRAP",
+        0xc8 => "TBIAS",
+        0xc9 => "TFUNC",
+        0xca => "TEC",
+        0xcb => "TFLUSH",
+        0xcc => "TSYNC",
+        0xcd => "FOG1",
+        0xce => "FOG2",
+        0xcf => "FC",
+        0xd0 => "TSLOPE",
+        0xd2 => "FPF",
+        0xd3 => "CLEAR",
+        0xd4 => "SCISSOR1",
+        0xd5 => "SCISSOR2",
+        0xd6 => "MINZ",
+        0xd7 => "MAXZ",
+        0xd8 => "CTST",
+        0xd9 => "CREF",
+        0xda => "CMSK",
+        0xdb => "ATST",
+        0xdc => "STST",
+        0xdd => "SOP",
+        0xde => "ZTST",
+        0xdf => "ALPHA",
+        0xe0 => "SFIX",
+        0xe1 => "DFIX",
+        0xe2..=0xe5 => DTH[(cmd - 0xe2) as usize],
+        0xe6 => "LOP",
+        0xe7 => "ZMSK",
+        0xe8 => "PMSKC",
+        0xe9 => "PMSKA",
+        0xea => "TRXKICK",
+        0xeb => "TRXSPOS",
+        0xec => "TRXDPOS",
+        0xee => "TRXSIZE",
+        _ => return None,
+    };
+    Some(name)
+}
+
+/// A decoded GE command word: an 8-bit command id and 24-bit argument.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GeWord {
+    pub cmd: u8,
+    pub arg: u32,
+}
+
+impl GeWord {
+    /// Split a raw command word.
+    pub const fn decode(word: u32) -> Self {
+        Self {
+            cmd: (word >> 24) as u8,
+            arg: word & 0x00ff_ffff,
+        }
+    }
+
+    /// Reassemble the raw command word.
+    pub const fn encode(self) -> u32 {
+        ((self.cmd as u32) << 24) | (self.arg & 0x00ff_ffff)
+    }
+
+    /// Mnemonic for the command, if known.
+    pub fn name(self) -> Option<&'static str> {
+        command_name(self.cmd)
+    }
+}
+
+impl core::fmt::Display for GeWord {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.name() {
+            Some(name) => write!(f, "{:<10} {:#08x}", name, self.arg)?,
+            None => write!(f, "CMD_{:02X}     {:#08x}", self.cmd, self.arg)?,
+        }
+        if self.cmd == CMD_PRIM {
+            write!(
+                f,
+                "  ; type={} count={}",
+                (self.arg >> 16) & 0x7,
+                self.arg & 0xffff
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Disassemble command words into one line per word.
+///
+/// `list_addr` is the list's original address; inline data that
+/// `sceGuGetMemory` placed in the list (skipped by a `JUMP`) is summarized
+/// rather than decoded. Pass 0 if unknown.
+pub fn disassemble(words: &[u32], list_addr: u32) -> String {
+    let mut out = String::new();
+    let mut next = 0;
+    for (i, word) in Commands::new(words, list_addr) {
+        if i > next {
+            let _ = writeln!(out, "        ... {} words of inline data", i - next);
+        }
+        let _ = writeln!(out, "{:06x}: {:08x}  {}", i * 4, words[i], word);
+        next = i + 1;
+    }
+    out
+}
+
+/// Iterator over the commands of a list with their word indices, skipping
+/// the inline data `sceGuGetMemory` jumps over.
+struct Commands<'a> {
+    words: &'a [u32],
+    list_addr: u32,
+    base: u32,
+    i: usize,
+}
+
+impl<'a> Commands<'a> {
+    fn new(words: &'a [u32], list_addr: u32) -> Self {
+        Self {
+            words,
+            list_addr,
+            base: 0,
+            i: 0,
+        }
+    }
+}
+
+impl Iterator for Commands<'_> {
+    type Item = (usize, GeWord);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let i = self.i;
+        let word = GeWord::decode(*self.words.get(i)?);
+        if word.cmd == CMD_BASE {
+            self.base = (word.arg & 0x000f_0000) << 8;
+        }
+        self.i = match word.cmd {
+            CMD_JUMP => list_index(self.base | word.arg, self.list_addr, self.words.len())
+                .filter(|&t| t > i + 1)
+                .unwrap_or(i + 1),
+            _ => i + 1,
+        };
+        Some((i, word))
+    }
+}
+
+/// Word index of `addr` within a list of `len` words at `list_addr`.
+fn list_index(addr: u32, list_addr: u32, len: usize) -> Option<usize> {
+    if list_addr == 0 {
+        return None;
+    }
+    let offset = (addr & ADDR_MASK).checked_sub(list_addr & ADDR_MASK)?;
+    let index = (offset / 4) as usize;
+    (offset % 4 == 0 && index < len).then_some(index)
+}
+
+// ── Inspection ──────────────────────────────────────────────────────
+
+const CMD_VTYPE: u8 = 0x12;
+const CMD_TME: u8 = 0x1e;
+const CMD_TBP0: u8 = 0xa0;
+
+/// `VTYPE` bits selecting an index format.
+const VTYPE_INDEX_MASK: u32 = 0x1800;
+
+/// Decode the display list `list` into commands, e.g. to `dprintln!` them
+/// (each [`GeWord`] displays as mnemonic and argument).
+///
+/// `list` is expected to be the list in place, such as the buffer passed
+/// to `sceGuStart`: its address is used to recognize and skip the inline
+/// data `sceGuGetMemory` embeds. For a copy of a list, use
+/// [`disassemble()`] with the original address instead.
+pub fn dump_list(list: &[u32]) -> Vec<GeWord> {
+    Commands::new(list, list.as_ptr() as u32 & ADDR_MASK)
+        .map(|(_, word)| word)
+        .collect()
+}
+
+/// A likely mistake in a display list, found by [`validate_list()`].
+///
+/// `index` is the word index of the offending command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListIssue {
+    /// The list has no `END`, so the GE would run past its end.
+    Unterminated,
+    /// `END` without a preceding `FINISH`, so `sceGuSync` never sees the
+    /// list complete.
+    MissingFinish { index: usize },
+    /// A draw before any `VTYPE` describes the vertex layout.
+    DrawWithoutVertexType { index: usize },
+    /// A draw before any `VADDR` binds a vertex buffer.
+    DrawWithoutVertices { index: usize },
+    /// An indexed draw before any `IADDR` binds an index buffer.
+    DrawWithoutIndices { index: usize },
+    /// A draw of zero vertices.
+    EmptyDraw { index: usize },
+    /// A draw with texturing enabled before any `TBP0` sets the texture.
+    TextureWithoutImage { index: usize },
+    /// A command id the GE doesn't define.
+    UnknownCommand { index: usize, cmd: u8 },
+}
+
+impl core::fmt::Display for ListIssue {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Unterminated => f.write_str("list has no END command"),
+            Self::MissingFinish { index } => write!(f, "{:06x}: END without FINISH", index * 4),
+            Self::DrawWithoutVertexType { index } => {
+                write!(f, "{:06x}: draw without VTYPE", index * 4)
+            },
+            Self::DrawWithoutVertices { index } => {
+                write!(f, "{:06x}: draw without a vertex buffer", index * 4)
+            },
+            Self::DrawWithoutIndices { index } => {
+                write!(f, "{:06x}: indexed draw without an index buffer", index * 4)
+            },
+            Self::EmptyDraw { index } => write!(f, "{:06x}: draw of zero vertices", index * 4),
+            Self::TextureWithoutImage { index } => {
+                write!(f, "{:06x}: textured draw without TBP0", index * 4)
+            },
+            Self::UnknownCommand { index, cmd } => {
+                write!(f, "{:06x}: unknown command {:#04x}", index * 4, cmd)
+            },
+        }
+    }
+}
+
+/// Check the display list `list` for common mistakes.
+///
+/// GE state persists between lists, so a list that relies on vertex
+/// type, buffers or textures set by an earlier list will be flagged even
+/// though it draws correctly; treat the result as warnings. Like
+/// [`dump_list()`], expects the list in place. Checking stops at the
+/// first `END`.
+pub fn validate_list(list: &[u32]) -> Vec<ListIssue> {
+    let mut issues = Vec::new();
+    let mut vtype = None;
+    let mut has_vertices = false;
+    let mut has_indices = false;
+    let mut has_texture = false;
+    let mut texturing = false;
+    let mut prev_cmd = None;
+
+    for (index, word) in Commands::new(list, list.as_ptr() as u32 & ADDR_MASK) {
+        match word.cmd {
+            CMD_VTYPE => vtype = Some(word.arg),
+            CMD_VADDR => has_vertices = true,
+            CMD_IADDR => has_indices = true,
+            CMD_TBP0 => has_texture = true,
+            CMD_TME => texturing = word.arg & 1 != 0,
+            CMD_PRIM => {
+                match vtype {
+                    None => issues.push(ListIssue::DrawWithoutVertexType { index }),
+                    Some(v) if v & VTYPE_INDEX_MASK != 0 && !has_indices => {
+                        issues.push(ListIssue::DrawWithoutIndices { index })
+                    },
+                    Some(_) => {},
+                }
+                if !has_vertices {
+                    issues.push(ListIssue::DrawWithoutVertices { index });
+                }
+                if word.arg & 0xffff == 0 {
+                    issues.push(ListIssue::EmptyDraw { index });
+                }
+                if texturing && !has_texture {
+                    issues.push(ListIssue::TextureWithoutImage { index });
+                }
+            },
+            CMD_END => {
+                if prev_cmd != Some(CMD_FINISH) {
+                    issues.push(ListIssue::MissingFinish { index });
+                }
+                return issues;
+            },
+            cmd if command_name(cmd).is_none() => {
+                issues.push(ListIssue::UnknownCommand { index, cmd })
+            },
+            _ => {},
+        }
+        prev_cmd = Some(word.cmd);
+    }
+    issues.push(ListIssue::Unterminated);
+    issues
+}
+
+// ── Capture ─────────────────────────────────────────────────────────
+
+/// Error from parsing or relocating a dump.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptureError {
+    /// The data is shorter than its header says.
+    Truncated,
+    /// The data doesn't start with [`MAGIC`].
+    BadMagic,
+    /// The dump was written by an unknown format version.
+    UnsupportedVersion(u32),
+    /// The address command at this word index points into the list but
+    /// could not be rewritten for replay.
+    Relocation(usize),
+}
+
+impl core::fmt::Display for CaptureError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Truncated => f.write_str("display list dump is truncated"),
+            Self::BadMagic => f.write_str("not a display list dump"),
+            Self::UnsupportedVersion(v) => write!(f, "unsupported dump version {}", v),
+            Self::Relocation(i) => write!(f, "cannot relocate address command at word {}", i),
+        }
+    }
+}
+
+/// Draw state recorded alongside a captured list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CaptureHeader {
+    /// Size of the list in bytes.
+    pub list_size: u32,
+    /// Address the list was built at.
+    pub list_addr: u32,
+    /// Draw buffer address (VRAM-relative).
+    pub fbp: u32,
+    /// Draw buffer width in pixels.
+    pub fbw: u32,
+    /// Draw buffer pixel format (`DisplayPixelFormat` as `u32`).
+    pub psm: u32,
+}
+
+/// A copy of a display list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapturedList {
+    pub header: CaptureHeader,
+    pub words: Vec<u32>,
+}
+
+impl CapturedList {
+    /// Copy the display list most recently finished on the direct context.
+    ///
+    /// Returns `None` if no list has been built yet.
+    ///
+    /// # Safety
+    ///
+    /// Must be called after `sceGuFinish` and before the next `sceGuStart`,
+    /// with no other thread building display lists.
+    pub unsafe fn capture_last() -> Option<Self> {
+        let (start, len) = unsafe { crate::sys::last_list(GuContextType::Direct) };
+        if start.is_null() || len == 0 {
+            return None;
+        }
+        // SAFETY: the caller guarantees the list is finished and untouched;
+        // `start` is the uncached alias the GU wrote through.
+        let words = unsafe { core::slice::from_raw_parts(start, len) }.to_vec();
+        let (fbp, fbw, psm) = unsafe { crate::sys::draw_buffer_config() };
+        Some(Self {
+            header: CaptureHeader {
+                list_size: (len * 4) as u32,
+                list_addr: start as u32 & ADDR_MASK,
+                fbp,
+                fbw: fbw as u32,
+                psm: psm as u32,
+            },
+            words,
+        })
+    }
+
+    /// Serialize to the dump format.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let h = &self.header;
+        let mut out = Vec::with_capacity(HEADER_SIZE + self.words.len() * 4);
+        out.extend_from_slice(&MAGIC);
+        for field in [VERSION, h.list_size, h.list_addr, h.fbp, h.fbw, h.psm] {
+            out.extend_from_slice(&field.to_le_bytes());
+        }
+        for word in &self.words {
+            out.extend_from_slice(&word.to_le_bytes());
+        }
+        out
+    }
+
+    /// Parse a dump written by [`to_bytes()`](Self::to_bytes).
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CaptureError> {
+        if bytes.len() < HEADER_SIZE {
+            return Err(CaptureError::Truncated);
+        }
+        if bytes[..4] != MAGIC {
+            return Err(CaptureError::BadMagic);
+        }
+        let field = |i: usize| {
+            let at = 4 + i * 4;
+            u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]])
+        };
+        let version = field(0);
+        if version != VERSION {
+            return Err(CaptureError::UnsupportedVersion(version));
+        }
+        let header = CaptureHeader {
+            list_size: field(1),
+            list_addr: field(2),
+            fbp: field(3),
+            fbw: field(4),
+            psm: field(5),
+        };
+        let body = &bytes[HEADER_SIZE..];
+        if body.len() < header.list_size as usize {
+            return Err(CaptureError::Truncated);
+        }
+        let words = body[..header.list_size as usize & !3]
+            .chunks_exact(4)
+            .map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+            .collect();
+        Ok(Self { header, words })
+    }
+
+    /// Disassemble the list as text, one command per line.
+    pub fn disassemble(&self) -> String {
+        let h = &self.header;
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "; {} bytes at {:#010x}, fbp={:#x} fbw={} psm={}",
+            h.list_size, h.list_addr, h.fbp, h.fbw, h.psm
+        );
+        out.push_str(&disassemble(&self.words, h.list_addr));
+        out
+    }
+
+    /// Write the binary dump to `path`.
+    pub fn save(&self, path: &str) -> Result<(), IoError> {
+        crate::io::write_bytes(path, &self.to_bytes())
+    }
+
+    /// Write the disassembly to `path`.
+    pub fn save_text(&self, path: &str) -> Result<(), IoError> {
+        crate::io::write_bytes(path, self.disassemble().as_bytes())
+    }
+}
+
+// ── Replay ──────────────────────────────────────────────────────────
+
+/// A captured list prepared to be called as a sub-list.
+///
+/// Pointers into the list's own inline data are relocated to the copy,
+/// and the trailing `FINISH`/`END` become `RET`. Pointers to other memory
+/// (textures, vertex buffers outside the list) are left as captured, so
+/// replay is only meaningful while those buffers still hold the same data.
+pub struct ReplayList {
+    words: Vec<u32>,
+}
+
+impl ReplayList {
+    /// Parse a dump and prepare it for replay.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CaptureError> {
+        Self::new(&CapturedList::from_bytes(bytes)?)
+    }
+
+    /// Prepare a captured list for replay.
+    pub fn new(list: &CapturedList) -> Result<Self, CaptureError> {
+        let mut words = list.words.clone();
+        if let Some(finish) = words
+            .iter()
+            .rposition(|&w| matches!(GeWord::decode(w).cmd, CMD_FINISH | CMD_END))
+        {
+            let first = words[..finish]
+                .iter()
+                .rposition(|&w| !matches!(GeWord::decode(w).cmd, CMD_FINISH | CMD_END))
+                .map_or(0, |i| i + 1);
+            words[first] = GeWord {
+                cmd: CMD_RET,
+                arg: 0,
+            }
+            .encode();
+            for word in &mut words[first + 1..=finish] {
+                *word = GeWord {
+                    cmd: CMD_NOP,
+                    arg: 0,
+                }
+                .encode();
+            }
+        }
+        let new_addr = words.as_ptr() as u32 & ADDR_MASK;
+        relocate(&mut words, list.header.list_addr, new_addr)?;
+        Ok(Self { words })
+    }
+
+    /// The prepared command words.
+    pub fn words(&self) -> &[u32] {
+        &self.words
+    }
+}
+
+/// Rewrite address commands that point into the list at `old_addr` so they
+/// point into the same list at `new_addr`.
+fn relocate(words: &mut [u32], old_addr: u32, new_addr: u32) -> Result<(), CaptureError> {
+    let mut base = 0u32;
+    for i in 0..words.len() {
+        let word = GeWord::decode(words[i]);
+        match word.cmd {
+            CMD_BASE => base = (word.arg & 0x000f_0000) << 8,
+            CMD_VADDR | CMD_IADDR | CMD_JUMP | CMD_BJUMP | CMD_CALL => {
+                let Some(index) = list_index(base | word.arg, old_addr, words.len()) else {
+                    continue;
+                };
+                let target = new_addr + (index as u32) * 4;
+                let new_base = (target >> 8) & 0x000f_0000;
+                let prev_is_base = i > 0 && GeWord::decode(words[i - 1]).cmd == CMD_BASE;
+                if prev_is_base {
+                    words[i - 1] = GeWord {
+                        cmd: CMD_BASE,
+                        arg: new_base,
+                    }
+                    .encode();
+                    base = new_base << 8;
+                } else if new_base << 8 != base {
+                    return Err(CaptureError::Relocation(i));
+                }
+                words[i] = GeWord {
+                    cmd: word.cmd,
+                    arg: target & 0x00ff_ffff,
+                }
+                .encode();
+            },
+            _ => {},
+        }
+    }
+    Ok(())
+}
+
+/// Call a prepared list from the display list being built.
+///
+/// # Safety
+///
+/// Must be called between `sceGuStart` and `sceGuFinish`, and `list` must
+/// stay alive until the GE has finished executing it (e.g. `sceGuSync`).
+pub unsafe fn replay_list(list: &ReplayList) {
+    let ptr = list.words.as_ptr() as *const c_void;
+    unsafe {
+        crate::sys::sceKernelDcacheWritebackRange(ptr, (list.words.len() * 4) as u32);
+        crate::sys::sceGuCallList(ptr);
+    }
+}
@@ -0,0 +1,358 @@
+//! Full-screen color grading.
+//!
+//! A [`ColorGrade`] collects brightness, contrast, saturation, tint and
+//! gamma adjustments and compiles them into three 256-entry tables, one
+//! per channel. Whatever order the builder methods are called in, a
+//! color goes through the adjustments in a fixed order:
+//!
+//! 1. Channel mixing: [`saturation`](ColorGrade::saturation) and the
+//!    [`sepia`](ColorGrade::sepia) toning blend the red, green and blue
+//!    inputs into each output.
+//! 2. Scale and offset: [`brightness`](ColorGrade::brightness),
+//!    [`contrast`](ColorGrade::contrast) and [`tint`](ColorGrade::tint),
+//!    composed in the order they were called.
+//! 3. [`gamma`](ColorGrade::gamma).
+//!
+//! There are two ways to grade a finished frame:
+//!
+//! - [`ColorGrade::apply_cpu`] rewrites a [`PixelSurface`] through the
+//!   tables. It supports every adjustment and pixel format, and reads
+//!   `Psm8888` pixels a word at a time, but touches every pixel on the
+//!   CPU; at 480x272 that's a large share of a 60 FPS frame.
+//! - [`ColorGrade::apply_ge`] draws full-screen rectangles blended with
+//!   the framebuffer, which costs the GE almost nothing. Blending can only
+//!   scale each channel by up to 2x and add or subtract a constant, so it
+//!   covers brightness, contrast and tint only; saturation, sepia and
+//!   gamma need the CPU path ([`ColorGrade::ge_supported`] tells which).
+//!
+//! # Example
+//!
+//! ```ignore
+//! use psp::gu_ext::ColorGrade;
+//!
+//! let dusk = ColorGrade::new().tint(1.0, 0.85, 0.7).contrast(1.1);
+//! // ... draw the frame ...
+//! unsafe { dusk.apply_ge() };
+//! sceGuFinish();
+//!
+//! // Or, for any grade, on the finished frame in memory:
+//! ColorGrade::sepia().apply_cpu(&mut db.draw_surface());
+//! ```
+
+use super::{BlendMode, GuStateSnapshot, draw_rect_filled, set_blend_mode};
+use crate::framebuffer::{PixelSurface, decode, encode};
+use crate::sys::{
+    BlendFactor, BlendOp, DisplayPixelFormat, GuState, sceGuBlendFunc, sceGuDisable, sceGuEnable,
+};
+
+const WIDTH: f32 = crate::SCREEN_WIDTH as f32;
+const HEIGHT: f32 = crate::SCREEN_HEIGHT as f32;
+
+const IDENTITY: [[f32; 3]; 3] = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+
+/// Rec. 601 luma weights of red, green and blue.
+const LUMA: [f32; 3] = [0.299, 0.587, 0.114];
+
+/// The classic sepia toning matrix.
+const SEPIA: [[f32; 3]; 3] = [
+    [0.393, 0.769, 0.189],
+    [0.349, 0.686, 0.168],
+    [0.272, 0.534, 0.131],
+];
+
+/// A set of color adjustments compiled into per-channel lookup tables.
+///
+/// Channel values below are fractions of full intensity, so 1.0 is 255.
+/// Results are clamped to 0.0-1.0 after the scale and offset step.
+#[derive(Clone)]
+pub struct ColorGrade {
+    /// Row `i` weights the input red, green and blue into output `i`.
+    mix: [[f32; 3]; 3],
+    /// `mix` in 8.8 fixed point, for the CPU path.
+    mix_fixed: [[i32; 3]; 3],
+    scale: [f32; 3],
+    offset: [f32; 3],
+    gamma: f32,
+    tables: [[u8; 256]; 3],
+}
+
+impl core::fmt::Debug for ColorGrade {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ColorGrade")
+            .field("mix", &self.mix)
+            .field("scale", &self.scale)
+            .field("offset", &self.offset)
+            .field("gamma", &self.gamma)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for ColorGrade {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ColorGrade {
+    /// A grade that leaves every color unchanged.
+    pub fn new() -> Self {
+        let mut grade = Self {
+            mix: IDENTITY,
+            mix_fixed: [[0; 3]; 3],
+            scale: [1.0; 3],
+            offset: [0.0; 3],
+            gamma: 1.0,
+            tables: [[0; 256]; 3],
+        };
+        grade.compile();
+        grade
+    }
+
+    /// Old-photo brown toning.
+    pub fn sepia() -> Self {
+        Self::new().then_mix(&SEPIA)
+    }
+
+    /// Day-for-night: dark, desaturated and blue.
+    pub fn night() -> Self {
+        Self::new()
+            .saturation(0.35)
+            .tint(0.55, 0.65, 1.0)
+            .contrast(1.15)
+            .brightness(-0.05)
+    }
+
+    /// Murky blue-green with softened contrast.
+    pub fn underwater() -> Self {
+        Self::new()
+            .saturation(0.7)
+            .tint(0.5, 0.85, 1.0)
+            .contrast(0.85)
+            .brightness(0.03)
+    }
+
+    /// Add `amount` (-1.0 to 1.0) to every channel.
+    pub fn brightness(mut self, amount: f32) -> Self {
+        self.offset = self.offset.map(|o| o + amount);
+        self.compile();
+        self
+    }
+
+    /// Stretch (`factor` above 1.0) or flatten (below 1.0) the channels
+    /// around mid-gray.
+    pub fn contrast(mut self, factor: f32) -> Self {
+        for c in 0..3 {
+            self.scale[c] *= factor;
+            self.offset[c] = (self.offset[c] - 0.5) * factor + 0.5;
+        }
+        self.compile();
+        self
+    }
+
+    /// Scale each channel separately, e.g. `tint(1.0, 0.9, 0.8)` for a
+    /// warm look.
+    pub fn tint(mut self, r: f32, g: f32, b: f32) -> Self {
+        for (c, factor) in [r, g, b].into_iter().enumerate() {
+            self.scale[c] *= factor;
+            self.offset[c] *= factor;
+        }
+        self.compile();
+        self
+    }
+
+    /// Blend each color towards its gray (`amount` 0.0) or away from it
+    /// (above 1.0).
+    pub fn saturation(self, amount: f32) -> Self {
+        let mut matrix = [[0.0; 3]; 3];
+        for (i, row) in matrix.iter_mut().enumerate() {
+            for (j, weight) in row.iter_mut().enumerate() {
+                *weight = (1.0 - amount) * LUMA[j] + amount * IDENTITY[i][j];
+            }
+        }
+        self.then_mix(&matrix)
+    }
+
+    /// Raise every channel to the power `1 / gamma`, so values above 1.0
+    /// brighten the midtones and values below darken them.
+    pub fn gamma(mut self, gamma: f32) -> Self {
+        self.gamma *= gamma;
+        self.compile();
+        self
+    }
+
+    /// Apply `matrix` after the current channel mixing.
+    fn then_mix(mut self, matrix: &[[f32; 3]; 3]) -> Self {
+        let mut mix = [[0.0; 3]; 3];
+        for (i, row) in mix.iter_mut().enumerate() {
+            for (j, weight) in row.iter_mut().enumerate() {
+                *weight = (0..3).map(|k| matrix[i][k] * self.mix[k][j]).sum();
+            }
+        }
+        self.mix = mix;
+        self.compile();
+        self
+    }
+
+    /// Rebuild the tables and fixed-point matrix from the settings.
+    fn compile(&mut self) {
+        for (fixed, row) in self.mix_fixed.iter_mut().zip(&self.mix) {
+            *fixed = row.map(|weight| (weight * 256.0 + 0.5 * weight.signum()) as i32);
+        }
+        for (c, table) in self.tables.iter_mut().enumerate() {
+            for (value, entry) in table.iter_mut().enumerate() {
+                let x = value as f32 / 255.0;
+                let mut y = (x * self.scale[c] + self.offset[c]).clamp(0.0, 1.0);
+                if self.gamma != 1.0 {
+                    y = libm::powf(y, 1.0 / self.gamma);
+                }
+                *entry = (y * 255.0 + 0.5) as u8;
+            }
+        }
+    }
+
+    /// The compiled red, green and blue tables, applied after channel
+    /// mixing.
+    pub fn tables(&self) -> &[[u8; 256]; 3] {
+        &self.tables
+    }
+
+    /// Whether the grade mixes channels, which the tables don't cover.
+    pub fn mixes_channels(&self) -> bool {
+        self.mix != IDENTITY
+    }
+
+    /// Whether [`apply_ge`](Self::apply_ge) can draw this grade, matching
+    /// [`apply_cpu`](Self::apply_cpu) to within 8-bit rounding of the
+    /// blend constants: no channel mixing, no gamma, and channel scales of
+    /// at most 2.0.
+    pub fn ge_supported(&self) -> bool {
+        !self.mixes_channels() && self.gamma == 1.0 && self.scale.iter().all(|&s| s <= 2.0)
+    }
+
+    /// Grade one `0xAABBGGRR` color, keeping its alpha.
+    pub fn map_color(&self, color: u32) -> u32 {
+        let [r, g, b, a] = color.to_le_bytes();
+        let [r, g, b] = self.map_rgb([r, g, b]);
+        u32::from_le_bytes([r, g, b, a])
+    }
+
+    fn map_rgb(&self, rgb: [u8; 3]) -> [u8; 3] {
+        let mixed = if self.mixes_channels() {
+            self.mix_fixed.map(|row| {
+                let sum: i32 = row.iter().zip(rgb).map(|(&w, v)| w * v as i32).sum();
+                ((sum + 128) >> 8).clamp(0, 255) as u8
+            })
+        } else {
+            rgb
+        };
+        [0, 1, 2].map(|c| self.tables[c][mixed[c] as usize])
+    }
+
+    /// Grade every pixel of `surface` on the CPU.
+    ///
+    /// Alpha is kept. 16-bit pixels are expanded to 8 bits per channel,
+    /// graded and truncated again, so repeated grading of a 16-bit frame
+    /// loses precision.
+    pub fn apply_cpu(&self, surface: &mut PixelSurface<'_>) {
+        let format = surface.format();
+        if format != DisplayPixelFormat::Psm8888 {
+            surface.map_raw(|raw| encode(format, self.map_color(decode(format, raw))));
+        } else if self.mixes_channels() {
+            surface.map_raw(|pixel| self.map_color(pixel));
+        } else {
+            let [red, green, blue] = &self.tables;
+            surface.map_raw(|pixel| {
+                (pixel & 0xFF00_0000)
+                    | red[(pixel & 0xFF) as usize] as u32
+                    | (green[(pixel >> 8 & 0xFF) as usize] as u32) << 8
+                    | (blue[(pixel >> 16 & 0xFF) as usize] as u32) << 16
+            });
+        }
+    }
+
+    /// Grade the 480x272 render target with the GE by blending rectangles
+    /// over it.
+    ///
+    /// Draws up to three passes: subtractions, then a channel scale, then
+    /// additions. The GE clamps the framebuffer after every pass, so the
+    /// subtraction is divided by the scale and done first; otherwise a
+    /// channel that contrast above 1.0 pushes past white would be clamped
+    /// before its offset brings it back down.
+    ///
+    /// Channel mixing and gamma are skipped and scales are capped at 2.0;
+    /// check [`ge_supported`](Self::ge_supported) or use
+    /// [`apply_cpu`](Self::apply_cpu) for those. Restores the
+    /// enabled/disabled GU states and leaves the blend function as
+    /// [`BlendMode::AlphaBlend`] sets it.
+    ///
+    /// # Safety
+    ///
+    /// Must be called within an active GU display list, after the frame
+    /// is drawn.
+    pub unsafe fn apply_ge(&self) {
+        let scale = self.scale.map(|s| s.clamp(0.0, 2.0));
+        let offset = self.offset.map(|o| o.clamp(-1.0, 1.0));
+
+        let snapshot = GuStateSnapshot::capture();
+        unsafe {
+            sceGuDisable(GuState::DepthTest);
+            sceGuDisable(GuState::AlphaTest);
+            sceGuEnable(GuState::Blend);
+
+            // `(D - o / s) * s` clamps at 0 where `D * s - o` would.
+            let subtract = [0, 1, 2].map(|c| {
+                let o = (-offset[c]).max(0.0);
+                if scale[c] > 0.0 {
+                    o / scale[c]
+                } else {
+                    o.min(1.0)
+                }
+            });
+            if subtract != [0.0; 3] {
+                sceGuBlendFunc(
+                    BlendOp::ReverseSubtract,
+                    BlendFactor::Fix,
+                    BlendFactor::Fix,
+                    0x00FF_FFFF,
+                    0x00FF_FFFF,
+                );
+                draw_rect_filled(0.0, 0.0, WIDTH, HEIGHT, rgb(subtract) | 0xFF00_0000);
+            }
+
+            // `D * k + D * f` with `k + f` = scale. As a source factor
+            // `Color` is the destination color.
+            if scale != [1.0; 3] {
+                let k = scale.map(|s| (s - 1.0).max(0.0));
+                let f = scale.map(|s| s.min(1.0));
+                sceGuBlendFunc(
+                    BlendOp::Add,
+                    BlendFactor::Color,
+                    BlendFactor::Fix,
+                    0,
+                    rgb(f),
+                );
+                draw_rect_filled(0.0, 0.0, WIDTH, HEIGHT, rgb(k) | 0xFF00_0000);
+            }
+            let add = offset.map(|o| o.max(0.0));
+            if add != [0.0; 3] {
+                sceGuBlendFunc(
+                    BlendOp::Add,
+                    BlendFactor::Fix,
+                    BlendFactor::Fix,
+                    0x00FF_FFFF,
+                    0x00FF_FFFF,
+                );
+                draw_rect_filled(0.0, 0.0, WIDTH, HEIGHT, rgb(add) | 0xFF00_0000);
+            }
+
+            set_blend_mode(BlendMode::AlphaBlend);
+        }
+        snapshot.restore();
+    }
+}
+
+/// Pack 0.0-1.0 channels as `0x00BBGGRR`.
+fn rgb(channels: [f32; 3]) -> u32 {
+    let [r, g, b] = channels.map(|c| (c.clamp(0.0, 1.0) * 255.0 + 0.5) as u32);
+    r | g << 8 | b << 16
+}
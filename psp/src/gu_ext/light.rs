@@ -0,0 +1,265 @@
+//! Hardware lighting and fog setup.
+//!
+//! The GE has four hardware lights, each configured through several
+//! `sceGuLight*` calls whose arguments are easy to mix up. [`Light`]
+//! collects a light's settings with a builder and applies them all with
+//! [`Light::enable`]. [`set_fog`] does the same for distance fog.
+//!
+//! Lit geometry needs normals, e.g. [`NormalVertex`](super::NormalVertex)
+//! or [`TexturedNormalVertex`](super::TexturedNormalVertex), and the
+//! material color set with `sceGuColor` or `sceGuMaterial`.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use psp::gu_ext::{Light, set_ambient, set_fog};
+//!
+//! unsafe {
+//!     set_ambient(0xFF20_2020);
+//!     Light::directional(0.0, 1.0, 1.0)
+//!         .color(0xFFFF_FFFF)
+//!         .specular(0xFF80_8080)
+//!         .enable(0);
+//!     Light::point(0.0, 2.0, 0.0)
+//!         .color(0xFF00_80FF)
+//!         .attenuation(1.0, 0.2, 0.0)
+//!         .enable(1);
+//!     set_fog(5.0, 20.0, 0xFF40_3020);
+//! }
+//! ```
+
+use crate::sys::{
+    GuState, LightComponent, LightType, ScePspFVector3, sceGuAmbient, sceGuDisable, sceGuEnable,
+    sceGuFog, sceGuLight, sceGuLightAtt, sceGuLightColor, sceGuLightSpot,
+};
+
+/// Number of hardware lights.
+pub const MAX_LIGHTS: usize = 4;
+
+/// What kind of light a [`Light`] is.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LightKind {
+    /// Infinitely far away, shining along one direction (the sun).
+    Directional,
+    /// Shining in all directions from a position (a bulb).
+    Point,
+    /// Shining in a cone from a position.
+    Spot {
+        /// Direction the cone points in.
+        direction: (f32, f32, f32),
+        /// Falloff from the cone's center to its edge; 0.0 is uniform.
+        exponent: f32,
+        /// Half-angle of the cone in radians.
+        cone_angle: f32,
+    },
+}
+
+/// Settings for one hardware light.
+///
+/// Build with [`directional`](Self::directional), [`point`](Self::point)
+/// or [`spot`](Self::spot), adjust with the other methods, then apply
+/// with [`enable`](Self::enable). Lights start out white with no specular
+/// highlight, no ambient contribution and no attenuation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Light {
+    kind: LightKind,
+    position: (f32, f32, f32),
+    diffuse: u32,
+    specular: Option<u32>,
+    ambient: u32,
+    attenuation: (f32, f32, f32),
+}
+
+impl Light {
+    fn new(kind: LightKind, position: (f32, f32, f32)) -> Self {
+        Self {
+            kind,
+            position,
+            diffuse: 0xFFFF_FFFF,
+            specular: None,
+            ambient: 0,
+            attenuation: (1.0, 0.0, 0.0),
+        }
+    }
+
+    /// A directional light. `(x, y, z)` points from the scene towards
+    /// the light, so `(0.0, 1.0, 0.0)` lights from above.
+    pub fn directional(x: f32, y: f32, z: f32) -> Self {
+        Self::new(LightKind::Directional, (x, y, z))
+    }
+
+    /// A point light at `(x, y, z)` in world space.
+    pub fn point(x: f32, y: f32, z: f32) -> Self {
+        Self::new(LightKind::Point, (x, y, z))
+    }
+
+    /// A spotlight at `position` pointing along `direction`, lighting a
+    /// cone with a half-angle of `cone_angle` radians.
+    pub fn spot(position: (f32, f32, f32), direction: (f32, f32, f32), cone_angle: f32) -> Self {
+        Self::new(
+            LightKind::Spot {
+                direction,
+                exponent: 0.0,
+                cone_angle,
+            },
+            position,
+        )
+    }
+
+    /// Set the diffuse color (ABGR; alpha is ignored).
+    pub fn color(mut self, color: u32) -> Self {
+        self.diffuse = color;
+        self
+    }
+
+    /// Add a specular highlight of `color` (ABGR).
+    ///
+    /// The highlight's sharpness is the material's specular power, set
+    /// with `sceGuSpecular`.
+    pub fn specular(mut self, color: u32) -> Self {
+        self.specular = Some(color);
+        self
+    }
+
+    /// Set the ambient color this light adds everywhere (ABGR).
+    pub fn ambient(mut self, color: u32) -> Self {
+        self.ambient = color;
+        self
+    }
+
+    /// Set distance attenuation as constant, linear and quadratic
+    /// factors: intensity is `1 / (constant + linear*d + quadratic*d²)`.
+    ///
+    /// Has no effect on directional lights.
+    pub fn attenuation(mut self, constant: f32, linear: f32, quadratic: f32) -> Self {
+        self.attenuation = (constant, linear, quadratic);
+        self
+    }
+
+    /// Set how sharply a spotlight falls off from the center of its cone
+    /// towards the edge. Has no effect on other lights.
+    pub fn spot_exponent(mut self, exponent: f32) -> Self {
+        if let LightKind::Spot { exponent: e, .. } = &mut self.kind {
+            *e = exponent;
+        }
+        self
+    }
+
+    /// What kind of light this is.
+    pub fn kind(&self) -> LightKind {
+        self.kind
+    }
+
+    /// Apply the settings to hardware light `index` and turn it and
+    /// lighting in general on.
+    ///
+    /// Returns `false` without touching the GE if `index` is not below
+    /// [`MAX_LIGHTS`].
+    ///
+    /// # Safety
+    ///
+    /// Must be called within an active GU display list.
+    pub unsafe fn enable(&self, index: usize) -> bool {
+        let Some(state) = light_state(index) else {
+            return false;
+        };
+        let light = index as i32;
+        let (kind, spot) = match self.kind {
+            LightKind::Directional => (LightType::Directional, None),
+            LightKind::Point => (LightType::Pointlight, None),
+            LightKind::Spot {
+                direction,
+                exponent,
+                cone_angle,
+            } => (
+                LightType::Spotlight,
+                Some((direction, exponent, cone_angle)),
+            ),
+        };
+        let components = if self.specular.is_some() {
+            LightComponent::DIFFUSE | LightComponent::SPECULAR
+        } else {
+            LightComponent::AMBIENT | LightComponent::DIFFUSE
+        };
+
+        unsafe {
+            sceGuLight(light, kind, components, &vector(self.position));
+            sceGuLightColor(light, LightComponent::DIFFUSE, self.diffuse);
+            sceGuLightColor(light, LightComponent::AMBIENT, self.ambient);
+            if let Some(specular) = self.specular {
+                sceGuLightColor(light, LightComponent::SPECULAR, specular);
+            }
+            let (constant, linear, quadratic) = self.attenuation;
+            sceGuLightAtt(light, constant, linear, quadratic);
+            if let Some((direction, exponent, cone_angle)) = spot {
+                // The GE compares against the cosine of the angle.
+                sceGuLightSpot(light, &vector(direction), exponent, libm::cosf(cone_angle));
+            }
+            sceGuEnable(state);
+            sceGuEnable(GuState::Lighting);
+        }
+        true
+    }
+}
+
+/// Turn off hardware light `index`. Lighting stays on for the others.
+///
+/// Returns `false` without touching the GE if `index` is not below
+/// [`MAX_LIGHTS`].
+///
+/// # Safety
+///
+/// Must be called within an active GU display list.
+pub unsafe fn disable_light(index: usize) -> bool {
+    let Some(state) = light_state(index) else {
+        return false;
+    };
+    unsafe { sceGuDisable(state) };
+    true
+}
+
+/// Set the scene-wide ambient light color (ABGR), added to every lit
+/// vertex regardless of the lights.
+///
+/// # Safety
+///
+/// Must be called within an active GU display list.
+pub unsafe fn set_ambient(color: u32) {
+    unsafe { sceGuAmbient(color) };
+}
+
+/// Enable linear distance fog: geometry fades from unfogged at `near` to
+/// fully `color` (ABGR; alpha is ignored) at `far`, in view space units.
+///
+/// # Safety
+///
+/// Must be called within an active GU display list.
+pub unsafe fn set_fog(near: f32, far: f32, color: u32) {
+    unsafe {
+        sceGuFog(near, far, color);
+        sceGuEnable(GuState::Fog);
+    }
+}
+
+/// Disable the fog enabled by [`set_fog`].
+///
+/// # Safety
+///
+/// Must be called within an active GU display list.
+pub unsafe fn disable_fog() {
+    unsafe { sceGuDisable(GuState::Fog) };
+}
+
+fn light_state(index: usize) -> Option<GuState> {
+    match index {
+        0 => Some(GuState::Light0),
+        1 => Some(GuState::Light1),
+        2 => Some(GuState::Light2),
+        3 => Some(GuState::Light3),
+        _ => None,
+    }
+}
+
+fn vector((x, y, z): (f32, f32, f32)) -> ScePspFVector3 {
+    ScePspFVector3 { x, y, z }
+}
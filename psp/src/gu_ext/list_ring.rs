@@ -0,0 +1,305 @@
+//! Asynchronous display-list submission through a ring of list buffers.
+//!
+//! The usual frame records one `Direct` list, finishes it and syncs, so the
+//! CPU sits idle while the GE draws and the GE sits idle while the CPU
+//! records. A [`ListRing`] records into `Send`-context lists instead and
+//! enqueues each one with `sceGeListEnQueue` as soon as it's finished,
+//! so the CPU can record list N+1 while the GE draws list N. It only
+//! blocks when every buffer is still queued or drawing, by waiting on the
+//! oldest.
+//!
+//! Each list starts by targeting the current draw buffer, so a heavy frame
+//! can be split across several lists that all land in the same image.
+//! The simple `Direct` path (`sceGuStart`/`sceGuFinish`/`sceGuSync`) keeps
+//! working alongside; call [`ListRing::sync_all()`] before swapping buffers.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use psp::gu_ext::ListRing;
+//!
+//! let mut ring = ListRing::new(3, 64 * 1024);
+//! loop {
+//!     for chunk in sprites.chunks(512) {
+//!         unsafe {
+//!             ring.begin();
+//!             draw_chunk(chunk);
+//!             ring.submit().unwrap();
+//!         }
+//!     }
+//!     ring.sync_all();
+//!     unsafe {
+//!         sys::sceDisplayWaitVblankStart();
+//!         sys::sceGuSwapBuffers();
+//!     }
+//! }
+//! ```
+
+use alloc::alloc::{Layout, alloc_zeroed, dealloc};
+use alloc::vec::Vec;
+use core::ffi::c_void;
+
+use crate::sys::{self, GeListState, GuContextType};
+
+/// Error from the GE, wrapping the raw SCE error code.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct GeError(pub i32);
+
+impl core::fmt::Debug for GeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "GeError({:#010x})", self.0 as u32)
+    }
+}
+
+impl core::fmt::Display for GeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "GE error {:#010x}", self.0 as u32)
+    }
+}
+
+/// Error from [`ListRing::submit()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubmitError {
+    /// No list was recording.
+    NotRecording,
+    /// The list and its terminating commands need `needed` bytes, more
+    /// than the buffer holds. The list was closed without being queued,
+    /// but whatever followed the buffer in memory has been overwritten.
+    Overflow {
+        /// Bytes the list needed.
+        needed: usize,
+    },
+    /// `sceGeListEnQueue` failed.
+    Ge(GeError),
+}
+
+impl core::fmt::Display for SubmitError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::NotRecording => f.write_str("no display list is recording"),
+            Self::Overflow { needed } => {
+                write!(
+                    f,
+                    "display list overflowed its buffer ({} bytes needed)",
+                    needed
+                )
+            },
+            Self::Ge(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// Display lists must be 16-byte aligned.
+const LIST_ALIGN: usize = 16;
+
+/// Bytes `sceGuFinish` adds to a `Send` list: a FINISH and an END command.
+const FINISH_BYTES: usize = 8;
+
+/// A ring of display-list buffers submitted to the GE without waiting.
+///
+/// See the [module documentation](self).
+pub struct ListRing {
+    buffers: Vec<*mut u8>,
+    list_bytes: usize,
+    /// Queue ID of each buffer's list while it may still be in flight.
+    queued: Vec<Option<i32>>,
+    /// Buffer the next [`begin()`](Self::begin) records into.
+    next: usize,
+    /// Buffer being recorded, between `begin` and `submit`.
+    recording: Option<usize>,
+}
+
+impl ListRing {
+    /// Allocate `lists` buffers of `list_bytes` bytes each.
+    ///
+    /// Two buffers are enough to overlap recording with drawing; a third
+    /// absorbs lists that vary in cost. Each buffer must hold a whole list,
+    /// including any vertices allocated with `sceGuGetMemory`; the GU
+    /// doesn't check for overflow.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `lists` is zero, or `list_bytes` is less than 16 or too
+    /// large to allocate.
+    pub fn new(lists: usize, list_bytes: usize) -> Self {
+        assert!(lists > 0, "a list ring needs at least one list");
+        assert!(list_bytes >= LIST_ALIGN, "display list buffer too small");
+        let layout = list_bytes
+            .checked_next_multiple_of(LIST_ALIGN)
+            .and_then(|size| Layout::from_size_align(size, LIST_ALIGN).ok());
+        let Some(layout) = layout else {
+            panic!("display list buffer too large");
+        };
+        let list_bytes = layout.size();
+
+        let buffers = (0..lists)
+            .map(|_| {
+                // SAFETY: the layout has a non-zero size.
+                let ptr = unsafe { alloc_zeroed(layout) };
+                if ptr.is_null() {
+                    alloc::alloc::handle_alloc_error(layout);
+                }
+                // Lists are written through the uncached mirror. Push out any
+                // dirty lines first so a later eviction can't overwrite them.
+                unsafe {
+                    sys::sceKernelDcacheWritebackInvalidateRange(
+                        ptr as *const c_void,
+                        list_bytes as u32,
+                    );
+                }
+                ptr
+            })
+            .collect();
+
+        Self {
+            buffers,
+            list_bytes,
+            queued: alloc::vec![None; lists],
+            next: 0,
+            recording: None,
+        }
+    }
+
+    fn layout(list_bytes: usize) -> Layout {
+        Layout::from_size_align(list_bytes, LIST_ALIGN).unwrap()
+    }
+
+    /// Number of list buffers.
+    pub fn len(&self) -> usize {
+        self.buffers.len()
+    }
+
+    /// Always `false`; a ring has at least one list.
+    pub fn is_empty(&self) -> bool {
+        self.buffers.is_empty()
+    }
+
+    /// Size of each list buffer in bytes.
+    pub fn list_bytes(&self) -> usize {
+        self.list_bytes
+    }
+
+    /// Start recording the next list, returning its buffer index.
+    ///
+    /// Blocks until that buffer's previous list has finished drawing. The
+    /// list opens by selecting the current draw buffer, so it draws where a
+    /// `Direct` list started now would.
+    ///
+    /// # Safety
+    ///
+    /// The GU must be initialized, and no other list may be recording.
+    /// Every `sceGu*` call up to [`submit()`](Self::submit) is recorded
+    /// into this list.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a list from this ring is already recording.
+    pub unsafe fn begin(&mut self) -> usize {
+        assert!(self.recording.is_none(), "list ring is already recording");
+        let index = self.next;
+        self.sync(index);
+
+        unsafe {
+            sys::sceGuStart(GuContextType::Send, self.buffers[index] as *mut c_void);
+            let (fbp, fbw, psm) = sys::draw_buffer_config();
+            if fbw != 0 {
+                sys::sceGuDrawBufferList(psm, fbp as *mut c_void, fbw);
+            }
+        }
+        self.recording = Some(index);
+        index
+    }
+
+    /// Finish the list started by [`begin()`](Self::begin) and queue it
+    /// for drawing, without waiting. Returns its buffer index.
+    ///
+    /// A list that doesn't fit its buffer is closed but never queued, and
+    /// the call fails with [`SubmitError::Overflow`]. Either way the
+    /// recording ends, so [`begin()`](Self::begin) can be called again.
+    ///
+    /// # Safety
+    ///
+    /// Everything the list references (textures, vertices outside the
+    /// list) must stay valid until it has been drawn.
+    pub unsafe fn submit(&mut self) -> Result<usize, SubmitError> {
+        let index = self.recording.take().ok_or(SubmitError::NotRecording)?;
+        let needed = unsafe { sys::sceGuCheckList() } as usize * 4 + FINISH_BYTES;
+        if needed > self.list_bytes {
+            // Don't write the terminating commands past the end as well.
+            unsafe { sys::abandon_list() };
+            return Err(SubmitError::Overflow { needed });
+        }
+        unsafe { sys::sceGuFinish() };
+
+        self.next = (index + 1) % self.buffers.len();
+        // The list was written through the uncached mirror; enqueue that
+        // address too.
+        let list = (self.buffers[index] as usize | 0x4000_0000) as *const c_void;
+        let id = unsafe {
+            sys::sceGeListEnQueue(
+                list,
+                core::ptr::null_mut(),
+                sys::ge_callback_id(),
+                core::ptr::null_mut(),
+            )
+        };
+        if id < 0 {
+            return Err(SubmitError::Ge(GeError(id)));
+        }
+        self.queued[index] = Some(id);
+        Ok(index)
+    }
+
+    /// Whether buffer `index` is free: its last list has finished drawing,
+    /// or it was never submitted.
+    pub fn is_done(&self, index: usize) -> bool {
+        match self.queued[index] {
+            Some(id) => matches!(
+                unsafe { sys::sceGeListSync(id, 1) },
+                GeListState::Done | GeListState::CancelDone
+            ),
+            None => true,
+        }
+    }
+
+    /// Block until buffer `index`'s last list has finished drawing.
+    pub fn sync(&mut self, index: usize) {
+        if let Some(id) = self.queued[index].take() {
+            unsafe {
+                sys::sceGeListSync(id, 0);
+            }
+        }
+    }
+
+    /// Block until every submitted list has finished drawing, e.g. before
+    /// swapping buffers.
+    pub fn sync_all(&mut self) {
+        for index in 0..self.buffers.len() {
+            self.sync(index);
+        }
+    }
+
+    /// Number of lists that are queued or drawing.
+    pub fn in_flight(&self) -> usize {
+        (0..self.buffers.len())
+            .filter(|&index| !self.is_done(index))
+            .count()
+    }
+}
+
+impl Drop for ListRing {
+    fn drop(&mut self) {
+        if self.recording.is_some() {
+            unsafe {
+                sys::sceGuFinish();
+            }
+        }
+        // The GE may still be reading the buffers.
+        self.sync_all();
+        let layout = Self::layout(self.list_bytes);
+        for &ptr in &self.buffers {
+            // SAFETY: allocated in `new` with the same layout.
+            unsafe { dealloc(ptr, layout) };
+        }
+    }
+}
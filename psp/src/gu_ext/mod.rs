@@ -0,0 +1,1504 @@
+//! GU rendering extensions for 2D sprite batching.
+//!
+//! Provides state snapshot/restore, 2D setup and clear helpers (including
+//! a scissored [`clear_rect()`] for dirty-rect redraws), blend presets
+//! ([`set_blend_mode()`]), a sprite batcher
+//! that draws textured quads efficiently using `GuPrimitive::Sprites`,
+//! immediate-mode primitives (lines, rectangles, circles) for debug overlays,
+//! one-call texture blits, palette ([`Clut`]) management, and stencil
+//! clipping ([`StencilMask`]).
+//!
+//! The [`capture`] submodule dumps, disassembles, validates and replays
+//! display lists for debugging, [`vertex`] provides typed vertex formats and a
+//! [`VertexBuffer`] that draws them with matching flags, and [`particles`]
+//! provides a pooled [`ParticleSystem`] drawn through a [`SpriteBatch`].
+//! For 3D scenes, [`light`] wraps hardware lighting in a [`Light`] builder
+//! and sets up distance fog with [`set_fog`], and [`skinning`] uploads
+//! [`Mat4`](crate::simd::Mat4) bone matrices for hardware skinning.
+//! [`transform`] composes parent and child model matrices on a
+//! [`TransformStack`] for hierarchical scenes, and [`picking`] maps
+//! screen coordinates back into the scene with [`screen_ray`].
+//! [`transition`] draws full-screen fades and wipes between scenes, and
+//! [`list_ring`] queues several display lists so the CPU records the next
+//! one while the GE draws the last. [`call_list`] records static drawing
+//! once into a [`CallList`] to replay every frame. [`color_grade`] applies
+//! a [`ColorGrade`] to the finished frame, on the CPU or the GE.
+
+use crate::sys::{
+    BlendFactor, BlendOp, ClearBuffer, ClutPixelFormat, GuPrimitive, GuState, MatrixMode,
+    MipmapLevel, StencilFunc, StencilOperation, TextureColorComponent, TextureEffect,
+    TexturePixelFormat, VertexType, sceGuBlendFunc, sceGuClear, sceGuClearColor, sceGuClearStencil,
+    sceGuClutLoad, sceGuClutMode, sceGuDisable, sceGuDrawArray, sceGuEnable, sceGuGetAllStatus,
+    sceGuGetMemory, sceGuGetStatus, sceGuPixelMask, sceGuScissor, sceGuSetAllStatus,
+    sceGuStencilFunc, sceGuStencilOp, sceGuTexFunc, sceGuTexImage, sceGuTexMode,
+    sceGumLoadIdentity, sceGumMatrixMode, sceGumOrtho,
+};
+use core::ffi::c_void;
+
+#[cfg(not(feature = "stub-only"))]
+pub mod call_list;
+#[cfg(not(feature = "stub-only"))]
+pub mod capture;
+pub mod color_grade;
+pub mod light;
+#[cfg(not(feature = "stub-only"))]
+pub mod list_ring;
+pub mod particles;
+pub mod picking;
+pub mod skinning;
+pub mod transform;
+pub mod transition;
+pub mod vertex;
+
+#[cfg(not(feature = "stub-only"))]
+pub use call_list::{AlignedBuf, CallList};
+#[cfg(not(feature = "stub-only"))]
+pub use capture::{GeWord, ListIssue, dump_list, validate_list};
+pub use color_grade::ColorGrade;
+pub use light::{Light, LightKind, MAX_LIGHTS, disable_fog, disable_light, set_ambient, set_fog};
+#[cfg(not(feature = "stub-only"))]
+pub use list_ring::{GeError, ListRing, SubmitError};
+pub use particles::{DrawOrder, EmitterConfig, Particle, ParticleSystem};
+pub use picking::{screen_ray, unproject};
+#[cfg(not(feature = "stub-only"))]
+pub use skinning::SkinnedMesh;
+pub use skinning::{
+    MAX_BONES, Morphed, SkinnedColoredVertex, SkinnedNormalVertex, SkinnedVertex, SkinningError,
+    WeightFormat, morphed_vertex_type, set_bone_matrices, set_bone_matrix, set_morph_weights,
+    skinned_vertex_type, weight_count,
+};
+pub use transform::{DEFAULT_TRANSFORM_DEPTH, TransformStack};
+pub use transition::{Transition, TransitionKind, TransitionState, WipeDirection};
+#[cfg(not(feature = "stub-only"))]
+pub use vertex::VertexBuffer;
+pub use vertex::{
+    ColoredVertex, NormalVertex, TexturedNormalVertex, TexturedVertex, VertexFormat, vertex_stride,
+};
+
+/// Snapshot of all 22 GU boolean states.
+///
+/// Only covers the states toggled by `sceGuEnable`/`sceGuDisable`.
+/// Other state (blend func, texture mode, scissor) must be saved manually.
+pub struct GuStateSnapshot {
+    bits: i32,
+}
+
+impl GuStateSnapshot {
+    /// Capture the current GU boolean state.
+    pub fn capture() -> Self {
+        Self {
+            bits: unsafe { sceGuGetAllStatus() },
+        }
+    }
+
+    /// Restore the captured state.
+    pub fn restore(&self) {
+        unsafe { sceGuSetAllStatus(self.bits) };
+    }
+}
+
+/// Set up GU for 2D rendering.
+///
+/// Configures an orthographic projection from (0,0) to (480,272), disables
+/// depth testing, and enables texture mapping and alpha blending.
+///
+/// # Safety
+///
+/// Must be called within an active GU display list.
+pub unsafe fn setup_2d() {
+    unsafe {
+        sceGumMatrixMode(MatrixMode::Projection);
+        sceGumLoadIdentity();
+        sceGumOrtho(0.0, 480.0, 272.0, 0.0, -1.0, 1.0);
+
+        sceGumMatrixMode(MatrixMode::View);
+        sceGumLoadIdentity();
+
+        sceGumMatrixMode(MatrixMode::Model);
+        sceGumLoadIdentity();
+
+        sceGuDisable(GuState::DepthTest);
+        sceGuEnable(GuState::Texture2D);
+        set_blend_mode(BlendMode::AlphaBlend);
+    }
+}
+
+/// Common blending setups for [`set_blend_mode()`].
+///
+/// `S` is the incoming fragment color, `D` the color already in the
+/// framebuffer and `Sa` the fragment's alpha.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlendMode {
+    /// Blending disabled: `S` replaces `D`.
+    None,
+    /// Standard transparency: `S*Sa + D*(1-Sa)`.
+    AlphaBlend,
+    /// Brightens by the fragment, weighted by its alpha: `S*Sa + D`.
+    /// For glows, fire and light particles.
+    Additive,
+    /// Darkens by the fragment: `S*D`. For shadows and tinting.
+    Multiply,
+    /// Inverse multiply, brightening without overshooting white:
+    /// `S + D*(1-S)`.
+    Screen,
+}
+
+/// Set the blend state to one of the [`BlendMode`] presets.
+///
+/// Enables the blend state and issues the matching `sceGuBlendFunc`, or
+/// disables blending for [`BlendMode::None`]. The GE clamps each channel
+/// of the result to 255.
+///
+/// # Safety
+///
+/// Must be called within an active GU display list.
+pub unsafe fn set_blend_mode(mode: BlendMode) {
+    // As a source factor `Color` is the destination color, and as a
+    // destination factor it's the source color.
+    let (src, dest, src_fix, dest_fix) = match mode {
+        BlendMode::None => {
+            unsafe { sceGuDisable(GuState::Blend) };
+            return;
+        },
+        BlendMode::AlphaBlend => (BlendFactor::SrcAlpha, BlendFactor::OneMinusSrcAlpha, 0, 0),
+        BlendMode::Additive => (BlendFactor::SrcAlpha, BlendFactor::Fix, 0, 0x00FF_FFFF),
+        BlendMode::Multiply => (BlendFactor::Color, BlendFactor::Fix, 0, 0),
+        BlendMode::Screen => (BlendFactor::Fix, BlendFactor::OneMinusColor, 0x00FF_FFFF, 0),
+    };
+    unsafe {
+        sceGuEnable(GuState::Blend);
+        sceGuBlendFunc(BlendOp::Add, src, dest, src_fix, dest_fix);
+    }
+}
+
+/// Clear the buffers selected by `flags`, the color buffer to `color`.
+///
+/// Depth and stencil are cleared to the values last set with
+/// `sceGuClearDepth` and `sceGuClearStencil`. A 3D frame usually wants
+/// `ClearBuffer::COLOR_BUFFER_BIT | ClearBuffer::DEPTH_BUFFER_BIT`.
+///
+/// # Safety
+///
+/// Must be called within an active GU display list.
+pub unsafe fn clear(color: u32, flags: ClearBuffer) {
+    unsafe {
+        sceGuClearColor(color);
+        sceGuClear(flags);
+    }
+}
+
+/// Clear only the `w`×`h` rectangle at (`x`, `y`) of the color buffer to
+/// `color`.
+///
+/// Sets a scissor around the rectangle for the clear and then puts the
+/// previous scissor region and enable state back. Paired with
+/// [`DirtyRect`](crate::framebuffer::DirtyRect), a mostly static screen
+/// can clear and redraw just the region that changed; see
+/// [`clear_dirty()`].
+///
+/// # Safety
+///
+/// Must be called within an active GU display list.
+pub unsafe fn clear_rect(x: u32, y: u32, w: u32, h: u32, color: u32) {
+    if w == 0 || h == 0 {
+        return;
+    }
+    unsafe {
+        let (enabled, [sx, sy, sw, sh]) = crate::sys::scissor_state();
+        sceGuScissor(x as i32, y as i32, (x + w) as i32, (y + h) as i32);
+        sceGuEnable(GuState::ScissorTest);
+        clear(color, ClearBuffer::COLOR_BUFFER_BIT);
+
+        // The scissor test is on, so this reloads the saved region.
+        sceGuScissor(sx, sy, sw, sh);
+        if !enabled {
+            sceGuDisable(GuState::ScissorTest);
+        }
+    }
+}
+
+/// Clear the bounding box of `dirty` to `color` with [`clear_rect()`].
+///
+/// Returns `(x, y, w, h)` of the cleared region so the caller can redraw
+/// what overlaps it, or `None` if nothing was dirty. `dirty` isn't reset.
+///
+/// # Safety
+///
+/// Must be called within an active GU display list.
+pub unsafe fn clear_dirty(
+    dirty: &crate::framebuffer::DirtyRect,
+    color: u32,
+) -> Option<(u32, u32, u32, u32)> {
+    let (x, y, w, h) = dirty.bounds()?;
+    unsafe { clear_rect(x, y, w, h, color) };
+    Some((x, y, w, h))
+}
+
+/// 2D sprite vertex: texture coords + color + position.
+///
+/// Layout matches `SPRITE_VERTEX_TYPE` for use with `GuPrimitive::Sprites`.
+#[repr(C, align(4))]
+#[derive(Clone, Copy)]
+pub struct SpriteVertex {
+    pub u: f32,
+    pub v: f32,
+    pub color: u32,
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+/// Vertex type flags for [`SpriteVertex`].
+pub const SPRITE_VERTEX_TYPE: VertexType = VertexType::from_bits_truncate(
+    VertexType::TEXTURE_32BITF.bits()
+        | VertexType::COLOR_8888.bits()
+        | VertexType::VERTEX_32BITF.bits()
+        | VertexType::TRANSFORM_2D.bits(),
+);
+
+/// A rectangular region of a texture, such as one frame of a sprite sheet.
+///
+/// Coordinates are in the same units [`SpriteBatch::draw_rect`] takes: texels
+/// when drawing with [`SPRITE_VERTEX_TYPE`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TexRegion {
+    pub u0: f32,
+    pub v0: f32,
+    pub u1: f32,
+    pub v1: f32,
+}
+
+impl TexRegion {
+    /// Region from `(u0, v0)` (top-left) to `(u1, v1)` (bottom-right).
+    pub const fn new(u0: f32, v0: f32, u1: f32, v1: f32) -> Self {
+        Self { u0, v0, u1, v1 }
+    }
+
+    /// Region covering a `w` x `h` texel texture from the origin.
+    pub const fn full(w: f32, h: f32) -> Self {
+        Self::new(0.0, 0.0, w, h)
+    }
+}
+
+/// Batches textured quads for efficient 2D rendering.
+///
+/// Each sprite is a pair of vertices (top-left, bottom-right) drawn with
+/// `GuPrimitive::Sprites`. Call [`flush`](SpriteBatch::flush) to submit
+/// all queued sprites in a single draw call.
+///
+/// Inside a display list, [`reserve`](SpriteBatch::reserve) claims vertex
+/// space from the list itself (via `sceGuGetMemory`), and sprites are then
+/// written straight into it: no heap buffer, no copy and no cache flush.
+/// Display-list memory belongs to the frame being built, so with the usual
+/// double-buffered lists each frame's vertices live alongside its commands.
+/// Sprites queued without a reservation, or beyond it, are staged in a heap
+/// buffer and copied into the display list on flush.
+///
+/// A batch can be flushed into a different context's list than the one it
+/// reserved in, such as a `Call` list recorded inside the frame's `Direct`
+/// list: [`flush_into`](SpriteBatch::flush_into) copies the reserved
+/// vertices into the target list rather than pointing it at memory the
+/// other list reuses. [`flush_to_buffer`](SpriteBatch::flush_to_buffer)
+/// puts the vertices in caller-owned memory instead, for lists replayed
+/// across frames (see [`CallList`]).
+#[cfg(not(feature = "stub-only"))]
+pub struct SpriteBatch {
+    /// Vertices queued outside the reservation.
+    staged: alloc::vec::Vec<SpriteVertex>,
+    /// Byte offset from the start of `reserved_context`'s list to the
+    /// display-list memory claimed by `reserve`. Kept as an offset rather
+    /// than a pointer so the batch stays `Send`.
+    reserved_offset: usize,
+    /// Context whose list the reservation is in.
+    reserved_context: crate::sys::GuContextType,
+    /// Vertices written to the reservation.
+    reserved_len: usize,
+    /// Vertex capacity of the reservation, 0 if there is none.
+    reserved_cap: usize,
+    max_sprites: usize,
+}
+
+#[cfg(not(feature = "stub-only"))]
+impl SpriteBatch {
+    /// Create a new sprite batch sized for `max_sprites` sprites.
+    ///
+    /// Nothing is allocated up front. The staging buffer grows to
+    /// `max_sprites * 2` vertices the first time a sprite is queued
+    /// without a [reservation](Self::reserve).
+    pub fn new(max_sprites: usize) -> Self {
+        Self {
+            staged: alloc::vec::Vec::new(),
+            reserved_offset: 0,
+            reserved_context: crate::sys::GuContextType::Direct,
+            reserved_len: 0,
+            reserved_cap: 0,
+            max_sprites,
+        }
+    }
+
+    /// Claim display-list memory for `n` more sprites.
+    ///
+    /// Queued sprites are moved into the new block, so this can be called
+    /// at any point before [`flush`](Self::flush). Does nothing if the
+    /// current reservation already has room. The reservation is released by
+    /// `flush`, so heavy UIs call this once per frame after `sceGuStart`.
+    ///
+    /// Returns `false` if the display list is out of memory, in which case
+    /// sprites keep going to the staging buffer.
+    ///
+    /// # Safety
+    ///
+    /// Must be called within an active GU display list, and the batch must
+    /// be flushed before that list is finished.
+    pub unsafe fn reserve(&mut self, n: usize) -> bool {
+        let needed = self.reserved_len + self.staged.len() + n * 2;
+        if needed <= self.reserved_cap && self.staged.is_empty() {
+            return true;
+        }
+
+        let byte_size = needed * core::mem::size_of::<SpriteVertex>();
+        let block = unsafe { sceGuGetMemory(byte_size as i32) } as *mut SpriteVertex;
+        if block.is_null() {
+            return false;
+        }
+        unsafe {
+            if self.reserved_len > 0 {
+                core::ptr::copy_nonoverlapping(self.reserved(), block, self.reserved_len);
+            }
+            core::ptr::copy_nonoverlapping(
+                self.staged.as_ptr(),
+                block.add(self.reserved_len),
+                self.staged.len(),
+            );
+            self.reserved_context = crate::sys::current_context();
+            self.reserved_offset =
+                block as usize - crate::sys::list_start(self.reserved_context) as usize;
+        }
+        self.reserved_len += self.staged.len();
+        self.reserved_cap = needed;
+        self.staged.clear();
+        true
+    }
+
+    /// Add a textured rectangle.
+    ///
+    /// `(x, y)` is the top-left corner, `(w, h)` is the size.
+    /// `(u0, v0)` to `(u1, v1)` are texture coordinates.
+    /// `color` is ABGR format (0xAABBGGRR).
+    pub fn draw_rect(
+        &mut self,
+        x: f32,
+        y: f32,
+        w: f32,
+        h: f32,
+        u0: f32,
+        v0: f32,
+        u1: f32,
+        v1: f32,
+        color: u32,
+    ) {
+        self.push(
+            SpriteVertex {
+                u: u0,
+                v: v0,
+                color,
+                x,
+                y,
+                z: 0.0,
+            },
+            SpriteVertex {
+                u: u1,
+                v: v1,
+                color,
+                x: x + w,
+                y: y + h,
+                z: 0.0,
+            },
+        );
+    }
+
+    /// Add an untextured colored rectangle.
+    ///
+    /// Texture coordinates are set to 0; bind a 1x1 white texture or
+    /// disable texturing before flushing.
+    pub fn draw_colored_rect(&mut self, x: f32, y: f32, w: f32, h: f32, color: u32) {
+        self.draw_rect(x, y, w, h, 0.0, 0.0, 0.0, 0.0, color);
+    }
+
+    fn push(&mut self, top_left: SpriteVertex, bottom_right: SpriteVertex) {
+        // Once anything is staged, later sprites are staged too so they
+        // stay in submission order.
+        if self.staged.is_empty() && self.reserved_len + 2 <= self.reserved_cap {
+            // SAFETY: The reservation has room for two more vertices, and
+            // `reserve`'s contract keeps it valid until `flush`.
+            unsafe {
+                let dst = self.reserved().add(self.reserved_len);
+                dst.write(top_left);
+                dst.add(1).write(bottom_right);
+            }
+            self.reserved_len += 2;
+            return;
+        }
+        if self.staged.capacity() == 0 {
+            self.staged.reserve(self.max_sprites * 2);
+        }
+        self.staged.push(top_left);
+        self.staged.push(bottom_right);
+    }
+
+    /// Number of sprites currently queued.
+    pub fn count(&self) -> usize {
+        (self.reserved_len + self.staged.len()) / 2
+    }
+
+    /// Sprites that still fit in the current reservation.
+    pub fn reserved_remaining(&self) -> usize {
+        if self.staged.is_empty() {
+            (self.reserved_cap - self.reserved_len) / 2
+        } else {
+            0
+        }
+    }
+
+    /// Discard all queued sprites.
+    ///
+    /// The reservation is kept, so its space can be reused.
+    pub fn clear(&mut self) {
+        self.reserved_len = 0;
+        self.staged.clear();
+    }
+
+    /// Submit all queued sprites to the GU, clear the batch and release
+    /// the reservation.
+    ///
+    /// Reserved sprites are already in display-list memory. Staged ones are
+    /// copied there (via `sceGuGetMemory`), so either way the vertex data
+    /// remains valid until `sceGuFinish`, regardless of when this
+    /// `SpriteBatch` is dropped.
+    ///
+    /// # Safety
+    ///
+    /// Must be called within an active GU display list with an appropriate
+    /// texture bound (for textured sprites).
+    pub unsafe fn flush(&mut self) {
+        // The current context is always open.
+        let _ = unsafe { self.flush_into(crate::sys::current_context()) };
+    }
+
+    /// Like [`flush`](Self::flush), but records the draw into `context`'s
+    /// list, which may be one the current list was started from.
+    ///
+    /// Reserved sprites are drawn in place only if they were reserved in
+    /// `context`'s list; otherwise they are copied into it, so the draw
+    /// never points at another list's memory.
+    ///
+    /// Returns `false`, leaving the batch untouched, if `context` has no
+    /// open display list.
+    ///
+    /// # Safety
+    ///
+    /// `context`'s display list must have an appropriate texture bound (for
+    /// textured sprites).
+    #[must_use]
+    pub unsafe fn flush_into(&mut self, context: crate::sys::GuContextType) -> bool {
+        let drawn = unsafe {
+            crate::sys::with_context(context, || {
+                if self.reserved_len > 0 {
+                    if self.reserved_context == context {
+                        draw_sprites(self.reserved(), self.reserved_len);
+                    } else {
+                        draw_copied_sprites(self.reserved(), self.reserved_len);
+                    }
+                }
+                if !self.staged.is_empty() {
+                    draw_copied_sprites(self.staged.as_ptr(), self.staged.len());
+                }
+            })
+        };
+        if drawn.is_none() {
+            return false;
+        }
+        self.reset();
+        true
+    }
+
+    /// Submit all queued sprites with their vertices copied into `storage`
+    /// rather than display-list memory, then clear the batch.
+    ///
+    /// The draw stays valid for as long as `storage` keeps those bytes, so
+    /// a [`CallList`] recorded with it can be replayed across frames.
+    /// Returns `false`, leaving the batch untouched, if `storage` is full.
+    ///
+    /// # Safety
+    ///
+    /// Must be called within an active GU display list with an appropriate
+    /// texture bound (for textured sprites), and `storage` must not be
+    /// cleared or dropped while a list that draws from it may run.
+    pub unsafe fn flush_to_buffer(&mut self, storage: &mut AlignedBuf) -> bool {
+        let count = self.reserved_len + self.staged.len();
+        if count == 0 {
+            return true;
+        }
+        let byte_size = count * core::mem::size_of::<SpriteVertex>();
+        let Some(block) = storage.alloc(byte_size) else {
+            return false;
+        };
+        let block = block as *mut SpriteVertex;
+        unsafe {
+            if self.reserved_len > 0 {
+                core::ptr::copy_nonoverlapping(self.reserved(), block, self.reserved_len);
+            }
+            core::ptr::copy_nonoverlapping(
+                self.staged.as_ptr(),
+                block.add(self.reserved_len),
+                self.staged.len(),
+            );
+            // The GE reads main memory, not the CPU's data cache.
+            crate::sys::sceKernelDcacheWritebackRange(block as *const c_void, byte_size as u32);
+            draw_sprites(block, count);
+        }
+        self.reset();
+        true
+    }
+
+    /// Clear the batch and release the reservation.
+    fn reset(&mut self) {
+        self.staged.clear();
+        self.reserved_offset = 0;
+        self.reserved_len = 0;
+        self.reserved_cap = 0;
+    }
+
+    /// The reserved vertices in display-list memory.
+    ///
+    /// # Safety
+    ///
+    /// Only valid while there is a reservation, whose list hasn't been
+    /// finished.
+    unsafe fn reserved(&self) -> *mut SpriteVertex {
+        unsafe {
+            crate::sys::list_start(self.reserved_context)
+                .byte_add(self.reserved_offset)
+                .cast()
+        }
+    }
+}
+
+/// Copy `count` vertices into display-list memory and draw them there.
+#[cfg(not(feature = "stub-only"))]
+unsafe fn draw_copied_sprites(vertices: *const SpriteVertex, count: usize) {
+    unsafe {
+        let byte_size = count * core::mem::size_of::<SpriteVertex>();
+        let dl_verts = sceGuGetMemory(byte_size as i32) as *mut SpriteVertex;
+        if !dl_verts.is_null() {
+            core::ptr::copy_nonoverlapping(vertices, dl_verts, count);
+            draw_sprites(dl_verts, count);
+        }
+    }
+}
+
+#[cfg(not(feature = "stub-only"))]
+unsafe fn draw_sprites(vertices: *const SpriteVertex, count: usize) {
+    unsafe {
+        sceGuDrawArray(
+            GuPrimitive::Sprites,
+            SPRITE_VERTEX_TYPE,
+            count as i32,
+            core::ptr::null::<c_void>(),
+            vertices as *const c_void,
+        );
+    }
+}
+
+// ── Debug primitives ────────────────────────────────────────────────
+
+/// Untextured 2D vertex: color + position.
+///
+/// Layout matches `COLOR_VERTEX_TYPE`. Used by the immediate-mode
+/// primitive helpers ([`draw_line`], [`draw_rect_filled`], ...).
+#[repr(C, align(4))]
+#[derive(Clone, Copy)]
+pub struct ColorVertex {
+    pub color: u32,
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+/// Vertex type flags for [`ColorVertex`].
+pub const COLOR_VERTEX_TYPE: VertexType = VertexType::from_bits_truncate(
+    VertexType::COLOR_8888.bits()
+        | VertexType::VERTEX_32BITF.bits()
+        | VertexType::TRANSFORM_2D.bits(),
+);
+
+/// Allocate `count` vertices from display-list memory, let `fill` write
+/// them, and draw them as `prim` with texturing temporarily disabled.
+///
+/// The previous `Texture2D` state is restored afterwards so these helpers
+/// can be freely mixed with [`SpriteBatch`] drawing after [`setup_2d`].
+unsafe fn draw_immediate(prim: GuPrimitive, count: usize, fill: impl FnOnce(&mut [ColorVertex])) {
+    if count == 0 {
+        return;
+    }
+    unsafe {
+        let byte_size = count * core::mem::size_of::<ColorVertex>();
+        let verts = sceGuGetMemory(byte_size as i32) as *mut ColorVertex;
+        if verts.is_null() {
+            return;
+        }
+        fill(core::slice::from_raw_parts_mut(verts, count));
+
+        let textured = sceGuGetStatus(GuState::Texture2D);
+        if textured {
+            sceGuDisable(GuState::Texture2D);
+        }
+        sceGuDrawArray(
+            prim,
+            COLOR_VERTEX_TYPE,
+            count as i32,
+            core::ptr::null::<c_void>(),
+            verts as *const c_void,
+        );
+        if textured {
+            sceGuEnable(GuState::Texture2D);
+        }
+    }
+}
+
+#[inline]
+fn cv(x: f32, y: f32, color: u32) -> ColorVertex {
+    ColorVertex {
+        color,
+        x,
+        y,
+        z: 0.0,
+    }
+}
+
+/// Draw a one-pixel line from `(x0, y0)` to `(x1, y1)`.
+///
+/// `color` is ABGR format (0xAABBGGRR).
+///
+/// # Safety
+///
+/// Must be called within an active GU display list, typically after
+/// [`setup_2d`].
+pub unsafe fn draw_line(x0: f32, y0: f32, x1: f32, y1: f32, color: u32) {
+    unsafe {
+        draw_immediate(GuPrimitive::Lines, 2, |v| {
+            v[0] = cv(x0, y0, color);
+            v[1] = cv(x1, y1, color);
+        });
+    }
+}
+
+/// Draw connected line segments through `points`.
+///
+/// Fewer than two points draws nothing.
+///
+/// # Safety
+///
+/// Must be called within an active GU display list.
+pub unsafe fn draw_polyline(points: &[(f32, f32)], color: u32) {
+    if points.len() < 2 {
+        return;
+    }
+    unsafe {
+        draw_immediate(GuPrimitive::LineStrip, points.len(), |v| {
+            for (vert, &(x, y)) in v.iter_mut().zip(points) {
+                *vert = cv(x, y, color);
+            }
+        });
+    }
+}
+
+/// Draw a filled axis-aligned rectangle.
+///
+/// `(x, y)` is the top-left corner, `(w, h)` is the size.
+///
+/// # Safety
+///
+/// Must be called within an active GU display list.
+pub unsafe fn draw_rect_filled(x: f32, y: f32, w: f32, h: f32, color: u32) {
+    unsafe {
+        draw_immediate(GuPrimitive::Sprites, 2, |v| {
+            v[0] = cv(x, y, color);
+            v[1] = cv(x + w, y + h, color);
+        });
+    }
+}
+
+/// Draw a one-pixel rectangle outline.
+///
+/// # Safety
+///
+/// Must be called within an active GU display list.
+pub unsafe fn draw_rect_outline(x: f32, y: f32, w: f32, h: f32, color: u32) {
+    unsafe {
+        draw_immediate(GuPrimitive::LineStrip, 5, |v| {
+            v[0] = cv(x, y, color);
+            v[1] = cv(x + w, y, color);
+            v[2] = cv(x + w, y + h, color);
+            v[3] = cv(x, y + h, color);
+            v[4] = cv(x, y, color);
+        });
+    }
+}
+
+/// Draw a filled triangle.
+///
+/// # Safety
+///
+/// Must be called within an active GU display list. Face culling, if
+/// enabled, applies to the winding of the three points.
+pub unsafe fn draw_triangle(x0: f32, y0: f32, x1: f32, y1: f32, x2: f32, y2: f32, color: u32) {
+    unsafe {
+        draw_immediate(GuPrimitive::Triangles, 3, |v| {
+            v[0] = cv(x0, y0, color);
+            v[1] = cv(x1, y1, color);
+            v[2] = cv(x2, y2, color);
+        });
+    }
+}
+
+/// Write `segments + 1` points around a circle into `out`, closing the loop.
+fn circle_points(out: &mut [ColorVertex], cx: f32, cy: f32, r: f32, color: u32) {
+    let segments = out.len() - 1;
+    let step = core::f32::consts::TAU / segments as f32;
+    let (sin, cos) = (libm::sinf(step), libm::cosf(step));
+    // Rotate an offset vector instead of evaluating sin/cos per vertex.
+    let (mut dx, mut dy) = (r, 0.0f32);
+    for vert in out.iter_mut().take(segments) {
+        *vert = cv(cx + dx, cy + dy, color);
+        (dx, dy) = (dx * cos - dy * sin, dx * sin + dy * cos);
+    }
+    out[segments] = cv(cx + r, cy, color);
+}
+
+/// Draw a circle outline centered on `(x, y)` with radius `r`.
+///
+/// `segments` is clamped to at least 3.
+///
+/// # Safety
+///
+/// Must be called within an active GU display list.
+pub unsafe fn draw_circle(x: f32, y: f32, r: f32, segments: u32, color: u32) {
+    let segments = segments.max(3) as usize;
+    unsafe {
+        draw_immediate(GuPrimitive::LineStrip, segments + 1, |v| {
+            circle_points(v, x, y, r, color);
+        });
+    }
+}
+
+/// Draw a filled circle centered on `(x, y)` with radius `r`.
+///
+/// `segments` is clamped to at least 3.
+///
+/// # Safety
+///
+/// Must be called within an active GU display list.
+pub unsafe fn draw_circle_filled(x: f32, y: f32, r: f32, segments: u32, color: u32) {
+    let segments = segments.max(3) as usize;
+    unsafe {
+        draw_immediate(GuPrimitive::TriangleFan, segments + 2, |v| {
+            v[0] = cv(x, y, color);
+            circle_points(&mut v[1..], x, y, r, color);
+        });
+    }
+}
+
+// ── Textures ────────────────────────────────────────────────────────
+
+/// Error from creating a [`Texture`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextureError {
+    /// Width or height exceeds the GE limit of 512 pixels, or is zero.
+    InvalidSize,
+    /// The pixel buffer could not be allocated.
+    OutOfMemory,
+    /// The source pixels don't match the size, or the format isn't an
+    /// indexed one.
+    InvalidData,
+}
+
+impl core::fmt::Display for TextureError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidSize => write!(f, "texture size must be 1..=512 pixels"),
+            Self::OutOfMemory => write!(f, "out of memory for texture pixels"),
+            Self::InvalidData => write!(f, "pixel data doesn't match the texture size or format"),
+        }
+    }
+}
+
+/// Largest texture dimension the GE can sample.
+pub const MAX_TEXTURE_SIZE: u32 = 512;
+
+/// A texture the GE can sample from: pixel data plus its layout.
+///
+/// Created either over existing memory with [`from_raw`](Self::from_raw)
+/// (e.g. VRAM from `vram_alloc`), or from a decoded image with
+/// [`from_image`](Self::from_image), in which case the pixels are owned
+/// and freed on drop.
+pub struct Texture {
+    data: *const c_void,
+    width: u32,
+    height: u32,
+    buf_width: u32,
+    format: TexturePixelFormat,
+    #[cfg(not(feature = "stub-only"))]
+    owned: Option<core::alloc::Layout>,
+}
+
+impl Texture {
+    /// Describe a texture over existing pixel memory.
+    ///
+    /// `buf_width` is the row stride in pixels (at least `width`).
+    ///
+    /// # Safety
+    ///
+    /// `data` must be 16-byte aligned, hold at least `buf_width * height`
+    /// pixels of `format`, be visible to the GE (written back from the
+    /// data cache), and outlive every display list that samples it.
+    pub unsafe fn from_raw(
+        data: *const c_void,
+        width: u32,
+        height: u32,
+        buf_width: u32,
+        format: TexturePixelFormat,
+    ) -> Self {
+        Self {
+            data,
+            width,
+            height,
+            buf_width,
+            format,
+            #[cfg(not(feature = "stub-only"))]
+            owned: None,
+        }
+    }
+
+    /// Upload a decoded image into a new 32-bit RGBA texture.
+    ///
+    /// The row stride is padded to a multiple of 4 pixels, as the GE
+    /// requires, and the pixels are written back from the data cache so
+    /// the texture can be drawn immediately. Fails with
+    /// [`TextureError::InvalidData`] if `image.data` isn't exactly
+    /// `width * height` pixels of its format.
+    #[cfg(not(feature = "stub-only"))]
+    pub fn from_image(image: &crate::image::DecodedImage) -> Result<Self, TextureError> {
+        use crate::image::PixelFormat;
+
+        let (width, height) = (image.width, image.height);
+        if width == 0 || height == 0 || width > MAX_TEXTURE_SIZE || height > MAX_TEXTURE_SIZE {
+            return Err(TextureError::InvalidSize);
+        }
+        let src_bpp = match image.format {
+            PixelFormat::Rgba8888 => 4,
+            PixelFormat::Rgb888 => 3,
+        };
+        if image.data.len() != width as usize * height as usize * src_bpp {
+            return Err(TextureError::InvalidData);
+        }
+        let buf_width = (width + 3) & !3;
+        let size = (buf_width * height * 4) as usize;
+        let layout = core::alloc::Layout::from_size_align(size, 16)
+            .map_err(|_| TextureError::OutOfMemory)?;
+        // SAFETY: `size` is non-zero.
+        let ptr = unsafe { alloc::alloc::alloc_zeroed(layout) } as *mut u32;
+        if ptr.is_null() {
+            return Err(TextureError::OutOfMemory);
+        }
+
+        for y in 0..height as usize {
+            let row = &image.data[y * width as usize * src_bpp..][..width as usize * src_bpp];
+            for (x, px) in row.chunks_exact(src_bpp).enumerate() {
+                let a = if src_bpp == 4 { px[3] } else { 0xff };
+                // GE 8888 is ABGR: red in the low byte.
+                let abgr = u32::from_le_bytes([px[0], px[1], px[2], a]);
+                // SAFETY: x < width <= buf_width, y < height.
+                unsafe { *ptr.add(y * buf_width as usize + x) = abgr };
+            }
+        }
+        unsafe { crate::cache::dcache_writeback_range(ptr as *const c_void, size as u32) };
+
+        Ok(Self {
+            data: ptr as *const c_void,
+            width,
+            height,
+            buf_width,
+            format: TexturePixelFormat::Psm8888,
+            owned: Some(layout),
+        })
+    }
+
+    /// Copy palette indices into a new `PsmT8` or `PsmT4` texture, drawn
+    /// with a [`Clut`] via [`bind_with_clut()`](Self::bind_with_clut).
+    ///
+    /// `indices` holds `height` tightly packed rows: one byte per pixel
+    /// for `PsmT8`, or two pixels per byte for `PsmT4`, the left pixel in
+    /// the low nibble. The row stride is padded to 16 bytes, as the GE
+    /// requires.
+    #[cfg(not(feature = "stub-only"))]
+    pub fn from_indexed(
+        indices: &[u8],
+        width: u32,
+        height: u32,
+        format: TexturePixelFormat,
+    ) -> Result<Self, TextureError> {
+        if width == 0 || height == 0 || width > MAX_TEXTURE_SIZE || height > MAX_TEXTURE_SIZE {
+            return Err(TextureError::InvalidSize);
+        }
+        // Pixels per 16-byte block and bytes per source row.
+        let (block, row_bytes) = match format {
+            TexturePixelFormat::PsmT8 => (16, width as usize),
+            TexturePixelFormat::PsmT4 => (32, (width as usize).div_ceil(2)),
+            _ => return Err(TextureError::InvalidData),
+        };
+        if indices.len() < row_bytes * height as usize {
+            return Err(TextureError::InvalidData);
+        }
+        let buf_width = width.next_multiple_of(block);
+        let stride = buf_width as usize * 16 / block as usize;
+        let size = stride * height as usize;
+        let layout = core::alloc::Layout::from_size_align(size, 16)
+            .map_err(|_| TextureError::OutOfMemory)?;
+        // SAFETY: `size` is non-zero.
+        let ptr = unsafe { alloc::alloc::alloc_zeroed(layout) };
+        if ptr.is_null() {
+            return Err(TextureError::OutOfMemory);
+        }
+        for (y, row) in indices
+            .chunks_exact(row_bytes)
+            .take(height as usize)
+            .enumerate()
+        {
+            // SAFETY: `row_bytes <= stride`, y < height.
+            unsafe {
+                core::ptr::copy_nonoverlapping(row.as_ptr(), ptr.add(y * stride), row_bytes);
+            }
+        }
+        unsafe { crate::cache::dcache_writeback_range(ptr as *const c_void, size as u32) };
+
+        Ok(Self {
+            data: ptr as *const c_void,
+            width,
+            height,
+            buf_width,
+            format,
+            owned: Some(layout),
+        })
+    }
+
+    /// Width of the image in pixels.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Height of the image in pixels.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Row stride in pixels.
+    pub fn buf_width(&self) -> u32 {
+        self.buf_width
+    }
+
+    /// Pixel format of the texture data.
+    pub fn format(&self) -> TexturePixelFormat {
+        self.format
+    }
+
+    /// Pointer to the first pixel.
+    pub fn as_ptr(&self) -> *const c_void {
+        self.data
+    }
+
+    /// Make this the current texture.
+    ///
+    /// Sets the texture mode, image, and a modulate texture function so
+    /// vertex color tints the texture (white leaves it unchanged).
+    /// Indexed formats additionally need a palette loaded with
+    /// [`Clut::upload`], or use [`bind_with_clut()`](Self::bind_with_clut).
+    ///
+    /// # Safety
+    ///
+    /// Must be called within an active GU display list.
+    pub unsafe fn bind(&self) {
+        unsafe {
+            sceGuTexMode(self.format, 0, 0, 0);
+            sceGuTexImage(
+                MipmapLevel::None,
+                self.width.next_power_of_two() as i32,
+                self.height.next_power_of_two() as i32,
+                self.buf_width as i32,
+                self.data,
+            );
+            sceGuTexFunc(TextureEffect::Modulate, TextureColorComponent::Rgba);
+        }
+    }
+
+    /// Load `clut` and make this the current texture.
+    ///
+    /// The palette stays loaded for later draws, so the blit helpers,
+    /// which rebind only the texture, use it too. Binding the same
+    /// texture with another palette swaps its colors.
+    ///
+    /// # Safety
+    ///
+    /// Same as [`bind()`](Self::bind) and [`Clut::upload()`].
+    pub unsafe fn bind_with_clut(&self, clut: &Clut) {
+        unsafe {
+            clut.upload();
+            self.bind();
+        }
+    }
+}
+
+#[cfg(not(feature = "stub-only"))]
+impl Drop for Texture {
+    fn drop(&mut self) {
+        if let Some(layout) = self.owned {
+            // SAFETY: allocated in `from_image` with this layout.
+            unsafe { alloc::alloc::dealloc(self.data as *mut u8, layout) };
+        }
+    }
+}
+
+/// Allocate `count` sprite vertices from display-list memory, let `fill`
+/// write them, and draw them as `GuPrimitive::Sprites` with texturing
+/// enabled.
+unsafe fn draw_textured_sprites(count: usize, fill: impl FnOnce(&mut [SpriteVertex])) {
+    unsafe {
+        let byte_size = count * core::mem::size_of::<SpriteVertex>();
+        let verts = sceGuGetMemory(byte_size as i32) as *mut SpriteVertex;
+        if verts.is_null() {
+            return;
+        }
+        fill(core::slice::from_raw_parts_mut(verts, count));
+
+        sceGuEnable(GuState::Texture2D);
+        sceGuDrawArray(
+            GuPrimitive::Sprites,
+            SPRITE_VERTEX_TYPE,
+            count as i32,
+            core::ptr::null::<c_void>(),
+            verts as *const c_void,
+        );
+    }
+}
+
+#[inline]
+fn sv(x: f32, y: f32, u: f32, v: f32) -> SpriteVertex {
+    SpriteVertex {
+        u,
+        v,
+        color: 0xffff_ffff,
+        x,
+        y,
+        z: 0.0,
+    }
+}
+
+/// Draw `tex` at its natural size with its top-left corner at `(x, y)`.
+///
+/// # Safety
+///
+/// Must be called within an active GU display list, typically after
+/// [`setup_2d`]. Rebinds the current texture.
+pub unsafe fn blit_texture(tex: &Texture, x: f32, y: f32) {
+    unsafe { blit_texture_scaled(tex, x, y, tex.width as f32, tex.height as f32) }
+}
+
+/// Draw `tex` stretched to `w` x `h` pixels at `(x, y)`.
+///
+/// Uses whatever texture filter is currently set; select
+/// `TextureFilter::Linear` with `sceGuTexFilter` for smooth scaling.
+///
+/// # Safety
+///
+/// Must be called within an active GU display list, typically after
+/// [`setup_2d`]. Rebinds the current texture.
+pub unsafe fn blit_texture_scaled(tex: &Texture, x: f32, y: f32, w: f32, h: f32) {
+    unsafe {
+        tex.bind();
+        draw_textured_sprites(2, |v| {
+            v[0] = sv(x, y, 0.0, 0.0);
+            v[1] = sv(x + w, y + h, tex.width as f32, tex.height as f32);
+        });
+    }
+}
+
+/// Draw `tex` as a resizable nine-slice (nine-patch) panel.
+///
+/// The source texture is split into a 3x3 grid with `border`-pixel
+/// corners. Corners are drawn unscaled, edges are stretched along one
+/// axis, and the center is stretched to fill `w` x `h`. All nine pieces
+/// go out in a single `GuPrimitive::Sprites` draw.
+///
+/// `border` is clamped so the corners never overlap, in either the
+/// texture or the destination rectangle.
+///
+/// # Safety
+///
+/// Must be called within an active GU display list, typically after
+/// [`setup_2d`]. Rebinds the current texture.
+pub unsafe fn draw_nine_slice(tex: &Texture, x: f32, y: f32, w: f32, h: f32, border: u32) {
+    let (tw, th) = (tex.width as f32, tex.height as f32);
+    let b = (border as f32).min(tw * 0.5).min(th * 0.5);
+    let bx = b.min(w * 0.5);
+    let by = b.min(h * 0.5);
+
+    // Column/row edges in destination and source space.
+    let dx = [x, x + bx, x + w - bx, x + w];
+    let dy = [y, y + by, y + h - by, y + h];
+    let sx = [0.0, b, tw - b, tw];
+    let sy = [0.0, b, th - b, th];
+
+    unsafe {
+        tex.bind();
+        draw_textured_sprites(18, |v| {
+            for row in 0..3 {
+                for col in 0..3 {
+                    let i = (row * 3 + col) * 2;
+                    v[i] = sv(dx[col], dy[row], sx[col], sy[row]);
+                    v[i + 1] = sv(dx[col + 1], dy[row + 1], sx[col + 1], sy[row + 1]);
+                }
+            }
+        });
+    }
+}
+
+// ── Palettes ────────────────────────────────────────────────────────
+
+/// Palette storage. The GE loads CLUTs in 16-byte blocks, so the
+/// alignment is part of the type rather than checked at runtime.
+#[repr(C, align(16))]
+#[derive(Clone, Copy)]
+struct ClutEntries([u32; 256]);
+
+/// A color lookup table (palette) for `PsmT4` / `PsmT8` textures.
+///
+/// Entries are 32-bit ABGR (`0xAABBGGRR`). A palette is uploaded with
+/// [`upload()`](Self::upload) before drawing indexed textures, or together
+/// with one by [`Texture::bind_with_clut()`]; swapping
+/// palettes between draws gives team colors or damage flashes from a
+/// single texture, and [`cycle()`](Self::cycle) rotates a range of
+/// entries for classic palette-cycling animation.
+///
+/// # Example
+///
+/// ```ignore
+/// use psp::gu_ext::Clut;
+///
+/// let mut water = Clut::new_8bit(&WATER_PALETTE);
+/// loop {
+///     water.cycle(16..32, 1);
+///     unsafe {
+///         water.upload_dirty();
+///         tex.bind();
+///         blit_texture(&tex, 0.0, 0.0);
+///     }
+/// }
+/// ```
+#[derive(Clone)]
+pub struct Clut {
+    entries: ClutEntries,
+    /// 16 or 256.
+    len: u16,
+    /// Entries `0..dirty_end` have changed since the last upload.
+    dirty_end: u16,
+}
+
+impl Clut {
+    /// A palette from up to 256 colors.
+    ///
+    /// Up to 16 colors make a 16-entry palette for `PsmT4` textures, more
+    /// a 256-entry one for `PsmT8`; unused entries are transparent black.
+    /// Returns `None` if `colors` has more than 256 entries.
+    pub fn new(colors: &[u32]) -> Option<Self> {
+        if colors.len() > 256 {
+            return None;
+        }
+        let len = if colors.len() <= 16 { 16 } else { 256 };
+        let mut entries = [0u32; 256];
+        entries[..colors.len()].copy_from_slice(colors);
+        Some(Self {
+            entries: ClutEntries(entries),
+            len,
+            dirty_end: len,
+        })
+    }
+
+    /// A 256-entry palette for `PsmT8` textures.
+    pub const fn new_8bit(colors: &[u32; 256]) -> Self {
+        Self {
+            entries: ClutEntries(*colors),
+            len: 256,
+            dirty_end: 256,
+        }
+    }
+
+    /// A 16-entry palette for `PsmT4` textures.
+    pub const fn new_4bit(colors: &[u32; 16]) -> Self {
+        let mut entries = [0u32; 256];
+        let mut i = 0;
+        while i < 16 {
+            entries[i] = colors[i];
+            i += 1;
+        }
+        Self {
+            entries: ClutEntries(entries),
+            len: 16,
+            dirty_end: 16,
+        }
+    }
+
+    /// Number of entries (16 or 256).
+    pub fn size(&self) -> usize {
+        self.len as usize
+    }
+
+    /// The palette entries.
+    pub fn entries(&self) -> &[u32] {
+        &self.entries.0[..self.len as usize]
+    }
+
+    /// Color at `index`, or `None` if out of range.
+    pub fn entry(&self, index: usize) -> Option<u32> {
+        self.entries().get(index).copied()
+    }
+
+    /// Change one entry. Out-of-range indices are ignored.
+    pub fn set_entry(&mut self, index: usize, color: u32) {
+        if index < self.len as usize {
+            self.entries.0[index] = color;
+            self.mark_dirty(index + 1);
+        }
+    }
+
+    /// Rotate the entries in `range` by `offset` positions towards the
+    /// end of the range (the last entries wrap around to the start).
+    ///
+    /// The range is clamped to the palette size.
+    pub fn cycle(&mut self, range: core::ops::Range<usize>, offset: usize) {
+        let end = range.end.min(self.len as usize);
+        if range.start >= end {
+            return;
+        }
+        let slice = &mut self.entries.0[range.start..end];
+        let n = slice.len();
+        slice.rotate_right(offset % n);
+        self.mark_dirty(end);
+    }
+
+    fn mark_dirty(&mut self, end: usize) {
+        self.dirty_end = self.dirty_end.max(end as u16);
+    }
+
+    /// Set the CLUT mode and load the whole palette.
+    ///
+    /// # Safety
+    ///
+    /// Must be called within an active GU display list. The GE reads the
+    /// palette while the list executes, so `self` must stay alive and
+    /// unmodified until the list has finished.
+    pub unsafe fn upload(&self) {
+        unsafe { self.load(self.len as usize) };
+    }
+
+    /// Reload only the changed part of the palette.
+    ///
+    /// The GE always loads palettes from the first entry, so this loads
+    /// the blocks up to the last changed entry. It assumes this palette
+    /// is still the one in the CLUT cache; after uploading a different
+    /// palette, use [`upload()`](Self::upload) instead. Does nothing if no
+    /// entry changed.
+    ///
+    /// # Safety
+    ///
+    /// Same as [`upload()`](Self::upload).
+    pub unsafe fn upload_dirty(&mut self) {
+        let count = self.take_dirty();
+        if count == 0 {
+            return;
+        }
+        unsafe { self.load(count) };
+    }
+
+    /// Number of leading entries [`upload_dirty()`](Self::upload_dirty)
+    /// would load, marking them clean. Public for the on-device tests.
+    #[doc(hidden)]
+    pub fn take_dirty(&mut self) -> usize {
+        core::mem::take(&mut self.dirty_end) as usize
+    }
+
+    /// Set the CLUT mode and load the first `count` entries.
+    unsafe fn load(&self, count: usize) {
+        let blocks = count.div_ceil(8);
+        let mask = self.len as u32 - 1;
+        let ptr = self.entries.0.as_ptr() as *const c_void;
+        unsafe {
+            crate::cache::dcache_writeback_range(ptr, (blocks * 8 * 4) as u32);
+            sceGuClutMode(ClutPixelFormat::Psm8888, 0, mask, 0);
+            sceGuClutLoad(blocks as i32, ptr);
+        }
+    }
+}
+
+// ── Stencil masks ───────────────────────────────────────────────────
+
+/// Clip drawing to an arbitrary shape using the stencil buffer.
+///
+/// Draws between [`begin_mask()`](Self::begin_mask) and
+/// [`end_mask()`](Self::end_mask) write the mask value into the stencil
+/// buffer without touching the color channels. Draws between
+/// [`begin_clipped()`](Self::begin_clipped) and
+/// [`end_clipped()`](Self::end_clipped) then only land where the stencil
+/// holds the mask value.
+///
+/// The stencil buffer is the alpha channel of the draw buffer: 8 bits in
+/// `Psm8888`, 4 in `Psm4444`, 1 in `Psm5551` and none in `Psm5650`. Any
+/// draw made with the stencil test disabled writes its color alpha into
+/// those bits, so draw the mask after the unclipped content it overlaps
+/// and clear it with [`clear()`](Self::clear) each frame.
+///
+/// # Example
+///
+/// ```ignore
+/// use psp::gu_ext::{StencilMask, draw_rect_filled};
+///
+/// let mut stencil = StencilMask::new();
+/// unsafe {
+///     stencil.clear();
+///     stencil.begin_mask();
+///     draw_rect_filled(40.0, 40.0, 200.0, 100.0, 0xffffffff);
+///     stencil.end_mask();
+///
+///     stencil.begin_clipped();
+///     renderer.draw_text(scroll_x, 90.0, 0xffffffff, "clipped to the panel");
+///     renderer.flush();
+///     stencil.end_clipped();
+/// }
+/// ```
+pub struct StencilMask {
+    value: u8,
+    saved: Option<GuStateSnapshot>,
+}
+
+impl StencilMask {
+    /// A mask that writes and tests for stencil value 1.
+    pub fn new() -> Self {
+        Self::with_value(1)
+    }
+
+    /// A mask that writes and tests for `value`.
+    ///
+    /// Several masks with different values can coexist in an 8-bit
+    /// stencil buffer.
+    pub fn with_value(value: u8) -> Self {
+        Self { value, saved: None }
+    }
+
+    /// The stencil value this mask writes and tests for.
+    pub fn value(&self) -> u8 {
+        self.value
+    }
+
+    /// Clear the whole stencil buffer to 0, leaving color and depth alone.
+    ///
+    /// # Safety
+    ///
+    /// Must be called within an active GU display list.
+    pub unsafe fn clear(&self) {
+        unsafe {
+            sceGuClearStencil(0);
+            sceGuClear(ClearBuffer::STENCIL_BUFFER_BIT);
+        }
+    }
+
+    /// Start drawing the mask shape.
+    ///
+    /// Every pixel drawn until [`end_mask()`](Self::end_mask) sets the
+    /// stencil to the mask value; color writes are disabled. Alpha-tested
+    /// pixels (e.g. transparent texels with `GuState::AlphaTest` on) are
+    /// left out of the mask.
+    ///
+    /// # Safety
+    ///
+    /// Must be called within an active GU display list.
+    pub unsafe fn begin_mask(&mut self) {
+        self.saved = Some(GuStateSnapshot::capture());
+        unsafe {
+            sceGuEnable(GuState::StencilTest);
+            sceGuStencilFunc(StencilFunc::Always, self.value as i32, 0xff);
+            sceGuStencilOp(
+                StencilOperation::Keep,
+                StencilOperation::Replace,
+                StencilOperation::Replace,
+            );
+            // Protect RGB; the alpha bits carry the stencil writes.
+            sceGuPixelMask(0x00ff_ffff);
+        }
+    }
+
+    /// Finish the mask shape and re-enable color writes.
+    ///
+    /// Restores the GU boolean state saved by
+    /// [`begin_mask()`](Self::begin_mask) and resets the pixel mask to 0.
+    ///
+    /// # Safety
+    ///
+    /// Must be called within an active GU display list.
+    pub unsafe fn end_mask(&mut self) {
+        unsafe { sceGuPixelMask(0) };
+        self.restore();
+    }
+
+    /// Start drawing content clipped to the mask.
+    ///
+    /// Until [`end_clipped()`](Self::end_clipped), pixels are only drawn
+    /// where the stencil equals the mask value, and the stencil is left
+    /// unchanged.
+    ///
+    /// # Safety
+    ///
+    /// Must be called within an active GU display list.
+    pub unsafe fn begin_clipped(&mut self) {
+        self.saved = Some(GuStateSnapshot::capture());
+        unsafe {
+            sceGuEnable(GuState::StencilTest);
+            sceGuStencilFunc(StencilFunc::Equal, self.value as i32, 0xff);
+            sceGuStencilOp(
+                StencilOperation::Keep,
+                StencilOperation::Keep,
+                StencilOperation::Keep,
+            );
+        }
+    }
+
+    /// Stop clipping and restore the GU boolean state saved by
+    /// [`begin_clipped()`](Self::begin_clipped).
+    ///
+    /// # Safety
+    ///
+    /// Must be called within an active GU display list.
+    pub unsafe fn end_clipped(&mut self) {
+        self.restore();
+    }
+
+    fn restore(&mut self) {
+        if let Some(saved) = self.saved.take() {
+            saved.restore();
+        }
+    }
+}
+
+impl Default for StencilMask {
+    fn default() -> Self {
+        Self::new()
+    }
+}
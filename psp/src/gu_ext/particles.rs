@@ -0,0 +1,364 @@
+//! Pooled 2D particle systems.
+//!
+//! A [`ParticleSystem`] owns a fixed pool of `N` particles stored inline,
+//! so spawning, simulating and killing particles never allocates. Each
+//! frame, [`update`](ParticleSystem::update) ages and moves the live
+//! particles and spawns new ones at the emitter's rate, and
+//! [`draw`](ParticleSystem::draw) queues one sprite per live particle into
+//! a [`SpriteBatch`](super::SpriteBatch), interpolating color and size over
+//! each particle's lifetime.
+//!
+//! Randomness comes from a seeded [`crate::rand::Rng`], so a system with
+//! the same seed, config and time steps always produces the same particles.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use psp::gu_ext::{EmitterConfig, ParticleSystem, SpriteBatch, TexRegion};
+//!
+//! let mut sparks = ParticleSystem::<256>::new(
+//!     EmitterConfig {
+//!         spawn_rate: 120.0,
+//!         lifetime: (0.4, 0.9),
+//!         velocity_min: (-60.0, -160.0),
+//!         velocity_max: (60.0, -80.0),
+//!         gravity: (0.0, 300.0),
+//!         start_color: 0xFF40_C0FF,
+//!         end_color: 0x0000_40FF,
+//!         start_size: 6.0,
+//!         end_size: 1.0,
+//!     },
+//!     0x1234,
+//! );
+//! sparks.set_position(240.0, 200.0);
+//!
+//! let dot = TexRegion::full(8.0, 8.0);
+//! let mut batch = SpriteBatch::new(256);
+//! loop {
+//!     sparks.update(1.0 / 60.0);
+//!     // Inside the frame's display list: write straight into it.
+//!     unsafe { batch.reserve(sparks.live_count()) };
+//!     sparks.draw(&mut batch, &dot);
+//!     unsafe { batch.flush() };
+//! }
+//! ```
+
+use crate::rand::Rng;
+use crate::simd::{Vec4, vec4_lerp};
+
+/// How a [`ParticleSystem`] spawns and animates its particles.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EmitterConfig {
+    /// Particles spawned per second while emitting.
+    pub spawn_rate: f32,
+    /// Range `(min, max)` of particle lifetimes in seconds.
+    pub lifetime: (f32, f32),
+    /// Lower corner of the initial velocity range, in pixels per second.
+    pub velocity_min: (f32, f32),
+    /// Upper corner of the initial velocity range, in pixels per second.
+    pub velocity_max: (f32, f32),
+    /// Constant acceleration applied to every particle, in pixels per
+    /// second squared. `(0.0, 300.0)` pulls particles down the screen.
+    pub gravity: (f32, f32),
+    /// Color at birth (ABGR, `0xAABBGGRR`).
+    pub start_color: u32,
+    /// Color at death (ABGR). Fading alpha to zero makes particles vanish
+    /// smoothly.
+    pub end_color: u32,
+    /// Sprite width and height at birth, in pixels.
+    pub start_size: f32,
+    /// Sprite width and height at death, in pixels.
+    pub end_size: f32,
+}
+
+impl Default for EmitterConfig {
+    /// 30 white particles per second, drifting in random directions and
+    /// fading out over one second.
+    fn default() -> Self {
+        Self {
+            spawn_rate: 30.0,
+            lifetime: (1.0, 1.0),
+            velocity_min: (-20.0, -20.0),
+            velocity_max: (20.0, 20.0),
+            gravity: (0.0, 0.0),
+            start_color: 0xFFFF_FFFF,
+            end_color: 0x00FF_FFFF,
+            start_size: 4.0,
+            end_size: 4.0,
+        }
+    }
+}
+
+/// A single live particle.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Particle {
+    /// Center position in pixels.
+    pub x: f32,
+    pub y: f32,
+    /// Velocity in pixels per second.
+    pub vx: f32,
+    pub vy: f32,
+    /// Seconds since the particle spawned.
+    pub age: f32,
+    /// Seconds the particle lives for.
+    pub lifetime: f32,
+}
+
+impl Particle {
+    const DEAD: Self = Self {
+        x: 0.0,
+        y: 0.0,
+        vx: 0.0,
+        vy: 0.0,
+        age: 0.0,
+        lifetime: 0.0,
+    };
+
+    /// How far through its life the particle is, from 0.0 to 1.0.
+    pub fn progress(&self) -> f32 {
+        if self.lifetime > 0.0 {
+            (self.age / self.lifetime).min(1.0)
+        } else {
+            1.0
+        }
+    }
+}
+
+/// Order in which [`ParticleSystem::draw`] queues particles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DrawOrder {
+    /// Oldest particles first, so newer ones are drawn on top.
+    #[default]
+    OldestFirst,
+    /// Newest particles first, so older ones are drawn on top.
+    NewestFirst,
+}
+
+/// A particle emitter with a fixed pool of `N` particles.
+///
+/// Live particles are kept packed at the front of the pool in spawn order,
+/// which is also age order, so drawing sorted by age costs nothing extra.
+/// When the pool is full, new spawns are dropped until particles die.
+pub struct ParticleSystem<const N: usize> {
+    config: EmitterConfig,
+    /// Config colors unpacked for interpolation.
+    start_color: Vec4,
+    end_color: Vec4,
+    particles: [Particle; N],
+    live: usize,
+    position: (f32, f32),
+    emitting: bool,
+    /// Fractional particles owed by the spawn rate.
+    spawn_debt: f32,
+    draw_order: DrawOrder,
+    rng: Rng,
+}
+
+impl<const N: usize> ParticleSystem<N> {
+    /// Create an emitting system at the origin with no live particles.
+    ///
+    /// `seed` seeds the system's random number generator.
+    pub fn new(config: EmitterConfig, seed: u64) -> Self {
+        Self {
+            config,
+            start_color: unpack_color(config.start_color),
+            end_color: unpack_color(config.end_color),
+            particles: [Particle::DEAD; N],
+            live: 0,
+            position: (0.0, 0.0),
+            emitting: true,
+            spawn_debt: 0.0,
+            draw_order: DrawOrder::OldestFirst,
+            rng: Rng::new(seed),
+        }
+    }
+
+    /// The current emitter config.
+    pub fn config(&self) -> &EmitterConfig {
+        &self.config
+    }
+
+    /// Replace the emitter config. Live particles keep their lifetime and
+    /// velocity but are drawn with the new colors and sizes.
+    pub fn set_config(&mut self, config: EmitterConfig) {
+        self.config = config;
+        self.start_color = unpack_color(config.start_color);
+        self.end_color = unpack_color(config.end_color);
+    }
+
+    /// Where new particles spawn.
+    pub fn position(&self) -> (f32, f32) {
+        self.position
+    }
+
+    /// Move the emitter. Live particles are unaffected.
+    pub fn set_position(&mut self, x: f32, y: f32) {
+        self.position = (x, y);
+    }
+
+    /// Whether [`update`](Self::update) spawns new particles.
+    pub fn is_emitting(&self) -> bool {
+        self.emitting
+    }
+
+    /// Start or stop spawning. Live particles keep animating either way.
+    pub fn set_emitting(&mut self, emitting: bool) {
+        self.emitting = emitting;
+        if !emitting {
+            self.spawn_debt = 0.0;
+        }
+    }
+
+    /// Set the order [`draw`](Self::draw) queues particles in.
+    pub fn set_draw_order(&mut self, order: DrawOrder) {
+        self.draw_order = order;
+    }
+
+    /// The live particles, oldest first.
+    pub fn particles(&self) -> &[Particle] {
+        &self.particles[..self.live]
+    }
+
+    /// Number of live particles.
+    pub fn live_count(&self) -> usize {
+        self.live
+    }
+
+    /// Size of the particle pool.
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Kill all live particles.
+    pub fn clear(&mut self) {
+        self.live = 0;
+        self.spawn_debt = 0.0;
+    }
+
+    /// Spawn up to `count` particles at once, regardless of the spawn rate
+    /// or whether the system is emitting. Useful for explosions.
+    ///
+    /// Returns how many were spawned, which is less than `count` if the
+    /// pool filled up.
+    pub fn burst(&mut self, count: usize) -> usize {
+        let count = count.min(N - self.live);
+        for _ in 0..count {
+            self.spawn();
+        }
+        count
+    }
+
+    /// Advance the simulation by `dt` seconds.
+    ///
+    /// Ages every live particle, removes the ones that outlived their
+    /// lifetime, moves the rest, then spawns new particles at the
+    /// emitter's position. Never allocates.
+    pub fn update(&mut self, dt: f32) {
+        let (gx, gy) = self.config.gravity;
+
+        // Compact survivors towards the front to keep spawn order.
+        let mut kept = 0;
+        for i in 0..self.live {
+            let mut p = self.particles[i];
+            p.age += dt;
+            if p.age >= p.lifetime {
+                continue;
+            }
+            p.vx += gx * dt;
+            p.vy += gy * dt;
+            p.x += p.vx * dt;
+            p.y += p.vy * dt;
+            self.particles[kept] = p;
+            kept += 1;
+        }
+        self.live = kept;
+
+        if !self.emitting {
+            return;
+        }
+        self.spawn_debt += self.config.spawn_rate * dt;
+        while self.spawn_debt >= 1.0 && self.live < N {
+            self.spawn();
+            self.spawn_debt -= 1.0;
+        }
+        // Don't bank spawns while the pool is full, or they'd all come
+        // out at once as soon as space frees up.
+        self.spawn_debt = self.spawn_debt.min(1.0);
+    }
+
+    fn spawn(&mut self) {
+        let c = &self.config;
+        let rng = &mut self.rng;
+        self.particles[self.live] = Particle {
+            x: self.position.0,
+            y: self.position.1,
+            vx: rng.range_f32(c.velocity_min.0, c.velocity_max.0),
+            vy: rng.range_f32(c.velocity_min.1, c.velocity_max.1),
+            age: 0.0,
+            lifetime: rng.range_f32(c.lifetime.0, c.lifetime.1),
+        };
+        self.live += 1;
+    }
+
+    /// Color of `p` at its current age (ABGR).
+    pub fn color_of(&self, p: &Particle) -> u32 {
+        pack_color(&vec4_lerp(&self.start_color, &self.end_color, p.progress()))
+    }
+
+    /// Sprite size of `p` at its current age, in pixels.
+    pub fn size_of(&self, p: &Particle) -> f32 {
+        let c = &self.config;
+        c.start_size + (c.end_size - c.start_size) * p.progress()
+    }
+
+    /// Queue one sprite per live particle into `batch`, centered on the
+    /// particle and textured with `region`.
+    ///
+    /// Particles are queued in the configured [`DrawOrder`].
+    #[cfg(not(feature = "stub-only"))]
+    pub fn draw(&self, batch: &mut super::SpriteBatch, region: &super::TexRegion) {
+        match self.draw_order {
+            DrawOrder::OldestFirst => {
+                for p in self.particles() {
+                    self.draw_particle(batch, region, p);
+                }
+            },
+            DrawOrder::NewestFirst => {
+                for p in self.particles().iter().rev() {
+                    self.draw_particle(batch, region, p);
+                }
+            },
+        }
+    }
+
+    #[cfg(not(feature = "stub-only"))]
+    fn draw_particle(
+        &self,
+        batch: &mut super::SpriteBatch,
+        region: &super::TexRegion,
+        p: &Particle,
+    ) {
+        let size = self.size_of(p);
+        let (x, y) = (p.x - size * 0.5, p.y - size * 0.5);
+        let color = self.color_of(p);
+        batch.draw_rect(
+            x, y, size, size, region.u0, region.v0, region.u1, region.v1, color,
+        );
+    }
+}
+
+/// Split an ABGR color into `(r, g, b, a)` components in `0.0..=255.0`.
+fn unpack_color(abgr: u32) -> Vec4 {
+    Vec4::new(
+        (abgr & 0xFF) as f32,
+        ((abgr >> 8) & 0xFF) as f32,
+        ((abgr >> 16) & 0xFF) as f32,
+        (abgr >> 24) as f32,
+    )
+}
+
+/// Inverse of [`unpack_color`], rounding and clamping each component.
+fn pack_color(c: &Vec4) -> u32 {
+    let channel = |v: f32| (v + 0.5).clamp(0.0, 255.0) as u32;
+    channel(c.x()) | channel(c.y()) << 8 | channel(c.z()) << 16 | channel(c.w()) << 24
+}
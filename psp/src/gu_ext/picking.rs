@@ -0,0 +1,77 @@
+//! Screen-to-world picking.
+//!
+//! Clicking on a 3D scene means finding what lies under a screen
+//! coordinate. [`unproject`] maps a point on the 480x272 screen back
+//! through the view and projection matrices to world space, and
+//! [`screen_ray`] turns it into a ray from the near plane into the scene,
+//! ready to intersect with bounding spheres or boxes.
+//!
+//! The matrices are the ones passed to `sceGumMatrixMode(MatrixMode::View)`
+//! and `MatrixMode::Projection`, for example from `sceGumLookAt` and
+//! `sceGumPerspective` read back with `sceGumStoreMatrix`, in the
+//! column-major [`Mat4`] layout.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use psp::gu_ext::screen_ray;
+//! use psp::simd::{vec4_dot, vec4_sub};
+//!
+//! let (origin, dir) = screen_ray(cursor_x, cursor_y, &view, &proj);
+//! // Distance along the ray to its closest approach to the sphere.
+//! let t = vec4_dot(&vec4_sub(&center, &origin), &dir);
+//! ```
+
+use crate::simd::{Mat4, Vec4, mat4_inverse, mat4_multiply, mat4_transform, vec4_normalize};
+
+const WIDTH: f32 = crate::SCREEN_WIDTH as f32;
+const HEIGHT: f32 = crate::SCREEN_HEIGHT as f32;
+
+/// The world-space point under screen pixel (`screen_x`, `screen_y`) at
+/// `depth`, from 0.0 on the near plane to 1.0 on the far plane.
+///
+/// Screen coordinates run from (0, 0) at the top left to (480, 272) at
+/// the bottom right, like the 2D helpers. The result has `w` = 1. If
+/// `proj * view` can't be inverted, every component is NaN.
+pub fn unproject(screen_x: f32, screen_y: f32, depth: f32, view: &Mat4, proj: &Mat4) -> Vec4 {
+    match mat4_inverse(&mat4_multiply(proj, view)) {
+        Some(inverse) => unproject_with(&inverse, screen_x, screen_y, depth),
+        None => Vec4([f32::NAN; 4]),
+    }
+}
+
+/// The ray through screen pixel (`screen_x`, `screen_y`), as an origin on
+/// the near plane (`w` = 1) and a unit direction towards the far plane
+/// (`w` = 0).
+///
+/// Points along the ray are `origin + dir * t` for `t` >= 0. With a
+/// perspective projection the rays spread out from the camera; with an
+/// orthographic one they are parallel. Like [`unproject`], both are NaN
+/// if `proj * view` can't be inverted.
+pub fn screen_ray(screen_x: f32, screen_y: f32, view: &Mat4, proj: &Mat4) -> (Vec4, Vec4) {
+    let Some(inverse) = mat4_inverse(&mat4_multiply(proj, view)) else {
+        return (Vec4([f32::NAN; 4]), Vec4([f32::NAN; 4]));
+    };
+    let near = unproject_with(&inverse, screen_x, screen_y, 0.0);
+    let far = unproject_with(&inverse, screen_x, screen_y, 1.0);
+    let dir = Vec4::new(
+        far.x() - near.x(),
+        far.y() - near.y(),
+        far.z() - near.z(),
+        0.0,
+    );
+    (near, vec4_normalize(&dir))
+}
+
+/// [`unproject`] with `inverse` = `(proj * view)^-1` already computed.
+fn unproject_with(inverse: &Mat4, screen_x: f32, screen_y: f32, depth: f32) -> Vec4 {
+    let ndc = Vec4::new(
+        screen_x / WIDTH * 2.0 - 1.0,
+        1.0 - screen_y / HEIGHT * 2.0,
+        depth * 2.0 - 1.0,
+        1.0,
+    );
+    let world = mat4_transform(inverse, &ndc);
+    let w = world.w();
+    Vec4::new(world.x() / w, world.y() / w, world.z() / w, 1.0)
+}
@@ -0,0 +1,344 @@
+//! Hardware vertex skinning and morphing.
+//!
+//! The GE can blend each vertex between up to eight bone matrices by
+//! per-vertex weights, so skeletal animation only needs the bone poses
+//! computed on the CPU (or VFPU, with [`crate::simd`]) each frame. Upload
+//! them with [`set_bone_matrices`], describe the weights in the vertex
+//! layout with [`skinned_vertex_type`], and draw as usual.
+//!
+//! Morphing blends between up to eight copies of each vertex instead; set
+//! the blend factors with [`set_morph_weights`] and the copy count with
+//! [`morphed_vertex_type`].
+//!
+//! The vertex structs here encode the weighted layouts as
+//! [`VertexFormat`]s: [`SkinnedVertex`], [`SkinnedColoredVertex`] and
+//! [`SkinnedNormalVertex`] put `N` float weights before the other
+//! components, and [`Morphed`] repeats any vertex format once per morph
+//! target. [`SkinnedMesh`] keeps the vertices together with the primitive
+//! and uploads the bone palette on every draw.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use psp::gu_ext::{WeightFormat, set_bone_matrices, skinned_vertex_type};
+//! use psp::simd::{Vec4, mat4_from_quat_translation};
+//! use psp::sys::VertexType;
+//!
+//! #[repr(C)]
+//! #[derive(Clone, Copy)]
+//! struct Vertex {
+//!     weights: [f32; 2],
+//!     x: f32,
+//!     y: f32,
+//!     z: f32,
+//! }
+//!
+//! const VTYPE: VertexType =
+//!     skinned_vertex_type(VertexType::VERTEX_32BITF, 2, WeightFormat::F32);
+//!
+//! let bones = [
+//!     mat4_from_quat_translation(&root_rotation, &Vec4::ZERO),
+//!     mat4_from_quat_translation(&elbow_rotation, &Vec4([0.0, 1.0, 0.0, 1.0])),
+//! ];
+//! unsafe {
+//!     set_bone_matrices(&bones)?;
+//!     sceGuDrawArray(GuPrimitive::Triangles, VTYPE, count, null(), verts);
+//! }
+//! ```
+
+use super::vertex::VertexFormat;
+use crate::simd::Mat4;
+use crate::sys::{ScePspFMatrix4, VertexType, sceGuBoneMatrix, sceGuMorphWeight};
+
+/// Number of bone matrices and morph weights the GE holds.
+pub const MAX_BONES: usize = 8;
+
+/// Error from uploading bone matrices or morph weights.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkinningError {
+    /// The bone index is not below [`MAX_BONES`].
+    BoneIndex(usize),
+    /// More matrices or weights than the GE's [`MAX_BONES`] slots.
+    TooMany(usize),
+    /// A [`SkinnedMesh`] was drawn with fewer bones than its vertices
+    /// have weights.
+    TooFewBones {
+        /// Bone matrices passed.
+        bones: usize,
+        /// Weights per vertex.
+        weights: usize,
+    },
+}
+
+impl core::fmt::Display for SkinningError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::BoneIndex(index) => write!(f, "bone index {index} out of range"),
+            Self::TooMany(count) => write!(f, "{count} bones or weights, the GE holds {MAX_BONES}"),
+            Self::TooFewBones { bones, weights } => {
+                write!(f, "{bones} bones for vertices with {weights} weights")
+            },
+        }
+    }
+}
+
+/// Storage format of per-vertex skinning weights.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WeightFormat {
+    /// Unsigned byte, where 128 is a weight of 1.0.
+    U8,
+    /// Unsigned 16-bit, where 32768 is a weight of 1.0.
+    U16,
+    /// 32-bit float.
+    F32,
+}
+
+/// Add `count` skinning weights of `format` to the vertex layout `base`.
+///
+/// The weights come first in each vertex, before texture coordinates, and
+/// weight `i` scales the vertex as transformed by bone matrix `i`.
+///
+/// # Panics
+///
+/// Panics if `count` is not between 1 and [`MAX_BONES`].
+pub const fn skinned_vertex_type(
+    base: VertexType,
+    count: usize,
+    format: WeightFormat,
+) -> VertexType {
+    assert!(
+        count >= 1 && count <= MAX_BONES,
+        "weight count out of range"
+    );
+    let format = match format {
+        WeightFormat::U8 => VertexType::WEIGHT_8BIT,
+        WeightFormat::U16 => VertexType::WEIGHT_16BIT,
+        WeightFormat::F32 => VertexType::WEIGHT_32BITF,
+    };
+    VertexType::from_bits_retain(base.bits() | format.bits() | ((count as i32 - 1) << 14))
+}
+
+/// Repeat the vertex layout `base` for `count` morph targets.
+///
+/// # Panics
+///
+/// Panics if `count` is not between 1 and [`MAX_BONES`].
+pub const fn morphed_vertex_type(base: VertexType, count: usize) -> VertexType {
+    assert!(count >= 1 && count <= MAX_BONES, "morph count out of range");
+    VertexType::from_bits_retain(base.bits() | ((count as i32 - 1) << 18))
+}
+
+/// Upload `matrix` as bone matrix `index`.
+///
+/// The GE stores bones as 4x3 matrices, so the bottom row of `matrix`
+/// (the projective part) is ignored.
+///
+/// Fails with [`SkinningError::BoneIndex`] if `index` is not below
+/// [`MAX_BONES`].
+///
+/// # Safety
+///
+/// Must be called within an active GU display list.
+pub unsafe fn set_bone_matrix(index: u8, matrix: &Mat4) -> Result<(), SkinningError> {
+    if index as usize >= MAX_BONES {
+        return Err(SkinningError::BoneIndex(index as usize));
+    }
+    let matrix = ScePspFMatrix4::from(*matrix);
+    unsafe { sceGuBoneMatrix(index as u32, &matrix) };
+    Ok(())
+}
+
+/// Upload `matrices` as bone matrices 0, 1, 2 and so on.
+///
+/// Fails with [`SkinningError::TooMany`], uploading nothing, if there are
+/// more than [`MAX_BONES`] matrices.
+///
+/// # Safety
+///
+/// Must be called within an active GU display list.
+pub unsafe fn set_bone_matrices(matrices: &[Mat4]) -> Result<(), SkinningError> {
+    if matrices.len() > MAX_BONES {
+        return Err(SkinningError::TooMany(matrices.len()));
+    }
+    for (index, matrix) in matrices.iter().enumerate() {
+        unsafe { set_bone_matrix(index as u8, matrix)? };
+    }
+    Ok(())
+}
+
+/// Set the blend factors of morph targets 0, 1, 2 and so on.
+///
+/// Fails with [`SkinningError::TooMany`], setting nothing, if there are
+/// more than [`MAX_BONES`] weights.
+///
+/// # Safety
+///
+/// Must be called within an active GU display list.
+pub unsafe fn set_morph_weights(weights: &[f32]) -> Result<(), SkinningError> {
+    if weights.len() > MAX_BONES {
+        return Err(SkinningError::TooMany(weights.len()));
+    }
+    for (index, &weight) in weights.iter().enumerate() {
+        unsafe { sceGuMorphWeight(index as i32, weight) };
+    }
+    Ok(())
+}
+
+/// Number of skinning weights per vertex in `vtype`, or 0 if it has none.
+pub const fn weight_count(vtype: VertexType) -> usize {
+    let bits = vtype.bits();
+    if (bits >> 9) & 3 == 0 {
+        0
+    } else {
+        ((bits >> 14) & 7) as usize + 1
+    }
+}
+
+const fn float_3d(components: VertexType) -> VertexType {
+    VertexType::from_bits_retain(
+        components.bits() | VertexType::VERTEX_32BITF.bits() | VertexType::TRANSFORM_3D.bits(),
+    )
+}
+
+/// Skinned 3D vertex: `N` float weights + position.
+///
+/// `weights[i]` scales the position as transformed by bone matrix `i`;
+/// the weights of a vertex normally sum to 1.0.
+#[repr(C, align(4))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SkinnedVertex<const N: usize> {
+    pub weights: [f32; N],
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl<const N: usize> VertexFormat for SkinnedVertex<N> {
+    const VERTEX_TYPE: VertexType =
+        skinned_vertex_type(float_3d(VertexType::empty()), N, WeightFormat::F32);
+}
+
+/// Skinned 3D vertex: `N` float weights + color (ABGR) + position.
+#[repr(C, align(4))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SkinnedColoredVertex<const N: usize> {
+    pub weights: [f32; N],
+    pub color: u32,
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl<const N: usize> VertexFormat for SkinnedColoredVertex<N> {
+    const VERTEX_TYPE: VertexType =
+        skinned_vertex_type(float_3d(VertexType::COLOR_8888), N, WeightFormat::F32);
+}
+
+/// Skinned 3D vertex: `N` float weights + normal + position, for lit
+/// meshes. The GE skins the normal with the same bone matrices.
+#[repr(C, align(4))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SkinnedNormalVertex<const N: usize> {
+    pub weights: [f32; N],
+    pub nx: f32,
+    pub ny: f32,
+    pub nz: f32,
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl<const N: usize> VertexFormat for SkinnedNormalVertex<N> {
+    const VERTEX_TYPE: VertexType =
+        skinned_vertex_type(float_3d(VertexType::NORMAL_32BITF), N, WeightFormat::F32);
+}
+
+/// `N` morph targets of a vertex, blended by [`set_morph_weights`].
+///
+/// The GE reads the targets back to back, so this is simply an array of
+/// the base format.
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Morphed<V: VertexFormat, const N: usize>(pub [V; N]);
+
+impl<V: VertexFormat, const N: usize> VertexFormat for Morphed<V, N> {
+    const VERTEX_TYPE: VertexType = morphed_vertex_type(V::VERTEX_TYPE, N);
+}
+
+/// A skinned vertex buffer drawn with its bone palette.
+///
+/// # Example
+///
+/// ```ignore
+/// use psp::gu_ext::{SkinnedColoredVertex, SkinnedMesh};
+/// use psp::sys::GuPrimitive;
+///
+/// let mut arm = SkinnedMesh::<SkinnedColoredVertex<2>>::new(GuPrimitive::TriangleStrip);
+/// arm.vertices_mut().extend_from_slice(&ARM_VERTICES);
+/// loop {
+///     // ... sceGuStart, camera setup ...
+///     unsafe { arm.draw(&[shoulder, elbow])? };
+/// }
+/// ```
+#[cfg(not(feature = "stub-only"))]
+pub struct SkinnedMesh<V: VertexFormat> {
+    vertices: super::VertexBuffer<V>,
+    primitive: crate::sys::GuPrimitive,
+}
+
+#[cfg(not(feature = "stub-only"))]
+impl<V: VertexFormat> SkinnedMesh<V> {
+    /// Create an empty mesh drawn as `primitive`.
+    ///
+    /// Fails to compile if `V` has no skinning weights.
+    pub fn new(primitive: crate::sys::GuPrimitive) -> Self {
+        const {
+            assert!(
+                weight_count(V::VERTEX_TYPE) > 0,
+                "vertex format has no weights"
+            )
+        };
+        Self {
+            vertices: super::VertexBuffer::new(),
+            primitive,
+        }
+    }
+
+    /// The mesh's vertices.
+    pub fn vertices(&self) -> &super::VertexBuffer<V> {
+        &self.vertices
+    }
+
+    /// Mutable access to the mesh's vertices.
+    pub fn vertices_mut(&mut self) -> &mut super::VertexBuffer<V> {
+        &mut self.vertices
+    }
+
+    /// Upload `bones` as bone matrices 0, 1, 2 and so on, then draw the
+    /// mesh.
+    ///
+    /// The bone matrices are part of the GE state and stay set after the
+    /// draw.
+    ///
+    /// Fails without drawing if there are fewer bones than weights per
+    /// vertex ([`SkinningError::TooFewBones`]) or more than [`MAX_BONES`]
+    /// ([`SkinningError::TooMany`]).
+    ///
+    /// # Safety
+    ///
+    /// Must be called between `sceGuStart` and `sceGuFinish`.
+    pub unsafe fn draw(&self, bones: &[Mat4]) -> Result<(), SkinningError> {
+        let weights = weight_count(V::VERTEX_TYPE);
+        if bones.len() < weights {
+            return Err(SkinningError::TooFewBones {
+                bones: bones.len(),
+                weights,
+            });
+        }
+        unsafe {
+            set_bone_matrices(bones)?;
+            self.vertices.draw(self.primitive);
+        }
+        Ok(())
+    }
+}
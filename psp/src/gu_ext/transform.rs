@@ -0,0 +1,149 @@
+//! Matrix stack for hierarchical transforms.
+//!
+//! A scene graph draws each child relative to its parent: a wheel in car
+//! space, the car in world space. [`TransformStack`] keeps the composed
+//! matrices so each node only supplies its local transform. Pushing
+//! multiplies the local matrix onto the current one with
+//! [`mat4_multiply`], popping returns to the parent, and
+//! [`apply`](TransformStack::apply) uploads the current matrix as the GU
+//! model matrix before a draw.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use psp::gu_ext::TransformStack;
+//!
+//! let mut stack = TransformStack::new();
+//! stack.push(&car_transform);
+//! unsafe { stack.apply() };
+//! body.draw(GuPrimitive::Triangles);
+//! for wheel in &wheel_transforms {
+//!     stack.with(wheel, |stack| unsafe {
+//!         stack.apply();
+//!         wheel_mesh.draw(GuPrimitive::Triangles);
+//!     });
+//! }
+//! stack.pop();
+//! ```
+
+use crate::simd::{Mat4, mat4_multiply};
+use crate::sys::{MatrixMode, ScePspFMatrix4, sceGuSetMatrix};
+
+/// Levels a [`TransformStack`] created with [`TransformStack::new`] can
+/// push.
+pub const DEFAULT_TRANSFORM_DEPTH: usize = 16;
+
+/// A stack of composed model matrices, up to `N` levels above the root.
+///
+/// The root is the identity matrix and can't be popped. The GU is only
+/// touched by [`apply`](Self::apply), so the stack can be built up
+/// outside a display list, and it works alongside `sceGum*` as long as
+/// `sceGumMatrixMode(MatrixMode::Model)` isn't used for the same draws:
+/// `sceGumUpdateMatrix` would upload the `sceGum` model matrix over it.
+#[derive(Debug, Clone)]
+pub struct TransformStack<const N: usize = DEFAULT_TRANSFORM_DEPTH> {
+    /// `matrices[i]` is the composed matrix at depth `i + 1`.
+    matrices: [Mat4; N],
+    root: Mat4,
+    depth: usize,
+}
+
+impl TransformStack {
+    /// An empty stack for [`DEFAULT_TRANSFORM_DEPTH`] levels.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<const N: usize> Default for TransformStack<N> {
+    fn default() -> Self {
+        Self {
+            matrices: [Mat4::IDENTITY; N],
+            root: Mat4::IDENTITY,
+            depth: 0,
+        }
+    }
+}
+
+impl<const N: usize> TransformStack<N> {
+    /// Make `current() * local` the current matrix.
+    ///
+    /// `local` is the child's transform in its parent's space, so points
+    /// are transformed by `local` first and then by the parents.
+    ///
+    /// Returns `false` and leaves the stack unchanged if `N` levels are
+    /// already pushed.
+    pub fn push(&mut self, local: &Mat4) -> bool {
+        if self.depth == N {
+            return false;
+        }
+        let composed = mat4_multiply(self.current(), local);
+        self.matrices[self.depth] = composed;
+        self.depth += 1;
+        true
+    }
+
+    /// Return to the parent's matrix, returning the popped one.
+    ///
+    /// Returns `None` and leaves the stack unchanged at the root.
+    pub fn pop(&mut self) -> Option<Mat4> {
+        if self.depth == 0 {
+            return None;
+        }
+        self.depth -= 1;
+        Some(self.matrices[self.depth])
+    }
+
+    /// Push `local`, run `f`, then pop.
+    ///
+    /// Pushes done inside `f` and not popped are popped too, so the stack
+    /// is always back at the caller's level afterwards.
+    ///
+    /// Returns `None` without running `f` if `N` levels are already
+    /// pushed.
+    pub fn with<R>(&mut self, local: &Mat4, f: impl FnOnce(&mut Self) -> R) -> Option<R> {
+        let depth = self.depth;
+        if !self.push(local) {
+            return None;
+        }
+        let result = f(self);
+        self.depth = depth;
+        Some(result)
+    }
+
+    /// The composed matrix at the top of the stack.
+    pub fn current(&self) -> &Mat4 {
+        match self.depth {
+            0 => &self.root,
+            depth => &self.matrices[depth - 1],
+        }
+    }
+
+    /// Number of matrices pushed above the root.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Maximum number of levels, `N`.
+    pub fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Pop everything, back to the identity root.
+    pub fn reset(&mut self) {
+        self.depth = 0;
+    }
+
+    /// Upload [`current`](Self::current) as the GU model matrix.
+    ///
+    /// Call this before each draw whose transform differs from the last
+    /// one applied.
+    ///
+    /// # Safety
+    ///
+    /// Must be called within an active GU display list.
+    pub unsafe fn apply(&self) {
+        let matrix = ScePspFMatrix4::from(*self.current());
+        unsafe { sceGuSetMatrix(MatrixMode::Model, &matrix) };
+    }
+}
@@ -0,0 +1,226 @@
+//! Full-screen scene transitions: fades and wipes.
+//!
+//! A [`Transition`] runs for a fixed number of frames. Call
+//! [`update`](Transition::update) once per frame and
+//! [`draw`](Transition::draw) at the end of the frame, after the scene, to
+//! cover the screen with a solid color by the transition's current amount.
+//! Progress is eased with [`ease_in_out_quad`].
+//!
+//! A scene change is usually an "out" transition that ends fully covered,
+//! a swap once [`is_finished`](Transition::is_finished) returns `true`,
+//! and an "in" transition that uncovers the new scene. An out transition
+//! keeps the screen covered after it finishes, so the swap never flashes
+//! the old scene.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use psp::gu_ext::{Transition, WipeDirection};
+//!
+//! let mut fade = Transition::fade_out(0xFF00_0000, 30);
+//! loop {
+//!     fade.update();
+//!     // ... draw the scene ...
+//!     unsafe { fade.draw() };
+//!     if fade.is_finished() && fade.kind().covers() {
+//!         scene = next_scene;
+//!         fade = Transition::wipe_in(WipeDirection::LeftToRight, 0xFF00_0000, 30);
+//!     }
+//! }
+//! ```
+
+use super::{BlendMode, GuStateSnapshot, draw_rect_filled, set_blend_mode};
+use crate::simd::ease_in_out_quad;
+use crate::sys::{GuState, sceGuDisable};
+
+const WIDTH: f32 = crate::SCREEN_WIDTH as f32;
+const HEIGHT: f32 = crate::SCREEN_HEIGHT as f32;
+
+/// Edge a wipe starts from and the direction it sweeps in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WipeDirection {
+    LeftToRight,
+    RightToLeft,
+    TopToBottom,
+    BottomToTop,
+}
+
+/// The effect a [`Transition`] draws.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransitionKind {
+    /// Fade from the scene to a solid color.
+    FadeOut,
+    /// Fade from a solid color to the scene.
+    FadeIn,
+    /// Sweep a solid color across the scene until it covers the screen.
+    WipeOut(WipeDirection),
+    /// Sweep a solid color off the screen, uncovering the scene. The
+    /// covered area recedes in the same direction a [`WipeOut`] advances.
+    ///
+    /// [`WipeOut`]: Self::WipeOut
+    WipeIn(WipeDirection),
+}
+
+impl TransitionKind {
+    /// Whether the transition ends with the screen fully covered.
+    pub fn covers(self) -> bool {
+        matches!(self, Self::FadeOut | Self::WipeOut(_))
+    }
+}
+
+/// Result of [`Transition::update`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TransitionState {
+    /// Still running, with eased progress from 0.0 to 1.0.
+    Running(f32),
+    /// All frames have elapsed.
+    Finished,
+}
+
+/// A fade or wipe running over a fixed number of frames.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transition {
+    kind: TransitionKind,
+    /// Color (ABGR) of the covering; its alpha is ignored.
+    color: u32,
+    duration: u32,
+    frame: u32,
+}
+
+impl Transition {
+    /// A transition of `kind` to or from `color` (ABGR) lasting
+    /// `duration_frames` frames. A duration of 0 finishes immediately.
+    pub fn new(kind: TransitionKind, color: u32, duration_frames: u32) -> Self {
+        Self {
+            kind,
+            color,
+            duration: duration_frames,
+            frame: 0,
+        }
+    }
+
+    /// Fade the scene out to `color`.
+    pub fn fade_out(color: u32, duration_frames: u32) -> Self {
+        Self::new(TransitionKind::FadeOut, color, duration_frames)
+    }
+
+    /// Fade the scene in from `color`.
+    pub fn fade_in(color: u32, duration_frames: u32) -> Self {
+        Self::new(TransitionKind::FadeIn, color, duration_frames)
+    }
+
+    /// Cover the scene with `color`, sweeping in `direction`.
+    pub fn wipe_out(direction: WipeDirection, color: u32, duration_frames: u32) -> Self {
+        Self::new(TransitionKind::WipeOut(direction), color, duration_frames)
+    }
+
+    /// Uncover the scene from `color`, sweeping in `direction`.
+    pub fn wipe_in(direction: WipeDirection, color: u32, duration_frames: u32) -> Self {
+        Self::new(TransitionKind::WipeIn(direction), color, duration_frames)
+    }
+
+    /// The effect this transition draws.
+    pub fn kind(&self) -> TransitionKind {
+        self.kind
+    }
+
+    /// Advance by one frame.
+    pub fn update(&mut self) -> TransitionState {
+        if self.frame < self.duration {
+            self.frame += 1;
+        }
+        self.state()
+    }
+
+    /// The current state without advancing.
+    pub fn state(&self) -> TransitionState {
+        if self.is_finished() {
+            TransitionState::Finished
+        } else {
+            TransitionState::Running(self.progress())
+        }
+    }
+
+    /// Whether all frames have elapsed.
+    pub fn is_finished(&self) -> bool {
+        self.frame >= self.duration
+    }
+
+    /// Eased progress from 0.0 (start) to 1.0 (finished).
+    pub fn progress(&self) -> f32 {
+        if self.duration == 0 {
+            return 1.0;
+        }
+        ease_in_out_quad(self.frame as f32 / self.duration as f32)
+    }
+
+    /// How much of the screen is covered, from 0.0 to 1.0: the fade's
+    /// opacity, or the fraction of the screen a wipe has covered.
+    pub fn coverage(&self) -> f32 {
+        if self.kind.covers() {
+            self.progress()
+        } else {
+            1.0 - self.progress()
+        }
+    }
+
+    /// Draw the transition over everything drawn so far this frame.
+    ///
+    /// Draws in screen space with standard alpha blending, and with depth
+    /// and alpha testing, fog and lighting off so none of them can keep
+    /// the cover from reaching every pixel. The enabled/disabled GU
+    /// states are restored afterwards.
+    ///
+    /// The blend function isn't: the GU can't read it back, so it is left
+    /// as `src * alpha + dst * (1 - alpha)`, the one
+    /// [`setup_2d`](super::setup_2d) sets. Set yours again before drawing
+    /// anything after the transition that blends differently.
+    ///
+    /// # Safety
+    ///
+    /// Must be called within an active GU display list.
+    pub unsafe fn draw(&self) {
+        let coverage = self.coverage();
+        if coverage <= 0.0 {
+            return;
+        }
+        let opaque = (self.color & 0x00FF_FFFF) | 0xFF00_0000;
+
+        let snapshot = GuStateSnapshot::capture();
+        unsafe {
+            sceGuDisable(GuState::DepthTest);
+            sceGuDisable(GuState::AlphaTest);
+            sceGuDisable(GuState::Fog);
+            sceGuDisable(GuState::Lighting);
+            set_blend_mode(BlendMode::AlphaBlend);
+
+            match self.kind {
+                TransitionKind::FadeOut | TransitionKind::FadeIn => {
+                    let alpha = (coverage * 255.0 + 0.5) as u32;
+                    let color = (self.color & 0x00FF_FFFF) | alpha.min(255) << 24;
+                    draw_rect_filled(0.0, 0.0, WIDTH, HEIGHT, color);
+                },
+                TransitionKind::WipeOut(direction) => {
+                    let (x, y, w, h) = wipe_rect(direction, 0.0, coverage);
+                    draw_rect_filled(x, y, w, h, opaque);
+                },
+                TransitionKind::WipeIn(direction) => {
+                    let (x, y, w, h) = wipe_rect(direction, 1.0 - coverage, 1.0);
+                    draw_rect_filled(x, y, w, h, opaque);
+                },
+            }
+        }
+        snapshot.restore();
+    }
+}
+
+/// Screen rectangle between fractions `from` and `to` of the way along a
+/// wipe in `direction`.
+fn wipe_rect(direction: WipeDirection, from: f32, to: f32) -> (f32, f32, f32, f32) {
+    match direction {
+        WipeDirection::LeftToRight => (WIDTH * from, 0.0, WIDTH * (to - from), HEIGHT),
+        WipeDirection::RightToLeft => (WIDTH * (1.0 - to), 0.0, WIDTH * (to - from), HEIGHT),
+        WipeDirection::TopToBottom => (0.0, HEIGHT * from, WIDTH, HEIGHT * (to - from)),
+        WipeDirection::BottomToTop => (0.0, HEIGHT * (1.0 - to), WIDTH, HEIGHT * (to - from)),
+    }
+}
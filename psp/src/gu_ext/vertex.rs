@@ -0,0 +1,295 @@
+//! Typed vertex formats and vertex buffers.
+//!
+//! `sceGuDrawArray` takes an untyped pointer plus [`VertexType`] flags
+//! describing the layout, and the GE happily reads garbage when the two
+//! disagree. [`VertexFormat`] ties the flags to a vertex struct, and
+//! [`VertexBuffer`] only draws vertices whose struct size matches the
+//! stride the GE will compute from those flags, checked at compile time.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use psp::gu_ext::{ColoredVertex, VertexBuffer};
+//! use psp::sys::GuPrimitive;
+//!
+//! let mut tri = VertexBuffer::<ColoredVertex>::new();
+//! tri.push(ColoredVertex { color: 0xFF0000FF, x: -1.0, y: -1.0, z: 0.0 });
+//! tri.push(ColoredVertex { color: 0xFF00FF00, x: 1.0, y: -1.0, z: 0.0 });
+//! tri.push(ColoredVertex { color: 0xFFFF0000, x: 0.0, y: 1.0, z: 0.0 });
+//! unsafe { tri.draw(GuPrimitive::Triangles) };
+//! ```
+
+use super::{COLOR_VERTEX_TYPE, ColorVertex, SPRITE_VERTEX_TYPE, SpriteVertex};
+use crate::sys::VertexType;
+
+/// A vertex struct with a known GE layout.
+///
+/// Implementors must be `#[repr(C)]` with fields in GE order: weights,
+/// texture coordinates, color, normal, position. [`VertexBuffer`] rejects
+/// formats whose size doesn't match [`vertex_stride`]`(VERTEX_TYPE)`.
+pub trait VertexFormat: Copy {
+    /// Flags passed to `sceGuDrawArray` for this layout.
+    const VERTEX_TYPE: VertexType;
+}
+
+impl VertexFormat for SpriteVertex {
+    const VERTEX_TYPE: VertexType = SPRITE_VERTEX_TYPE;
+}
+
+impl VertexFormat for ColorVertex {
+    const VERTEX_TYPE: VertexType = COLOR_VERTEX_TYPE;
+}
+
+/// 3D vertex: texture coords + position.
+#[repr(C, align(4))]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TexturedVertex {
+    pub u: f32,
+    pub v: f32,
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl VertexFormat for TexturedVertex {
+    const VERTEX_TYPE: VertexType = VertexType::from_bits_truncate(
+        VertexType::TEXTURE_32BITF.bits()
+            | VertexType::VERTEX_32BITF.bits()
+            | VertexType::TRANSFORM_3D.bits(),
+    );
+}
+
+/// 3D vertex: color (ABGR) + position.
+#[repr(C, align(4))]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ColoredVertex {
+    pub color: u32,
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl VertexFormat for ColoredVertex {
+    const VERTEX_TYPE: VertexType = VertexType::from_bits_truncate(
+        VertexType::COLOR_8888.bits()
+            | VertexType::VERTEX_32BITF.bits()
+            | VertexType::TRANSFORM_3D.bits(),
+    );
+}
+
+/// 3D vertex: normal + position, for lit untextured geometry.
+#[repr(C, align(4))]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct NormalVertex {
+    pub nx: f32,
+    pub ny: f32,
+    pub nz: f32,
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl VertexFormat for NormalVertex {
+    const VERTEX_TYPE: VertexType = VertexType::from_bits_truncate(
+        VertexType::NORMAL_32BITF.bits()
+            | VertexType::VERTEX_32BITF.bits()
+            | VertexType::TRANSFORM_3D.bits(),
+    );
+}
+
+/// 3D vertex: texture coords + normal + position, for lit textured meshes.
+#[repr(C, align(4))]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TexturedNormalVertex {
+    pub u: f32,
+    pub v: f32,
+    pub nx: f32,
+    pub ny: f32,
+    pub nz: f32,
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl VertexFormat for TexturedNormalVertex {
+    const VERTEX_TYPE: VertexType = VertexType::from_bits_truncate(
+        VertexType::TEXTURE_32BITF.bits()
+            | VertexType::NORMAL_32BITF.bits()
+            | VertexType::VERTEX_32BITF.bits()
+            | VertexType::TRANSFORM_3D.bits(),
+    );
+}
+
+/// Size in bytes of one component of a 2-bit format field (8-bit, 16-bit
+/// or float), or 0 if the field is absent.
+const fn field_size(format: i32) -> usize {
+    match format & 3 {
+        1 => 1,
+        2 => 2,
+        3 => 4,
+        _ => 0,
+    }
+}
+
+const fn align_up(offset: usize, align: usize) -> usize {
+    if align == 0 {
+        offset
+    } else {
+        (offset + align - 1) & !(align - 1)
+    }
+}
+
+/// Size in bytes of one vertex as the GE reads it for `vtype`.
+///
+/// Components are laid out in the order weights, texture, color, normal,
+/// position, each aligned to its own element size, and the whole vertex
+/// is padded to its largest element. Morph targets repeat the layout.
+/// Returns 0 for flags without a position.
+pub const fn vertex_stride(vtype: VertexType) -> usize {
+    let bits = vtype.bits();
+    let weight = field_size(bits >> 9);
+    let weight_count = ((bits >> 14) & 7) as usize + 1;
+    let texture = field_size(bits);
+    let color = match (bits >> 2) & 7 {
+        4..=6 => 2,
+        7 => 4,
+        _ => 0,
+    };
+    let normal = field_size(bits >> 5);
+    let position = field_size(bits >> 7);
+    let morph_count = ((bits >> 18) & 7) as usize + 1;
+    if position == 0 {
+        return 0;
+    }
+
+    let mut size = 0;
+    let mut max_align = 0;
+    let components = [
+        (weight, if weight == 0 { 0 } else { weight_count }),
+        (texture, 2),
+        (color, 1),
+        (normal, 3),
+        (position, 3),
+    ];
+    let mut i = 0;
+    while i < components.len() {
+        let (elem, count) = components[i];
+        if elem != 0 {
+            size = align_up(size, elem) + elem * count;
+            if elem > max_align {
+                max_align = elem;
+            }
+        }
+        i += 1;
+    }
+    align_up(size, max_align) * morph_count
+}
+
+/// A growable array of vertices of one [`VertexFormat`].
+///
+/// Unlike [`SpriteBatch`](super::SpriteBatch), drawing doesn't clear the
+/// buffer, so static geometry can be built once and drawn every frame.
+#[cfg(not(feature = "stub-only"))]
+pub struct VertexBuffer<V: VertexFormat> {
+    vertices: alloc::vec::Vec<V>,
+}
+
+#[cfg(not(feature = "stub-only"))]
+impl<V: VertexFormat> VertexBuffer<V> {
+    /// Create an empty vertex buffer.
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Create an empty vertex buffer with room for `capacity` vertices.
+    pub fn with_capacity(capacity: usize) -> Self {
+        // Fails to compile if `V` doesn't match its vertex type flags.
+        const {
+            assert!(
+                core::mem::size_of::<V>() == vertex_stride(V::VERTEX_TYPE),
+                "vertex struct size doesn't match the stride of its VERTEX_TYPE"
+            )
+        };
+        Self {
+            vertices: alloc::vec::Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Append a vertex.
+    pub fn push(&mut self, vertex: V) {
+        self.vertices.push(vertex);
+    }
+
+    /// Append several vertices.
+    pub fn extend_from_slice(&mut self, vertices: &[V]) {
+        self.vertices.extend_from_slice(vertices);
+    }
+
+    /// Number of vertices in the buffer.
+    pub fn len(&self) -> usize {
+        self.vertices.len()
+    }
+
+    /// Whether the buffer holds no vertices.
+    pub fn is_empty(&self) -> bool {
+        self.vertices.is_empty()
+    }
+
+    /// Remove all vertices, keeping the allocation.
+    pub fn clear(&mut self) {
+        self.vertices.clear();
+    }
+
+    /// The vertices in the buffer.
+    pub fn as_slice(&self) -> &[V] {
+        &self.vertices
+    }
+
+    /// Mutable access to the vertices, e.g. to animate them in place.
+    pub fn as_mut_slice(&mut self) -> &mut [V] {
+        &mut self.vertices
+    }
+
+    /// Draw all vertices as `prim` with `V::VERTEX_TYPE`.
+    ///
+    /// The vertices are copied into display-list memory, so the buffer can
+    /// be modified or dropped right after this returns. Does nothing if the
+    /// buffer is empty or the display list is out of memory.
+    ///
+    /// # Safety
+    ///
+    /// Must be called between `sceGuStart` and `sceGuFinish`.
+    pub unsafe fn draw(&self, prim: crate::sys::GuPrimitive) {
+        if self.vertices.is_empty() {
+            return;
+        }
+        let byte_size = core::mem::size_of_val(self.vertices.as_slice());
+        unsafe {
+            // Display-list memory is only word aligned, which is all the GE
+            // needs, so copy bytes rather than typed values.
+            let dl_verts = crate::sys::sceGuGetMemory(byte_size as i32) as *mut u8;
+            if dl_verts.is_null() {
+                return;
+            }
+            core::ptr::copy_nonoverlapping(
+                self.vertices.as_ptr() as *const u8,
+                dl_verts,
+                byte_size,
+            );
+            crate::sys::sceGuDrawArray(
+                prim,
+                V::VERTEX_TYPE,
+                self.vertices.len() as i32,
+                core::ptr::null::<core::ffi::c_void>(),
+                dl_verts as *const core::ffi::c_void,
+            );
+        }
+    }
+}
+
+#[cfg(not(feature = "stub-only"))]
+impl<V: VertexFormat> Default for VertexBuffer<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,256 @@
+//! Checksums for detecting corrupted data.
+//!
+//! Memory Sticks (especially cheap third-party ones) can silently corrupt
+//! data, so persisted files benefit from an integrity check. This module
+//! provides:
+//!
+//! - [`crc32()`] / [`Crc32`]: CRC-32 (IEEE 802.3, the zlib/PNG/Ethernet
+//!   polynomial), table-driven. Used by [`crate::config`] and
+//!   [`crate::savedata`] checksums.
+//! - [`fnv1a_64()`]: 64-bit FNV-1a, for hashing keys or fingerprinting
+//!   content where a wider result is useful.
+//! - [`siphash24()`]: SipHash-2-4, a keyed hash. Without the key its
+//!   output can't be forged, so it works as a short MAC.
+//! - [`sha1()`]: SHA-1, for protocols that specify it, such as the
+//!   WebSocket handshake. It is broken for collision resistance; don't
+//!   pick it for anything new.
+//!
+//! CRC-32 and FNV-1a are not cryptographic: they detect accidental
+//! corruption, not deliberate tampering. Use
+//! [`crate::savedata::Savedata::secure_key`] or
+//! [`encrypt_with`](crate::savedata::Savedata::encrypt_with) to keep saves
+//! from being edited.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use psp::hash;
+//!
+//! let mut file = payload.to_vec();
+//! file.extend_from_slice(&hash::crc32(payload).to_le_bytes());
+//!
+//! match hash::strip_crc32(&file) {
+//!     Some(data) => assert_eq!(data, payload),
+//!     None => psp::dprintln!("corrupt"),
+//! }
+//! ```
+
+/// Reflected CRC-32 polynomial (IEEE 802.3).
+const CRC32_POLY: u32 = 0xEDB8_8320;
+
+/// Lookup table for byte-at-a-time CRC-32, built at compile time.
+static CRC32_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ CRC32_POLY
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// Incremental CRC-32 computation, for data that arrives in pieces.
+///
+/// Feeding the same bytes in any split produces the same result as
+/// [`crc32()`] over the whole input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Crc32 {
+    state: u32,
+}
+
+impl Crc32 {
+    /// Start a new checksum.
+    pub const fn new() -> Self {
+        Self { state: !0 }
+    }
+
+    /// Add `data` to the checksum.
+    pub fn update(&mut self, data: &[u8]) {
+        let mut crc = self.state;
+        for &b in data {
+            crc = CRC32_TABLE[((crc ^ b as u32) & 0xff) as usize] ^ (crc >> 8);
+        }
+        self.state = crc;
+    }
+
+    /// The checksum of all data added so far.
+    pub fn finish(&self) -> u32 {
+        !self.state
+    }
+}
+
+impl Default for Crc32 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// CRC-32 (IEEE) of `data`.
+///
+/// `crc32(b"123456789") == 0xCBF4_3926`.
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = Crc32::new();
+    crc.update(data);
+    crc.finish()
+}
+
+/// Split a little-endian CRC-32 trailer off `data` and verify it.
+///
+/// Returns the payload without the trailer, or `None` if `data` is shorter
+/// than 4 bytes or the checksum doesn't match.
+pub fn strip_crc32(data: &[u8]) -> Option<&[u8]> {
+    let split = data.len().checked_sub(4)?;
+    let (payload, trailer) = data.split_at(split);
+    let expected = u32::from_le_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]);
+    (crc32(payload) == expected).then_some(payload)
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// 64-bit FNV-1a hash of `data`.
+///
+/// Fast and simple, with good distribution for short inputs such as
+/// identifiers. Not collision-resistant against chosen inputs.
+pub fn fnv1a_64(data: &[u8]) -> u64 {
+    data.iter().fold(FNV_OFFSET_BASIS, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(FNV_PRIME)
+    })
+}
+
+/// One SipRound over the state.
+#[inline(always)]
+fn sip_round(v: &mut [u64; 4]) {
+    v[0] = v[0].wrapping_add(v[1]);
+    v[1] = v[1].rotate_left(13) ^ v[0];
+    v[0] = v[0].rotate_left(32);
+    v[2] = v[2].wrapping_add(v[3]);
+    v[3] = v[3].rotate_left(16) ^ v[2];
+    v[0] = v[0].wrapping_add(v[3]);
+    v[3] = v[3].rotate_left(21) ^ v[0];
+    v[2] = v[2].wrapping_add(v[1]);
+    v[1] = v[1].rotate_left(17) ^ v[2];
+    v[2] = v[2].rotate_left(32);
+}
+
+/// SipHash-2-4 of `data` under a 128-bit `key`.
+///
+/// A pseudo-random function: without the key, the hash of a message
+/// can't be predicted, so it authenticates data (a 64-bit MAC) as well as
+/// hashing it. Matches the reference implementation, e.g. key `00..0f`
+/// and message `00..0e` hash to `0xa129_ca61_49be_45e5`.
+pub fn siphash24(key: &[u8; 16], data: &[u8]) -> u64 {
+    let k0 = u64::from_le_bytes(key[..8].try_into().unwrap_or_default());
+    let k1 = u64::from_le_bytes(key[8..].try_into().unwrap_or_default());
+    let mut v = [
+        k0 ^ 0x736f_6d65_7073_6575,
+        k1 ^ 0x646f_7261_6e64_6f6d,
+        k0 ^ 0x6c79_6765_6e65_7261,
+        k1 ^ 0x7465_6462_7974_6573,
+    ];
+
+    let mut chunks = data.chunks_exact(8);
+    for chunk in &mut chunks {
+        let m = u64::from_le_bytes(chunk.try_into().unwrap_or_default());
+        v[3] ^= m;
+        sip_round(&mut v);
+        sip_round(&mut v);
+        v[0] ^= m;
+    }
+    // Last block: remaining bytes, with the length in the top byte.
+    let mut last = [0u8; 8];
+    let tail = chunks.remainder();
+    last[..tail.len()].copy_from_slice(tail);
+    last[7] = data.len() as u8;
+    let m = u64::from_le_bytes(last);
+    v[3] ^= m;
+    sip_round(&mut v);
+    sip_round(&mut v);
+    v[0] ^= m;
+
+    v[2] ^= 0xff;
+    for _ in 0..4 {
+        sip_round(&mut v);
+    }
+    v[0] ^ v[1] ^ v[2] ^ v[3]
+}
+
+/// SHA-1 digest of `data` (FIPS 180-4).
+///
+/// E.g. `"abc"` hashes to `a9993e36 4706816a ba3e2571 7850c26c 9cd0d89d`.
+pub fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut state: [u32; 5] = [
+        0x6745_2301,
+        0xefcd_ab89,
+        0x98ba_dcfe,
+        0x1032_5476,
+        0xc3d2_e1f0,
+    ];
+
+    let mut blocks = data.chunks_exact(64);
+    for block in &mut blocks {
+        sha1_block(&mut state, block);
+    }
+    // Padding: a 1 bit, zeros, then the length in bits, filling one or two
+    // final blocks.
+    let tail = blocks.remainder();
+    let mut last = [0u8; 128];
+    last[..tail.len()].copy_from_slice(tail);
+    last[tail.len()] = 0x80;
+    let end = if tail.len() < 56 { 64 } else { 128 };
+    last[end - 8..end].copy_from_slice(&(data.len() as u64 * 8).to_be_bytes());
+    for block in last[..end].chunks_exact(64) {
+        sha1_block(&mut state, block);
+    }
+
+    let mut digest = [0u8; 20];
+    for (out, word) in digest.chunks_exact_mut(4).zip(state) {
+        out.copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
+
+/// Run the SHA-1 compression function over one 64-byte block.
+fn sha1_block(state: &mut [u32; 5], block: &[u8]) {
+    let mut w = [0u32; 80];
+    for (word, bytes) in w.iter_mut().zip(block.chunks_exact(4)) {
+        *word = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    }
+    for i in 16..80 {
+        w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e] = *state;
+    for (i, &word) in w.iter().enumerate() {
+        let (f, k) = match i {
+            0..20 => ((b & c) | (!b & d), 0x5a82_7999),
+            20..40 => (b ^ c ^ d, 0x6ed9_eba1),
+            40..60 => ((b & c) | (b & d) | (c & d), 0x8f1b_bcdc),
+            _ => (b ^ c ^ d, 0xca62_c1d6),
+        };
+        let t = a
+            .rotate_left(5)
+            .wrapping_add(f)
+            .wrapping_add(e)
+            .wrapping_add(k)
+            .wrapping_add(word);
+        e = d;
+        d = c;
+        c = b.rotate_left(30);
+        b = a;
+        a = t;
+    }
+    for (s, v) in state.iter_mut().zip([a, b, c, d, e]) {
+        *s = s.wrapping_add(v);
+    }
+}
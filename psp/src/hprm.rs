@@ -0,0 +1,165 @@
+//! Headphone remote input.
+//!
+//! The remote that plugs in beside the headphone jack (play/pause,
+//! forward, back, volume and a hold switch) is read through `sceHprm`,
+//! separately from the controller. [`peek()`] returns the keys held right
+//! now, and [`Remote`] tracks them between frames for press/release
+//! detection, like [`Controller`](crate::input::Controller) does for the
+//! buttons.
+//!
+//! The PSP Go has no remote connector. There, [`is_remote_connected()`],
+//! [`peek()`] and [`Remote::new()`] return [`HprmError::NotPresent`]
+//! instead of whatever the driver reports.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use psp::hprm::{HprmButtons, Remote};
+//!
+//! let mut remote = Remote::new().ok();
+//! loop {
+//!     if let Some(remote) = &mut remote {
+//!         remote.update();
+//!         if remote.is_pressed(HprmButtons::PLAY_PAUSE) {
+//!             player.toggle_pause();
+//!         }
+//!     }
+//!     // ...
+//! }
+//! ```
+
+use crate::sys;
+
+/// Keys on the headphone remote.
+pub use crate::sys::HprmKey as HprmButtons;
+
+/// Error from a headphone remote query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HprmError {
+    /// This model has no headphone remote connector.
+    NotPresent,
+    /// An `sceHprm*` call failed with this SCE error code.
+    Kernel(i32),
+}
+
+impl core::fmt::Display for HprmError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::NotPresent => f.write_str("no headphone remote connector on this model"),
+            Self::Kernel(code) => write!(f, "headphone remote error {:#010x}", *code as u32),
+        }
+    }
+}
+
+/// Whether this model has a headphone remote connector.
+///
+/// Assumes it does when the model can't be determined.
+pub fn has_remote_port() -> bool {
+    crate::model::detect().map_or(true, |model| model.has_remote_port())
+}
+
+fn check_port() -> Result<(), HprmError> {
+    if has_remote_port() {
+        Ok(())
+    } else {
+        Err(HprmError::NotPresent)
+    }
+}
+
+fn exists(ret: i32) -> Result<bool, HprmError> {
+    if ret < 0 {
+        Err(HprmError::Kernel(ret))
+    } else {
+        Ok(ret != 0)
+    }
+}
+
+/// Whether a headphone remote is plugged in.
+pub fn is_remote_connected() -> Result<bool, HprmError> {
+    check_port()?;
+    exists(unsafe { sys::sceHprmIsRemoteExist() })
+}
+
+/// Whether headphones are plugged in, with or without a remote.
+pub fn is_headphone_connected() -> Result<bool, HprmError> {
+    exists(unsafe { sys::sceHprmIsHeadphoneExist() })
+}
+
+/// The remote keys held right now.
+///
+/// Empty when no remote is plugged in. [`HprmButtons::HOLD`] is set while
+/// the remote's hold switch is on.
+pub fn peek() -> Result<HprmButtons, HprmError> {
+    check_port()?;
+    read_keys()
+}
+
+/// [`peek()`] without the model check, which probes the hardware.
+fn read_keys() -> Result<HprmButtons, HprmError> {
+    if !exists(unsafe { sys::sceHprmIsRemoteExist() })? {
+        return Ok(HprmButtons::empty());
+    }
+    let mut keys = HprmButtons::empty();
+    let ret = unsafe { sys::sceHprmPeekCurrentKey(&mut keys) };
+    if ret < 0 {
+        return Err(HprmError::Kernel(ret));
+    }
+    Ok(keys)
+}
+
+/// Headphone remote input with press/release detection.
+///
+/// Call [`update()`](Self::update) once per frame, then query keys the
+/// same way as [`Controller`](crate::input::Controller).
+pub struct Remote {
+    current: HprmButtons,
+    previous: HprmButtons,
+}
+
+impl Remote {
+    /// Create a poller with no keys held.
+    ///
+    /// Fails with [`HprmError::NotPresent`] on models without a remote
+    /// connector.
+    pub fn new() -> Result<Self, HprmError> {
+        check_port()?;
+        Ok(Self {
+            current: HprmButtons::empty(),
+            previous: HprmButtons::empty(),
+        })
+    }
+
+    /// Read the current remote state.
+    ///
+    /// Must be called once per frame for press/release detection to work.
+    /// A failed read counts as no keys held, so unplugging the remote
+    /// releases everything.
+    pub fn update(&mut self) {
+        self.previous = self.current;
+        self.current = read_keys().unwrap_or(HprmButtons::empty());
+    }
+
+    /// Returns `true` if the key is currently held down.
+    pub fn is_held(&self, key: HprmButtons) -> bool {
+        self.current.contains(key)
+    }
+
+    /// Returns `true` if the key was just pressed this frame.
+    ///
+    /// (Down now, was not down last frame.)
+    pub fn is_pressed(&self, key: HprmButtons) -> bool {
+        self.current.contains(key) && !self.previous.contains(key)
+    }
+
+    /// Returns `true` if the key was just released this frame.
+    ///
+    /// (Not down now, was down last frame.)
+    pub fn is_released(&self, key: HprmButtons) -> bool {
+        !self.current.contains(key) && self.previous.contains(key)
+    }
+
+    /// All keys held this frame.
+    pub fn buttons(&self) -> HprmButtons {
+        self.current
+    }
+}
@@ -13,16 +13,80 @@
 //! psp::dprintln!("Status: {}", response.status_code);
 //! psp::dprintln!("Body: {} bytes", response.body.len());
 //! ```
+//!
+//! # Large downloads
+//!
+//! [`HttpClient::download()`] streams a response straight to a file
+//! instead of collecting it in memory, and can resume a partial file from
+//! an earlier attempt:
+//!
+//! ```ignore
+//! use psp::http::{DownloadOptions, HttpClient};
+//!
+//! let client = HttpClient::new().unwrap();
+//! let mut report = |done: u64, total: Option<u64>| {
+//!     psp::dprintln!("{} / {:?} bytes", done, total);
+//! };
+//! client.download(
+//!     b"http://example.com/pack.bin\0",
+//!     "ms0:/PSP/GAME/MYGAME/pack.bin",
+//!     DownloadOptions {
+//!         resume: true,
+//!         progress: Some(&mut report),
+//!         ..Default::default()
+//!     },
+//! )?;
+//! ```
+//!
+//! # HTTPS
+//!
+//! [`HttpClient::new_https()`] additionally initializes `sceSsl` and
+//! `sceHttps` and loads the firmware's root certificates, after which
+//! `https://` URLs work like `http://` ones. A plain [`HttpClient::new()`]
+//! client rejects `https://` URLs with [`HTTP_ERROR_HTTPS_DISABLED`].
+//! The firmware's certificate store is old, so servers with newer CAs may
+//! fail verification; [`HttpClient::disable_cert_verification()`] is an
+//! escape hatch for those and for self-signed development servers.
 
 use alloc::vec::Vec;
 use core::ffi::c_void;
 
+use crate::sync::SpinMutex;
 use crate::sys;
+use crate::utility::{self, UtilityModule};
 
 /// Error from an HTTP operation, wrapping the raw SCE error code.
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub struct HttpError(pub i32);
 
+/// Sentinel error code returned when an `https://` URL is requested from a
+/// client created without HTTPS support.
+pub const HTTP_ERROR_HTTPS_DISABLED: i32 = -2;
+
+/// Sentinel error code returned when the HTTP or SSL utility modules
+/// could not be loaded.
+pub const HTTP_ERROR_MODULE_LOAD_FAILED: i32 = -3;
+
+/// Sentinel error code returned when a `Transfer-Encoding: chunked` body
+/// is malformed or ends before its terminating zero-length chunk.
+pub const HTTP_ERROR_BAD_CHUNK: i32 = -4;
+
+/// Sentinel error code returned by [`HttpClient::download()`] when the
+/// server answers with a status other than 2xx.
+pub const HTTP_ERROR_STATUS: i32 = -5;
+
+/// Sentinel error code returned by [`HttpClient::download()`] when a
+/// resumed download's `206 Partial Content` response doesn't start where
+/// the partial file ends.
+pub const HTTP_ERROR_BAD_RANGE: i32 = -6;
+
+/// Sentinel error code returned by [`HttpClient::download()`] when the
+/// body ends before the length the server announced.
+pub const HTTP_ERROR_INCOMPLETE: i32 = -7;
+
+/// Heap size passed to `sceSslInit`.
+const SSL_POOL_SIZE: i32 = 0x28000;
+
 impl core::fmt::Debug for HttpError {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "HttpError({:#010x})", self.0 as u32)
@@ -40,15 +104,39 @@ impl core::fmt::Display for HttpError {
 /// Manages the sceHttp subsystem initialization and template lifecycle.
 /// All connections and requests created through this client are cleaned
 /// up on drop.
+///
+/// Connections are kept alive between requests: consecutive requests to
+/// the same scheme, host and port reuse one TCP (and TLS) connection,
+/// which is only closed when a request goes to a different server, a
+/// request fails, [`close_connection()`](Self::close_connection) is
+/// called, or the client is dropped.
 pub struct HttpClient {
     template_id: i32,
+    https: bool,
+    /// Idle keep-alive connection from the last request, kept for reuse.
+    connection: SpinMutex<Option<Connection>>,
+}
+
+/// An open `sceHttp` connection and the origin it is connected to.
+struct Connection {
+    origin: Vec<u8>,
+    id: i32,
 }
 
 impl HttpClient {
     /// Initialize the HTTP subsystem and create a client.
     ///
-    /// Calls `sceHttpInit` and creates a default template.
+    /// Loads the `NetHttp` utility module (see [`crate::utility`]), calls
+    /// `sceHttpInit` and creates a default template.
     pub fn new() -> Result<Self, HttpError> {
+        utility::load_module(UtilityModule::NetHttp)
+            .map_err(|_| HttpError(HTTP_ERROR_MODULE_LOAD_FAILED))?;
+        Self::create().inspect_err(|_| {
+            let _ = utility::unload_module(UtilityModule::NetHttp);
+        })
+    }
+
+    fn create() -> Result<Self, HttpError> {
         let ret = unsafe { sys::sceHttpInit(0x20000) };
         if ret < 0 {
             return Err(HttpError(ret));
@@ -66,10 +154,98 @@ impl HttpClient {
             return Err(HttpError(template_id));
         }
 
-        // Enable redirects by default.
-        unsafe { sys::sceHttpEnableRedirect(template_id) };
+        // Enable redirects and connection reuse by default.
+        unsafe {
+            sys::sceHttpEnableRedirect(template_id);
+            sys::sceHttpEnableKeepAlive(template_id);
+        }
+
+        Ok(Self {
+            template_id,
+            https: false,
+            connection: SpinMutex::new(None),
+        })
+    }
+
+    /// Initialize the HTTP and HTTPS subsystems and create a client that
+    /// accepts both `http://` and `https://` URLs.
+    ///
+    /// Calls `sceSslInit`, `sceHttpsInit` and `sceHttpsLoadDefaultCert` on
+    /// top of what [`new()`](Self::new) does. Certificates are verified
+    /// against the firmware's root store.
+    pub fn new_https() -> Result<Self, HttpError> {
+        utility::load_module(UtilityModule::NetSsl)
+            .map_err(|_| HttpError(HTTP_ERROR_MODULE_LOAD_FAILED))?;
+        let ret = unsafe { sys::sceSslInit(SSL_POOL_SIZE) };
+        if ret < 0 {
+            let _ = utility::unload_module(UtilityModule::NetSsl);
+            return Err(HttpError(ret));
+        }
+
+        let mut client = match Self::new() {
+            Ok(client) => client,
+            Err(e) => {
+                unsafe { sys::sceSslEnd() };
+                let _ = utility::unload_module(UtilityModule::NetSsl);
+                return Err(e);
+            },
+        };
+
+        let ret = unsafe { sys::sceHttpsInit(0, 0, 0, 0) };
+        if ret < 0 {
+            drop(client);
+            unsafe { sys::sceSslEnd() };
+            let _ = utility::unload_module(UtilityModule::NetSsl);
+            return Err(HttpError(ret));
+        }
+        // From here on, drop tears down HTTPS and SSL as well.
+        client.https = true;
 
-        Ok(Self { template_id })
+        let ret = unsafe { sys::sceHttpsLoadDefaultCert(0, 0) };
+        if ret < 0 {
+            return Err(HttpError(ret));
+        }
+
+        Ok(client)
+    }
+
+    /// Whether this client was created with HTTPS support.
+    pub fn is_https(&self) -> bool {
+        self.https
+    }
+
+    /// Stop verifying server certificates, for self-signed endpoints or
+    /// servers whose CA is missing from the firmware's store.
+    ///
+    /// This makes connections vulnerable to interception. The setting is
+    /// global to the `sceHttps` library, not just this client.
+    pub fn disable_cert_verification(&self) -> Result<(), HttpError> {
+        self.set_cert_verification(false)
+    }
+
+    /// Re-enable the certificate checks turned off by
+    /// [`disable_cert_verification()`](Self::disable_cert_verification).
+    pub fn enable_cert_verification(&self) -> Result<(), HttpError> {
+        self.set_cert_verification(true)
+    }
+
+    fn set_cert_verification(&self, enable: bool) -> Result<(), HttpError> {
+        if !self.https {
+            return Err(HttpError(HTTP_ERROR_HTTPS_DISABLED));
+        }
+        let flags = sys::HttpsFlags::SERVER_VERIFY
+            | sys::HttpsFlags::CN_CHECK
+            | sys::HttpsFlags::NOT_AFTER_CHECK
+            | sys::HttpsFlags::NOT_BEFORE_CHECK
+            | sys::HttpsFlags::KNOWN_CA_CHECK;
+        let ret = unsafe {
+            if enable {
+                sys::sceHttpsEnableOption(flags)
+            } else {
+                sys::sceHttpsDisableOption(flags)
+            }
+        };
+        if ret < 0 { Err(HttpError(ret)) } else { Ok(()) }
     }
 
     /// Perform an HTTP GET request.
@@ -88,6 +264,114 @@ impl HttpClient {
             .send()
     }
 
+    /// Download `url` straight into the file at `dest_path`.
+    ///
+    /// The body is streamed through one fixed buffer, so files far larger
+    /// than the heap can be downloaded. `url` must be a null-terminated
+    /// byte string. Fails with [`HTTP_ERROR_STATUS`] if the server doesn't
+    /// answer 2xx, and with [`HTTP_ERROR_INCOMPLETE`] if the body is cut
+    /// short. File errors are returned with their `sceIo` error code.
+    ///
+    /// # Resuming
+    ///
+    /// With [`DownloadOptions::resume`] set and a non-empty file already at
+    /// `dest_path`, only the missing tail is requested with a `Range`
+    /// header and appended. If the server ignores the range and sends the
+    /// whole file, the file is rewritten from the start; if its
+    /// `Content-Range` doesn't begin at the end of the file, the download
+    /// fails with [`HTTP_ERROR_BAD_RANGE`] and the file is left untouched.
+    ///
+    /// Data is only ever appended in order, so whatever stops a download
+    /// (a network error, a full Memory Stick, a crash) leaves a file that
+    /// is a prefix of the resource, ready to be resumed. Nothing checks
+    /// that the resource hasn't changed between attempts.
+    pub fn download(
+        &self,
+        url: &[u8],
+        dest_path: &str,
+        mut options: DownloadOptions<'_>,
+    ) -> Result<Download, HttpError> {
+        let existing = if options.resume {
+            crate::io::stat(dest_path).map_or(0, |st| st.st_size.max(0) as u64)
+        } else {
+            0
+        };
+
+        let mut request = RequestBuilder::new(self, sys::HttpMethod::Get, url);
+        request.timeout_ms = options.timeout_ms;
+        if existing > 0 {
+            request.range_from = Some(existing);
+        }
+
+        request.send_with(|response| {
+            let range = content_range(&response.headers);
+            let (offset, total_size) = match response.status_code {
+                206 if existing > 0 => {
+                    let range = range.ok_or(HttpError(HTTP_ERROR_BAD_RANGE))?;
+                    match range.range {
+                        Some((start, _)) if start == existing => {},
+                        _ => return Err(HttpError(HTTP_ERROR_BAD_RANGE)),
+                    }
+                    (existing, range.total)
+                },
+                // Asked for bytes past the end: done if the file is whole.
+                416 if existing > 0 => match range.and_then(|r| r.total) {
+                    Some(total) if total == existing => {
+                        return Ok(Download {
+                            status_code: response.status_code,
+                            bytes_written: 0,
+                            file_size: existing,
+                            total_size: Some(total),
+                            resumed: true,
+                        });
+                    },
+                    _ => return Err(HttpError(HTTP_ERROR_BAD_RANGE)),
+                },
+                200..=299 => (0, response.content_length),
+                _ => return Err(HttpError(HTTP_ERROR_STATUS)),
+            };
+
+            let file = if offset > 0 {
+                crate::io::File::open(
+                    dest_path,
+                    sys::IoOpenFlags::WR_ONLY | sys::IoOpenFlags::APPEND,
+                )
+            } else {
+                crate::io::File::create(dest_path)
+            }
+            .map_err(|e| HttpError(e.code()))?;
+
+            let mut written = 0u64;
+            let mut buf = alloc::vec![0u8; DOWNLOAD_BUFFER_SIZE];
+            response.read_body(&mut buf, |mut data| {
+                while !data.is_empty() {
+                    let n = file.write(data).map_err(|e| HttpError(e.code()))?;
+                    if n == 0 {
+                        return Err(HttpError(HTTP_ERROR_INCOMPLETE));
+                    }
+                    data = &data[n..];
+                    written += n as u64;
+                }
+                if let Some(progress) = options.progress.as_mut() {
+                    progress(offset + written, total_size);
+                }
+                Ok(())
+            })?;
+
+            let file_size = offset + written;
+            if total_size.is_some_and(|total| file_size < total) {
+                return Err(HttpError(HTTP_ERROR_INCOMPLETE));
+            }
+            Ok(Download {
+                status_code: response.status_code,
+                bytes_written: written,
+                file_size,
+                total_size,
+                resumed: offset > 0,
+            })
+        })
+    }
+
     /// Create a request builder for more control.
     pub fn request<'a>(&'a self, method: sys::HttpMethod, url: &'a [u8]) -> RequestBuilder<'a> {
         RequestBuilder::new(self, method, url)
@@ -97,13 +381,77 @@ impl HttpClient {
     pub fn template_id(&self) -> i32 {
         self.template_id
     }
+
+    /// Close the kept-alive connection, if any.
+    ///
+    /// The next request opens a fresh connection. Useful before a long
+    /// idle period, since servers drop idle connections on their own.
+    pub fn close_connection(&self) {
+        if let Some(conn) = self.connection.lock().take() {
+            unsafe { sys::sceHttpDeleteConnection(conn.id) };
+        }
+    }
+
+    /// Take the idle connection to `url`'s server, or open a new one.
+    ///
+    /// A cached connection to a different server is closed. While a
+    /// request runs its connection is out of the cache, so concurrent
+    /// requests on the same client each get their own.
+    fn take_connection(&self, url: &[u8]) -> Result<i32, HttpError> {
+        let origin = url_origin(url);
+        let cached = self.connection.lock().take();
+        if let Some(conn) = cached {
+            if conn.origin.eq_ignore_ascii_case(origin) {
+                return Ok(conn.id);
+            }
+            unsafe { sys::sceHttpDeleteConnection(conn.id) };
+        }
+
+        let id = unsafe {
+            sys::sceHttpCreateConnectionWithURL(
+                self.template_id,
+                url.as_ptr(),
+                1, // keep-alive
+            )
+        };
+        if id < 0 {
+            return Err(HttpError(id));
+        }
+        Ok(id)
+    }
+
+    /// Put a connection to `url`'s server back in the cache after a
+    /// successful request.
+    fn return_connection(&self, url: &[u8], id: i32) {
+        let mut slot = self.connection.lock();
+        if slot.is_some() {
+            // Another request finished first; keep only one.
+            unsafe { sys::sceHttpDeleteConnection(id) };
+            return;
+        }
+        *slot = Some(Connection {
+            origin: Vec::from(url_origin(url)),
+            id,
+        });
+    }
 }
 
 impl Drop for HttpClient {
     fn drop(&mut self) {
+        self.close_connection();
         unsafe {
             sys::sceHttpDeleteTemplate(self.template_id);
+            if self.https {
+                sys::sceHttpsEnd();
+            }
             sys::sceHttpEnd();
+            if self.https {
+                sys::sceSslEnd();
+            }
+        }
+        let _ = utility::unload_module(UtilityModule::NetHttp);
+        if self.https {
+            let _ = utility::unload_module(UtilityModule::NetSsl);
         }
     }
 }
@@ -114,10 +462,45 @@ pub struct Response {
     pub status_code: u16,
     /// Content length if provided by the server, or `None`.
     pub content_length: Option<u64>,
-    /// Response body.
+    /// Response body, with any chunked transfer-encoding removed.
     pub body: Vec<u8>,
 }
 
+/// Name of the header used to resume downloads.
+const RANGE_HEADER: &[u8] = b"Range\0";
+
+/// Buffer size [`HttpClient::download()`] reads and writes in.
+const DOWNLOAD_BUFFER_SIZE: usize = 32 * 1024;
+
+/// Options for [`HttpClient::download()`].
+#[derive(Default)]
+pub struct DownloadOptions<'a> {
+    /// Continue a partial file at the destination instead of replacing it.
+    pub resume: bool,
+    /// Request timeout in milliseconds, as for
+    /// [`RequestBuilder::timeout()`].
+    pub timeout_ms: Option<u32>,
+    /// Called after each piece of the body is written, with the file size
+    /// so far and the full size if the server reported one.
+    pub progress: Option<&'a mut dyn FnMut(u64, Option<u64>)>,
+}
+
+/// Result of a finished [`HttpClient::download()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Download {
+    /// HTTP status code of the response (200, 206, or 416 if the file was
+    /// already complete).
+    pub status_code: u16,
+    /// Bytes written to the file by this call.
+    pub bytes_written: u64,
+    /// Size of the file now.
+    pub file_size: u64,
+    /// Full size of the resource, if the server reported it.
+    pub total_size: Option<u64>,
+    /// Whether an existing partial file was continued.
+    pub resumed: bool,
+}
+
 /// Builder for HTTP requests.
 pub struct RequestBuilder<'a> {
     client: &'a HttpClient,
@@ -125,6 +508,8 @@ pub struct RequestBuilder<'a> {
     url: &'a [u8],
     body: Option<&'a [u8]>,
     timeout_ms: Option<u32>,
+    /// First byte to request with a `Range` header, for resuming.
+    range_from: Option<u64>,
 }
 
 impl<'a> RequestBuilder<'a> {
@@ -135,6 +520,7 @@ impl<'a> RequestBuilder<'a> {
             url,
             body: None,
             timeout_ms: None,
+            range_from: None,
         }
     }
 
@@ -151,21 +537,58 @@ impl<'a> RequestBuilder<'a> {
     }
 
     /// Send the request and return the response.
+    ///
+    /// Reuses the client's open connection when the URL has the same
+    /// scheme, host and port as the previous request.
     pub fn send(self) -> Result<Response, HttpError> {
+        self.send_with(|response| {
+            let mut body = Vec::new();
+            let mut buf = [0u8; 4096];
+            response.read_body(&mut buf, |data| {
+                body.extend_from_slice(data);
+                Ok(())
+            })?;
+            Ok(Response {
+                status_code: response.status_code,
+                content_length: response.content_length,
+                body,
+            })
+        })
+    }
+
+    /// Send the request and pass the response, before its body is read, to
+    /// `handle`.
+    fn send_with<R>(
+        self,
+        handle: impl FnOnce(&PendingResponse) -> Result<R, HttpError>,
+    ) -> Result<R, HttpError> {
         // Validate null termination — the SCE HTTP syscalls expect C strings.
         if self.url.last() != Some(&0) {
             return Err(HttpError(-1));
         }
+        if !self.client.https && is_https_url(self.url) {
+            return Err(HttpError(HTTP_ERROR_HTTPS_DISABLED));
+        }
 
-        let content_length = self.body.map(|b| b.len() as u64).unwrap_or(0);
-
-        // Create connection + request using URL-based APIs.
-        let conn_id = unsafe {
-            sys::sceHttpCreateConnectionWithURL(self.client.template_id, self.url.as_ptr(), 0)
-        };
-        if conn_id < 0 {
-            return Err(HttpError(conn_id));
+        let conn_id = self.client.take_connection(self.url)?;
+        let result = self.perform(conn_id, handle);
+        match result {
+            Ok(_) => self.client.return_connection(self.url, conn_id),
+            // The connection may be in an unknown state; don't reuse it.
+            Err(_) => unsafe {
+                sys::sceHttpDeleteConnection(conn_id);
+            },
         }
+        result
+    }
+
+    /// Run the request on connection `conn_id`.
+    fn perform<R>(
+        &self,
+        conn_id: i32,
+        handle: impl FnOnce(&PendingResponse) -> Result<R, HttpError>,
+    ) -> Result<R, HttpError> {
+        let content_length = self.body.map(|b| b.len() as u64).unwrap_or(0);
 
         let req_id = unsafe {
             sys::sceHttpCreateRequestWithURL(
@@ -176,10 +599,16 @@ impl<'a> RequestBuilder<'a> {
             )
         };
         if req_id < 0 {
-            unsafe { sys::sceHttpDeleteConnection(conn_id) };
             return Err(HttpError(req_id));
         }
 
+        let result = self.exchange(req_id).and_then(|response| handle(&response));
+        unsafe { sys::sceHttpDeleteRequest(req_id) };
+        result
+    }
+
+    /// Send request `req_id` and read the response status and headers.
+    fn exchange(&self, req_id: i32) -> Result<PendingResponse, HttpError> {
         // Apply timeout if set.
         if let Some(ms) = self.timeout_ms {
             unsafe {
@@ -189,6 +618,22 @@ impl<'a> RequestBuilder<'a> {
             }
         }
 
+        if let Some(start) = self.range_from {
+            let mut value = alloc::format!("bytes={}-", start).into_bytes();
+            value.push(0);
+            let ret = unsafe {
+                sys::sceHttpAddExtraHeader(
+                    req_id,
+                    RANGE_HEADER.as_ptr() as *mut u8,
+                    value.as_mut_ptr(),
+                    0,
+                )
+            };
+            if ret < 0 {
+                return Err(HttpError(ret));
+            }
+        }
+
         // Send the request.
         let (data_ptr, data_size) = match self.body {
             Some(b) => (b.as_ptr() as *mut c_void, b.len() as u32),
@@ -196,10 +641,6 @@ impl<'a> RequestBuilder<'a> {
         };
         let ret = unsafe { sys::sceHttpSendRequest(req_id, data_ptr, data_size) };
         if ret < 0 {
-            unsafe {
-                sys::sceHttpDeleteRequest(req_id);
-                sys::sceHttpDeleteConnection(conn_id);
-            }
             return Err(HttpError(ret));
         }
 
@@ -207,10 +648,6 @@ impl<'a> RequestBuilder<'a> {
         let mut status_code: i32 = 0;
         let ret = unsafe { sys::sceHttpGetStatusCode(req_id, &mut status_code) };
         if ret < 0 {
-            unsafe {
-                sys::sceHttpDeleteRequest(req_id);
-                sys::sceHttpDeleteConnection(conn_id);
-            }
             return Err(HttpError(ret));
         }
 
@@ -219,36 +656,333 @@ impl<'a> RequestBuilder<'a> {
         let cl_ret = unsafe { sys::sceHttpGetContentLength(req_id, &mut cl) };
         let content_length = if cl_ret >= 0 { Some(cl) } else { None };
 
-        // Read body.
-        let mut body = Vec::new();
-        let mut buf = [0u8; 4096];
+        Ok(PendingResponse {
+            req_id,
+            status_code: status_code as u16,
+            content_length,
+            headers: response_headers(req_id),
+        })
+    }
+}
+
+/// A response whose status and headers have arrived but whose body is
+/// still unread.
+struct PendingResponse {
+    req_id: i32,
+    status_code: u16,
+    content_length: Option<u64>,
+    headers: Vec<u8>,
+}
+
+impl PendingResponse {
+    /// Read the whole body through `buf`, passing it to `sink` piece by
+    /// piece with any chunked transfer-encoding removed.
+    ///
+    /// Bodies with a `Content-Length` and chunked bodies end up in the same
+    /// sink, so callers never see the difference.
+    fn read_body(
+        &self,
+        buf: &mut [u8],
+        mut sink: impl FnMut(&[u8]) -> Result<(), HttpError>,
+    ) -> Result<(), HttpError> {
+        // Servers that stream the body send it chunked with no length.
+        let mut decoder = if self.content_length.is_none() && is_chunked(&self.headers) {
+            Some(ChunkedDecoder::new())
+        } else {
+            None
+        };
         loop {
             let n = unsafe {
-                sys::sceHttpReadData(req_id, buf.as_mut_ptr() as *mut c_void, buf.len() as u32)
+                sys::sceHttpReadData(
+                    self.req_id,
+                    buf.as_mut_ptr() as *mut c_void,
+                    buf.len() as u32,
+                )
             };
             if n < 0 {
-                unsafe {
-                    sys::sceHttpDeleteRequest(req_id);
-                    sys::sceHttpDeleteConnection(conn_id);
-                }
                 return Err(HttpError(n));
             }
             if n == 0 {
                 break;
             }
-            body.extend_from_slice(&buf[..n as usize]);
+            let data = &buf[..n as usize];
+            match &mut decoder {
+                Some(decoder) => decoder.feed(data, &mut sink)?,
+                None => sink(data)?,
+            }
+        }
+        match decoder {
+            Some(decoder) => decoder.finish(),
+            None => Ok(()),
         }
+    }
+}
 
-        // Cleanup.
-        unsafe {
-            sys::sceHttpDeleteRequest(req_id);
-            sys::sceHttpDeleteConnection(conn_id);
+/// The `scheme://host[:port]` prefix of a null-terminated URL, which
+/// identifies the server a connection talks to.
+fn url_origin(url: &[u8]) -> &[u8] {
+    let url = url.strip_suffix(&[0]).unwrap_or(url);
+    let host_start = url
+        .windows(3)
+        .position(|w| w == b"://")
+        .map_or(0, |i| i + 3);
+    let host_end = url[host_start..]
+        .iter()
+        .position(|&b| matches!(b, b'/' | b'?' | b'#'))
+        .map_or(url.len(), |i| host_start + i);
+    &url[..host_end]
+}
+
+/// Raw response header block of `req_id`, or empty if unavailable.
+///
+/// The buffer is owned by the HTTP library, so it is copied out while the
+/// request is still alive.
+fn response_headers(req_id: i32) -> Vec<u8> {
+    let mut ptr: *mut u8 = core::ptr::null_mut();
+    let mut size: u32 = 0;
+    let ret = unsafe { sys::sceHttpGetAllHeader(req_id, &mut ptr, &mut size) };
+    if ret < 0 || ptr.is_null() {
+        return Vec::new();
+    }
+    unsafe { core::slice::from_raw_parts(ptr, size as usize) }.to_vec()
+}
+
+/// Whether a raw header block declares `Transfer-Encoding: chunked`.
+///
+/// Header names are matched case-insensitively; `chunked` must be the last
+/// listed coding, as RFC 9112 requires.
+pub fn is_chunked(headers: &[u8]) -> bool {
+    const NAME: &[u8] = b"transfer-encoding";
+    headers.split(|&b| b == b'\n').any(|line| {
+        let Some(colon) = line.iter().position(|&b| b == b':') else {
+            return false;
+        };
+        if !line[..colon].trim_ascii().eq_ignore_ascii_case(NAME) {
+            return false;
+        }
+        line[colon + 1..]
+            .split(|&b| b == b',')
+            .next_back()
+            .is_some_and(|coding| coding.trim_ascii().eq_ignore_ascii_case(b"chunked"))
+    })
+}
+
+/// A parsed `Content-Range` response header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContentRange {
+    /// First and last byte (inclusive) of the range sent, or `None` for
+    /// the `*` form used by `416 Range Not Satisfiable`.
+    pub range: Option<(u64, u64)>,
+    /// Full size of the resource, or `None` if the server sent `*`.
+    pub total: Option<u64>,
+}
+
+/// Find and parse the `Content-Range` header in a raw header block.
+///
+/// Accepts `bytes first-last/total`, where `total` may be `*`, and
+/// `bytes */total`. Returns `None` if the header is missing or malformed.
+pub fn content_range(headers: &[u8]) -> Option<ContentRange> {
+    let value = header_value(headers, b"content-range")?;
+    let rest = value.strip_prefix(b"bytes")?.trim_ascii_start();
+    let slash = rest.iter().position(|&b| b == b'/')?;
+    let (range, total) = (rest[..slash].trim_ascii(), rest[slash + 1..].trim_ascii());
+
+    let total = if total == b"*" {
+        None
+    } else {
+        Some(parse_decimal(total)?)
+    };
+    let range = if range == b"*" {
+        None
+    } else {
+        let dash = range.iter().position(|&b| b == b'-')?;
+        let first = parse_decimal(range[..dash].trim_ascii())?;
+        let last = parse_decimal(range[dash + 1..].trim_ascii())?;
+        if last < first {
+            return None;
         }
+        Some((first, last))
+    };
+    Some(ContentRange { range, total })
+}
 
-        Ok(Response {
-            status_code: status_code as u16,
-            content_length,
-            body,
-        })
+/// Value of the first header called `name` (lowercase) in a raw header
+/// block, trimmed of surrounding whitespace.
+fn header_value<'a>(headers: &'a [u8], name: &[u8]) -> Option<&'a [u8]> {
+    headers.split(|&b| b == b'\n').find_map(|line| {
+        let colon = line.iter().position(|&b| b == b':')?;
+        if line[..colon].trim_ascii().eq_ignore_ascii_case(name) {
+            Some(line[colon + 1..].trim_ascii())
+        } else {
+            None
+        }
+    })
+}
+
+/// Parse a non-empty decimal number, rejecting overflow.
+fn parse_decimal(digits: &[u8]) -> Option<u64> {
+    if digits.is_empty() {
+        return None;
     }
+    digits.iter().try_fold(0u64, |acc, &b| {
+        let digit = (b as char).to_digit(10)? as u64;
+        acc.checked_mul(10)?.checked_add(digit)
+    })
+}
+
+/// Decode a `Transfer-Encoding: chunked` body.
+///
+/// Each chunk is a hex size line (optionally followed by `;extensions`),
+/// the data, and a CRLF. Decoding stops at the zero-length chunk; any
+/// trailer headers after it are ignored. Fails with
+/// [`HTTP_ERROR_BAD_CHUNK`] on a malformed size line or a body that ends
+/// before the terminator.
+pub fn decode_chunked(data: &[u8]) -> Result<Vec<u8>, HttpError> {
+    let mut out = Vec::with_capacity(data.len());
+    let mut decoder = ChunkedDecoder::new();
+    decoder.feed(data, |chunk| {
+        out.extend_from_slice(chunk);
+        Ok(())
+    })?;
+    decoder.finish()?;
+    Ok(out)
+}
+
+/// Longest chunk size line accepted, extensions included.
+const MAX_CHUNK_LINE: usize = 256;
+
+/// Incremental `Transfer-Encoding: chunked` decoder.
+///
+/// Accepts the raw body in pieces of any size, split anywhere, and passes
+/// decoded data on as soon as it arrives, so a large chunked body can be
+/// streamed without holding it in memory. Follows the same rules as
+/// [`decode_chunked`].
+pub struct ChunkedDecoder {
+    state: ChunkState,
+    /// Size line read so far.
+    line: Vec<u8>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ChunkState {
+    /// Reading a size line.
+    Size,
+    /// This many data bytes of the current chunk remain.
+    Data(usize),
+    /// Expecting the CRLF after a chunk's data; `true` once the CR is seen.
+    DataEnd(bool),
+    /// The zero-length chunk has been seen.
+    Done,
+}
+
+impl ChunkedDecoder {
+    /// Create a decoder at the start of a body.
+    pub fn new() -> Self {
+        Self {
+            state: ChunkState::Size,
+            line: Vec::new(),
+        }
+    }
+
+    /// Decode the next piece of the body, passing decoded data to `sink`.
+    ///
+    /// Input after the terminating chunk is ignored. Errors from `sink` are
+    /// returned as is.
+    pub fn feed(
+        &mut self,
+        mut data: &[u8],
+        mut sink: impl FnMut(&[u8]) -> Result<(), HttpError>,
+    ) -> Result<(), HttpError> {
+        const BAD: HttpError = HttpError(HTTP_ERROR_BAD_CHUNK);
+
+        while !data.is_empty() {
+            match self.state {
+                ChunkState::Size => {
+                    let eol = data.iter().position(|&b| b == b'\n');
+                    let take = eol.map_or(data.len(), |i| i + 1);
+                    if self.line.len() + take > MAX_CHUNK_LINE {
+                        return Err(BAD);
+                    }
+                    self.line.extend_from_slice(&data[..take]);
+                    data = &data[take..];
+                    if eol.is_none() {
+                        break;
+                    }
+
+                    let line = &self.line[..self.line.len() - 1];
+                    let size_field = match line.iter().position(|&b| b == b';') {
+                        Some(i) => &line[..i],
+                        None => line,
+                    };
+                    let size = parse_hex(size_field.trim_ascii()).ok_or(BAD)?;
+                    self.line.clear();
+                    self.state = if size == 0 {
+                        ChunkState::Done
+                    } else {
+                        ChunkState::Data(size)
+                    };
+                },
+                ChunkState::Data(remaining) => {
+                    let take = remaining.min(data.len());
+                    sink(&data[..take])?;
+                    data = &data[take..];
+                    self.state = if take == remaining {
+                        ChunkState::DataEnd(false)
+                    } else {
+                        ChunkState::Data(remaining - take)
+                    };
+                },
+                // Each chunk's data is followed by CRLF (tolerate a bare LF).
+                ChunkState::DataEnd(seen_cr) => {
+                    self.state = match data[0] {
+                        b'\r' if !seen_cr => ChunkState::DataEnd(true),
+                        b'\n' => ChunkState::Size,
+                        _ => return Err(BAD),
+                    };
+                    data = &data[1..];
+                },
+                ChunkState::Done => break,
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the terminating zero-length chunk has been decoded.
+    pub fn is_done(&self) -> bool {
+        self.state == ChunkState::Done
+    }
+
+    /// Check that the body ended with its terminating chunk.
+    ///
+    /// Fails with [`HTTP_ERROR_BAD_CHUNK`] if it didn't, meaning the body
+    /// was cut short.
+    pub fn finish(&self) -> Result<(), HttpError> {
+        if self.is_done() {
+            Ok(())
+        } else {
+            Err(HttpError(HTTP_ERROR_BAD_CHUNK))
+        }
+    }
+}
+
+impl Default for ChunkedDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Parse a non-empty hexadecimal number, rejecting overflow.
+fn parse_hex(digits: &[u8]) -> Option<usize> {
+    if digits.is_empty() {
+        return None;
+    }
+    digits.iter().try_fold(0usize, |acc, &b| {
+        let digit = (b as char).to_digit(16)? as usize;
+        acc.checked_mul(16)?.checked_add(digit)
+    })
+}
+
+/// Whether `url` uses the `https` scheme (case-insensitive).
+fn is_https_url(url: &[u8]) -> bool {
+    url.len() >= 8 && url[..8].eq_ignore_ascii_case(b"https://")
 }
@@ -197,6 +197,9 @@ pub unsafe fn hw_write8(addr: u32, value: u8) {
 /// const GPIO_READ: Register<u32> = Register::new(0xBE24_0004);
 ///
 /// let value = unsafe { GPIO_READ.read() };
+///
+/// const PERIPH_CLK1: Register<u32> = Register::new(psp::hw::SYSREG_PERIPH_CLK1);
+/// unsafe { PERIPH_CLK1.modify(|v| v | 1 << 8) };
 /// ```
 pub struct Register<T: Copy> {
     addr: u32,
@@ -240,6 +243,22 @@ impl Register<u32> {
     pub unsafe fn write(&self, value: u32) {
         unsafe { core::ptr::write_volatile(self.addr as *mut u32, value) };
     }
+
+    /// Read the register, pass the value to `f`, and write back what it
+    /// returns.
+    ///
+    /// The read and write are separate bus accesses, so an interrupt
+    /// handler that writes the same register in between loses its change.
+    /// Suspend interrupts around the call if that matters.
+    ///
+    /// # Safety
+    ///
+    /// The register address must be valid, and the caller must be
+    /// in kernel mode.
+    #[inline(always)]
+    pub unsafe fn modify(&self, f: impl FnOnce(u32) -> u32) {
+        unsafe { self.write(f(self.read())) };
+    }
 }
 
 impl Register<u16> {
@@ -264,6 +283,20 @@ impl Register<u16> {
     pub unsafe fn write(&self, value: u16) {
         unsafe { core::ptr::write_volatile(self.addr as *mut u16, value) };
     }
+
+    /// Read the register, pass the value to `f`, and write back what it
+    /// returns.
+    ///
+    /// Not atomic; see `Register<u32>::modify`.
+    ///
+    /// # Safety
+    ///
+    /// The register address must be valid and 2-byte aligned, and the
+    /// caller must be in kernel mode.
+    #[inline(always)]
+    pub unsafe fn modify(&self, f: impl FnOnce(u16) -> u16) {
+        unsafe { self.write(f(self.read())) };
+    }
 }
 
 impl Register<u8> {
@@ -288,4 +321,100 @@ impl Register<u8> {
     pub unsafe fn write(&self, value: u8) {
         unsafe { core::ptr::write_volatile(self.addr as *mut u8, value) };
     }
+
+    /// Read the register, pass the value to `f`, and write back what it
+    /// returns.
+    ///
+    /// Not atomic; see `Register<u32>::modify`.
+    ///
+    /// # Safety
+    ///
+    /// The register address must be valid, and the caller must be
+    /// in kernel mode.
+    #[inline(always)]
+    pub unsafe fn modify(&self, f: impl FnOnce(u8) -> u8) {
+        unsafe { self.write(f(self.read())) };
+    }
+}
+
+// ── LEDs ────────────────────────────────────────────────────────────
+
+/// A front-panel LED.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Led {
+    /// Memory Stick access LED, on GPIO pin 6.
+    MemoryStick,
+    /// WLAN activity LED, on GPIO pin 7.
+    Wlan,
+    /// Power LED, controlled by Syscon.
+    Power,
+    /// Bluetooth LED (PSP Go only), controlled by Syscon.
+    Bluetooth,
+}
+
+impl Led {
+    /// The GPIO pin driving this LED, if it is GPIO-driven.
+    pub fn gpio_pin(self) -> Option<u32> {
+        match self {
+            Self::MemoryStick => Some(crate::gpio::pins::MS_LED),
+            Self::Wlan => Some(crate::gpio::pins::WLAN_LED),
+            Self::Power | Self::Bluetooth => None,
+        }
+    }
+
+    /// The LED number `sceSysconCtrlLED` takes.
+    pub fn syscon_index(self) -> u32 {
+        match self {
+            Self::MemoryStick => 0,
+            Self::Wlan => 1,
+            Self::Power => 2,
+            Self::Bluetooth => 3,
+        }
+    }
+}
+
+/// Error from [`led_set`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LedError {
+    /// The LED is driven by Syscon, and `sceSysconCtrlLED` isn't resolved
+    /// (see [`syscon::init`](crate::syscon::init)).
+    Unsupported(Led),
+    /// `sceSysconCtrlLED` failed.
+    Syscon(crate::syscon::SysconError),
+}
+
+impl core::fmt::Display for LedError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Unsupported(led) => write!(f, "{led:?} LED can't be switched without Syscon"),
+            Self::Syscon(e) => write!(f, "LED switch failed: {e}"),
+        }
+    }
+}
+
+/// Turn `led` on or off.
+///
+/// The Memory Stick and WLAN LEDs are switched directly through the GPIO
+/// set/clear registers. The power and Bluetooth LEDs go through
+/// [`syscon::set_led`](crate::syscon::set_led), which needs
+/// [`syscon::init`](crate::syscon::init) first; for them this fails with
+/// [`LedError::Unsupported`] if the Syscon function isn't resolved.
+///
+/// The firmware drives these LEDs too (e.g. the Memory Stick LED during
+/// I/O), so a change may be overridden soon after.
+///
+/// # Safety
+///
+/// Caller must be in kernel mode.
+pub unsafe fn led_set(led: Led, on: bool) -> Result<(), LedError> {
+    match led.gpio_pin() {
+        Some(pin) => {
+            let reg = if on { GPIO_PORT_SET } else { GPIO_PORT_CLEAR };
+            unsafe { hw_write32(reg, 1 << pin) };
+            Ok(())
+        },
+        None => crate::syscon::set_led(led, on)
+            .ok_or(LedError::Unsupported(led))?
+            .map_err(LedError::Syscon),
+    }
 }
@@ -0,0 +1,66 @@
+//! Per-console identification.
+//!
+//! [`open_psid()`] reads the console's OpenPSID, a 16-byte ID that is
+//! unique to each console, and [`device_hash()`] condenses it to a `u64`
+//! for apps that only need a short, stable identifier (e.g. to tag
+//! online scores or to mix into a save obfuscation key).
+//!
+//! Under PPSSPP every install reports the same fixed OpenPSID, so it
+//! identifies nothing. Check [`is_unique()`] before relying on it.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use psp::ident;
+//!
+//! match ident::open_psid() {
+//!     Ok(psid) if ident::is_unique() => psp::dprintln!("PSID: {:02x?}", psid),
+//!     Ok(_) => psp::dprintln!("emulated PSID"),
+//!     Err(e) => psp::dprintln!("{}", e),
+//! }
+//! ```
+
+use crate::sys::{OpenPSID, sceOpenPSIDGetOpenPSID};
+
+/// Error from [`open_psid()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdentError {
+    /// The OpenPSID syscall failed with this SCE error code.
+    Unsupported(i32),
+}
+
+impl core::fmt::Display for IdentError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Unsupported(code) => write!(f, "OpenPSID unavailable: {:#010x}", *code as u32),
+        }
+    }
+}
+
+/// Read the console's 16-byte OpenPSID.
+pub fn open_psid() -> Result<[u8; 16], IdentError> {
+    let mut psid = OpenPSID { data: [0; 16] };
+    let ret = unsafe { sceOpenPSIDGetOpenPSID(&mut psid) };
+    if ret < 0 {
+        return Err(IdentError::Unsupported(ret));
+    }
+    Ok(psid.data)
+}
+
+/// A 64-bit identifier for the console: the
+/// [`fnv1a_64`](crate::hash::fnv1a_64) hash of its OpenPSID.
+///
+/// Stable across boots and firmware updates, but not secret: anyone with
+/// the console can compute it.
+pub fn device_hash() -> Result<u64, IdentError> {
+    open_psid().map(|psid| crate::hash::fnv1a_64(&psid))
+}
+
+/// Whether [`open_psid()`] identifies this particular console.
+///
+/// Returns `false` under PPSSPP (see
+/// [`model::is_emulator()`](crate::model::is_emulator)), which reports
+/// the same OpenPSID on every install.
+pub fn is_unique() -> bool {
+    !crate::model::is_emulator()
+}
@@ -0,0 +1,241 @@
+//! BMP decoding and encoding.
+//!
+//! Decodes uncompressed 8-bit (palettized), 24-bit and 32-bit bitmaps in
+//! either row order into [`DecodedImage`]s with [`PixelFormat::Rgba8888`]
+//! pixels, and encodes images as 32-bit bottom-up bitmaps. The encoder is
+//! also used by [`crate::screenshot_bmp`].
+//!
+//! # Example
+//!
+//! ```ignore
+//! use psp::image::bmp;
+//!
+//! let image = bmp::decode(&psp::io::read_to_vec("ms0:/sprite.bmp")?)?;
+//! let bytes = bmp::encode(&image);
+//! ```
+
+use alloc::vec::Vec;
+
+use super::{DecodedImage, PixelFormat};
+
+/// Size of the file header plus the `BITMAPINFOHEADER`.
+pub const HEADER_SIZE: usize = 54;
+
+/// Error from [`decode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BmpError {
+    /// The data is shorter than the headers.
+    TooSmall,
+    /// The file does not start with `BM`.
+    BadMagic,
+    /// The DIB header is older than `BITMAPINFOHEADER`.
+    UnsupportedHeader,
+    /// Width or height is zero or too large.
+    InvalidDimensions,
+    /// RLE or bitfield compression is not supported.
+    Compressed,
+    /// Only 8, 24 and 32 bits per pixel are supported.
+    UnsupportedBitDepth(u16),
+    /// The color table of a palettized image is missing or truncated.
+    BadPalette,
+    /// The pixel data ends before the last row.
+    Truncated,
+}
+
+impl BmpError {
+    /// Short description of the error.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::TooSmall => "file too small",
+            Self::BadMagic => "bad magic",
+            Self::UnsupportedHeader => "unsupported DIB header",
+            Self::InvalidDimensions => "invalid dimensions",
+            Self::Compressed => "compressed BMPs not supported",
+            Self::UnsupportedBitDepth(_) => "only 8/24/32-bit supported",
+            Self::BadPalette => "invalid color table",
+            Self::Truncated => "unexpected end of data",
+        }
+    }
+}
+
+impl core::fmt::Display for BmpError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::UnsupportedBitDepth(bpp) => write!(f, "unsupported BMP bit depth {bpp}"),
+            _ => write!(f, "invalid BMP: {}", self.as_str()),
+        }
+    }
+}
+
+/// Decode an uncompressed 8-bit, 24-bit or 32-bit BMP.
+///
+/// The output is always [`PixelFormat::Rgba8888`], top row first. 24-bit
+/// and palettized images get an opaque alpha channel; 32-bit images keep
+/// the alpha byte stored in the file.
+pub fn decode(data: &[u8]) -> Result<DecodedImage, BmpError> {
+    if data.len() < HEADER_SIZE {
+        return Err(BmpError::TooSmall);
+    }
+    if data[0] != b'B' || data[1] != b'M' {
+        return Err(BmpError::BadMagic);
+    }
+
+    let data_offset = read_u32_le(data, 10) as usize;
+    let dib_size = read_u32_le(data, 14) as usize;
+    if dib_size < 40 {
+        return Err(BmpError::UnsupportedHeader);
+    }
+
+    let width = read_i32_le(data, 18);
+    let height_raw = read_i32_le(data, 22);
+    let top_down = height_raw < 0;
+    let height = height_raw.unsigned_abs() as usize;
+    if width <= 0 || height == 0 {
+        return Err(BmpError::InvalidDimensions);
+    }
+    let width = width as usize;
+
+    let bpp = read_u16_le(data, 28);
+    if read_u32_le(data, 30) != 0 {
+        return Err(BmpError::Compressed);
+    }
+
+    let palette = match bpp {
+        8 => {
+            let count = match read_u32_le(data, 46) {
+                0 => 256,
+                n => (n as usize).min(256),
+            };
+            let start = dib_size
+                .checked_add(14)
+                .ok_or(BmpError::UnsupportedHeader)?;
+            let end = start.checked_add(count * 4).ok_or(BmpError::BadPalette)?;
+            data.get(start..end).ok_or(BmpError::BadPalette)?
+        },
+        24 | 32 => &[][..],
+        _ => return Err(BmpError::UnsupportedBitDepth(bpp)),
+    };
+
+    let bytes_pp = bpp as usize / 8;
+    let row_stride = width
+        .checked_mul(bpp as usize)
+        .ok_or(BmpError::InvalidDimensions)?
+        .div_ceil(32)
+        * 4;
+    let out_len = width
+        .checked_mul(height)
+        .and_then(|n| n.checked_mul(4))
+        .ok_or(BmpError::InvalidDimensions)?;
+    // Every row must be in the file before sizing the output by the
+    // header, which may claim far more pixels than the data holds.
+    let pixels_end = (height - 1)
+        .checked_mul(row_stride)
+        .and_then(|n| n.checked_add(width * bytes_pp))
+        .and_then(|n| n.checked_add(data_offset))
+        .ok_or(BmpError::InvalidDimensions)?;
+    if pixels_end > data.len() {
+        return Err(BmpError::Truncated);
+    }
+    let mut output = alloc::vec![0u8; out_len];
+
+    for y in 0..height {
+        let src_y = if top_down { y } else { height - 1 - y };
+        let src_offset = data_offset + src_y * row_stride;
+        let row = data
+            .get(src_offset..src_offset + width * bytes_pp)
+            .ok_or(BmpError::Truncated)?;
+        let dst = &mut output[y * width * 4..(y + 1) * width * 4];
+
+        for (px, out) in row.chunks_exact(bytes_pp).zip(dst.chunks_exact_mut(4)) {
+            // BMP stores BGR(A); convert to RGBA.
+            let bgra = match bpp {
+                8 => {
+                    let i = px[0] as usize * 4;
+                    let entry = palette.get(i..i + 3).ok_or(BmpError::BadPalette)?;
+                    [entry[0], entry[1], entry[2], 0xFF]
+                },
+                24 => [px[0], px[1], px[2], 0xFF],
+                _ => [px[0], px[1], px[2], px[3]],
+            };
+            out.copy_from_slice(&[bgra[2], bgra[1], bgra[0], bgra[3]]);
+        }
+    }
+
+    Ok(DecodedImage {
+        width: width as u32,
+        height: height as u32,
+        format: PixelFormat::Rgba8888,
+        data: output,
+    })
+}
+
+/// Encode an image as a 32-bit, bottom-up BMP.
+///
+/// [`PixelFormat::Rgb888`] images are written with opaque alpha.
+pub fn encode(image: &DecodedImage) -> Vec<u8> {
+    let width = image.width as usize;
+    let bytes_pp = match image.format {
+        PixelFormat::Rgba8888 => 4,
+        PixelFormat::Rgb888 => 3,
+    };
+    encode_with(image.width, image.height, |x, y| {
+        let i = (y as usize * width + x as usize) * bytes_pp;
+        let p = &image.data[i..i + bytes_pp];
+        let a = if bytes_pp == 4 { p[3] } else { 0xFF };
+        [p[2], p[1], p[0], a]
+    })
+}
+
+/// Write a 32-bit, bottom-up BMP, reading each pixel as BGRA bytes from
+/// `pixel(x, y)` with `y = 0` at the top.
+pub(crate) fn encode_with(
+    width: u32,
+    height: u32,
+    mut pixel: impl FnMut(u32, u32) -> [u8; 4],
+) -> Vec<u8> {
+    let image_len = width * height * 4;
+    let mut out = Vec::with_capacity(HEADER_SIZE + image_len as usize);
+
+    // BITMAPFILEHEADER
+    out.extend_from_slice(b"BM");
+    out.extend_from_slice(&(HEADER_SIZE as u32 + image_len).to_le_bytes());
+    out.extend_from_slice(&[0; 4]); // reserved
+    out.extend_from_slice(&(HEADER_SIZE as u32).to_le_bytes());
+
+    // BITMAPINFOHEADER
+    out.extend_from_slice(&40u32.to_le_bytes());
+    out.extend_from_slice(&width.to_le_bytes());
+    out.extend_from_slice(&height.to_le_bytes()); // positive: bottom-up
+    out.extend_from_slice(&1u16.to_le_bytes()); // color planes
+    out.extend_from_slice(&32u16.to_le_bytes()); // bpp
+    out.extend_from_slice(&0u32.to_le_bytes()); // BI_RGB
+    out.extend_from_slice(&image_len.to_le_bytes());
+    out.extend_from_slice(&2835u32.to_le_bytes()); // 72 DPI
+    out.extend_from_slice(&2835u32.to_le_bytes()); // 72 DPI
+    out.extend_from_slice(&0u32.to_le_bytes()); // palette colors
+    out.extend_from_slice(&0u32.to_le_bytes()); // important colors
+
+    for y in (0..height).rev() {
+        for x in 0..width {
+            out.extend_from_slice(&pixel(x, y));
+        }
+    }
+    out
+}
+
+fn read_u16_le(data: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([data[offset], data[offset + 1]])
+}
+
+fn read_u32_le(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([
+        data[offset],
+        data[offset + 1],
+        data[offset + 2],
+        data[offset + 3],
+    ])
+}
+
+fn read_i32_le(data: &[u8], offset: usize) -> i32 {
+    read_u32_le(data, offset) as i32
+}
@@ -0,0 +1,153 @@
+//! Pixel-perfect collision masks.
+
+use alloc::vec::Vec;
+
+use super::{DecodedImage, PixelFormat};
+
+/// One bit per pixel marking which pixels of an image are solid.
+///
+/// Rows are packed into `u32` words, so [`overlaps`](Self::overlaps)
+/// compares 32 pixels at a time.
+///
+/// # Example
+///
+/// ```ignore
+/// use psp::image::{self, CollisionMask};
+///
+/// let ship = CollisionMask::from_image(&image::load("ms0:/ship.bmp")?, 127);
+/// let rock = CollisionMask::from_image(&image::load("ms0:/rock.bmp")?, 127);
+/// // Rock's top-left corner relative to the ship's.
+/// if ship.overlaps(&rock, rock_x - ship_x, rock_y - ship_y) {
+///     explode();
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CollisionMask {
+    width: u32,
+    height: u32,
+    /// Words per row.
+    stride: usize,
+    /// Bit `i` of word `w` in a row is pixel `w * 32 + i`. Bits past the
+    /// width are always clear.
+    bits: Vec<u32>,
+}
+
+impl CollisionMask {
+    /// Build a mask of the pixels whose alpha is above `alpha_threshold`.
+    ///
+    /// [`Rgb888`](PixelFormat::Rgb888) images have no alpha, so every
+    /// pixel is solid. Pixels missing from a short `data` buffer are
+    /// empty.
+    pub fn from_image(img: &DecodedImage, alpha_threshold: u8) -> Self {
+        let mut mask = Self::empty(img.width, img.height);
+        let bpp = match img.format {
+            PixelFormat::Rgba8888 => 4,
+            PixelFormat::Rgb888 => 3,
+        };
+        let row_bytes = img.width as usize * bpp;
+        for y in 0..img.height as usize {
+            let Some(row) = img.data.get(y * row_bytes..(y + 1) * row_bytes) else {
+                break;
+            };
+            for (x, px) in row.chunks_exact(bpp).enumerate() {
+                if bpp == 3 || px[3] > alpha_threshold {
+                    mask.bits[y * mask.stride + x / 32] |= 1 << (x % 32);
+                }
+            }
+        }
+        mask
+    }
+
+    /// A `width` x `height` mask with no solid pixels.
+    pub fn empty(width: u32, height: u32) -> Self {
+        let stride = (width as usize).div_ceil(32);
+        Self {
+            width,
+            height,
+            stride,
+            bits: alloc::vec![0; stride * height as usize],
+        }
+    }
+
+    /// Width in pixels.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Height in pixels.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Mark a pixel solid or empty. Out-of-bounds coordinates are ignored.
+    pub fn set(&mut self, x: i32, y: i32, solid: bool) {
+        if let Some((index, bit)) = self.locate(x, y) {
+            if solid {
+                self.bits[index] |= bit;
+            } else {
+                self.bits[index] &= !bit;
+            }
+        }
+    }
+
+    /// Whether the pixel at `(x, y)` is solid. Anything outside the mask
+    /// is empty.
+    pub fn test_point(&self, x: i32, y: i32) -> bool {
+        self.locate(x, y)
+            .is_some_and(|(index, bit)| self.bits[index] & bit != 0)
+    }
+
+    /// Whether any solid pixel of this mask touches one of `other`'s, with
+    /// `other`'s top-left corner at `(dx, dy)` relative to this mask's.
+    pub fn overlaps(&self, other: &CollisionMask, dx: i32, dy: i32) -> bool {
+        // Overlapping rectangle in this mask's coordinates.
+        let x0 = dx.max(0) as i64;
+        let y0 = dy.max(0) as i64;
+        let x1 = (self.width as i64).min(dx as i64 + other.width as i64);
+        let y1 = (self.height as i64).min(dy as i64 + other.height as i64);
+        if x0 >= x1 || y0 >= y1 {
+            return false;
+        }
+
+        for y in y0..y1 {
+            let own = self.row(y as usize);
+            let theirs = other.row((y - dy as i64) as usize);
+            let mut x = x0;
+            while x < x1 {
+                let n = (x1 - x).min(32) as u32;
+                let keep = if n == 32 { u32::MAX } else { (1 << n) - 1 };
+                let a = bits_at(own, x as usize);
+                let b = bits_at(theirs, (x - dx as i64) as usize);
+                if a & b & keep != 0 {
+                    return true;
+                }
+                x += 32;
+            }
+        }
+        false
+    }
+
+    fn row(&self, y: usize) -> &[u32] {
+        &self.bits[y * self.stride..(y + 1) * self.stride]
+    }
+
+    fn locate(&self, x: i32, y: i32) -> Option<(usize, u32)> {
+        if x < 0 || y < 0 || x as u32 >= self.width || y as u32 >= self.height {
+            return None;
+        }
+        let (x, y) = (x as usize, y as usize);
+        Some((y * self.stride + x / 32, 1 << (x % 32)))
+    }
+}
+
+/// The 32 pixels of `row` starting at pixel `x`, with pixels past the end
+/// empty.
+fn bits_at(row: &[u32], x: usize) -> u32 {
+    let (word, shift) = (x / 32, x % 32);
+    let lo = row.get(word).map_or(0, |w| w >> shift);
+    let hi = match shift {
+        0 => 0,
+        _ => row.get(word + 1).map_or(0, |w| w << (32 - shift)),
+    };
+    lo | hi
+}
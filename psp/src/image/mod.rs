@@ -0,0 +1,239 @@
+//! Image decoding for the PSP.
+//!
+//! Supports hardware-accelerated JPEG decoding via `sceJpeg*`, one-off or
+//! with a reusable [`JpegDecoder`], and software BMP decoding and encoding
+//! (see [`bmp`]). [`CollisionMask`]
+//! turns a decoded image into a bitmask for pixel-perfect collision.
+
+pub mod bmp;
+mod collision;
+
+pub use collision::CollisionMask;
+
+use alloc::vec::Vec;
+use core::ffi::c_void;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// Pixel format of decoded image data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PixelFormat {
+    /// 4 bytes per pixel: R, G, B, A.
+    Rgba8888,
+    /// 3 bytes per pixel: R, G, B.
+    Rgb888,
+}
+
+/// A decoded image in memory.
+pub struct DecodedImage {
+    pub width: u32,
+    pub height: u32,
+    pub format: PixelFormat,
+    pub data: Vec<u8>,
+}
+
+/// Error from an image operation.
+pub enum ImageError {
+    /// Could not determine image format from magic bytes.
+    UnknownFormat,
+    /// Hardware JPEG decode error (SCE error code).
+    JpegError(i32),
+    /// A [`JpegDecoder`] already holds the firmware's MJPEG context.
+    JpegBusy,
+    /// BMP parsing error.
+    InvalidBmp(&'static str),
+    /// I/O error loading from file.
+    Io(crate::io::IoError),
+}
+
+impl core::fmt::Debug for ImageError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::UnknownFormat => write!(f, "ImageError::UnknownFormat"),
+            Self::JpegError(e) => write!(f, "ImageError::JpegError({e:#010x})"),
+            Self::JpegBusy => write!(f, "ImageError::JpegBusy"),
+            Self::InvalidBmp(msg) => write!(f, "ImageError::InvalidBmp({msg:?})"),
+            Self::Io(e) => write!(f, "ImageError::Io({e:?})"),
+        }
+    }
+}
+
+impl core::fmt::Display for ImageError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::UnknownFormat => write!(f, "unknown image format"),
+            Self::JpegError(e) => write!(f, "JPEG decode error {e:#010x}"),
+            Self::JpegBusy => write!(f, "JPEG decoder already in use"),
+            Self::InvalidBmp(msg) => write!(f, "invalid BMP: {msg}"),
+            Self::Io(e) => write!(f, "image I/O error: {e}"),
+        }
+    }
+}
+
+impl From<bmp::BmpError> for ImageError {
+    fn from(e: bmp::BmpError) -> Self {
+        Self::InvalidBmp(e.as_str())
+    }
+}
+
+impl From<crate::io::IoError> for ImageError {
+    fn from(e: crate::io::IoError) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// Auto-detect format from magic bytes and decode.
+pub fn decode(data: &[u8]) -> Result<DecodedImage, ImageError> {
+    if data.len() >= 2 {
+        if data[0] == 0xFF && data[1] == 0xD8 {
+            // JPEG: use 1024x1024 as default max size.
+            return decode_jpeg(data, 1024, 1024);
+        }
+        if data[0] == b'B' && data[1] == b'M' {
+            return decode_bmp(data);
+        }
+    }
+    Err(ImageError::UnknownFormat)
+}
+
+/// Decode a JPEG image using PSP hardware.
+///
+/// `max_width` and `max_height` specify the maximum output dimensions.
+/// The JPEG must fit within these bounds. To decode many images, such as
+/// camera frames, keep a [`JpegDecoder`] instead.
+///
+/// Fails with [`ImageError::JpegBusy`] while a [`JpegDecoder`] exists.
+pub fn decode_jpeg(
+    data: &[u8],
+    max_width: i32,
+    max_height: i32,
+) -> Result<DecodedImage, ImageError> {
+    JpegDecoder::new(max_width, max_height)?.decode(data)
+}
+
+/// A hardware JPEG decoder kept open across images.
+///
+/// Creating the decoder loads the `AvCodec` module and sets up the
+/// firmware's MJPEG context, which [`decode_jpeg`] redoes on every call.
+/// The firmware has a single context, so only one decoder can exist at a
+/// time; creating a second one, or calling [`decode_jpeg`] while one
+/// exists, fails with [`ImageError::JpegBusy`].
+///
+/// # Example
+///
+/// ```ignore
+/// use psp::image::JpegDecoder;
+///
+/// let mut decoder = JpegDecoder::new(480, 272)?;
+/// let mut pixels = vec![0u32; 480 * 272];
+/// loop {
+///     let len = camera.capture_frame(&mut jpeg)?;
+///     let (width, height) = decoder.decode_into(&jpeg[..len], &mut pixels)?;
+///     // ... draw `pixels` ...
+/// }
+/// ```
+pub struct JpegDecoder {
+    max_width: i32,
+    max_height: i32,
+}
+
+/// Set while a [`JpegDecoder`] holds the MJPEG context.
+static JPEG_IN_USE: AtomicBool = AtomicBool::new(false);
+
+impl JpegDecoder {
+    /// Set up a decoder for images of at most `max_width` x `max_height`.
+    pub fn new(max_width: i32, max_height: i32) -> Result<Self, ImageError> {
+        if JPEG_IN_USE
+            .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            return Err(ImageError::JpegBusy);
+        }
+        Self::create(max_width, max_height)
+            .inspect_err(|_| JPEG_IN_USE.store(false, Ordering::Release))
+    }
+
+    /// [`new`](Self::new) once `JPEG_IN_USE` is taken.
+    fn create(max_width: i32, max_height: i32) -> Result<Self, ImageError> {
+        crate::utility::load_module(crate::utility::UtilityModule::AvCodec)
+            .map_err(|e| ImageError::JpegError(e.code))?;
+
+        let ret = unsafe { crate::sys::sceJpegInitMJpeg() };
+        if ret < 0 {
+            let _ = crate::utility::unload_module(crate::utility::UtilityModule::AvCodec);
+            return Err(ImageError::JpegError(ret));
+        }
+
+        let ret = unsafe { crate::sys::sceJpegCreateMJpeg(max_width, max_height) };
+        if ret < 0 {
+            unsafe { crate::sys::sceJpegFinishMJpeg() };
+            let _ = crate::utility::unload_module(crate::utility::UtilityModule::AvCodec);
+            return Err(ImageError::JpegError(ret));
+        }
+
+        Ok(Self {
+            max_width,
+            max_height,
+        })
+    }
+
+    /// Decode `data` into a new RGBA image.
+    pub fn decode(&mut self, data: &[u8]) -> Result<DecodedImage, ImageError> {
+        let buf_size = self.max_width as usize * self.max_height as usize * 4;
+        let mut output = alloc::vec![0u8; buf_size];
+        let (width, height) = self.decode_raw(data, output.as_mut_ptr() as *mut c_void)?;
+        output.truncate((width * height * 4) as usize);
+
+        Ok(DecodedImage {
+            width,
+            height,
+            format: PixelFormat::Rgba8888,
+            data: output,
+        })
+    }
+
+    /// Decode `data` into `out` as `0xAABBGGRR` pixels, rows packed at
+    /// the image's width, and return the image's width and height.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `out` holds fewer than `max_width * max_height` pixels.
+    pub fn decode_into(&mut self, data: &[u8], out: &mut [u32]) -> Result<(u32, u32), ImageError> {
+        assert!(
+            out.len() >= self.max_width as usize * self.max_height as usize,
+            "JPEG output buffer too small"
+        );
+        self.decode_raw(data, out.as_mut_ptr() as *mut c_void)
+    }
+
+    /// Decode into `out`, which must hold `max_width * max_height` pixels.
+    fn decode_raw(&mut self, data: &[u8], out: *mut c_void) -> Result<(u32, u32), ImageError> {
+        let ret =
+            unsafe { crate::sys::sceJpegDecodeMJpeg(data.as_ptr() as *mut u8, data.len(), out, 0) };
+        if ret < 0 {
+            return Err(ImageError::JpegError(ret));
+        }
+        Ok((((ret >> 16) & 0xFFFF) as u32, (ret & 0xFFFF) as u32))
+    }
+}
+
+impl Drop for JpegDecoder {
+    fn drop(&mut self) {
+        unsafe {
+            crate::sys::sceJpegDeleteMJpeg();
+            crate::sys::sceJpegFinishMJpeg();
+        }
+        let _ = crate::utility::unload_module(crate::utility::UtilityModule::AvCodec);
+        JPEG_IN_USE.store(false, Ordering::Release);
+    }
+}
+
+/// Decode an uncompressed BMP. See [`bmp::decode`].
+pub fn decode_bmp(data: &[u8]) -> Result<DecodedImage, ImageError> {
+    Ok(bmp::decode(data)?)
+}
+
+/// Load an image from a file path (auto-detect format).
+pub fn load(path: &str) -> Result<DecodedImage, ImageError> {
+    let data = crate::io::read_to_vec(path)?;
+    decode(&data)
+}
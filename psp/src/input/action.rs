@@ -0,0 +1,341 @@
+//! Mapping physical buttons and the analog stick to named game actions.
+
+use alloc::format;
+use alloc::vec::Vec;
+
+use super::{Controller, apply_deadzone};
+use crate::config::{Config, ConfigValue};
+use crate::sys::CtrlButtons;
+
+/// One frame of controller state, as evaluated by an [`ActionMap`].
+///
+/// Build one with [`Controller::snapshot()`], or fill in the fields
+/// directly to drive an `ActionMap` without hardware.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InputSnapshot {
+    /// Buttons held this frame.
+    pub held: CtrlButtons,
+    /// Buttons held last frame.
+    pub previous: CtrlButtons,
+    /// Stick X in -1.0..=1.0 (right is positive), before any deadzone.
+    pub x: f32,
+    /// Stick Y in -1.0..=1.0 (down is positive), before any deadzone.
+    pub y: f32,
+}
+
+impl Controller {
+    /// This frame's state for an [`ActionMap`].
+    ///
+    /// The stick position includes [smoothing](Self::set_analog_smoothing)
+    /// but no deadzone; the action map applies its own.
+    pub fn snapshot(&self) -> InputSnapshot {
+        InputSnapshot {
+            held: self.buttons(),
+            previous: self.previous_buttons(),
+            x: self.smoothed_x,
+            y: self.smoothed_y,
+        }
+    }
+}
+
+/// An analog stick axis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    /// Horizontal, right is positive.
+    X,
+    /// Vertical, down is positive.
+    Y,
+}
+
+/// A physical input an action is bound to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Binding {
+    /// A button, or a chord of buttons that must all be held.
+    Buttons(CtrlButtons),
+    /// A stick axis, multiplied by a scale (negative to invert).
+    Analog(Axis, f32),
+}
+
+/// Two actions bound to the same button chord, found by
+/// [`ActionMap::conflicts()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Conflict<A> {
+    pub first: A,
+    pub second: A,
+    pub buttons: CtrlButtons,
+}
+
+/// Default deadzone applied to analog bindings.
+pub const DEFAULT_DEADZONE: f32 = 0.2;
+
+const TAG_BUTTONS: u8 = 0;
+const TAG_ANALOG_X: u8 = 1;
+const TAG_ANALOG_Y: u8 = 2;
+
+/// Maps the game's actions to buttons, chords and stick axes.
+///
+/// `A` is the game's action type, typically a fieldless enum. An action
+/// may have any number of bindings and is active when any one of them is.
+/// Game code queries actions instead of buttons, so remapping controls is
+/// a change to the map rather than to every call site.
+///
+/// # Example
+///
+/// ```ignore
+/// use psp::input::{ActionMap, Axis, Controller};
+/// use psp::sys::CtrlButtons;
+///
+/// #[derive(Clone, Copy, PartialEq, Eq)]
+/// enum Action { Jump, Pause, Move, Special }
+///
+/// let mut actions = ActionMap::new();
+/// actions.bind(Action::Jump, CtrlButtons::CROSS);
+/// actions.bind(Action::Pause, CtrlButtons::START);
+/// actions.bind(Action::Special, CtrlButtons::LTRIGGER | CtrlButtons::RTRIGGER);
+/// actions.bind_analog(Action::Move, Axis::X, 1.0);
+/// actions.bind(Action::Move, CtrlButtons::RIGHT);
+///
+/// loop {
+///     ctrl.update();
+///     let input = ctrl.snapshot();
+///     if actions.was_action_pressed(Action::Jump, &input) {
+///         player.jump();
+///     }
+///     player.walk(actions.action_value(Action::Move, &input));
+/// }
+/// ```
+pub struct ActionMap<A: Copy + Eq> {
+    bindings: Vec<(A, Binding)>,
+    deadzone: f32,
+}
+
+impl<A: Copy + Eq> ActionMap<A> {
+    /// Create a map with no bindings and the [`DEFAULT_DEADZONE`].
+    pub fn new() -> Self {
+        Self {
+            bindings: Vec::new(),
+            deadzone: DEFAULT_DEADZONE,
+        }
+    }
+
+    /// Bind `action` to `buttons`, in addition to its existing bindings.
+    ///
+    /// With several buttons (e.g. `LTRIGGER | RTRIGGER`) this is a chord
+    /// that is active only while all of them are held.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buttons` is empty.
+    pub fn bind(&mut self, action: A, buttons: CtrlButtons) {
+        assert!(!buttons.is_empty(), "binding needs at least one button");
+        self.bindings.push((action, Binding::Buttons(buttons)));
+    }
+
+    /// Bind `action` to a stick axis, in addition to its existing bindings.
+    ///
+    /// The action's [value](Self::action_value) is the axis position times
+    /// `scale`; use `-1.0` to invert the axis.
+    pub fn bind_analog(&mut self, action: A, axis: Axis, scale: f32) {
+        self.bindings.push((action, Binding::Analog(axis, scale)));
+    }
+
+    /// Remove all of `action`'s bindings.
+    pub fn unbind(&mut self, action: A) {
+        self.bindings.retain(|(a, _)| *a != action);
+    }
+
+    /// Remove all bindings of `action` and bind it to `bindings` instead.
+    pub fn rebind(&mut self, action: A, bindings: &[Binding]) {
+        self.unbind(action);
+        self.bindings
+            .extend(bindings.iter().map(|&binding| (action, binding)));
+    }
+
+    /// The bindings of `action`, in the order they were added.
+    pub fn bindings(&self, action: A) -> impl Iterator<Item = Binding> + '_ {
+        self.bindings
+            .iter()
+            .filter(move |(a, _)| *a == action)
+            .map(|&(_, binding)| binding)
+    }
+
+    /// Set the deadzone for analog bindings, as a fraction of travel.
+    pub fn set_deadzone(&mut self, deadzone: f32) {
+        self.deadzone = deadzone.clamp(0.0, 0.99);
+    }
+
+    /// The deadzone for analog bindings.
+    pub fn deadzone(&self) -> f32 {
+        self.deadzone
+    }
+
+    /// Whether any button binding of `action` is held this frame.
+    ///
+    /// Analog bindings only contribute to [`action_value()`](Self::action_value).
+    pub fn is_action_pressed(&self, action: A, input: &InputSnapshot) -> bool {
+        self.is_held_in(action, input.held)
+    }
+
+    /// Whether `action` became pressed this frame.
+    ///
+    /// True when a button binding is held this frame but wasn't last
+    /// frame, so a chord fires once, when its last button goes down.
+    pub fn was_action_pressed(&self, action: A, input: &InputSnapshot) -> bool {
+        self.is_held_in(action, input.held) && !self.is_held_in(action, input.previous)
+    }
+
+    /// The strength of `action`: its analog bindings' scaled axis
+    /// positions after the deadzone, or 1.0 for a held button binding,
+    /// whichever is largest in magnitude. 0.0 when inactive.
+    pub fn action_value(&self, action: A, input: &InputSnapshot) -> f32 {
+        self.bindings(action)
+            .map(|binding| match binding {
+                Binding::Buttons(buttons) if input.held.contains(buttons) => 1.0,
+                Binding::Buttons(_) => 0.0,
+                Binding::Analog(axis, scale) => {
+                    let raw = match axis {
+                        Axis::X => input.x,
+                        Axis::Y => input.y,
+                    };
+                    apply_deadzone(raw, self.deadzone) * scale
+                },
+            })
+            .fold(0.0, |best, v| if v.abs() > best.abs() { v } else { best })
+    }
+
+    /// Pairs of different actions bound to the same button chord.
+    ///
+    /// Check defaults and user remappings with this, and warn about (or
+    /// refuse) any conflict it reports. Each pair is reported once, with
+    /// `first` being the action bound earlier.
+    pub fn conflicts(&self) -> Vec<Conflict<A>> {
+        let mut conflicts = Vec::new();
+        for (i, &(first, a)) in self.bindings.iter().enumerate() {
+            let Binding::Buttons(buttons) = a else {
+                continue;
+            };
+            for &(second, b) in &self.bindings[i + 1..] {
+                if second != first
+                    && b == a
+                    && !conflicts.contains(&Conflict {
+                        first,
+                        second,
+                        buttons,
+                    })
+                {
+                    conflicts.push(Conflict {
+                        first,
+                        second,
+                        buttons,
+                    });
+                }
+            }
+        }
+        conflicts
+    }
+
+    /// Store each action's bindings in `config` under `prefix` followed by
+    /// its name, e.g. `"input.jump"`.
+    ///
+    /// `name` gives each action a stable key; don't derive it from enum
+    /// discriminants that may be reordered.
+    pub fn save_to_config<F>(&self, config: &mut Config, prefix: &str, name: F)
+    where
+        F: Fn(A) -> &'static str,
+    {
+        let mut done: Vec<A> = Vec::new();
+        for &(action, _) in &self.bindings {
+            if done.contains(&action) {
+                continue;
+            }
+            done.push(action);
+            let mut bytes = Vec::new();
+            for binding in self.bindings(action) {
+                match binding {
+                    Binding::Buttons(buttons) => {
+                        bytes.push(TAG_BUTTONS);
+                        bytes.extend_from_slice(&buttons.bits().to_le_bytes());
+                    },
+                    Binding::Analog(axis, scale) => {
+                        bytes.push(match axis {
+                            Axis::X => TAG_ANALOG_X,
+                            Axis::Y => TAG_ANALOG_Y,
+                        });
+                        bytes.extend_from_slice(&scale.to_le_bytes());
+                    },
+                }
+            }
+            config.set(
+                &format!("{}{}", prefix, name(action)),
+                ConfigValue::Bytes(bytes),
+            );
+        }
+    }
+
+    /// Replace bindings with those stored by
+    /// [`save_to_config()`](Self::save_to_config).
+    ///
+    /// Only actions that already have bindings are looked up, so bind the
+    /// defaults first; an action whose entry is missing or malformed keeps
+    /// its defaults. Returns the number of actions that were loaded.
+    pub fn load_from_config<F>(&mut self, config: &Config, prefix: &str, name: F) -> usize
+    where
+        F: Fn(A) -> &'static str,
+    {
+        let mut actions: Vec<A> = Vec::new();
+        for &(action, _) in &self.bindings {
+            if !actions.contains(&action) {
+                actions.push(action);
+            }
+        }
+
+        let mut loaded = 0;
+        for action in actions {
+            let key = format!("{}{}", prefix, name(action));
+            let Some(ConfigValue::Bytes(bytes)) = config.get(&key) else {
+                continue;
+            };
+            if let Some(bindings) = decode_bindings(bytes) {
+                self.rebind(action, &bindings);
+                loaded += 1;
+            }
+        }
+        loaded
+    }
+
+    /// Whether a button binding of `action` is contained in `held`.
+    fn is_held_in(&self, action: A, held: CtrlButtons) -> bool {
+        self.bindings(action)
+            .any(|binding| matches!(binding, Binding::Buttons(buttons) if held.contains(buttons)))
+    }
+}
+
+impl<A: Copy + Eq> Default for ActionMap<A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Parse the encoding written by [`ActionMap::save_to_config()`]: a tag
+/// byte per binding followed by a little-endian `u32` button mask or
+/// `f32` scale.
+fn decode_bindings(bytes: &[u8]) -> Option<Vec<Binding>> {
+    let entries = bytes.chunks_exact(5);
+    if !entries.remainder().is_empty() {
+        return None;
+    }
+    entries
+        .map(|entry| {
+            let value = [entry[1], entry[2], entry[3], entry[4]];
+            match entry[0] {
+                TAG_BUTTONS => {
+                    let buttons = CtrlButtons::from_bits_truncate(u32::from_le_bytes(value));
+                    (!buttons.is_empty()).then_some(Binding::Buttons(buttons))
+                },
+                TAG_ANALOG_X => Some(Binding::Analog(Axis::X, f32::from_le_bytes(value))),
+                TAG_ANALOG_Y => Some(Binding::Analog(Axis::Y, f32::from_le_bytes(value))),
+                _ => None,
+            }
+        })
+        .collect()
+}
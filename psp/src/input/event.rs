@@ -0,0 +1,189 @@
+//! Controller state changes as a queue of discrete events.
+
+use alloc::collections::VecDeque;
+
+use super::action::{DEFAULT_DEADZONE, InputSnapshot};
+use super::{Controller, apply_radial_deadzone};
+use crate::sys::CtrlButtons;
+
+/// A change in controller state, produced by an [`EventQueue`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InputEvent {
+    /// A button was pressed. Holds a single button.
+    ButtonDown(CtrlButtons),
+    /// A button was released. Holds a single button.
+    ButtonUp(CtrlButtons),
+    /// The stick moved to `(x, y)`, each in -1.0..=1.0 after the radial
+    /// deadzone (right and down are positive). Returning to the deadzone
+    /// reports `(0.0, 0.0)`.
+    AnalogMoved(f32, f32),
+}
+
+/// Turns per-frame controller state into [`InputEvent`]s to drain.
+///
+/// Feed it the [`Controller`] after each [`Controller::update()`]. Each
+/// update queues a `ButtonUp` for every released button, then a
+/// `ButtonDown` for every pressed one, then an `AnalogMoved` if the stick
+/// has moved at least the [threshold](Self::set_analog_threshold) since
+/// the last reported position.
+///
+/// The queue holds at most [`capacity()`](Self::capacity) events; if
+/// nothing drains it, the oldest are dropped.
+///
+/// # Example
+///
+/// ```ignore
+/// use psp::input::{Controller, EventQueue, InputEvent};
+///
+/// let mut ctrl = Controller::new();
+/// let mut events = EventQueue::new();
+/// loop {
+///     ctrl.update();
+///     events.update(&ctrl);
+///     for event in events.drain() {
+///         match event {
+///             InputEvent::ButtonDown(b) => ui.on_button(b, true),
+///             InputEvent::ButtonUp(b) => ui.on_button(b, false),
+///             InputEvent::AnalogMoved(x, y) => ui.on_stick(x, y),
+///         }
+///     }
+/// }
+/// ```
+pub struct EventQueue {
+    events: VecDeque<InputEvent>,
+    capacity: usize,
+    deadzone: f32,
+    threshold: f32,
+    /// Last reported stick position, after the deadzone.
+    stick: (f32, f32),
+    dropped: usize,
+}
+
+impl EventQueue {
+    /// Default number of events kept before the oldest are dropped.
+    pub const DEFAULT_CAPACITY: usize = 64;
+
+    /// Default stick movement needed to report a new position.
+    pub const DEFAULT_ANALOG_THRESHOLD: f32 = 0.05;
+
+    /// Create an empty queue with the [`DEFAULT_CAPACITY`](Self::DEFAULT_CAPACITY),
+    /// [`DEFAULT_DEADZONE`] and
+    /// [`DEFAULT_ANALOG_THRESHOLD`](Self::DEFAULT_ANALOG_THRESHOLD).
+    pub fn new() -> Self {
+        Self::with_capacity(Self::DEFAULT_CAPACITY)
+    }
+
+    /// Create an empty queue that keeps at most `capacity` events.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn with_capacity(capacity: usize) -> Self {
+        assert!(capacity > 0, "event queue capacity must be non-zero");
+        Self {
+            events: VecDeque::with_capacity(capacity),
+            capacity,
+            deadzone: DEFAULT_DEADZONE,
+            threshold: Self::DEFAULT_ANALOG_THRESHOLD,
+            stick: (0.0, 0.0),
+            dropped: 0,
+        }
+    }
+
+    /// Set the radial deadzone applied to the stick, clamped to
+    /// `0.0..=0.99`.
+    pub fn set_deadzone(&mut self, deadzone: f32) {
+        self.deadzone = deadzone.clamp(0.0, 0.99);
+    }
+
+    /// The stick deadzone.
+    pub fn deadzone(&self) -> f32 {
+        self.deadzone
+    }
+
+    /// Set how far the stick must move, on either axis, before a new
+    /// `AnalogMoved` is queued. `0.0` reports every change.
+    pub fn set_analog_threshold(&mut self, threshold: f32) {
+        self.threshold = threshold.max(0.0);
+    }
+
+    /// The stick movement threshold.
+    pub fn analog_threshold(&self) -> f32 {
+        self.threshold
+    }
+
+    /// Queue the events for the controller's latest update.
+    pub fn update(&mut self, ctrl: &Controller) {
+        self.feed(&ctrl.snapshot());
+    }
+
+    /// Like [`update()`](Self::update), from an explicit snapshot.
+    pub fn feed(&mut self, input: &InputSnapshot) {
+        for button in (input.previous & !input.held).iter() {
+            self.push(InputEvent::ButtonUp(button));
+        }
+        for button in (input.held & !input.previous).iter() {
+            self.push(InputEvent::ButtonDown(button));
+        }
+
+        let (x, y) = apply_radial_deadzone(input.x, input.y, self.deadzone);
+        let (last_x, last_y) = self.stick;
+        let centered = x == 0.0 && y == 0.0;
+        let moved = (x - last_x).abs() >= self.threshold || (y - last_y).abs() >= self.threshold;
+        // Always report reaching the center so a UI doesn't drift.
+        if (x, y) != self.stick && (moved || centered) {
+            self.stick = (x, y);
+            self.push(InputEvent::AnalogMoved(x, y));
+        }
+    }
+
+    fn push(&mut self, event: InputEvent) {
+        if self.events.len() == self.capacity {
+            self.events.pop_front();
+            self.dropped += 1;
+        }
+        self.events.push_back(event);
+    }
+
+    /// Take the oldest event.
+    pub fn pop(&mut self) -> Option<InputEvent> {
+        self.events.pop_front()
+    }
+
+    /// Take every queued event, oldest first.
+    pub fn drain(&mut self) -> impl Iterator<Item = InputEvent> + '_ {
+        self.events.drain(..)
+    }
+
+    /// Number of queued events.
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Whether no events are queued.
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Maximum number of queued events.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of events dropped because the queue was full.
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+
+    /// Discard all queued events. The last reported stick position is
+    /// kept, so the stick isn't reported again until it moves.
+    pub fn clear(&mut self) {
+        self.events.clear();
+    }
+}
+
+impl Default for EventQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
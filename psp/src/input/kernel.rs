@@ -0,0 +1,228 @@
+//! Kernel-side controller access for CFW plugins.
+//!
+//! [`intercept_buttons`] hooks the controller buffer functions of
+//! `sceController_Service`, so a plugin can rewrite [`SceCtrlData`] after
+//! the driver fills it and before the game (or any other module) sees it.
+//! Button remappers, turbo buttons and input recorders are all built this
+//! way.
+//!
+//! For simply forcing buttons on or off, [`sceCtrlSetButtonIntercept`]
+//! needs no hook at all.
+//!
+//! [`read_extended`] reads the extended pad data of a PSP Go external
+//! controller, and [`set_sampling_mode`] calls the driver-level
+//! `sceCtrlSetSamplingMode`, which works regardless of the calling
+//! thread's privileges.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use psp::input::kernel::intercept_buttons;
+//! use psp::sys::{CtrlButtons, SceCtrlData};
+//!
+//! fn swap_cross_circle(pad: &mut SceCtrlData) {
+//!     let cross = pad.buttons.contains(CtrlButtons::CROSS);
+//!     let circle = pad.buttons.contains(CtrlButtons::CIRCLE);
+//!     pad.buttons.set(CtrlButtons::CROSS, circle);
+//!     pad.buttons.set(CtrlButtons::CIRCLE, cross);
+//! }
+//!
+//! unsafe { intercept_buttons(swap_cross_circle) }.unwrap();
+//! ```
+//!
+//! [`sceCtrlSetButtonIntercept`]: crate::sys::sceCtrlSetButtonIntercept
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::hook::{SyscallHook, find_function};
+use crate::sys::{CtrlButtons, CtrlMode, SceCtrlData, SceCtrlData2};
+
+const MODULE: &[u8] = b"sceController_Service\0";
+const USER_LIBRARY: &[u8] = b"sceCtrl\0";
+const DRIVER_LIBRARY: &[u8] = b"sceCtrl_driver\0";
+
+const NID_PEEK_POSITIVE: u32 = 0x3A622550;
+const NID_PEEK_NEGATIVE: u32 = 0xC152080A;
+const NID_READ_POSITIVE: u32 = 0x1F803938;
+const NID_READ_NEGATIVE: u32 = 0x60B81F86;
+const NID_SET_SAMPLING_MODE: u32 = 0x1F4011E6;
+
+/// A function that rewrites controller data in place.
+///
+/// Runs on whatever thread read the controller, often with a small stack
+/// and inside a game's frame loop, so keep it short and don't block.
+pub type InterceptFn = fn(&mut SceCtrlData);
+
+/// A controller function could not be hooked.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct InterceptError {
+    /// NID of the function that couldn't be hooked.
+    pub nid: u32,
+}
+
+impl core::fmt::Debug for InterceptError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "InterceptError(nid={:#010x})", self.nid)
+    }
+}
+
+impl core::fmt::Display for InterceptError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "failed to hook controller function {:#010x}", self.nid)
+    }
+}
+
+/// The installed [`InterceptFn`], or 0 for none.
+static INTERCEPT: AtomicUsize = AtomicUsize::new(0);
+
+/// One hook per buffer function, indexed like [`HOOKED`]. Inline hooks
+/// keep their trampoline inside the `SyscallHook`, so these must stay put.
+static mut HOOKS: [Option<SyscallHook>; 4] = [None, None, None, None];
+
+/// NID and replacement of each hooked function.
+const HOOKED: [(u32, unsafe extern "C" fn(*mut SceCtrlData, i32) -> i32); 4] = [
+    (NID_PEEK_POSITIVE, peek_positive),
+    (NID_PEEK_NEGATIVE, peek_negative),
+    (NID_READ_POSITIVE, read_positive),
+    (NID_READ_NEGATIVE, read_negative),
+];
+
+/// Route every controller read through `f`.
+///
+/// Hooks `sceCtrlPeekBuffer*` and `sceCtrlReadBuffer*` the first time it
+/// is called; later calls only swap the function. Negative reads are
+/// converted so `f` always sees pressed buttons as set bits. Latch reads
+/// (`sceCtrlReadLatch`) are not affected.
+///
+/// If a hook fails, the ones installed before it stay in place, and
+/// calling again retries the rest.
+///
+/// # Safety
+///
+/// Must be called from a kernel-mode module (see
+/// [`module_kernel!`](crate::module_kernel)) on custom firmware that
+/// provides `SystemCtrlForKernel`.
+pub unsafe fn intercept_buttons(f: InterceptFn) -> Result<(), InterceptError> {
+    INTERCEPT.store(f as usize, Ordering::Release);
+
+    // SAFETY: Only this function writes `HOOKS`, and with interrupts off
+    // no game thread can call a hook before its slot is filled.
+    unsafe {
+        for (i, &(nid, replacement)) in HOOKED.iter().enumerate() {
+            let slot = &raw mut HOOKS[i];
+            if (*slot).is_some() {
+                continue;
+            }
+            let intr = crate::sys::sceKernelCpuSuspendIntr();
+            *slot = SyscallHook::install(
+                MODULE.as_ptr(),
+                USER_LIBRARY.as_ptr(),
+                nid,
+                replacement as *mut u8,
+            );
+            crate::sys::sceKernelCpuResumeIntr(intr);
+            if (*slot).is_none() {
+                return Err(InterceptError { nid });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Stop rewriting controller data.
+///
+/// The hooks stay installed but pass data through unchanged.
+pub fn clear_intercept() {
+    INTERCEPT.store(0, Ordering::Release);
+}
+
+/// Call the original function for `slot`, then apply the intercept to the
+/// buffers it filled.
+///
+/// # Safety
+///
+/// Only called from the replacements in [`HOOKED`], with their arguments.
+unsafe fn call_hooked(slot: usize, pad_data: *mut SceCtrlData, count: i32, negative: bool) -> i32 {
+    // SAFETY: `intercept_buttons` fills a slot before its hook can run.
+    let ret = unsafe {
+        let hook = &raw const HOOKS[slot];
+        let hook = match (*hook).as_ref() {
+            Some(hook) => hook,
+            None => return -1,
+        };
+        let original: unsafe extern "C" fn(*mut SceCtrlData, i32) -> i32 =
+            core::mem::transmute(hook.original_ptr());
+        original(pad_data, count)
+    };
+
+    let f = INTERCEPT.load(Ordering::Acquire);
+    if f == 0 || ret <= 0 || pad_data.is_null() {
+        return ret;
+    }
+    // SAFETY: Only `InterceptFn`s are stored in `INTERCEPT`.
+    let f: InterceptFn = unsafe { core::mem::transmute(f) };
+    // SAFETY: The original function validated `pad_data` and filled `ret`
+    // buffers.
+    let pads = unsafe { core::slice::from_raw_parts_mut(pad_data, ret as usize) };
+    for pad in pads {
+        if negative {
+            pad.buttons = CtrlButtons::from_bits_retain(!pad.buttons.bits());
+        }
+        f(pad);
+        if negative {
+            pad.buttons = CtrlButtons::from_bits_retain(!pad.buttons.bits());
+        }
+    }
+    ret
+}
+
+unsafe extern "C" fn peek_positive(pad_data: *mut SceCtrlData, count: i32) -> i32 {
+    unsafe { call_hooked(0, pad_data, count, false) }
+}
+
+unsafe extern "C" fn peek_negative(pad_data: *mut SceCtrlData, count: i32) -> i32 {
+    unsafe { call_hooked(1, pad_data, count, true) }
+}
+
+unsafe extern "C" fn read_positive(pad_data: *mut SceCtrlData, count: i32) -> i32 {
+    unsafe { call_hooked(2, pad_data, count, false) }
+}
+
+unsafe extern "C" fn read_negative(pad_data: *mut SceCtrlData, count: i32) -> i32 {
+    unsafe { call_hooked(3, pad_data, count, true) }
+}
+
+/// Read the latest extended pad data from controller `port` without
+/// waiting for the next sample.
+///
+/// Port 0 is the built-in controls; ports 1 and up are external
+/// controllers paired with a PSP Go. Returns `None` if the port has no
+/// controller or the firmware lacks extended pad support.
+pub fn read_extended(port: i32) -> Option<SceCtrlData2> {
+    let mut data = SceCtrlData2::default();
+    // SAFETY: `data` is a valid buffer for one sample.
+    let ret = unsafe { crate::sys::sceCtrlPeekBufferPositive2(port, &mut data, 1) };
+    if ret > 0 { Some(data) } else { None }
+}
+
+/// Set the controller sampling mode through `sceCtrl_driver`.
+///
+/// Returns the previous mode, or the negative SCE error code. Returns
+/// `None` if the driver function couldn't be resolved.
+///
+/// # Safety
+///
+/// Must be called from a kernel-mode module on custom firmware that
+/// provides `SystemCtrlForKernel`.
+pub unsafe fn set_sampling_mode(mode: CtrlMode) -> Option<i32> {
+    // SAFETY: Caller guarantees kernel mode; the names are NUL-terminated.
+    unsafe {
+        let ptr = find_function(
+            MODULE.as_ptr(),
+            DRIVER_LIBRARY.as_ptr(),
+            NID_SET_SAMPLING_MODE,
+        )?;
+        let set_mode: unsafe extern "C" fn(CtrlMode) -> i32 = core::mem::transmute(ptr);
+        Some(set_mode(mode))
+    }
+}
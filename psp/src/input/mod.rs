@@ -0,0 +1,455 @@
+//! Controller input with state change detection.
+//!
+//! Wraps `sceCtrlReadBufferPositive` with a high-level [`Controller`] that
+//! tracks previous/current state for press/release detection and provides
+//! normalized analog stick values with deadzone support and optional
+//! low-pass smoothing, and can make the stick press the d-pad for code
+//! that only reads buttons. [`ComboDetector`] matches timed button sequences
+//! such as fighting-game special moves. [`ActionMap`] binds buttons,
+//! chords and stick axes to the game's own actions so controls can be
+//! remapped and saved to a [`Config`](crate::config::Config).
+//! [`EventQueue`] turns each update into button and stick events for
+//! event-driven code.
+//!
+//! With the `kernel` feature, [`kernel`] lets plugins rewrite controller
+//! data before games read it.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use psp::input::Controller;
+//! use psp::sys::CtrlButtons;
+//!
+//! psp::input::enable_analog();
+//! let mut ctrl = Controller::new();
+//! ctrl.set_analog_smoothing(0.5); // damp stick jitter
+//!
+//! loop {
+//!     ctrl.update();
+//!     if ctrl.is_pressed(CtrlButtons::CROSS) {
+//!         // just pressed this frame
+//!     }
+//!     let x = ctrl.analog_x_f32(0.2);
+//!     // x is -1.0..1.0 with 20% deadzone
+//! }
+//! ```
+
+use crate::sys::{CtrlButtons, CtrlMode, SceCtrlData, sceCtrlReadBufferPositive};
+
+#[cfg(not(feature = "stub-only"))]
+mod action;
+#[cfg(not(feature = "stub-only"))]
+mod event;
+#[cfg(feature = "kernel")]
+pub mod kernel;
+
+#[cfg(not(feature = "stub-only"))]
+pub use action::{ActionMap, Axis, Binding, Conflict, DEFAULT_DEADZONE, InputSnapshot};
+#[cfg(not(feature = "stub-only"))]
+pub use event::{EventQueue, InputEvent};
+
+/// Initialize analog input mode.
+///
+/// Call this once at startup before reading the analog stick.
+/// Sets the sampling cycle to 0 (default) and mode to Analog.
+pub fn enable_analog() {
+    unsafe {
+        crate::sys::sceCtrlSetSamplingCycle(0);
+        crate::sys::sceCtrlSetSamplingMode(CtrlMode::Analog);
+    }
+}
+
+/// High-level controller input with state change detection.
+///
+/// Call [`update()`](Self::update) once per frame to refresh the state,
+/// then query buttons and analog stick.
+pub struct Controller {
+    current: SceCtrlData,
+    previous: SceCtrlData,
+    /// EMA weight of the newest sample; 1.0 disables smoothing.
+    smoothing: f32,
+    /// Filtered stick position in -1.0..=1.0, before the deadzone.
+    smoothed_x: f32,
+    smoothed_y: f32,
+    /// Whether the filter has been seeded with a first sample.
+    primed: bool,
+    /// Stick travel that presses a d-pad direction, if emulation is on.
+    dpad_threshold: Option<f32>,
+    /// D-pad directions pressed by the stick this frame and last frame.
+    emulated: CtrlButtons,
+    emulated_previous: CtrlButtons,
+}
+
+impl Controller {
+    /// Create a new controller with zeroed initial state.
+    pub fn new() -> Self {
+        Self {
+            current: SceCtrlData::default(),
+            previous: SceCtrlData::default(),
+            smoothing: 1.0,
+            smoothed_x: 0.0,
+            smoothed_y: 0.0,
+            primed: false,
+            dpad_threshold: None,
+            emulated: CtrlButtons::empty(),
+            emulated_previous: CtrlButtons::empty(),
+        }
+    }
+
+    /// Default stick travel for [`set_analog_emulates_dpad()`](Self::set_analog_emulates_dpad).
+    pub const DEFAULT_DPAD_THRESHOLD: f32 = 0.5;
+
+    /// Enable an exponential moving average on the normalized analog axes.
+    ///
+    /// Each [`update()`](Self::update) moves the filtered position by
+    /// `alpha` of the way towards the new sample. `1.0` (the default)
+    /// disables smoothing; lower values smooth more but add lag. `alpha`
+    /// is clamped to `f32::EPSILON..=1.0`, since at `0.0` the stick would
+    /// never move, and NaN disables smoothing.
+    pub fn set_analog_smoothing(&mut self, alpha: f32) {
+        self.smoothing = if alpha.is_nan() {
+            1.0
+        } else {
+            alpha.clamp(f32::EPSILON, 1.0)
+        };
+    }
+
+    /// The current analog smoothing factor.
+    pub fn analog_smoothing(&self) -> f32 {
+        self.smoothing
+    }
+
+    /// Make the stick press the d-pad.
+    ///
+    /// While enabled, [`is_held()`](Self::is_held),
+    /// [`is_pressed()`](Self::is_pressed), [`is_released()`](Self::is_released),
+    /// [`buttons()`](Self::buttons) and [`snapshot()`](Self::snapshot)
+    /// report [`analog_as_dpad()`](Self::analog_as_dpad) directions as if
+    /// the d-pad were held, so menus that only read buttons work with the
+    /// stick. Uses [`DEFAULT_DPAD_THRESHOLD`](Self::DEFAULT_DPAD_THRESHOLD)
+    /// unless [`set_analog_dpad_threshold()`](Self::set_analog_dpad_threshold)
+    /// was called. [`raw()`](Self::raw) is unaffected.
+    pub fn set_analog_emulates_dpad(&mut self, enabled: bool) {
+        self.dpad_threshold = match (enabled, self.dpad_threshold) {
+            (false, _) => None,
+            (true, Some(threshold)) => Some(threshold),
+            (true, None) => Some(Self::DEFAULT_DPAD_THRESHOLD),
+        };
+        if !enabled {
+            self.emulated = CtrlButtons::empty();
+            self.emulated_previous = CtrlButtons::empty();
+        }
+    }
+
+    /// Set the stick travel that presses a d-pad direction and enable
+    /// [d-pad emulation](Self::set_analog_emulates_dpad). Clamped to
+    /// `0.0..=0.99`.
+    pub fn set_analog_dpad_threshold(&mut self, threshold: f32) {
+        self.dpad_threshold = Some(threshold.clamp(0.0, 0.99));
+    }
+
+    /// Whether the stick presses the d-pad.
+    pub fn analog_emulates_dpad(&self) -> bool {
+        self.dpad_threshold.is_some()
+    }
+
+    /// Read the current controller state.
+    ///
+    /// Must be called once per frame for press/release detection to work.
+    pub fn update(&mut self) {
+        self.previous = self.current;
+        unsafe {
+            sceCtrlReadBufferPositive(&mut self.current, 1);
+        }
+        let x = normalize_raw(self.current.lx);
+        let y = normalize_raw(self.current.ly);
+        if self.primed {
+            // Weighted so alpha = 1.0 yields exactly the new sample.
+            let a = self.smoothing;
+            self.smoothed_x = a * x + (1.0 - a) * self.smoothed_x;
+            self.smoothed_y = a * y + (1.0 - a) * self.smoothed_y;
+        } else {
+            self.smoothed_x = x;
+            self.smoothed_y = y;
+            self.primed = true;
+        }
+        self.emulated_previous = self.emulated;
+        if let Some(threshold) = self.dpad_threshold {
+            self.emulated = self.analog_as_dpad(threshold);
+        }
+    }
+
+    /// Buttons held this frame, including d-pad directions pressed by the
+    /// stick when [emulation](Self::set_analog_emulates_dpad) is on.
+    pub fn buttons(&self) -> CtrlButtons {
+        self.current.buttons | self.emulated
+    }
+
+    /// Like [`buttons()`](Self::buttons), for the previous frame.
+    pub fn previous_buttons(&self) -> CtrlButtons {
+        self.previous.buttons | self.emulated_previous
+    }
+
+    /// Returns `true` if the button is currently held down.
+    pub fn is_held(&self, button: CtrlButtons) -> bool {
+        self.buttons().contains(button)
+    }
+
+    /// Returns `true` if the button was just pressed this frame.
+    ///
+    /// (Down now, was not down last frame.)
+    pub fn is_pressed(&self, button: CtrlButtons) -> bool {
+        self.buttons().contains(button) && !self.previous_buttons().contains(button)
+    }
+
+    /// Returns `true` if the button was just released this frame.
+    ///
+    /// (Not down now, was down last frame.)
+    pub fn is_released(&self, button: CtrlButtons) -> bool {
+        !self.buttons().contains(button) && self.previous_buttons().contains(button)
+    }
+
+    /// Raw analog stick X value (0..=255, 128 is center).
+    pub fn analog_x(&self) -> u8 {
+        self.current.lx
+    }
+
+    /// Raw analog stick Y value (0..=255, 128 is center).
+    pub fn analog_y(&self) -> u8 {
+        self.current.ly
+    }
+
+    /// Normalized analog X in -1.0..=1.0 with deadzone.
+    ///
+    /// `deadzone` is the fraction of travel to ignore (e.g. 0.2 = 20%).
+    /// Returns 0.0 if within the deadzone. The value is filtered if
+    /// [`set_analog_smoothing()`](Self::set_analog_smoothing) is in use.
+    pub fn analog_x_f32(&self, deadzone: f32) -> f32 {
+        apply_deadzone(self.smoothed_x, deadzone)
+    }
+
+    /// Normalized analog Y in -1.0..=1.0 with deadzone.
+    pub fn analog_y_f32(&self, deadzone: f32) -> f32 {
+        apply_deadzone(self.smoothed_y, deadzone)
+    }
+
+    /// Normalized stick position with a radial deadzone.
+    ///
+    /// Unlike the per-axis deadzone of [`analog_x_f32()`](Self::analog_x_f32),
+    /// the deadzone is a circle, so diagonals aren't snapped to the axes.
+    /// The returned vector's length is at most 1.0.
+    pub fn analog_radial(&self, deadzone: f32) -> (f32, f32) {
+        apply_radial_deadzone(self.smoothed_x, self.smoothed_y, deadzone)
+    }
+
+    /// The d-pad directions the stick is pushed past `threshold` in.
+    ///
+    /// Each axis is checked on its own, so a diagonal past the threshold
+    /// on both gives two directions. Uses the smoothed position if
+    /// [`set_analog_smoothing()`](Self::set_analog_smoothing) is in use.
+    pub fn analog_as_dpad(&self, threshold: f32) -> CtrlButtons {
+        stick_to_dpad(self.smoothed_x, self.smoothed_y, threshold)
+    }
+
+    /// Access the raw current controller data.
+    pub fn raw(&self) -> &SceCtrlData {
+        &self.current
+    }
+
+    /// Access the raw previous-frame controller data.
+    pub fn raw_previous(&self) -> &SceCtrlData {
+        &self.previous
+    }
+}
+
+impl Default for Controller {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Map a raw 0..=255 axis value to roughly -1.0..=1.0 (128 is center).
+fn normalize_raw(raw: u8) -> f32 {
+    (raw as f32 - 128.0) / 127.0
+}
+
+/// The d-pad directions for a normalized stick position (right and down
+/// positive) whose axes are beyond `threshold`.
+pub fn stick_to_dpad(x: f32, y: f32, threshold: f32) -> CtrlButtons {
+    let mut dpad = CtrlButtons::empty();
+    if x < -threshold {
+        dpad |= CtrlButtons::LEFT;
+    } else if x > threshold {
+        dpad |= CtrlButtons::RIGHT;
+    }
+    if y < -threshold {
+        dpad |= CtrlButtons::UP;
+    } else if y > threshold {
+        dpad |= CtrlButtons::DOWN;
+    }
+    dpad
+}
+
+/// Apply a deadzone to a normalized axis value, clamping to -1.0..=1.0.
+fn apply_deadzone(normalized: f32, deadzone: f32) -> f32 {
+    let abs = if normalized < 0.0 {
+        -normalized
+    } else {
+        normalized
+    };
+    if abs < deadzone {
+        0.0
+    } else {
+        // Remap so the edge of the deadzone maps to 0.0
+        let sign = if normalized < 0.0 { -1.0 } else { 1.0 };
+        let remapped = (abs - deadzone) / (1.0 - deadzone);
+        // Clamp to 1.0 (raw=0 or raw=255 can slightly exceed 1.0)
+        let clamped = if remapped > 1.0 { 1.0 } else { remapped };
+        sign * clamped
+    }
+}
+
+/// Apply a circular deadzone to a normalized stick position, remapping
+/// the remaining travel so the vector's length is 0.0..=1.0.
+pub(crate) fn apply_radial_deadzone(x: f32, y: f32, deadzone: f32) -> (f32, f32) {
+    let len = libm::sqrtf(x * x + y * y);
+    if len <= deadzone {
+        return (0.0, 0.0);
+    }
+    let remapped = ((len - deadzone) / (1.0 - deadzone)).min(1.0);
+    let scale = remapped / len;
+    (x * scale, y * scale)
+}
+
+// ── ComboDetector ───────────────────────────────────────────────────
+
+/// A registered button sequence.
+#[cfg(not(feature = "stub-only"))]
+struct Combo {
+    steps: alloc::vec::Vec<CtrlButtons>,
+    window_us: u64,
+    /// Index of the next step to match.
+    progress: usize,
+    /// Time the last step matched.
+    last_us: u64,
+}
+
+/// Detects timed button sequences ("down, down-forward, forward + punch").
+///
+/// Each step is a set of buttons that must all be held, with at least one
+/// of them newly pressed that frame (the same edge detection as
+/// [`Controller::is_pressed()`]). Each step must follow the previous one
+/// within the combo's window; a press that fits neither the next step nor
+/// the first one restarts the sequence.
+///
+/// # Example
+///
+/// ```ignore
+/// let mut combos = ComboDetector::new();
+/// let hadouken = combos.register(
+///     &[
+///         CtrlButtons::DOWN,
+///         CtrlButtons::DOWN | CtrlButtons::RIGHT,
+///         CtrlButtons::RIGHT | CtrlButtons::SQUARE,
+///     ],
+///     200,
+/// );
+/// loop {
+///     ctrl.update();
+///     if combos.update(&ctrl) == Some(hadouken) {
+///         fire_projectile();
+///     }
+/// }
+/// ```
+#[cfg(not(feature = "stub-only"))]
+pub struct ComboDetector {
+    combos: alloc::vec::Vec<Combo>,
+}
+
+#[cfg(not(feature = "stub-only"))]
+impl ComboDetector {
+    /// Create a detector with no combos.
+    pub fn new() -> Self {
+        Self {
+            combos: alloc::vec::Vec::new(),
+        }
+    }
+
+    /// Register a sequence whose steps must each follow the previous one
+    /// within `window_ms`. Returns the combo's id, as later reported by
+    /// [`update()`](Self::update).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `steps` is empty or contains an empty step.
+    pub fn register(&mut self, steps: &[CtrlButtons], window_ms: u32) -> usize {
+        assert!(
+            !steps.is_empty() && steps.iter().all(|s| !s.is_empty()),
+            "combo steps must be non-empty"
+        );
+        self.combos.push(Combo {
+            steps: steps.to_vec(),
+            window_us: window_ms as u64 * 1000,
+            progress: 0,
+            last_us: 0,
+        });
+        self.combos.len() - 1
+    }
+
+    /// Feed this frame's controller state, returning the id of a combo
+    /// that completed this frame.
+    ///
+    /// If several complete at once, the longest wins.
+    pub fn update(&mut self, ctrl: &Controller) -> Option<usize> {
+        let held = ctrl.buttons();
+        let pressed = held & !ctrl.previous_buttons();
+        let now_us = unsafe { crate::sys::sceKernelGetSystemTimeWide() } as u64;
+        self.feed(held, pressed, now_us)
+    }
+
+    /// Like [`update()`](Self::update), with explicit held/newly-pressed
+    /// buttons and a timestamp in microseconds.
+    pub fn feed(&mut self, held: CtrlButtons, pressed: CtrlButtons, now_us: u64) -> Option<usize> {
+        // (id, step count) of the longest combo completed so far.
+        let mut completed: Option<(usize, usize)> = None;
+        for (id, combo) in self.combos.iter_mut().enumerate() {
+            if combo.progress > 0 && now_us.saturating_sub(combo.last_us) > combo.window_us {
+                combo.progress = 0;
+            }
+            if pressed.is_empty() {
+                continue;
+            }
+            let matches = |step: CtrlButtons| held.contains(step) && step.intersects(pressed);
+            if matches(combo.steps[combo.progress]) {
+                combo.progress += 1;
+            } else if matches(combo.steps[0]) {
+                combo.progress = 1;
+            } else {
+                combo.progress = 0;
+                continue;
+            }
+            combo.last_us = now_us;
+            if combo.progress == combo.steps.len() {
+                combo.progress = 0;
+                if completed.is_none_or(|(_, len)| len < combo.steps.len()) {
+                    completed = Some((id, combo.steps.len()));
+                }
+            }
+        }
+        completed.map(|(id, _)| id)
+    }
+
+    /// Forget all partial progress.
+    pub fn reset(&mut self) {
+        for combo in &mut self.combos {
+            combo.progress = 0;
+        }
+    }
+}
+
+#[cfg(not(feature = "stub-only"))]
+impl Default for ComboDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
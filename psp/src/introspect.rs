@@ -0,0 +1,509 @@
+//! Snapshots of kernel objects and memory, and an on-screen task manager.
+//!
+//! [`threads()`], [`semaphores()`] and [`event_flags()`] list the objects
+//! visible to the caller: in user mode that's the ones user modules
+//! created, and with the `kernel` feature, kernel objects too.
+//! [`memory_partitions()`] reports every partition in kernel mode, and
+//! only the free space of the user partition otherwise.
+//!
+//! Each entry is a copy taken when it was listed; objects created or
+//! deleted meanwhile may be missing or left out.
+//!
+//! # Task manager overlay
+//!
+//! [`TaskManager`] shows the lists as pages over the game, toggled with a
+//! button chord (L + R + SELECT by default), for spotting stuck threads,
+//! stack overflows and leaks on the device.
+//!
+//! ```ignore
+//! use psp::introspect::TaskManager;
+//!
+//! let mut tasks = TaskManager::new();
+//! loop {
+//!     ctrl.update();
+//!     tasks.update(&ctrl);
+//!     // ... inside the frame's display list, after setup_2d:
+//!     unsafe {
+//!         tasks.draw_overlay(&mut renderer);
+//!         renderer.flush();
+//!     }
+//! }
+//! ```
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::font::FontRenderer;
+use crate::input::Controller;
+use crate::sys::{
+    self, CtrlButtons, SceKernelEventFlagInfo, SceKernelIdListType, SceKernelSemaInfo, SceUid,
+};
+use crate::thread::{self, ThreadError, ThreadInfo};
+
+/// Every thread visible to the caller, in the kernel's order.
+///
+/// UIDs are listed up front; each thread's state is read as the iterator
+/// reaches it, and threads that exited by then are skipped.
+pub fn threads() -> Result<impl Iterator<Item = ThreadInfo>, ThreadError> {
+    let uids = thread::id_list(SceKernelIdListType::Thread)?;
+    Ok(uids.into_iter().filter_map(|uid| thread::info(uid).ok()))
+}
+
+/// A snapshot of a semaphore.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SemaphoreInfo {
+    pub uid: SceUid,
+    pub name: String,
+    /// Creation attributes.
+    pub attr: u32,
+    /// Count the semaphore was created with.
+    pub init_count: i32,
+    pub current_count: i32,
+    pub max_count: i32,
+    /// Threads blocked waiting on it.
+    pub waiting_threads: usize,
+}
+
+/// Every semaphore visible to the caller.
+pub fn semaphores() -> Result<impl Iterator<Item = SemaphoreInfo>, ThreadError> {
+    let uids = thread::id_list(SceKernelIdListType::Semaphore)?;
+    Ok(uids.into_iter().filter_map(|uid| {
+        let mut raw = core::mem::MaybeUninit::<SceKernelSemaInfo>::uninit();
+        let ret = unsafe {
+            (&raw mut (*raw.as_mut_ptr()).size).write(core::mem::size_of::<SceKernelSemaInfo>());
+            sys::sceKernelReferSemaStatus(uid, raw.as_mut_ptr())
+        };
+        if ret < 0 {
+            return None;
+        }
+        // SAFETY: the kernel filled in the structure.
+        let raw = unsafe { raw.assume_init() };
+        Some(SemaphoreInfo {
+            uid,
+            name: c_name(&raw.name),
+            attr: raw.attr,
+            init_count: raw.init_count,
+            current_count: raw.current_count,
+            max_count: raw.max_count,
+            waiting_threads: raw.num_wait_threads.max(0) as usize,
+        })
+    }))
+}
+
+/// A snapshot of an event flag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventFlagInfo {
+    pub uid: SceUid,
+    pub name: String,
+    /// Creation attributes.
+    pub attr: u32,
+    /// Bits the flag was created with.
+    pub init_pattern: u32,
+    pub current_pattern: u32,
+    /// Threads blocked waiting on it.
+    pub waiting_threads: usize,
+}
+
+/// Every event flag visible to the caller.
+pub fn event_flags() -> Result<impl Iterator<Item = EventFlagInfo>, ThreadError> {
+    let uids = thread::id_list(SceKernelIdListType::EventFlag)?;
+    Ok(uids.into_iter().filter_map(|uid| {
+        let mut raw = core::mem::MaybeUninit::<SceKernelEventFlagInfo>::uninit();
+        let ret = unsafe {
+            (&raw mut (*raw.as_mut_ptr()).size)
+                .write(core::mem::size_of::<SceKernelEventFlagInfo>());
+            sys::sceKernelReferEventFlagStatus(uid, raw.as_mut_ptr())
+        };
+        if ret < 0 {
+            return None;
+        }
+        // SAFETY: the kernel filled in the structure.
+        let raw = unsafe { raw.assume_init() };
+        Some(EventFlagInfo {
+            uid,
+            name: c_name(&raw.name),
+            attr: raw.attr,
+            init_pattern: raw.init_pattern,
+            current_pattern: raw.current_pattern,
+            waiting_threads: raw.num_wait_threads.max(0) as usize,
+        })
+    }))
+}
+
+/// A snapshot of a memory partition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PartitionInfo {
+    /// Partition number, as in [`SceSysMemPartitionId`](sys::SceSysMemPartitionId).
+    pub id: i32,
+    /// Start address, or `None` outside kernel mode.
+    pub start: Option<u32>,
+    /// Size in bytes, or `None` outside kernel mode.
+    pub size: Option<usize>,
+    /// Free bytes in total.
+    pub free: usize,
+    /// Largest free block in bytes; allocations bigger than this fail.
+    pub max_free: usize,
+}
+
+/// The memory partitions that exist on this console.
+///
+/// With the `kernel` feature this queries each of the 12 partitions and
+/// leaves out the ones the model doesn't have. In user mode only the user
+/// partition's free space can be read.
+pub fn memory_partitions() -> Vec<PartitionInfo> {
+    #[cfg(feature = "kernel")]
+    {
+        kernel_partitions()
+    }
+    #[cfg(not(feature = "kernel"))]
+    {
+        alloc::vec![PartitionInfo {
+            id: sys::SceSysMemPartitionId::SceKernelPrimaryUserPartition as i32,
+            start: None,
+            size: None,
+            free: unsafe { sys::sceKernelTotalFreeMemSize() },
+            max_free: unsafe { sys::sceKernelMaxFreeMemSize() },
+        }]
+    }
+}
+
+#[cfg(feature = "kernel")]
+fn kernel_partitions() -> Vec<PartitionInfo> {
+    use sys::SceSysMemPartitionId as Id;
+
+    const PARTITIONS: [Id; 12] = [
+        Id::SceKernelPrimaryKernelPartition,
+        Id::SceKernelPrimaryUserPartition,
+        Id::SceKernelOtherKernelPartition1,
+        Id::SceKernelOtherKernelPartition2,
+        Id::SceKernelVshellPARTITION,
+        Id::SceKernelScUserPartition,
+        Id::SceKernelMeUserPartition,
+        Id::SceKernelExtendedScKernelPartition,
+        Id::SceKernelExtendedSc2KernelPartition,
+        Id::SceKernelExtendedMeKernelPartition,
+        Id::SceKernelVshellKernelPartition,
+        Id::SceKernelExtendedKernelPartition,
+    ];
+
+    PARTITIONS
+        .iter()
+        .filter_map(|&pid| {
+            let mut raw = sys::PspSysmemPartitionInfo {
+                size: core::mem::size_of::<sys::PspSysmemPartitionInfo>(),
+                start_addr: 0,
+                mem_size: 0,
+                attr: 0,
+            };
+            if unsafe { sys::sceKernelQueryMemoryPartitionInfo(pid, &mut raw) } < 0 {
+                return None;
+            }
+            Some(PartitionInfo {
+                id: pid as i32,
+                start: Some(raw.start_addr),
+                size: Some(raw.mem_size as usize),
+                free: unsafe { sys::sceKernelPartitionTotalFreeMemSize(pid) },
+                max_free: unsafe { sys::sceKernelPartitionMaxFreeMemSize(pid) },
+            })
+        })
+        .collect()
+}
+
+/// A NUL-terminated kernel object name.
+fn c_name(raw: &[u8]) -> String {
+    let len = raw.iter().position(|&b| b == 0).unwrap_or(raw.len());
+    String::from_utf8_lossy(&raw[..len]).into_owned()
+}
+
+// ── Task manager ────────────────────────────────────────────────────
+
+/// The buttons held together to show or hide a [`TaskManager`].
+pub const DEFAULT_CHORD: CtrlButtons = CtrlButtons::LTRIGGER
+    .union(CtrlButtons::RTRIGGER)
+    .union(CtrlButtons::SELECT);
+
+/// Frames between refreshes of the visible page.
+const REFRESH_FRAMES: u32 = 30;
+
+/// Overlay panel position and size.
+const PANEL_X: f32 = 8.0;
+const PANEL_Y: f32 = 8.0;
+const PANEL_W: f32 = 464.0;
+const PANEL_H: f32 = 256.0;
+const PADDING: f32 = 6.0;
+
+const PANEL_BG: u32 = 0xD020_2020;
+const TITLE_FG: u32 = 0xFF40_D0FF;
+const HEADER_FG: u32 = 0xFFA0_A0A0;
+const ROW_FG: u32 = 0xFFFF_FFFF;
+
+/// A page of the [`TaskManager`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Page {
+    Threads,
+    Semaphores,
+    EventFlags,
+    Memory,
+}
+
+impl Page {
+    const ALL: [Page; 4] = [
+        Page::Threads,
+        Page::Semaphores,
+        Page::EventFlags,
+        Page::Memory,
+    ];
+
+    fn title(self) -> &'static str {
+        match self {
+            Page::Threads => "Threads",
+            Page::Semaphores => "Semaphores",
+            Page::EventFlags => "Event flags",
+            Page::Memory => "Memory",
+        }
+    }
+
+    /// Column headers and their x offsets within the panel.
+    fn columns(self) -> &'static [(&'static str, f32)] {
+        match self {
+            Page::Threads => &[
+                ("Name", 0.0),
+                ("Pri", 190.0),
+                ("Status", 230.0),
+                ("Stack free/size", 330.0),
+            ],
+            Page::Semaphores => &[
+                ("Name", 0.0),
+                ("Count", 190.0),
+                ("Max", 270.0),
+                ("Waiting", 350.0),
+            ],
+            Page::EventFlags => &[
+                ("Name", 0.0),
+                ("Pattern", 190.0),
+                ("Attr", 290.0),
+                ("Waiting", 350.0),
+            ],
+            Page::Memory => &[
+                ("Partition", 0.0),
+                ("Size", 100.0),
+                ("Free", 210.0),
+                ("Largest free", 320.0),
+            ],
+        }
+    }
+
+    /// One row of cells per object, matching [`columns()`](Self::columns).
+    fn rows(self) -> Vec<[String; 4]> {
+        match self {
+            Page::Threads => threads().map_or_else(error_row, |threads| {
+                threads
+                    .map(|t| {
+                        let free = t.stack_free.map_or(String::from("?"), kib);
+                        [
+                            t.name,
+                            format!("{}", t.current_priority),
+                            format!("{:?}", t.status),
+                            format!("{}/{}", free, kib(t.stack_size)),
+                        ]
+                    })
+                    .collect()
+            }),
+            Page::Semaphores => semaphores().map_or_else(error_row, |semas| {
+                semas
+                    .map(|s| {
+                        [
+                            s.name,
+                            format!("{}", s.current_count),
+                            format!("{}", s.max_count),
+                            format!("{}", s.waiting_threads),
+                        ]
+                    })
+                    .collect()
+            }),
+            Page::EventFlags => event_flags().map_or_else(error_row, |flags| {
+                flags
+                    .map(|f| {
+                        [
+                            f.name,
+                            format!("{:#010x}", f.current_pattern),
+                            format!("{:#x}", f.attr),
+                            format!("{}", f.waiting_threads),
+                        ]
+                    })
+                    .collect()
+            }),
+            Page::Memory => memory_partitions()
+                .into_iter()
+                .map(|p| {
+                    [
+                        format!("{}", p.id),
+                        p.size.map_or(String::from("?"), kib),
+                        kib(p.free),
+                        kib(p.max_free),
+                    ]
+                })
+                .collect(),
+        }
+    }
+}
+
+fn error_row(e: ThreadError) -> Vec<[String; 4]> {
+    alloc::vec![[
+        format!("{}", e),
+        String::new(),
+        String::new(),
+        String::new()
+    ]]
+}
+
+fn kib(bytes: usize) -> String {
+    if bytes < 10 * 1024 {
+        format!("{}", bytes)
+    } else {
+        format!("{}K", bytes / 1024)
+    }
+}
+
+/// An on-screen task manager for debug builds.
+///
+/// Hold the chord to show or hide it. While it's shown, Left and Right
+/// switch pages and Up and Down scroll. The visible page is re-read from
+/// the kernel every half second.
+///
+/// See the [module documentation](self).
+pub struct TaskManager {
+    chord: CtrlButtons,
+    visible: bool,
+    page: usize,
+    scroll: usize,
+    rows: Vec<[String; 4]>,
+    /// Frames until the next refresh.
+    refresh_in: u32,
+}
+
+impl TaskManager {
+    /// A hidden task manager toggled by [`DEFAULT_CHORD`].
+    pub fn new() -> Self {
+        Self::with_chord(DEFAULT_CHORD)
+    }
+
+    /// A hidden task manager toggled by holding all of `chord`.
+    pub fn with_chord(chord: CtrlButtons) -> Self {
+        Self {
+            chord,
+            visible: false,
+            page: 0,
+            scroll: 0,
+            rows: Vec::new(),
+            refresh_in: 0,
+        }
+    }
+
+    /// Handle input and refresh the page. Call once per frame after
+    /// [`Controller::update`].
+    pub fn update(&mut self, ctrl: &Controller) {
+        let chord_down = |buttons: CtrlButtons| buttons.contains(self.chord);
+        if chord_down(ctrl.buttons()) && !chord_down(ctrl.previous_buttons()) {
+            self.visible = !self.visible;
+            self.refresh_in = 0;
+        }
+        if !self.visible {
+            return;
+        }
+
+        let pages = Page::ALL.len();
+        if ctrl.is_pressed(CtrlButtons::RIGHT) {
+            self.show_page((self.page + 1) % pages);
+        } else if ctrl.is_pressed(CtrlButtons::LEFT) {
+            self.show_page((self.page + pages - 1) % pages);
+        }
+        if ctrl.is_pressed(CtrlButtons::DOWN) {
+            self.scroll += 1;
+        } else if ctrl.is_pressed(CtrlButtons::UP) {
+            self.scroll = self.scroll.saturating_sub(1);
+        }
+
+        if self.refresh_in == 0 {
+            self.rows = self.page().rows();
+            self.refresh_in = REFRESH_FRAMES;
+        }
+        self.refresh_in -= 1;
+        self.scroll = self.scroll.min(self.rows.len().saturating_sub(1));
+    }
+
+    /// Whether the overlay is shown.
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Show or hide the overlay.
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+        self.refresh_in = 0;
+    }
+
+    /// The page being shown.
+    pub fn page(&self) -> Page {
+        Page::ALL[self.page]
+    }
+
+    fn show_page(&mut self, page: usize) {
+        self.page = page;
+        self.scroll = 0;
+        self.refresh_in = 0;
+    }
+
+    /// Draw the overlay if it's shown.
+    ///
+    /// The panel is drawn immediately; text is queued on `renderer` and
+    /// appears when the caller flushes it.
+    ///
+    /// # Safety
+    ///
+    /// Must be called within an active GU display list, after
+    /// [`crate::gu_ext::setup_2d`].
+    pub unsafe fn draw_overlay(&self, renderer: &mut FontRenderer<'_>) {
+        if !self.visible {
+            return;
+        }
+        unsafe {
+            crate::gu_ext::draw_rect_filled(PANEL_X, PANEL_Y, PANEL_W, PANEL_H, PANEL_BG);
+        }
+
+        let line = renderer.line_height();
+        let x = PANEL_X + PADDING;
+        let mut y = PANEL_Y + PADDING;
+        let page = self.page();
+        let title = format!(
+            "{} ({}/{})  {} rows   < > page  ^ v scroll",
+            page.title(),
+            self.page + 1,
+            Page::ALL.len(),
+            self.rows.len()
+        );
+        renderer.draw_text(x, y, TITLE_FG, &title);
+        y += line;
+
+        let columns = page.columns();
+        for &(header, cx) in columns {
+            renderer.draw_text(x + cx, y, HEADER_FG, header);
+        }
+        y += line;
+
+        let visible_rows = ((PANEL_Y + PANEL_H - PADDING - y) / line).max(0.0) as usize;
+        for row in self.rows.iter().skip(self.scroll).take(visible_rows) {
+            for (cell, &(_, cx)) in row.iter().zip(columns) {
+                renderer.draw_text(x + cx, y, ROW_FG, cell);
+            }
+            y += line;
+        }
+    }
+}
+
+impl Default for TaskManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
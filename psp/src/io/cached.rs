@@ -0,0 +1,275 @@
+//! Block-cached random-access file reader.
+//!
+//! Every [`File::read`] is a syscall to the memory stick, so index +
+//! records access patterns made of many small reads are dominated by
+//! syscall latency. [`CachedFile`] reads whole fixed-size blocks into a
+//! small pool and serves later reads from memory, evicting the least
+//! recently used block on a miss.
+
+use super::{File, IoError};
+use crate::sys::{IoOpenFlags, IoWhence};
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+/// Default number of cached blocks.
+pub const DEFAULT_CACHE_BLOCKS: usize = 8;
+
+/// Default block size in bytes.
+pub const DEFAULT_CACHE_BLOCK_SIZE: usize = 8 * 1024;
+
+/// A source that can read bytes at an absolute offset.
+///
+/// Implemented for [`File`]; implement it for other sources (or a mock)
+/// to put them behind a [`CachedFile`].
+pub trait ReadAt {
+    /// Read into `buf` starting at `offset`, returning the number of bytes
+    /// read. Returning 0 means end of file.
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> Result<usize, IoError>;
+
+    /// Total length of the source in bytes, if known.
+    ///
+    /// Readers of indexed formats use it to reject offsets and sizes past
+    /// the end before allocating for them. The default doesn't know.
+    fn len_hint(&mut self) -> Result<Option<u64>, IoError> {
+        Ok(None)
+    }
+}
+
+impl ReadAt for File {
+    /// Seeks and then reads, so this moves the file position.
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> Result<usize, IoError> {
+        self.seek(offset as i64, IoWhence::Set)?;
+        self.read_all(buf)
+    }
+
+    fn len_hint(&mut self) -> Result<Option<u64>, IoError> {
+        self.size().map(|size| Some(size as u64))
+    }
+}
+
+/// Hit/miss counters for a [`CachedFile`].
+///
+/// Each block touched by a [`read_at()`](CachedFile::read_at) counts once;
+/// blocks loaded by [`prefetch()`](CachedFile::prefetch) are not counted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Block lookups served from the cache.
+    pub hits: u64,
+    /// Block lookups that had to read from the source.
+    pub misses: u64,
+}
+
+impl CacheStats {
+    /// Fraction of lookups that hit, or 0.0 if there were none.
+    pub fn hit_rate(&self) -> f32 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f32 / total as f32
+        }
+    }
+}
+
+/// One cache slot.
+struct Block {
+    /// Block number held by this slot, or `None` if empty.
+    index: Option<u64>,
+    /// Valid bytes in `data`; less than the block size only at EOF.
+    len: usize,
+    /// Access tick for LRU eviction.
+    last_used: u64,
+    data: Box<[u8]>,
+}
+
+/// A read-only file with an LRU cache of fixed-size blocks.
+///
+/// # Example
+///
+/// ```ignore
+/// use psp::io::CachedFile;
+///
+/// let mut data = CachedFile::open("ms0:/game/data.pak")?;
+/// let mut header = [0u8; 64];
+/// data.read_at(0, &mut header)?;
+/// data.prefetch(4096, 16 * 1024)?;
+/// psp::dprintln!("hit rate {:.2}", data.stats().hit_rate());
+/// ```
+pub struct CachedFile<R: ReadAt = File> {
+    inner: R,
+    block_size: usize,
+    blocks: Vec<Block>,
+    tick: u64,
+    stats: CacheStats,
+}
+
+impl CachedFile<File> {
+    /// Open `path` read-only with the default cache of
+    /// [`DEFAULT_CACHE_BLOCKS`] x [`DEFAULT_CACHE_BLOCK_SIZE`] bytes.
+    pub fn open(path: &str) -> Result<Self, IoError> {
+        Ok(Self::new(File::open(path, IoOpenFlags::RD_ONLY)?))
+    }
+}
+
+impl<R: ReadAt> CachedFile<R> {
+    /// Wrap `inner` with the default cache configuration.
+    pub fn new(inner: R) -> Self {
+        Self::with_blocks(inner, DEFAULT_CACHE_BLOCKS, DEFAULT_CACHE_BLOCK_SIZE)
+    }
+
+    /// Wrap `inner` with `block_count` blocks of `block_size` bytes.
+    ///
+    /// The whole pool is allocated up front.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `block_count` or `block_size` is zero.
+    pub fn with_blocks(inner: R, block_count: usize, block_size: usize) -> Self {
+        assert!(
+            block_count > 0 && block_size > 0,
+            "cache needs at least one non-empty block"
+        );
+        let blocks = (0..block_count)
+            .map(|_| Block {
+                index: None,
+                len: 0,
+                last_used: 0,
+                data: alloc::vec![0u8; block_size].into_boxed_slice(),
+            })
+            .collect();
+        Self {
+            inner,
+            block_size,
+            blocks,
+            tick: 0,
+            stats: CacheStats::default(),
+        }
+    }
+
+    /// Read into `buf` starting at `offset`.
+    ///
+    /// Returns the number of bytes read, which is less than `buf.len()`
+    /// only at end of file.
+    pub fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> Result<usize, IoError> {
+        let block_size = self.block_size as u64;
+        let mut done = 0;
+        while done < buf.len() {
+            let pos = offset + done as u64;
+            let slot = self.lookup(pos / block_size, true)?;
+            let block = &self.blocks[slot];
+            let start = (pos % block_size) as usize;
+            if start >= block.len {
+                break;
+            }
+            let n = (block.len - start).min(buf.len() - done);
+            buf[done..done + n].copy_from_slice(&block.data[start..start + n]);
+            done += n;
+            if block.len < self.block_size {
+                // A short block is the last one in the file.
+                break;
+            }
+        }
+        Ok(done)
+    }
+
+    /// Load the blocks covering `offset..offset + len` ahead of use.
+    ///
+    /// At most [`block_count()`](Self::block_count) blocks are loaded so a
+    /// long range cannot evict its own start.
+    pub fn prefetch(&mut self, offset: u64, len: usize) -> Result<(), IoError> {
+        if len == 0 {
+            return Ok(());
+        }
+        let block_size = self.block_size as u64;
+        let first = offset / block_size;
+        let last =
+            ((offset + len as u64 - 1) / block_size).min(first + self.blocks.len() as u64 - 1);
+        for index in first..=last {
+            let slot = self.lookup(index, false)?;
+            if self.blocks[slot].len < self.block_size {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Hit/miss counters since creation or the last
+    /// [`reset_stats()`](Self::reset_stats).
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    /// Zero the hit/miss counters.
+    pub fn reset_stats(&mut self) {
+        self.stats = CacheStats::default();
+    }
+
+    /// Drop all cached blocks, e.g. after the underlying file changed.
+    pub fn invalidate(&mut self) {
+        for block in &mut self.blocks {
+            block.index = None;
+        }
+    }
+
+    /// Size of each cached block in bytes.
+    pub fn block_size(&self) -> usize {
+        self.block_size
+    }
+
+    /// Number of blocks in the cache.
+    pub fn block_count(&self) -> usize {
+        self.blocks.len()
+    }
+
+    /// Access the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Unwrap the underlying reader, discarding the cache.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Find or load block `index`, returning its slot.
+    fn lookup(&mut self, index: u64, count: bool) -> Result<usize, IoError> {
+        self.tick += 1;
+        if let Some(slot) = self.blocks.iter().position(|b| b.index == Some(index)) {
+            if count {
+                self.stats.hits += 1;
+            }
+            self.blocks[slot].last_used = self.tick;
+            return Ok(slot);
+        }
+        if count {
+            self.stats.misses += 1;
+        }
+
+        // Prefer an empty slot, otherwise evict the least recently used.
+        let slot = self
+            .blocks
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, b)| (b.index.is_some(), b.last_used))
+            .map(|(i, _)| i)
+            .unwrap_or(0);
+        let block = &mut self.blocks[slot];
+        // Leave the slot empty if the read fails part-way.
+        block.index = None;
+        let base = index * self.block_size as u64;
+        let mut filled = 0;
+        while filled < block.data.len() {
+            let n = self
+                .inner
+                .read_at(base + filled as u64, &mut block.data[filled..])?;
+            if n == 0 {
+                break;
+            }
+            filled += n;
+        }
+        block.index = Some(index);
+        block.len = filled;
+        block.last_used = self.tick;
+        Ok(slot)
+    }
+}
@@ -0,0 +1,149 @@
+//! Memory Stick insert/eject notification.
+
+use core::ffi::c_void;
+use core::ptr;
+use core::sync::atomic::{AtomicBool, AtomicI32, AtomicUsize, Ordering};
+
+use super::IoError;
+use crate::sys::{
+    MScmIsMediumInserted, MScmRegisterMSInsertEjectCallback, MsCbEvent, ThreadAttributes,
+    sceKernelCreateCallback, sceKernelCreateThread, sceKernelDelayThread, sceKernelDeleteCallback,
+    sceKernelDeleteThread, sceKernelSleepThreadCB, sceKernelStartThread, sceKernelWaitThreadEnd,
+};
+
+/// A Memory Stick was inserted or removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MsEvent {
+    Inserted,
+    Removed,
+}
+
+/// The handler passed to [`register_ms_callback`], or 0 for none.
+static HANDLER: AtomicUsize = AtomicUsize::new(0);
+
+/// Whether the event thread has been started.
+static STARTED: AtomicBool = AtomicBool::new(false);
+
+/// Registration result reported by the event thread: the SCE error code,
+/// 0 on success, or `PENDING` until the thread gets that far.
+static REGISTER_RESULT: AtomicI32 = AtomicI32::new(PENDING);
+const PENDING: i32 = 1;
+
+const THREAD_NAME: &[u8] = b"ms_event_thread\0";
+const CALLBACK_NAME: &[u8] = b"ms_event_callback\0";
+
+/// Call `f` whenever a Memory Stick is inserted or removed.
+///
+/// The first call starts a background thread that owns the firmware
+/// callback and calls `f` from it, so `f` runs concurrently with the
+/// rest of the app and should only record the event (e.g. in an atomic)
+/// for the main loop to act on. Later calls replace the handler.
+///
+/// Once the stick is removed, file operations on `ms0:` fail until it is
+/// reinserted; pause saving on [`MsEvent::Removed`] and warn the user
+/// instead of surfacing the raw errors.
+///
+/// If the event thread or the firmware callback can't be set up, the
+/// error is returned, nothing is left behind and `f` is not kept.
+pub fn register_ms_callback(f: fn(MsEvent)) -> Result<(), IoError> {
+    HANDLER.store(f as usize, Ordering::Release);
+    if STARTED.swap(true, Ordering::AcqRel) {
+        return Ok(());
+    }
+    start_event_thread().inspect_err(|_| {
+        // Unless another call has replaced it meanwhile.
+        let _ = HANDLER.compare_exchange(f as usize, 0, Ordering::AcqRel, Ordering::Relaxed);
+        STARTED.store(false, Ordering::Release);
+    })
+}
+
+/// Start the event thread and wait for it to register the callback,
+/// deleting it again if that fails.
+fn start_event_thread() -> Result<(), IoError> {
+    let thid = unsafe {
+        sceKernelCreateThread(
+            THREAD_NAME.as_ptr(),
+            event_thread,
+            crate::DEFAULT_THREAD_PRIORITY,
+            4096,
+            ThreadAttributes::empty(),
+            ptr::null_mut(),
+        )
+    };
+    if thid.0 < 0 {
+        return Err(IoError(thid.0));
+    }
+    let ret = unsafe { sceKernelStartThread(thid, 0, ptr::null_mut()) };
+    if ret < 0 {
+        unsafe { sceKernelDeleteThread(thid) };
+        return Err(IoError(ret));
+    }
+
+    // The callback has to be created on the thread that will sleep for
+    // it, so wait for that thread to report how registration went.
+    loop {
+        match REGISTER_RESULT.load(Ordering::Acquire) {
+            PENDING => unsafe {
+                sceKernelDelayThread(1000);
+            },
+            0 => return Ok(()),
+            code => {
+                // The thread has cleaned up its callback and is returning.
+                unsafe {
+                    sceKernelWaitThreadEnd(thid, ptr::null_mut());
+                    sceKernelDeleteThread(thid);
+                }
+                REGISTER_RESULT.store(PENDING, Ordering::Release);
+                return Err(IoError(code));
+            },
+        }
+    }
+}
+
+/// Stop calling the handler passed to [`register_ms_callback`].
+///
+/// The event thread keeps running and ignores events until a handler is
+/// registered again.
+pub fn clear_ms_callback() {
+    HANDLER.store(0, Ordering::Release);
+}
+
+/// Whether a Memory Stick is currently inserted.
+pub fn is_ms_inserted() -> bool {
+    unsafe { MScmIsMediumInserted() == 1 }
+}
+
+unsafe extern "C" fn ms_callback(_count: i32, event: i32, _arg: *mut c_void) -> i32 {
+    let event = match event {
+        e if e == MsCbEvent::Inserted as i32 => MsEvent::Inserted,
+        e if e == MsCbEvent::Ejected as i32 => MsEvent::Removed,
+        _ => return 0,
+    };
+    let f = HANDLER.load(Ordering::Acquire);
+    if f != 0 {
+        // SAFETY: Only `fn(MsEvent)`s are stored in `HANDLER`.
+        let f: fn(MsEvent) = unsafe { core::mem::transmute(f) };
+        f(event);
+    }
+    0
+}
+
+unsafe extern "C" fn event_thread(_args: usize, _argp: *mut c_void) -> i32 {
+    let cbid =
+        unsafe { sceKernelCreateCallback(CALLBACK_NAME.as_ptr(), ms_callback, ptr::null_mut()) };
+    if cbid.0 < 0 {
+        REGISTER_RESULT.store(cbid.0, Ordering::Release);
+        return 0;
+    }
+    let ret = unsafe { MScmRegisterMSInsertEjectCallback(cbid) };
+    if ret < 0 {
+        unsafe { sceKernelDeleteCallback(cbid) };
+        REGISTER_RESULT.store(ret, Ordering::Release);
+        return 0;
+    }
+    REGISTER_RESULT.store(0, Ordering::Release);
+
+    loop {
+        unsafe { sceKernelSleepThreadCB() };
+    }
+}
@@ -17,6 +17,15 @@
 //! let mut buf = [0u8; 64];
 //! let n = f.read(&mut buf).unwrap();
 //! ```
+//!
+//! For many small random reads, [`CachedFile`] serves them from an
+//! in-memory block cache instead of issuing a syscall per read.
+//!
+//! [`register_ms_callback`] reports Memory Stick insertion and removal,
+//! so an app can pause saving and warn the user when the stick is pulled.
+//!
+//! [`Watcher`] polls files for changes, so assets edited on the host over
+//! psplink's `host0:` can be reloaded while the game runs.
 
 use crate::sys::{
     IoOpenFlags, IoWhence, SceIoDirent, SceIoStat, SceUid, sceIoClose, sceIoDclose, sceIoDopen,
@@ -26,6 +35,21 @@ use crate::sys::{
 use core::ffi::c_void;
 use core::marker::PhantomData;
 
+#[cfg(not(feature = "stub-only"))]
+mod cached;
+#[cfg(not(feature = "stub-only"))]
+mod hotplug;
+mod watch;
+
+#[cfg(not(feature = "stub-only"))]
+pub use cached::{CacheStats, CachedFile, DEFAULT_CACHE_BLOCK_SIZE, DEFAULT_CACHE_BLOCKS, ReadAt};
+#[cfg(not(feature = "stub-only"))]
+pub use hotplug::{MsEvent, clear_ms_callback, is_ms_inserted, register_ms_callback};
+pub use watch::{
+    Change, ChangedPath, DEFAULT_WATCH_CAPACITY, DEFAULT_WATCH_INTERVAL, WatchError, WatchId,
+    Watcher,
+};
+
 // ── IoError ─────────────────────────────────────────────────────────
 
 /// Error from a PSP I/O operation, wrapping the raw SCE error code.
@@ -54,12 +78,12 @@ impl core::fmt::Display for IoError {
 // ── Helpers ─────────────────────────────────────────────────────────
 
 /// Maximum path length (including null terminator) that fits on the stack.
-const MAX_PATH: usize = 256;
+pub(crate) const MAX_PATH: usize = 256;
 
 /// Copy a `&str` into a stack buffer with a null terminator.
 ///
 /// Returns `Err` if the path is too long.
-fn path_to_cstr(path: &str, buf: &mut [u8; MAX_PATH]) -> Result<(), IoError> {
+pub(crate) fn path_to_cstr(path: &str, buf: &mut [u8; MAX_PATH]) -> Result<(), IoError> {
     let bytes = path.as_bytes();
     if bytes.len() >= MAX_PATH {
         // SCE_KERNEL_ERROR_NAMETOOLONG = 0x8001005B
@@ -0,0 +1,291 @@
+//! Polling file watcher for hot-reloading assets.
+//!
+//! The PSP has no change notifications, so [`Watcher`] stats each watched
+//! file with `sceIoGetstat` and compares its modification time and size
+//! against the last check. Checks are rate-limited to one per interval, so
+//! [`Watcher::poll`] can be called every frame. This is mainly useful
+//! over psplink's `host0:`, where files are edited on the development
+//! machine while the game runs.
+
+use super::{IoError, MAX_PATH, path_to_cstr};
+use crate::sys::{SceIoStat, ScePspDateTime, sceIoGetstat};
+use crate::time::{Duration, Instant};
+
+/// How often a [`Watcher`] stats its files by default.
+pub const DEFAULT_WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Paths a [`Watcher`] created with [`Watcher::new`] can hold.
+pub const DEFAULT_WATCH_CAPACITY: usize = 8;
+
+/// Error from [`Watcher::watch`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchError {
+    /// All of the watcher's slots are in use.
+    Full,
+    /// The path is too long for a path buffer.
+    Io(IoError),
+}
+
+impl core::fmt::Display for WatchError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Full => f.write_str("watcher is full"),
+            Self::Io(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl From<IoError> for WatchError {
+    fn from(e: IoError) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// Handle to a watched path, returned by [`Watcher::watch`].
+///
+/// Each call to `watch` returns a new id, so an id kept after
+/// [`Watcher::unwatch`] never matches a path watched later in its slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WatchId(u32);
+
+/// How a watched file changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+    /// The modification time or size differs from the last check.
+    Modified,
+    /// The file could no longer be stat'd.
+    Removed,
+    /// The file exists again after being reported [`Removed`](Self::Removed),
+    /// or appeared for the first time.
+    Added,
+}
+
+/// A change reported by [`Watcher::poll`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChangedPath<'a> {
+    /// The handle [`Watcher::watch`] returned for this path.
+    pub id: WatchId,
+    /// The path as it was passed to [`Watcher::watch`].
+    pub path: &'a str,
+    /// What happened to the file.
+    pub change: Change,
+}
+
+/// What a file looked like at the last check.
+#[derive(Clone, Copy, PartialEq)]
+enum FileState {
+    Missing,
+    Present { mtime: ScePspDateTime, size: i64 },
+}
+
+impl FileState {
+    fn of(path: &[u8; MAX_PATH]) -> Self {
+        let mut st: SceIoStat = unsafe { core::mem::zeroed() };
+        if unsafe { sceIoGetstat(path.as_ptr(), &mut st) } < 0 {
+            Self::Missing
+        } else {
+            Self::Present {
+                mtime: st.st_mtime,
+                size: st.st_size,
+            }
+        }
+    }
+}
+
+struct Entry {
+    id: WatchId,
+    /// Null-terminated path.
+    path: [u8; MAX_PATH],
+    len: usize,
+    state: FileState,
+    /// Change found by the last check and not yet yielded.
+    pending: Option<Change>,
+}
+
+impl Entry {
+    fn path(&self) -> &str {
+        // SAFETY: copied from a `&str` by `watch`.
+        unsafe { core::str::from_utf8_unchecked(&self.path[..self.len]) }
+    }
+}
+
+/// Watches up to `N` files for changes by polling their status.
+///
+/// [`Watcher::new`] holds [`DEFAULT_WATCH_CAPACITY`] paths; other
+/// capacities are created with `Watcher::<N>::default()`. Each path takes
+/// a 256-byte buffer inline, so large watchers belong in a `static` or a
+/// `Box` rather than on a small thread stack.
+///
+/// # Example
+///
+/// ```ignore
+/// use psp::io::{Change, Watcher};
+///
+/// let mut watcher = Watcher::new();
+/// let background = watcher.watch("host0:/assets/background.bmp").unwrap();
+/// loop {
+///     for changed in watcher.poll() {
+///         if changed.id == background && changed.change != Change::Removed {
+///             // reload changed.path
+///         }
+///     }
+///     // ... draw the frame ...
+/// }
+/// ```
+pub struct Watcher<const N: usize = DEFAULT_WATCH_CAPACITY> {
+    entries: [Option<Entry>; N],
+    /// Id for the next [`watch`](Self::watch).
+    next_id: u32,
+    interval: Duration,
+    last_check: Option<Instant>,
+}
+
+impl Watcher {
+    /// An empty watcher for [`DEFAULT_WATCH_CAPACITY`] paths, checking
+    /// every [`DEFAULT_WATCH_INTERVAL`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<const N: usize> Default for Watcher<N> {
+    fn default() -> Self {
+        Self {
+            entries: core::array::from_fn(|_| None),
+            next_id: 0,
+            interval: DEFAULT_WATCH_INTERVAL,
+            last_check: None,
+        }
+    }
+}
+
+impl<const N: usize> Watcher<N> {
+    /// Builder-style [`set_interval`](Self::set_interval).
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Minimum time between two checks by [`poll`](Self::poll).
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Set the minimum time between two checks by [`poll`](Self::poll).
+    pub fn set_interval(&mut self, interval: Duration) {
+        self.interval = interval;
+    }
+
+    /// Start watching `path`.
+    ///
+    /// The file is stat'd right away, so only changes after this call are
+    /// reported. A file that doesn't exist yet is reported as
+    /// [`Change::Added`] once it appears.
+    pub fn watch(&mut self, path: &str) -> Result<WatchId, WatchError> {
+        let slot = self
+            .entries
+            .iter()
+            .position(Option::is_none)
+            .ok_or(WatchError::Full)?;
+        let mut buf = [0u8; MAX_PATH];
+        path_to_cstr(path, &mut buf)?;
+        let id = WatchId(self.next_id);
+        self.next_id = self.next_id.wrapping_add(1);
+        self.entries[slot] = Some(Entry {
+            id,
+            state: FileState::of(&buf),
+            path: buf,
+            len: path.len(),
+            pending: None,
+        });
+        Ok(id)
+    }
+
+    /// Stop watching the path behind `id`, freeing its slot.
+    ///
+    /// Returns `false` if `id` wasn't being watched.
+    pub fn unwatch(&mut self, id: WatchId) -> bool {
+        self.entries
+            .iter_mut()
+            .find(|entry| entry.as_ref().is_some_and(|entry| entry.id == id))
+            .and_then(Option::take)
+            .is_some()
+    }
+
+    /// The path behind `id`, if it's being watched.
+    pub fn path(&self, id: WatchId) -> Option<&str> {
+        self.entries
+            .iter()
+            .flatten()
+            .find(|entry| entry.id == id)
+            .map(Entry::path)
+    }
+
+    /// Number of watched paths.
+    pub fn len(&self) -> usize {
+        self.entries.iter().flatten().count()
+    }
+
+    /// Whether no paths are watched.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Maximum number of watched paths, `N`.
+    pub fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Check the watched files if the interval has passed since the last
+    /// check and return what changed.
+    ///
+    /// Between checks this only reads the clock. Changes left in the
+    /// iterator when it's dropped are returned again by the next call.
+    pub fn poll(&mut self) -> impl Iterator<Item = ChangedPath<'_>> {
+        let now = Instant::now();
+        let due = self
+            .last_check
+            .is_none_or(|last| now.duration_since(last) >= self.interval);
+        if due {
+            self.last_check = Some(now);
+            self.check();
+        }
+        self.drain()
+    }
+
+    /// Check every watched file now, regardless of the interval, and
+    /// return what changed.
+    pub fn poll_now(&mut self) -> impl Iterator<Item = ChangedPath<'_>> {
+        self.last_check = Some(Instant::now());
+        self.check();
+        self.drain()
+    }
+
+    fn check(&mut self) {
+        for entry in self.entries.iter_mut().flatten() {
+            let state = FileState::of(&entry.path);
+            let change = match (entry.state, state) {
+                (FileState::Missing, FileState::Missing) => None,
+                (FileState::Present { .. }, FileState::Missing) => Some(Change::Removed),
+                (FileState::Missing, FileState::Present { .. }) => Some(Change::Added),
+                (old, new) => (old != new).then_some(Change::Modified),
+            };
+            entry.state = state;
+            // The latest change wins over one that wasn't consumed.
+            if change.is_some() {
+                entry.pending = change;
+            }
+        }
+    }
+
+    fn drain(&mut self) -> impl Iterator<Item = ChangedPath<'_>> {
+        self.entries.iter_mut().flatten().filter_map(|entry| {
+            let change = entry.pending.take()?;
+            Some(ChangedPath {
+                id: entry.id,
+                path: entry.path(),
+                change,
+            })
+        })
+    }
+}
@@ -0,0 +1,426 @@
+//! Append-only persistent key/value store.
+//!
+//! [`Config`](crate::config::Config) rewrites its whole file on every
+//! save. A [`KvStore`] instead appends each update to a log, which is
+//! cheaper and safer for data that changes often, like high scores or
+//! checkpoints. Superseded entries stay in the log until
+//! [`compact()`](KvStore::compact) rewrites it.
+//!
+//! # Crash safety
+//!
+//! Updates are appended as checksummed records, and a [`Batch`] of
+//! updates is a single record, so it is applied entirely or not at all.
+//! A record torn by a crash or power loss fails its checksum and is
+//! dropped, along with anything after it, the next time the store is
+//! opened.
+//!
+//! Compaction writes the live entries to `<path>.tmp`, removes the log and
+//! renames the new file into place. The Memory Stick can't rename over an
+//! existing file, so [`open()`](KvStore::open) finishes an interrupted
+//! compaction: a `.tmp` file without a log is the complete new log, and
+//! one next to a log is a partial write and is discarded.
+//!
+//! # Log Format
+//!
+//! ```text
+//! Magic: b"RKVS" (4 bytes)
+//! Version: 1 (u16 LE)
+//! Reserved: 0 (u16 LE)
+//! Record[..]:
+//!   ops_len: u32 LE
+//!   Op[..] (ops_len bytes):
+//!     kind: u8 (0=Put, 1=Remove)
+//!     key_len: u8
+//!     key: [u8; key_len]
+//!     value_len: u32 LE (Put only)
+//!     value: [u8; value_len] (Put only)
+//!   Checksum: CRC-32 of ops_len and the ops (u32 LE)
+//! ```
+//!
+//! # Example
+//!
+//! ```ignore
+//! use psp::kvstore::{Batch, KvStore};
+//!
+//! let mut store = KvStore::open("ms0:/PSP/SAVEDATA/MYGAME/scores.kv")?;
+//! store.put("best", &score.to_le_bytes())?;
+//!
+//! // Both keys change together or not at all.
+//! store.apply(Batch::new().put("level", &[3]).remove("checkpoint"))?;
+//!
+//! if store.garbage_bytes() > 16 * 1024 {
+//!     store.compact()?;
+//! }
+//! ```
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::io::{File, IoError};
+use crate::sys::IoOpenFlags;
+
+const MAGIC: &[u8; 4] = b"RKVS";
+const VERSION: u16 = 1;
+const HEADER_SIZE: usize = 8;
+
+const OP_PUT: u8 = 0;
+const OP_REMOVE: u8 = 1;
+
+/// Error from a key/value store operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KvError {
+    /// I/O error reading or writing the log.
+    Io(IoError),
+    /// The file isn't a key/value log, or has an unsupported version.
+    InvalidFormat,
+    /// A key exceeds 255 bytes.
+    KeyTooLong,
+}
+
+impl core::fmt::Display for KvError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "kvstore I/O error: {e}"),
+            Self::InvalidFormat => write!(f, "invalid kvstore log format"),
+            Self::KeyTooLong => write!(f, "kvstore key too long"),
+        }
+    }
+}
+
+impl From<IoError> for KvError {
+    fn from(e: IoError) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// A group of updates applied atomically by [`KvStore::apply()`].
+#[derive(Debug, Clone, Default)]
+pub struct Batch {
+    ops: Vec<u8>,
+    key_too_long: bool,
+}
+
+impl Batch {
+    /// Create an empty batch.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set `key` to `value`.
+    pub fn put(&mut self, key: &str, value: &[u8]) -> &mut Self {
+        if self.push_key(OP_PUT, key) {
+            self.ops
+                .extend_from_slice(&(value.len() as u32).to_le_bytes());
+            self.ops.extend_from_slice(value);
+        }
+        self
+    }
+
+    /// Remove `key`.
+    pub fn remove(&mut self, key: &str) -> &mut Self {
+        self.push_key(OP_REMOVE, key);
+        self
+    }
+
+    /// Whether the batch has no updates.
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    fn push_key(&mut self, kind: u8, key: &str) -> bool {
+        if key.len() > 255 {
+            self.key_too_long = true;
+            return false;
+        }
+        self.ops.push(kind);
+        self.ops.push(key.len() as u8);
+        self.ops.extend_from_slice(key.as_bytes());
+        true
+    }
+}
+
+/// A key/value store backed by an append-only log file.
+///
+/// All entries are kept in memory; the log is only read by
+/// [`open()`](Self::open).
+pub struct KvStore {
+    path: String,
+    entries: Vec<(String, Vec<u8>)>,
+    /// Size of the log file in bytes, up to the last complete record.
+    log_len: usize,
+    /// A failed append may have left part of a record after `log_len`,
+    /// so the log must be compacted before anything else is appended.
+    torn: bool,
+}
+
+impl KvStore {
+    /// Open the store at `path`, creating an empty log if there is none.
+    ///
+    /// Replays the log, finishing or discarding an interrupted
+    /// [`compact()`](Self::compact) first. If the log ends in a torn
+    /// record, it is compacted so that new records aren't appended after
+    /// the damage.
+    pub fn open(path: &str) -> Result<Self, KvError> {
+        let tmp = tmp_path(path);
+        match (crate::io::stat(path), crate::io::stat(&tmp)) {
+            (Err(_), Ok(_)) => crate::io::rename(&tmp, path)?,
+            (Ok(_), Ok(_)) => crate::io::remove_file(&tmp)?,
+            _ => {},
+        }
+
+        let mut store = Self {
+            path: String::from(path),
+            entries: Vec::new(),
+            log_len: 0,
+            torn: false,
+        };
+        let data = match crate::io::read_to_vec(path) {
+            Ok(data) => data,
+            Err(_) if crate::io::stat(path).is_err() => {
+                store.compact()?;
+                return Ok(store);
+            },
+            Err(e) => return Err(e.into()),
+        };
+
+        let valid = store.replay(&data)?;
+        store.log_len = valid;
+        if valid < data.len() {
+            store.compact()?;
+        }
+        Ok(store)
+    }
+
+    /// Get the value of `key`.
+    pub fn get(&self, key: &str) -> Option<&[u8]> {
+        self.entries
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_slice())
+    }
+
+    /// Set `key` to `value`, appending the update to the log.
+    pub fn put(&mut self, key: &str, value: &[u8]) -> Result<(), KvError> {
+        self.apply(Batch::new().put(key, value))
+    }
+
+    /// Remove `key`, appending the update to the log.
+    ///
+    /// Returns whether the key existed. Nothing is written if it didn't.
+    pub fn remove(&mut self, key: &str) -> Result<bool, KvError> {
+        if self.get(key).is_none() {
+            return Ok(false);
+        }
+        self.apply(Batch::new().remove(key))?;
+        Ok(true)
+    }
+
+    /// Append all of `batch`'s updates as one record.
+    ///
+    /// The in-memory entries only change once the record is written, and
+    /// after a crash either all of the batch is in the log or none of it.
+    /// If the write fails partway, the log is compacted before the next
+    /// update so that nothing is appended after the torn record.
+    pub fn apply(&mut self, batch: &Batch) -> Result<(), KvError> {
+        self.apply_with(batch, write_all)
+    }
+
+    /// [`apply()`](Self::apply), with `append` writing the record to the
+    /// open log. Lets tests simulate a write that fails partway.
+    #[doc(hidden)]
+    pub fn apply_with(
+        &mut self,
+        batch: &Batch,
+        append: impl FnOnce(&File, &[u8]) -> Result<(), IoError>,
+    ) -> Result<(), KvError> {
+        if batch.key_too_long {
+            return Err(KvError::KeyTooLong);
+        }
+        if batch.is_empty() {
+            return Ok(());
+        }
+
+        if self.torn {
+            self.compact()?;
+        }
+        let record = encode_record(&batch.ops);
+        let file = File::open(
+            &self.path,
+            IoOpenFlags::WR_ONLY | IoOpenFlags::APPEND | IoOpenFlags::CREAT,
+        )?;
+        if let Err(e) = append(&file, &record) {
+            self.torn = true;
+            return Err(e.into());
+        }
+        drop(file);
+
+        self.log_len += record.len();
+        apply_ops(&mut self.entries, &batch.ops);
+        Ok(())
+    }
+
+    /// Rewrite the log with only the live entries.
+    ///
+    /// See the [module documentation](self) for why this is safe to
+    /// interrupt.
+    pub fn compact(&mut self) -> Result<(), KvError> {
+        let mut data = Vec::new();
+        data.extend_from_slice(MAGIC);
+        data.extend_from_slice(&VERSION.to_le_bytes());
+        data.extend_from_slice(&0u16.to_le_bytes());
+        if !self.entries.is_empty() {
+            let mut batch = Batch::new();
+            for (key, value) in &self.entries {
+                batch.put(key, value);
+            }
+            data.extend_from_slice(&encode_record(&batch.ops));
+        }
+
+        let tmp = tmp_path(&self.path);
+        crate::io::write_bytes(&tmp, &data)?;
+        if crate::io::stat(&self.path).is_ok() {
+            crate::io::remove_file(&self.path)?;
+        }
+        crate::io::rename(&tmp, &self.path)?;
+        self.log_len = data.len();
+        self.torn = false;
+        Ok(())
+    }
+
+    /// Iterate over all entries.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &[u8])> {
+        self.entries.iter().map(|(k, v)| (k.as_str(), v.as_slice()))
+    }
+
+    /// Number of entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the store has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Size of the log file in bytes.
+    pub fn log_len(&self) -> usize {
+        self.log_len
+    }
+
+    /// Bytes of the log that [`compact()`](Self::compact) would reclaim,
+    /// an estimate of when compacting is worthwhile.
+    pub fn garbage_bytes(&self) -> usize {
+        let live: usize = self
+            .entries
+            .iter()
+            .map(|(k, v)| 2 + k.len() + 4 + v.len())
+            .sum();
+        let overhead = HEADER_SIZE + if live > 0 { 8 } else { 0 };
+        self.log_len.saturating_sub(live + overhead)
+    }
+
+    /// Apply the records in `data`, returning the length of the valid
+    /// prefix.
+    fn replay(&mut self, data: &[u8]) -> Result<usize, KvError> {
+        if data.len() < HEADER_SIZE || &data[..4] != MAGIC {
+            return Err(KvError::InvalidFormat);
+        }
+        if u16::from_le_bytes([data[4], data[5]]) != VERSION {
+            return Err(KvError::InvalidFormat);
+        }
+
+        let mut pos = HEADER_SIZE;
+        while data.len() - pos >= 8 {
+            let len = u32::from_le_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]])
+                as usize;
+            let Some(end) = (pos + 4).checked_add(len).and_then(|e| e.checked_add(4)) else {
+                break;
+            };
+            if end > data.len() {
+                break;
+            }
+            let Some(ops) = crate::hash::strip_crc32(&data[pos..end]) else {
+                break;
+            };
+            let ops = &ops[4..];
+            if !ops_valid(ops) {
+                break;
+            }
+            apply_ops(&mut self.entries, ops);
+            pos = end;
+        }
+        Ok(pos)
+    }
+}
+
+fn tmp_path(path: &str) -> String {
+    format!("{path}.tmp")
+}
+
+fn encode_record(ops: &[u8]) -> Vec<u8> {
+    let mut record = Vec::with_capacity(ops.len() + 8);
+    record.extend_from_slice(&(ops.len() as u32).to_le_bytes());
+    record.extend_from_slice(ops);
+    let checksum = crate::hash::crc32(&record);
+    record.extend_from_slice(&checksum.to_le_bytes());
+    record
+}
+
+/// One decoded operation: the key, the value for a put, and the
+/// remaining ops.
+type Op<'a> = (&'a str, Option<&'a [u8]>, &'a [u8]);
+
+fn next_op(ops: &[u8]) -> Option<Op<'_>> {
+    let (&kind, rest) = ops.split_first()?;
+    let (&key_len, rest) = rest.split_first()?;
+    let (key, rest) = rest.split_at_checked(key_len as usize)?;
+    let key = core::str::from_utf8(key).ok()?;
+    match kind {
+        OP_PUT => {
+            let (len, rest) = rest.split_at_checked(4)?;
+            let len = u32::from_le_bytes([len[0], len[1], len[2], len[3]]) as usize;
+            let (value, rest) = rest.split_at_checked(len)?;
+            Some((key, Some(value), rest))
+        },
+        OP_REMOVE => Some((key, None, rest)),
+        _ => None,
+    }
+}
+
+fn ops_valid(mut ops: &[u8]) -> bool {
+    while !ops.is_empty() {
+        match next_op(ops) {
+            Some((_, _, rest)) => ops = rest,
+            None => return false,
+        }
+    }
+    true
+}
+
+/// Apply ops already checked by [`ops_valid`] or built by [`Batch`].
+fn apply_ops(entries: &mut Vec<(String, Vec<u8>)>, mut ops: &[u8]) {
+    while let Some((key, value, rest)) = next_op(ops) {
+        let idx = entries.iter().position(|(k, _)| k == key);
+        match (idx, value) {
+            (Some(i), Some(value)) => entries[i].1 = Vec::from(value),
+            (None, Some(value)) => entries.push((String::from(key), Vec::from(value))),
+            (Some(i), None) => {
+                entries.remove(i);
+            },
+            (None, None) => {},
+        }
+        ops = rest;
+    }
+}
+
+fn write_all(file: &File, mut data: &[u8]) -> Result<(), IoError> {
+    while !data.is_empty() {
+        let n = file.write(data)?;
+        if n == 0 {
+            return Err(IoError(-1));
+        }
+        data = &data[n..];
+    }
+    Ok(())
+}
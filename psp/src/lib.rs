@@ -6,6 +6,7 @@
 #![allow(stable_features, internal_features, clippy::missing_safety_doc)]
 // Nightly features still required for PSP target:
 #![feature(
+    allocator_api,         // mem::PartitionAllocator
     asm_experimental_arch, // MIPS inline assembly
     core_intrinsics,       // core::intrinsics (unreachable, etc.)
     c_variadic,            // variadic extern "C" functions
@@ -47,7 +48,10 @@ pub mod cache;
 #[cfg(not(feature = "stub-only"))]
 pub mod callback;
 #[cfg(not(feature = "stub-only"))]
+pub mod camera;
+#[cfg(not(feature = "stub-only"))]
 pub mod config;
+pub mod crypto;
 pub mod dialog;
 pub mod display;
 pub mod dma;
@@ -58,29 +62,45 @@ pub mod framebuffer;
 #[cfg(feature = "kernel")]
 pub mod gpio;
 pub mod gu_ext;
+pub mod hash;
 #[cfg(feature = "kernel")]
 pub mod hook;
+pub mod hprm;
 #[cfg(not(feature = "stub-only"))]
 pub mod http;
 #[cfg(feature = "kernel")]
 pub mod hw;
+pub mod ident;
 #[cfg(not(feature = "stub-only"))]
 pub mod image;
 pub mod input;
+#[cfg(not(feature = "stub-only"))]
+pub mod introspect;
 pub mod io;
+#[cfg(not(feature = "stub-only"))]
+pub mod kvstore;
+#[cfg(not(feature = "stub-only"))]
+pub mod log;
 pub mod math;
 #[cfg(feature = "kernel")]
 pub mod me;
 pub mod mem;
+pub mod model;
+pub mod module_mgr;
 #[cfg(not(feature = "stub-only"))]
 pub mod mp3;
 #[cfg(not(feature = "stub-only"))]
 pub mod mpeg;
+#[cfg(feature = "kernel")]
+pub mod nand;
 #[cfg(not(feature = "stub-only"))]
 pub mod net;
 #[cfg(not(feature = "stub-only"))]
 pub mod osk;
+#[cfg(not(feature = "stub-only"))]
+pub mod pak;
 pub mod power;
+pub mod rand;
 pub mod rtc;
 #[cfg(not(feature = "stub-only"))]
 pub mod savedata;
@@ -89,13 +109,20 @@ pub mod sync;
 pub mod sys;
 pub mod system_param;
 #[cfg(not(feature = "stub-only"))]
+pub mod task;
+#[cfg(not(feature = "stub-only"))]
 pub mod test_runner;
 #[cfg(not(feature = "stub-only"))]
 pub mod thread;
 pub mod time;
 #[cfg(not(feature = "stub-only"))]
 pub mod timer;
+#[cfg(not(feature = "stub-only"))]
+pub mod ui;
 pub mod usb;
+pub mod utility;
+#[cfg(not(feature = "stub-only"))]
+pub mod volatile_mem;
 #[cfg(not(feature = "stub-only"))]
 pub mod vram_alloc;
 pub mod wlan;
@@ -108,6 +135,8 @@ pub mod sysreg;
 #[cfg(not(feature = "stub-only"))]
 mod alloc_impl;
 #[cfg(not(feature = "stub-only"))]
+pub use alloc_impl::{AllocStats, alloc_stats, reset_peak};
+#[cfg(not(feature = "stub-only"))]
 pub mod panic;
 #[cfg(feature = "std")]
 mod std_support;
@@ -122,9 +151,7 @@ mod benchmark;
 #[cfg(not(feature = "stub-only"))]
 pub use benchmark::*;
 
-#[cfg(not(feature = "stub-only"))]
 mod constants;
-#[cfg(not(feature = "stub-only"))]
 pub use constants::*;
 
 #[doc(hidden)]
@@ -0,0 +1,305 @@
+//! Leveled, timestamped logging with screen and file sinks.
+//!
+//! Replaces ad-hoc `dprintln!` debugging with filtered log records that
+//! can be routed to the debug screen, a log file on the Memory Stick, or
+//! both. Each record is prefixed with the time since [`init()`] (from the
+//! monotonic tick counter) and its level.
+//!
+//! File output is appended and buffered; the buffer is flushed when it
+//! fills up, when [`FLUSH_INTERVAL`] has elapsed since the last flush,
+//! on every [`Level::Error`] record, and on [`flush()`] / [`shutdown()`].
+//!
+//! # Example
+//!
+//! ```ignore
+//! use psp::log::{self, Level, LogConfig};
+//!
+//! log::init(LogConfig {
+//!     to_screen: true,
+//!     to_file: Some("ms0:/PSP/GAME/MYAPP/log.txt"),
+//!     level: Level::Info,
+//! })
+//! .unwrap();
+//!
+//! psp::info!("loaded {} assets", 12);
+//! psp::warn!("texture cache is {}% full", 90);
+//! psp::debug!("filtered out at Level::Info");
+//! ```
+
+use crate::io::IoError;
+use crate::sync::SpinMutex;
+use crate::sys::{IoOpenFlags, SceUid};
+use crate::time::{Duration, Instant};
+use core::ffi::c_void;
+use core::fmt::{self, Write};
+use core::sync::atomic::{AtomicU8, Ordering};
+
+/// Log a message at an explicit [`Level`].
+///
+/// ```ignore
+/// psp::log!(psp::log::Level::Warn, "low battery: {}%", pct);
+/// ```
+#[macro_export]
+macro_rules! log {
+    ($level:expr, $($arg:tt)*) => {
+        $crate::log::log_args($level, core::format_args!($($arg)*))
+    };
+}
+
+/// Log a message at [`Level::Error`](crate::log::Level::Error).
+#[macro_export]
+macro_rules! error {
+    ($($arg:tt)*) => {
+        $crate::log!($crate::log::Level::Error, $($arg)*)
+    };
+}
+
+/// Log a message at [`Level::Warn`](crate::log::Level::Warn).
+#[macro_export]
+macro_rules! warn {
+    ($($arg:tt)*) => {
+        $crate::log!($crate::log::Level::Warn, $($arg)*)
+    };
+}
+
+/// Log a message at [`Level::Info`](crate::log::Level::Info).
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)*) => {
+        $crate::log!($crate::log::Level::Info, $($arg)*)
+    };
+}
+
+/// Log a message at [`Level::Debug`](crate::log::Level::Debug).
+#[macro_export]
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        $crate::log!($crate::log::Level::Debug, $($arg)*)
+    };
+}
+
+// ── Level ───────────────────────────────────────────────────────────
+
+/// Severity of a log record. Records below the configured level are
+/// discarded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u8)]
+pub enum Level {
+    Debug = 0,
+    Info = 1,
+    Warn = 2,
+    Error = 3,
+}
+
+impl Level {
+    /// Fixed-width label used in the record prefix.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Level::Debug => "DEBUG",
+            Level::Info => "INFO ",
+            Level::Warn => "WARN ",
+            Level::Error => "ERROR",
+        }
+    }
+}
+
+// ── LogConfig ───────────────────────────────────────────────────────
+
+/// Sink and filter configuration passed to [`init()`].
+#[derive(Debug, Clone, Copy)]
+pub struct LogConfig<'a> {
+    /// Print records to the debug screen (same output as `dprintln!`).
+    pub to_screen: bool,
+    /// Append records to this file, e.g. `"ms0:/log.txt"`.
+    pub to_file: Option<&'a str>,
+    /// Minimum level that is emitted.
+    pub level: Level,
+}
+
+impl Default for LogConfig<'_> {
+    fn default() -> Self {
+        Self {
+            to_screen: true,
+            to_file: None,
+            level: Level::Info,
+        }
+    }
+}
+
+// ── Logger state ────────────────────────────────────────────────────
+
+/// Maximum time buffered file output may wait before being written.
+pub const FLUSH_INTERVAL: Duration = Duration::from_secs(2);
+
+const FILE_BUF_SIZE: usize = 1024;
+
+/// Sentinel stored in [`MAX_LEVEL`] while logging is disabled.
+const LEVEL_OFF: u8 = u8::MAX;
+
+/// Lowest enabled level, checked before taking the lock so filtered-out
+/// records cost a single atomic load.
+static MAX_LEVEL: AtomicU8 = AtomicU8::new(LEVEL_OFF);
+
+static LOGGER: SpinMutex<Option<Logger>> = SpinMutex::new(None);
+
+struct FileSink {
+    fd: SceUid,
+    buf: [u8; FILE_BUF_SIZE],
+    len: usize,
+    last_flush: Instant,
+}
+
+impl FileSink {
+    fn flush(&mut self) {
+        let mut written = 0;
+        while written < self.len {
+            let ret = unsafe {
+                crate::sys::sceIoWrite(
+                    self.fd,
+                    self.buf[written..].as_ptr() as *const c_void,
+                    self.len - written,
+                )
+            };
+            if ret <= 0 {
+                break;
+            }
+            written += ret as usize;
+        }
+        self.len = 0;
+        self.last_flush = Instant::now();
+    }
+}
+
+impl Write for FileSink {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut bytes = s.as_bytes();
+        while !bytes.is_empty() {
+            if self.len == FILE_BUF_SIZE {
+                self.flush();
+            }
+            let n = bytes.len().min(FILE_BUF_SIZE - self.len);
+            self.buf[self.len..self.len + n].copy_from_slice(&bytes[..n]);
+            self.len += n;
+            bytes = &bytes[n..];
+        }
+        Ok(())
+    }
+}
+
+impl Drop for FileSink {
+    fn drop(&mut self) {
+        self.flush();
+        unsafe {
+            crate::sys::sceIoClose(self.fd);
+        }
+    }
+}
+
+struct Logger {
+    to_screen: bool,
+    file: Option<FileSink>,
+    start: Instant,
+}
+
+/// Timestamp prefix: seconds and milliseconds since `init()`.
+struct Timestamp(Duration);
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ms = self.0.as_millis();
+        write!(f, "[{:>5}.{:03}]", ms / 1000, ms % 1000)
+    }
+}
+
+// ── Public API ──────────────────────────────────────────────────────
+
+/// Initialize (or reconfigure) the global logger.
+///
+/// If a log file was previously open it is flushed and closed first. The
+/// new file is opened in append mode and created if missing.
+pub fn init(config: LogConfig<'_>) -> Result<(), IoError> {
+    let file = match config.to_file {
+        Some(path) => {
+            let f = crate::io::File::open(
+                path,
+                IoOpenFlags::WR_ONLY | IoOpenFlags::CREAT | IoOpenFlags::APPEND,
+            )?;
+            let fd = f.fd();
+            // Ownership of the descriptor moves to the FileSink.
+            core::mem::forget(f);
+            Some(FileSink {
+                fd,
+                buf: [0; FILE_BUF_SIZE],
+                len: 0,
+                last_flush: Instant::now(),
+            })
+        },
+        None => None,
+    };
+
+    let mut guard = LOGGER.lock();
+    *guard = Some(Logger {
+        to_screen: config.to_screen,
+        file,
+        start: Instant::now(),
+    });
+    MAX_LEVEL.store(config.level as u8, Ordering::Release);
+    Ok(())
+}
+
+/// Change the minimum level without touching the configured sinks.
+///
+/// Has no effect before [`init()`].
+pub fn set_level(level: Level) {
+    if LOGGER.lock().is_some() {
+        MAX_LEVEL.store(level as u8, Ordering::Release);
+    }
+}
+
+/// Returns `true` if a record at `level` would be emitted.
+pub fn enabled(level: Level) -> bool {
+    let max = MAX_LEVEL.load(Ordering::Acquire);
+    max != LEVEL_OFF && level as u8 >= max
+}
+
+/// Write any buffered file output to the Memory Stick.
+pub fn flush() {
+    if let Some(file) = LOGGER.lock().as_mut().and_then(|l| l.file.as_mut()) {
+        file.flush();
+    }
+}
+
+/// Flush and close the log file and disable logging.
+pub fn shutdown() {
+    MAX_LEVEL.store(LEVEL_OFF, Ordering::Release);
+    // Dropping the logger flushes and closes the file.
+    LOGGER.lock().take();
+}
+
+#[doc(hidden)]
+pub fn log_args(level: Level, args: fmt::Arguments<'_>) {
+    if !enabled(level) {
+        return;
+    }
+
+    let mut guard = LOGGER.lock();
+    let Some(logger) = guard.as_mut() else {
+        return;
+    };
+    let ts = Timestamp(logger.start.elapsed());
+
+    if let Some(file) = logger.file.as_mut() {
+        let _ = writeln!(file, "{} {} {}", ts, level.as_str(), args);
+        if level == Level::Error || file.last_flush.elapsed() >= FLUSH_INTERVAL {
+            file.flush();
+        }
+    }
+
+    let to_screen = logger.to_screen;
+    // Release the logger before touching the debug screen, which has its
+    // own lock.
+    drop(guard);
+    if to_screen {
+        crate::debug::print_args(format_args!("{} {} {}\n", ts, level.as_str(), args));
+    }
+}
@@ -32,6 +32,24 @@
 //! let result = executor.wait(&handle); // returns 42
 //! ```
 //!
+//! For jobs that transform a block of data, give the executor a work
+//! buffer and use [`MeExecutor::submit_buffer_task`]. It copies the input
+//! into ME-shared memory and hands the output back once the cache is
+//! coherent, so the caller never touches uncached addresses:
+//!
+//! ```ignore
+//! unsafe extern "C" fn invert(data: *mut u8, len: usize) -> i32 {
+//!     for i in 0..len {
+//!         *data.add(i) = !*data.add(i);
+//!     }
+//!     0
+//! }
+//!
+//! let mut executor = MeExecutor::with_buffer(4096, 16 * 1024).unwrap();
+//! let job = unsafe { executor.submit_buffer_task(invert, &input) }.unwrap();
+//! let (status, output) = executor.wait_job(&job).unwrap();
+//! ```
+//!
 //! # Kernel Mode Required
 //!
 //! All functions in this module require `feature = "kernel"` and the module
@@ -49,6 +67,13 @@ pub const UNCACHED_MASK: u32 = 0x4000_0000;
 /// The function receives a single `i32` argument and returns an `i32` result.
 pub type MeTask = unsafe extern "C" fn(arg: i32) -> i32;
 
+/// ME buffer task signature, for [`MeExecutor::submit_buffer_task`].
+///
+/// Receives the executor's work buffer (an uncached address) and the
+/// length of the input copied into it, and transforms the data in place.
+/// The return value is passed back alongside the output.
+pub type MeBufferTask = unsafe extern "C" fn(data: *mut u8, len: usize) -> i32;
+
 /// Parameters passed to the ME boot entry point.
 ///
 /// This struct is placed in ME-accessible (uncached) memory and its address
@@ -122,6 +147,16 @@ pub fn to_cached<T>(ptr: *mut T) -> *mut T {
     (ptr as u32 & !UNCACHED_MASK) as *mut T
 }
 
+/// Whether `addr` is in main RAM, which the ME can reach, rather than
+/// scratchpad, VRAM or hardware registers.
+#[cfg(all(target_os = "psp", feature = "kernel"))]
+fn is_me_visible(addr: usize) -> bool {
+    // Strip the cached/uncached/kernel segment bits. Main RAM is 32 MiB on
+    // the PSP-1000 and 64 MiB on later models.
+    let phys = addr as u32 & 0x1FFF_FFFF;
+    (0x0800_0000..0x0C00_0000).contains(&phys)
+}
+
 /// Allocate memory in the ME kernel partition (partition 3).
 ///
 /// Returns an uncached pointer suitable for ME access. The caller is
@@ -198,6 +233,15 @@ struct MeSharedState {
     real_arg: i32,
     /// Boot parameters for the ME (always points to the wrapper).
     boot_params: MeBootParams,
+    /// The buffer task run by a [`MeExecutor::submit_buffer_task`] job.
+    buffer_task: MeBufferTask,
+    /// Uncached address of the work buffer.
+    buffer: *mut u8,
+    /// Length of the input in the work buffer.
+    buffer_len: usize,
+    /// Bumped by every submit and reset, so a [`MeJob`] can tell whether
+    /// the slot still holds its task.
+    generation: u32,
 }
 
 /// An opaque handle to a submitted ME task.
@@ -212,6 +256,69 @@ pub struct MeHandle {
     _slot: u32,
 }
 
+/// A data job started by [`MeExecutor::submit_buffer_task`].
+///
+/// Use with [`MeExecutor::poll_job`] or [`MeExecutor::wait_job`] to get
+/// the task's result and output.
+#[cfg(feature = "kernel")]
+#[derive(Debug)]
+#[must_use]
+pub struct MeJob {
+    handle: MeHandle,
+    len: usize,
+    /// The slot's generation when the job was submitted.
+    generation: u32,
+}
+
+#[cfg(feature = "kernel")]
+impl MeJob {
+    /// Length of the input, and of the output slice.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the job was submitted with no data.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+/// Error from a [`MeExecutor`] buffer job.
+#[cfg(feature = "kernel")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeJobError {
+    /// The task's code is not in main RAM, where the ME can fetch it.
+    NotMeVisible,
+    /// The input is larger than the executor's work buffer.
+    TooLarge {
+        /// Length of the input.
+        len: usize,
+        /// Size of the work buffer.
+        capacity: usize,
+    },
+    /// A task is still running on the ME.
+    Busy,
+    /// The job's result is gone: the executor was reset or has run another
+    /// task since.
+    Stale,
+}
+
+#[cfg(feature = "kernel")]
+impl core::fmt::Display for MeJobError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::NotMeVisible => f.write_str("ME task code is not in main RAM"),
+            Self::TooLarge { len, capacity } => write!(
+                f,
+                "{} bytes of input do not fit the {}-byte ME work buffer",
+                len, capacity
+            ),
+            Self::Busy => f.write_str("a task is still running on the ME"),
+            Self::Stale => f.write_str("the ME job was superseded by a reset or another task"),
+        }
+    }
+}
+
 /// High-level Media Engine task executor.
 ///
 /// Manages uncached memory allocation, ME boot parameters, and
@@ -241,6 +348,12 @@ pub struct MeExecutor {
     stack_block: crate::sys::SceUid,
     /// Size of the ME stack.
     stack_size: u32,
+    /// Uncached pointer to the work buffer for buffer tasks.
+    buffer: *mut u8,
+    /// Block ID for the work buffer, if one was allocated.
+    buffer_block: Option<crate::sys::SceUid>,
+    /// Size of the work buffer.
+    buffer_size: usize,
 }
 
 #[cfg(feature = "kernel")]
@@ -254,6 +367,19 @@ impl MeExecutor {
     ///
     /// Returns the PSP error code if memory allocation fails.
     pub fn new(stack_size: u32) -> Result<Self, i32> {
+        Self::with_buffer(stack_size, 0)
+    }
+
+    /// Create a `MeExecutor` with a `buffer_size`-byte work buffer for
+    /// [`submit_buffer_task`](Self::submit_buffer_task).
+    ///
+    /// The buffer is allocated next to the stack in partition 3, rounded
+    /// up to whole cache lines.
+    ///
+    /// # Errors
+    ///
+    /// Returns the PSP error code if memory allocation fails.
+    pub fn with_buffer(stack_size: u32, buffer_size: usize) -> Result<Self, i32> {
         let shared_size = core::mem::size_of::<MeSharedState>() as u32;
 
         // SAFETY: Kernel mode is required. We allocate from partition 3.
@@ -273,11 +399,30 @@ impl MeExecutor {
                 },
             };
 
+        // Whole cache lines, so invalidating the buffer can't discard
+        // neighbouring data.
+        let buffer_size = (buffer_size + 63) & !63;
+        let (buffer, buffer_block) = if buffer_size == 0 {
+            (core::ptr::null_mut(), None)
+        } else {
+            match unsafe { me_alloc(buffer_size as u32, b"MeExecBuffer\0".as_ptr()) } {
+                Ok((ptr, block)) => (ptr, Some(block)),
+                Err(e) => {
+                    unsafe {
+                        crate::sys::sceKernelFreePartitionMemory(stack_block);
+                        crate::sys::sceKernelFreePartitionMemory(shared_block);
+                    }
+                    return Err(e);
+                },
+            }
+        };
+
         // Initialize shared state to idle
         // SAFETY: shared is a valid uncached pointer.
         unsafe {
             core::ptr::write_volatile(&raw mut (*shared).status, status::IDLE);
             core::ptr::write_volatile(&raw mut (*shared).result, 0);
+            core::ptr::write_volatile(&raw mut (*shared).generation, 0);
         }
 
         Ok(Self {
@@ -286,9 +431,18 @@ impl MeExecutor {
             stack_base,
             stack_block,
             stack_size,
+            buffer,
+            buffer_block,
+            buffer_size,
         })
     }
 
+    /// Size of the work buffer for
+    /// [`submit_buffer_task`](Self::submit_buffer_task).
+    pub fn buffer_capacity(&self) -> usize {
+        self.buffer_size
+    }
+
     /// Submit a task to the Media Engine.
     ///
     /// The ME will execute `task(arg)` on its own core. Use the returned
@@ -325,6 +479,7 @@ impl MeExecutor {
         let stack_top = self.stack_base.add(self.stack_size as usize);
 
         // Write the real task and arg to dedicated fields first
+        self.bump_generation();
         unsafe {
             core::ptr::write_volatile(&raw mut (*self.shared).status, status::RUNNING);
             core::ptr::write_volatile(&raw mut (*self.shared).real_task, task);
@@ -352,6 +507,125 @@ impl MeExecutor {
         MeHandle { _slot: 0 }
     }
 
+    /// Run `task` on the ME over a copy of `data`.
+    ///
+    /// `data` is copied into the executor's work buffer and the data cache
+    /// written back, then `task` runs on the ME with the buffer's uncached
+    /// address. Collect the result and the transformed bytes with
+    /// [`poll_job`](Self::poll_job) or [`wait_job`](Self::wait_job).
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`MeJobError::NotMeVisible`] if `task` is not in main
+    /// RAM, where the ME can fetch it, [`MeJobError::TooLarge`] if `data`
+    /// doesn't fit the work buffer, and [`MeJobError::Busy`] while a task
+    /// is running.
+    ///
+    /// # Safety
+    ///
+    /// - `task` must be safe to execute on the ME core, as for
+    ///   [`submit`](Self::submit), and may only access the `len` bytes it
+    ///   is given (or up to [`buffer_capacity`](Self::buffer_capacity)).
+    /// - The caller must be in kernel mode.
+    #[cfg(all(target_os = "psp", feature = "kernel"))]
+    pub unsafe fn submit_buffer_task(
+        &mut self,
+        task: MeBufferTask,
+        data: &[u8],
+    ) -> Result<MeJob, MeJobError> {
+        unsafe extern "C" fn buffer_trampoline(shared_addr: i32) -> i32 {
+            let shared = shared_addr as *mut MeSharedState;
+            unsafe {
+                let task = core::ptr::read_volatile(&raw const (*shared).buffer_task);
+                let buffer = core::ptr::read_volatile(&raw const (*shared).buffer);
+                let len = core::ptr::read_volatile(&raw const (*shared).buffer_len);
+                task(buffer, len)
+            }
+        }
+
+        if !is_me_visible(task as usize) {
+            return Err(MeJobError::NotMeVisible);
+        }
+        if data.len() > self.buffer_size {
+            return Err(MeJobError::TooLarge {
+                len: data.len(),
+                capacity: self.buffer_size,
+            });
+        }
+        if !self.is_idle() {
+            return Err(MeJobError::Busy);
+        }
+
+        // Copy through the cached view and write it back; uncached writes
+        // are much slower for bulk data.
+        unsafe {
+            if !data.is_empty() {
+                let cached = to_cached(self.buffer);
+                core::ptr::copy_nonoverlapping(data.as_ptr(), cached, data.len());
+                crate::sys::sceKernelDcacheWritebackInvalidateRange(
+                    cached as *const core::ffi::c_void,
+                    self.buffer_size as u32,
+                );
+            }
+            core::ptr::write_volatile(&raw mut (*self.shared).buffer_task, task);
+            core::ptr::write_volatile(&raw mut (*self.shared).buffer, self.buffer);
+            core::ptr::write_volatile(&raw mut (*self.shared).buffer_len, data.len());
+            let handle = self.submit(buffer_trampoline, self.shared as i32);
+            Ok(MeJob {
+                handle,
+                len: data.len(),
+                generation: self.generation(),
+            })
+        }
+    }
+
+    /// Poll a [`MeJob`] without blocking.
+    ///
+    /// Returns the task's result and the output bytes once it has
+    /// finished, or `None` while it's still running.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`MeJobError::Stale`] if the executor has been reset or
+    /// has started another task since `job` was submitted, so its output
+    /// has been overwritten.
+    pub fn poll_job(&self, job: &MeJob) -> Result<Option<(i32, &[u8])>, MeJobError> {
+        if job.generation != self.generation() {
+            return Err(MeJobError::Stale);
+        }
+        let Some(result) = self.poll(&job.handle) else {
+            return Ok(None);
+        };
+        if job.len == 0 {
+            return Ok(Some((result, &[])));
+        }
+        let cached = to_cached(self.buffer);
+        // SAFETY: The ME has finished with the buffer. Invalidating drops
+        // any stale lines so the cached view sees what the ME wrote, and
+        // the borrow of `self` keeps the buffer from being resubmitted.
+        unsafe {
+            crate::sys::sceKernelDcacheInvalidateRange(
+                cached as *const core::ffi::c_void,
+                self.buffer_size as u32,
+            );
+            Ok(Some((result, core::slice::from_raw_parts(cached, job.len))))
+        }
+    }
+
+    /// Block until a [`MeJob`] finishes and return its result and output.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`MeJobError::Stale`] like [`poll_job`](Self::poll_job).
+    pub fn wait_job(&self, job: &MeJob) -> Result<(i32, &[u8]), MeJobError> {
+        loop {
+            if let Some(done) = self.poll_job(job)? {
+                return Ok(done);
+            }
+            core::hint::spin_loop();
+        }
+    }
+
     /// Poll for task completion without blocking.
     ///
     /// Returns `Some(result)` if the task has completed, `None` if it's
@@ -387,10 +661,22 @@ impl MeExecutor {
     ///
     /// Call this after retrieving a result to allow submitting new tasks.
     pub fn reset(&mut self) {
+        self.bump_generation();
         unsafe {
             core::ptr::write_volatile(&raw mut (*self.shared).status, status::IDLE);
         }
     }
+
+    /// The slot's current generation.
+    fn generation(&self) -> u32 {
+        unsafe { core::ptr::read_volatile(&raw const (*self.shared).generation) }
+    }
+
+    /// Invalidate every [`MeJob`] handed out so far.
+    fn bump_generation(&mut self) {
+        let next = self.generation().wrapping_add(1);
+        unsafe { core::ptr::write_volatile(&raw mut (*self.shared).generation, next) };
+    }
 }
 
 #[cfg(feature = "kernel")]
@@ -398,6 +684,9 @@ impl Drop for MeExecutor {
     fn drop(&mut self) {
         // SAFETY: We own these allocations
         unsafe {
+            if let Some(block) = self.buffer_block {
+                crate::sys::sceKernelFreePartitionMemory(block);
+            }
             crate::sys::sceKernelFreePartitionMemory(self.stack_block);
             crate::sys::sceKernelFreePartitionMemory(self.shared_block);
         }
@@ -14,6 +14,11 @@
 //! | ME Kernel | 3  | CPU + ME      | Shared state, ME task stacks      |
 //! | ME User   | 7  | CPU + ME      | ME-accessible user memory         |
 //!
+//! [`PartitionAllocator`] covers the other direction: placing standard
+//! collections in a partition of your choice through the unstable
+//! `allocator_api`. [`VolatileMem`] locks the extra 4 MB volatile region
+//! as a plain byte buffer.
+//!
 //! # Kernel Mode Required
 //!
 //! Partitions 1, 3-5, 8-12 require kernel mode. Partition 2 is available
@@ -263,3 +268,225 @@ pub fn alloc_me_bytes(size: u32, name: &[u8]) -> Result<PartitionAlloc<MePartiti
     // SAFETY: Byte buffers don't need initialization
     unsafe { PartitionAlloc::<MePartition, u8>::new_uninit(size, name) }
 }
+
+// ── PartitionAllocator ──────────────────────────────────────────────
+
+/// Bytes reserved before each allocation for its block ID.
+const BLOCK_ID_SIZE: usize = core::mem::size_of::<SceUid>();
+/// Kernel name of blocks made by [`PartitionAllocator`].
+const BLOCK_NAME: &[u8] = b"PartitionAllocator\0";
+
+/// A [`core::alloc::Allocator`] that places each allocation in its own
+/// kernel memory block in a chosen partition.
+///
+/// Use it with the `allocator_api` collection constructors to put big
+/// buffers somewhere other than the global heap, such as the ME user
+/// partition for media buffers:
+///
+/// ```ignore
+/// #![feature(allocator_api)]
+/// use psp::mem::PartitionAllocator;
+/// use psp::sys::SceSysMemPartitionId;
+///
+/// let me_user = PartitionAllocator::new(SceSysMemPartitionId::SceKernelMeUserPartition);
+/// let mut frame: Vec<u8, _> = Vec::with_capacity_in(480 * 272 * 4, me_user);
+/// ```
+///
+/// Every allocation (and every reallocation while a collection grows)
+/// costs one kernel block, and the firmware only supports a limited
+/// number of live blocks, so reserve capacity up front and keep this for
+/// a handful of large buffers. Kernel-only partitions fail to allocate
+/// in user mode. For the extra 4 MB on PSP-2000 and later, see
+/// [`crate::volatile_mem`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PartitionAllocator {
+    partition: SceSysMemPartitionId,
+}
+
+impl PartitionAllocator {
+    /// Allocator for user partition 2, the partition the global heap
+    /// lives in.
+    pub const USER: Self = Self::new(SceSysMemPartitionId::SceKernelPrimaryUserPartition);
+
+    /// Allocator for `partition`.
+    pub const fn new(partition: SceSysMemPartitionId) -> Self {
+        Self { partition }
+    }
+
+    /// The partition this allocator draws from.
+    pub fn partition(&self) -> SceSysMemPartitionId {
+        self.partition
+    }
+}
+
+// SAFETY: Each allocation is a distinct kernel block that stays valid
+// until `deallocate`; copies of the allocator share no state, so memory
+// from one copy can be freed through any other.
+unsafe impl core::alloc::Allocator for PartitionAllocator {
+    fn allocate(
+        &self,
+        layout: core::alloc::Layout,
+    ) -> Result<core::ptr::NonNull<[u8]>, core::alloc::AllocError> {
+        use core::ptr::NonNull;
+
+        if layout.size() == 0 {
+            let dangling = core::ptr::without_provenance_mut::<u8>(layout.align());
+            // SAFETY: Alignments are non-zero.
+            let ptr = unsafe { NonNull::new_unchecked(dangling) };
+            return Ok(NonNull::slice_from_raw_parts(ptr, 0));
+        }
+
+        // Room for the block ID in front of the data plus worst-case
+        // alignment padding.
+        let total = layout
+            .size()
+            .checked_add(BLOCK_ID_SIZE + layout.align())
+            .filter(|&total| total <= u32::MAX as usize)
+            .ok_or(core::alloc::AllocError)?;
+        let block_id = unsafe {
+            sceKernelAllocPartitionMemory(
+                self.partition,
+                BLOCK_NAME.as_ptr(),
+                SceSysMemBlockTypes::Low,
+                total as u32,
+                core::ptr::null_mut(),
+            )
+        };
+        if block_id.0 < 0 {
+            return Err(core::alloc::AllocError);
+        }
+
+        // SAFETY: The block is `total` bytes, enough for the ID and the
+        // aligned data.
+        unsafe {
+            let base = sceKernelGetBlockHeadAddr(block_id) as *mut u8;
+            let data = base.add(BLOCK_ID_SIZE);
+            let data = data.add(data.align_offset(layout.align()));
+            data.sub(BLOCK_ID_SIZE)
+                .cast::<SceUid>()
+                .write_unaligned(block_id);
+            Ok(NonNull::slice_from_raw_parts(
+                NonNull::new_unchecked(data),
+                layout.size(),
+            ))
+        }
+    }
+
+    unsafe fn deallocate(&self, ptr: core::ptr::NonNull<u8>, layout: core::alloc::Layout) {
+        if layout.size() == 0 {
+            return;
+        }
+        // SAFETY: `allocate` stored the block ID right before the data.
+        unsafe {
+            let block_id = ptr
+                .as_ptr()
+                .sub(BLOCK_ID_SIZE)
+                .cast::<SceUid>()
+                .read_unaligned();
+            sceKernelFreePartitionMemory(block_id);
+        }
+    }
+}
+
+// ── VolatileMem ─────────────────────────────────────────────────────
+
+/// The extra 4 MB volatile memory region (PSP-2000 and later), locked for
+/// exclusive use as one raw byte buffer.
+///
+/// Derefs to the whole region and unlocks it on drop. Good for scratch
+/// space whose contents don't need to survive a suspend, such as a
+/// decompression window:
+///
+/// ```ignore
+/// use psp::mem::VolatileMem;
+///
+/// let mut scratch = VolatileMem::lock()?;
+/// let window = &mut scratch[..64 * 1024];
+/// inflate_into(window);
+/// ```
+///
+/// The region's contents are lost when the PSP suspends, and nothing here
+/// tracks that. For a bump allocator with suspend detection, use
+/// [`crate::volatile_mem`] instead.
+#[cfg(not(feature = "stub-only"))]
+pub struct VolatileMem {
+    base: *mut u8,
+    size: usize,
+}
+
+#[cfg(not(feature = "stub-only"))]
+impl VolatileMem {
+    /// Lock the region, waiting if another user holds it.
+    ///
+    /// Returns the SCE error code on failure.
+    pub fn lock() -> Result<Self, i32> {
+        Self::lock_with(crate::sys::sceKernelVolatileMemLock)
+    }
+
+    /// Lock the region, failing immediately if it is in use.
+    ///
+    /// Returns the SCE error code on failure.
+    pub fn try_lock() -> Result<Self, i32> {
+        Self::lock_with(crate::sys::sceKernelVolatileMemTryLock)
+    }
+
+    fn lock_with(lock_fn: crate::volatile_mem::LockFn) -> Result<Self, i32> {
+        let (base, size) = crate::volatile_mem::lock_raw(lock_fn)?;
+        Ok(Self { base, size })
+    }
+
+    /// Start address of the region.
+    pub fn as_ptr(&self) -> *mut u8 {
+        self.base
+    }
+
+    /// Size of the region in bytes.
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    /// Whether the region is empty (never true for a successful lock).
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+}
+
+#[cfg(not(feature = "stub-only"))]
+impl core::ops::Deref for VolatileMem {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        // SAFETY: The region stays locked, and so exclusively ours, until
+        // drop.
+        unsafe { core::slice::from_raw_parts(self.base, self.size) }
+    }
+}
+
+#[cfg(not(feature = "stub-only"))]
+impl core::ops::DerefMut for VolatileMem {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        // SAFETY: As in `deref`; `&mut self` rules out other borrows.
+        unsafe { core::slice::from_raw_parts_mut(self.base, self.size) }
+    }
+}
+
+#[cfg(not(feature = "stub-only"))]
+impl Drop for VolatileMem {
+    fn drop(&mut self) {
+        crate::volatile_mem::unlock();
+    }
+}
+
+#[cfg(not(feature = "stub-only"))]
+// SAFETY: The guard owns the locked region outright.
+unsafe impl Send for VolatileMem {}
+
+#[cfg(not(feature = "stub-only"))]
+impl core::fmt::Debug for VolatileMem {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("VolatileMem")
+            .field("base", &self.base)
+            .field("size", &self.size)
+            .finish()
+    }
+}
@@ -0,0 +1,258 @@
+//! Hardware model detection and capability flags.
+//!
+//! PSP models differ in ways that matter to homebrew: the PSP-2000 and
+//! later have 64 MB of RAM, the PSP Go has internal storage (`ef0:`) and
+//! no UMD drive, and the PSP Street (E1000) has neither Wi-Fi nor video
+//! out. Code that depends on these differences should consult this module
+//! instead of duplicating its own checks.
+//!
+//! # Detection
+//!
+//! With `feature = "kernel"`, [`detect()`] asks the kernel directly via
+//! `sceKernelGetModel`. In user mode it tries the CFW `KUBridge` library,
+//! then falls back to probing for `ef0:`, which identifies a PSP Go. If
+//! none of these give a definite answer, [`detect()`] returns
+//! [`ModelError::Undetermined`]; the free capability helpers such as
+//! [`has_extra_ram()`] still answer in that case by probing the hardware
+//! directly.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use psp::model;
+//!
+//! match model::detect() {
+//!     Ok(m) => psp::dprintln!("Running on a {}", m.name()),
+//!     Err(e) => psp::dprintln!("Model unknown: {}", e),
+//! }
+//! if model::is_emulator() {
+//!     psp::dprintln!("Hello, PPSSPP");
+//! }
+//! ```
+
+use core::ffi::c_void;
+
+/// A PSP hardware family.
+///
+/// Motherboard revisions within a family (e.g. the 03g/04g/07g/09g
+/// PSP-3000 boards) are grouped together.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PspModel {
+    /// PSP-1000 ("fat"), 32 MB RAM.
+    Psp1000,
+    /// PSP-2000 ("slim"), 64 MB RAM.
+    Psp2000,
+    /// PSP-3000 ("brite"), 64 MB RAM.
+    Psp3000,
+    /// PSP Go (N1000), 64 MB RAM, 16 GB internal storage, no UMD drive.
+    PspGo,
+    /// PSP Street (E1000), 64 MB RAM, no Wi-Fi or video out.
+    PspStreet,
+}
+
+impl PspModel {
+    /// Map a zero-based model generation, as returned by
+    /// `sceKernelGetModel`, to a model family.
+    pub fn from_generation(generation: i32) -> Option<Self> {
+        match generation {
+            0 => Some(Self::Psp1000),
+            1 => Some(Self::Psp2000),
+            2 | 3 | 6 | 8 => Some(Self::Psp3000),
+            4 => Some(Self::PspGo),
+            10 => Some(Self::PspStreet),
+            _ => None,
+        }
+    }
+
+    /// Marketing name, e.g. `"PSP-3000"`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Psp1000 => "PSP-1000",
+            Self::Psp2000 => "PSP-2000",
+            Self::Psp3000 => "PSP-3000",
+            Self::PspGo => "PSP Go",
+            Self::PspStreet => "PSP Street",
+        }
+    }
+
+    /// Whether the model has 64 MB of RAM rather than 32 MB.
+    ///
+    /// The extra RAM is only usable by apps that request the extended
+    /// user partition.
+    pub fn has_extra_ram(self) -> bool {
+        self != Self::Psp1000
+    }
+
+    /// Whether the model has built-in flash storage (`ef0:`).
+    pub fn has_internal_storage(self) -> bool {
+        self == Self::PspGo
+    }
+
+    /// Whether the model has a UMD drive.
+    pub fn has_umd_drive(self) -> bool {
+        self != Self::PspGo
+    }
+
+    /// Whether the model has component/composite video out.
+    pub fn has_video_out(self) -> bool {
+        matches!(self, Self::Psp2000 | Self::Psp3000 | Self::PspGo)
+    }
+
+    /// Whether the model has the connector for the headphone remote.
+    pub fn has_remote_port(self) -> bool {
+        self != Self::PspGo
+    }
+
+    /// Whether the model has Wi-Fi.
+    pub fn has_wlan(self) -> bool {
+        self != Self::PspStreet
+    }
+}
+
+impl core::fmt::Display for PspModel {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Error from [`detect()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModelError {
+    /// The model query syscall failed with this SCE error code.
+    Syscall(i32),
+    /// The console reported a model generation this crate doesn't know.
+    UnknownGeneration(i32),
+    /// No definite answer is available from user mode.
+    Undetermined,
+}
+
+impl core::fmt::Display for ModelError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Syscall(code) => write!(f, "model query failed: {:#010x}", *code as u32),
+            Self::UnknownGeneration(generation) => {
+                write!(f, "unknown model generation {}", generation)
+            },
+            Self::Undetermined => f.write_str("model could not be determined"),
+        }
+    }
+}
+
+/// Detect the console's model family.
+///
+/// See the [module documentation](self) for the detection strategy.
+pub fn detect() -> Result<PspModel, ModelError> {
+    match query_generation() {
+        Ok(generation) => {
+            PspModel::from_generation(generation).ok_or(ModelError::UnknownGeneration(generation))
+        },
+        // The PSP Go is the only model with internal storage.
+        Err(ModelError::Undetermined) if probe_ef0() => Ok(PspModel::PspGo),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(feature = "kernel")]
+fn query_generation() -> Result<i32, ModelError> {
+    let generation = unsafe { crate::sys::sceKernelGetModel() };
+    if generation < 0 {
+        Err(ModelError::Syscall(generation))
+    } else {
+        Ok(generation)
+    }
+}
+
+#[cfg(not(feature = "kernel"))]
+fn query_generation() -> Result<i32, ModelError> {
+    // KUBridge is weakly imported, so this returns an error code rather
+    // than failing to link when the CFW doesn't provide it.
+    let generation = unsafe { crate::sys::kubridge::kuKernelGetModel() };
+    if generation >= 0 {
+        Ok(generation)
+    } else {
+        Err(ModelError::Undetermined)
+    }
+}
+
+/// Root of the PSP Go's internal storage.
+const EF0_ROOT: &[u8] = b"ef0:/\0";
+
+/// Whether `ef0:` (PSP Go internal storage) can be opened.
+fn probe_ef0() -> bool {
+    let fd = unsafe { crate::sys::sceIoDopen(EF0_ROOT.as_ptr()) };
+    if fd.0 < 0 {
+        return false;
+    }
+    unsafe {
+        crate::sys::sceIoDclose(fd);
+    }
+    true
+}
+
+/// User partition size above which the extended 64 MB layout is active.
+const EXTENDED_PARTITION_THRESHOLD: usize = 32 * 1024 * 1024;
+
+/// Whether the console has 64 MB of RAM.
+///
+/// Asks the kernel for the model generation (`sceKernelGetModel`, or
+/// KUBridge in user mode); every generation after the PSP-1000 has the
+/// extra RAM, including ones [`PspModel`] doesn't know yet. Only when
+/// user mode has no way to ask does this fall back to checking whether
+/// the user partition is larger than a PSP-1000 could provide, which
+/// only detects the extra RAM when the app requested the extended
+/// partition (when it's the only way to use it).
+pub fn has_extra_ram() -> bool {
+    match query_generation() {
+        Ok(generation) => generation != 0,
+        Err(ModelError::Undetermined) if probe_ef0() => true,
+        Err(ModelError::Undetermined) => {
+            (unsafe { crate::sys::sceKernelTotalFreeMemSize() }) > EXTENDED_PARTITION_THRESHOLD
+        },
+        Err(_) => false,
+    }
+}
+
+/// Whether the console has internal storage (`ef0:`).
+pub fn has_internal_storage() -> bool {
+    match detect() {
+        Ok(model) => model.has_internal_storage(),
+        Err(_) => probe_ef0(),
+    }
+}
+
+/// Whether the console has a UMD drive.
+///
+/// The PSP Go is the only model without one, and the only one with
+/// `ef0:`, so this falls back to probing for internal storage.
+pub fn has_umd_drive() -> bool {
+    match detect() {
+        Ok(model) => model.has_umd_drive(),
+        Err(_) => !probe_ef0(),
+    }
+}
+
+/// PPSSPP's emulator control device.
+const EMULATOR_DEVICE: &[u8] = b"kemulator:\0";
+
+/// `kemulator:` devctl command that reports whether this is an emulator.
+const EMULATOR_DEVCTL_IS_EMULATOR: u32 = 3;
+
+/// Whether the code is running under the PPSSPP emulator.
+///
+/// PPSSPP exposes a `kemulator:` device whose devctl commands let
+/// homebrew talk to the emulator. Real hardware has no such device, so
+/// the query fails there.
+pub fn is_emulator() -> bool {
+    let mut out: u32 = 0;
+    let ret = unsafe {
+        crate::sys::sceIoDevctl(
+            EMULATOR_DEVICE.as_ptr(),
+            EMULATOR_DEVCTL_IS_EMULATOR,
+            core::ptr::null_mut(),
+            0,
+            &mut out as *mut u32 as *mut c_void,
+            core::mem::size_of::<u32>() as i32,
+        )
+    };
+    ret >= 0 && out != 0
+}
@@ -0,0 +1,208 @@
+//! Loading, starting and stopping other PRX modules.
+//!
+//! [`Module::load`] loads a PRX and returns a handle that unloads it again
+//! when dropped, stopping it first if it was started. [`find_by_name`]
+//! looks up a module that is already loaded, e.g. to check whether a
+//! plugin is present.
+//!
+//! User-mode modules can only load from the Memory Stick and the UMD;
+//! loading from `flash0:` or loading kernel modules needs a kernel-mode
+//! caller (see [`module_kernel!`](crate::module_kernel)). Loading must
+//! happen on a thread, not in an interrupt or callback.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use psp::module_mgr::{Module, find_by_name};
+//!
+//! let helper = Module::load("ms0:/PSP/GAME/MYGAME/helper.prx")?;
+//! helper.start(b"")?;
+//! // ... use the helper's exports ...
+//! drop(helper); // stops and unloads it
+//!
+//! if find_by_name("sceNetInet_Library").is_some() {
+//!     psp::dprintln!("networking modules are loaded");
+//! }
+//! ```
+
+use core::cell::Cell;
+use core::ffi::c_void;
+use core::ptr;
+
+use crate::sys::{
+    SceKernelModuleInfo, SceUid, sceKernelGetModuleIdList, sceKernelLoadModule,
+    sceKernelQueryModuleInfo, sceKernelStartModule, sceKernelStopModule, sceKernelUnloadModule,
+};
+
+/// Most module IDs [`module_ids`] reports.
+pub const MAX_MODULES: usize = 256;
+
+/// Error from a module operation, wrapping the raw SCE error code.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct ModuleError(pub i32);
+
+impl core::fmt::Debug for ModuleError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "ModuleError({:#010x})", self.0 as u32)
+    }
+}
+
+impl core::fmt::Display for ModuleError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "module error {:#010x}", self.0 as u32)
+    }
+}
+
+/// A loaded PRX module.
+///
+/// A module loaded with [`load`](Self::load) is owned: dropping the handle
+/// stops it (if [`start`](Self::start) succeeded) and unloads it. Use
+/// [`leak`](Self::leak) to keep it loaded. Handles from [`find_by_name`]
+/// or [`from_id`](Self::from_id) are borrowed and never stop or unload
+/// the module.
+pub struct Module {
+    id: SceUid,
+    owned: bool,
+    started: Cell<bool>,
+}
+
+impl Module {
+    /// Load the module at `path`, e.g. `"ms0:/seplugins/helper.prx"`.
+    ///
+    /// The module is loaded but not started.
+    pub fn load(path: &str) -> Result<Self, ModuleError> {
+        let mut buf = [0u8; crate::io::MAX_PATH];
+        crate::io::path_to_cstr(path, &mut buf).map_err(|e| ModuleError(e.0))?;
+        let id = unsafe { sceKernelLoadModule(buf.as_ptr(), 0, ptr::null_mut()) };
+        if id.0 < 0 {
+            return Err(ModuleError(id.0));
+        }
+        Ok(Self {
+            id,
+            owned: true,
+            started: Cell::new(false),
+        })
+    }
+
+    /// A borrowed handle to the already loaded module `id`.
+    pub fn from_id(id: SceUid) -> Self {
+        Self {
+            id,
+            owned: false,
+            started: Cell::new(false),
+        }
+    }
+
+    /// The module's UID.
+    pub fn id(&self) -> SceUid {
+        self.id
+    }
+
+    /// Start the module, passing `args` to its `module_start`.
+    ///
+    /// Returns the status `module_start` returned.
+    pub fn start(&self, args: &[u8]) -> Result<i32, ModuleError> {
+        let mut status = 0;
+        let ret = unsafe {
+            sceKernelStartModule(
+                self.id,
+                args.len(),
+                args.as_ptr() as *mut c_void,
+                &mut status,
+                ptr::null_mut(),
+            )
+        };
+        if ret < 0 {
+            return Err(ModuleError(ret));
+        }
+        self.started.set(true);
+        Ok(status)
+    }
+
+    /// Stop the module, passing `args` to its `module_stop`.
+    ///
+    /// Returns the status `module_stop` returned.
+    pub fn stop(&self, args: &[u8]) -> Result<i32, ModuleError> {
+        let mut status = 0;
+        let ret = unsafe {
+            sceKernelStopModule(
+                self.id,
+                args.len(),
+                args.as_ptr() as *mut c_void,
+                &mut status,
+                ptr::null_mut(),
+            )
+        };
+        if ret < 0 {
+            return Err(ModuleError(ret));
+        }
+        self.started.set(false);
+        Ok(status)
+    }
+
+    /// Query the module's segments, entry point and name.
+    pub fn info(&self) -> Result<SceKernelModuleInfo, ModuleError> {
+        query_info(self.id)
+    }
+
+    /// Keep the module loaded after the handle is gone, returning its UID.
+    pub fn leak(self) -> SceUid {
+        let id = self.id;
+        core::mem::forget(self);
+        id
+    }
+}
+
+impl Drop for Module {
+    fn drop(&mut self) {
+        if !self.owned {
+            return;
+        }
+        if self.started.get() {
+            let _ = self.stop(&[]);
+        }
+        unsafe { sceKernelUnloadModule(self.id) };
+    }
+}
+
+/// The UIDs of all loaded modules, written to the front of `ids`.
+///
+/// Returns how many were written. In user mode the list only includes the
+/// modules the caller is allowed to see.
+pub fn module_ids(ids: &mut [SceUid; MAX_MODULES]) -> Result<usize, ModuleError> {
+    let mut count = 0;
+    let ret = unsafe { sceKernelGetModuleIdList(ids.as_mut_ptr(), MAX_MODULES as i32, &mut count) };
+    if ret < 0 {
+        return Err(ModuleError(ret));
+    }
+    Ok((count.max(0) as usize).min(MAX_MODULES))
+}
+
+/// Find a loaded module by its name (the name in its module info, e.g.
+/// `"sceNetInet_Library"`, not its file name).
+///
+/// Returns a borrowed handle, or `None` if no visible module has that
+/// name.
+pub fn find_by_name(name: &str) -> Option<Module> {
+    let mut ids = [SceUid(0); MAX_MODULES];
+    let count = module_ids(&mut ids).ok()?;
+    ids[..count]
+        .iter()
+        .find(|&&id| {
+            query_info(id).is_ok_and(|info| {
+                let len = info.name.iter().position(|&b| b == 0).unwrap_or(28);
+                &info.name[..len] == name.as_bytes()
+            })
+        })
+        .map(|&id| Module::from_id(id))
+}
+
+fn query_info(id: SceUid) -> Result<SceKernelModuleInfo, ModuleError> {
+    let mut info: SceKernelModuleInfo = unsafe { core::mem::zeroed() };
+    info.size = core::mem::size_of::<SceKernelModuleInfo>();
+    let ret = unsafe { sceKernelQueryModuleInfo(id, &mut info) };
+    if ret < 0 {
+        return Err(ModuleError(ret));
+    }
+    Ok(info)
+}
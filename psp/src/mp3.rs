@@ -41,6 +41,7 @@
 //! ```
 
 use crate::sys;
+use crate::utility::{self, UtilityModule};
 use alloc::vec::Vec;
 use core::ffi::c_void;
 
@@ -48,15 +49,36 @@ use core::ffi::c_void;
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub struct Mp3Error(pub i32);
 
+/// Sentinel error code returned when the `AvMp3` utility module (or the
+/// `AvCodec` module it needs) could not be loaded.
+///
+/// Call [`utility::load_module`] directly to see the firmware's error code.
+pub const MP3_ERROR_MODULE_LOAD_FAILED: i32 = -2;
+
+impl Mp3Error {
+    /// Returns `true` if the MP3 decoder module could not be loaded.
+    pub fn is_module_load_failed(&self) -> bool {
+        self.0 == MP3_ERROR_MODULE_LOAD_FAILED
+    }
+}
+
 impl core::fmt::Debug for Mp3Error {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        write!(f, "Mp3Error({:#010x})", self.0 as u32)
+        if self.is_module_load_failed() {
+            write!(f, "Mp3Error(ModuleLoadFailed)")
+        } else {
+            write!(f, "Mp3Error({:#010x})", self.0 as u32)
+        }
     }
 }
 
 impl core::fmt::Display for Mp3Error {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        write!(f, "mp3 error {:#010x}", self.0 as u32)
+        if self.is_module_load_failed() {
+            write!(f, "mp3 decoder module could not be loaded")
+        } else {
+            write!(f, "mp3 error {:#010x}", self.0 as u32)
+        }
     }
 }
 
@@ -92,15 +114,20 @@ const STREAM_END_MAX: u32 = 0x0FFF_FFFF; // 256 MiB
 impl Mp3Decoder {
     /// Create a decoder from in-memory MP3 data.
     ///
-    /// Initializes the MP3 resource subsystem, reserves a handle, and
+    /// Loads the `AvMp3` utility module (see [`crate::utility`]),
+    /// initializes the MP3 resource subsystem, reserves a handle, and
     /// feeds the initial data to the decoder.
     pub fn new(data: &[u8]) -> Result<Self, Mp3Error> {
+        utility::load_module(UtilityModule::AvMp3)
+            .map_err(|_| Mp3Error(MP3_ERROR_MODULE_LOAD_FAILED))?;
         let ret = unsafe { sys::sceMp3InitResource() };
         if ret < 0 {
+            let _ = utility::unload_module(UtilityModule::AvMp3);
             return Err(Mp3Error(ret));
         }
         Self::create(data).map_err(|e| {
             unsafe { sys::sceMp3TermResource() };
+            let _ = utility::unload_module(UtilityModule::AvMp3);
             e
         })
     }
@@ -303,6 +330,7 @@ impl Drop for Mp3Decoder {
             sys::sceMp3ReleaseMp3Handle(self.handle);
             sys::sceMp3TermResource();
         }
+        let _ = utility::unload_module(UtilityModule::AvMp3);
     }
 }
 
@@ -4,6 +4,11 @@
 //! NAL units from MP4 containers. The decoder uses the Media Engine (ME)
 //! coprocessor for hardware-accelerated H.264 decode and color space conversion.
 //!
+//! For PMF files, [`MpegPlayer`] takes the standard ringbuffer path
+//! instead: it streams the file from disk, decodes video frames into a
+//! [`Texture`](crate::gu_ext::Texture) and plays the ATRAC3plus audio
+//! track on its own thread.
+//!
 //! # Architecture
 //!
 //! The PSP has two decode paths through `sceMpeg`:
@@ -36,8 +41,8 @@
 //! # Requirements
 //!
 //! - Load AV modules before creating a decoder:
-//!   `sceUtilityLoadModule(PSP_MODULE_AV_AVCODEC)` and
-//!   `sceUtilityLoadModule(PSP_MODULE_AV_MP3)` (for ME codec support).
+//!   [`utility::load_module(UtilityModule::AvMp3)`](crate::utility::load_module),
+//!   which also loads `AvCodec` (for ME codec support).
 //! - Load `mpeg_vsh370.prx` via `sceKernelLoadModule` + `sceKernelStartModule`
 //!   from a non-main thread (loading on main thread can freeze GU). This PRX
 //!   registers the "sceMpeg" library, which resolves the EBOOT's weak import
@@ -78,6 +83,10 @@
 use alloc::{boxed::Box, vec, vec::Vec};
 use core::{ffi::c_void, marker::PhantomData};
 
+mod pmf;
+
+pub use pmf::{MpegPlayer, PmfInfo};
+
 /// Tracks which sceMpeg call is currently executing for hang diagnosis.
 /// 0=idle, 1=GetAvcNalAu, 2=AvcDecode, 3=AvcDecodeDetail2, 4=BaseCscAvc.
 /// Read from a watchdog or diagnostic logger on another thread.
@@ -0,0 +1,702 @@
+//! PMF (PSMF) video playback through the standard ringbuffer path.
+//!
+//! The file is streamed into an `sceMpeg` ringbuffer in 2048-byte packets.
+//! The demuxer splits it into an H.264 video stream, decoded to ABGR
+//! frames by the Media Engine, and an optional ATRAC3plus audio stream,
+//! decoded to PCM and played through a [`StreamPlayer`]. [`PmfInfo`]
+//! reads which streams a file has from its header.
+
+use alloc::boxed::Box;
+use alloc::collections::VecDeque;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::ffi::c_void;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use super::MpegError;
+use crate::audio::{LoopedStream, StreamError, StreamPlayer, StreamSource};
+use crate::gu_ext::Texture;
+use crate::io::File;
+use crate::sync::SpinMutex;
+use crate::sys::{
+    self, DisplayPixelFormat, IoOpenFlags, IoWhence, SceMpeg, SceMpegAu, SceMpegAvcMode,
+    SceMpegRingbuffer, SceMpegStream, SceUid, TexturePixelFormat,
+};
+use crate::time::{Duration, Instant};
+use crate::utility::{self, UtilityModule};
+
+/// Size of a PSMF packet, and of the file header.
+const PACKET_SIZE: usize = 2048;
+
+/// Packets in the ringbuffer (1 MiB).
+const RING_PACKETS: i32 = 512;
+
+/// Row stride of decoded frames in pixels.
+const FRAME_STRIDE: u32 = 512;
+
+/// Largest frame the PSMF decoder produces.
+const FRAME_WIDTH: u32 = 480;
+const FRAME_HEIGHT: u32 = 272;
+
+/// Decoded audio blocks queued ahead of the audio thread (about 190 ms).
+const AUDIO_QUEUE: usize = 4;
+
+/// Stereo frames per output call of the audio thread.
+const AUDIO_SAMPLE_COUNT: i32 = 1024;
+
+/// Timestamps count at 90 kHz.
+const PTS_PER_SEC: u64 = 90_000;
+
+/// `sceMpegGetAvcAu`/`sceMpegGetAtracAu`: the ringbuffer holds no complete
+/// access unit.
+const ERROR_NO_DATA: i32 = 0x8061_8001_u32 as i32;
+
+/// `sceMpegInit`: already initialized.
+const ERROR_ALREADY_INIT: [i32; 2] = [0x8061_8003_u32 as i32, 0x8061_8005_u32 as i32];
+
+/// The header isn't a PSMF header, as `sceMpegQueryStreamOffset` reports.
+const ERROR_INVALID_VALUE: i32 = 0x8061_01FE_u32 as i32;
+
+/// Header layout, from the PSMF files written by the UMD Stream Composer.
+const PSMF_MAGIC: &[u8; 4] = b"PSMF";
+const STREAM_OFFSET: usize = 0x08;
+const STREAM_SIZE: usize = 0x0C;
+const FIRST_PTS: usize = 0x54;
+const LAST_PTS: usize = 0x5A;
+const STREAM_COUNT: usize = 0x80;
+const STREAM_TABLE: usize = 0x82;
+const STREAM_ENTRY_SIZE: usize = 16;
+
+/// MPEG-PS stream ids: video streams are `0xE0..=0xEF`, audio goes in
+/// private stream 1 with a sub-id, `0x0_` for ATRAC3plus.
+const VIDEO_STREAM_ID: u8 = 0xE0;
+const PRIVATE_STREAM_ID: u8 = 0xBD;
+
+/// Stream information from the header of a PMF file.
+///
+/// [`MpegPlayer::open`] parses this from the file's first packet; use
+/// [`parse`](Self::parse) to inspect a file without opening a player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PmfInfo {
+    /// File offset of the first stream packet.
+    pub stream_offset: u32,
+    /// Bytes of stream data from `stream_offset`.
+    pub stream_size: u32,
+    /// Timestamp of the first frame, in 90 kHz ticks.
+    pub first_pts: u64,
+    /// Timestamp of the last frame, in 90 kHz ticks.
+    pub last_pts: u64,
+    /// Width of the first video stream in pixels, 0 without video.
+    pub width: u32,
+    /// Height of the first video stream in pixels, 0 without video.
+    pub height: u32,
+    /// Number of H.264 video streams.
+    pub video_streams: u32,
+    /// Number of ATRAC3plus audio streams.
+    pub audio_streams: u32,
+}
+
+impl PmfInfo {
+    /// Parse the header at the start of a PMF file. `header` needs to
+    /// hold the stream table, which the first 2048 bytes always do.
+    ///
+    /// Fails with `sceMpeg`'s invalid-value error if `header` doesn't
+    /// start with `PSMF` or is cut short.
+    pub fn parse(header: &[u8]) -> Result<Self, MpegError> {
+        let invalid = MpegError(ERROR_INVALID_VALUE);
+        if !header.starts_with(PSMF_MAGIC) {
+            return Err(invalid);
+        }
+        let count = be(header, STREAM_COUNT, 2).ok_or(invalid)? as usize;
+        let table = header
+            .get(STREAM_TABLE..STREAM_TABLE + count * STREAM_ENTRY_SIZE)
+            .ok_or(invalid)?;
+
+        let mut info = Self {
+            stream_offset: be(header, STREAM_OFFSET, 4).ok_or(invalid)? as u32,
+            stream_size: be(header, STREAM_SIZE, 4).ok_or(invalid)? as u32,
+            first_pts: be(header, FIRST_PTS, 6).ok_or(invalid)?,
+            last_pts: be(header, LAST_PTS, 6).ok_or(invalid)?,
+            width: 0,
+            height: 0,
+            video_streams: 0,
+            audio_streams: 0,
+        };
+        for entry in table.chunks_exact(STREAM_ENTRY_SIZE) {
+            if entry[0] & 0xF0 == VIDEO_STREAM_ID {
+                if info.video_streams == 0 {
+                    info.width = entry[12] as u32 * 16;
+                    info.height = entry[13] as u32 * 16;
+                }
+                info.video_streams += 1;
+            } else if entry[0] == PRIVATE_STREAM_ID && entry[1] & 0xF0 == 0 {
+                info.audio_streams += 1;
+            }
+        }
+        Ok(info)
+    }
+
+    /// Playing time from the first to the last frame.
+    pub fn duration(&self) -> Duration {
+        let ticks = self.last_pts.saturating_sub(self.first_pts);
+        Duration::from_micros(ticks * 1_000_000 / PTS_PER_SEC)
+    }
+}
+
+/// The `len`-byte big-endian number at `offset` in `buf`.
+fn be(buf: &[u8], offset: usize, len: usize) -> Option<u64> {
+    let bytes = buf.get(offset..offset + len)?;
+    Some(bytes.iter().fold(0, |n, &b| (n << 8) | b as u64))
+}
+
+/// Ringbuffer callback: read up to `num_packets` packets from the file
+/// whose descriptor is `param`.
+unsafe extern "C" fn read_packets(data: *mut c_void, num_packets: i32, param: *mut c_void) -> i32 {
+    let fd = SceUid(param as i32);
+    let ret = unsafe { sys::sceIoRead(fd, data, num_packets as u32 * PACKET_SIZE as u32) };
+    if ret < 0 {
+        ret
+    } else {
+        ret / PACKET_SIZE as i32
+    }
+}
+
+/// A zeroed heap buffer aligned to 64 bytes, for memory the Media Engine
+/// reads or writes.
+struct MeBuf {
+    data: Vec<u8>,
+    offset: usize,
+}
+
+impl MeBuf {
+    fn new(len: usize) -> Self {
+        let data = alloc::vec![0u8; len + 64];
+        let offset = data.as_ptr().align_offset(64);
+        Self { data, offset }
+    }
+
+    fn as_mut_ptr(&mut self) -> *mut c_void {
+        self.data[self.offset..].as_mut_ptr() as *mut c_void
+    }
+}
+
+/// Decoded audio handed from [`MpegPlayer::update`] to the audio thread.
+struct AudioQueue {
+    blocks: SpinMutex<VecDeque<Vec<i16>>>,
+    /// No more blocks will be queued.
+    closed: AtomicBool,
+}
+
+/// The audio thread's end of an [`AudioQueue`].
+struct QueuedAudio {
+    queue: Arc<AudioQueue>,
+    current: Vec<i16>,
+}
+
+impl StreamSource for QueuedAudio {
+    fn next_block(&mut self) -> &[i16] {
+        loop {
+            if let Some(block) = self.queue.blocks.lock().pop_front() {
+                self.current = block;
+                return &self.current;
+            }
+            if self.queue.closed.load(Ordering::Acquire) {
+                return &[];
+            }
+            // Decoding fell behind; wait rather than end the stream.
+            crate::thread::sleep_ms(1);
+        }
+    }
+
+    fn rewind(&mut self) -> bool {
+        false
+    }
+}
+
+/// Plays a PMF video file: H.264 video decoded into a texture, with its
+/// ATRAC3plus audio, if it has any, on its own thread.
+///
+/// Call [`update`](Self::update) once per frame. It keeps the ringbuffer
+/// full, queues decoded audio and decodes the next video frame once its
+/// timestamp is due, then [`frame`](Self::frame) can be drawn. Video is
+/// paced by the system clock from the first `update`; the audio plays at
+/// the hardware rate alongside it.
+///
+/// Decoded frames are 480×272 with a 512-pixel stride; smaller videos
+/// fill the top-left corner. The decoder leaves the alpha channel
+/// undefined, so draw the frame with blending off.
+///
+/// Only the first video and audio streams are played. A file without
+/// audio plays silently and finishes with its last video frame.
+///
+/// Opening a player loads the `AvMpegBase` and `AvAtrac3Plus` utility
+/// modules, which are released again on drop.
+///
+/// # Example
+///
+/// ```ignore
+/// use psp::gu_ext::{self, BlendMode};
+/// use psp::mpeg::MpegPlayer;
+///
+/// let mut player = MpegPlayer::open("ms0:/PSP/GAME/intro/intro.pmf")?;
+/// while !player.is_finished() {
+///     player.update()?;
+///     // ... start the display list ...
+///     gu_ext::set_blend_mode(BlendMode::None);
+///     gu_ext::blit_texture(player.frame(), 0.0, 0.0);
+///     // ... finish, sync and swap ...
+/// }
+/// ```
+pub struct MpegPlayer {
+    decoder: Decoder,
+    video_stream: SceMpegStream,
+    /// `None` for a video-only file.
+    audio_stream: Option<SceMpegStream>,
+    video_es: *mut c_void,
+    video_au: SceMpegAu,
+    audio_es: MeBuf,
+    audio_au: SceMpegAu,
+    audio_out_len: usize,
+    frame_buf: MeBuf,
+    frame: Texture,
+    audio_queue: Arc<AudioQueue>,
+    audio: Option<StreamPlayer>,
+    /// Whether `video_au` holds an access unit that isn't decoded yet.
+    video_pending: bool,
+    audio_init: bool,
+    decode_init: i32,
+    /// The file is fully read into the ringbuffer.
+    eof: bool,
+    video_done: bool,
+    /// Clock and timestamp of the first video frame.
+    start: Option<(Instant, u64)>,
+    frames: u32,
+}
+
+impl MpegPlayer {
+    /// Open the PMF file at `path` and prepare playback.
+    ///
+    /// Nothing is decoded until the first [`update`](Self::update). If
+    /// the file has audio, the audio thread starts right away and waits
+    /// for decoded audio.
+    ///
+    /// Fails with `sceMpeg`'s invalid-value error if the file isn't a PMF
+    /// or has no video stream.
+    pub fn open(path: &str) -> Result<Self, MpegError> {
+        utility::load_module(UtilityModule::AvMpegBase).map_err(|e| MpegError(e.code))?;
+        if let Err(e) = utility::load_module(UtilityModule::AvAtrac3Plus) {
+            let _ = utility::unload_module(UtilityModule::AvMpegBase);
+            return Err(MpegError(e.code));
+        }
+        let ret = unsafe { sys::sceMpegInit() };
+        if ret < 0 && !ERROR_ALREADY_INIT.contains(&ret) {
+            unload_modules();
+            return Err(MpegError(ret));
+        }
+        // Someone else initialized the library and will finish it.
+        let owns_init = ret >= 0;
+        let decoder = Decoder::create(path, owns_init).inspect_err(|_| {
+            if owns_init {
+                unsafe { sys::sceMpegFinish() };
+            }
+            unload_modules();
+        })?;
+
+        // From here on the player owns everything and cleans up on drop.
+        let mut player = Self::new(decoder);
+        player.init_streams()?;
+        if player.audio_stream.is_none() {
+            return Ok(player);
+        }
+        let source = QueuedAudio {
+            queue: player.audio_queue.clone(),
+            current: Vec::new(),
+        };
+        let audio = StreamPlayer::new(LoopedStream::new(source), AUDIO_SAMPLE_COUNT).map_err(
+            |e| match e {
+                StreamError::Audio(e) => MpegError(e.0),
+                StreamError::Thread(e) => MpegError(e.0),
+            },
+        )?;
+        player.audio = Some(audio);
+        Ok(player)
+    }
+
+    fn new(decoder: Decoder) -> Self {
+        let mpeg = decoder.mpeg();
+        let mut frame_buf = MeBuf::new((FRAME_STRIDE * FRAME_HEIGHT * 4) as usize);
+        // SAFETY: the buffer is 64-byte aligned, holds a full frame, and
+        // lives as long as the player that hands out `frame`.
+        let frame = unsafe {
+            Texture::from_raw(
+                frame_buf.as_mut_ptr(),
+                FRAME_WIDTH,
+                FRAME_HEIGHT,
+                FRAME_STRIDE,
+                TexturePixelFormat::Psm8888,
+            )
+        };
+        Self {
+            video_stream: unsafe { sys::sceMpegRegistStream(mpeg, 0, 0) },
+            audio_stream: (decoder.info.audio_streams > 0)
+                .then(|| unsafe { sys::sceMpegRegistStream(mpeg, 1, 0) }),
+            video_es: unsafe { sys::sceMpegMallocAvcEsBuf(mpeg) },
+            decoder,
+            video_au: unsafe { core::mem::zeroed() },
+            audio_es: MeBuf::new(0),
+            audio_au: unsafe { core::mem::zeroed() },
+            audio_out_len: 0,
+            frame_buf,
+            frame,
+            audio_queue: Arc::new(AudioQueue {
+                blocks: SpinMutex::new(VecDeque::new()),
+                closed: AtomicBool::new(false),
+            }),
+            audio: None,
+            video_pending: false,
+            audio_init: false,
+            decode_init: 0,
+            eof: false,
+            video_done: false,
+            start: None,
+            frames: 0,
+        }
+    }
+
+    /// Prepare the access units and decode mode and seek to the stream.
+    fn init_streams(&mut self) -> Result<(), MpegError> {
+        let mpeg = self.mpeg();
+        if self.video_es.is_null() {
+            return Err(MpegError(-1));
+        }
+        check(unsafe { sys::sceMpegInitAu(mpeg, self.video_es, &mut self.video_au) })?;
+
+        if self.audio_stream.is_some() {
+            let (mut es_size, mut out_size) = (0, 0);
+            check(unsafe { sys::sceMpegQueryAtracEsSize(mpeg, &mut es_size, &mut out_size) })?;
+            self.audio_es = MeBuf::new(es_size as usize);
+            self.audio_out_len = out_size as usize / 2;
+            check(unsafe {
+                sys::sceMpegInitAu(mpeg, self.audio_es.as_mut_ptr(), &mut self.audio_au)
+            })?;
+        }
+
+        let mut mode = SceMpegAvcMode {
+            unk0: -1,
+            pixel_format: DisplayPixelFormat::Psm8888,
+        };
+        check(unsafe { sys::sceMpegAvcDecodeMode(mpeg, &mut mode) })?;
+
+        let mut offset = 0;
+        check(unsafe {
+            sys::sceMpegQueryStreamOffset(mpeg, self.decoder.header.as_mut_ptr(), &mut offset)
+        })?;
+        self.decoder
+            .file
+            .seek(offset as i64, IoWhence::Set)
+            .map_err(|e| MpegError(e.code()))?;
+        Ok(())
+    }
+
+    fn mpeg(&self) -> SceMpeg {
+        self.decoder.mpeg()
+    }
+
+    /// Advance playback: read ahead, queue audio and decode the next video
+    /// frame if it's due.
+    ///
+    /// Returns `true` if [`frame`](Self::frame) changed.
+    pub fn update(&mut self) -> Result<bool, MpegError> {
+        self.fill_ringbuffer()?;
+        self.queue_audio()?;
+        if self.video_done {
+            return Ok(false);
+        }
+
+        if !self.video_pending {
+            match self.get_au(true)? {
+                true => self.video_pending = true,
+                false => {
+                    if self.eof {
+                        self.video_done = true;
+                    }
+                    return Ok(false);
+                },
+            }
+        }
+
+        let pts = pts(&self.video_au);
+        let (start, first_pts) = *self.start.get_or_insert((Instant::now(), pts));
+        let due = pts.saturating_sub(first_pts) * 1_000_000 / PTS_PER_SEC;
+        if start.elapsed().as_micros() < due {
+            return Ok(false);
+        }
+
+        self.video_pending = false;
+        // The ME reads the access unit from memory and writes the frame
+        // behind the data cache.
+        unsafe { sys::sceKernelDcacheWritebackInvalidateAll() };
+        let mut dst = self.frame_buf.as_mut_ptr();
+        check(unsafe {
+            sys::sceMpegAvcDecode(
+                self.mpeg(),
+                &mut self.video_au,
+                FRAME_STRIDE as i32,
+                &mut dst as *mut *mut c_void as *mut c_void,
+                &mut self.decode_init,
+            )
+        })?;
+        // The first access units can go in without a picture coming out.
+        if self.decode_init > 0 {
+            self.frames += 1;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    /// Top up the ringbuffer from the file.
+    fn fill_ringbuffer(&mut self) -> Result<(), MpegError> {
+        if self.eof {
+            return Ok(());
+        }
+        let free = unsafe { sys::sceMpegRingbufferAvailableSize(&mut *self.decoder.ringbuffer) };
+        if free <= 0 {
+            return check(free);
+        }
+        let put = unsafe { sys::sceMpegRingbufferPut(&mut *self.decoder.ringbuffer, free, free) };
+        check(put)?;
+        if put == 0 {
+            self.eof = true;
+        }
+        Ok(())
+    }
+
+    /// Fetch the next video or audio access unit, returning `false` if the
+    /// ringbuffer doesn't hold one or the file has no audio.
+    fn get_au(&mut self, video: bool) -> Result<bool, MpegError> {
+        let mpeg = self.mpeg();
+        let ret = if video {
+            let mut attr = 0;
+            unsafe { sys::sceMpegGetAvcAu(mpeg, self.video_stream, &mut self.video_au, &mut attr) }
+        } else {
+            let Some(audio_stream) = self.audio_stream else {
+                return Ok(false);
+            };
+            let mut attr = core::ptr::null_mut::<c_void>();
+            unsafe {
+                sys::sceMpegGetAtracAu(
+                    mpeg,
+                    audio_stream,
+                    &mut self.audio_au,
+                    &mut attr as *mut *mut c_void as *mut c_void,
+                )
+            }
+        };
+        match ret {
+            ERROR_NO_DATA => Ok(false),
+            ret => check(ret).map(|()| true),
+        }
+    }
+
+    /// Decode audio until the queue is full or the ringbuffer runs dry.
+    fn queue_audio(&mut self) -> Result<(), MpegError> {
+        if self.audio_stream.is_none() {
+            return Ok(());
+        }
+        while !self.audio_queue.closed.load(Ordering::Relaxed)
+            && self.audio_queue.blocks.lock().len() < AUDIO_QUEUE
+        {
+            if !self.get_au(false)? {
+                if self.eof {
+                    self.audio_queue.closed.store(true, Ordering::Release);
+                }
+                break;
+            }
+            let mut block = alloc::vec![0i16; self.audio_out_len];
+            check(unsafe {
+                sys::sceMpegAtracDecode(
+                    self.mpeg(),
+                    &mut self.audio_au,
+                    block.as_mut_ptr() as *mut c_void,
+                    !self.audio_init as i32,
+                )
+            })?;
+            self.audio_init = true;
+            self.audio_queue.blocks.lock().push_back(block);
+        }
+        Ok(())
+    }
+
+    /// The most recently decoded video frame, as a 32-bit texture.
+    ///
+    /// Black until the first frame is decoded.
+    pub fn frame(&self) -> &Texture {
+        &self.frame
+    }
+
+    /// The streams and length of the file, from its header.
+    pub fn info(&self) -> &PmfInfo {
+        &self.decoder.info
+    }
+
+    /// Number of video frames decoded so far.
+    pub fn frames_decoded(&self) -> u32 {
+        self.frames
+    }
+
+    /// Set the audio volume (0..=0x8000).
+    pub fn set_volume(&self, volume: i32) {
+        if let Some(audio) = &self.audio {
+            audio.set_volume(volume);
+        }
+    }
+
+    /// Whether the last video frame was decoded and any audio played out.
+    pub fn is_finished(&self) -> bool {
+        self.video_done && self.audio.as_ref().is_none_or(StreamPlayer::is_finished)
+    }
+}
+
+impl Drop for MpegPlayer {
+    fn drop(&mut self) {
+        // Let the audio thread's wait for more blocks end before joining.
+        self.audio_queue.closed.store(true, Ordering::Release);
+        self.audio_queue.blocks.lock().clear();
+        self.audio = None;
+
+        let mpeg = self.mpeg();
+        unsafe {
+            sys::sceMpegUnRegistStream(mpeg, self.video_stream);
+            if let Some(audio_stream) = self.audio_stream {
+                sys::sceMpegUnRegistStream(mpeg, audio_stream);
+            }
+            if !self.video_es.is_null() {
+                sys::sceMpegFreeAvcEsBuf(mpeg, self.video_es);
+            }
+        }
+    }
+}
+
+/// The file, ringbuffer and `sceMpeg` instance, torn down in reverse on
+/// drop along with the decoder library.
+struct Decoder {
+    file: File,
+    /// The file's first packet.
+    header: MeBuf,
+    info: PmfInfo,
+    handle: *mut *mut c_void,
+    _mpeg_data: MeBuf,
+    /// The decoder keeps pointers to the ringbuffer, so it's boxed.
+    ringbuffer: Box<SceMpegRingbuffer>,
+    _ring_data: MeBuf,
+    /// `sceMpegInit` was called for this decoder, so drop calls
+    /// `sceMpegFinish`.
+    owns_init: bool,
+}
+
+impl Decoder {
+    /// Open `path` and create an `sceMpeg` instance reading it through a
+    /// ringbuffer. `sceMpegInit` must have succeeded, in this call if
+    /// `owns_init`.
+    fn create(path: &str, owns_init: bool) -> Result<Self, MpegError> {
+        let file = File::open(path, IoOpenFlags::RD_ONLY).map_err(|e| MpegError(e.code()))?;
+        let mut header = MeBuf::new(PACKET_SIZE);
+        let header_bytes =
+            unsafe { core::slice::from_raw_parts_mut(header.as_mut_ptr() as *mut u8, PACKET_SIZE) };
+        if file
+            .read_all(header_bytes)
+            .map_err(|e| MpegError(e.code()))?
+            < PACKET_SIZE
+        {
+            return Err(MpegError(ERROR_NO_DATA));
+        }
+        let info = PmfInfo::parse(header_bytes)?;
+        if info.video_streams == 0 {
+            return Err(MpegError(ERROR_INVALID_VALUE));
+        }
+
+        let ring_size = unsafe { sys::sceMpegRingbufferQueryMemSize(RING_PACKETS) };
+        check(ring_size)?;
+        let mut ring_data = MeBuf::new(ring_size as usize);
+        let mut ringbuffer = Box::new(unsafe { core::mem::zeroed::<SceMpegRingbuffer>() });
+        check(unsafe {
+            sys::sceMpegRingbufferConstruct(
+                &mut *ringbuffer,
+                RING_PACKETS,
+                ring_data.as_mut_ptr(),
+                ring_size,
+                Some(read_packets),
+                file.fd().0 as *mut c_void,
+            )
+        })?;
+
+        let mem_size = unsafe { sys::sceMpegQueryMemSize(0) };
+        let mut mpeg_data = MeBuf::new(mem_size.max(0) as usize);
+        let handle = Box::into_raw(Box::new(core::ptr::null_mut::<c_void>()));
+        let ret = if mem_size < 0 {
+            mem_size
+        } else {
+            unsafe {
+                sys::sceMpegCreate(
+                    core::mem::transmute::<*mut *mut c_void, SceMpeg>(handle),
+                    mpeg_data.as_mut_ptr(),
+                    mem_size,
+                    &mut *ringbuffer,
+                    FRAME_STRIDE as i32,
+                    0,
+                    0,
+                )
+            }
+        };
+        if ret < 0 {
+            unsafe {
+                sys::sceMpegRingbufferDestruct(&mut *ringbuffer);
+                drop(Box::from_raw(handle));
+            }
+            return Err(MpegError(ret));
+        }
+
+        Ok(Self {
+            file,
+            header,
+            info,
+            handle,
+            _mpeg_data: mpeg_data,
+            ringbuffer,
+            _ring_data: ring_data,
+            owns_init,
+        })
+    }
+
+    fn mpeg(&self) -> SceMpeg {
+        unsafe { core::mem::transmute(self.handle) }
+    }
+}
+
+impl Drop for Decoder {
+    fn drop(&mut self) {
+        unsafe {
+            sys::sceMpegDelete(self.mpeg());
+            sys::sceMpegRingbufferDestruct(&mut *self.ringbuffer);
+            if self.owns_init {
+                sys::sceMpegFinish();
+            }
+            drop(Box::from_raw(self.handle));
+        }
+        unload_modules();
+    }
+}
+
+fn unload_modules() {
+    let _ = utility::unload_module(UtilityModule::AvAtrac3Plus);
+    let _ = utility::unload_module(UtilityModule::AvMpegBase);
+}
+
+/// A presentation timestamp in 90 kHz ticks.
+fn pts(au: &SceMpegAu) -> u64 {
+    ((au.pts_msb as u64) << 32) | au.pts as u64
+}
+
+fn check(ret: i32) -> Result<(), MpegError> {
+    if ret < 0 { Err(MpegError(ret)) } else { Ok(()) }
+}
@@ -0,0 +1,210 @@
+//! Read-only access to the internal NAND flash, for dump and backup tools.
+//!
+//! Wraps the `sceNand_driver` read path: [`geometry()`] reports the chip
+//! layout, [`read_page()`] and [`read_block()`] copy data out, and
+//! [`is_bad_block()`] checks a block's status byte. Every read holds the
+//! driver's NAND lock (`sceNandLock(0)`) for its duration, so it can't
+//! interleave with the firmware's own flash accesses.
+//!
+//! There are deliberately no write or erase wrappers. A bad write to the
+//! flash bricks the console; tools that need one can call the raw
+//! `psp::sys` bindings and own the consequences.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use psp::nand;
+//!
+//! let geo = nand::geometry()?;
+//! let mut block = alloc::vec![0u8; geo.block_size()];
+//! for b in 0..geo.total_blocks {
+//!     if !nand::is_bad_block(b)? {
+//!         nand::read_block(b, &mut block)?;
+//!         // ... save `block` ...
+//!     }
+//! }
+//! ```
+
+use core::ffi::c_void;
+
+use crate::sys;
+
+/// Spare (out-of-band) bytes returned per page, after the driver has
+/// checked and stripped the raw spare area's ECC bytes.
+pub const SPARE_SIZE: usize = 12;
+
+/// Offset of the block status byte in a page's spare data. Anything other
+/// than `0xFF` in a block's first page marks the block as bad.
+pub const BLOCK_STATUS_OFFSET: usize = 1;
+
+/// Error from a NAND operation.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum NandError {
+    /// A `sceNand*` call failed with this code.
+    Kernel(i32),
+    /// The page or block number is past the end of the flash.
+    OutOfRange,
+    /// A buffer is smaller than a page, block or spare area.
+    BufferTooSmall,
+}
+
+impl core::fmt::Debug for NandError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Kernel(code) => write!(f, "NandError::Kernel({:#010x})", *code as u32),
+            Self::OutOfRange => f.write_str("NandError::OutOfRange"),
+            Self::BufferTooSmall => f.write_str("NandError::BufferTooSmall"),
+        }
+    }
+}
+
+impl core::fmt::Display for NandError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Kernel(code) => write!(f, "NAND error {:#010x}", *code as u32),
+            Self::OutOfRange => f.write_str("NAND page or block out of range"),
+            Self::BufferTooSmall => f.write_str("buffer too small for NAND read"),
+        }
+    }
+}
+
+fn check(ret: i32) -> Result<u32, NandError> {
+    if ret < 0 {
+        Err(NandError::Kernel(ret))
+    } else {
+        Ok(ret as u32)
+    }
+}
+
+/// Layout of the NAND flash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NandGeometry {
+    /// Bytes of user data per page (512 on every PSP model).
+    pub page_size: u32,
+    /// Pages per erase block (32 on every PSP model).
+    pub pages_per_block: u32,
+    /// Number of erase blocks.
+    pub total_blocks: u32,
+}
+
+impl NandGeometry {
+    /// Bytes of user data per block.
+    pub fn block_size(&self) -> usize {
+        (self.page_size * self.pages_per_block) as usize
+    }
+
+    /// Number of pages on the chip.
+    pub fn total_pages(&self) -> u32 {
+        self.pages_per_block * self.total_blocks
+    }
+
+    /// Bytes of user data on the chip, excluding spare areas.
+    pub fn total_bytes(&self) -> u64 {
+        self.block_size() as u64 * self.total_blocks as u64
+    }
+}
+
+/// Query the flash layout from the NAND driver.
+pub fn geometry() -> Result<NandGeometry, NandError> {
+    unsafe {
+        Ok(NandGeometry {
+            page_size: check(sys::sceNandGetPageSize())?,
+            pages_per_block: check(sys::sceNandGetPagesPerBlock())?,
+            total_blocks: check(sys::sceNandGetTotalBlocks())?,
+        })
+    }
+}
+
+/// Holds the driver's NAND lock for reading; unlocks on drop.
+struct ReadLock;
+
+impl ReadLock {
+    fn acquire() -> Result<Self, NandError> {
+        check(unsafe { sys::sceNandLock(0) })?;
+        Ok(Self)
+    }
+}
+
+impl Drop for ReadLock {
+    fn drop(&mut self) {
+        unsafe { sys::sceNandUnlock() };
+    }
+}
+
+fn buf_ptr(buf: &mut [u8]) -> *mut c_void {
+    if buf.is_empty() {
+        core::ptr::null_mut()
+    } else {
+        buf.as_mut_ptr() as *mut c_void
+    }
+}
+
+/// Read physical page `ppn` into `user` (page data) and `spare` (spare
+/// data, [`SPARE_SIZE`] bytes).
+///
+/// Pass an empty slice for either buffer to skip that part. Extra bytes
+/// past a page or spare area are left untouched.
+pub fn read_page(ppn: u32, user: &mut [u8], spare: &mut [u8]) -> Result<(), NandError> {
+    let geo = geometry()?;
+    if ppn >= geo.total_pages() {
+        return Err(NandError::OutOfRange);
+    }
+    if (!user.is_empty() && user.len() < geo.page_size as usize)
+        || (!spare.is_empty() && spare.len() < SPARE_SIZE)
+    {
+        return Err(NandError::BufferTooSmall);
+    }
+
+    let _lock = ReadLock::acquire()?;
+    check(unsafe { sys::sceNandReadPages(ppn, buf_ptr(user), buf_ptr(spare), 1) })?;
+    Ok(())
+}
+
+/// Read every page of erase block `block` into `buf`, which must hold at
+/// least [`NandGeometry::block_size()`] bytes.
+///
+/// Pages are read in order under a single lock. A page that fails its
+/// ECC check fails the whole read; bad blocks usually do, so check
+/// [`is_bad_block()`] first.
+pub fn read_block(block: u32, buf: &mut [u8]) -> Result<(), NandError> {
+    let geo = geometry()?;
+    if block >= geo.total_blocks {
+        return Err(NandError::OutOfRange);
+    }
+    let page_size = geo.page_size as usize;
+    if buf.len() < geo.block_size() {
+        return Err(NandError::BufferTooSmall);
+    }
+
+    let first = block * geo.pages_per_block;
+    let _lock = ReadLock::acquire()?;
+    for (i, page) in buf[..geo.block_size()]
+        .chunks_exact_mut(page_size)
+        .enumerate()
+    {
+        check(unsafe {
+            sys::sceNandReadPages(
+                first + i as u32,
+                page.as_mut_ptr() as *mut c_void,
+                core::ptr::null_mut(),
+                1,
+            )
+        })?;
+    }
+    Ok(())
+}
+
+/// Whether erase block `block` is marked bad, from the status byte in its
+/// first page's spare data.
+///
+/// The driver reads the marker straight from the raw spare area
+/// (`sceNandIsBadBlock`) rather than through an ECC-checked page read,
+/// which a worn-out block would fail before the marker could be seen.
+pub fn is_bad_block(block: u32) -> Result<bool, NandError> {
+    let geo = geometry()?;
+    if block >= geo.total_blocks {
+        return Err(NandError::OutOfRange);
+    }
+    let _lock = ReadLock::acquire()?;
+    Ok(check(unsafe { sys::sceNandIsBadBlock(block * geo.pages_per_block) })? != 0)
+}
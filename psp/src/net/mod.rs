@@ -0,0 +1,1315 @@
+//! Network sockets and WiFi access for the PSP.
+//!
+//! Provides RAII wrappers around the PSP's networking stack: access
+//! point connection, DNS resolution, and TCP/UDP sockets, including a
+//! [`TcpListener`] for accepting connections. The [`ntp`] submodule
+//! queries time servers, and [`websocket`] is a `ws://` client.
+//!
+//! # Initialization
+//!
+//! Before using any networking, call [`init`] to set up the network
+//! subsystem. Call [`term`] when done. Connect to a WiFi access point
+//! with [`connect_ap`].
+//!
+//! # Example
+//!
+//! ```ignore
+//! use psp::net;
+//!
+//! net::init(0x20000).unwrap();
+//! net::connect_ap(1).unwrap();
+//!
+//! let ip = net::get_ip_address().unwrap();
+//! psp::dprintln!("IP: {}", core::str::from_utf8(&ip).unwrap_or("?"));
+//!
+//! let mut stream = net::TcpStream::connect(net::Ipv4Addr([93, 184, 216, 34]), 80).unwrap();
+//! stream.write(b"GET / HTTP/1.0\r\nHost: example.com\r\n\r\n").unwrap();
+//!
+//! let mut buf = [0u8; 1024];
+//! let n = stream.read(&mut buf).unwrap();
+//! ```
+
+use core::ffi::c_void;
+use core::marker::PhantomData;
+use core::sync::atomic::{AtomicBool, AtomicI32, AtomicU32, Ordering};
+
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use crate::sync::SpinMutex;
+use crate::sys;
+use crate::time::{Duration, Instant};
+use crate::utility::{self, UtilityModule};
+
+pub mod ntp;
+pub mod websocket;
+
+/// Error from a network operation, wrapping the raw SCE error code.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct NetError(pub i32);
+
+/// Sentinel error code returned when the user cancels the WiFi dialog.
+///
+/// This is distinct from SCE error codes (which are negative) and allows
+/// callers to distinguish "user pressed Circle" from "connection failed".
+pub const NET_ERROR_CANCELLED: i32 = -2;
+
+/// Sentinel error code returned when the physical WLAN switch is off.
+pub const NET_ERROR_WLAN_OFF: i32 = -3;
+
+/// Sentinel error code returned when a server's reply is malformed or
+/// refuses the request (e.g. an NTP kiss-o'-death packet).
+pub const NET_ERROR_BAD_RESPONSE: i32 = -4;
+
+/// Sentinel error code returned when the networking utility modules could
+/// not be loaded.
+///
+/// Call [`utility::load_module`] directly to see the firmware's error code.
+pub const NET_ERROR_MODULE_LOAD_FAILED: i32 = -5;
+
+/// inet errno for a receive or accept that ran past its timeout.
+const EAGAIN: i32 = 11;
+
+impl NetError {
+    /// Returns `true` if this error represents user cancellation of the
+    /// WiFi dialog (pressed Circle / back button).
+    pub fn is_cancelled(&self) -> bool {
+        self.0 == NET_ERROR_CANCELLED
+    }
+
+    /// Returns `true` if the operation failed because the WLAN switch is
+    /// off.
+    pub fn is_wlan_off(&self) -> bool {
+        self.0 == NET_ERROR_WLAN_OFF
+    }
+
+    /// Returns `true` if the server sent an unusable reply.
+    pub fn is_bad_response(&self) -> bool {
+        self.0 == NET_ERROR_BAD_RESPONSE
+    }
+
+    /// Returns `true` if the networking modules could not be loaded.
+    pub fn is_module_load_failed(&self) -> bool {
+        self.0 == NET_ERROR_MODULE_LOAD_FAILED
+    }
+
+    /// Returns `true` if a read or accept gave up after the timeout set
+    /// with [`TcpStream::set_read_timeout`] or
+    /// [`TcpListener::set_accept_timeout`].
+    pub fn is_timeout(&self) -> bool {
+        self.0 == EAGAIN
+    }
+}
+
+impl core::fmt::Debug for NetError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if self.is_cancelled() {
+            write!(f, "NetError(Cancelled)")
+        } else if self.is_wlan_off() {
+            write!(f, "NetError(WlanOff)")
+        } else if self.is_bad_response() {
+            write!(f, "NetError(BadResponse)")
+        } else if self.is_module_load_failed() {
+            write!(f, "NetError(ModuleLoadFailed)")
+        } else {
+            write!(f, "NetError({:#010x})", self.0 as u32)
+        }
+    }
+}
+
+impl core::fmt::Display for NetError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if self.is_cancelled() {
+            write!(f, "net dialog cancelled by user")
+        } else if self.is_wlan_off() {
+            write!(f, "WLAN switch is off")
+        } else if self.is_bad_response() {
+            write!(f, "malformed or rejected server response")
+        } else if self.is_module_load_failed() {
+            write!(f, "network modules could not be loaded")
+        } else {
+            write!(f, "net error {:#010x}", self.0 as u32)
+        }
+    }
+}
+
+/// An IPv4 address in network byte order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ipv4Addr(pub [u8; 4]);
+
+impl Ipv4Addr {
+    /// Convert to a `u32` in network byte order (big-endian).
+    pub fn to_u32_be(self) -> u32 {
+        u32::from_be_bytes(self.0)
+    }
+}
+
+/// Initialize the PSP network subsystem.
+///
+/// `pool_size` is the memory pool size for the networking stack.
+/// A typical value is `0x20000` (128 KiB).
+///
+/// Loads the `NetCommon` and `NetInet` utility modules first (see
+/// [`crate::utility`]), failing with [`NET_ERROR_MODULE_LOAD_FAILED`] if
+/// they are unavailable.
+pub fn init(pool_size: u32) -> Result<(), NetError> {
+    utility::load_module(UtilityModule::NetInet)
+        .map_err(|_| NetError(NET_ERROR_MODULE_LOAD_FAILED))?;
+    init_stack(pool_size).inspect_err(|_| {
+        let _ = utility::unload_module(UtilityModule::NetInet);
+    })?;
+    INITIALIZED.store(true, Ordering::Release);
+    Ok(())
+}
+
+/// Whether [`init`] has succeeded and [`term`] hasn't been called since.
+pub fn is_initialized() -> bool {
+    INITIALIZED.load(Ordering::Acquire)
+}
+
+static INITIALIZED: AtomicBool = AtomicBool::new(false);
+
+fn init_stack(pool_size: u32) -> Result<(), NetError> {
+    let ret = unsafe { sys::sceNetInit(pool_size as i32, 0x20, 0x1000, 0x20, 0x1000) };
+    if ret < 0 {
+        return Err(NetError(ret));
+    }
+
+    let ret = unsafe { sys::sceNetInetInit() };
+    if ret < 0 {
+        unsafe { sys::sceNetTerm() };
+        return Err(NetError(ret));
+    }
+
+    let ret = unsafe { sys::sceNetResolverInit() };
+    if ret < 0 {
+        unsafe {
+            sys::sceNetInetTerm();
+            sys::sceNetTerm();
+        }
+        return Err(NetError(ret));
+    }
+
+    let ret = unsafe { sys::sceNetApctlInit(0x1600, 42) };
+    if ret < 0 {
+        unsafe {
+            sys::sceNetResolverTerm();
+            sys::sceNetInetTerm();
+            sys::sceNetTerm();
+        }
+        return Err(NetError(ret));
+    }
+
+    RX_BYTES.store(0, Ordering::Relaxed);
+    TX_BYTES.store(0, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Terminate the network subsystem.
+///
+/// Call when networking is no longer needed. Releases the utility
+/// modules loaded by [`init`].
+pub fn term() {
+    INITIALIZED.store(false, Ordering::Release);
+    unsafe {
+        sys::sceNetApctlTerm();
+        sys::sceNetResolverTerm();
+        sys::sceNetInetTerm();
+        sys::sceNetTerm();
+    }
+    let _ = utility::unload_module(UtilityModule::NetInet);
+}
+
+/// Connect to a WiFi access point using a stored PSP network config slot.
+///
+/// `config_index` is 1-based (matches the PSP's Network Settings list).
+/// Blocks until the connection is established or fails.
+/// Uses a default timeout of 30 seconds.
+pub fn connect_ap(config_index: i32) -> Result<(), NetError> {
+    connect_ap_timeout(config_index, 30_000)
+}
+
+/// Connect to a WiFi access point with a custom timeout.
+///
+/// `config_index` is 1-based (matches the PSP's Network Settings list).
+/// `timeout_ms` is the maximum time to wait in milliseconds.
+pub fn connect_ap_timeout(config_index: i32, timeout_ms: u32) -> Result<(), NetError> {
+    let ret = unsafe { sys::sceNetApctlConnect(config_index) };
+    if ret < 0 {
+        return Err(NetError(ret));
+    }
+
+    // Poll until we get an IP, hit an error, or time out.
+    let timeout = crate::time::Timeout::from_millis(timeout_ms);
+    while !timeout.expired() {
+        let mut state = sys::ApctlState::Disconnected;
+        let ret = unsafe { sys::sceNetApctlGetState(&mut state) };
+        if ret < 0 {
+            return Err(NetError(ret));
+        }
+        match state {
+            sys::ApctlState::GotIp => return Ok(()),
+            sys::ApctlState::Disconnected => return Err(NetError(-1)),
+            _ => {},
+        }
+        crate::thread::sleep_ms(50);
+    }
+
+    // Timed out — disconnect and return error.
+    let _ = unsafe { sys::sceNetApctlDisconnect() };
+    Err(NetError(-1))
+}
+
+/// Check whether the PSP is currently connected to a WiFi access point.
+///
+/// Returns `true` if the WLAN interface has obtained an IP address.
+/// This is a lightweight check that does not show any dialogs or block.
+///
+/// Useful for background threads that need to verify connectivity
+/// without triggering the system dialog (which requires the main thread
+/// and GU access).
+pub fn is_connected() -> bool {
+    let mut state = sys::ApctlState::Disconnected;
+    let ret = unsafe { sys::sceNetApctlGetState(&mut state) };
+    ret >= 0 && state == sys::ApctlState::GotIp
+}
+
+/// Connect to WiFi using the PSP's built-in network configuration dialog.
+///
+/// Shows a system dialog that lets the user select a stored WiFi profile
+/// and manages the entire connection flow (scan, auth, DHCP). This is the
+/// most compatible way to establish internet connectivity — it works on
+/// both real PSP hardware and PPSSPP.
+///
+/// The dialog renders into the framebuffer, so the caller's GU display
+/// list is finished before the dialog runs. The caller must re-open a
+/// new display list with `sceGuStart` after this function returns.
+///
+/// # Important
+///
+/// **Must be called from the main thread only.** The dialog uses GU
+/// rendering and `sceDisplay` calls that are not safe from background
+/// threads. Calling from a background thread will freeze the EBOOT.
+///
+/// Use [`is_connected`] to check connectivity from any thread without
+/// showing a dialog.
+///
+/// # Return values
+///
+/// - `Ok(())` — Connected successfully.
+/// - `Err(e)` where `e.is_cancelled()` — User pressed Circle to cancel.
+/// - `Err(e)` — Dialog or connection failed (check `e.0` for SCE code).
+pub fn connect_dialog() -> Result<(), NetError> {
+    // Check if we're already connected.
+    let mut state = sys::ApctlState::Disconnected;
+    let ret = unsafe { sys::sceNetApctlGetState(&mut state) };
+    if ret >= 0 && state == sys::ApctlState::GotIp {
+        return Ok(());
+    }
+
+    let mut data = sys::UtilityNetconfData {
+        base: crate::dialog::make_netconf_common(
+            core::mem::size_of::<sys::UtilityNetconfData>() as u32
+        ),
+        action: sys::UtilityNetconfAction::ConnectAP,
+        adhocparam: core::ptr::null_mut(),
+        hotspot: 0,
+        hotspot_connected: 0,
+        wifisp: 0,
+    };
+
+    let ret = unsafe { sys::sceUtilityNetconfInitStart(&mut data) };
+    if ret < 0 {
+        return Err(NetError(ret));
+    }
+
+    // Close the caller's open GU display list.
+    unsafe {
+        sys::sceGuFinish();
+        sys::sceGuSync(sys::GuSyncMode::Finish, sys::GuSyncBehavior::Wait);
+    }
+
+    // Dialog rendering loop (up to ~30 seconds at 60 fps).
+    for _ in 0..1800 {
+        let status = unsafe { sys::sceUtilityNetconfGetStatus() };
+        if status < 0 {
+            return Err(NetError(status));
+        }
+        if status == 0 {
+            break;
+        }
+
+        // Provide a GU frame for the dialog background.
+        // SAFETY: DIALOG_LIST is shared with dialog.rs but both run on
+        // the main thread and never overlap.
+        unsafe {
+            sys::sceGuStart(
+                sys::GuContextType::Direct,
+                crate::dialog::DIALOG_LIST.as_mut_ptr(),
+            );
+            sys::sceGuClearColor(0xff00_0000);
+            sys::sceGuClear(sys::ClearBuffer::COLOR_BUFFER_BIT);
+            sys::sceGuFinish();
+            sys::sceGuSync(sys::GuSyncMode::Finish, sys::GuSyncBehavior::Wait);
+        }
+
+        match status {
+            2 => unsafe {
+                sys::sceUtilityNetconfUpdate(1);
+            },
+            3 => unsafe {
+                sys::sceUtilityNetconfShutdownStart();
+            },
+            _ => {},
+        }
+
+        unsafe {
+            sys::sceDisplayWaitVblankStart();
+            sys::sceGuSwapBuffers();
+        }
+    }
+
+    // Drain shutdown if needed. Call ShutdownStart once if still in
+    // QUIT state (3), then wait for FINISHED (4) to drain to NONE (0).
+    let s = unsafe { sys::sceUtilityNetconfGetStatus() };
+    if s == 3 {
+        unsafe {
+            sys::sceUtilityNetconfShutdownStart();
+        }
+    }
+    if s == 3 || s == 4 {
+        for _ in 0..120 {
+            let s = unsafe { sys::sceUtilityNetconfGetStatus() };
+            if s != 3 && s != 4 {
+                break;
+            }
+            unsafe {
+                sys::sceDisplayWaitVblankStart();
+            }
+        }
+    }
+
+    // Verify we actually got connected. If the dialog completed but
+    // we don't have an IP, the user cancelled (pressed Circle).
+    let mut state = sys::ApctlState::Disconnected;
+    let ret = unsafe { sys::sceNetApctlGetState(&mut state) };
+    if ret < 0 {
+        return Err(NetError(ret));
+    }
+    if state != sys::ApctlState::GotIp {
+        return Err(NetError(NET_ERROR_CANCELLED));
+    }
+
+    Ok(())
+}
+
+/// Disconnect from the current access point.
+pub fn disconnect_ap() -> Result<(), NetError> {
+    let ret = unsafe { sys::sceNetApctlDisconnect() };
+    if ret < 0 { Err(NetError(ret)) } else { Ok(()) }
+}
+
+/// Get the IP address assigned to the WLAN interface.
+///
+/// Returns a null-terminated string in a 16-byte buffer (e.g. `"192.168.1.42\0"`).
+pub fn get_ip_address() -> Result<[u8; 16], NetError> {
+    let mut info: sys::SceNetApctlInfo = unsafe { core::mem::zeroed() };
+    let ret = unsafe { sys::sceNetApctlGetInfo(sys::ApctlInfo::Ip, &mut info) };
+    if ret < 0 {
+        return Err(NetError(ret));
+    }
+    // IP is stored as a string in the `name` field of the union
+    let mut out = [0u8; 16];
+    let src = unsafe { &info.name[..16] };
+    out.copy_from_slice(src);
+    Ok(out)
+}
+
+// ── Link info ──────────────────────────────────────────────────────
+
+/// Bytes received through this module's sockets since [`init`].
+static RX_BYTES: AtomicU32 = AtomicU32::new(0);
+/// Bytes sent through this module's sockets since [`init`].
+static TX_BYTES: AtomicU32 = AtomicU32::new(0);
+
+/// Current access point link quality and traffic counters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LinkInfo {
+    /// SSID of the access point, null-padded.
+    pub ssid: [u8; 32],
+    /// Signal strength in percent (0..=100).
+    pub signal_strength: u8,
+    /// WiFi channel (1..=14).
+    pub channel: u8,
+    /// Bytes received through [`TcpStream`] / [`UdpSocket`] since [`init`].
+    ///
+    /// The firmware does not expose interface counters, so traffic from
+    /// other modules (e.g. `sceHttp`) is not included. Wraps at 4 GiB.
+    pub rx_bytes: u32,
+    /// Bytes sent through [`TcpStream`] / [`UdpSocket`] since [`init`].
+    pub tx_bytes: u32,
+}
+
+impl LinkInfo {
+    /// The SSID as a string, up to the first null byte.
+    ///
+    /// Returns an empty string if the SSID is not valid UTF-8.
+    pub fn ssid_str(&self) -> &str {
+        let len = self.ssid.iter().position(|&b| b == 0).unwrap_or(32);
+        core::str::from_utf8(&self.ssid[..len]).unwrap_or("")
+    }
+
+    /// Returns `true` if the signal is too weak for large transfers
+    /// (below 40%).
+    pub fn is_weak(&self) -> bool {
+        self.signal_strength < 40
+    }
+}
+
+/// Query the current access point link.
+///
+/// Fails with [`NET_ERROR_WLAN_OFF`] if the WLAN switch is off, or with
+/// the SCE error if the PSP is not associated with an access point.
+pub fn link_info() -> Result<LinkInfo, NetError> {
+    if unsafe { sys::sceWlanGetSwitchState() } != 1 {
+        return Err(NetError(NET_ERROR_WLAN_OFF));
+    }
+
+    let mut info: sys::SceNetApctlInfo = unsafe { core::mem::zeroed() };
+    let ret = unsafe { sys::sceNetApctlGetInfo(sys::ApctlInfo::Ssid, &mut info) };
+    if ret < 0 {
+        return Err(NetError(ret));
+    }
+    let ssid = unsafe { info.ssid };
+
+    let ret = unsafe { sys::sceNetApctlGetInfo(sys::ApctlInfo::Strength, &mut info) };
+    if ret < 0 {
+        return Err(NetError(ret));
+    }
+    let signal_strength = unsafe { info.strength };
+
+    let ret = unsafe { sys::sceNetApctlGetInfo(sys::ApctlInfo::Channel, &mut info) };
+    if ret < 0 {
+        return Err(NetError(ret));
+    }
+    let channel = unsafe { info.channel };
+
+    Ok(LinkInfo {
+        ssid,
+        signal_strength,
+        channel,
+        rx_bytes: RX_BYTES.load(Ordering::Relaxed),
+        tx_bytes: TX_BYTES.load(Ordering::Relaxed),
+    })
+}
+
+/// Resolve a hostname to an IPv4 address.
+///
+/// `hostname` must be a null-terminated byte string. This blocks for up to
+/// 15 seconds on a slow DNS server; see [`resolve_hostname_async`] for a
+/// non-blocking alternative.
+pub fn resolve_hostname(hostname: &[u8]) -> Result<Ipv4Addr, NetError> {
+    resolve_with(hostname, |_| {}, |_| {})
+}
+
+/// Resolve `hostname`, passing the resolver id to `on_create` before the
+/// blocking lookup starts so it can be stopped from another thread, and
+/// to `on_finish` before the resolver is deleted.
+fn resolve_with(
+    hostname: &[u8],
+    on_create: impl FnOnce(i32),
+    on_finish: impl FnOnce(i32),
+) -> Result<Ipv4Addr, NetError> {
+    let mut rid: i32 = 0;
+    let mut buf = [0u8; 1024];
+
+    let ret = unsafe {
+        sys::sceNetResolverCreate(&mut rid, buf.as_mut_ptr() as *mut c_void, buf.len() as u32)
+    };
+    if ret < 0 {
+        return Err(NetError(ret));
+    }
+    on_create(rid);
+
+    let mut addr = sys::in_addr(0);
+    let ret = unsafe { sys::sceNetResolverStartNtoA(rid, hostname.as_ptr(), &mut addr, 5, 3) };
+    on_finish(rid);
+    unsafe { sys::sceNetResolverDelete(rid) };
+
+    if ret < 0 {
+        return Err(NetError(ret));
+    }
+
+    // sceNetResolverStartNtoA stores the IP in network byte order
+    // (big-endian) in memory. On little-endian MIPS, the u32 value has
+    // the bytes swapped relative to the in-memory layout. Use to_ne_bytes()
+    // to get the raw memory bytes (which are already in network order),
+    // not to_be_bytes() which would reverse them.
+    Ok(Ipv4Addr(addr.0.to_ne_bytes()))
+}
+
+/// `ResolveState::rid` while [`ResolveJob`]'s drop is stopping the lookup.
+const RESOLVER_STOPPING: i32 = -1;
+
+/// State shared between a [`ResolveJob`] and its worker thread.
+struct ResolveState {
+    /// Resolver id while the lookup runs, [`RESOLVER_STOPPING`] while the
+    /// job's drop stops it, or 0.
+    rid: AtomicI32,
+    result: SpinMutex<Option<Result<Ipv4Addr, NetError>>>,
+}
+
+/// Start resolving a hostname on a background thread.
+///
+/// `hostname` need not be null-terminated. Poll the returned job from the
+/// main loop; if the worker thread can't be created, the first
+/// [`poll`](ResolveJob::poll) reports the error.
+///
+/// # Example
+///
+/// ```ignore
+/// let mut job = net::resolve_hostname_async(b"example.com");
+/// loop {
+///     match job.poll() {
+///         Some(Ok(ip)) => break ip,
+///         Some(Err(e)) => return Err(e),
+///         None => draw_spinner(),
+///     }
+/// }
+/// ```
+pub fn resolve_hostname_async(hostname: &[u8]) -> ResolveJob {
+    let mut name = Vec::with_capacity(hostname.len() + 1);
+    name.extend_from_slice(hostname);
+    if name.last() != Some(&0) {
+        name.push(0);
+    }
+
+    let state = Arc::new(ResolveState {
+        rid: AtomicI32::new(0),
+        result: SpinMutex::new(None),
+    });
+    let worker_state = state.clone();
+    let handle = crate::thread::spawn(b"psp_resolver\0", move || {
+        let result = resolve_with(
+            &name,
+            |rid| worker_state.rid.store(rid, Ordering::Release),
+            |rid| {
+                // Don't delete the resolver, letting its id be reused,
+                // while the job's drop is stopping it.
+                while worker_state
+                    .rid
+                    .compare_exchange(rid, 0, Ordering::AcqRel, Ordering::Acquire)
+                    .is_err()
+                {
+                    unsafe { sys::sceKernelDelayThread(100) };
+                }
+            },
+        );
+        *worker_state.result.lock() = Some(result);
+        0
+    });
+    let handle = match handle {
+        Ok(handle) => Some(handle),
+        Err(e) => {
+            *state.result.lock() = Some(Err(NetError(e.0)));
+            None
+        },
+    };
+    ResolveJob { state, handle }
+}
+
+/// A hostname lookup running on a background thread.
+///
+/// Dropping an unfinished job stops the lookup and waits for the worker
+/// thread to exit.
+pub struct ResolveJob {
+    state: Arc<ResolveState>,
+    handle: Option<crate::thread::JoinHandle>,
+}
+
+impl ResolveJob {
+    /// Return the result once the lookup has finished, or `None` while it
+    /// is still in progress.
+    pub fn poll(&mut self) -> Option<Result<Ipv4Addr, NetError>> {
+        let result = (*self.state.result.lock())?;
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+        Some(result)
+    }
+
+    /// Whether the lookup has finished.
+    pub fn is_finished(&self) -> bool {
+        self.state.result.lock().is_some()
+    }
+}
+
+impl Drop for ResolveJob {
+    fn drop(&mut self) {
+        let Some(handle) = self.handle.take() else {
+            return;
+        };
+        // Claim the id so the worker can't delete the resolver until the
+        // stop has returned.
+        let rid = self.state.rid.load(Ordering::Acquire);
+        if rid > 0
+            && self
+                .state
+                .rid
+                .compare_exchange(rid, RESOLVER_STOPPING, Ordering::AcqRel, Ordering::Relaxed)
+                .is_ok()
+        {
+            unsafe { sys::sceNetResolverStop(rid) };
+            self.state.rid.store(rid, Ordering::Release);
+        }
+        let _ = handle.join();
+    }
+}
+
+fn make_sockaddr_in(addr: Ipv4Addr, port: u16) -> sys::sockaddr {
+    let mut sa = sys::sockaddr {
+        sa_len: 16,
+        sa_family: 2, // AF_INET
+        sa_data: [0u8; 14],
+    };
+    // sockaddr_in layout: family(2) + port(2, big-endian) + addr(4, big-endian) + pad(8)
+    let port_be = port.to_be_bytes();
+    sa.sa_data[0] = port_be[0];
+    sa.sa_data[1] = port_be[1];
+    sa.sa_data[2] = addr.0[0];
+    sa.sa_data[3] = addr.0[1];
+    sa.sa_data[4] = addr.0[2];
+    sa.sa_data[5] = addr.0[3];
+    sa
+}
+
+// ── Socket statistics ──────────────────────────────────────────────
+
+/// Maximum number of live sockets whose counters are tracked. Sockets
+/// opened beyond this limit work normally but report zeroed stats.
+pub const MAX_TRACKED_SOCKETS: usize = 16;
+
+/// Traffic counters for one socket, or a sum over several.
+///
+/// All counters saturate at `u32::MAX`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SocketStats {
+    pub bytes_sent: u32,
+    pub bytes_received: u32,
+    /// Datagrams sent (UDP only).
+    pub packets_sent: u32,
+    /// Datagrams received (UDP only).
+    pub packets_received: u32,
+}
+
+impl SocketStats {
+    fn accumulate(&mut self, other: &SocketStats) {
+        self.bytes_sent = self.bytes_sent.saturating_add(other.bytes_sent);
+        self.bytes_received = self.bytes_received.saturating_add(other.bytes_received);
+        self.packets_sent = self.packets_sent.saturating_add(other.packets_sent);
+        self.packets_received = self.packets_received.saturating_add(other.packets_received);
+    }
+
+    /// Bytes sent and received per second between an earlier snapshot
+    /// and this one, `elapsed` apart.
+    pub fn rate_since(&self, earlier: &SocketStats, elapsed: Duration) -> (u32, u32) {
+        let us = elapsed.as_micros().max(1);
+        let per_sec = |now: u32, then: u32| (now.wrapping_sub(then) as u64 * 1_000_000 / us) as u32;
+        (
+            per_sec(self.bytes_sent, earlier.bytes_sent),
+            per_sec(self.bytes_received, earlier.bytes_received),
+        )
+    }
+}
+
+/// Snapshot returned by [`stats()`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NetStats {
+    /// Number of live, tracked sockets.
+    pub sockets: usize,
+    /// Sum of the counters of all live, tracked sockets.
+    pub totals: SocketStats,
+}
+
+/// Counters for one tracked socket, updated without a lock so the I/O
+/// path is a few atomic adds.
+struct StatsCounters {
+    bytes_sent: AtomicU32,
+    bytes_received: AtomicU32,
+    packets_sent: AtomicU32,
+    packets_received: AtomicU32,
+}
+
+impl StatsCounters {
+    fn snapshot(&self) -> SocketStats {
+        SocketStats {
+            bytes_sent: self.bytes_sent.load(Ordering::Relaxed),
+            bytes_received: self.bytes_received.load(Ordering::Relaxed),
+            packets_sent: self.packets_sent.load(Ordering::Relaxed),
+            packets_received: self.packets_received.load(Ordering::Relaxed),
+        }
+    }
+
+    fn reset(&self) {
+        self.bytes_sent.store(0, Ordering::Relaxed);
+        self.bytes_received.store(0, Ordering::Relaxed);
+        self.packets_sent.store(0, Ordering::Relaxed);
+        self.packets_received.store(0, Ordering::Relaxed);
+    }
+}
+
+/// Add to a counter, saturating at `u32::MAX`.
+#[inline]
+fn saturating_add(counter: &AtomicU32, n: u32) {
+    let _ = counter.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |v| {
+        Some(v.saturating_add(n))
+    });
+}
+
+/// Counters for every live socket, in a fixed array so the hot path needs
+/// no allocation.
+static SOCKET_STATS: [StatsCounters; MAX_TRACKED_SOCKETS] = [const {
+    StatsCounters {
+        bytes_sent: AtomicU32::new(0),
+        bytes_received: AtomicU32::new(0),
+        packets_sent: AtomicU32::new(0),
+        packets_received: AtomicU32::new(0),
+    }
+}; MAX_TRACKED_SOCKETS];
+
+/// Which entries of [`SOCKET_STATS`] belong to a live socket. Only taken
+/// when a socket is opened or closed, and by [`stats()`].
+static STATS_IN_USE: SpinMutex<[bool; MAX_TRACKED_SOCKETS]> =
+    SpinMutex::new([false; MAX_TRACKED_SOCKETS]);
+
+/// A socket's entry in [`SOCKET_STATS`], released on drop.
+struct StatsSlot(Option<u8>);
+
+impl StatsSlot {
+    fn register() -> Self {
+        let mut in_use = STATS_IN_USE.lock();
+        let slot = in_use.iter().position(|used| !used).map(|i| {
+            in_use[i] = true;
+            SOCKET_STATS[i].reset();
+            i as u8
+        });
+        Self(slot)
+    }
+
+    #[inline]
+    fn sent(&self, bytes: usize, packets: u32) {
+        TX_BYTES.fetch_add(bytes as u32, Ordering::Relaxed);
+        if let Some(i) = self.0 {
+            let s = &SOCKET_STATS[i as usize];
+            saturating_add(&s.bytes_sent, bytes as u32);
+            saturating_add(&s.packets_sent, packets);
+        }
+    }
+
+    #[inline]
+    fn received(&self, bytes: usize, packets: u32) {
+        RX_BYTES.fetch_add(bytes as u32, Ordering::Relaxed);
+        if let Some(i) = self.0 {
+            let s = &SOCKET_STATS[i as usize];
+            saturating_add(&s.bytes_received, bytes as u32);
+            saturating_add(&s.packets_received, packets);
+        }
+    }
+
+    fn snapshot(&self) -> SocketStats {
+        match self.0 {
+            Some(i) => SOCKET_STATS[i as usize].snapshot(),
+            None => SocketStats::default(),
+        }
+    }
+}
+
+impl Drop for StatsSlot {
+    fn drop(&mut self) {
+        if let Some(i) = self.0 {
+            STATS_IN_USE.lock()[i as usize] = false;
+        }
+    }
+}
+
+/// Aggregate traffic counters over all live sockets.
+///
+/// Counters of closed sockets are dropped; see [`LinkInfo`] for totals
+/// since [`init`].
+pub fn stats() -> NetStats {
+    let mut out = NetStats::default();
+    let in_use = STATS_IN_USE.lock();
+    for (counters, _) in SOCKET_STATS
+        .iter()
+        .zip(in_use.iter())
+        .filter(|(_, used)| **used)
+    {
+        out.sockets += 1;
+        out.totals.accumulate(&counters.snapshot());
+    }
+    out
+}
+
+// ── Ping ───────────────────────────────────────────────────────────
+
+/// Port of the standard UDP echo service (RFC 862), used by [`ping`] when
+/// raw sockets are unavailable.
+pub const UDP_ECHO_PORT: u16 = 7;
+
+const SOL_SOCKET: i32 = 0xffff;
+const SO_RCVTIMEO: i32 = 0x1006;
+const IPPROTO_ICMP: i32 = 1;
+const ICMP_ECHO_REPLY: u8 = 0;
+const ICMP_ECHO_REQUEST: u8 = 8;
+const PING_ID: u16 = 0x5053; // "PS"
+
+/// Measure the round-trip time to `addr`.
+///
+/// Sends an ICMP echo request over a raw socket. If the inet stack
+/// refuses to create a raw socket, falls back to sending a datagram to
+/// the UDP echo service ([`UDP_ECHO_PORT`]) on `addr`, which must be a
+/// cooperating server that sends each datagram back unchanged.
+///
+/// Fails if no matching reply arrives within `timeout_ms` milliseconds.
+pub fn ping(addr: Ipv4Addr, timeout_ms: u32) -> Result<Duration, NetError> {
+    static SEQ: AtomicU32 = AtomicU32::new(0);
+    let seq = SEQ.fetch_add(1, Ordering::Relaxed) as u16;
+
+    // AF_INET=2, SOCK_RAW=3
+    let fd = unsafe { sys::sceNetInetSocket(2, 3, IPPROTO_ICMP) };
+    if fd >= 0 {
+        let result = ping_icmp(fd, addr, seq, timeout_ms);
+        unsafe { sys::sceNetInetClose(fd) };
+        return result;
+    }
+
+    // AF_INET=2, SOCK_DGRAM=2
+    let fd = unsafe { sys::sceNetInetSocket(2, 2, 0) };
+    if fd < 0 {
+        return Err(NetError(unsafe { sys::sceNetInetGetErrno() }));
+    }
+    let result = ping_udp(fd, addr, seq, timeout_ms);
+    unsafe { sys::sceNetInetClose(fd) };
+    result
+}
+
+fn set_recv_timeout(fd: i32, timeout_ms: u32) -> Result<(), NetError> {
+    // The PSP takes the timeout as a plain microsecond count, not a timeval.
+    set_sockopt(fd, SOL_SOCKET, SO_RCVTIMEO, timeout_ms.saturating_mul(1000))
+}
+
+fn set_sockopt(fd: i32, level: i32, name: i32, value: u32) -> Result<(), NetError> {
+    let ret = unsafe {
+        sys::sceNetInetSetsockopt(
+            fd,
+            level,
+            name,
+            &value as *const u32 as *const c_void,
+            core::mem::size_of::<u32>() as u32,
+        )
+    };
+    if ret < 0 {
+        return Err(NetError(unsafe { sys::sceNetInetGetErrno() }));
+    }
+    Ok(())
+}
+
+fn get_sockopt(fd: i32, level: i32, name: i32) -> Result<u32, NetError> {
+    let mut value: u32 = 0;
+    let mut len = core::mem::size_of::<u32>() as u32;
+    let ret = unsafe {
+        sys::sceNetInetGetsockopt(
+            fd,
+            level,
+            name,
+            &mut value as *mut u32 as *mut c_void,
+            &mut len,
+        )
+    };
+    if ret < 0 {
+        return Err(NetError(unsafe { sys::sceNetInetGetErrno() }));
+    }
+    Ok(value)
+}
+
+/// Send `packet` to `addr:port`, then receive until `is_reply` accepts a
+/// datagram or the deadline passes.
+fn ping_exchange(
+    fd: i32,
+    addr: Ipv4Addr,
+    port: u16,
+    packet: &[u8],
+    timeout_ms: u32,
+    is_reply: impl Fn(&[u8]) -> bool,
+) -> Result<Duration, NetError> {
+    set_recv_timeout(fd, timeout_ms)?;
+    let sa = make_sockaddr_in(addr, port);
+    let start = Instant::now();
+    let ret = unsafe {
+        sys::sceNetInetSendto(
+            fd,
+            packet.as_ptr() as *const c_void,
+            packet.len(),
+            0,
+            &sa,
+            core::mem::size_of::<sys::sockaddr>() as u32,
+        )
+    };
+    if ret < 0 {
+        return Err(NetError(unsafe { sys::sceNetInetGetErrno() }));
+    }
+
+    let timeout = crate::time::Timeout::from_millis(timeout_ms);
+    let mut buf = [0u8; 128];
+    while !timeout.expired() {
+        let ret = unsafe { sys::sceNetInetRecv(fd, buf.as_mut_ptr() as *mut c_void, buf.len(), 0) };
+        if ret < 0 {
+            return Err(NetError(unsafe { sys::sceNetInetGetErrno() }));
+        }
+        if is_reply(&buf[..ret as usize]) {
+            return Ok(start.elapsed());
+        }
+    }
+    Err(NetError(-1))
+}
+
+fn ping_icmp(fd: i32, addr: Ipv4Addr, seq: u16, timeout_ms: u32) -> Result<Duration, NetError> {
+    let mut packet = [0u8; 16];
+    packet[0] = ICMP_ECHO_REQUEST;
+    packet[4..6].copy_from_slice(&PING_ID.to_be_bytes());
+    packet[6..8].copy_from_slice(&seq.to_be_bytes());
+    packet[8..].copy_from_slice(b"rust-psp");
+    let checksum = icmp_checksum(&packet);
+    packet[2..4].copy_from_slice(&checksum.to_be_bytes());
+
+    ping_exchange(fd, addr, 0, &packet, timeout_ms, |reply| {
+        // Raw sockets deliver the IP header in front of the ICMP message.
+        let Some(&vhl) = reply.first() else {
+            return false;
+        };
+        let icmp = &reply[((vhl & 0x0f) as usize * 4).min(reply.len())..];
+        icmp.len() >= 8
+            && icmp[0] == ICMP_ECHO_REPLY
+            && icmp[4..6] == PING_ID.to_be_bytes()
+            && icmp[6..8] == seq.to_be_bytes()
+    })
+}
+
+fn ping_udp(fd: i32, addr: Ipv4Addr, seq: u16, timeout_ms: u32) -> Result<Duration, NetError> {
+    let mut packet = [0u8; 12];
+    packet[..8].copy_from_slice(b"rust-psp");
+    packet[8..10].copy_from_slice(&PING_ID.to_be_bytes());
+    packet[10..].copy_from_slice(&seq.to_be_bytes());
+
+    ping_exchange(fd, addr, UDP_ECHO_PORT, &packet, timeout_ms, |reply| {
+        reply == packet
+    })
+}
+
+/// RFC 1071 internet checksum.
+fn icmp_checksum(data: &[u8]) -> u16 {
+    let mut sum: u32 = data
+        .chunks(2)
+        .map(|c| u16::from_be_bytes([c[0], *c.get(1).unwrap_or(&0)]) as u32)
+        .sum();
+    while sum > 0xffff {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    !(sum as u16)
+}
+
+// ── TcpStream ──────────────────────────────────────────────────────
+
+const SO_KEEPALIVE: i32 = 0x0008;
+const IPPROTO_TCP: i32 = 6;
+const TCP_NODELAY: i32 = 0x01;
+
+/// A TCP stream with RAII socket management.
+pub struct TcpStream {
+    fd: i32,
+    stats: StatsSlot,
+    _marker: PhantomData<*const ()>, // !Send + !Sync
+}
+
+impl TcpStream {
+    /// Connect to a remote TCP endpoint.
+    pub fn connect(addr: Ipv4Addr, port: u16) -> Result<Self, NetError> {
+        // AF_INET=2, SOCK_STREAM=1, protocol=0
+        let fd = unsafe { sys::sceNetInetSocket(2, 1, 0) };
+        if fd < 0 {
+            return Err(NetError(unsafe { sys::sceNetInetGetErrno() }));
+        }
+
+        let sa = make_sockaddr_in(addr, port);
+        let ret = unsafe {
+            sys::sceNetInetConnect(fd, &sa, core::mem::size_of::<sys::sockaddr>() as u32)
+        };
+        if ret < 0 {
+            let errno = unsafe { sys::sceNetInetGetErrno() };
+            unsafe { sys::sceNetInetClose(fd) };
+            return Err(NetError(errno));
+        }
+
+        Ok(Self {
+            fd,
+            stats: StatsSlot::register(),
+            _marker: PhantomData,
+        })
+    }
+
+    /// Read data from the stream.
+    ///
+    /// Returns the number of bytes read. Returns 0 at EOF.
+    pub fn read(&self, buf: &mut [u8]) -> Result<usize, NetError> {
+        let ret =
+            unsafe { sys::sceNetInetRecv(self.fd, buf.as_mut_ptr() as *mut c_void, buf.len(), 0) };
+        if ret < 0 {
+            Err(NetError(unsafe { sys::sceNetInetGetErrno() }))
+        } else {
+            self.stats.received(ret as usize, 0);
+            Ok(ret as usize)
+        }
+    }
+
+    /// Write data to the stream.
+    ///
+    /// Returns the number of bytes written.
+    pub fn write(&self, buf: &[u8]) -> Result<usize, NetError> {
+        let ret =
+            unsafe { sys::sceNetInetSend(self.fd, buf.as_ptr() as *const c_void, buf.len(), 0) };
+        if ret < 0 {
+            Err(NetError(unsafe { sys::sceNetInetGetErrno() }))
+        } else {
+            self.stats.sent(ret as usize, 0);
+            Ok(ret as usize)
+        }
+    }
+
+    /// Make [`read()`](Self::read) fail instead of blocking for more than
+    /// `timeout_ms` milliseconds without data. 0 waits forever.
+    pub fn set_read_timeout(&self, timeout_ms: u32) -> Result<(), NetError> {
+        set_recv_timeout(self.fd, timeout_ms)
+    }
+
+    /// Enable or disable Nagle's algorithm (`TCP_NODELAY`).
+    ///
+    /// With `nodelay` set, small writes are sent immediately instead of
+    /// being coalesced, trading bandwidth for latency in request/response
+    /// protocols.
+    pub fn set_nodelay(&self, nodelay: bool) -> Result<(), NetError> {
+        set_sockopt(self.fd, IPPROTO_TCP, TCP_NODELAY, nodelay as u32)
+    }
+
+    /// Whether `TCP_NODELAY` is set.
+    pub fn nodelay(&self) -> Result<bool, NetError> {
+        Ok(get_sockopt(self.fd, IPPROTO_TCP, TCP_NODELAY)? != 0)
+    }
+
+    /// Enable or disable TCP keepalive probes (`SO_KEEPALIVE`).
+    ///
+    /// The probe interval is fixed by the inet stack.
+    pub fn set_keepalive(&self, keepalive: bool) -> Result<(), NetError> {
+        set_sockopt(self.fd, SOL_SOCKET, SO_KEEPALIVE, keepalive as u32)
+    }
+
+    /// Whether `SO_KEEPALIVE` is set.
+    pub fn keepalive(&self) -> Result<bool, NetError> {
+        Ok(get_sockopt(self.fd, SOL_SOCKET, SO_KEEPALIVE)? != 0)
+    }
+
+    /// Traffic counters for this stream.
+    pub fn stats(&self) -> SocketStats {
+        self.stats.snapshot()
+    }
+}
+
+impl Drop for TcpStream {
+    fn drop(&mut self) {
+        unsafe {
+            sys::sceNetInetClose(self.fd);
+        }
+    }
+}
+
+// ── TcpListener ────────────────────────────────────────────────────
+
+const SO_REUSEADDR: i32 = 0x0004;
+
+/// Pending connections queued by a [`TcpListener`] before `accept`.
+const LISTEN_BACKLOG: i32 = 4;
+
+/// A TCP socket listening for incoming connections, with RAII management.
+pub struct TcpListener {
+    fd: i32,
+    _marker: PhantomData<*const ()>, // !Send + !Sync
+}
+
+impl TcpListener {
+    /// Listen for connections on `port` on all interfaces.
+    pub fn bind(port: u16) -> Result<Self, NetError> {
+        // AF_INET=2, SOCK_STREAM=1, protocol=0
+        let fd = unsafe { sys::sceNetInetSocket(2, 1, 0) };
+        if fd < 0 {
+            return Err(NetError(unsafe { sys::sceNetInetGetErrno() }));
+        }
+
+        // Allow rebinding right after a previous listener on the port
+        // closed; failure only means that rebinding may have to wait.
+        let _ = set_sockopt(fd, SOL_SOCKET, SO_REUSEADDR, 1);
+
+        let sa = make_sockaddr_in(Ipv4Addr([0, 0, 0, 0]), port);
+        let ret =
+            unsafe { sys::sceNetInetBind(fd, &sa, core::mem::size_of::<sys::sockaddr>() as u32) };
+        if ret < 0 || unsafe { sys::sceNetInetListen(fd, LISTEN_BACKLOG) } < 0 {
+            let errno = unsafe { sys::sceNetInetGetErrno() };
+            unsafe { sys::sceNetInetClose(fd) };
+            return Err(NetError(errno));
+        }
+
+        Ok(Self {
+            fd,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Make [`accept()`](Self::accept) fail instead of blocking for more
+    /// than `timeout_ms` milliseconds without a client. 0 waits forever.
+    pub fn set_accept_timeout(&self, timeout_ms: u32) -> Result<(), NetError> {
+        set_recv_timeout(self.fd, timeout_ms)
+    }
+
+    /// Block until a client connects.
+    ///
+    /// Returns `(stream, peer_addr, peer_port)`.
+    pub fn accept(&self) -> Result<(TcpStream, Ipv4Addr, u16), NetError> {
+        let mut sa = sys::sockaddr {
+            sa_len: 16,
+            sa_family: 2,
+            sa_data: [0u8; 14],
+        };
+        let mut sa_len = core::mem::size_of::<sys::sockaddr>() as u32;
+        let fd = unsafe { sys::sceNetInetAccept(self.fd, &mut sa, &mut sa_len) };
+        if fd < 0 {
+            return Err(NetError(unsafe { sys::sceNetInetGetErrno() }));
+        }
+
+        let port = u16::from_be_bytes([sa.sa_data[0], sa.sa_data[1]]);
+        let addr = Ipv4Addr([sa.sa_data[2], sa.sa_data[3], sa.sa_data[4], sa.sa_data[5]]);
+        let stream = TcpStream {
+            fd,
+            stats: StatsSlot::register(),
+            _marker: PhantomData,
+        };
+        Ok((stream, addr, port))
+    }
+}
+
+impl Drop for TcpListener {
+    fn drop(&mut self) {
+        unsafe {
+            sys::sceNetInetClose(self.fd);
+        }
+    }
+}
+
+// ── UdpSocket ──────────────────────────────────────────────────────
+
+/// A UDP socket with RAII management.
+pub struct UdpSocket {
+    fd: i32,
+    stats: StatsSlot,
+    _marker: PhantomData<*const ()>, // !Send + !Sync
+}
+
+impl UdpSocket {
+    /// Create a UDP socket bound to the given port.
+    ///
+    /// Pass `0` to let the OS choose an ephemeral port.
+    pub fn bind(port: u16) -> Result<Self, NetError> {
+        // AF_INET=2, SOCK_DGRAM=2, protocol=0
+        let fd = unsafe { sys::sceNetInetSocket(2, 2, 0) };
+        if fd < 0 {
+            return Err(NetError(unsafe { sys::sceNetInetGetErrno() }));
+        }
+
+        let sa = make_sockaddr_in(Ipv4Addr([0, 0, 0, 0]), port);
+        let ret =
+            unsafe { sys::sceNetInetBind(fd, &sa, core::mem::size_of::<sys::sockaddr>() as u32) };
+        if ret < 0 {
+            let errno = unsafe { sys::sceNetInetGetErrno() };
+            unsafe { sys::sceNetInetClose(fd) };
+            return Err(NetError(errno));
+        }
+
+        Ok(Self {
+            fd,
+            stats: StatsSlot::register(),
+            _marker: PhantomData,
+        })
+    }
+
+    /// Send data to a remote UDP endpoint.
+    pub fn send_to(&self, buf: &[u8], addr: Ipv4Addr, port: u16) -> Result<usize, NetError> {
+        let sa = make_sockaddr_in(addr, port);
+        let ret = unsafe {
+            sys::sceNetInetSendto(
+                self.fd,
+                buf.as_ptr() as *const c_void,
+                buf.len(),
+                0,
+                &sa,
+                core::mem::size_of::<sys::sockaddr>() as u32,
+            )
+        };
+        if ret < 0 {
+            Err(NetError(unsafe { sys::sceNetInetGetErrno() }))
+        } else {
+            self.stats.sent(ret as usize, 1);
+            Ok(ret as usize)
+        }
+    }
+
+    /// Receive data from any remote endpoint.
+    ///
+    /// Returns `(bytes_read, sender_addr, sender_port)`.
+    pub fn recv_from(&self, buf: &mut [u8]) -> Result<(usize, Ipv4Addr, u16), NetError> {
+        let mut sa = sys::sockaddr {
+            sa_len: 16,
+            sa_family: 2,
+            sa_data: [0u8; 14],
+        };
+        let mut sa_len = core::mem::size_of::<sys::sockaddr>() as u32;
+
+        let ret = unsafe {
+            sys::sceNetInetRecvfrom(
+                self.fd,
+                buf.as_mut_ptr() as *mut c_void,
+                buf.len(),
+                0,
+                &mut sa,
+                &mut sa_len,
+            )
+        };
+        if ret < 0 {
+            return Err(NetError(unsafe { sys::sceNetInetGetErrno() }));
+        }
+        self.stats.received(ret as usize, 1);
+
+        let port = u16::from_be_bytes([sa.sa_data[0], sa.sa_data[1]]);
+        let addr = Ipv4Addr([sa.sa_data[2], sa.sa_data[3], sa.sa_data[4], sa.sa_data[5]]);
+        Ok((ret as usize, addr, port))
+    }
+
+    /// Traffic counters for this socket.
+    pub fn stats(&self) -> SocketStats {
+        self.stats.snapshot()
+    }
+}
+
+impl Drop for UdpSocket {
+    fn drop(&mut self) {
+        unsafe {
+            sys::sceNetInetClose(self.fd);
+        }
+    }
+}
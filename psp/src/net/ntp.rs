@@ -0,0 +1,234 @@
+//! SNTP client (RFC 4330) for fetching the time from a network server.
+//!
+//! [`query()`] returns a server's time as a [`Tick`]. [`sync_offset()`]
+//! measures how far the local clock is off and stores the correction so
+//! [`rtc::corrected_now()`](crate::rtc::corrected_now) returns network
+//! time without changing the user's clock settings. Kernel-mode code can
+//! instead set the system clock with [`sync_system_clock()`].
+//!
+//! # Example
+//!
+//! ```ignore
+//! use psp::net::{self, ntp};
+//!
+//! net::init(0x20000).unwrap();
+//! net::connect_ap(1).unwrap();
+//!
+//! let offset_us = ntp::sync_offset("pool.ntp.org", 3000).unwrap();
+//! psp::dprintln!("Local clock is off by {} ms", offset_us / 1000);
+//! let now = psp::rtc::corrected_now().unwrap();
+//! ```
+
+use super::{NET_ERROR_BAD_RESPONSE, NetError, UdpSocket};
+use crate::rtc::Tick;
+use crate::time::Instant;
+
+/// UDP port of the NTP service.
+pub const NTP_PORT: u16 = 123;
+
+/// Size of an SNTP packet without extensions.
+pub const PACKET_SIZE: usize = 48;
+
+/// RTC tick of the NTP epoch, 1900-01-01 00:00:00 UTC.
+///
+/// RTC ticks count microseconds from 0001-01-01; the NTP epoch is
+/// 693,595 days later.
+pub const NTP_EPOCH_TICK: u64 = 693_595 * 86_400 * 1_000_000;
+
+/// Longest server name [`query()`] accepts.
+const MAX_SERVER_LEN: usize = 255;
+
+/// LI = 0 (no warning), VN = 4, mode = 3 (client).
+const CLIENT_HEADER: u8 = (4 << 3) | 3;
+/// Mode of a unicast server reply.
+const MODE_SERVER: u8 = 4;
+/// Leap indicator 3 means the server clock is unsynchronized.
+const LI_ALARM: u8 = 3;
+
+/// Convert a 64-bit NTP timestamp to a tick.
+///
+/// Timestamps with the top bit of `seconds` clear are taken to be in NTP
+/// era 1 (from 2036-02-07), per RFC 4330 section 3.
+pub fn timestamp_to_tick(seconds: u32, fraction: u32) -> Tick {
+    let mut secs = seconds as u64;
+    if seconds & 0x8000_0000 == 0 {
+        secs += 1 << 32;
+    }
+    let micros = (fraction as u64 * 1_000_000) >> 32;
+    Tick(NTP_EPOCH_TICK + secs * 1_000_000 + micros)
+}
+
+/// Convert a tick to a 64-bit NTP timestamp `(seconds, fraction)`.
+///
+/// Inverse of [`timestamp_to_tick()`], to microsecond precision. Ticks
+/// before the NTP epoch map to 0.
+pub fn tick_to_timestamp(tick: Tick) -> (u32, u32) {
+    let since_epoch = tick.0.saturating_sub(NTP_EPOCH_TICK);
+    let seconds = (since_epoch / 1_000_000) as u32;
+    let fraction = (((since_epoch % 1_000_000) << 32) / 1_000_000) as u32;
+    (seconds, fraction)
+}
+
+/// Build a client request whose transmit timestamp is `sent`.
+///
+/// The server echoes the transmit timestamp back as the originate
+/// timestamp, which [`parse_response()`] uses to match the reply.
+pub fn build_request(sent: Tick) -> [u8; PACKET_SIZE] {
+    let mut packet = [0u8; PACKET_SIZE];
+    packet[0] = CLIENT_HEADER;
+    write_timestamp(&mut packet[40..48], sent);
+    packet
+}
+
+/// Server timestamps from a reply.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Reply {
+    /// When the server received the request.
+    pub receive: Tick,
+    /// When the server sent the reply.
+    pub transmit: Tick,
+}
+
+/// Validate and parse a server reply to a request built with
+/// [`build_request(sent)`](build_request).
+///
+/// Fails with [`NET_ERROR_BAD_RESPONSE`] if the packet is short, isn't a
+/// server reply, answers a different request, or comes from an
+/// unsynchronized or refusing (kiss-o'-death) server.
+pub fn parse_response(packet: &[u8], sent: Tick) -> Result<Reply, NetError> {
+    let bad = NetError(NET_ERROR_BAD_RESPONSE);
+    if packet.len() < PACKET_SIZE {
+        return Err(bad);
+    }
+    let leap = packet[0] >> 6;
+    let version = (packet[0] >> 3) & 0x7;
+    let mode = packet[0] & 0x7;
+    let stratum = packet[1];
+    if mode != MODE_SERVER || version == 0 || leap == LI_ALARM {
+        return Err(bad);
+    }
+    // Stratum 0 is a kiss-o'-death; 16 and above are unsynchronized.
+    if stratum == 0 || stratum >= 16 {
+        return Err(bad);
+    }
+    if packet[24..32] != timestamp_bytes(sent) {
+        return Err(bad);
+    }
+    let (rs, rf) = read_timestamp(&packet[32..40]);
+    let (ts, tf) = read_timestamp(&packet[40..48]);
+    if ts == 0 {
+        return Err(bad);
+    }
+    Ok(Reply {
+        receive: timestamp_to_tick(rs, rf),
+        transmit: timestamp_to_tick(ts, tf),
+    })
+}
+
+/// Clock offset in microseconds from the four timestamps of an exchange:
+/// request sent (`t1`, local), received (`t2`, server), reply sent (`t3`,
+/// server) and received (`t4`, local).
+///
+/// Positive means the local clock is behind the server.
+pub fn compute_offset(t1: Tick, t2: Tick, t3: Tick, t4: Tick) -> i64 {
+    let d = |a: Tick, b: Tick| a.0 as i64 - b.0 as i64;
+    (d(t2, t1) + d(t3, t4)) / 2
+}
+
+fn timestamp_bytes(tick: Tick) -> [u8; 8] {
+    let mut out = [0u8; 8];
+    write_timestamp(&mut out, tick);
+    out
+}
+
+fn write_timestamp(out: &mut [u8], tick: Tick) {
+    let (seconds, fraction) = tick_to_timestamp(tick);
+    out[..4].copy_from_slice(&seconds.to_be_bytes());
+    out[4..8].copy_from_slice(&fraction.to_be_bytes());
+}
+
+fn read_timestamp(bytes: &[u8]) -> (u32, u32) {
+    (
+        u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+        u32::from_be_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]),
+    )
+}
+
+/// One request/reply exchange with its local send and receive times.
+struct Exchange {
+    sent: Tick,
+    reply: Reply,
+    received: Tick,
+}
+
+fn exchange(server: &str, timeout_ms: u32) -> Result<Exchange, NetError> {
+    let bad = NetError(NET_ERROR_BAD_RESPONSE);
+    if server.len() > MAX_SERVER_LEN {
+        return Err(bad);
+    }
+    let mut name = [0u8; MAX_SERVER_LEN + 1];
+    name[..server.len()].copy_from_slice(server.as_bytes());
+    let addr = super::resolve_hostname(&name[..=server.len()])?;
+
+    let socket = UdpSocket::bind(0)?;
+    super::set_recv_timeout(socket.fd, timeout_ms)?;
+    let now = || Tick::now().map_err(|e| NetError(e.0));
+
+    let start = Instant::now();
+    let sent = now()?;
+    socket.send_to(&build_request(sent), addr, NTP_PORT)?;
+
+    let mut buf = [0u8; PACKET_SIZE];
+    loop {
+        let (len, from, _) = socket.recv_from(&mut buf)?;
+        let received = now()?;
+        if let (true, Ok(reply)) = (from == addr, parse_response(&buf[..len], sent)) {
+            return Ok(Exchange {
+                sent,
+                reply,
+                received,
+            });
+        }
+        // Ignore stray datagrams until the timeout runs out.
+        if start.elapsed().as_millis() >= timeout_ms as u64 {
+            return Err(bad);
+        }
+    }
+}
+
+/// Ask `server` (a hostname or dotted IPv4 address) for the current time.
+///
+/// Returns the server's transmit timestamp as a UTC tick. Fails if no
+/// valid reply arrives within `timeout_ms` milliseconds.
+pub fn query(server: &str, timeout_ms: u32) -> Result<Tick, NetError> {
+    Ok(exchange(server, timeout_ms)?.reply.transmit)
+}
+
+/// Measure the local clock's offset from `server` and store it as the
+/// [`rtc::clock_offset()`](crate::rtc::clock_offset).
+///
+/// Returns the offset in microseconds; positive means the local clock is
+/// behind. The round-trip delay is compensated for, assuming it is split
+/// evenly between the two directions.
+pub fn sync_offset(server: &str, timeout_ms: u32) -> Result<i64, NetError> {
+    let ex = exchange(server, timeout_ms)?;
+    let offset = compute_offset(ex.sent, ex.reply.receive, ex.reply.transmit, ex.received);
+    crate::rtc::set_clock_offset(offset);
+    Ok(offset)
+}
+
+/// Set the system clock from `server` and reset the stored
+/// [`rtc::clock_offset()`](crate::rtc::clock_offset) to 0.
+///
+/// Returns the correction that was applied, in microseconds. Requires
+/// `feature = "kernel"`.
+#[cfg(feature = "kernel")]
+pub fn sync_system_clock(server: &str, timeout_ms: u32) -> Result<i64, NetError> {
+    let ex = exchange(server, timeout_ms)?;
+    let offset = compute_offset(ex.sent, ex.reply.receive, ex.reply.transmit, ex.received);
+    let now = Tick::now().map_err(|e| NetError(e.0))?;
+    crate::rtc::set_current_tick(Tick(now.0.saturating_add_signed(offset)))
+        .map_err(|e| NetError(e.0))?;
+    crate::rtc::set_clock_offset(0);
+    Ok(offset)
+}
@@ -0,0 +1,671 @@
+//! WebSocket client (RFC 6455) over a [`TcpStream`].
+//!
+//! [`WsClient::connect()`] performs the HTTP Upgrade handshake and checks
+//! the server's `Sec-WebSocket-Accept` hash. Messages are then sent with
+//! [`send_text()`](WsClient::send_text) and
+//! [`send_binary()`](WsClient::send_binary) and received with
+//! [`recv()`](WsClient::recv), which reassembles fragmented messages and
+//! answers pings on its own. Dropping the client performs the close
+//! handshake.
+//!
+//! Only plain `ws://` is supported: the firmware's TLS is only reachable
+//! through `sceHttps` requests, not raw sockets.
+//!
+//! The framing helpers ([`FrameHeader`], [`encode_frame()`],
+//! [`apply_mask()`], [`accept_key()`], [`MessageAssembler`]) are public so
+//! they can be tested without a server.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use psp::net::{self, websocket::{WsClient, WsMessage}};
+//!
+//! net::init(0x20000).unwrap();
+//! net::connect_ap(1).unwrap();
+//!
+//! let mut ws = WsClient::connect("echo.websocket.org", 80, "/").unwrap();
+//! ws.send_text("hello").unwrap();
+//! while let Ok(msg) = ws.recv() {
+//!     match msg {
+//!         WsMessage::Text(text) => psp::dprintln!("{}", text),
+//!         WsMessage::Binary(data) => psp::dprintln!("{} bytes", data.len()),
+//!         WsMessage::Close(_) => break,
+//!     }
+//! }
+//! ```
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use super::{NetError, TcpStream};
+
+/// Default limit on the size of a received message.
+pub const DEFAULT_MAX_MESSAGE_SIZE: usize = 64 * 1024;
+
+/// Appended to the client's key before hashing it for the accept header.
+const ACCEPT_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// Longest HTTP response head [`WsClient::connect()`] reads.
+const MAX_RESPONSE_HEAD: usize = 8 * 1024;
+
+/// Longest host name [`WsClient::connect()`] accepts.
+const MAX_HOST_LEN: usize = 255;
+
+/// How long dropping a client waits for the server's close frame.
+const CLOSE_TIMEOUT_MS: u32 = 1000;
+
+/// Close status for a normal closure.
+pub const CLOSE_NORMAL: u16 = 1000;
+
+/// Close status for a frame that breaks the protocol.
+pub const CLOSE_PROTOCOL_ERROR: u16 = 1002;
+
+/// Close status for a message too big to process.
+pub const CLOSE_TOO_BIG: u16 = 1009;
+
+/// Error from a WebSocket operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WsError {
+    /// The underlying socket failed.
+    Net(NetError),
+    /// The host name passed to [`WsClient::connect()`] is empty, longer
+    /// than 255 bytes or contains a NUL byte.
+    InvalidHost,
+    /// The server refused the upgrade or answered with a bad accept key.
+    Handshake,
+    /// The server sent a malformed or masked frame or broke the framing
+    /// rules. The connection is closed.
+    Protocol,
+    /// A message was larger than the
+    /// [limit](WsClient::set_max_message_size). The connection is closed.
+    MessageTooLarge {
+        /// Size of the message so far, in bytes.
+        len: u64,
+        /// The limit.
+        max: usize,
+    },
+    /// A text message wasn't valid UTF-8.
+    InvalidUtf8,
+    /// The connection has been closed.
+    Closed,
+}
+
+impl core::fmt::Display for WsError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Net(e) => write!(f, "websocket: {}", e),
+            Self::InvalidHost => f.write_str("invalid websocket host name"),
+            Self::Handshake => f.write_str("websocket handshake rejected"),
+            Self::Protocol => f.write_str("websocket protocol violation"),
+            Self::MessageTooLarge { len, max } => {
+                write!(
+                    f,
+                    "websocket message of {} bytes exceeds {} byte limit",
+                    len, max
+                )
+            },
+            Self::InvalidUtf8 => f.write_str("websocket text message is not valid UTF-8"),
+            Self::Closed => f.write_str("websocket connection closed"),
+        }
+    }
+}
+
+impl From<NetError> for WsError {
+    fn from(e: NetError) -> Self {
+        Self::Net(e)
+    }
+}
+
+/// Frame opcode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Opcode {
+    /// A later fragment of a message.
+    Continuation = 0x0,
+    /// The first frame of a UTF-8 text message.
+    Text = 0x1,
+    /// The first frame of a binary message.
+    Binary = 0x2,
+    /// Close handshake.
+    Close = 0x8,
+    /// Keepalive request; answered with a pong.
+    Ping = 0x9,
+    /// Keepalive reply.
+    Pong = 0xA,
+}
+
+impl Opcode {
+    /// Decode the low nibble of a frame's first byte.
+    pub fn from_u8(value: u8) -> Option<Self> {
+        Some(match value {
+            0x0 => Self::Continuation,
+            0x1 => Self::Text,
+            0x2 => Self::Binary,
+            0x8 => Self::Close,
+            0x9 => Self::Ping,
+            0xA => Self::Pong,
+            _ => return None,
+        })
+    }
+
+    /// Whether this is a control frame (close, ping or pong).
+    pub fn is_control(self) -> bool {
+        self as u8 & 0x8 != 0
+    }
+}
+
+/// The header of a frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameHeader {
+    /// Whether this is the last frame of its message.
+    pub fin: bool,
+    pub opcode: Opcode,
+    /// Masking key, present on client-to-server frames.
+    pub mask: Option<[u8; 4]>,
+    /// Payload length in bytes.
+    pub payload_len: u64,
+    /// Header length in bytes; the payload starts here.
+    pub header_len: usize,
+}
+
+impl FrameHeader {
+    /// Parse the header at the start of `buf`.
+    ///
+    /// Returns `Ok(None)` if `buf` doesn't hold the whole header yet, and
+    /// [`WsError::Protocol`] for reserved bits, unknown opcodes, or
+    /// fragmented or oversized control frames.
+    pub fn parse(buf: &[u8]) -> Result<Option<Self>, WsError> {
+        let [b0, b1, ..] = *buf else {
+            return Ok(None);
+        };
+        // No extensions are negotiated, so RSV1-3 must be clear.
+        if b0 & 0x70 != 0 {
+            return Err(WsError::Protocol);
+        }
+        let fin = b0 & 0x80 != 0;
+        let opcode = Opcode::from_u8(b0 & 0x0f).ok_or(WsError::Protocol)?;
+        let masked = b1 & 0x80 != 0;
+
+        let (payload_len, mut header_len) = match b1 & 0x7f {
+            126 => match buf.get(2..4) {
+                Some(len) => (u16::from_be_bytes([len[0], len[1]]) as u64, 4),
+                None => return Ok(None),
+            },
+            127 => match buf.get(2..10) {
+                Some(len) => (u64::from_be_bytes(len.try_into().unwrap_or_default()), 10),
+                None => return Ok(None),
+            },
+            len => (len as u64, 2),
+        };
+        if opcode.is_control() && (!fin || payload_len > 125) {
+            return Err(WsError::Protocol);
+        }
+
+        let mask = if masked {
+            let Some(key) = buf.get(header_len..header_len + 4) else {
+                return Ok(None);
+            };
+            header_len += 4;
+            Some([key[0], key[1], key[2], key[3]])
+        } else {
+            None
+        };
+
+        Ok(Some(Self {
+            fin,
+            opcode,
+            mask,
+            payload_len,
+            header_len,
+        }))
+    }
+}
+
+/// Encode a single final frame carrying `payload`, masked with `mask` if
+/// given.
+///
+/// Clients must mask every frame they send; servers never mask.
+pub fn encode_frame(opcode: Opcode, payload: &[u8], mask: Option<[u8; 4]>) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(payload.len() + 14);
+    frame.push(0x80 | opcode as u8);
+    let mask_bit = if mask.is_some() { 0x80 } else { 0 };
+    match payload.len() {
+        len @ 0..=125 => frame.push(mask_bit | len as u8),
+        len @ 126..=0xffff => {
+            frame.push(mask_bit | 126);
+            frame.extend_from_slice(&(len as u16).to_be_bytes());
+        },
+        len => {
+            frame.push(mask_bit | 127);
+            frame.extend_from_slice(&(len as u64).to_be_bytes());
+        },
+    }
+    let start = frame.len() + if mask.is_some() { 4 } else { 0 };
+    if let Some(key) = mask {
+        frame.extend_from_slice(&key);
+    }
+    frame.extend_from_slice(payload);
+    if let Some(key) = mask {
+        apply_mask(&mut frame[start..], key);
+    }
+    frame
+}
+
+/// XOR `data` with the repeating masking key. Masking is its own inverse.
+pub fn apply_mask(data: &mut [u8], mask: [u8; 4]) {
+    for (i, byte) in data.iter_mut().enumerate() {
+        *byte ^= mask[i % 4];
+    }
+}
+
+/// The `Sec-WebSocket-Accept` value a server must answer `key` with.
+pub fn accept_key(key: &str) -> String {
+    let mut input = String::with_capacity(key.len() + ACCEPT_GUID.len());
+    input.push_str(key);
+    input.push_str(ACCEPT_GUID);
+    base64(&crate::hash::sha1(input.as_bytes()))
+}
+
+/// Check the head of a handshake response (status line and headers, up
+/// to the blank line) against the `key` the request sent.
+pub fn verify_handshake(head: &str, key: &str) -> Result<(), WsError> {
+    let mut lines = head.split("\r\n");
+    let status = lines.next().unwrap_or("");
+    let mut parts = status.split(' ');
+    let (Some(version), Some("101")) = (parts.next(), parts.next()) else {
+        return Err(WsError::Handshake);
+    };
+    if !version.starts_with("HTTP/1.") {
+        return Err(WsError::Handshake);
+    }
+
+    let expected = accept_key(key);
+    let accepted = lines
+        .filter_map(|line| line.split_once(':'))
+        .any(|(name, value)| {
+            name.trim().eq_ignore_ascii_case("sec-websocket-accept") && value.trim() == expected
+        });
+    if accepted {
+        Ok(())
+    } else {
+        Err(WsError::Handshake)
+    }
+}
+
+/// Standard base64 with padding.
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = ((b[0] as u32) << 16) | ((b[1] as u32) << 8) | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[((n >> (18 - 6 * i)) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Fresh bytes for a handshake key or masking key, from a source the
+/// game's own RNG seeding can't repeat.
+fn random_bytes<const N: usize>() -> [u8; N] {
+    let mut bytes = [0u8; N];
+    crate::crypto::fill_random(&mut bytes);
+    bytes
+}
+
+/// A received message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WsMessage {
+    Text(String),
+    Binary(Vec<u8>),
+    /// The server closed the connection, with its status code if it sent
+    /// one. The close has already been answered.
+    Close(Option<u16>),
+}
+
+/// Reassembles data frames into messages, as [`WsClient::recv()`] does.
+///
+/// Feed it the header and payload of every text, binary and continuation
+/// frame; control frames are the caller's to handle, and may arrive
+/// between the fragments of a message.
+#[derive(Debug)]
+pub struct MessageAssembler {
+    /// Opcode and payload of a fragmented message in progress.
+    partial: Option<(Opcode, Vec<u8>)>,
+    max_message_size: usize,
+}
+
+impl MessageAssembler {
+    /// An assembler for messages of at most `max_message_size` bytes.
+    pub fn new(max_message_size: usize) -> Self {
+        Self {
+            partial: None,
+            max_message_size,
+        }
+    }
+
+    /// Bytes of the unfinished message received so far.
+    pub fn buffered(&self) -> usize {
+        self.partial.as_ref().map_or(0, |(_, data)| data.len())
+    }
+
+    /// Add a data frame, returning the message once its final frame is in.
+    ///
+    /// Fails with [`WsError::Protocol`] for a control frame, a continuation
+    /// with nothing to continue or a new message before the last one
+    /// finished, with [`WsError::MessageTooLarge`] if the message outgrows
+    /// the limit, and with [`WsError::InvalidUtf8`] for a bad text message.
+    pub fn push(
+        &mut self,
+        header: &FrameHeader,
+        payload: Vec<u8>,
+    ) -> Result<Option<WsMessage>, WsError> {
+        let buffered = self.buffered();
+        if buffered + payload.len() > self.max_message_size {
+            return Err(WsError::MessageTooLarge {
+                len: (buffered + payload.len()) as u64,
+                max: self.max_message_size,
+            });
+        }
+        let (opcode, data) = match (header.opcode, self.partial.take()) {
+            (Opcode::Continuation, Some((opcode, mut data))) => {
+                data.extend_from_slice(&payload);
+                (opcode, data)
+            },
+            (Opcode::Text | Opcode::Binary, None) => (header.opcode, payload),
+            _ => return Err(WsError::Protocol),
+        };
+
+        if !header.fin {
+            self.partial = Some((opcode, data));
+            return Ok(None);
+        }
+        match opcode {
+            Opcode::Text => String::from_utf8(data)
+                .map(|text| Some(WsMessage::Text(text)))
+                .map_err(|_| WsError::InvalidUtf8),
+            _ => Ok(Some(WsMessage::Binary(data))),
+        }
+    }
+}
+
+/// A WebSocket connection.
+///
+/// See the [module documentation](self).
+pub struct WsClient {
+    stream: TcpStream,
+    /// Bytes read from the socket but not yet parsed.
+    rx: Vec<u8>,
+    max_message_size: usize,
+    /// Whether we have sent a close frame.
+    close_sent: bool,
+    /// Whether the server has sent a close frame.
+    close_received: bool,
+}
+
+impl WsClient {
+    /// Connect to `ws://host:port/path` and perform the opening handshake.
+    ///
+    /// `host` is a hostname or dotted IPv4 address, and `path` includes
+    /// any query string (e.g. `"/chat?room=1"`).
+    pub fn connect(host: &str, port: u16, path: &str) -> Result<Self, WsError> {
+        if host.is_empty() || host.len() > MAX_HOST_LEN || host.contains('\0') {
+            return Err(WsError::InvalidHost);
+        }
+        let mut name = [0u8; MAX_HOST_LEN + 1];
+        name[..host.len()].copy_from_slice(host.as_bytes());
+        let addr = super::resolve_hostname(&name[..=host.len()])?;
+        let stream = TcpStream::connect(addr, port)?;
+
+        let key = base64(&random_bytes::<16>());
+        let host_header = if port == 80 {
+            String::from(host)
+        } else {
+            format!("{}:{}", host, port)
+        };
+        let request = format!(
+            "GET {} HTTP/1.1\r\nHost: {}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+             Sec-WebSocket-Key: {}\r\nSec-WebSocket-Version: 13\r\n\r\n",
+            if path.is_empty() { "/" } else { path },
+            host_header,
+            key
+        );
+
+        let mut client = Self {
+            stream,
+            rx: Vec::new(),
+            max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
+            close_sent: false,
+            close_received: false,
+        };
+        client.write_all(request.as_bytes())?;
+
+        let head_len = loop {
+            if let Some(pos) = client.rx.windows(4).position(|w| w == b"\r\n\r\n") {
+                break pos + 4;
+            }
+            if client.rx.len() > MAX_RESPONSE_HEAD {
+                return Err(WsError::Handshake);
+            }
+            client.fill()?;
+        };
+        let head = core::str::from_utf8(&client.rx[..head_len]).map_err(|_| WsError::Handshake)?;
+        if let Err(e) = verify_handshake(head, &key) {
+            // Not a WebSocket; don't send it a close frame.
+            client.close_sent = true;
+            client.close_received = true;
+            return Err(e);
+        }
+        // Frames the server sent right after the handshake stay buffered.
+        client.rx.drain(..head_len);
+        Ok(client)
+    }
+
+    /// Limit the size of received messages, counting all fragments.
+    /// Larger messages fail [`recv()`](Self::recv) with
+    /// [`WsError::MessageTooLarge`] before they are buffered, and close the
+    /// connection with [`CLOSE_TOO_BIG`], since the rest of the message
+    /// can't be skipped without reading it.
+    pub fn set_max_message_size(&mut self, max: usize) {
+        self.max_message_size = max;
+    }
+
+    /// The received message size limit.
+    pub fn max_message_size(&self) -> usize {
+        self.max_message_size
+    }
+
+    /// Make [`recv()`](Self::recv) fail instead of blocking for more than
+    /// `timeout_ms` milliseconds without data. 0 waits forever.
+    pub fn set_read_timeout(&self, timeout_ms: u32) -> Result<(), WsError> {
+        Ok(super::set_recv_timeout(self.stream.fd, timeout_ms)?)
+    }
+
+    /// Send a text message.
+    pub fn send_text(&mut self, text: &str) -> Result<(), WsError> {
+        self.send(Opcode::Text, text.as_bytes())
+    }
+
+    /// Send a binary message.
+    pub fn send_binary(&mut self, data: &[u8]) -> Result<(), WsError> {
+        self.send(Opcode::Binary, data)
+    }
+
+    /// Send a ping with up to 125 bytes of `payload`. The server's pong is
+    /// consumed by [`recv()`](Self::recv).
+    pub fn ping(&mut self, payload: &[u8]) -> Result<(), WsError> {
+        if payload.len() > 125 {
+            return Err(WsError::Protocol);
+        }
+        self.send(Opcode::Ping, payload)
+    }
+
+    /// Start the close handshake with status `code`.
+    ///
+    /// Keep calling [`recv()`](Self::recv) until it returns
+    /// [`WsMessage::Close`] to receive what the server sent before closing,
+    /// or just drop the client.
+    pub fn close(&mut self, code: u16) -> Result<(), WsError> {
+        if self.close_sent {
+            return Ok(());
+        }
+        let result = self.send(Opcode::Close, &code.to_be_bytes());
+        self.close_sent = true;
+        result
+    }
+
+    fn send(&mut self, opcode: Opcode, payload: &[u8]) -> Result<(), WsError> {
+        if self.close_sent {
+            return Err(WsError::Closed);
+        }
+        let frame = encode_frame(opcode, payload, Some(random_bytes::<4>()));
+        self.write_all(&frame)
+    }
+
+    fn write_all(&self, mut data: &[u8]) -> Result<(), WsError> {
+        while !data.is_empty() {
+            let n = self.stream.write(data)?;
+            if n == 0 {
+                return Err(WsError::Closed);
+            }
+            data = &data[n..];
+        }
+        Ok(())
+    }
+
+    /// Read more bytes from the socket into `rx`.
+    fn fill(&mut self) -> Result<(), WsError> {
+        let mut buf = [0u8; 2048];
+        let n = self.stream.read(&mut buf)?;
+        if n == 0 {
+            return Err(WsError::Closed);
+        }
+        self.rx.extend_from_slice(&buf[..n]);
+        Ok(())
+    }
+
+    /// Read one frame, returning its header and payload. `limit` is the
+    /// most payload the caller can accept.
+    fn read_frame(&mut self, limit: usize) -> Result<(FrameHeader, Vec<u8>), WsError> {
+        let header = loop {
+            if let Some(header) = FrameHeader::parse(&self.rx)? {
+                break header;
+            }
+            self.fill()?;
+        };
+        // Servers must not mask their frames (RFC 6455 section 5.1).
+        if header.mask.is_some() {
+            return Err(WsError::Protocol);
+        }
+        if header.payload_len > limit as u64 {
+            return Err(WsError::MessageTooLarge {
+                len: header.payload_len,
+                max: self.max_message_size,
+            });
+        }
+        let end = header.header_len + header.payload_len as usize;
+        while self.rx.len() < end {
+            self.fill()?;
+        }
+        let payload = self.rx[header.header_len..end].to_vec();
+        self.rx.drain(..end);
+        Ok((header, payload))
+    }
+
+    /// Receive the next message.
+    ///
+    /// Fragments are reassembled, pings are answered with pongs, and pongs
+    /// are discarded. When the server closes the connection, its close is
+    /// echoed and [`WsMessage::Close`] is returned; after that, this
+    /// fails with [`WsError::Closed`]. It does the same after
+    /// [`WsError::Protocol`] and [`WsError::MessageTooLarge`], which close
+    /// the connection.
+    pub fn recv(&mut self) -> Result<WsMessage, WsError> {
+        let result = self.recv_message();
+        match result {
+            Err(WsError::Protocol) => self.fail(CLOSE_PROTOCOL_ERROR),
+            Err(WsError::MessageTooLarge { .. }) => self.fail(CLOSE_TOO_BIG),
+            _ => {},
+        }
+        result
+    }
+
+    /// Give up on a connection whose stream can't be read any further:
+    /// send a close with status `code` and stop reading.
+    fn fail(&mut self, code: u16) {
+        let _ = self.close(code);
+        self.close_received = true;
+        self.rx.clear();
+    }
+
+    fn recv_message(&mut self) -> Result<WsMessage, WsError> {
+        if self.close_received {
+            return Err(WsError::Closed);
+        }
+        let mut message = MessageAssembler::new(self.max_message_size);
+        loop {
+            let limit = self
+                .max_message_size
+                .saturating_sub(message.buffered())
+                .max(125);
+            let (header, payload) = self.read_frame(limit)?;
+            match header.opcode {
+                Opcode::Ping => {
+                    if !self.close_sent {
+                        let pong = encode_frame(Opcode::Pong, &payload, Some(random_bytes::<4>()));
+                        self.write_all(&pong)?;
+                    }
+                },
+                Opcode::Pong => {},
+                Opcode::Close => {
+                    self.close_received = true;
+                    let code = payload.get(..2).map(|c| u16::from_be_bytes([c[0], c[1]]));
+                    if !self.close_sent {
+                        self.close_sent = true;
+                        let echo = code.map_or([0; 2], u16::to_be_bytes);
+                        let len = if code.is_some() { 2 } else { 0 };
+                        let frame =
+                            encode_frame(Opcode::Close, &echo[..len], Some(random_bytes::<4>()));
+                        let _ = self.write_all(&frame);
+                    }
+                    return Ok(WsMessage::Close(code));
+                },
+                _ => {
+                    if let Some(msg) = message.push(&header, payload)? {
+                        return Ok(msg);
+                    }
+                },
+            }
+        }
+    }
+}
+
+impl Drop for WsClient {
+    fn drop(&mut self) {
+        if self.close_received {
+            return;
+        }
+        if self.close(CLOSE_NORMAL).is_err() {
+            return;
+        }
+        // Wait briefly for the server's close, discarding anything it sent
+        // before it.
+        if self.set_read_timeout(CLOSE_TIMEOUT_MS).is_err() {
+            return;
+        }
+        while let Ok((header, _)) = self.read_frame(self.max_message_size.max(125)) {
+            if header.opcode == Opcode::Close {
+                break;
+            }
+        }
+    }
+}
@@ -0,0 +1,334 @@
+//! Controller-driven software keyboard rendered into the caller's frame.
+//!
+//! Unlike the system OSK ([`super::text_input`]), [`InlineKeyboard`] never
+//! touches the `sceUtility*` syscalls: it is drawn with the caller's
+//! [`FontRenderer`] inside their own display list, so the game loop keeps
+//! running and the keyboard can be used from plugins or mid-gameplay.
+//!
+//! The layout is danzeff-style: the analog stick selects one of nine
+//! cells in a 3x3 grid, and the four face buttons pick one of the four
+//! characters in that cell (Triangle = top, Square = left, Circle = right,
+//! Cross = bottom).
+//!
+//! | Button | Action |
+//! |--------|--------|
+//! | L trigger | Backspace |
+//! | R trigger | Toggle shift (lowercase / uppercase) |
+//! | SELECT | Toggle the number/symbol layer |
+//! | START | Submit ([`KeyEvent::Enter`]) |
+//!
+//! # Example
+//!
+//! ```ignore
+//! use psp::osk::inline::{InlineKeyboard, KeyEvent};
+//!
+//! let mut kb = InlineKeyboard::new(16);
+//! loop {
+//!     ctrl.update();
+//!     if let Some(KeyEvent::Enter) = kb.update(&ctrl) {
+//!         break;
+//!     }
+//!     // ... inside the frame's display list:
+//!     unsafe { kb.draw(&mut renderer, 300.0, 150.0) };
+//!     renderer.draw_text(20.0, 20.0, 0xffffffff, kb.current_text());
+//!     unsafe { renderer.flush() };
+//! }
+//! ```
+
+use alloc::string::String;
+
+use crate::font::FontRenderer;
+use crate::input::Controller;
+use crate::sys::CtrlButtons;
+
+// ── Layout ──────────────────────────────────────────────────────────
+
+/// Character layer shown by the keyboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layer {
+    Lower,
+    Upper,
+    Symbols,
+}
+
+/// Characters in one cell, ordered top (Triangle), left (Square),
+/// right (Circle), bottom (Cross).
+pub type Cell = [char; 4];
+
+/// Nine cells, row-major from the top-left.
+pub type Layout = [Cell; 9];
+
+/// Lowercase letters. The center cell (selected when the stick is at
+/// rest) holds space and the most common punctuation.
+pub const LOWER: Layout = [
+    ['a', 'b', 'c', 'd'],
+    ['e', 'f', 'g', 'h'],
+    ['i', 'j', 'k', 'l'],
+    ['m', 'n', 'o', 'p'],
+    [' ', '.', ',', '?'],
+    ['q', 'r', 's', 't'],
+    ['u', 'v', 'w', 'x'],
+    ['y', 'z', '-', '\''],
+    ['!', '@', ':', '/'],
+];
+
+/// Uppercase letters; punctuation matches [`LOWER`].
+pub const UPPER: Layout = [
+    ['A', 'B', 'C', 'D'],
+    ['E', 'F', 'G', 'H'],
+    ['I', 'J', 'K', 'L'],
+    ['M', 'N', 'O', 'P'],
+    [' ', '.', ',', '?'],
+    ['Q', 'R', 'S', 'T'],
+    ['U', 'V', 'W', 'X'],
+    ['Y', 'Z', '-', '\''],
+    ['!', '@', ':', '/'],
+];
+
+/// Digits and symbols.
+pub const SYMBOLS: Layout = [
+    ['1', '2', '3', '4'],
+    ['5', '6', '7', '8'],
+    ['9', '0', '+', '='],
+    ['(', ')', '[', ']'],
+    [' ', '.', ',', '?'],
+    ['<', '>', '{', '}'],
+    ['#', '$', '%', '&'],
+    ['*', '^', '_', '~'],
+    [';', '"', '\\', '|'],
+];
+
+impl Layer {
+    /// The character layout for this layer.
+    pub fn layout(self) -> &'static Layout {
+        match self {
+            Layer::Lower => &LOWER,
+            Layer::Upper => &UPPER,
+            Layer::Symbols => &SYMBOLS,
+        }
+    }
+}
+
+/// Stick deflection (as a fraction of full travel) needed to leave the
+/// center column/row.
+const STICK_THRESHOLD: f32 = 0.5;
+
+/// Map a normalized stick position to a cell index (0..9, row-major).
+pub fn cell_for_stick(x: f32, y: f32) -> usize {
+    let axis = |v: f32| {
+        if v < -STICK_THRESHOLD {
+            0
+        } else if v > STICK_THRESHOLD {
+            2
+        } else {
+            1
+        }
+    };
+    axis(y) * 3 + axis(x)
+}
+
+// ── Input state machine ─────────────────────────────────────────────
+
+/// Result of feeding one frame of input to the keyboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyEvent {
+    /// A character was appended to the text buffer.
+    Char(char),
+    /// The last character was removed from the text buffer.
+    Backspace,
+    /// The user pressed START to submit the text.
+    Enter,
+}
+
+/// One frame of controller input, decoupled from [`Controller`] so the
+/// state machine can be driven from recorded or synthetic input.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct KeyInput {
+    /// Buttons that went down this frame.
+    pub pressed: CtrlButtons,
+    /// Analog X in -1.0..=1.0.
+    pub stick_x: f32,
+    /// Analog Y in -1.0..=1.0 (positive is down).
+    pub stick_y: f32,
+}
+
+impl KeyInput {
+    /// Build the input for this frame from a [`Controller`].
+    pub fn from_controller(ctrl: &Controller) -> Self {
+        let now = ctrl.raw().buttons;
+        let before = ctrl.raw_previous().buttons;
+        Self {
+            pressed: now & !before,
+            stick_x: ctrl.analog_x_f32(0.0),
+            stick_y: ctrl.analog_y_f32(0.0),
+        }
+    }
+}
+
+/// Face buttons in [`Cell`] order.
+const FACE_BUTTONS: [CtrlButtons; 4] = [
+    CtrlButtons::TRIANGLE,
+    CtrlButtons::SQUARE,
+    CtrlButtons::CIRCLE,
+    CtrlButtons::CROSS,
+];
+
+/// Danzeff-style software keyboard with its own text buffer.
+pub struct InlineKeyboard {
+    text: String,
+    max_len: usize,
+    layer: Layer,
+    cell: usize,
+}
+
+impl InlineKeyboard {
+    /// Create a keyboard whose buffer holds at most `max_len` characters.
+    pub fn new(max_len: usize) -> Self {
+        Self {
+            text: String::new(),
+            max_len,
+            layer: Layer::Lower,
+            cell: 4,
+        }
+    }
+
+    /// Read this frame's input from `ctrl` and apply it.
+    ///
+    /// Call once per frame after [`Controller::update`].
+    pub fn update(&mut self, ctrl: &Controller) -> Option<KeyEvent> {
+        self.handle(KeyInput::from_controller(ctrl))
+    }
+
+    /// Apply one frame of input.
+    ///
+    /// At most one event is produced per frame; when several buttons go
+    /// down together, START wins over backspace, which wins over typing.
+    pub fn handle(&mut self, input: KeyInput) -> Option<KeyEvent> {
+        self.cell = cell_for_stick(input.stick_x, input.stick_y);
+
+        if input.pressed.contains(CtrlButtons::RTRIGGER) {
+            self.layer = match self.layer {
+                Layer::Lower => Layer::Upper,
+                Layer::Upper | Layer::Symbols => Layer::Lower,
+            };
+        }
+        if input.pressed.contains(CtrlButtons::SELECT) {
+            self.layer = match self.layer {
+                Layer::Symbols => Layer::Lower,
+                Layer::Lower | Layer::Upper => Layer::Symbols,
+            };
+        }
+
+        if input.pressed.contains(CtrlButtons::START) {
+            return Some(KeyEvent::Enter);
+        }
+        if input.pressed.contains(CtrlButtons::LTRIGGER) {
+            return self.text.pop().map(|_| KeyEvent::Backspace);
+        }
+
+        let slot = FACE_BUTTONS
+            .iter()
+            .position(|&b| input.pressed.contains(b))?;
+        let c = self.layer.layout()[self.cell][slot];
+        if self.text.chars().count() >= self.max_len {
+            return None;
+        }
+        self.text.push(c);
+        Some(KeyEvent::Char(c))
+    }
+
+    /// The text entered so far.
+    pub fn current_text(&self) -> &str {
+        &self.text
+    }
+
+    /// Replace the buffer contents, truncated to the maximum length.
+    pub fn set_text(&mut self, text: &str) {
+        self.text.clear();
+        self.text.extend(text.chars().take(self.max_len));
+    }
+
+    /// Empty the text buffer.
+    pub fn clear(&mut self) {
+        self.text.clear();
+    }
+
+    /// Take the text out of the keyboard, leaving the buffer empty.
+    pub fn take_text(&mut self) -> String {
+        core::mem::take(&mut self.text)
+    }
+
+    /// Maximum number of characters the buffer accepts.
+    pub fn max_len(&self) -> usize {
+        self.max_len
+    }
+
+    /// The currently displayed layer.
+    pub fn layer(&self) -> Layer {
+        self.layer
+    }
+
+    /// Index (0..9, row-major) of the cell selected by the stick.
+    pub fn selected_cell(&self) -> usize {
+        self.cell
+    }
+
+    // ── Rendering ───────────────────────────────────────────────────
+
+    /// Width and height of the drawn keyboard in pixels.
+    pub const SIZE: f32 = CELL_SIZE * 3.0;
+
+    /// Draw the keyboard with its top-left corner at `(x, y)`.
+    ///
+    /// Cell backgrounds are drawn immediately; glyphs are queued on
+    /// `renderer` and appear when the caller flushes it, so they end up
+    /// on top of the backgrounds.
+    ///
+    /// # Safety
+    ///
+    /// Must be called within an active GU display list, after
+    /// [`crate::gu_ext::setup_2d`].
+    pub unsafe fn draw(&self, renderer: &mut FontRenderer<'_>, x: f32, y: f32) {
+        let layout = self.layer.layout();
+        let half_line = renderer.line_height() * 0.5;
+
+        for (i, cell) in layout.iter().enumerate() {
+            let cx = x + (i % 3) as f32 * CELL_SIZE;
+            let cy = y + (i / 3) as f32 * CELL_SIZE;
+            let (bg, fg) = if i == self.cell {
+                (SELECTED_BG, SELECTED_FG)
+            } else {
+                (CELL_BG, CELL_FG)
+            };
+            unsafe {
+                crate::gu_ext::draw_rect_filled(cx, cy, CELL_SIZE - 1.0, CELL_SIZE - 1.0, bg);
+            }
+
+            let mid = CELL_SIZE * 0.5;
+            let offsets = [
+                (mid, CELL_SIZE * 0.2),
+                (CELL_SIZE * 0.2, mid),
+                (CELL_SIZE * 0.8, mid),
+                (mid, CELL_SIZE * 0.8),
+            ];
+            let mut buf = [0u8; 4];
+            for (&c, &(ox, oy)) in cell.iter().zip(offsets.iter()) {
+                // Show space as an underscore so the slot isn't blank.
+                let s = if c == ' ' {
+                    "_"
+                } else {
+                    c.encode_utf8(&mut buf)
+                };
+                let w = renderer.measure_text(s);
+                renderer.draw_text(cx + ox - w * 0.5, cy + oy - half_line, fg, s);
+            }
+        }
+    }
+}
+
+/// Side length of one cell in pixels.
+const CELL_SIZE: f32 = 36.0;
+const CELL_BG: u32 = 0xc0302020;
+const CELL_FG: u32 = 0xffc0c0c0;
+const SELECTED_BG: u32 = 0xe0a06020;
+const SELECTED_FG: u32 = 0xffffffff;
@@ -12,6 +12,11 @@
 //!     psp::dprintln!("Hello, {}!", text);
 //! }
 //! ```
+//!
+//! For text entry that stays inside the game's own frame loop, see the
+//! controller-driven [`inline::InlineKeyboard`].
+
+pub mod inline;
 
 use alloc::string::String;
 use alloc::vec::Vec;
@@ -0,0 +1,445 @@
+//! Asset bundles: many files packed into one archive.
+//!
+//! Opening hundreds of loose files on a Memory Stick is slow, mostly in
+//! directory lookups. A pak file holds them all behind one handle:
+//! [`PakReader`] loads the file table once, then serves each asset with a
+//! single seek and read. [`PakBuilder`] creates archives on the PSP, for
+//! content made at runtime or packed on first start. To pack assets at
+//! build time, use `cargo_psp::pak::PakBuilder` from a build script; it
+//! runs on the host and writes the same bytes.
+//!
+//! # Format
+//!
+//! All integers are little-endian.
+//!
+//! | Section | Contents |
+//! |---------|----------|
+//! | Header (16 bytes) | magic `b"PPAK"`, version `u32` (1), entry count `u32`, name table size `u32` |
+//! | Entry table (24 bytes each) | path hash `u64`, data offset `u32`, data length `u32`, name offset `u32`, name length `u16`, flags `u16` |
+//! | Name table | UTF-8 paths, not terminated |
+//! | Data | file contents |
+//!
+//! The path hash is [`fnv1a_64`] of the normalized path (see
+//! [`normalize_path`]), and entries are sorted by it so lookups are a
+//! binary search. Flag bit 0 marks a compressed entry; it is reserved,
+//! and [`PakReader`] rejects such entries with
+//! [`PakError::Compressed`].
+//!
+//! # Example
+//!
+//! ```ignore
+//! use psp::pak::{PakBuilder, PakReader};
+//!
+//! let mut builder = PakBuilder::new();
+//! builder.add("sprites/hero.png", &hero_png)?;
+//! builder.add("levels/1.bin", &level_1)?;
+//! psp::io::write_bytes("ms0:/PSP/GAME/MYGAME/assets.pak", &builder.finish()?)?;
+//!
+//! let mut pak = PakReader::open("ms0:/PSP/GAME/MYGAME/assets.pak")?;
+//! let hero = pak.read("sprites/hero.png")?;
+//! for entry in pak.entries() {
+//!     psp::dprintln!("{} ({} bytes)", entry.name, entry.size);
+//! }
+//! ```
+
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::hash::fnv1a_64;
+use crate::io::{File, IoError, ReadAt};
+use crate::sys::IoOpenFlags;
+
+const MAGIC: &[u8; 4] = b"PPAK";
+const VERSION: u32 = 1;
+const HEADER_SIZE: usize = 16;
+const ENTRY_SIZE: usize = 24;
+
+/// Entry flag marking compressed data (reserved).
+const FLAG_COMPRESSED: u16 = 1 << 0;
+
+/// Error from reading or building a pak archive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PakError {
+    /// Reading the archive failed.
+    Io(IoError),
+    /// The archive doesn't start with the pak magic.
+    BadMagic,
+    /// The archive was written by a newer format version.
+    UnsupportedVersion(u32),
+    /// The header or entry table is truncated or inconsistent.
+    Corrupt,
+    /// There isn't enough memory for the archive's tables.
+    OutOfMemory,
+    /// No entry has this path.
+    NotFound,
+    /// The entry is compressed, which this reader doesn't support.
+    Compressed,
+    /// The buffer passed to [`PakReader::read_into`] is smaller than the
+    /// entry.
+    BufferTooSmall {
+        /// Size of the entry in bytes.
+        needed: usize,
+    },
+    /// [`PakBuilder::add`] was given a path already in the archive.
+    DuplicatePath(String),
+    /// Two different paths have the same hash. Rename one of them.
+    HashCollision(String, String),
+    /// The archive would exceed 4 GiB, or a path 64 KiB.
+    TooLarge,
+}
+
+impl core::fmt::Display for PakError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "pak I/O error: {}", e),
+            Self::BadMagic => f.write_str("not a pak archive"),
+            Self::UnsupportedVersion(v) => write!(f, "unsupported pak version {}", v),
+            Self::Corrupt => f.write_str("corrupt pak archive"),
+            Self::OutOfMemory => f.write_str("out of memory for pak tables"),
+            Self::NotFound => f.write_str("no such entry in pak archive"),
+            Self::Compressed => f.write_str("compressed pak entries are not supported"),
+            Self::BufferTooSmall { needed } => {
+                write!(f, "buffer too small for pak entry ({} bytes)", needed)
+            },
+            Self::DuplicatePath(path) => write!(f, "duplicate pak path {:?}", path),
+            Self::HashCollision(a, b) => {
+                write!(f, "pak paths {:?} and {:?} have the same hash", a, b)
+            },
+            Self::TooLarge => f.write_str("pak archive too large"),
+        }
+    }
+}
+
+impl From<IoError> for PakError {
+    fn from(e: IoError) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// Normalize an asset path as stored in and looked up from an archive:
+/// backslashes become forward slashes and leading slashes are removed.
+/// Paths are otherwise case-sensitive.
+pub fn normalize_path(path: &str) -> Cow<'_, str> {
+    let trimmed = path.trim_start_matches(['/', '\\']);
+    if trimmed.contains('\\') {
+        Cow::Owned(trimmed.replace('\\', "/"))
+    } else {
+        Cow::Borrowed(trimmed)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    hash: u64,
+    offset: u32,
+    size: u32,
+    name_offset: u32,
+    name_len: u16,
+    flags: u16,
+}
+
+/// An entry listed by [`PakReader::entries`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PakEntry<'a> {
+    /// Normalized path of the entry.
+    pub name: &'a str,
+    /// Size of the entry's data in bytes.
+    pub size: u32,
+}
+
+/// Reads entries from a pak archive.
+///
+/// [`open`](PakReader::open) reads the header, entry table and name table
+/// up front; after that every [`read`](Self::read) or
+/// [`read_into`](Self::read_into) is one positioned read.
+pub struct PakReader<R: ReadAt = File> {
+    inner: R,
+    entries: Vec<Entry>,
+    names: String,
+}
+
+impl PakReader<File> {
+    /// Open the archive at `path` (e.g. `"ms0:/data/assets.pak"`).
+    pub fn open(path: &str) -> Result<Self, PakError> {
+        Self::new(File::open(path, IoOpenFlags::RD_ONLY)?)
+    }
+}
+
+impl<R: ReadAt> PakReader<R> {
+    /// Read the tables of the archive in `inner`.
+    ///
+    /// The tables must fit in the archive: its length comes from
+    /// [`ReadAt::len_hint`], or if that's unknown, from checking that the
+    /// last byte of the tables can be read. So a corrupt header fails
+    /// with [`PakError::Corrupt`] rather than a huge allocation, and a
+    /// table that fits but can't be allocated fails with
+    /// [`PakError::OutOfMemory`]. Each entry's name must lie in the name
+    /// table, and its data after the tables and within the archive
+    /// length if that is known.
+    pub fn new(mut inner: R) -> Result<Self, PakError> {
+        let mut header = [0u8; HEADER_SIZE];
+        if inner.read_at(0, &mut header)? != HEADER_SIZE {
+            return Err(PakError::Corrupt);
+        }
+        if &header[0..4] != MAGIC {
+            return Err(PakError::BadMagic);
+        }
+        let version = le_u32(&header[4..]);
+        if version != VERSION {
+            return Err(PakError::UnsupportedVersion(version));
+        }
+        let count = le_u32(&header[8..]) as usize;
+        let names_size = le_u32(&header[12..]) as usize;
+
+        let table_size = count
+            .checked_mul(ENTRY_SIZE)
+            .and_then(|n| n.checked_add(names_size))
+            .ok_or(PakError::Corrupt)?;
+        let table_end = HEADER_SIZE as u64 + table_size as u64;
+        let len = inner.len_hint()?;
+        let fits = match len {
+            Some(len) => table_end <= len,
+            None => table_size == 0 || inner.read_at(table_end - 1, &mut [0u8])? == 1,
+        };
+        if !fits {
+            return Err(PakError::Corrupt);
+        }
+        let mut table = Vec::new();
+        table
+            .try_reserve_exact(table_size)
+            .map_err(|_| PakError::OutOfMemory)?;
+        table.resize(table_size, 0);
+        if inner.read_at(HEADER_SIZE as u64, &mut table)? != table_size {
+            return Err(PakError::Corrupt);
+        }
+
+        let names = String::from_utf8(table.split_off(count * ENTRY_SIZE))
+            .map_err(|_| PakError::Corrupt)?;
+        let mut entries = Vec::new();
+        entries
+            .try_reserve_exact(count)
+            .map_err(|_| PakError::OutOfMemory)?;
+        for raw in table.chunks_exact(ENTRY_SIZE) {
+            let entry = Entry {
+                hash: le_u64(raw),
+                offset: le_u32(&raw[8..]),
+                size: le_u32(&raw[12..]),
+                name_offset: le_u32(&raw[16..]),
+                name_len: le_u16(&raw[20..]),
+                flags: le_u16(&raw[22..]),
+            };
+            let name_start = entry.name_offset as usize;
+            let name_end = name_start.checked_add(entry.name_len as usize);
+            if name_end
+                .and_then(|end| names.get(name_start..end))
+                .is_none()
+            {
+                return Err(PakError::Corrupt);
+            }
+            // Data lies after the tables and, if the length is known,
+            // before the end.
+            let data_end = entry.offset.checked_add(entry.size);
+            let in_bounds = data_end.is_some_and(|end| {
+                entry.offset as u64 >= table_end && len.is_none_or(|len| end as u64 <= len)
+            });
+            if !in_bounds {
+                return Err(PakError::Corrupt);
+            }
+            entries.push(entry);
+        }
+        if entries.windows(2).any(|w| w[0].hash >= w[1].hash) {
+            return Err(PakError::Corrupt);
+        }
+
+        Ok(Self {
+            inner,
+            entries,
+            names,
+        })
+    }
+
+    /// Number of entries in the archive.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the archive has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Whether the archive has an entry at `path`.
+    pub fn exists(&self, path: &str) -> bool {
+        self.find(path).is_some()
+    }
+
+    /// Size in bytes of the entry at `path`.
+    pub fn size(&self, path: &str) -> Option<u32> {
+        self.find(path).map(|entry| entry.size)
+    }
+
+    /// Read the whole entry at `path`.
+    pub fn read(&mut self, path: &str) -> Result<Vec<u8>, PakError> {
+        let entry = *self.find_readable(path)?;
+        let mut data = vec![0u8; entry.size as usize];
+        self.read_entry(&entry, &mut data)?;
+        Ok(data)
+    }
+
+    /// Read the entry at `path` into the start of `buf`, returning its
+    /// size.
+    pub fn read_into(&mut self, path: &str, buf: &mut [u8]) -> Result<usize, PakError> {
+        let entry = *self.find_readable(path)?;
+        let size = entry.size as usize;
+        if buf.len() < size {
+            return Err(PakError::BufferTooSmall { needed: size });
+        }
+        self.read_entry(&entry, &mut buf[..size])?;
+        Ok(size)
+    }
+
+    /// Iterate over the entries, in hash order.
+    pub fn entries(&self) -> impl Iterator<Item = PakEntry<'_>> + '_ {
+        self.entries.iter().map(|entry| PakEntry {
+            name: self.name(entry),
+            size: entry.size,
+        })
+    }
+
+    /// Give back the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn name(&self, entry: &Entry) -> &str {
+        let start = entry.name_offset as usize;
+        &self.names[start..start + entry.name_len as usize]
+    }
+
+    fn find(&self, path: &str) -> Option<&Entry> {
+        let path = normalize_path(path);
+        let hash = fnv1a_64(path.as_bytes());
+        let index = self.entries.binary_search_by_key(&hash, |e| e.hash).ok()?;
+        let entry = &self.entries[index];
+        // A path that isn't in the archive can still share a hash with
+        // one that is.
+        (self.name(entry) == path).then_some(entry)
+    }
+
+    fn find_readable(&self, path: &str) -> Result<&Entry, PakError> {
+        let entry = self.find(path).ok_or(PakError::NotFound)?;
+        if entry.flags & FLAG_COMPRESSED != 0 {
+            return Err(PakError::Compressed);
+        }
+        Ok(entry)
+    }
+
+    fn read_entry(&mut self, entry: &Entry, buf: &mut [u8]) -> Result<(), PakError> {
+        if self.inner.read_at(entry.offset as u64, buf)? != buf.len() {
+            return Err(PakError::Corrupt);
+        }
+        Ok(())
+    }
+}
+
+/// Builds a pak archive in memory, to be written out with
+/// [`io::write_bytes`](crate::io::write_bytes) or a [`File`].
+///
+/// Paths are normalized with [`normalize_path`]. Adding a path twice, or
+/// two paths whose hashes collide, fails right away so the problem shows
+/// up when the archive is built rather than as a wrong asset at runtime.
+#[derive(Default)]
+pub struct PakBuilder {
+    files: Vec<(u64, String, Vec<u8>)>,
+}
+
+impl PakBuilder {
+    /// Create an empty builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `data` at `path`.
+    pub fn add(&mut self, path: &str, data: &[u8]) -> Result<(), PakError> {
+        let path = normalize_path(path).into_owned();
+        if path.len() > u16::MAX as usize {
+            return Err(PakError::TooLarge);
+        }
+        let hash = fnv1a_64(path.as_bytes());
+        match self.files.binary_search_by_key(&hash, |(h, _, _)| *h) {
+            Ok(index) => {
+                let existing = &self.files[index].1;
+                Err(if *existing == path {
+                    PakError::DuplicatePath(path)
+                } else {
+                    PakError::HashCollision(existing.clone(), path)
+                })
+            },
+            Err(index) => {
+                self.files.insert(index, (hash, path, data.to_vec()));
+                Ok(())
+            },
+        }
+    }
+
+    /// Number of files added so far.
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Whether no files have been added.
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Serialize the archive.
+    pub fn finish(self) -> Result<Vec<u8>, PakError> {
+        let names_size: usize = self.files.iter().map(|(_, path, _)| path.len()).sum();
+        let data_size: usize = self.files.iter().map(|(_, _, data)| data.len()).sum();
+        let data_start = HEADER_SIZE + self.files.len() * ENTRY_SIZE + names_size;
+        let total = data_start + data_size;
+        if u32::try_from(total).is_err() {
+            return Err(PakError::TooLarge);
+        }
+
+        let mut out = Vec::with_capacity(total);
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&VERSION.to_le_bytes());
+        out.extend_from_slice(&(self.files.len() as u32).to_le_bytes());
+        out.extend_from_slice(&(names_size as u32).to_le_bytes());
+
+        let mut name_offset = 0;
+        let mut data_offset = data_start;
+        for (hash, path, data) in &self.files {
+            out.extend_from_slice(&hash.to_le_bytes());
+            out.extend_from_slice(&(data_offset as u32).to_le_bytes());
+            out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            out.extend_from_slice(&(name_offset as u32).to_le_bytes());
+            out.extend_from_slice(&(path.len() as u16).to_le_bytes());
+            out.extend_from_slice(&0u16.to_le_bytes());
+            name_offset += path.len();
+            data_offset += data.len();
+        }
+        for (_, path, _) in &self.files {
+            out.extend_from_slice(path.as_bytes());
+        }
+        for (_, _, data) in &self.files {
+            out.extend_from_slice(data);
+        }
+        Ok(out)
+    }
+}
+
+fn le_u16(b: &[u8]) -> u16 {
+    u16::from_le_bytes([b[0], b[1]])
+}
+
+fn le_u32(b: &[u8]) -> u32 {
+    u32::from_le_bytes([b[0], b[1], b[2], b[3]])
+}
+
+fn le_u64(b: &[u8]) -> u64 {
+    u64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]])
+}
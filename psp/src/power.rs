@@ -1,8 +1,18 @@
 //! Power and clock management for the PSP.
 //!
 //! Provides clock speed control, battery monitoring, AC power detection,
-//! power event callbacks, and idle-timer control. Wraps `scePower*`
-//! syscalls into safe, ergonomic functions.
+//! power event callbacks, resume tracking, and idle-timer control. Wraps
+//! `scePower*` syscalls into safe, ergonomic functions.
+//!
+//! Code that needs a minimum CPU speed, like a network download, holds a
+//! [`ClockRequest`] so another part of the program lowering the clock
+//! with [`set_clock`] can't starve it. [`on_clock_change`] reports clock
+//! changes made by anyone, including the host game of a plugin.
+//! [`on_low_battery`] gives a game the chance to autosave before the
+//! battery runs out.
+
+use crate::sync::SpinMutex;
+use core::sync::atomic::{AtomicU32, Ordering};
 
 /// CPU and bus clock frequencies in MHz.
 #[derive(Debug, Clone, Copy)]
@@ -57,14 +67,13 @@ pub fn get_clock() -> ClockFrequency {
 /// Set the CPU and bus clock frequencies.
 ///
 /// `cpu_mhz`: 1-333, `bus_mhz`: 1-166.
-/// The PLL frequency is set equal to `cpu_mhz`.
+/// The PLL frequency is set equal to `cpu_mhz`. Like
+/// [`set_clock_frequency`], the CPU runs at least as fast as the highest
+/// active [`ClockRequest`].
 ///
 /// Returns the new clock frequencies on success.
 pub fn set_clock(cpu_mhz: i32, bus_mhz: i32) -> Result<ClockFrequency, PowerError> {
-    let ret = unsafe { crate::sys::scePowerSetClockFrequency(cpu_mhz, cpu_mhz, bus_mhz) };
-    if ret < 0 {
-        return Err(PowerError(ret));
-    }
+    set_clock_frequency(cpu_mhz, bus_mhz, cpu_mhz)?;
     Ok(get_clock())
 }
 
@@ -72,13 +81,15 @@ pub fn set_clock(cpu_mhz: i32, bus_mhz: i32) -> Result<ClockFrequency, PowerErro
 ///
 /// `cpu`: 1-333, `bus`: 1-166, `gpu` (PLL): 19-333.
 /// Constraints: `cpu <= gpu`, `bus*2 <= gpu`.
+///
+/// If an active [`ClockRequest`] asks for more than `cpu`, the CPU runs
+/// at the request's frequency instead, with the bus at half of it and
+/// the PLL raised to match. The requested clocks are remembered and
+/// applied in full once the request is dropped.
 pub fn set_clock_frequency(cpu: i32, bus: i32, gpu: i32) -> Result<(), PowerError> {
-    let ret = unsafe { crate::sys::scePowerSetClockFrequency(gpu, cpu, bus) };
-    if ret < 0 {
-        Err(PowerError(ret))
-    } else {
-        Ok(())
-    }
+    apply_clocks((cpu, bus, gpu))?;
+    *REQUESTED.lock() = Some((cpu, bus, gpu));
+    Ok(())
 }
 
 /// Query battery status in a single call.
@@ -99,6 +110,266 @@ pub fn is_ac_power() -> bool {
     (unsafe { crate::sys::scePowerIsPowerOnline() }) == 1
 }
 
+// ── Clock requests ───────────────────────────────────────────────────
+
+/// Highest CPU frequency in MHz.
+const MAX_CPU_MHZ: usize = 333;
+
+/// Highest bus frequency in MHz.
+const MAX_BUS_MHZ: i32 = 166;
+
+/// Number of live [`ClockRequest`]s for each CPU frequency in MHz.
+static FLOORS: SpinMutex<[u16; MAX_CPU_MHZ + 1]> = SpinMutex::new([0; MAX_CPU_MHZ + 1]);
+
+/// The `(cpu, bus, pll)` clocks last passed to [`set_clock_frequency`],
+/// before any [`ClockRequest`] raises them.
+static REQUESTED: SpinMutex<Option<(i32, i32, i32)>> = SpinMutex::new(None);
+
+/// A floor on the CPU clock, held until dropped.
+///
+/// While any request is alive, [`set_clock`] and [`set_clock_frequency`]
+/// run the CPU at no less than the highest requested frequency. Creating
+/// and dropping a request applies the change right away with
+/// [`apply_policies`].
+///
+/// # Example
+///
+/// ```ignore
+/// use psp::power::{self, ClockRequest};
+///
+/// let _fast = ClockRequest::minimum(222);
+/// // A music player elsewhere saving battery stays at 222 MHz:
+/// power::set_clock(111, 55)?;
+/// download_update()?;
+/// // Dropping `_fast` goes down to 111 MHz.
+/// ```
+#[must_use = "the floor is released when the request is dropped"]
+pub struct ClockRequest {
+    mhz: i32,
+}
+
+impl ClockRequest {
+    /// Keep the CPU at `mhz` (clamped to 1-333) or faster.
+    ///
+    /// The clock is raised immediately if it's slower. Errors from doing
+    /// so are dropped; call [`apply_policies`] to see them.
+    pub fn minimum(mhz: i32) -> Self {
+        let mhz = mhz.clamp(1, MAX_CPU_MHZ as i32);
+        let mut floors = FLOORS.lock();
+        floors[mhz as usize] = floors[mhz as usize].saturating_add(1);
+        drop(floors);
+        let _ = apply_policies();
+        Self { mhz }
+    }
+
+    /// The floor in MHz.
+    pub fn mhz(&self) -> i32 {
+        self.mhz
+    }
+}
+
+impl Drop for ClockRequest {
+    fn drop(&mut self) {
+        let mut floors = FLOORS.lock();
+        floors[self.mhz as usize] = floors[self.mhz as usize].saturating_sub(1);
+        drop(floors);
+        let _ = apply_policies();
+    }
+}
+
+/// The highest CPU frequency held by a live [`ClockRequest`], if any.
+pub fn clock_floor() -> Option<i32> {
+    FLOORS
+        .lock()
+        .iter()
+        .rposition(|&count| count > 0)
+        .map(|mhz| mhz as i32)
+}
+
+/// Set the clocks last asked for with [`set_clock_frequency`], raised to
+/// the current [`clock_floor`].
+///
+/// If the clocks were never set through this module, the ones running
+/// now are taken as the request.
+pub fn apply_policies() -> Result<(), PowerError> {
+    let requested = *REQUESTED.lock().get_or_insert_with(|| unsafe {
+        (
+            crate::sys::scePowerGetCpuClockFrequencyInt(),
+            crate::sys::scePowerGetBusClockFrequencyInt(),
+            crate::sys::scePowerGetPllClockFrequencyInt(),
+        )
+    });
+    apply_clocks(requested)
+}
+
+/// Set `(cpu, bus, pll)` raised to the floor, skipping the syscall if the
+/// clocks already match.
+fn apply_clocks((cpu, bus, pll): (i32, i32, i32)) -> Result<(), PowerError> {
+    let (cpu, bus, pll) = match clock_floor() {
+        Some(floor) if floor > cpu => (floor, bus.max(floor / 2).min(MAX_BUS_MHZ), pll.max(floor)),
+        _ => (cpu, bus, pll),
+    };
+    let current = unsafe {
+        (
+            crate::sys::scePowerGetCpuClockFrequencyInt(),
+            crate::sys::scePowerGetBusClockFrequencyInt(),
+            crate::sys::scePowerGetPllClockFrequencyInt(),
+        )
+    };
+    if current == (cpu, bus, pll) {
+        return Ok(());
+    }
+    let ret = unsafe { crate::sys::scePowerSetClockFrequency(pll, cpu, bus) };
+    if ret < 0 {
+        Err(PowerError(ret))
+    } else {
+        Ok(())
+    }
+}
+
+// ── Clock change notifications ───────────────────────────────────────
+
+/// How often the thread started by [`on_clock_change`] reads the clock.
+pub const CLOCK_POLL_MS: u32 = 100;
+
+/// A CPU clock change reported by [`on_clock_change`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockChange {
+    /// CPU frequency before the change, in MHz.
+    pub old_mhz: i32,
+    /// CPU frequency after the change, in MHz.
+    pub new_mhz: i32,
+}
+
+/// Call `handler` whenever the CPU clock changes.
+///
+/// The firmware's power callback doesn't report clock changes, so a
+/// low-priority thread polls `scePowerGetCpuClockFrequencyInt` every
+/// [`CLOCK_POLL_MS`] and calls `handler` on that thread. A change and
+/// its reversal between two polls go unnoticed.
+///
+/// Returns a handle that stops the thread on drop.
+#[cfg(not(feature = "stub-only"))]
+pub fn on_clock_change<F>(mut handler: F) -> Result<PowerWatchHandle, PowerError>
+where
+    F: FnMut(ClockChange) + Send + 'static,
+{
+    let mut last = unsafe { crate::sys::scePowerGetCpuClockFrequencyInt() };
+    spawn_watcher(b"clock_watch\0", CLOCK_POLL_MS, move || {
+        let now = unsafe { crate::sys::scePowerGetCpuClockFrequencyInt() };
+        if now != last {
+            handler(ClockChange {
+                old_mhz: last,
+                new_mhz: now,
+            });
+            last = now;
+        }
+        true
+    })
+}
+
+// ── Low battery guard ────────────────────────────────────────────────
+
+/// How often the thread started by [`on_low_battery`] reads the battery
+/// level.
+pub const BATTERY_POLL_MS: u32 = 5000;
+
+/// Call `f` once when the battery charge drops below `threshold_pct`
+/// percent, so the game can autosave before the PSP shuts down.
+///
+/// A low-priority thread polls `scePowerGetBatteryLifePercent` every
+/// [`BATTERY_POLL_MS`] and calls `f` on that thread, then exits. If the
+/// charge is already below the threshold, `f` is called at the first
+/// poll. Readings while no battery is present are ignored.
+///
+/// Returns a handle that stops the thread on drop.
+#[cfg(not(feature = "stub-only"))]
+pub fn on_low_battery(threshold_pct: u8, f: fn()) -> Result<PowerWatchHandle, PowerError> {
+    spawn_watcher(b"battery_watch\0", BATTERY_POLL_MS, move || {
+        let percent = unsafe { crate::sys::scePowerGetBatteryLifePercent() };
+        if (0..threshold_pct as i32).contains(&percent) {
+            f();
+            return false;
+        }
+        true
+    })
+}
+
+/// Estimated battery time left, in minutes.
+///
+/// `None` while running on AC power or without a battery, when the
+/// firmware doesn't estimate a time.
+pub fn battery_minutes_remaining() -> Option<u32> {
+    let minutes = unsafe { crate::sys::scePowerGetBatteryLifeTime() };
+    u32::try_from(minutes).ok()
+}
+
+/// How long the watcher thread sleeps between checks for a dropped
+/// [`PowerWatchHandle`].
+#[cfg(not(feature = "stub-only"))]
+const WATCH_SLICE_MS: u32 = 100;
+
+/// Spawn a low-priority thread calling `tick` every `period_ms` until it
+/// returns `false` or the handle is dropped.
+#[cfg(not(feature = "stub-only"))]
+fn spawn_watcher(
+    name: &'static [u8],
+    period_ms: u32,
+    mut tick: impl FnMut() -> bool + Send + 'static,
+) -> Result<PowerWatchHandle, PowerError> {
+    use alloc::sync::Arc;
+    use core::sync::atomic::AtomicBool;
+
+    let quit = Arc::new(AtomicBool::new(false));
+    let worker_quit = quit.clone();
+    let slice = period_ms.min(WATCH_SLICE_MS);
+    let worker = crate::thread::ThreadBuilder::new(name)
+        .priority(crate::DEFAULT_THREAD_PRIORITY + 16)
+        .spawn(move || {
+            // Sleep in slices so dropping the handle doesn't wait a whole
+            // period.
+            let mut waited = 0;
+            while !worker_quit.load(Ordering::Acquire) {
+                crate::thread::sleep_ms(slice);
+                waited += slice;
+                if waited >= period_ms {
+                    waited = 0;
+                    if !tick() {
+                        break;
+                    }
+                }
+            }
+            0
+        })
+        .map_err(|e| PowerError(e.0))?;
+    Ok(PowerWatchHandle {
+        quit,
+        worker: Some(worker),
+    })
+}
+
+/// RAII handle for an [`on_clock_change`] or [`on_low_battery`] watcher.
+///
+/// Stops the watcher thread on drop and waits for it to exit. The thread
+/// notices within 100 ms, but if the handler (the `on_clock_change`
+/// callback or the `on_low_battery` hook) is running at the time, the drop
+/// also waits for it to return, however long that takes.
+#[cfg(not(feature = "stub-only"))]
+pub struct PowerWatchHandle {
+    quit: alloc::sync::Arc<core::sync::atomic::AtomicBool>,
+    worker: Option<crate::thread::JoinHandle>,
+}
+
+#[cfg(not(feature = "stub-only"))]
+impl Drop for PowerWatchHandle {
+    fn drop(&mut self) {
+        self.quit.store(true, Ordering::Release);
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
 // ── Power event callbacks ────────────────────────────────────────────
 
 /// Register a power event callback.
@@ -117,21 +388,44 @@ pub fn on_power_event(
     handler: unsafe extern "C" fn(i32, i32, *mut core::ffi::c_void) -> i32,
 ) -> Result<PowerCallbackHandle, PowerError> {
     use core::ffi::c_void;
+    use core::sync::atomic::{AtomicBool, AtomicI32};
 
-    let cbid = unsafe {
-        crate::sys::sceKernelCreateCallback(b"power_cb\0".as_ptr(), handler, core::ptr::null_mut())
-    };
-    if cbid.0 < 0 {
-        return Err(PowerError(cbid.0));
+    /// Handed to the callback thread, which fills in the results.
+    struct Setup {
+        handler: unsafe extern "C" fn(i32, i32, *mut c_void) -> i32,
+        cb_id: AtomicI32,
+        slot: AtomicI32,
+        done: AtomicBool,
     }
 
-    let slot = unsafe { crate::sys::scePowerRegisterCallback(-1, cbid) };
-    if slot < 0 {
-        return Err(PowerError(slot));
-    }
+    // Callbacks only run on the thread that created them, so the callback
+    // must be created by the thread that sleeps waiting for it.
+    unsafe extern "C" fn callback_thread(_args: usize, argp: *mut c_void) -> i32 {
+        // SAFETY: `argp` holds a pointer to the `Setup`, which stays alive
+        // until `done` is set.
+        let setup = unsafe { &**(argp as *const *const Setup) };
+        let cbid = unsafe {
+            crate::sys::sceKernelCreateCallback(
+                b"power_cb\0".as_ptr(),
+                setup.handler,
+                core::ptr::null_mut(),
+            )
+        };
+        let slot = if cbid.0 < 0 {
+            cbid.0
+        } else {
+            unsafe { crate::sys::scePowerRegisterCallback(-1, cbid) }
+        };
+        setup.cb_id.store(cbid.0, Ordering::Relaxed);
+        setup.slot.store(slot, Ordering::Relaxed);
+        setup.done.store(true, Ordering::Release);
 
-    // Spawn a thread that sleeps with CB processing enabled.
-    unsafe extern "C" fn sleep_thread(_args: usize, _argp: *mut c_void) -> i32 {
+        if slot < 0 {
+            if cbid.0 >= 0 {
+                unsafe { crate::sys::sceKernelDeleteCallback(cbid) };
+            }
+            return 0;
+        }
         unsafe { crate::sys::sceKernelSleepThreadCB() };
         0
     }
@@ -139,7 +433,7 @@ pub fn on_power_event(
     let thid = unsafe {
         crate::sys::sceKernelCreateThread(
             b"power_cb_thread\0".as_ptr(),
-            sleep_thread,
+            callback_thread,
             crate::DEFAULT_THREAD_PRIORITY,
             4096,
             crate::sys::ThreadAttributes::empty(),
@@ -147,26 +441,44 @@ pub fn on_power_event(
         )
     };
     if thid.0 < 0 {
-        unsafe {
-            crate::sys::scePowerUnregisterCallback(slot);
-            crate::sys::sceKernelDeleteCallback(cbid);
-        }
         return Err(PowerError(thid.0));
     }
 
-    let ret = unsafe { crate::sys::sceKernelStartThread(thid, 0, core::ptr::null_mut()) };
+    let setup = Setup {
+        handler,
+        cb_id: AtomicI32::new(0),
+        slot: AtomicI32::new(0),
+        done: AtomicBool::new(false),
+    };
+    let mut setup_ptr: *const Setup = &setup;
+    let ret = unsafe {
+        crate::sys::sceKernelStartThread(
+            thid,
+            core::mem::size_of::<*const Setup>(),
+            &mut setup_ptr as *mut *const Setup as *mut c_void,
+        )
+    };
     if ret < 0 {
+        unsafe { crate::sys::sceKernelDeleteThread(thid) };
+        return Err(PowerError(ret));
+    }
+    while !setup.done.load(Ordering::Acquire) {
+        unsafe { crate::sys::sceKernelDelayThread(1000) };
+    }
+
+    let cb_id = crate::sys::SceUid(setup.cb_id.load(Ordering::Relaxed));
+    let slot = setup.slot.load(Ordering::Relaxed);
+    if slot < 0 {
         unsafe {
-            crate::sys::scePowerUnregisterCallback(slot);
+            crate::sys::sceKernelWaitThreadEnd(thid, core::ptr::null_mut());
             crate::sys::sceKernelDeleteThread(thid);
-            crate::sys::sceKernelDeleteCallback(cbid);
         }
-        return Err(PowerError(ret));
+        return Err(PowerError(slot));
     }
 
     Ok(PowerCallbackHandle {
         slot,
-        cb_id: cbid,
+        cb_id,
         thread_id: thid,
     })
 }
@@ -192,11 +504,74 @@ impl Drop for PowerCallbackHandle {
     }
 }
 
+// ── Resume tracking ──────────────────────────────────────────────────
+
+/// Number of resumes from suspend seen so far.
+static RESUME_COUNT: AtomicU32 = AtomicU32::new(0);
+
+/// Record that the system has resumed from suspend.
+///
+/// The display controller and GE lose their state across a suspend, so
+/// anything that configured them checks [`resume_count`] to find out it
+/// must redo that setup (see
+/// [`DoubleBuffer::needs_reinit`](crate::framebuffer::DoubleBuffer::needs_reinit)).
+///
+/// [`watch_resume`] calls this automatically. Call it yourself from a
+/// custom [`on_power_event`] handler when `PowerInfo::RESUME_COMPLETE` is
+/// set, instead of installing both.
+pub fn notify_resumed() {
+    RESUME_COUNT.fetch_add(1, Ordering::Release);
+}
+
+/// Number of times [`notify_resumed`] has been called.
+///
+/// Compare against a value saved earlier to detect a resume in between.
+pub fn resume_count() -> u32 {
+    RESUME_COUNT.load(Ordering::Acquire)
+}
+
+/// Register a power callback that calls [`notify_resumed`] whenever the
+/// system finishes resuming from suspend.
+///
+/// Keep the returned handle alive for as long as resumes should be
+/// tracked.
+#[cfg(not(feature = "stub-only"))]
+pub fn watch_resume() -> Result<PowerCallbackHandle, PowerError> {
+    unsafe extern "C" fn handler(
+        _count: i32,
+        power_info: i32,
+        _common: *mut core::ffi::c_void,
+    ) -> i32 {
+        let info = crate::sys::PowerInfo::from_bits_truncate(power_info as u32);
+        if info.contains(crate::sys::PowerInfo::RESUME_COMPLETE) {
+            notify_resumed();
+        }
+        0
+    }
+
+    on_power_event(handler)
+}
+
+/// Reset the idle timers that dim the screen and put the PSP to sleep.
+///
+/// Nothing counts as activity during video playback or a cutscene
+/// without input, so call this at least every few seconds, e.g. once per
+/// decoded frame.
+pub fn request_idle_timer_reset() -> Result<(), PowerError> {
+    let ret = unsafe { crate::sys::scePowerTick(crate::sys::PowerTick::All) };
+    if ret < 0 {
+        Err(PowerError(ret))
+    } else {
+        Ok(())
+    }
+}
+
 /// Reset the idle timer to prevent the PSP from auto-sleeping.
 ///
-/// Call this once per frame in your main loop.
+/// Call this once per frame in your main loop. Same as
+/// [`request_idle_timer_reset`], ignoring the error.
 pub fn prevent_sleep() {
-    unsafe { crate::sys::scePowerTick(crate::sys::PowerTick::All) };
+    let _ = request_idle_timer_reset();
 }
 
 /// Reset the display idle timer to prevent the screen from turning off.
@@ -0,0 +1,215 @@
+//! Small, fast pseudo-random numbers.
+//!
+//! [`Rng`] is a xoshiro128++ generator: 128 bits of state, a handful of
+//! adds, shifts and rotates per number, and no multiplications or
+//! divisions on the hot path. The same seed always produces the same
+//! sequence, which keeps simulations such as
+//! [`crate::gu_ext::ParticleSystem`] reproducible and testable.
+//!
+//! Seeds can come from a constant (for replays and tests), the system
+//! timer ([`Rng::from_time`]), the RTC ([`Rng::from_rtc`]), or
+//! [`Rng::from_entropy`], which mixes both with the kernel's Mersenne
+//! Twister, the generator behind std's random number shim.
+//!
+//! For code that doesn't want to carry an `Rng` around, [`with_global`]
+//! and the free functions ([`random_u32`], [`random_f32`], [`random_range`])
+//! share one lazily seeded instance behind a [`SpinMutex`].
+//!
+//! Not suitable for anything security-related.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use psp::rand::{self, Rng};
+//!
+//! let mut rng = Rng::from_entropy();
+//! let roll = rng.range(1..7);
+//! let mut deck: [u8; 52] = core::array::from_fn(|i| i as u8);
+//! rng.shuffle(&mut deck);
+//!
+//! if rand::with_global(|rng| rng.chance(0.1)) {
+//!     // Critical hit.
+//! }
+//! ```
+
+use core::ops::Range;
+
+use crate::sync::SpinMutex;
+
+/// A seedable xoshiro128++ pseudo-random number generator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rng {
+    state: [u32; 4],
+}
+
+/// One step of SplitMix64, used to expand a seed into generator state.
+const fn splitmix64(x: &mut u64) -> u64 {
+    *x = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *x;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+impl Rng {
+    /// Create a generator from `seed`.
+    ///
+    /// Any value is accepted, including zero: the seed is expanded with
+    /// SplitMix64, so similar seeds still give unrelated sequences.
+    pub const fn new(seed: u64) -> Self {
+        let mut x = seed;
+        let a = splitmix64(&mut x);
+        let b = splitmix64(&mut x);
+        let mut state = [a as u32, (a >> 32) as u32, b as u32, (b >> 32) as u32];
+        // The all-zero state is a fixed point; SplitMix64 can't produce two
+        // consecutive zeros, but stay safe regardless.
+        if state[0] | state[1] | state[2] | state[3] == 0 {
+            state[0] = 1;
+        }
+        Self { state }
+    }
+
+    /// Create a generator seeded from the microsecond system timer.
+    pub fn from_time() -> Self {
+        // SAFETY: Reads the free-running system timer; no preconditions.
+        Self::new(unsafe { crate::sys::sceKernelGetSystemTimeWide() } as u64)
+    }
+
+    /// Create a generator seeded from the current RTC tick.
+    pub fn from_rtc() -> Self {
+        let mut tick = 0u64;
+        // SAFETY: `tick` is a valid out pointer.
+        unsafe {
+            crate::sys::sceRtcGetCurrentTick(&mut tick);
+        }
+        Self::new(tick)
+    }
+
+    /// Create a generator from the best entropy available without a
+    /// hardware RNG.
+    ///
+    /// Mixes the system timer and RTC tick through the kernel's
+    /// `sceKernelUtilsMt19937` generator, the same source std's random
+    /// shim draws from, so `no_std` and std builds seed alike.
+    pub fn from_entropy() -> Self {
+        let mut tick = 0u64;
+        let mut ctx = crate::sys::SceKernelUtilsMt19937Context {
+            count: 0,
+            state: [0; 624],
+        };
+        // SAFETY: `tick` and `ctx` are valid for the calls.
+        let (hi, lo) = unsafe {
+            crate::sys::sceRtcGetCurrentTick(&mut tick);
+            let time = crate::sys::sceKernelGetSystemTimeWide() as u64;
+            crate::sys::sceKernelUtilsMt19937Init(&mut ctx, (time ^ tick ^ (tick >> 32)) as u32);
+            (
+                crate::sys::sceKernelUtilsMt19937UInt(&mut ctx),
+                crate::sys::sceKernelUtilsMt19937UInt(&mut ctx),
+            )
+        };
+        Self::new(((hi as u64) << 32 | lo as u64) ^ tick)
+    }
+
+    /// The next 32 random bits.
+    ///
+    /// `Rng::new(1)` produces 2146930148, 2585199205, 3670091704, ...
+    pub fn next_u32(&mut self) -> u32 {
+        let s = &mut self.state;
+        let result = s[0].wrapping_add(s[3]).rotate_left(7).wrapping_add(s[0]);
+        let t = s[1] << 9;
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(11);
+        result
+    }
+
+    /// The next 64 random bits.
+    pub fn next_u64(&mut self) -> u64 {
+        (self.next_u32() as u64) << 32 | self.next_u32() as u64
+    }
+
+    /// A uniformly distributed float in `[0, 1)`.
+    pub fn next_f32(&mut self) -> f32 {
+        // 24 bits fill the f32 mantissa exactly, so every result is
+        // representable and 1.0 is never produced.
+        (self.next_u32() >> 8) as f32 * (1.0 / (1 << 24) as f32)
+    }
+
+    /// A uniformly distributed float in `[lo, hi)`.
+    ///
+    /// Returns exactly `lo` when `lo == hi`.
+    pub fn range_f32(&mut self, lo: f32, hi: f32) -> f32 {
+        lo + (hi - lo) * self.next_f32()
+    }
+
+    /// A uniformly distributed integer in `[0, n)`, or 0 if `n` is 0.
+    ///
+    /// Uses a multiply-shift reduction rather than `%`, which avoids a
+    /// division and most of the modulo bias.
+    pub fn below(&mut self, n: u32) -> u32 {
+        ((self.next_u32() as u64 * n as u64) >> 32) as u32
+    }
+
+    /// A uniformly distributed integer in `range`, or `range.start` if the
+    /// range is empty.
+    pub fn range(&mut self, range: Range<i32>) -> i32 {
+        if range.end <= range.start {
+            return range.start;
+        }
+        let span = range.end.wrapping_sub(range.start) as u32;
+        range.start.wrapping_add(self.below(span) as i32)
+    }
+
+    /// `true` with probability `p` (clamped to `[0, 1]`).
+    pub fn chance(&mut self, p: f32) -> bool {
+        self.next_f32() < p
+    }
+
+    /// A uniformly chosen element of `items`, or `None` if it is empty.
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        items.get(self.below(items.len() as u32) as usize)
+    }
+
+    /// Shuffle `items` in place (Fisher-Yates).
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.below(i as u32 + 1) as usize;
+            items.swap(i, j);
+        }
+    }
+}
+
+/// Shared generator, seeded from [`Rng::from_entropy`] on first use.
+static GLOBAL: SpinMutex<Option<Rng>> = SpinMutex::new(None);
+
+/// Run `f` with exclusive access to the global generator.
+///
+/// Keep `f` short: other threads using the global generator spin while it
+/// runs.
+pub fn with_global<R>(f: impl FnOnce(&mut Rng) -> R) -> R {
+    let mut global = GLOBAL.lock();
+    f(global.get_or_insert_with(Rng::from_entropy))
+}
+
+/// Reseed the global generator, e.g. for a reproducible replay.
+pub fn seed_global(seed: u64) {
+    *GLOBAL.lock() = Some(Rng::new(seed));
+}
+
+/// [`Rng::next_u32`] on the global generator.
+pub fn random_u32() -> u32 {
+    with_global(Rng::next_u32)
+}
+
+/// [`Rng::next_f32`] on the global generator.
+pub fn random_f32() -> f32 {
+    with_global(Rng::next_f32)
+}
+
+/// [`Rng::range`] on the global generator.
+pub fn random_range(range: Range<i32>) -> i32 {
+    with_global(|rng| rng.range(range))
+}
@@ -1,7 +1,8 @@
 //! Extended real-time clock operations for the PSP.
 //!
 //! Provides tick arithmetic, date validation, RFC 3339 formatting/parsing,
-//! and UTC/local time conversion. Builds on the basic types in [`crate::time`].
+//! UTC/local time conversion, and a clock correction offset (see
+//! [`corrected_now()`]). Builds on the basic types in [`crate::time`].
 //!
 //! # Example
 //!
@@ -14,6 +15,7 @@
 //! psp::dprintln!("{}-{:02}-{:02}", dt.year(), dt.month(), dt.day());
 //! ```
 
+use crate::sync::SpinMutex;
 use crate::sys;
 use crate::time::DateTime;
 
@@ -154,6 +156,42 @@ impl Tick {
     }
 }
 
+// ── Clock correction ────────────────────────────────────────────────
+
+/// Correction applied by [`corrected_now()`], in microseconds.
+static CLOCK_OFFSET: SpinMutex<i64> = SpinMutex::new(0);
+
+/// Set the correction, in microseconds, that [`corrected_now()`] adds to
+/// the system clock.
+///
+/// Usually set by `net::ntp::sync_offset()` rather than directly.
+pub fn set_clock_offset(offset_us: i64) {
+    *CLOCK_OFFSET.lock() = offset_us;
+}
+
+/// The current clock correction in microseconds (0 until set).
+pub fn clock_offset() -> i64 {
+    *CLOCK_OFFSET.lock()
+}
+
+/// The current UTC tick with the [`clock_offset()`] applied.
+///
+/// Unlike setting the system clock, this needs no kernel privileges and
+/// leaves the user's clock settings alone.
+pub fn corrected_now() -> Result<Tick, RtcError> {
+    let now = Tick::now()?;
+    Ok(Tick(now.0.saturating_add_signed(clock_offset())))
+}
+
+/// Set the system clock to `tick` (UTC).
+///
+/// Requires `feature = "kernel"`.
+#[cfg(feature = "kernel")]
+pub fn set_current_tick(tick: Tick) -> Result<(), RtcError> {
+    let ret = unsafe { sys::sceRtcSetCurrentTick(&tick.0) };
+    if ret < 0 { Err(RtcError(ret)) } else { Ok(()) }
+}
+
 /// Convert a [`DateTime`] to a [`Tick`].
 pub fn datetime_to_tick(dt: &DateTime) -> Result<Tick, RtcError> {
     let mut tick: u64 = 0;
@@ -239,3 +277,111 @@ pub fn check_valid(dt: &DateTime) -> Result<(), RtcError> {
     let ret = unsafe { sys::sceRtcCheckValid(dt.as_raw()) };
     if ret < 0 { Err(RtcError(ret)) } else { Ok(()) }
 }
+
+/// A pausable countdown on the monotonic system clock.
+///
+/// Unlike [`Tick`], the system clock can't be changed by the user or by
+/// [`set_clock_offset()`], so a countdown is unaffected by clock
+/// corrections. Time spent paused doesn't count; pausing an expired
+/// countdown keeps it expired.
+///
+/// # Example
+///
+/// ```ignore
+/// use psp::rtc::Countdown;
+///
+/// let mut round = Countdown::new(90 * 1_000_000);
+/// loop {
+///     if menu_open {
+///         round.pause();
+///     } else {
+///         round.resume();
+///     }
+///     if round.is_expired() {
+///         break;
+///     }
+///     draw_timer(round.remaining_us() / 1_000_000);
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Countdown {
+    duration_us: u64,
+    /// System time at which a running countdown expires.
+    deadline_us: u64,
+    /// Time left when paused, or `None` while running.
+    paused_remaining_us: Option<u64>,
+}
+
+impl Countdown {
+    /// Start a countdown of `duration_us` microseconds.
+    pub fn new(duration_us: u64) -> Self {
+        Self {
+            duration_us,
+            deadline_us: system_time_us().saturating_add(duration_us),
+            paused_remaining_us: None,
+        }
+    }
+
+    /// Microseconds left, or 0 once expired.
+    pub fn remaining_us(&self) -> u64 {
+        match self.paused_remaining_us {
+            Some(remaining) => remaining,
+            None => self.deadline_us.saturating_sub(system_time_us()),
+        }
+    }
+
+    /// Whether the countdown has reached zero.
+    pub fn is_expired(&self) -> bool {
+        self.remaining_us() == 0
+    }
+
+    /// Stop the clock. Does nothing if already paused.
+    pub fn pause(&mut self) {
+        if self.paused_remaining_us.is_none() {
+            self.paused_remaining_us = Some(self.remaining_us());
+        }
+    }
+
+    /// Restart the clock with the time that was left when paused. Does
+    /// nothing if running.
+    pub fn resume(&mut self) {
+        if let Some(remaining) = self.paused_remaining_us.take() {
+            self.deadline_us = system_time_us().saturating_add(remaining);
+        }
+    }
+
+    /// Whether the countdown is paused.
+    pub fn is_paused(&self) -> bool {
+        self.paused_remaining_us.is_some()
+    }
+
+    /// The full duration the countdown started with.
+    pub fn duration_us(&self) -> u64 {
+        self.duration_us
+    }
+
+    /// Add `us` microseconds to the time left, e.g. for a time bonus.
+    /// Extends an expired countdown from zero.
+    pub fn extend(&mut self, us: u64) {
+        match &mut self.paused_remaining_us {
+            Some(remaining) => *remaining = remaining.saturating_add(us),
+            None => {
+                let now = system_time_us();
+                self.deadline_us = self.deadline_us.max(now).saturating_add(us);
+            },
+        }
+    }
+
+    /// Start over from the full duration, keeping the paused state.
+    pub fn restart(&mut self) {
+        match &mut self.paused_remaining_us {
+            Some(remaining) => *remaining = self.duration_us,
+            None => self.deadline_us = system_time_us().saturating_add(self.duration_us),
+        }
+    }
+}
+
+/// Current system time in microseconds since boot.
+fn system_time_us() -> u64 {
+    unsafe { sys::sceKernelGetSystemTimeWide() as u64 }
+}
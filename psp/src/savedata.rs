@@ -20,10 +20,100 @@
 //!     .load(b"SAVE0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0", 1024)
 //!     .unwrap();
 //! ```
+//!
+//! `save()` and `load()` block until the operation finishes. To keep the
+//! game loop running (e.g. to draw a "Saving..." indicator), use
+//! [`Savedata::start_save`] / [`Savedata::start_load`] and poll the
+//! returned [`SaveOperation`] once per frame.
+//!
+//! # Encrypted saves
+//!
+//! With [`Savedata::secure_key`], the firmware encrypts `DATA.BIN` on save
+//! and decrypts it on load, so the save can't be edited on a PC. The same
+//! key must be supplied when loading; keep it constant for the lifetime
+//! of the game.
+//!
+//! ```ignore
+//! const SAVE_KEY: [u8; 16] = *b"my-secret-key-16";
+//!
+//! let mut save = Savedata::new(b"MYAPP00000\0\0\0");
+//! save.secure_key(SAVE_KEY);
+//! save.save(b"SAVE0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0", data).unwrap();
+//! ```
+//!
+//! # Encryption at rest
+//!
+//! Homebrew can't always rely on the firmware's secure mode, so
+//! [`Savedata::encrypt_with`] encrypts the payload itself with ChaCha20
+//! (see [`crate::crypto`]) under a fresh random nonce per save, and
+//! authenticates it with a SipHash MAC. Loading with a different key fails
+//! with [`SAVEDATA_ERROR_WRONG_KEY`], and an edited or damaged file with
+//! [`SAVEDATA_ERROR_CORRUPT_DATA`]. The key ships inside the game, so this
+//! stops casual hex-editing, not a determined attacker.
+//!
+//! ```ignore
+//! const PAYLOAD_KEY: [u8; 16] = *b"not-a-real-key!!";
+//!
+//! let save = Savedata::new(b"MYAPP00000\0\0\0").encrypt_with(&PAYLOAD_KEY);
+//! save.save(SLOT, &progress)?;
+//! let progress = match save.load(SLOT, 1024) {
+//!     Err(e) if e.is_wrong_key() => return Err(e), // saved by another build
+//!     other => other?,
+//! };
+//! ```
+//!
+//! # Checksums
+//!
+//! [`Savedata::with_checksum`] appends a CRC-32 of the data on save and
+//! verifies it on load, so a save corrupted on the Memory Stick fails with
+//! [`SAVEDATA_ERROR_CORRUPT_DATA`] instead of handing back garbage. Games
+//! that keep a backup slot can fall back to it:
+//!
+//! ```ignore
+//! let save = Savedata::new(b"MYAPP00000\0\0\0").with_checksum();
+//! let data = match save.load(MAIN_SLOT, 1024) {
+//!     Err(e) if e.is_corrupt_data() => save.load(BACKUP_SLOT, 1024)?,
+//!     other => other?,
+//! };
+//! ```
+//!
+//! # Multiple files
+//!
+//! A save is a directory. [`Savedata::write_file`] and
+//! [`Savedata::read_file`] store and fetch individual named files in it,
+//! for games that keep a manifest alongside several data files:
+//!
+//! ```ignore
+//! const SLOT: &[u8; 20] = b"SAVE0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0";
+//!
+//! let save = Savedata::new(b"MYAPP00000\0\0\0").title("My Save");
+//! save.write_file(SLOT, b"MANIFEST.BIN\0", &manifest)?;
+//! save.write_file(SLOT, b"LEVEL01.DAT\0\0", &level)?;
+//!
+//! let manifest = save.read_file(SLOT, b"MANIFEST.BIN\0", 4096)?;
+//! ```
+//!
+//! # Typed saves
+//!
+//! [`Savedata::save_typed`] and [`Savedata::load_typed`] store any type
+//! implementing [`SaveCodec`] instead of a byte blob. Impls are written by
+//! hand from [`SaveField`], which covers integers, floats, strings and
+//! vectors. Each save records the type's [`SaveCodec::VERSION`], so a
+//! newer build can migrate old saves in [`SaveCodec::decode_version`]:
+//!
+//! ```ignore
+//! let save = Savedata::new(b"MYAPP00000\0\0\0").with_checksum();
+//! save.save_typed(SLOT, &progress)?;
+//! let progress: Progress = save.load_typed(SLOT, 4096)?;
+//! ```
 
+use alloc::boxed::Box;
+use alloc::string::String;
 use alloc::vec::Vec;
 use core::ffi::c_void;
+use core::mem::ManuallyDrop;
 
+use crate::dialog::DIALOG_LIST;
 use crate::sys::{
     SceUtilitySavedataParam, SystemParamLanguage, UtilityDialogButtonAccept, UtilityDialogCommon,
     UtilitySavedataFocus, UtilitySavedataMode, UtilitySavedataSFOParam,
@@ -33,15 +123,65 @@ use crate::sys::{
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub struct SavedataError(pub i32);
 
+/// Sentinel error code returned when loading with
+/// [`Savedata::with_checksum`] and the data's checksum doesn't match, or
+/// with [`Savedata::encrypt_with`] and the data fails authentication.
+pub const SAVEDATA_ERROR_CORRUPT_DATA: i32 = -2;
+
+/// Sentinel error code returned when loading with
+/// [`Savedata::encrypt_with`] and the save was encrypted with a different
+/// key, or not encrypted at all.
+pub const SAVEDATA_ERROR_WRONG_KEY: i32 = -3;
+
+/// Sentinel error code returned by [`Savedata::load_typed`] when the data
+/// can't be decoded as the requested type. [`decode_typed`] reports the
+/// [`CodecError`] behind it.
+pub const SAVEDATA_ERROR_DECODE: i32 = -4;
+
+impl SavedataError {
+    /// Returns `true` if the loaded data failed its checksum or MAC.
+    pub fn is_corrupt_data(&self) -> bool {
+        self.0 == SAVEDATA_ERROR_CORRUPT_DATA
+    }
+
+    /// Returns `true` if the loaded data wasn't encrypted with the
+    /// [`encrypt_with`](Savedata::encrypt_with) key.
+    pub fn is_wrong_key(&self) -> bool {
+        self.0 == SAVEDATA_ERROR_WRONG_KEY
+    }
+
+    /// Returns `true` if [`Savedata::load_typed`] couldn't decode the
+    /// loaded data.
+    pub fn is_decode_error(&self) -> bool {
+        self.0 == SAVEDATA_ERROR_DECODE
+    }
+}
+
 impl core::fmt::Debug for SavedataError {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        write!(f, "SavedataError({:#010x})", self.0 as u32)
+        if self.is_corrupt_data() {
+            write!(f, "SavedataError(CorruptData)")
+        } else if self.is_wrong_key() {
+            write!(f, "SavedataError(WrongKey)")
+        } else if self.is_decode_error() {
+            write!(f, "SavedataError(Decode)")
+        } else {
+            write!(f, "SavedataError({:#010x})", self.0 as u32)
+        }
     }
 }
 
 impl core::fmt::Display for SavedataError {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        write!(f, "savedata error {:#010x}", self.0 as u32)
+        if self.is_corrupt_data() {
+            write!(f, "save data is corrupt (checksum mismatch)")
+        } else if self.is_wrong_key() {
+            write!(f, "save data was encrypted with a different key")
+        } else if self.is_decode_error() {
+            write!(f, "save data doesn't match the expected type")
+        } else {
+            write!(f, "savedata error {:#010x}", self.0 as u32)
+        }
     }
 }
 
@@ -51,8 +191,32 @@ const ACCESS_THREAD: i32 = 0x13;
 const FONT_THREAD: i32 = 0x12;
 const SOUND_THREAD: i32 = 0x10;
 
-/// Maximum iterations for savedata polling (~30 seconds at 60 fps).
-const MAX_SAVEDATA_ITERATIONS: u32 = 1800;
+/// Maximum time to wait for a non-interactive savedata operation to
+/// finish. Interactive dialogs wait for the user indefinitely.
+const SAVEDATA_TIMEOUT_MS: u32 = 30_000;
+
+/// `UtilityDialogCommon::result` when the user backs out of a dialog.
+const RESULT_CANCELLED: i32 = 1;
+
+/// `sceUtilitySavedataGetStatus` values.
+const STATUS_NONE: i32 = 0;
+const STATUS_VISIBLE: i32 = 2;
+const STATUS_QUIT: i32 = 3;
+const STATUS_FINISHED: i32 = 4;
+
+/// Returned when a non-interactive operation exceeds
+/// [`SAVEDATA_TIMEOUT_MS`].
+const SCE_KERNEL_ERROR_WAIT_TIMEOUT: i32 = 0x8002_01a8_u32 as i32;
+
+/// How [`Savedata::save_with_mode`] presents a save to the user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SaveMode {
+    /// Save without any UI, overwriting existing data (`AutoSave`).
+    Auto,
+    /// Show the system save dialog, which asks for confirmation before
+    /// overwriting existing data (`Save`).
+    Prompt,
+}
 
 fn make_common() -> UtilityDialogCommon {
     UtilityDialogCommon {
@@ -73,8 +237,18 @@ pub struct Savedata {
     game_name: [u8; 13],
     title: [u8; 128],
     detail: [u8; 1024],
+    key: Option<[u8; 16]>,
+    checksum: bool,
+    /// Key for [`encrypt_with`](Savedata::encrypt_with).
+    payload_key: Option<[u8; 16]>,
 }
 
+/// Size of the CRC-32 trailer added by [`Savedata::with_checksum`].
+const CHECKSUM_SIZE: usize = 4;
+
+/// The data file written by [`Savedata::save`].
+const DATA_FILE: [u8; 13] = *b"DATA.BIN\0\0\0\0\0";
+
 impl Savedata {
     /// Create a new savedata builder.
     ///
@@ -85,6 +259,9 @@ impl Savedata {
             game_name: *game_name,
             title: [0u8; 128],
             detail: [0u8; 1024],
+            key: None,
+            checksum: false,
+            payload_key: None,
         }
     }
 
@@ -102,88 +279,929 @@ impl Savedata {
         self
     }
 
+    /// Encrypt the save data with a per-game key.
+    ///
+    /// The key is passed to the firmware, which encrypts the data file on
+    /// save and decrypts it on load. Loading an encrypted save without the
+    /// matching key fails. An all-zero key is treated by the firmware as
+    /// no key (plaintext).
+    pub fn secure_key(&mut self, key: [u8; 16]) {
+        self.key = Some(key);
+    }
+
+    /// Protect the data with a CRC-32 checksum.
+    ///
+    /// Saves store the data followed by its 4-byte checksum; loads verify
+    /// and remove it, failing with [`SAVEDATA_ERROR_CORRUPT_DATA`] on a
+    /// mismatch. The trailer is invisible to the caller, but saves written
+    /// with and without this flag are not interchangeable, so use it
+    /// consistently for a given slot.
+    pub fn with_checksum(mut self) -> Self {
+        self.checksum = true;
+        self
+    }
+
+    /// Encrypt and authenticate the payload with `key` before it reaches
+    /// the firmware.
+    ///
+    /// Each save gets a random nonce, stored with the ciphertext and a
+    /// key check value, and a SipHash-2-4 MAC over both. Loads verify and
+    /// decrypt transparently, failing with [`SAVEDATA_ERROR_WRONG_KEY`] or
+    /// [`SAVEDATA_ERROR_CORRUPT_DATA`]. The data grows by
+    /// [`ENCRYPTION_OVERHEAD`] bytes, which `max_size` need not include.
+    ///
+    /// This is anti-tamper obfuscation: the key is in the game binary. It
+    /// works whether or not [`secure_key`](Self::secure_key) is also set,
+    /// and combines with [`with_checksum`](Self::with_checksum) (the
+    /// checksum is encrypted along with the data).
+    pub fn encrypt_with(mut self, key: &[u8; 16]) -> Self {
+        self.payload_key = Some(*key);
+        self
+    }
+
     /// Save data to the specified save slot.
     ///
     /// `save_name` must be exactly 20 bytes (null-padded).
-    /// `data` is the raw bytes to save.
+    /// `data` is the raw bytes to save. Existing data in the slot is
+    /// overwritten silently; use [`save_with_prompt`](Self::save_with_prompt)
+    /// for manual saves that should ask first.
     pub fn save(&self, save_name: &[u8; 20], data: &[u8]) -> Result<(), SavedataError> {
-        let mut data_buf = Vec::from(data);
-
-        let mut sfo = UtilitySavedataSFOParam {
-            title: self.title,
-            savedata_title: [0u8; 128],
-            detail: self.detail,
-            parental_level: 0,
-            unknown: [0u8; 3],
-        };
+        self.save_with_mode(save_name, data, SaveMode::Auto)
+            .map(|_| ())
+    }
 
-        let mut params: SceUtilitySavedataParam = unsafe { core::mem::zeroed() };
-        params.base = make_common();
-        params.mode = UtilitySavedataMode::AutoSave;
-        params.game_name = self.game_name;
-        params.save_name = *save_name;
-        params.file_name = *b"DATA.BIN\0\0\0\0\0";
-        params.data_buf = data_buf.as_mut_ptr() as *mut c_void;
-        params.data_buf_size = data_buf.len();
-        params.data_size = data_buf.len();
-        params.sfo_param = sfo;
-        params.focus = UtilitySavedataFocus::Latest;
+    /// Save data through the system save dialog.
+    ///
+    /// If the slot already holds data, the system overwrite-confirmation
+    /// dialog is shown. Returns `Ok(false)` if the user declined or backed
+    /// out of the dialog, `Ok(true)` once the data is written.
+    ///
+    /// The dialog renders into the framebuffer, so the caller's open GU
+    /// display list is finished first and must be re-opened with
+    /// `sceGuStart` afterwards.
+    pub fn save_with_prompt(
+        &self,
+        save_name: &[u8; 20],
+        data: &[u8],
+    ) -> Result<bool, SavedataError> {
+        self.save_with_mode(save_name, data, SaveMode::Prompt)
+    }
 
-        self.run_savedata(&mut params)
+    /// Save data using the given [`SaveMode`].
+    ///
+    /// Returns `Ok(false)` if the user cancelled a [`SaveMode::Prompt`]
+    /// dialog.
+    pub fn save_with_mode(
+        &self,
+        save_name: &[u8; 20],
+        data: &[u8],
+        mode: SaveMode,
+    ) -> Result<bool, SavedataError> {
+        let interactive = mode == SaveMode::Prompt;
+        let mut op = self.start_save(save_name, data, mode)?;
+        if interactive {
+            finish_gu_list();
+        }
+        match run_blocking(&mut op, interactive)? {
+            SaveProgress::Cancelled => Ok(false),
+            _ => Ok(true),
+        }
     }
 
     /// Load data from the specified save slot.
     ///
     /// `save_name` must be exactly 20 bytes (null-padded).
-    /// `max_size` is the maximum expected data size.
+    /// `max_size` is the maximum expected data size, not counting the
+    /// [`with_checksum`](Self::with_checksum) trailer.
     pub fn load(&self, save_name: &[u8; 20], max_size: usize) -> Result<Vec<u8>, SavedataError> {
-        let mut data_buf = alloc::vec![0u8; max_size];
+        let mut op = self.start_load(save_name, max_size)?;
+        run_blocking(&mut op, false)?;
+        Ok(op.into_data())
+    }
 
-        let mut params: SceUtilitySavedataParam = unsafe { core::mem::zeroed() };
+    /// Save `value` to the specified save slot, encoded with
+    /// [`SaveCodec`] and tagged with its format version.
+    ///
+    /// Combines with [`with_checksum`](Self::with_checksum) and
+    /// [`encrypt_with`](Self::encrypt_with) like [`save`](Self::save).
+    pub fn save_typed<T: SaveCodec>(
+        &self,
+        save_name: &[u8; 20],
+        value: &T,
+    ) -> Result<(), SavedataError> {
+        self.save(save_name, &encode_typed(value))
+    }
+
+    /// Load a value saved with [`save_typed`](Self::save_typed).
+    ///
+    /// `max_size` is the maximum expected encoded size, not counting the
+    /// version header. Fails with [`SAVEDATA_ERROR_DECODE`] if the data
+    /// isn't a typed save or can't be decoded as `T`.
+    pub fn load_typed<T: SaveCodec>(
+        &self,
+        save_name: &[u8; 20],
+        max_size: usize,
+    ) -> Result<T, SavedataError> {
+        let data = self.load(save_name, max_size + TYPED_HEADER_SIZE)?;
+        decode_typed(&data).map_err(|_| SavedataError(SAVEDATA_ERROR_DECODE))
+    }
+
+    /// Start a save without blocking.
+    ///
+    /// Call [`SaveOperation::poll`] once per frame until it stops
+    /// returning [`SaveProgress::Running`]. See [`SaveOperation`] for the
+    /// frame sequencing required while a dialog is visible.
+    pub fn start_save(
+        &self,
+        save_name: &[u8; 20],
+        data: &[u8],
+        mode: SaveMode,
+    ) -> Result<SaveOperation, SavedataError> {
+        let mut params = self.params(
+            match mode {
+                SaveMode::Auto => UtilitySavedataMode::AutoSave,
+                SaveMode::Prompt => UtilitySavedataMode::Save,
+            },
+            save_name,
+            &DATA_FILE,
+        );
+        // Let the Save dialog offer to overwrite an existing slot (it asks
+        // the user for confirmation first).
+        params.overwrite = 1;
+        let buf = self.payload(data);
+        params.data_size = buf.len();
+
+        SaveOperation::start(params, buf, mode == SaveMode::Prompt, LoadCheck::default())
+    }
+
+    /// Start a load without blocking.
+    ///
+    /// Once [`SaveOperation::poll`] returns [`SaveProgress::Done`], the
+    /// loaded bytes are available from [`SaveOperation::into_data`].
+    pub fn start_load(
+        &self,
+        save_name: &[u8; 20],
+        max_size: usize,
+    ) -> Result<SaveOperation, SavedataError> {
+        let params = self.params(UtilitySavedataMode::AutoLoad, save_name, &DATA_FILE);
+        SaveOperation::start(params, self.load_buffer(max_size), false, self.load_check())
+    }
+
+    /// Write `data` to the file `file_name` inside the save `save_name`,
+    /// creating the save if it doesn't exist yet.
+    ///
+    /// Unlike [`save()`](Self::save), which always writes `DATA.BIN`, this
+    /// lets a save hold several files, e.g. a manifest plus per-level data.
+    /// Other files in the save are left untouched. No dialog is shown.
+    ///
+    /// `file_name` must be exactly 13 bytes: a null-padded 8.3 name such as
+    /// `b"MANIFEST.BIN\0"`. Files are written in the firmware's secure
+    /// format, encrypted with the [`secure_key`](Self::secure_key) if one
+    /// is set, and [`with_checksum`](Self::with_checksum) applies as for
+    /// `save()`.
+    pub fn write_file(
+        &self,
+        save_name: &[u8; 20],
+        file_name: &[u8; 13],
+        data: &[u8],
+    ) -> Result<(), SavedataError> {
+        let mut params = self.params(UtilitySavedataMode::MakeDataSecure, save_name, file_name);
+        let buf = self.payload(data);
+        params.data_size = buf.len();
+
+        let mut op = SaveOperation::start(params, buf, false, LoadCheck::default())?;
+        run_blocking(&mut op, false).map(|_| ())
+    }
+
+    /// Read the file `file_name` written by
+    /// [`write_file()`](Self::write_file) from the save `save_name`.
+    ///
+    /// `max_size` is the maximum expected file size, as for
+    /// [`load()`](Self::load). The same key and checksum settings used to
+    /// write the file must be used to read it.
+    pub fn read_file(
+        &self,
+        save_name: &[u8; 20],
+        file_name: &[u8; 13],
+        max_size: usize,
+    ) -> Result<Vec<u8>, SavedataError> {
+        let params = self.params(UtilitySavedataMode::ReadDataSecure, save_name, file_name);
+        let mut op =
+            SaveOperation::start(params, self.load_buffer(max_size), false, self.load_check())?;
+        run_blocking(&mut op, false)?;
+        Ok(op.into_data())
+    }
+
+    /// Parameters common to every operation on `save_name`/`file_name`.
+    fn params(
+        &self,
+        mode: UtilitySavedataMode,
+        save_name: &[u8; 20],
+        file_name: &[u8; 13],
+    ) -> Box<SceUtilitySavedataParam> {
+        let mut params: Box<SceUtilitySavedataParam> = Box::new(unsafe { core::mem::zeroed() });
         params.base = make_common();
-        params.mode = UtilitySavedataMode::AutoLoad;
+        params.mode = mode;
         params.game_name = self.game_name;
         params.save_name = *save_name;
-        params.file_name = *b"DATA.BIN\0\0\0\0\0";
-        params.data_buf = data_buf.as_mut_ptr() as *mut c_void;
-        params.data_buf_size = data_buf.len();
-        params.data_size = 0;
+        params.file_name = *file_name;
+        params.sfo_param = UtilitySavedataSFOParam {
+            title: self.title,
+            savedata_title: [0u8; 128],
+            detail: self.detail,
+            parental_level: 0,
+            unknown: [0u8; 3],
+        };
         params.focus = UtilitySavedataFocus::Latest;
+        self.apply_key(&mut params);
+        params
+    }
+
+    /// The bytes to write for `data`, with the checksum trailer if enabled,
+    /// encrypted if enabled.
+    fn payload(&self, data: &[u8]) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(data.len() + CHECKSUM_SIZE + ENCRYPTION_OVERHEAD);
+        buf.extend_from_slice(data);
+        if self.checksum {
+            buf.extend_from_slice(&crate::hash::crc32(data).to_le_bytes());
+        }
+        match self.payload_key {
+            Some(key) => seal(&key, &buf),
+            None => buf,
+        }
+    }
+
+    /// A read buffer for up to `max_size` bytes of data.
+    fn load_buffer(&self, max_size: usize) -> Vec<u8> {
+        let mut buf_size = max_size;
+        if self.checksum {
+            buf_size += CHECKSUM_SIZE;
+        }
+        if self.payload_key.is_some() {
+            buf_size += ENCRYPTION_OVERHEAD;
+        }
+        alloc::vec![0u8; buf_size]
+    }
+
+    fn load_check(&self) -> LoadCheck {
+        LoadCheck {
+            checksum: self.checksum,
+            payload_key: self.payload_key,
+        }
+    }
+
+    /// Fill in the encryption key, if one was set.
+    ///
+    /// `make_common` reports the full (firmware 2.00+) parameter size, so
+    /// the firmware reads `key` and picks the encrypted format whenever it
+    /// is non-zero.
+    fn apply_key(&self, params: &mut SceUtilitySavedataParam) {
+        if let Some(key) = self.key {
+            params.key = key;
+        }
+    }
+}
+
+// ── Typed saves ─────────────────────────────────────────────────────
+
+/// Marks a [`Savedata::save_typed`] payload. Followed by the
+/// [`SaveCodec::VERSION`] it was written with, as a little-endian `u32`.
+const TYPED_MAGIC: [u8; 4] = *b"PST1";
+const TYPED_HEADER_SIZE: usize = TYPED_MAGIC.len() + 4;
+
+/// Error from decoding a typed save.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodecError {
+    /// The data ended in the middle of a value.
+    Truncated,
+    /// Bytes were left over after the value was decoded.
+    TrailingData,
+    /// The data wasn't written by [`encode_typed`].
+    BadMagic,
+    /// The data was written with a format version the type can't decode.
+    UnsupportedVersion(u32),
+    /// A field held a value its type can't represent, such as a `bool`
+    /// other than 0 or 1 or a string that isn't UTF-8.
+    Invalid,
+}
+
+impl core::fmt::Display for CodecError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Truncated => f.write_str("save data is truncated"),
+            Self::TrailingData => f.write_str("unexpected data after the end of the save"),
+            Self::BadMagic => f.write_str("not a typed save"),
+            Self::UnsupportedVersion(v) => write!(f, "unsupported save version {}", v),
+            Self::Invalid => f.write_str("invalid value in save data"),
+        }
+    }
+}
+
+/// A value that can be stored with [`Savedata::save_typed`].
+///
+/// Implement it by writing each field in turn with [`SaveField::write`]
+/// and reading them back in the same order with a [`SaveReader`]:
+///
+/// ```ignore
+/// use psp::savedata::{CodecError, SaveCodec, SaveField, SaveReader};
+///
+/// struct Progress {
+///     level: u16,
+///     score: u32,
+///     name: String,
+///     items: Vec<u8>,
+/// }
+///
+/// impl SaveCodec for Progress {
+///     fn encode(&self, buf: &mut Vec<u8>) {
+///         self.level.write(buf);
+///         self.score.write(buf);
+///         self.name.write(buf);
+///         self.items.write(buf);
+///     }
+///
+///     fn decode(buf: &[u8]) -> Result<Self, CodecError> {
+///         let mut r = SaveReader::new(buf);
+///         let progress = Self {
+///             level: r.read()?,
+///             score: r.read()?,
+///             name: r.read()?,
+///             items: r.read()?,
+///         };
+///         r.finish()?;
+///         Ok(progress)
+///     }
+/// }
+/// ```
+pub trait SaveCodec: Sized {
+    /// Format version stored with each save. Bump it whenever
+    /// [`encode`](Self::encode) changes.
+    const VERSION: u32 = 1;
 
-        self.run_savedata(&mut params)?;
+    /// Append the encoded value to `buf`.
+    fn encode(&self, buf: &mut Vec<u8>);
 
-        let actual_size = params.data_size.min(max_size);
-        data_buf.truncate(actual_size);
-        Ok(data_buf)
+    /// Decode a value written by [`encode`](Self::encode) at the current
+    /// [`VERSION`](Self::VERSION).
+    fn decode(buf: &[u8]) -> Result<Self, CodecError>;
+
+    /// Decode a value written at format `version`.
+    ///
+    /// The default only accepts the current [`VERSION`](Self::VERSION).
+    /// Override it to migrate saves written by older builds of the game.
+    fn decode_version(version: u32, buf: &[u8]) -> Result<Self, CodecError> {
+        if version == Self::VERSION {
+            Self::decode(buf)
+        } else {
+            Err(CodecError::UnsupportedVersion(version))
+        }
+    }
+}
+
+/// A field type with a fixed encoding, for building [`SaveCodec`] impls.
+///
+/// Integers and floats are little-endian, `bool` is one byte, and
+/// `String` and `Vec<T>` are a `u32` length followed by their contents.
+/// `Option<T>` is a presence byte followed by the value, and `[T; N]` is
+/// its `N` elements with no length. Implement it for nested structs to
+/// use them as fields.
+pub trait SaveField: Sized {
+    /// Append the encoded field to `buf`.
+    fn write(&self, buf: &mut Vec<u8>);
+
+    /// Read the field from the front of `reader`.
+    fn read(reader: &mut SaveReader<'_>) -> Result<Self, CodecError>;
+}
+
+/// Cursor over encoded fields, for [`SaveCodec::decode`].
+pub struct SaveReader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> SaveReader<'a> {
+    /// Start reading at the beginning of `buf`.
+    pub fn new(buf: &'a [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    /// Read the next field.
+    pub fn read<T: SaveField>(&mut self) -> Result<T, CodecError> {
+        T::read(self)
+    }
+
+    /// Take the next `len` raw bytes.
+    pub fn bytes(&mut self, len: usize) -> Result<&'a [u8], CodecError> {
+        if len > self.remaining() {
+            return Err(CodecError::Truncated);
+        }
+        let bytes = &self.buf[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    /// Number of bytes not read yet.
+    pub fn remaining(&self) -> usize {
+        self.buf.len() - self.pos
+    }
+
+    /// Check that everything was read.
+    pub fn finish(self) -> Result<(), CodecError> {
+        if self.remaining() == 0 {
+            Ok(())
+        } else {
+            Err(CodecError::TrailingData)
+        }
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], CodecError> {
+        let mut out = [0u8; N];
+        out.copy_from_slice(self.bytes(N)?);
+        Ok(out)
+    }
+
+    fn len(&mut self) -> Result<usize, CodecError> {
+        Ok(u32::from_le_bytes(self.array()?) as usize)
+    }
+}
+
+macro_rules! impl_save_field_le {
+    ($($t:ty),*) => {$(
+        impl SaveField for $t {
+            fn write(&self, buf: &mut Vec<u8>) {
+                buf.extend_from_slice(&self.to_le_bytes());
+            }
+
+            fn read(reader: &mut SaveReader<'_>) -> Result<Self, CodecError> {
+                Ok(<$t>::from_le_bytes(reader.array()?))
+            }
+        }
+    )*};
+}
+
+impl_save_field_le!(u8, u16, u32, u64, i8, i16, i32, i64, f32, f64);
+
+impl SaveField for bool {
+    fn write(&self, buf: &mut Vec<u8>) {
+        buf.push(*self as u8);
+    }
+
+    fn read(reader: &mut SaveReader<'_>) -> Result<Self, CodecError> {
+        match reader.read::<u8>()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(CodecError::Invalid),
+        }
+    }
+}
+
+impl SaveField for String {
+    fn write(&self, buf: &mut Vec<u8>) {
+        (self.len() as u32).write(buf);
+        buf.extend_from_slice(self.as_bytes());
+    }
+
+    fn read(reader: &mut SaveReader<'_>) -> Result<Self, CodecError> {
+        let len = reader.len()?;
+        let bytes = reader.bytes(len)?;
+        core::str::from_utf8(bytes)
+            .map(String::from)
+            .map_err(|_| CodecError::Invalid)
+    }
+}
+
+impl<T: SaveField> SaveField for Vec<T> {
+    fn write(&self, buf: &mut Vec<u8>) {
+        (self.len() as u32).write(buf);
+        for item in self {
+            item.write(buf);
+        }
+    }
+
+    fn read(reader: &mut SaveReader<'_>) -> Result<Self, CodecError> {
+        let len = reader.len()?;
+        // A corrupt length fails at the end of the data rather than
+        // reserving memory up front.
+        let mut items = Vec::with_capacity(len.min(reader.remaining()));
+        for _ in 0..len {
+            items.push(reader.read()?);
+        }
+        Ok(items)
+    }
+}
+
+impl<T: SaveField> SaveField for Option<T> {
+    fn write(&self, buf: &mut Vec<u8>) {
+        self.is_some().write(buf);
+        if let Some(value) = self {
+            value.write(buf);
+        }
+    }
+
+    fn read(reader: &mut SaveReader<'_>) -> Result<Self, CodecError> {
+        if reader.read::<bool>()? {
+            Ok(Some(reader.read()?))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl<T: SaveField, const N: usize> SaveField for [T; N] {
+    fn write(&self, buf: &mut Vec<u8>) {
+        for item in self {
+            item.write(buf);
+        }
+    }
+
+    fn read(reader: &mut SaveReader<'_>) -> Result<Self, CodecError> {
+        let mut items = Vec::with_capacity(N);
+        for _ in 0..N {
+            items.push(reader.read()?);
+        }
+        items.try_into().map_err(|_| CodecError::Invalid)
+    }
+}
+
+/// Encode `value` with the header [`Savedata::save_typed`] writes.
+pub fn encode_typed<T: SaveCodec>(value: &T) -> Vec<u8> {
+    let mut buf = Vec::new();
+    buf.extend_from_slice(&TYPED_MAGIC);
+    buf.extend_from_slice(&T::VERSION.to_le_bytes());
+    value.encode(&mut buf);
+    buf
+}
+
+/// Decode data written by [`encode_typed`], dispatching on its version
+/// through [`SaveCodec::decode_version`].
+pub fn decode_typed<T: SaveCodec>(data: &[u8]) -> Result<T, CodecError> {
+    if data.len() < TYPED_HEADER_SIZE {
+        return Err(CodecError::Truncated);
     }
+    if data[..4] != TYPED_MAGIC {
+        return Err(CodecError::BadMagic);
+    }
+    let mut version = [0u8; 4];
+    version.copy_from_slice(&data[4..TYPED_HEADER_SIZE]);
+    T::decode_version(u32::from_le_bytes(version), &data[TYPED_HEADER_SIZE..])
+}
+
+// ── Payload encryption ──────────────────────────────────────────────
+
+/// Bytes [`Savedata::encrypt_with`] adds to the data: magic, key check
+/// value and nonce in front, MAC behind.
+pub const ENCRYPTION_OVERHEAD: usize = HEADER_SIZE + MAC_SIZE;
+
+/// Marks an encrypted payload, and its format version.
+const ENCRYPTION_MAGIC: [u8; 4] = *b"PSE1";
+const KEY_CHECK_SIZE: usize = 4;
+const HEADER_SIZE: usize = ENCRYPTION_MAGIC.len() + KEY_CHECK_SIZE + crate::crypto::NONCE_SIZE;
+const MAC_SIZE: usize = 8;
+
+/// Keys derived from the caller's key, so the cipher, the MAC and the key
+/// check never share one.
+struct PayloadKeys {
+    cipher: [u8; crate::crypto::KEY_SIZE],
+    mac: [u8; 16],
+    check: [u8; KEY_CHECK_SIZE],
+}
+
+impl PayloadKeys {
+    fn derive(key: &[u8; 16]) -> Self {
+        let word = |label: &[u8], i: u8| {
+            let mut input = [0u8; 16];
+            input[..label.len()].copy_from_slice(label);
+            input[15] = i;
+            crate::hash::siphash24(key, &input).to_le_bytes()
+        };
+        let mut cipher = [0u8; crate::crypto::KEY_SIZE];
+        for (i, chunk) in cipher.chunks_exact_mut(8).enumerate() {
+            chunk.copy_from_slice(&word(b"save cipher", i as u8));
+        }
+        let mut mac = [0u8; 16];
+        for (i, chunk) in mac.chunks_exact_mut(8).enumerate() {
+            chunk.copy_from_slice(&word(b"save mac", i as u8));
+        }
+        let mut check = [0u8; KEY_CHECK_SIZE];
+        check.copy_from_slice(&word(b"save key check", 0)[..KEY_CHECK_SIZE]);
+        Self { cipher, mac, check }
+    }
+}
+
+/// Encrypt `plaintext`: header, ciphertext, then a MAC over both.
+fn seal(key: &[u8; 16], plaintext: &[u8]) -> Vec<u8> {
+    let keys = PayloadKeys::derive(key);
+    let mut nonce = [0u8; crate::crypto::NONCE_SIZE];
+    crate::crypto::fill_random(&mut nonce);
+
+    let mut out = Vec::with_capacity(plaintext.len() + ENCRYPTION_OVERHEAD);
+    out.extend_from_slice(&ENCRYPTION_MAGIC);
+    out.extend_from_slice(&keys.check);
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(plaintext);
+    crate::crypto::chacha20_xor(&keys.cipher, &nonce, 1, &mut out[HEADER_SIZE..]);
+    let mac = crate::hash::siphash24(&keys.mac, &out);
+    out.extend_from_slice(&mac.to_le_bytes());
+    out
+}
+
+/// Verify and decrypt a payload written by [`seal`].
+fn open(key: &[u8; 16], data: &[u8]) -> Result<Vec<u8>, SavedataError> {
+    let keys = PayloadKeys::derive(key);
+    if data.len() < ENCRYPTION_OVERHEAD || data[..4] != ENCRYPTION_MAGIC {
+        return Err(SavedataError(SAVEDATA_ERROR_WRONG_KEY));
+    }
+    if data[4..4 + KEY_CHECK_SIZE] != keys.check {
+        return Err(SavedataError(SAVEDATA_ERROR_WRONG_KEY));
+    }
+    let (sealed, mac) = data.split_at(data.len() - MAC_SIZE);
+    let expected = crate::hash::siphash24(&keys.mac, sealed).to_le_bytes();
+    // Compare without an early exit so timing doesn't reveal how much of
+    // a forged MAC was right.
+    if mac
+        .iter()
+        .zip(expected)
+        .fold(0, |acc, (a, b)| acc | (a ^ b))
+        != 0
+    {
+        return Err(SavedataError(SAVEDATA_ERROR_CORRUPT_DATA));
+    }
+
+    let mut nonce = [0u8; crate::crypto::NONCE_SIZE];
+    nonce.copy_from_slice(&sealed[4 + KEY_CHECK_SIZE..HEADER_SIZE]);
+    let mut plaintext = sealed[HEADER_SIZE..].to_vec();
+    crate::crypto::chacha20_xor(&keys.cipher, &nonce, 1, &mut plaintext);
+    Ok(plaintext)
+}
+
+// ── Non-blocking operations ─────────────────────────────────────────
+
+/// Verification applied to a completed load. Saves use the default,
+/// which does nothing.
+#[derive(Clone, Copy, Default)]
+struct LoadCheck {
+    /// Strip and verify a CRC-32 trailer.
+    checksum: bool,
+    /// Decrypt with this [`Savedata::encrypt_with`] key first.
+    payload_key: Option<[u8; 16]>,
+}
+
+/// State of a [`SaveOperation`], returned by [`SaveOperation::poll`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SaveProgress {
+    /// Still working. `dialog_visible` is `true` while the system dialog
+    /// is on screen and drawing over the game's frame.
+    Running { dialog_visible: bool },
+    /// The operation completed successfully.
+    Done,
+    /// The operation failed.
+    Failed(SavedataError),
+    /// The user backed out of the dialog.
+    Cancelled,
+}
+
+/// A save or load in progress, started by [`Savedata::start_save`] or
+/// [`Savedata::start_load`].
+///
+/// Drive it by calling [`poll()`](Self::poll) once per frame, between
+/// finishing the game's display list and swapping buffers, so the system
+/// dialog draws on top of the game's own frame:
+///
+/// ```ignore
+/// let mut op = Savedata::new(b"MYAPP00000\0\0\0").start_save(slot, &data, SaveMode::Prompt)?;
+/// loop {
+///     draw_saving_spinner(); // sceGuStart ... sceGuFinish + sceGuSync
+///     match op.poll() {
+///         SaveProgress::Running { .. } => {},
+///         SaveProgress::Done => break,
+///         SaveProgress::Cancelled => break,
+///         SaveProgress::Failed(e) => return Err(e),
+///     }
+///     psp::sys::sceDisplayWaitVblankStart();
+///     psp::sys::sceGuSwapBuffers();
+/// }
+/// ```
+///
+/// The savedata utility is a single system-wide resource: only one
+/// operation may run at a time. Dropping an unfinished operation keeps
+/// updating the utility until it finishes, shuts it down and waits for it
+/// to become idle so the next operation can start. If that takes longer
+/// than [`SAVEDATA_TIMEOUT_MS`] (a dialog left waiting for the user), the
+/// parameter block and data buffer are leaked rather than freed under the
+/// firmware.
+pub struct SaveOperation {
+    /// Read by the firmware for the whole operation, so it lives on the
+    /// heap at a fixed address. Only freed once the utility is idle.
+    params: ManuallyDrop<Box<SceUtilitySavedataParam>>,
+    data_buf: Vec<u8>,
+    interactive: bool,
+    /// How a completed load is verified and decoded.
+    check: LoadCheck,
+    /// Terminal state, once reached.
+    outcome: Option<SaveProgress>,
+}
+
+impl SaveOperation {
+    fn start(
+        mut params: Box<SceUtilitySavedataParam>,
+        mut data_buf: Vec<u8>,
+        interactive: bool,
+        check: LoadCheck,
+    ) -> Result<Self, SavedataError> {
+        params.data_buf = data_buf.as_mut_ptr() as *mut c_void;
+        params.data_buf_size = data_buf.len();
 
-    fn run_savedata(&self, params: &mut SceUtilitySavedataParam) -> Result<(), SavedataError> {
         let ret = unsafe {
-            crate::sys::sceUtilitySavedataInitStart(params as *mut SceUtilitySavedataParam)
+            crate::sys::sceUtilitySavedataInitStart(&mut *params as *mut SceUtilitySavedataParam)
         };
         if ret < 0 {
             return Err(SavedataError(ret));
         }
 
-        for _ in 0..MAX_SAVEDATA_ITERATIONS {
-            let status = unsafe { crate::sys::sceUtilitySavedataGetStatus() };
-            match status {
-                2 => {
-                    unsafe { crate::sys::sceUtilitySavedataUpdate(1) };
-                },
-                3 => {
-                    unsafe { crate::sys::sceUtilitySavedataShutdownStart() };
+        Ok(Self {
+            params: ManuallyDrop::new(params),
+            data_buf,
+            interactive,
+            check,
+            outcome: None,
+        })
+    }
+
+    /// Advance the operation by one step and report its state.
+    ///
+    /// Calls `sceUtilitySavedataUpdate` while the utility is running and
+    /// `sceUtilitySavedataShutdownStart` once it has finished, then keeps
+    /// reporting [`SaveProgress::Running`] until the utility is fully idle.
+    /// After that the final state is returned on every call.
+    pub fn poll(&mut self) -> SaveProgress {
+        if let Some(outcome) = self.outcome {
+            return outcome;
+        }
+
+        let status = unsafe { crate::sys::sceUtilitySavedataGetStatus() };
+        let progress = match status {
+            s if s < 0 => SaveProgress::Failed(SavedataError(s)),
+            STATUS_NONE => match self.params.base.result {
+                r if r < 0 => SaveProgress::Failed(SavedataError(r)),
+                RESULT_CANCELLED => SaveProgress::Cancelled,
+                _ => self.finish_load(),
+            },
+            STATUS_VISIBLE => {
+                unsafe { crate::sys::sceUtilitySavedataUpdate(1) };
+                SaveProgress::Running {
+                    dialog_visible: self.interactive,
+                }
+            },
+            STATUS_QUIT => {
+                unsafe { crate::sys::sceUtilitySavedataShutdownStart() };
+                SaveProgress::Running {
+                    dialog_visible: false,
+                }
+            },
+            _ => SaveProgress::Running {
+                dialog_visible: false,
+            },
+        };
+
+        if !matches!(progress, SaveProgress::Running { .. }) {
+            self.outcome = Some(progress);
+        }
+        progress
+    }
+
+    /// Decrypt a completed load and check and remove its checksum
+    /// trailer, as the load asked for.
+    fn finish_load(&mut self) -> SaveProgress {
+        if let Some(key) = self.check.payload_key {
+            let len = self.params.data_size.min(self.data_buf.len());
+            match open(&key, &self.data_buf[..len]) {
+                Ok(plaintext) => {
+                    // The firmware is done with the old buffer.
+                    self.params.data_size = plaintext.len();
+                    self.data_buf = plaintext;
                 },
-                0 => break,
-                _ => {},
+                Err(e) => return SaveProgress::Failed(e),
+            }
+        }
+        if !self.check.checksum {
+            return SaveProgress::Done;
+        }
+        let len = self.params.data_size.min(self.data_buf.len());
+        match crate::hash::strip_crc32(&self.data_buf[..len]) {
+            Some(payload) => {
+                self.params.data_size = payload.len();
+                SaveProgress::Done
+            },
+            None => SaveProgress::Failed(SavedataError(SAVEDATA_ERROR_CORRUPT_DATA)),
+        }
+    }
+
+    /// Returns `true` once [`poll()`](Self::poll) has reported a final
+    /// state.
+    pub fn is_finished(&self) -> bool {
+        self.outcome.is_some()
+    }
+
+    /// Consume the operation and return its data buffer.
+    ///
+    /// For a completed load this is the loaded data. For a save, or a load
+    /// that has not completed, it is empty.
+    pub fn into_data(mut self) -> Vec<u8> {
+        let is_load = matches!(
+            self.params.mode,
+            UtilitySavedataMode::AutoLoad | UtilitySavedataMode::ReadDataSecure
+        );
+        if !is_load || self.outcome != Some(SaveProgress::Done) {
+            return Vec::new();
+        }
+        let mut data = core::mem::take(&mut self.data_buf);
+        data.truncate(self.params.data_size.min(data.len()));
+        data
+    }
+}
+
+impl Drop for SaveOperation {
+    fn drop(&mut self) {
+        // The firmware holds pointers into `params` and `data_buf` until
+        // the utility is idle. If it never gets there, leaking them is the
+        // only safe option.
+        if self.outcome.is_none() && !wait_for_idle() {
+            core::mem::forget(core::mem::take(&mut self.data_buf));
+            return;
+        }
+        // SAFETY: The utility is idle, so the firmware no longer reads
+        // `params`, and it is not used again.
+        unsafe { ManuallyDrop::drop(&mut self.params) };
+    }
+}
+
+/// Drive the savedata utility until it is idle: keep a running operation
+/// updating, and shut it down once it quits. Returns `false` if it is
+/// still running after [`SAVEDATA_TIMEOUT_MS`].
+fn wait_for_idle() -> bool {
+    let timeout = crate::time::Timeout::from_millis(SAVEDATA_TIMEOUT_MS);
+    loop {
+        match unsafe { crate::sys::sceUtilitySavedataGetStatus() } {
+            s if s < 0 || s == STATUS_NONE || s == STATUS_FINISHED => return true,
+            STATUS_VISIBLE => unsafe {
+                crate::sys::sceUtilitySavedataUpdate(1);
+            },
+            STATUS_QUIT => unsafe {
+                crate::sys::sceUtilitySavedataShutdownStart();
+            },
+            _ => {},
+        }
+        if timeout.expired() {
+            return false;
+        }
+        unsafe { crate::sys::sceDisplayWaitVblankStart() };
+    }
+}
+
+/// Finish and sync the current display list so a dialog can render into
+/// the framebuffer.
+fn finish_gu_list() {
+    // SAFETY: Finishing and syncing the current list is valid whether or
+    // not one is open.
+    unsafe {
+        crate::sys::sceGuFinish();
+        crate::sys::sceGuSync(
+            crate::sys::GuSyncMode::Finish,
+            crate::sys::GuSyncBehavior::Wait,
+        );
+    }
+}
+
+/// Drive `op` to completion, blocking the calling thread.
+///
+/// Interactive operations present a cleared GU frame each iteration (same
+/// convention as [`crate::dialog`]) and wait for the user indefinitely;
+/// others give up after [`SAVEDATA_TIMEOUT_MS`].
+fn run_blocking(op: &mut SaveOperation, interactive: bool) -> Result<SaveProgress, SavedataError> {
+    let timeout = crate::time::Timeout::from_millis(SAVEDATA_TIMEOUT_MS);
+    loop {
+        if interactive {
+            // SAFETY: DIALOG_LIST is only used by utility dialog loops
+            // which run on the main thread and never overlap.
+            unsafe {
+                crate::sys::sceGuStart(crate::sys::GuContextType::Direct, DIALOG_LIST.as_mut_ptr());
+                crate::sys::sceGuClearColor(0xff00_0000);
+                crate::sys::sceGuClear(crate::sys::ClearBuffer::COLOR_BUFFER_BIT);
             }
-            unsafe { crate::sys::sceDisplayWaitVblankStart() };
+            finish_gu_list();
         }
 
-        if params.base.result < 0 {
-            return Err(SavedataError(params.base.result));
+        match op.poll() {
+            SaveProgress::Running { .. } => {},
+            SaveProgress::Failed(e) => return Err(e),
+            done => return Ok(done),
         }
 
-        Ok(())
+        unsafe { crate::sys::sceDisplayWaitVblankStart() };
+        if interactive {
+            unsafe { crate::sys::sceGuSwapBuffers() };
+        } else if timeout.expired() {
+            return Err(SavedataError(SCE_KERNEL_ERROR_WAIT_TIMEOUT));
+        }
     }
 }
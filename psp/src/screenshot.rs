@@ -2,80 +2,8 @@ use crate::sys::{self, DisplayPixelFormat};
 use crate::{SCREEN_HEIGHT, SCREEN_WIDTH};
 use core::{ffi::c_void, ptr};
 
-// RGBA
-const BYTES_PER_PIXEL: usize = 4;
-
 const NUM_PIXELS: usize = (SCREEN_WIDTH * SCREEN_HEIGHT) as usize;
 
-#[repr(C, packed)]
-#[derive(Clone, Copy)]
-struct BmpHeader {
-    pub file_type: [u8; 2],
-    pub file_size: u32,
-    pub reserved_1: u16,
-    pub reserved_2: u16,
-    pub image_data_start: u32,
-    pub dib_header_size: u32,
-    pub image_width: u32,
-    pub image_height: u32,
-    pub color_planes: u16,
-    pub bpp: u16,
-    pub compression: u32,
-    pub image_data_len: u32,
-    pub print_resolution_x: u32,
-    pub print_resolution_y: u32,
-    pub palette_color_count: u32,
-    pub important_colors: u32,
-}
-
-impl BmpHeader {
-    const BYTES: usize = core::mem::size_of::<Self>();
-
-    fn to_bytes(self) -> [u8; Self::BYTES] {
-        let mut buf = [0u8; Self::BYTES];
-        let mut off = 0;
-        macro_rules! put {
-            ($val:expr, u8x2) => {{
-                buf[off] = $val[0];
-                buf[off + 1] = $val[1];
-                off += 2;
-            }};
-            ($val:expr, u16) => {{
-                let b = ($val).to_le_bytes();
-                buf[off] = b[0];
-                buf[off + 1] = b[1];
-                off += 2;
-            }};
-            ($val:expr, u32) => {{
-                let b = ($val).to_le_bytes();
-                buf[off] = b[0];
-                buf[off + 1] = b[1];
-                buf[off + 2] = b[2];
-                buf[off + 3] = b[3];
-                off += 4;
-            }};
-        }
-        put!(self.file_type, u8x2);
-        put!(self.file_size, u32);
-        put!(self.reserved_1, u16);
-        put!(self.reserved_2, u16);
-        put!(self.image_data_start, u32);
-        put!(self.dib_header_size, u32);
-        put!(self.image_width, u32);
-        put!(self.image_height, u32);
-        put!(self.color_planes, u16);
-        put!(self.bpp, u16);
-        put!(self.compression, u32);
-        put!(self.image_data_len, u32);
-        put!(self.print_resolution_x, u32);
-        put!(self.print_resolution_y, u32);
-        put!(self.palette_color_count, u32);
-        put!(self.important_colors, u32);
-        debug_assert_eq!(off, Self::BYTES);
-        buf
-    }
-}
-
 fn rgba_to_bgra(rgba: u32) -> u32 {
     // 0xAABBGGRR -> 0xAARRGGBB
 
@@ -175,38 +103,11 @@ pub fn screenshot_argb_be() -> alloc::vec::Vec<u32> {
 
 /// Take a screenshot, returning a valid bitmap file.
 pub fn screenshot_bmp() -> alloc::vec::Vec<u8> {
-    let mut screenshot_buffer = alloc::vec![0; BmpHeader::BYTES + NUM_PIXELS * BYTES_PER_PIXEL];
-
+    // Rows are already bottom-up, so flip back to the encoder's top-down
+    // coordinates. Little-endian 0xAARRGGBB is BGRA in memory.
     let payload = screenshot_argb_be();
-
-    let bmp_header = BmpHeader {
-        file_type: *b"BM",
-        file_size: BmpHeader::BYTES as u32 + payload.len() as u32 * 4,
-        reserved_1: 0,
-        reserved_2: 0,
-        image_data_start: BmpHeader::BYTES as u32,
-        dib_header_size: 40,
-        image_width: SCREEN_WIDTH,
-        image_height: SCREEN_HEIGHT,
-        color_planes: 1,
-        bpp: 32,
-        compression: 0,
-        image_data_len: payload.len() as u32 * 4,
-        print_resolution_x: 2835, // 72 DPI
-        print_resolution_y: 2835, // 72 DPI
-        palette_color_count: 0,
-        important_colors: 0,
-    };
-
-    screenshot_buffer[0..BmpHeader::BYTES].copy_from_slice(&bmp_header.to_bytes());
-
-    unsafe {
-        core::ptr::copy_nonoverlapping(
-            &payload[0] as *const _ as _,
-            &mut screenshot_buffer[BmpHeader::BYTES] as *mut u8,
-            NUM_PIXELS * BYTES_PER_PIXEL,
-        );
-    }
-
-    screenshot_buffer
+    crate::image::bmp::encode_with(SCREEN_WIDTH, SCREEN_HEIGHT, |x, y| {
+        let y_inv = SCREEN_HEIGHT - y - 1;
+        payload[(x + y_inv * SCREEN_WIDTH) as usize].to_le_bytes()
+    })
 }
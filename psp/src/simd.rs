@@ -8,9 +8,36 @@
 //! # Categories
 //!
 //! - **Vector operations**: lerp, dot product, normalize, cross product
-//! - **Matrix operations**: multiply, transpose, transform
+//! - **Matrix operations**: multiply, transpose, inverse, transform,
+//!   quaternion to matrix, conversion to and from [`ScePspFMatrix4`]
 //! - **Color operations**: RGBA blending, HSV↔RGB conversion
+//! - **Splines**: Cubic Bézier and Catmull-Rom evaluation and tangents
 //! - **Easing functions**: Quadratic, cubic, spring-damped interpolation
+//!
+//! # Writing your own VFPU code
+//!
+//! VFPU instructions raise an exception unless the running thread was
+//! created with `ThreadAttributes::VFPU` (the main thread and
+//! [`crate::thread`] defaults have it). The kernel preserves VFPU registers
+//! across thread switches, but not across calls within a thread: the
+//! functions here use matrices 0-2 as scratch, and `sceGum*` keeps its
+//! matrix stack in matrix 3. Wrap hand-written `vfpu_asm!` blocks in a
+//! [`VfpuContext`] to check the thread attribute and to save any matrices
+//! you clobber:
+//!
+//! ```ignore
+//! use psp::simd::{MatrixSet, VfpuContext};
+//!
+//! let _vfpu = VfpuContext::saving(MatrixSet::VMAT3)?;
+//! unsafe { psp::vfpu_asm!("vmidt.q M300") };
+//! // Matrix 3 is restored when `_vfpu` drops.
+//! ```
+
+use crate::sys::vfpu_context::Context;
+use crate::sys::{ScePspFMatrix4, ScePspFVector4};
+use core::marker::PhantomData;
+
+pub use crate::sys::vfpu_context::MatrixSet;
 
 // ── Vector Types ────────────────────────────────────────────────────
 
@@ -58,6 +85,118 @@ impl Mat4 {
     pub const ZERO: Self = Self([[0.0; 4]; 4]);
 }
 
+impl From<Mat4> for ScePspFMatrix4 {
+    fn from(m: Mat4) -> Self {
+        let column = |c: [f32; 4]| ScePspFVector4 {
+            x: c[0],
+            y: c[1],
+            z: c[2],
+            w: c[3],
+        };
+        Self {
+            x: column(m.0[0]),
+            y: column(m.0[1]),
+            z: column(m.0[2]),
+            w: column(m.0[3]),
+        }
+    }
+}
+
+impl From<ScePspFMatrix4> for Mat4 {
+    fn from(m: ScePspFMatrix4) -> Self {
+        let column = |v: ScePspFVector4| [v.x, v.y, v.z, v.w];
+        Self([column(m.x), column(m.y), column(m.z), column(m.w)])
+    }
+}
+
+// ── VFPU Context ────────────────────────────────────────────────────
+
+/// Error from [`VfpuContext`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VfpuError {
+    /// The current thread was not created with `ThreadAttributes::VFPU`.
+    NotEnabled,
+    /// Querying the thread's attributes failed with this SCE error code.
+    Kernel(i32),
+}
+
+impl core::fmt::Display for VfpuError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::NotEnabled => f.write_str("VFPU not enabled for the current thread"),
+            Self::Kernel(code) => write!(f, "thread status query failed: {:#010x}", *code as u32),
+        }
+    }
+}
+
+/// Returns `true` if the current thread may execute VFPU instructions.
+pub fn vfpu_enabled() -> Result<bool, VfpuError> {
+    use crate::sys::SceKernelThreadInfo;
+
+    let mut info = core::mem::MaybeUninit::<SceKernelThreadInfo>::uninit();
+    let ret = unsafe {
+        (&raw mut (*info.as_mut_ptr()).size).write(core::mem::size_of::<SceKernelThreadInfo>());
+        let thid = crate::sys::sceKernelGetThreadId();
+        crate::sys::sceKernelReferThreadStatus(crate::sys::SceUid(thid), info.as_mut_ptr())
+    };
+    if ret < 0 {
+        return Err(VfpuError::Kernel(ret));
+    }
+    // SAFETY: the kernel filled in the structure.
+    let info = unsafe { info.assume_init() };
+    Ok(info.attr & crate::sys::ThreadAttributes::VFPU.bits() != 0)
+}
+
+/// RAII guard for a block of user-written VFPU code.
+///
+/// Creating the guard checks that the current thread has VFPU access, and
+/// [`saving()`](Self::saving) additionally saves the given matrices so
+/// they are restored when the guard is dropped. The guard is `!Send`
+/// because the saved registers belong to the thread that created it.
+pub struct VfpuContext {
+    context: Context,
+    saved: MatrixSet,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl VfpuContext {
+    /// Check that the current thread has VFPU access, saving nothing.
+    pub fn new() -> Result<Self, VfpuError> {
+        Self::saving(MatrixSet::empty())
+    }
+
+    /// Check VFPU access and save `matrices`, restoring them on drop.
+    pub fn saving(matrices: MatrixSet) -> Result<Self, VfpuError> {
+        if !vfpu_enabled()? {
+            return Err(VfpuError::NotEnabled);
+        }
+        let mut context = Context::new();
+        // SAFETY: the thread has VFPU access, and with no in/out matrices
+        // `prepare` only saves the clobber set.
+        unsafe { context.prepare(MatrixSet::empty(), matrices) };
+        Ok(Self {
+            context,
+            saved: matrices,
+            _not_send: PhantomData,
+        })
+    }
+
+    /// The matrices that will be restored on drop.
+    pub fn saved(&self) -> MatrixSet {
+        self.saved
+    }
+}
+
+impl Drop for VfpuContext {
+    fn drop(&mut self) {
+        if !self.saved.is_empty() {
+            // SAFETY: still on the creating thread (the guard is !Send),
+            // which was checked for VFPU access.
+            unsafe { self.context.prepare(self.saved, MatrixSet::empty()) };
+        }
+    }
+}
+
 // ── Vector Operations ───────────────────────────────────────────────
 
 /// Linearly interpolate between two Vec4 values.
@@ -370,6 +509,86 @@ pub fn mat4_identity() -> Mat4 {
     out
 }
 
+/// Build a rotation-then-translation matrix from a unit quaternion
+/// `rotation` (`x, y, z, w`) and a `translation` (its `w` is ignored).
+///
+/// This is the usual form of an animation bone pose; pass the result to
+/// [`gu_ext::set_bone_matrix`](crate::gu_ext::set_bone_matrix).
+pub fn mat4_from_quat_translation(rotation: &Vec4, translation: &Vec4) -> Mat4 {
+    let [x, y, z, w] = rotation.0;
+    let (xx, yy, zz) = (x * x, y * y, z * z);
+    let (xy, xz, yz) = (x * y, x * z, y * z);
+    let (wx, wy, wz) = (w * x, w * y, w * z);
+    Mat4([
+        [1.0 - 2.0 * (yy + zz), 2.0 * (xy + wz), 2.0 * (xz - wy), 0.0],
+        [2.0 * (xy - wz), 1.0 - 2.0 * (xx + zz), 2.0 * (yz + wx), 0.0],
+        [2.0 * (xz + wy), 2.0 * (yz - wx), 1.0 - 2.0 * (xx + yy), 0.0],
+        [translation.x(), translation.y(), translation.z(), 1.0],
+    ])
+}
+
+/// Invert a 4x4 matrix, or `None` if it is singular.
+///
+/// Computed on the CPU by cofactor expansion; the VFPU has no inverse
+/// instruction. Use it to undo a general transform, such as the combined
+/// view-projection matrix when picking. For a rigid transform (rotation
+/// and translation only), transposing the rotation is cheaper.
+pub fn mat4_inverse(m: &Mat4) -> Option<Mat4> {
+    let [
+        [a00, a01, a02, a03],
+        [a10, a11, a12, a13],
+        [a20, a21, a22, a23],
+        [a30, a31, a32, a33],
+    ] = m.0;
+
+    // 2x2 determinants of the first two and last two columns.
+    let b00 = a00 * a11 - a01 * a10;
+    let b01 = a00 * a12 - a02 * a10;
+    let b02 = a00 * a13 - a03 * a10;
+    let b03 = a01 * a12 - a02 * a11;
+    let b04 = a01 * a13 - a03 * a11;
+    let b05 = a02 * a13 - a03 * a12;
+    let b06 = a20 * a31 - a21 * a30;
+    let b07 = a20 * a32 - a22 * a30;
+    let b08 = a20 * a33 - a23 * a30;
+    let b09 = a21 * a32 - a22 * a31;
+    let b10 = a21 * a33 - a23 * a31;
+    let b11 = a22 * a33 - a23 * a32;
+
+    let det = b00 * b11 - b01 * b10 + b02 * b09 + b03 * b08 - b04 * b07 + b05 * b06;
+    if det == 0.0 || !det.is_finite() {
+        return None;
+    }
+    let inv = 1.0 / det;
+
+    Some(Mat4([
+        [
+            (a11 * b11 - a12 * b10 + a13 * b09) * inv,
+            (a02 * b10 - a01 * b11 - a03 * b09) * inv,
+            (a31 * b05 - a32 * b04 + a33 * b03) * inv,
+            (a22 * b04 - a21 * b05 - a23 * b03) * inv,
+        ],
+        [
+            (a12 * b08 - a10 * b11 - a13 * b07) * inv,
+            (a00 * b11 - a02 * b08 + a03 * b07) * inv,
+            (a32 * b02 - a30 * b05 - a33 * b01) * inv,
+            (a20 * b05 - a22 * b02 + a23 * b01) * inv,
+        ],
+        [
+            (a10 * b10 - a11 * b08 + a13 * b06) * inv,
+            (a01 * b08 - a00 * b10 - a03 * b06) * inv,
+            (a30 * b04 - a31 * b02 + a33 * b00) * inv,
+            (a21 * b02 - a20 * b04 - a23 * b00) * inv,
+        ],
+        [
+            (a11 * b07 - a10 * b09 - a12 * b06) * inv,
+            (a00 * b09 - a01 * b07 + a02 * b06) * inv,
+            (a31 * b01 - a30 * b03 - a32 * b00) * inv,
+            (a20 * b03 - a21 * b01 + a22 * b00) * inv,
+        ],
+    ]))
+}
+
 // ── Color Operations ────────────────────────────────────────────────
 
 /// Blend two RGBA colors using alpha blending.
@@ -476,6 +695,119 @@ pub fn color_rgb_to_hsv(rgb: &Vec4) -> Vec4 {
     Vec4::new(h, s, v, a)
 }
 
+// ── Splines ─────────────────────────────────────────────────────────
+
+/// The curve family passed to [`spline_tangent`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplineKind {
+    /// Cubic Bézier: passes through `p0` and `p3`, pulled towards `p1`
+    /// and `p2`.
+    CubicBezier,
+    /// Uniform Catmull-Rom: passes through `p1` and `p2`, with `p0` and
+    /// `p3` shaping the tangents.
+    CatmullRom,
+}
+
+/// Evaluate a cubic Bézier curve at `t` in `0.0..=1.0`.
+pub fn cubic_bezier(p0: &Vec4, p1: &Vec4, p2: &Vec4, p3: &Vec4, t: f32) -> Vec4 {
+    let u = 1.0 - t;
+    weighted_sum(
+        [p0, p1, p2, p3],
+        [u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t],
+    )
+}
+
+/// Evaluate a uniform Catmull-Rom segment between `p1` and `p2` at `t` in
+/// `0.0..=1.0`.
+///
+/// Chaining segments over consecutive points gives a smooth path through
+/// every point.
+pub fn catmull_rom(p0: &Vec4, p1: &Vec4, p2: &Vec4, p3: &Vec4, t: f32) -> Vec4 {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    weighted_sum(
+        [p0, p1, p2, p3],
+        [
+            0.5 * (-t + 2.0 * t2 - t3),
+            0.5 * (2.0 - 5.0 * t2 + 3.0 * t3),
+            0.5 * (t + 4.0 * t2 - 3.0 * t3),
+            0.5 * (t3 - t2),
+        ],
+    )
+}
+
+/// First derivative of a spline at `t`, for orienting objects along it.
+///
+/// The result is not normalized; its length is the speed along the curve
+/// per unit of `t`.
+pub fn spline_tangent(
+    kind: SplineKind,
+    p0: &Vec4,
+    p1: &Vec4,
+    p2: &Vec4,
+    p3: &Vec4,
+    t: f32,
+) -> Vec4 {
+    let weights = match kind {
+        SplineKind::CubicBezier => {
+            let u = 1.0 - t;
+            [
+                -3.0 * u * u,
+                3.0 * u * u - 6.0 * u * t,
+                6.0 * u * t - 3.0 * t * t,
+                3.0 * t * t,
+            ]
+        },
+        SplineKind::CatmullRom => {
+            let t2 = t * t;
+            [
+                0.5 * (-1.0 + 4.0 * t - 3.0 * t2),
+                0.5 * (-10.0 * t + 9.0 * t2),
+                0.5 * (1.0 + 8.0 * t - 9.0 * t2),
+                0.5 * (-2.0 * t + 3.0 * t2),
+            ]
+        },
+    };
+    weighted_sum([p0, p1, p2, p3], weights)
+}
+
+/// `w[0] * p[0] + w[1] * p[1] + w[2] * p[2] + w[3] * p[3]` on the VFPU.
+fn weighted_sum(p: [&Vec4; 4], w: [f32; 4]) -> Vec4 {
+    let mut out = Vec4::ZERO;
+    let out_ptr = out.0.as_mut_ptr();
+    unsafe {
+        vfpu_asm!(
+            "lv.q C000, 0({p0})",
+            "lv.q C010, 0({p1})",
+            "lv.q C020, 0({p2})",
+            "lv.q C030, 0({p3})",
+            "mtv {w0}, S100",
+            "mtv {w1}, S101",
+            "mtv {w2}, S102",
+            "mtv {w3}, S103",
+            "vscl.q C000, C000, S100",
+            "vscl.q C010, C010, S101",
+            "vscl.q C020, C020, S102",
+            "vscl.q C030, C030, S103",
+            "vadd.q C000, C000, C010",
+            "vadd.q C020, C020, C030",
+            "vadd.q C000, C000, C020",
+            "sv.q C000, 0({out_ptr})",
+            p0 = in(reg) p[0].0.as_ptr(),
+            p1 = in(reg) p[1].0.as_ptr(),
+            p2 = in(reg) p[2].0.as_ptr(),
+            p3 = in(reg) p[3].0.as_ptr(),
+            w0 = in(reg) w[0].to_bits(),
+            w1 = in(reg) w[1].to_bits(),
+            w2 = in(reg) w[2].to_bits(),
+            w3 = in(reg) w[3].to_bits(),
+            out_ptr = in(reg) out_ptr,
+            options(nostack),
+        );
+    }
+    out
+}
+
 // ── Easing Functions ────────────────────────────────────────────────
 
 /// Quadratic ease-in-out.
@@ -7,7 +7,8 @@
 //! # Primitives
 //!
 //! - [`SpinMutex<T>`]: Exclusive-access spinlock (extracted from `debug.rs`)
-//! - [`SpinRwLock<T>`]: Reader-writer spinlock for shared-read / exclusive-write
+//! - [`SpinRwLock<T>`]: Writer-preferring reader-writer spinlock with
+//!   upgradable reads
 //! - [`SpscQueue<T, N>`]: Lock-free single-producer single-consumer ring buffer
 //! - [`UncachedBox<T>`]: Heap-allocated box in uncached (ME-accessible) memory
 
@@ -116,10 +117,21 @@ impl<T> Drop for SpinGuard<'_, T> {
 /// Allows multiple concurrent readers or one exclusive writer.
 /// Useful for the "UI reads state while IO writes" pattern.
 ///
+/// Writers take priority: once a writer is waiting, new readers spin until
+/// it has taken and released the lock, so readers that overlap every frame
+/// can't starve it. A consequence is that taking a second read lock on a
+/// thread that already holds one can deadlock against a waiting writer.
+///
+/// [`read_upgradable()`](Self::read_upgradable) takes a read lock that can
+/// later become a write lock without being released, for "check, then
+/// modify" updates. Only one upgradable lock exists at a time, alongside
+/// any number of plain readers.
+///
 /// The state is encoded in a single `AtomicU32`:
-/// - `0` = unlocked
-/// - `WRITER_BIT` set = write-locked
-/// - Otherwise, the value is the reader count
+/// - `WRITER_BIT`: write-locked
+/// - `WRITER_WAITING_BIT`: a writer is waiting; new readers hold off
+/// - `UPGRADABLE_BIT`: an upgradable read lock is held
+/// - The remaining low bits: the plain reader count
 ///
 /// # Example
 ///
@@ -135,14 +147,24 @@ impl<T> Drop for SpinGuard<'_, T> {
 /// // Writer (IO thread):
 /// let mut guard = STATE.write();
 /// guard.score += 10;
+///
+/// // Check, then modify without letting another writer in between:
+/// let guard = STATE.read_upgradable();
+/// if guard.score > guard.high_score {
+///     let mut guard = guard.upgrade();
+///     guard.high_score = guard.score;
+/// }
 /// ```
 pub struct SpinRwLock<T> {
-    /// 0 = unlocked, WRITER_BIT = write-locked, else reader count
+    /// Writer, writer-waiting and upgradable bits plus the reader count.
     state: AtomicU32,
     data: UnsafeCell<T>,
 }
 
 const WRITER_BIT: u32 = 1 << 31;
+const WRITER_WAITING_BIT: u32 = 1 << 30;
+const UPGRADABLE_BIT: u32 = 1 << 29;
+const READER_MASK: u32 = UPGRADABLE_BIT - 1;
 
 // SAFETY: SpinRwLock provides reader/writer exclusion via atomic state.
 unsafe impl<T: Send> Send for SpinRwLock<T> {}
@@ -157,33 +179,26 @@ impl<T> SpinRwLock<T> {
         }
     }
 
-    /// Acquire a read lock, spinning until no writer holds the lock.
+    /// Acquire a read lock, spinning while a writer holds the lock or is
+    /// waiting for it.
     pub fn read(&self) -> ReadGuard<'_, T> {
         loop {
-            let s = self.state.load(Ordering::Relaxed);
-            // Cannot acquire read lock while a writer holds it
-            if s & WRITER_BIT != 0 {
-                core::hint::spin_loop();
-                continue;
-            }
-            // Try to increment the reader count
-            if self
-                .state
-                .compare_exchange_weak(s, s + 1, Ordering::Acquire, Ordering::Relaxed)
-                .is_ok()
-            {
-                return ReadGuard { lock: self };
+            if let Some(guard) = self.try_read() {
+                return guard;
             }
             core::hint::spin_loop();
         }
     }
 
     /// Try to acquire a read lock without spinning.
+    ///
+    /// Fails while a writer holds the lock or is waiting for it.
     pub fn try_read(&self) -> Option<ReadGuard<'_, T>> {
         let s = self.state.load(Ordering::Relaxed);
-        if s & WRITER_BIT != 0 {
+        if s & (WRITER_BIT | WRITER_WAITING_BIT) != 0 {
             return None;
         }
+        // Try to increment the reader count
         if self
             .state
             .compare_exchange(s, s + 1, Ordering::Acquire, Ordering::Relaxed)
@@ -195,25 +210,51 @@ impl<T> SpinRwLock<T> {
         }
     }
 
-    /// Acquire a write lock, spinning until all readers and writers release.
-    pub fn write(&self) -> WriteGuard<'_, T> {
+    /// Acquire an upgradable read lock, spinning while a writer or another
+    /// upgradable reader holds the lock, or a writer is waiting.
+    pub fn read_upgradable(&self) -> UpgradableReadGuard<'_, T> {
         loop {
-            if self
-                .state
-                .compare_exchange_weak(0, WRITER_BIT, Ordering::Acquire, Ordering::Relaxed)
-                .is_ok()
-            {
-                return WriteGuard { lock: self };
+            if let Some(guard) = self.try_read_upgradable() {
+                return guard;
             }
             core::hint::spin_loop();
         }
     }
 
+    /// Try to acquire an upgradable read lock without spinning.
+    pub fn try_read_upgradable(&self) -> Option<UpgradableReadGuard<'_, T>> {
+        let s = self.state.load(Ordering::Relaxed);
+        if s & (WRITER_BIT | WRITER_WAITING_BIT | UPGRADABLE_BIT) != 0 {
+            return None;
+        }
+        if self
+            .state
+            .compare_exchange(s, s | UPGRADABLE_BIT, Ordering::Acquire, Ordering::Relaxed)
+            .is_ok()
+        {
+            Some(UpgradableReadGuard { lock: self })
+        } else {
+            None
+        }
+    }
+
+    /// Acquire a write lock, spinning until all readers and writers release.
+    ///
+    /// While spinning, the writer-waiting bit keeps new readers out.
+    pub fn write(&self) -> WriteGuard<'_, T> {
+        self.acquire_write(WRITER_WAITING_BIT);
+        WriteGuard { lock: self }
+    }
+
     /// Try to acquire a write lock without spinning.
     pub fn try_write(&self) -> Option<WriteGuard<'_, T>> {
+        let s = self.state.load(Ordering::Relaxed);
+        if s & !WRITER_WAITING_BIT != 0 {
+            return None;
+        }
         if self
             .state
-            .compare_exchange(0, WRITER_BIT, Ordering::Acquire, Ordering::Relaxed)
+            .compare_exchange(s, WRITER_BIT, Ordering::Acquire, Ordering::Relaxed)
             .is_ok()
         {
             Some(WriteGuard { lock: self })
@@ -221,6 +262,33 @@ impl<T> SpinRwLock<T> {
             None
         }
     }
+
+    /// Spin until every bit of the state other than `ignore` is clear,
+    /// then replace the state with `WRITER_BIT`, which also clears the
+    /// writer-waiting bit. Other waiting writers set it again on their
+    /// next spin.
+    fn acquire_write(&self, ignore: u32) {
+        loop {
+            let s = self.state.load(Ordering::Relaxed);
+            if s & !ignore == 0 {
+                if self
+                    .state
+                    .compare_exchange_weak(s, WRITER_BIT, Ordering::Acquire, Ordering::Relaxed)
+                    .is_ok()
+                {
+                    return;
+                }
+            } else if s & WRITER_WAITING_BIT == 0 {
+                let _ = self.state.compare_exchange_weak(
+                    s,
+                    s | WRITER_WAITING_BIT,
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                );
+            }
+            core::hint::spin_loop();
+        }
+    }
 }
 
 /// RAII read guard for [`SpinRwLock`].
@@ -242,6 +310,62 @@ impl<T> Drop for ReadGuard<'_, T> {
     }
 }
 
+/// RAII upgradable read guard for [`SpinRwLock`].
+///
+/// Reads like a [`ReadGuard`]; [`upgrade()`](Self::upgrade) turns it into
+/// a [`WriteGuard`] without releasing the lock in between.
+pub struct UpgradableReadGuard<'a, T> {
+    lock: &'a SpinRwLock<T>,
+}
+
+impl<'a, T> UpgradableReadGuard<'a, T> {
+    /// Wait for the plain readers to leave and take the write lock.
+    ///
+    /// No other writer can get in first: writers can't acquire while this
+    /// guard exists, and new readers are held off while it waits.
+    pub fn upgrade(self) -> WriteGuard<'a, T> {
+        let lock = self.lock;
+        core::mem::forget(self);
+        lock.acquire_write(UPGRADABLE_BIT | WRITER_WAITING_BIT);
+        WriteGuard { lock }
+    }
+
+    /// Try to take the write lock without spinning, handing the guard back
+    /// if plain readers still hold the lock.
+    pub fn try_upgrade(self) -> Result<WriteGuard<'a, T>, Self> {
+        let s = self.lock.state.load(Ordering::Relaxed);
+        if s & READER_MASK == 0
+            && self
+                .lock
+                .state
+                .compare_exchange(s, WRITER_BIT, Ordering::Acquire, Ordering::Relaxed)
+                .is_ok()
+        {
+            let lock = self.lock;
+            core::mem::forget(self);
+            Ok(WriteGuard { lock })
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl<T> core::ops::Deref for UpgradableReadGuard<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        // SAFETY: A read lock is held; no writer can exist.
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T> Drop for UpgradableReadGuard<'_, T> {
+    fn drop(&mut self) {
+        self.lock
+            .state
+            .fetch_and(!UPGRADABLE_BIT, Ordering::Release);
+    }
+}
+
 /// RAII write guard for [`SpinRwLock`].
 pub struct WriteGuard<'a, T> {
     lock: &'a SpinRwLock<T>,
@@ -264,7 +388,8 @@ impl<T> core::ops::DerefMut for WriteGuard<'_, T> {
 
 impl<T> Drop for WriteGuard<'_, T> {
     fn drop(&mut self) {
-        self.lock.state.store(0, Ordering::Release);
+        // Keep the writer-waiting bit of any writer spinning meanwhile.
+        self.lock.state.fetch_and(!WRITER_BIT, Ordering::Release);
     }
 }
 
@@ -6,7 +6,7 @@ bitflags::bitflags! {
     /// Home, Note, Screen, VolUp, VolDown, Disc, WlanUp, Remote, and MS can only be
     /// read in kernel mode.
     #[repr(transparent)]
-    #[derive(Clone, Copy, Debug, Default)]
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
     pub struct CtrlButtons: u32 {
         /// Select button.
         const SELECT = 0x000001;
@@ -80,6 +80,55 @@ pub struct SceCtrlData {
     pub rsrv: [u8; 6],
 }
 
+/// Extended controller data, including the right stick and pressure
+/// sensitivity reported by controllers paired with a PSP Go.
+///
+/// The first fields mirror [`SceCtrlData`]; on a PSP without an external
+/// controller the extra fields read as zero.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SceCtrlData2 {
+    /// The current read frame.
+    pub timestamp: u32,
+    /// Bit mask containing zero or more of `CtrlButtons`.
+    pub buttons: CtrlButtons,
+    /// Left analogue stick, X axis.
+    pub lx: u8,
+    /// Left analogue stick, Y axis.
+    pub ly: u8,
+    /// Right analogue stick, X axis.
+    pub rx: u8,
+    /// Right analogue stick, Y axis.
+    pub ry: u8,
+    /// Reserved.
+    pub rsrv: [u8; 4],
+    /// D-pad pressure sensitivity.
+    pub d_pad_sense_a: i32,
+    pub d_pad_sense_b: i32,
+    /// Face button pressure sensitivity.
+    pub g_pad_sense_a: i32,
+    pub g_pad_sense_b: i32,
+    /// Axis sensor readings.
+    pub axis_sense_a: i32,
+    pub axis_sense_b: i32,
+    /// Tilt sensor readings.
+    pub tilt_a: i32,
+    pub tilt_b: i32,
+}
+
+/// How [`sceCtrlSetButtonIntercept`] treats the buttons in its mask.
+#[cfg(feature = "kernel")]
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CtrlInterceptMode {
+    /// Report the buttons as they are (removes an intercept).
+    Normal = 0,
+    /// Always report the buttons as released.
+    Mask = 1,
+    /// Always report the buttons as pressed.
+    Force = 2,
+}
+
 #[repr(C)]
 #[derive(Debug, Clone, Copy, Default)]
 pub struct SceCtrlLatch {
@@ -200,4 +249,66 @@ psp_extern! {
     ///
     /// < 0 on error.
     pub fn sceCtrlGetIdleCancelThreshold(idlereset: *mut i32, idleback: *mut i32) -> i32;
+
+    #[psp(0x5A36B1C2)]
+    /// Read the latest extended controller data without waiting.
+    ///
+    /// # Parameters
+    ///
+    /// - `port`: Controller port; 0 is the built-in controls, 1 and up are
+    ///   external controllers on a PSP Go.
+    /// - `pad_data`: Pointer to `count` `SceCtrlData2` buffers.
+    /// - `count`: Number of buffers to read.
+    ///
+    /// # Return value
+    ///
+    /// Number of buffers read, < 0 on error.
+    pub fn sceCtrlPeekBufferPositive2(port: i32, pad_data: *mut SceCtrlData2, count: i32) -> i32;
+
+    #[psp(0x239A6BA7)]
+    /// Like `sceCtrlPeekBufferPositive2`, but with the button bits
+    /// inverted (a set bit means released).
+    pub fn sceCtrlPeekBufferNegative2(port: i32, pad_data: *mut SceCtrlData2, count: i32) -> i32;
+}
+
+// The driver library also exports the sampling and buffer functions under
+// the same NIDs as `sceCtrl`. Their names would clash with the user-mode
+// imports above, so kernel code resolves them with
+// `psp::hook::find_function` instead (see `psp::input::kernel`).
+#[cfg(feature = "kernel")]
+psp_extern! {
+    #![name = "sceCtrl_driver"]
+    #![flags = 0x0001]
+    #![version = (0x00, 0x00)]
+
+    #[psp(0x7CA723DC)]
+    /// Override the state of some buttons for every reader of the
+    /// controller, including games.
+    ///
+    /// # Kernel Mode Required
+    ///
+    /// This function requires `feature = "kernel"` and `psp::module_kernel!()`.
+    ///
+    /// # Parameters
+    ///
+    /// - `mask`: `CtrlButtons` bits to apply `mode` to.
+    /// - `mode`: Whether the buttons are reported normally, as released,
+    ///   or as pressed.
+    ///
+    /// # Return value
+    ///
+    /// The previous mode of the buttons, < 0 on error.
+    pub fn sceCtrlSetButtonIntercept(mask: u32, mode: CtrlInterceptMode) -> i32;
+
+    #[psp(0x5E77BC8A)]
+    /// Get the intercept mode of the buttons in `mask`.
+    ///
+    /// # Kernel Mode Required
+    ///
+    /// This function requires `feature = "kernel"` and `psp::module_kernel!()`.
+    ///
+    /// # Return value
+    ///
+    /// A `CtrlInterceptMode` value, < 0 on error.
+    pub fn sceCtrlGetButtonIntercept(mask: u32) -> i32;
 }
@@ -11,7 +11,7 @@ pub enum DisplayMode {
     // TODO: What are the other modes?
 }
 
-#[derive(Debug, Copy, Clone, TryFromPrimitive)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, TryFromPrimitive)]
 #[repr(u32)]
 /// Framebuffer pixel formats.
 pub enum DisplayPixelFormat {
@@ -143,3 +143,44 @@ psp_extern! {
     /// Test whether vblank is active
     pub fn sceDisplayIsVblank() -> i32;
 }
+
+#[cfg(feature = "kernel")]
+psp_extern! {
+    #![name = "sceDisplay_driver"]
+    #![flags = 0x0001]
+    #![version = (0x00, 0x00)]
+
+    #[psp(0x9E3C6DC6)]
+    /// Set the LCD backlight brightness.
+    ///
+    /// # Kernel Mode Required
+    ///
+    /// This function requires `feature = "kernel"` and `psp::module_kernel!()`.
+    ///
+    /// # Parameters
+    ///
+    /// - `level`: Brightness, 0-100.
+    /// - `unk1`: Unknown, pass 0.
+    ///
+    /// # Return Value
+    ///
+    /// < 0 on error.
+    pub fn sceDisplaySetBrightness(level: i32, unk1: i32) -> i32;
+
+    #[psp(0x31C4BAA8)]
+    /// Get the LCD backlight brightness.
+    ///
+    /// # Kernel Mode Required
+    ///
+    /// This function requires `feature = "kernel"` and `psp::module_kernel!()`.
+    ///
+    /// # Parameters
+    ///
+    /// - `level`: Receives the brightness, 0-100.
+    /// - `unk1`: Receives an unknown value.
+    ///
+    /// # Return Value
+    ///
+    /// < 0 on error.
+    pub fn sceDisplayGetBrightness(level: *mut i32, unk1: *mut i32) -> i32;
+}
@@ -212,6 +212,7 @@ pub enum ShadingModel {
 }
 
 /// Logical operation
+#[derive(Copy, Clone, Debug)]
 #[repr(u32)]
 pub enum LogicalOperation {
     Clear = 0,
@@ -460,6 +461,7 @@ pub enum BlendFactor {
 }
 
 /// Stencil Operations
+#[derive(Copy, Clone, Debug)]
 #[repr(u32)]
 pub enum StencilOperation {
     /// Keeps the current value
@@ -510,7 +512,7 @@ pub enum LightType {
 
 /// Contexts
 #[repr(u32)]
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum GuContextType {
     Direct = 0,
     Call = 1,
@@ -1101,6 +1103,14 @@ pub unsafe extern "C" fn sceGuDepthMask(mask: i32) {
     send_command_i(GeCommand::ZWriteDisable, mask);
 }
 
+/// Set the offset added to the depth range
+///
+/// Applied by `sceGuDepthRange`; useful to push decals or outlines in
+/// front of coplanar geometry.
+///
+/// # Parameters
+///
+/// - `offset`: Depth offset (0-65535)
 #[allow(non_snake_case)]
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn sceGuDepthOffset(offset: i32) {
@@ -1716,6 +1726,53 @@ pub unsafe extern "C" fn sceGuFinish() -> i32 {
     size as i32
 }
 
+/// Leave the current list without terminating it and go back to the
+/// parent context, as `sceGuFinish` does. The list must not be run.
+pub(crate) unsafe fn abandon_list() {
+    CURR_CONTEXT = (*LIST).parent_context;
+    LIST = &mut CONTEXTS[CURR_CONTEXT as usize].list;
+}
+
+/// Context that `sceGu*` calls are currently recorded into.
+pub(crate) unsafe fn current_context() -> GuContextType {
+    CURR_CONTEXT
+}
+
+/// Start of the list `context` was last started with.
+pub(crate) unsafe fn list_start(context: GuContextType) -> *mut u32 {
+    CONTEXTS[context as usize].list.start
+}
+
+/// Run `f` with `context`'s list as the target of `sceGu*` calls, then
+/// switch back.
+///
+/// Returns `None` without running `f` if `context` isn't open: the
+/// current context or one it was started from.
+pub(crate) unsafe fn with_context<R>(context: GuContextType, f: impl FnOnce() -> R) -> Option<R> {
+    if context == CURR_CONTEXT {
+        return Some(f());
+    }
+    let mut open = CURR_CONTEXT;
+    // At most three contexts can be nested.
+    for _ in 0..3 {
+        open = CONTEXTS[open as usize].list.parent_context;
+        if open == context {
+            break;
+        }
+    }
+    if open != context || CONTEXTS[context as usize].list.start.is_null() {
+        return None;
+    }
+
+    let (saved_context, saved_list) = (CURR_CONTEXT, LIST);
+    CURR_CONTEXT = context;
+    LIST = &mut CONTEXTS[context as usize].list;
+    let result = f();
+    CURR_CONTEXT = saved_context;
+    LIST = saved_list;
+    Some(result)
+}
+
 /// Finish current display list and go back to the parent context, sending
 /// argument id for the finish callback.
 ///
@@ -1802,6 +1859,48 @@ pub unsafe extern "C" fn sceGuCheckList() -> i32 {
     (*LIST).current.offset_from((*LIST).start) as i32
 }
 
+/// Start and length in words of the most recent list built in `context`.
+///
+/// The bounds stay valid after `sceGuFinish` until the next `sceGuStart`
+/// on the same context.
+pub(crate) unsafe fn last_list(context: GuContextType) -> (*const u32, usize) {
+    let list = CONTEXTS[context as usize].list;
+    if list.start.is_null() {
+        return (list.start, 0);
+    }
+    (list.start, list.current.offset_from(list.start) as usize)
+}
+
+/// Current draw buffer as (VRAM-relative address, width, pixel format).
+pub(crate) unsafe fn draw_buffer_config() -> (u32, i32, DisplayPixelFormat) {
+    (
+        DRAW_BUFFER.frame_buffer as u32,
+        DRAW_BUFFER.frame_width,
+        DRAW_BUFFER.pixel_size,
+    )
+}
+
+/// Callback ID the GU registered with `sceGeSetCallback` in `sceGuInit`,
+/// for lists enqueued outside the GU.
+pub(crate) unsafe fn ge_callback_id() -> i32 {
+    SETTINGS.ge_callback_id
+}
+
+/// Scissor state of the current context as (enabled, `sceGuScissor`
+/// arguments that would set the same region).
+pub(crate) unsafe fn scissor_state() -> (bool, [i32; 4]) {
+    let context = &CONTEXTS[CURR_CONTEXT as usize];
+    (
+        context.scissor_enable != 0,
+        [
+            context.scissor_start[0],
+            context.scissor_start[1],
+            context.scissor_end[0] + 1,
+            context.scissor_end[1] + 1,
+        ],
+    )
+}
+
 /// Send a list to the GE directly
 ///
 /// # Parameters
@@ -1,13 +1,21 @@
 //! Headphone Remote
 
 bitflags::bitflags! {
+    /// Headphone remote keys.
     #[repr(transparent)]
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
     pub struct HprmKey: u32 {
+        /// Play/pause button.
         const PLAY_PAUSE  = 0x1;
+        /// Forward button.
         const FORWARD     = 0x4;
+        /// Back button.
         const BACK        = 0x8;
+        /// Volume up button.
         const VOL_UP      = 0x10;
+        /// Volume down button.
         const VOL_DOWN    = 0x20;
+        /// Hold switch.
         const HOLD        = 0x80;
     }
 }
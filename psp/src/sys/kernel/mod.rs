@@ -87,6 +87,7 @@ impl From<i32> for SceUid {
 /// Use `SceKernelPrimaryUserPartition` (2) for user-mode allocations.
 // https://github.com/uofw/uofw/blob/f099b78dc0937df4e7346e2e417b63f471f8a3af/include/sysmem_user.h#L12
 #[repr(i32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum SceSysMemPartitionId {
     /// Unknown/invalid partition.
     SceKernelUnknownPartition = 0,
@@ -1203,6 +1204,88 @@ psp_extern! {
     ) -> i32;
 }
 
+// ── System Memory (kernel mode only) ────────────────────────────────
+
+#[cfg(feature = "kernel")]
+psp_extern! {
+    #![name = "SysMemForKernel"]
+    #![flags = 0x0001]
+    #![version = (0x00, 0x00)]
+
+    #[psp(0x6373995D)]
+    /// Get the hardware model of the console.
+    ///
+    /// # Kernel Mode Required
+    ///
+    /// This function requires `feature = "kernel"` and `psp::module_kernel!()`.
+    ///
+    /// # Return Value
+    ///
+    /// The zero-based motherboard generation: 0 = PSP-1000, 1 = PSP-2000,
+    /// 2/3/6/8 = PSP-3000 revisions, 4 = PSP Go, 10 = PSP Street (E1000).
+    pub fn sceKernelGetModel() -> i32;
+
+    #[psp(0x55A40B2C)]
+    /// Get the address range and attributes of a memory partition.
+    ///
+    /// # Kernel Mode Required
+    ///
+    /// This function requires `feature = "kernel"` and `psp::module_kernel!()`.
+    ///
+    /// # Parameters
+    ///
+    /// - `pid`: The partition ID, one of `SceSysMemPartitionId`.
+    /// - `info`: Pointer to a `PspSysmemPartitionInfo` structure with `size`
+    ///   set.
+    ///
+    /// # Return Value
+    ///
+    /// 0 on success, < 0 on error (e.g. no such partition on this model).
+    pub fn sceKernelQueryMemoryPartitionInfo(
+        pid: SceSysMemPartitionId,
+        info: *mut PspSysmemPartitionInfo,
+    ) -> i32;
+
+    #[psp(0xE6581468)]
+    /// Get the total free memory in a partition.
+    ///
+    /// # Kernel Mode Required
+    ///
+    /// This function requires `feature = "kernel"` and `psp::module_kernel!()`.
+    ///
+    /// # Return Value
+    ///
+    /// The free size in bytes.
+    pub fn sceKernelPartitionTotalFreeMemSize(pid: SceSysMemPartitionId) -> usize;
+
+    #[psp(0x9697CD32)]
+    /// Get the largest free block in a partition.
+    ///
+    /// # Kernel Mode Required
+    ///
+    /// This function requires `feature = "kernel"` and `psp::module_kernel!()`.
+    ///
+    /// # Return Value
+    ///
+    /// The size of the largest free block in bytes.
+    pub fn sceKernelPartitionMaxFreeMemSize(pid: SceSysMemPartitionId) -> usize;
+}
+
+/// Memory partition information from `sceKernelQueryMemoryPartitionInfo`.
+#[cfg(feature = "kernel")]
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct PspSysmemPartitionInfo {
+    /// Size of the structure (should be set prior to the call).
+    pub size: usize,
+    /// Start address of the partition.
+    pub start_addr: u32,
+    /// Size of the partition in bytes.
+    pub mem_size: u32,
+    /// Access attributes.
+    pub attr: u32,
+}
+
 psp_extern! {
     #![name = "StdioForUser"]
     #![flags = 0x4001]
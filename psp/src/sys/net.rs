@@ -1691,6 +1691,26 @@ pub type HttpPasswordCB = Option<
     ) -> i32,
 >;
 
+bitflags::bitflags! {
+    /// Certificate checks performed on https connections.
+    #[repr(transparent)]
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    pub struct HttpsFlags: u32 {
+        /// Verify the server's certificate chain.
+        const SERVER_VERIFY = 0x01;
+        /// Send a client certificate.
+        const CLIENT_VERIFY = 0x02;
+        /// Check that the certificate's common name matches the host.
+        const CN_CHECK = 0x04;
+        /// Reject expired certificates.
+        const NOT_AFTER_CHECK = 0x08;
+        /// Reject certificates that are not yet valid.
+        const NOT_BEFORE_CHECK = 0x10;
+        /// Require a certificate signed by a known CA.
+        const KNOWN_CA_CHECK = 0x20;
+    }
+}
+
 psp_extern! {
     #![name = "sceHttp"]
     #![flags = 0x0009]
@@ -2152,6 +2172,30 @@ psp_extern! {
         unknown2: i32,
     ) -> i32;
 
+    #[psp(0xBAC31BF1)]
+    /// Enable certificate checks for all https connections.
+    ///
+    /// # Parameters
+    ///
+    /// - `flags`: Checks to enable
+    ///
+    /// # Return Value
+    ///
+    /// 0 on success, < 0 on error.
+    pub fn sceHttpsEnableOption(flags: HttpsFlags) -> i32;
+
+    #[psp(0xB3FAF831)]
+    /// Disable certificate checks for all https connections.
+    ///
+    /// # Parameters
+    ///
+    /// - `flags`: Checks to disable
+    ///
+    /// # Return Value
+    ///
+    /// 0 on success, < 0 on error.
+    pub fn sceHttpsDisableOption(flags: HttpsFlags) -> i32;
+
     #[psp(0xAE948FEE)]
     pub fn sceHttpDisableAuth(id: i32) -> i32;
 
@@ -1,3 +1,5 @@
+/// The console's OpenPSID: a 16-byte identifier that is unique to each
+/// console and survives firmware updates.
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 pub struct OpenPSID {
@@ -10,5 +12,14 @@ psp_extern! {
     #![version = (0x00, 0x11)]
 
     #[psp(0xC69BEBCE)]
+    /// Read the console's OpenPSID.
+    ///
+    /// # Parameters
+    ///
+    /// - `openpsid`: Pointer to the buffer that receives the ID.
+    ///
+    /// # Return Value
+    ///
+    /// 0 on success, < 0 on error.
     pub fn sceOpenPSIDGetOpenPSID(openpsid: *mut OpenPSID) -> i32;
 }
@@ -211,6 +211,14 @@ psp_extern! {
     /// frequency as float
     pub fn scePowerGetBusClockFrequencyFloat() -> f32;
 
+    #[psp(0x34F9C463)]
+    /// Get PLL frequency as Integer
+    ///
+    /// # Return Value
+    ///
+    /// Frequency as an integer
+    pub fn scePowerGetPllClockFrequencyInt() -> i32;
+
     #[psp(0x737486F2)]
     /// Set Clock Frequencies
     ///
@@ -2,7 +2,7 @@ use num_enum::TryFromPrimitive;
 
 /// PSP Time structure
 #[repr(C)]
-#[derive(Debug, Copy, Clone, Default)]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
 pub struct ScePspDateTime {
     pub year: u16,
     pub month: u16,
@@ -424,3 +424,26 @@ psp_extern! {
         p_sz_date_time: *const u8,
     ) -> i32;
 }
+
+#[cfg(feature = "kernel")]
+psp_extern! {
+    #![name = "sceRtc_driver"]
+    #![flags = 0x0001]
+    #![version = (0x00, 0x00)]
+
+    #[psp(0x9763C138)]
+    /// Set the system clock.
+    ///
+    /// # Kernel Mode Required
+    ///
+    /// This function requires `feature = "kernel"` and `psp::module_kernel!()`.
+    ///
+    /// # Parameters
+    ///
+    /// - `tick`: pointer to the new UTC tick count
+    ///
+    /// # Return Value
+    ///
+    /// 0 on success, < 0 on error
+    pub fn sceRtcSetCurrentTick(tick: *const u64) -> i32;
+}
@@ -31,6 +31,9 @@ pub const NID_SYSCON_IS_AC_SUPPLIED: u32 = 0xE0DDFE18;
 /// NID for `sceSysconCtrlUsbPower` — **resolves to getter stub on 6.61**.
 pub const NID_SYSCON_CTRL_USB_POWER: u32 = 0xC8D97773;
 
+/// NID for `sceSysconCtrlLED`.
+pub const NID_SYSCON_CTRL_LED: u32 = 0x18BFBE65;
+
 /// NID for `sceSysconCommonWrite` — raw Syscon SPI SET command.
 pub const NID_SYSCON_COMMON_WRITE: u32 = 0x7EC5A957;
 
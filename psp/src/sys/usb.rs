@@ -5,6 +5,9 @@ use core::ffi::c_void;
 pub const USB_CAM_PID: i32 = 0x282;
 
 pub const USB_BUS_DRIVER_NAME: &str = "USBBusDriver";
+/// Accessory base driver, started after the bus driver and before the
+/// camera drivers.
+pub const USB_ACC_DRIVER_NAME: &str = "USBAccBaseDriver";
 pub const USB_CAM_DRIVER_NAME: &str = "USBCamDriver";
 pub const USB_CAM_MIC_DRIVER_NAME: &str = "USBCamMicDriver";
 pub const USB_STOR_DRIVER_NAME: &str = "USBStor_Driver";
@@ -240,7 +243,7 @@ pub struct UsbCamSetupVideoExParam {
 ///
 /// DO NOT use on `sceUsbCamSetupStillEx` & `sceUsbCamSetupVideoEx`
 #[repr(i32)]
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum UsbCamResolution {
     Px160_120 = 0,
     Px176_144 = 1,
@@ -256,7 +259,7 @@ pub enum UsbCamResolution {
 /// Resolutions for `sceUsbCamSetupStillEx` & `sceUsbCamSetupVideoEx`
 ///
 /// DO NOT use on `sceUsbCamSetupStill` & `sceUsbCamSetupVideo`
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 #[repr(i32)]
 pub enum UsbCamResolutionEx {
     Px160_120 = 0,
@@ -292,7 +295,7 @@ pub enum UsbCamDelay {
 
 /// Usbcam framerates
 #[repr(i32)]
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum UsbCamFrameRate {
     /// 3.75 FPS
     Fps3_75 = 0,
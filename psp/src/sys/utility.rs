@@ -141,7 +141,7 @@ pub enum SystemParamLanguage {
 
 #[repr(u32)]
 #[derive(Debug, Clone, Copy)]
-/// #9 seems to be Region or maybe X/O button swap.
+/// #9 is the X/O button assignment: 0 confirms with Circle, 1 with Cross.
 /// It doesn't exist on JAP v1.0
 /// is 1 on NA v1.5s
 /// is 0 on JAP v1.5s
@@ -218,12 +218,17 @@ pub enum Module {
     NetAdhoc,
     NetInet,
     NetParseUri,
+    NetParseHttp,
     NetHttp,
     NetSsl,
 
     UsbPspCm = 0x200,
+    UsbAcc,
+    /// Requires UsbAcc loading first
     UsbMic,
+    /// Requires UsbAcc loading first
     UsbCam,
+    /// Requires UsbAcc loading first
     UsbGps,
 
     AvCodec = 0x300,
@@ -388,6 +393,36 @@ pub enum UtilitySavedataMode {
     ListSave,
     ListDelete,
     Delete,
+    /// Query free space and the size of a save (firmware 2.00+).
+    Sizes,
+    /// Delete a save without UI.
+    AutoDelete,
+    /// Delete a save after confirmation.
+    SingleDelete,
+    /// List existing saves.
+    List,
+    /// List the files in a save.
+    Files,
+    /// Create the save directory if needed and write an encrypted file.
+    MakeDataSecure,
+    /// Create the save directory if needed and write a file.
+    MakeData,
+    /// Read an encrypted file from an existing save.
+    ReadDataSecure,
+    /// Read a file from an existing save.
+    ReadData,
+    /// Write an encrypted file into an existing save.
+    WriteDataSecure,
+    /// Write a file into an existing save.
+    WriteData,
+    /// Delete an encrypted file from a save.
+    EraseSecure,
+    /// Delete a file from a save.
+    Erase,
+    /// Delete a whole save directory.
+    DeleteData,
+    /// Query the size needed to write a save.
+    GetSize,
 }
 
 #[repr(u32)]
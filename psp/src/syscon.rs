@@ -38,6 +38,7 @@ type GetI32Fn = unsafe extern "C" fn(*mut i32) -> i32;
 type IsAcFn = unsafe extern "C" fn() -> i32;
 type CommonWriteFn = unsafe extern "C" fn(i32, *const u8, i32) -> i32;
 type CommonReadFn = unsafe extern "C" fn(i32, *mut u8, i32) -> i32;
+type CtrlLedFn = unsafe extern "C" fn(u32, u32) -> i32;
 
 /// Stores a resolved function pointer as an `AtomicUsize` (0 = not resolved).
 struct AtomicFnPtr(AtomicUsize);
@@ -68,6 +69,7 @@ static GET_BATTERY_TEMP: AtomicFnPtr = AtomicFnPtr::new();
 static IS_AC_SUPPLIED: AtomicFnPtr = AtomicFnPtr::new();
 static COMMON_WRITE: AtomicFnPtr = AtomicFnPtr::new();
 static COMMON_READ: AtomicFnPtr = AtomicFnPtr::new();
+static CTRL_LED: AtomicFnPtr = AtomicFnPtr::new();
 static INITIALIZED: AtomicBool = AtomicBool::new(false);
 
 /// Resolve a NID from Syscon driver, trying multiple module names.
@@ -109,6 +111,7 @@ pub unsafe fn init() -> u32 {
     try_resolve!(nids::NID_SYSCON_IS_AC_SUPPLIED, IS_AC_SUPPLIED);
     try_resolve!(nids::NID_SYSCON_COMMON_WRITE, COMMON_WRITE);
     try_resolve!(nids::NID_SYSCON_COMMON_READ, COMMON_READ);
+    try_resolve!(nids::NID_SYSCON_CTRL_LED, CTRL_LED);
 
     INITIALIZED.store(true, Ordering::Release);
     count
@@ -174,6 +177,22 @@ pub fn is_ac_connected() -> Option<bool> {
     Some(unsafe { f() } == 1)
 }
 
+/// Turn a front-panel LED on or off through `sceSysconCtrlLED`.
+///
+/// Works for every [`Led`](crate::hw::Led); [`hw::led_set`] switches
+/// the GPIO-driven ones without Syscon.
+///
+/// [`hw::led_set`]: crate::hw::led_set
+pub fn set_led(led: crate::hw::Led, on: bool) -> Option<Result<(), SysconError>> {
+    let f: CtrlLedFn = unsafe { core::mem::transmute(CTRL_LED.load()?) };
+    let ret = unsafe { f(led.syscon_index(), on as u32) };
+    Some(if ret < 0 {
+        Err(SysconError(ret))
+    } else {
+        Ok(())
+    })
+}
+
 /// Send a raw Syscon GET command and read the response.
 ///
 /// # Warning
@@ -1,8 +1,9 @@
 //! System parameter queries for the PSP.
 //!
 //! Read system-level settings like language, nickname, date/time format,
-//! timezone, and daylight saving status. These are configured by the user
-//! in the PSP's System Settings menu.
+//! timezone, daylight saving status, the confirm button, WLAN power
+//! saving and the ad hoc channel. These are configured by the user in the
+//! PSP's System Settings menu, or fixed by the region.
 //!
 //! # Example
 //!
@@ -12,11 +13,18 @@
 //! let lang = system_param::language();
 //! let tz = system_param::timezone_offset();
 //! psp::dprintln!("Language: {:?}, TZ offset: {} min", lang, tz);
+//!
+//! let confirm = system_param::button_assignment()
+//!     .unwrap_or(system_param::ConfirmButton::Cross);
+//! if ctrl.is_pressed(confirm.confirm()) {
+//!     menu.select();
+//! }
 //! ```
 
 use crate::sys::{
-    SystemParamDateFormat, SystemParamDaylightSavings, SystemParamId, SystemParamLanguage,
-    SystemParamTimeFormat, sceUtilityGetSystemParamInt, sceUtilityGetSystemParamString,
+    CtrlButtons, SystemParamAdhocChannel, SystemParamDateFormat, SystemParamDaylightSavings,
+    SystemParamId, SystemParamLanguage, SystemParamTimeFormat, SystemParamWlanPowerSaveState,
+    sceUtilityGetSystemParamInt, sceUtilityGetSystemParamString,
 };
 
 /// Error from a system parameter operation.
@@ -86,3 +94,56 @@ pub fn daylight_saving() -> Result<bool, ParamError> {
     let val = get_int(SystemParamId::DaylightSavings)?;
     Ok(val == SystemParamDaylightSavings::Dst as i32)
 }
+
+/// Which face button confirms in system menus and dialogs.
+///
+/// Japanese and Asian firmware confirm with Circle and cancel with Cross;
+/// elsewhere it's the other way round.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfirmButton {
+    Circle,
+    Cross,
+}
+
+impl ConfirmButton {
+    /// The button that confirms.
+    pub fn confirm(self) -> CtrlButtons {
+        match self {
+            Self::Circle => CtrlButtons::CIRCLE,
+            Self::Cross => CtrlButtons::CROSS,
+        }
+    }
+
+    /// The button that cancels.
+    pub fn cancel(self) -> CtrlButtons {
+        match self {
+            Self::Circle => CtrlButtons::CROSS,
+            Self::Cross => CtrlButtons::CIRCLE,
+        }
+    }
+}
+
+/// Get the confirm button the system uses, so menus can match it.
+///
+/// Read-only and set by the firmware region. The parameter doesn't exist
+/// on Japanese firmware 1.00, where this fails.
+pub fn button_assignment() -> Result<ConfirmButton, ParamError> {
+    // Parameter 9 is the button assignment: 0 for Circle, 1 for Cross.
+    match get_int(SystemParamId::Unknown)? {
+        0 => Ok(ConfirmButton::Circle),
+        1 => Ok(ConfirmButton::Cross),
+        val => Err(ParamError(val)),
+    }
+}
+
+/// Check if WLAN power saving is enabled.
+pub fn wlan_power_save() -> Result<bool, ParamError> {
+    let val = get_int(SystemParamId::WlanPowerSave)?;
+    Ok(val == SystemParamWlanPowerSaveState::On as i32)
+}
+
+/// Get the ad hoc channel setting.
+pub fn adhoc_channel() -> Result<SystemParamAdhocChannel, ParamError> {
+    let val = get_int(SystemParamId::AdhocChannel)?;
+    SystemParamAdhocChannel::try_from(val as u32).map_err(|_| ParamError(val))
+}
@@ -0,0 +1,275 @@
+//! Cooperative, frame-driven async tasks for game scripting.
+//!
+//! Multi-frame logic such as a cutscene ("walk here, wait a second, show
+//! a line of dialog, wait for a button") reads naturally as an `async fn`
+//! instead of a hand-written state machine. [`Executor`] runs such
+//! futures on the main thread: call [`run_once()`](Executor::run_once)
+//! once per frame and every unfinished task is polled exactly once.
+//!
+//! Tasks suspend with the leaf futures in this module:
+//!
+//! - [`next_frame()`] and [`wait_frames()`] for frame counts,
+//! - [`wait_ms()`] for wall time, measured with [`Instant`],
+//! - [`wait_button()`] for a button press on a [`Controller`],
+//! - [`oneshot()`] to pass a value from one task (or the game loop) to
+//!   another.
+//!
+//! There is no waker-driven scheduling: wakers are ignored and every task
+//! is polled each frame, which costs little for the handful of tasks a
+//! game loop runs. Other futures work too, provided they make progress
+//! when polled rather than waiting to be woken.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use core::cell::RefCell;
+//! use psp::input::Controller;
+//! use psp::sys::CtrlButtons;
+//! use psp::task::{Executor, wait_button, wait_frames};
+//!
+//! let controller = RefCell::new(Controller::new());
+//! let mut executor = Executor::new();
+//! executor.spawn(async {
+//!     psp::dprintln!("Press X");
+//!     wait_button(&controller, CtrlButtons::CROSS).await;
+//!     wait_frames(60).await;
+//!     psp::dprintln!("One second later");
+//! });
+//!
+//! while !executor.is_empty() {
+//!     controller.borrow_mut().update();
+//!     executor.run_once();
+//!     unsafe { psp::sys::sceDisplayWaitVblankStart() };
+//! }
+//! ```
+
+use alloc::boxed::Box;
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll, Waker};
+
+use crate::input::Controller;
+use crate::sys::CtrlButtons;
+use crate::time::{Duration, Instant};
+
+/// A single-threaded executor that polls its tasks once per frame.
+///
+/// Tasks may borrow from the enclosing scope for `'a`, so a cutscene can
+/// share the game's state through `Cell`s and `RefCell`s without `Rc`.
+pub struct Executor<'a> {
+    tasks: Vec<Pin<Box<dyn Future<Output = ()> + 'a>>>,
+}
+
+impl<'a> Executor<'a> {
+    /// Create an executor with no tasks.
+    pub fn new() -> Self {
+        Self { tasks: Vec::new() }
+    }
+
+    /// Add a task. It is first polled by the next
+    /// [`run_once()`](Self::run_once).
+    pub fn spawn<F>(&mut self, future: F)
+    where
+        F: Future<Output = ()> + 'a,
+    {
+        self.tasks.push(Box::pin(future));
+    }
+
+    /// Poll every task once, dropping the ones that finish.
+    ///
+    /// Call once per frame. Returns the number of tasks still running.
+    pub fn run_once(&mut self) -> usize {
+        let mut cx = Context::from_waker(Waker::noop());
+        self.tasks
+            .retain_mut(|task| task.as_mut().poll(&mut cx).is_pending());
+        self.tasks.len()
+    }
+
+    /// Number of unfinished tasks.
+    pub fn len(&self) -> usize {
+        self.tasks.len()
+    }
+
+    /// Whether all tasks have finished.
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+
+    /// Drop all tasks, finished or not.
+    pub fn clear(&mut self) {
+        self.tasks.clear();
+    }
+}
+
+impl Default for Executor<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Future returned by [`wait_frames()`] and [`next_frame()`].
+#[must_use = "futures do nothing unless awaited"]
+pub struct WaitFrames {
+    remaining: u32,
+}
+
+impl Future for WaitFrames {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<()> {
+        if self.remaining == 0 {
+            Poll::Ready(())
+        } else {
+            self.remaining -= 1;
+            Poll::Pending
+        }
+    }
+}
+
+/// Suspend until the next [`Executor::run_once()`].
+pub fn next_frame() -> WaitFrames {
+    wait_frames(1)
+}
+
+/// Suspend for `frames` calls of [`Executor::run_once()`]. Zero frames
+/// completes immediately.
+pub fn wait_frames(frames: u32) -> WaitFrames {
+    WaitFrames { remaining: frames }
+}
+
+/// Future returned by [`wait_ms()`].
+#[must_use = "futures do nothing unless awaited"]
+pub struct WaitTime {
+    duration: Duration,
+    start: Option<Instant>,
+}
+
+impl Future for WaitTime {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<()> {
+        let start = *self.start.get_or_insert_with(Instant::now);
+        if start.elapsed() >= self.duration {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+/// Suspend until at least `ms` milliseconds have passed.
+///
+/// Time is measured from the first poll, and completion is only noticed
+/// on a frame, so the wait rounds up to whole frames.
+pub fn wait_ms(ms: u32) -> WaitTime {
+    WaitTime {
+        duration: Duration::from_millis(ms as u64),
+        start: None,
+    }
+}
+
+/// Future returned by [`wait_button()`].
+#[must_use = "futures do nothing unless awaited"]
+pub struct WaitButton<'c> {
+    controller: &'c RefCell<Controller>,
+    buttons: CtrlButtons,
+}
+
+impl Future for WaitButton<'_> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<()> {
+        if self.controller.borrow().is_pressed(self.buttons) {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+/// Suspend until `buttons` are pressed on `controller`.
+///
+/// Completes on a frame where [`Controller::is_pressed()`] is true for
+/// `buttons`, so a button already held when the wait starts must be
+/// released and pressed again. The game loop owns the controller and
+/// calls [`Controller::update()`] before [`Executor::run_once()`]; the
+/// `RefCell` lets tasks read it in between.
+pub fn wait_button(controller: &RefCell<Controller>, buttons: CtrlButtons) -> WaitButton<'_> {
+    WaitButton {
+        controller,
+        buttons,
+    }
+}
+
+/// The [`Sender`] of a [`oneshot()`] channel was dropped without sending.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecvError;
+
+impl core::fmt::Display for RecvError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("oneshot sender dropped without sending")
+    }
+}
+
+struct Channel<T> {
+    value: Option<T>,
+    closed: bool,
+}
+
+/// Sending half of a [`oneshot()`] channel.
+pub struct Sender<T> {
+    channel: Rc<RefCell<Channel<T>>>,
+}
+
+impl<T> Sender<T> {
+    /// Send `value` to the receiver.
+    ///
+    /// The value is dropped if the receiver is already gone.
+    pub fn send(self, value: T) {
+        self.channel.borrow_mut().value = Some(value);
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        self.channel.borrow_mut().closed = true;
+    }
+}
+
+/// Receiving half of a [`oneshot()`] channel. Await it for the value.
+#[must_use = "futures do nothing unless awaited"]
+pub struct Receiver<T> {
+    channel: Rc<RefCell<Channel<T>>>,
+}
+
+impl<T> Future for Receiver<T> {
+    type Output = Result<T, RecvError>;
+
+    fn poll(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut channel = self.channel.borrow_mut();
+        match channel.value.take() {
+            Some(value) => Poll::Ready(Ok(value)),
+            None if channel.closed => Poll::Ready(Err(RecvError)),
+            None => Poll::Pending,
+        }
+    }
+}
+
+/// Create a channel that carries a single value from a [`Sender`] to a
+/// [`Receiver`], e.g. a dialog choice from the task that shows the dialog
+/// to the one that acts on it.
+pub fn oneshot<T>() -> (Sender<T>, Receiver<T>) {
+    let channel = Rc::new(RefCell::new(Channel {
+        value: None,
+        closed: false,
+    }));
+    (
+        Sender {
+            channel: channel.clone(),
+        },
+        Receiver { channel },
+    )
+}
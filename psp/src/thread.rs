@@ -17,16 +17,73 @@
 //! let result = handle.join().unwrap();
 //! assert_eq!(result, 42);
 //! ```
+//!
+//! [`scope()`] spawns threads that may borrow from the caller's stack:
+//!
+//! ```ignore
+//! let level = load_level();
+//! thread::scope(|s| {
+//!     s.spawn(b"bake\0", || bake_lighting(&level)).unwrap();
+//!     s.spawn(b"nav\0", || build_navmesh(&level)).unwrap();
+//! })
+//! .unwrap();
+//! ```
+//!
+//! # Stack usage
+//!
+//! A thread whose stack is too small silently corrupts whatever lies below
+//! it. [`ThreadBuilder::stack_canary()`] fills a new thread's unused stack
+//! with a pattern, and [`JoinHandle::stack_usage()`] reports how much of
+//! it was overwritten, so stack sizes can be tuned from measurements:
+//!
+//! ```ignore
+//! let handle = ThreadBuilder::new(b"decoder\0")
+//!     .stack_size(16 * 1024)
+//!     .stack_canary(true)
+//!     .spawn(decode)
+//!     .unwrap();
+//! let (_, usage) = handle.join_with_stack_usage().unwrap();
+//! if let Some(usage) = usage {
+//!     psp::dprintln!("decoder used {} of {} bytes", usage.used, usage.size);
+//! }
+//! ```
+//!
+//! [`paint_main_stack()`] does the same for the main thread created by
+//! [`module!`](crate::module), and [`check_stack_headroom()`] measures
+//! the calling thread.
+//!
+//! # Listing threads
+//!
+//! [`list()`] reports every thread with its state and priorities, for
+//! debug overlays and tracking down deadlocks:
+//!
+//! ```ignore
+//! for t in thread::list().unwrap() {
+//!     psp::dprintln!("{:#x} {:<24} {:?} prio {}", t.uid.0, t.name, t.status, t.current_priority);
+//! }
+//! ```
+//!
+//! [`introspect`](crate::introspect) lists other kernel objects and
+//! memory alongside threads, and draws them as an on-screen overlay.
 
+use crate::sync::SpinMutex;
 use crate::sys::{
-    SceUid, ThreadAttributes, sceKernelCreateThread, sceKernelDelayThread, sceKernelDeleteThread,
-    sceKernelGetThreadExitStatus, sceKernelGetThreadId, sceKernelSleepThread, sceKernelStartThread,
+    SceKernelIdListType, SceKernelThreadInfo, SceUid, ThreadAttributes, sceKernelCreateThread,
+    sceKernelDelayThread, sceKernelDeleteThread, sceKernelGetThreadExitStatus,
+    sceKernelGetThreadId, sceKernelGetThreadStackFreeSize, sceKernelGetThreadmanIdList,
+    sceKernelReferThreadStatus, sceKernelSleepThread, sceKernelStartThread,
     sceKernelTerminateDeleteThread, sceKernelWaitThreadEnd,
 };
 use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
 use core::ffi::c_void;
+use core::marker::PhantomData;
 use core::sync::atomic::{AtomicBool, Ordering};
 
+/// Exit status of a thread whose closure panicked.
+pub const PANIC_EXIT_STATUS: i32 = -0x7FFF_FFFF;
+
 // ── ThreadError ─────────────────────────────────────────────────────
 
 /// Error from a PSP thread operation, wrapping the raw SCE error code.
@@ -73,6 +130,7 @@ pub struct ThreadBuilder {
     priority: i32,
     stack_size: i32,
     attributes: ThreadAttributes,
+    stack_canary: bool,
 }
 
 impl ThreadBuilder {
@@ -83,6 +141,7 @@ impl ThreadBuilder {
             priority: 32,
             stack_size: 64 * 1024,
             attributes: ThreadAttributes::USER | ThreadAttributes::VFPU,
+            stack_canary: false,
         }
     }
 
@@ -104,6 +163,18 @@ impl ThreadBuilder {
         self
     }
 
+    /// Fill the thread's unused stack with a pattern before running the
+    /// closure, so [`JoinHandle::stack_usage()`] can measure how much of
+    /// the stack it used. Off by default.
+    ///
+    /// Filling costs time proportional to the stack size, so this is
+    /// meant for development builds. Up to 32 threads can be measured at
+    /// once; beyond that `stack_usage()` returns `None`.
+    pub fn stack_canary(mut self, enable: bool) -> Self {
+        self.stack_canary = enable;
+        self
+    }
+
     /// Spawn the thread, running `f` on it.
     ///
     /// The closure must be `Send + 'static` because it runs on a different
@@ -117,9 +188,42 @@ impl ThreadBuilder {
             self.priority,
             self.stack_size,
             self.attributes,
-            f,
+            self.stack_canary,
+            Box::new(f),
         )
     }
+
+    /// Spawn a thread inside a [`scope()`], running `f` on it.
+    ///
+    /// Unlike [`spawn()`](Self::spawn), `f` may borrow anything that
+    /// outlives the scope.
+    pub fn spawn_scoped<'scope, 'env, F: FnOnce() -> i32 + Send + 'scope>(
+        self,
+        scope: &'scope Scope<'scope, 'env>,
+        f: F,
+    ) -> Result<ScopedJoinHandle<'scope>, ThreadError> {
+        let f: Box<dyn FnOnce() -> i32 + Send + 'scope> = Box::new(f);
+        // SAFETY: `scope()` joins or terminates every thread registered
+        // with it before `'scope` ends, so the closure never outlives its
+        // borrows.
+        let f: Box<dyn FnOnce() -> i32 + Send + 'static> = unsafe { core::mem::transmute(f) };
+        let handle = spawn_inner(
+            self.name,
+            self.priority,
+            self.stack_size,
+            self.attributes,
+            self.stack_canary,
+            f,
+        )?;
+        let thid = handle.id();
+        let mut threads = scope.threads.lock();
+        threads.push(Some(handle));
+        Ok(ScopedJoinHandle {
+            threads: &scope.threads,
+            index: threads.len() - 1,
+            thid,
+        })
+    }
 }
 
 // ── ThreadPayload ───────────────────────────────────────────────────
@@ -131,6 +235,8 @@ struct ThreadPayload {
     closure: Option<Box<dyn FnOnce() -> i32 + Send + 'static>>,
     /// Set to `true` by the trampoline after consuming the closure.
     consumed: AtomicBool,
+    /// Whether the trampoline should paint the stack first.
+    stack_canary: bool,
 }
 
 // ── spawn ───────────────────────────────────────────────────────────
@@ -150,12 +256,13 @@ pub fn spawn<F: FnOnce() -> i32 + Send + 'static>(
 }
 
 /// Internal spawn implementation.
-fn spawn_inner<F: FnOnce() -> i32 + Send + 'static>(
+fn spawn_inner(
     name: &'static [u8],
     priority: i32,
     stack_size: i32,
     attributes: ThreadAttributes,
-    f: F,
+    stack_canary: bool,
+    f: Box<dyn FnOnce() -> i32 + Send + 'static>,
 ) -> Result<JoinHandle, ThreadError> {
     // Validate null termination — the PSP kernel expects a C string.
     // Without this check, safe code could cause out-of-bounds reads.
@@ -165,8 +272,9 @@ fn spawn_inner<F: FnOnce() -> i32 + Send + 'static>(
 
     // Box the closure into a ThreadPayload with an atomic flag.
     let payload = Box::into_raw(Box::new(ThreadPayload {
-        closure: Some(Box::new(f)),
+        closure: Some(f),
         consumed: AtomicBool::new(false),
+        stack_canary,
     }));
 
     let thid = unsafe {
@@ -231,9 +339,12 @@ unsafe extern "C" fn trampoline(_args: usize, argp: *mut c_void) -> i32 {
     // Mark as consumed BEFORE running, so Drop won't try to free it
     // even if the thread is terminated mid-execution.
     payload.consumed.store(true, Ordering::Release);
+    if payload.stack_canary {
+        paint_current_stack();
+    }
     match crate::catch_unwind(core::panic::AssertUnwindSafe(closure)) {
         Ok(code) => code,
-        Err(_) => -0x7FFF_FFFF, // panic sentinel
+        Err(_) => PANIC_EXIT_STATUS,
     }
 }
 
@@ -259,12 +370,21 @@ unsafe impl Send for JoinHandle {}
 
 impl JoinHandle {
     /// Block until the thread exits and return its exit status.
-    pub fn join(mut self) -> Result<i32, ThreadError> {
+    pub fn join(self) -> Result<i32, ThreadError> {
+        self.join_with_stack_usage().map(|(status, _)| status)
+    }
+
+    /// Like [`join()`](Self::join), also returning the thread's final
+    /// [`stack_usage()`](Self::stack_usage).
+    pub fn join_with_stack_usage(mut self) -> Result<(i32, Option<StackUsage>), ThreadError> {
         let ret = unsafe { sceKernelWaitThreadEnd(self.thid, core::ptr::null_mut()) };
         if ret < 0 {
             return Err(ThreadError(ret));
         }
         self.joined = true;
+        // Measure before deleting the thread frees its stack.
+        let usage = self.stack_usage();
+        unregister_painted(self.thid);
         // Retrieve the actual thread exit status.
         let exit_status = unsafe { sceKernelGetThreadExitStatus(self.thid) };
         let del = unsafe { sceKernelDeleteThread(self.thid) };
@@ -274,7 +394,16 @@ impl JoinHandle {
         if del < 0 {
             return Err(ThreadError(del));
         }
-        Ok(exit_status)
+        Ok((exit_status, usage))
+    }
+
+    /// The thread's stack usage so far, if it was spawned with
+    /// [`ThreadBuilder::stack_canary()`] and has started running.
+    ///
+    /// Reads the stack while the thread may still be using it, so the
+    /// result of a running thread is a snapshot.
+    pub fn stack_usage(&self) -> Option<StackUsage> {
+        painted_stack_usage(self.thid)
     }
 
     /// Get the thread's kernel UID.
@@ -291,6 +420,7 @@ impl Drop for JoinHandle {
         // Forcibly terminate and delete the thread. This is synchronous:
         // after it returns the thread is dead.
         unsafe { sceKernelTerminateDeleteThread(self.thid) };
+        unregister_painted(self.thid);
         // Check the atomic flag to determine if the trampoline already
         // consumed the closure. This prevents a double-free race where
         // the thread finishes between the wait-check and terminate.
@@ -304,6 +434,384 @@ impl Drop for JoinHandle {
     }
 }
 
+// ── Scoped threads ──────────────────────────────────────────────────
+
+/// Spawn threads that can borrow non-`'static` data.
+///
+/// All threads spawned through the [`Scope`] are joined before `scope`
+/// returns, which is what makes the borrows sound. If `f` panics, the
+/// remaining threads are terminated while the panic unwinds.
+///
+/// Returns `f`'s result, or an error if a thread that was not joined
+/// explicitly panicked ([`PANIC_EXIT_STATUS`]) or could not be joined.
+///
+/// # Example
+///
+/// ```ignore
+/// let counter = SpinMutex::new(0);
+/// thread::scope(|s| {
+///     for _ in 0..4 {
+///         s.spawn(b"worker\0", || {
+///             *counter.lock() += 1;
+///             0
+///         })
+///         .unwrap();
+///     }
+/// })
+/// .unwrap();
+/// assert_eq!(*counter.lock(), 4);
+/// ```
+pub fn scope<'env, F, T>(f: F) -> Result<T, ThreadError>
+where
+    F: for<'scope> FnOnce(&'scope Scope<'scope, 'env>) -> T,
+{
+    // If `f` unwinds, dropping `scope` drops the remaining JoinHandles,
+    // which terminates their threads before the borrowed data goes away.
+    let scope = Scope {
+        threads: SpinMutex::new(Vec::new()),
+        scope: PhantomData,
+        env: PhantomData,
+    };
+    let value = f(&scope);
+    scope.join_all().map(|()| value)
+}
+
+/// A scope for spawning borrowing threads, created by [`scope()`].
+pub struct Scope<'scope, 'env: 'scope> {
+    /// Handles of threads not yet joined. Slots are emptied, not removed,
+    /// so [`ScopedJoinHandle`] indices stay valid.
+    threads: SpinMutex<Vec<Option<JoinHandle>>>,
+    scope: PhantomData<&'scope mut &'scope ()>,
+    env: PhantomData<&'env mut &'env ()>,
+}
+
+impl<'scope, 'env> Scope<'scope, 'env> {
+    /// Spawn a thread with default settings within this scope.
+    ///
+    /// Equivalent to `ThreadBuilder::new(name).spawn_scoped(self, f)`.
+    pub fn spawn<F: FnOnce() -> i32 + Send + 'scope>(
+        &'scope self,
+        name: &'static [u8],
+        f: F,
+    ) -> Result<ScopedJoinHandle<'scope>, ThreadError> {
+        ThreadBuilder::new(name).spawn_scoped(self, f)
+    }
+
+    /// Join every remaining thread, including ones spawned while joining.
+    fn join_all(&self) -> Result<(), ThreadError> {
+        let mut result = Ok(());
+        loop {
+            let next = self.threads.lock().iter_mut().find_map(Option::take);
+            let Some(handle) = next else {
+                return result;
+            };
+            let status = match handle.join() {
+                Ok(PANIC_EXIT_STATUS) => Err(ThreadError(PANIC_EXIT_STATUS)),
+                Ok(_) => Ok(()),
+                Err(e) => Err(e),
+            };
+            result = result.and(status);
+        }
+    }
+}
+
+/// A handle to a thread spawned within a [`Scope`].
+///
+/// Dropping it does not terminate the thread; it is joined when the
+/// scope ends instead.
+pub struct ScopedJoinHandle<'scope> {
+    threads: &'scope SpinMutex<Vec<Option<JoinHandle>>>,
+    index: usize,
+    thid: SceUid,
+}
+
+impl ScopedJoinHandle<'_> {
+    /// Block until the thread exits and return its exit status.
+    pub fn join(self) -> Result<i32, ThreadError> {
+        let handle = self.threads.lock()[self.index].take();
+        match handle {
+            Some(handle) => handle.join(),
+            None => Err(ThreadError(-1)),
+        }
+    }
+
+    /// Get the thread's kernel UID.
+    pub fn id(&self) -> SceUid {
+        self.thid
+    }
+}
+
+// ── Stack usage ─────────────────────────────────────────────────────
+
+/// How much of a thread's stack has been used, measured by
+/// [`ThreadBuilder::stack_canary()`] or [`paint_main_stack()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StackUsage {
+    /// Stack size in bytes.
+    pub size: usize,
+    /// Deepest extent of the stack reached so far (the high-water mark),
+    /// in bytes. Accurate to within a few hundred bytes.
+    pub used: usize,
+    /// Whether the lowest [`STACK_GUARD_BYTES`] of the stack are still
+    /// untouched. `false` means the thread came within that distance of
+    /// overflowing, or overflowed and corrupted the memory below.
+    pub guard_intact: bool,
+}
+
+impl StackUsage {
+    /// Bytes of stack that have never been used.
+    pub fn headroom(&self) -> usize {
+        self.size - self.used
+    }
+}
+
+/// Size of the region at the bottom of a painted stack whose corruption
+/// is reported by [`StackUsage::guard_intact`].
+pub const STACK_GUARD_BYTES: usize = 256;
+
+const STACK_PATTERN: u32 = 0x5354_4b43; // "STKC"
+
+/// Distance kept between the painted region and the painting function's
+/// own frame.
+const PAINT_MARGIN: usize = 512;
+
+/// Most stacks that can be painted at once; further threads run without
+/// measurement until a painted one exits.
+const MAX_PAINTED: usize = 32;
+
+/// Painted stacks: thread id, lowest stack address and stack size. Fixed
+/// size so registering never allocates while the lock is held.
+static PAINTED: SpinMutex<[Option<(SceUid, usize, usize)>; MAX_PAINTED]> =
+    SpinMutex::new([None; MAX_PAINTED]);
+
+/// Fill the main thread's unused stack with the canary pattern.
+///
+/// Call this first thing in `psp_main` to measure the
+/// [`DEFAULT_MAIN_STACK_SIZE`](crate::DEFAULT_MAIN_STACK_SIZE) stack that
+/// [`module!`](crate::module) gives the main thread, then check it with
+/// [`check_stack_headroom()`]. Calling it from any other thread paints
+/// that thread's stack instead.
+pub fn paint_main_stack() {
+    paint_current_stack();
+}
+
+/// Stack usage of the calling thread, if its stack was painted by
+/// [`ThreadBuilder::stack_canary()`] or [`paint_main_stack()`].
+pub fn check_stack_headroom() -> Option<StackUsage> {
+    painted_stack_usage(current_thread_id())
+}
+
+/// Paint the calling thread's stack below the current frame and register
+/// it for measurement.
+#[inline(never)]
+fn paint_current_stack() {
+    let thid = current_thread_id();
+    let Ok(info) = refer_status(thid) else {
+        return;
+    };
+    let base = info.stack as usize;
+    let size = info.stack_size as usize;
+
+    // The address of a local is close enough to the stack pointer; stay
+    // well below it so the loop never overwrites a live frame.
+    let marker = 0u32;
+    let top =
+        (core::hint::black_box(&marker) as *const u32 as usize).saturating_sub(PAINT_MARGIN) & !3;
+    if top <= base || top > base + size {
+        return;
+    }
+
+    let mut addr = base;
+    while addr < top {
+        unsafe { core::ptr::write_volatile(addr as *mut u32, STACK_PATTERN) };
+        addr += 4;
+    }
+
+    let mut painted = PAINTED.lock();
+    let slot = painted
+        .iter()
+        .position(|entry| matches!(entry, Some((id, _, _)) if *id == thid))
+        .or_else(|| painted.iter().position(Option::is_none));
+    if let Some(slot) = slot {
+        painted[slot] = Some((thid, base, size));
+    }
+}
+
+fn painted_stack_usage(thid: SceUid) -> Option<StackUsage> {
+    let (base, size) = PAINTED
+        .lock()
+        .iter()
+        .flatten()
+        .find(|&&(id, _, _)| id == thid)
+        .map(|&(_, base, size)| (base, size))?;
+
+    let mut untouched = 0;
+    while untouched < size {
+        let word = unsafe { core::ptr::read_volatile((base + untouched) as *const u32) };
+        if word != STACK_PATTERN {
+            break;
+        }
+        untouched += 4;
+    }
+    Some(StackUsage {
+        size,
+        used: size - untouched,
+        guard_intact: untouched >= STACK_GUARD_BYTES,
+    })
+}
+
+fn unregister_painted(thid: SceUid) {
+    for entry in PAINTED.lock().iter_mut() {
+        if matches!(entry, Some((id, _, _)) if *id == thid) {
+            *entry = None;
+        }
+    }
+}
+
+// ── Thread listing ──────────────────────────────────────────────────
+
+/// Scheduling state of a thread.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThreadStatus {
+    /// Currently executing (the thread calling [`list()`]).
+    Running,
+    /// Ready to run, waiting for the CPU.
+    Ready,
+    /// Blocked: sleeping, delayed, or waiting on a sync object.
+    Waiting,
+    /// Suspended with `sceKernelSuspendThread`.
+    Suspended,
+    /// Both waiting and suspended.
+    WaitingSuspended,
+    /// Created but not started, or finished.
+    Dormant,
+    /// Killed.
+    Killed,
+    /// A status value not listed here.
+    Unknown(i32),
+}
+
+impl ThreadStatus {
+    /// Decode the `status` field of `SceKernelThreadInfo`.
+    pub fn from_raw(status: i32) -> Self {
+        match status {
+            0x01 => Self::Running,
+            0x02 => Self::Ready,
+            0x04 => Self::Waiting,
+            0x08 => Self::Suspended,
+            0x0C => Self::WaitingSuspended,
+            0x10 => Self::Dormant,
+            0x20 => Self::Killed,
+            other => Self::Unknown(other),
+        }
+    }
+}
+
+/// A snapshot of one thread's state, from [`list()`] or [`info()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThreadInfo {
+    pub uid: SceUid,
+    pub name: String,
+    pub status: ThreadStatus,
+    /// Priority the thread was created with (lower runs first).
+    pub priority: i32,
+    /// Priority now, after any `sceKernelChangeThreadPriority`.
+    pub current_priority: i32,
+    /// Stack size in bytes.
+    pub stack_size: usize,
+    /// Bytes of stack the thread has never touched, as measured by the
+    /// kernel, or `None` if it couldn't tell.
+    pub stack_free: Option<usize>,
+}
+
+impl ThreadInfo {
+    fn from_raw(uid: SceUid, raw: &SceKernelThreadInfo) -> Self {
+        let len = raw
+            .name
+            .iter()
+            .position(|&b| b == 0)
+            .unwrap_or(raw.name.len());
+        Self {
+            uid,
+            name: String::from_utf8_lossy(&raw.name[..len]).into_owned(),
+            status: ThreadStatus::from_raw(raw.status),
+            priority: raw.init_priority,
+            current_priority: raw.current_priority,
+            stack_size: raw.stack_size as usize,
+            stack_free: usize::try_from(unsafe { sceKernelGetThreadStackFreeSize(uid) }).ok(),
+        }
+    }
+}
+
+/// Every thread visible to the caller (user threads from user mode),
+/// in the kernel's order.
+///
+/// Threads that exit while the list is being built are left out.
+pub fn list() -> Result<Vec<ThreadInfo>, ThreadError> {
+    let uids = id_list(SceKernelIdListType::Thread)?;
+    Ok(uids.into_iter().filter_map(|uid| info(uid).ok()).collect())
+}
+
+/// Most UIDs [`id_list()`] returns, which bounds its buffer.
+const MAX_IDS: usize = 1024;
+
+/// Extra room [`id_list()`] leaves for objects created between asking
+/// for the count and fetching the UIDs.
+const ID_SLACK: usize = 8;
+
+/// How many times [`id_list()`] fetches before settling for what fit.
+const ID_LIST_ATTEMPTS: usize = 4;
+
+/// UIDs of every threadman object of `kind` visible to the caller.
+///
+/// Asks the kernel for the count with an empty buffer, then fetches into
+/// a buffer of that size plus [`ID_SLACK`], retrying if more objects
+/// appeared in between. At most [`MAX_IDS`] are returned.
+pub(crate) fn id_list(kind: SceKernelIdListType) -> Result<Vec<SceUid>, ThreadError> {
+    let mut count = 0;
+    let ret = unsafe { sceKernelGetThreadmanIdList(kind, core::ptr::null_mut(), 0, &mut count) };
+    if ret < 0 {
+        return Err(ThreadError(ret));
+    }
+
+    let mut uids = Vec::new();
+    for attempt in 1..=ID_LIST_ATTEMPTS {
+        let capacity = (count.max(0) as usize + ID_SLACK).min(MAX_IDS);
+        uids.resize(capacity, SceUid(0));
+        let ret = unsafe {
+            sceKernelGetThreadmanIdList(kind, uids.as_mut_ptr(), capacity as i32, &mut count)
+        };
+        if ret < 0 {
+            return Err(ThreadError(ret));
+        }
+        // `count` is the total, which may not have fit.
+        let total = count.max(0) as usize;
+        if total <= capacity || capacity == MAX_IDS || attempt == ID_LIST_ATTEMPTS {
+            uids.truncate(total.min(capacity));
+            break;
+        }
+    }
+    Ok(uids)
+}
+
+/// The state of one thread.
+pub fn info(uid: SceUid) -> Result<ThreadInfo, ThreadError> {
+    refer_status(uid).map(|raw| ThreadInfo::from_raw(uid, &raw))
+}
+
+fn refer_status(thid: SceUid) -> Result<SceKernelThreadInfo, ThreadError> {
+    let mut info = core::mem::MaybeUninit::<SceKernelThreadInfo>::uninit();
+    let ret = unsafe {
+        (&raw mut (*info.as_mut_ptr()).size).write(core::mem::size_of::<SceKernelThreadInfo>());
+        sceKernelReferThreadStatus(thid, info.as_mut_ptr())
+    };
+    if ret < 0 {
+        return Err(ThreadError(ret));
+    }
+    // SAFETY: the kernel filled in the structure.
+    Ok(unsafe { info.assume_init() })
+}
+
 // ── Free functions ──────────────────────────────────────────────────
 
 /// Sleep the current thread for `ms` milliseconds.
@@ -1,7 +1,8 @@
 //! Time and clock abstractions for the PSP.
 //!
 //! Provides monotonic timing ([`Instant`], [`Duration`]), wall-clock
-//! date/time ([`DateTime`]), and a frame-rate tracker ([`FrameTimer`]).
+//! date/time ([`DateTime`]), a frame-rate tracker ([`FrameTimer`]), and
+//! small no-alloc timing helpers ([`Stopwatch`], [`Cooldown`], [`Timeout`]).
 //!
 //! # Example
 //!
@@ -243,3 +244,173 @@ impl Default for FrameTimer {
         Self::new()
     }
 }
+
+// ── System time helpers ─────────────────────────────────────────────
+
+/// Current 64-bit system time in microseconds since boot.
+fn system_time_us() -> u64 {
+    unsafe { crate::sys::sceKernelGetSystemTimeWide() as u64 }
+}
+
+// ── Stopwatch ───────────────────────────────────────────────────────
+
+/// Measures elapsed time from a start point using the 64-bit system
+/// timer.
+///
+/// # Example
+///
+/// ```ignore
+/// let sw = Stopwatch::start();
+/// load_level();
+/// psp::dprintln!("level loaded in {} ms", sw.elapsed_ms());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stopwatch {
+    start_us: u64,
+}
+
+impl Stopwatch {
+    /// Start a new stopwatch at the current time.
+    pub fn start() -> Self {
+        Self {
+            start_us: system_time_us(),
+        }
+    }
+
+    /// Microseconds since the stopwatch was started or restarted.
+    pub fn elapsed_us(&self) -> u64 {
+        system_time_us().saturating_sub(self.start_us)
+    }
+
+    /// Whole milliseconds since the stopwatch was started or restarted.
+    pub fn elapsed_ms(&self) -> u64 {
+        self.elapsed_us() / 1000
+    }
+
+    /// Elapsed time as a [`Duration`].
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_micros(self.elapsed_us())
+    }
+
+    /// Reset the start point to now, returning the time elapsed before
+    /// the reset.
+    pub fn restart(&mut self) -> Duration {
+        let now = system_time_us();
+        let elapsed = now.saturating_sub(self.start_us);
+        self.start_us = now;
+        Duration::from_micros(elapsed)
+    }
+}
+
+// ── Cooldown ────────────────────────────────────────────────────────
+
+/// Rate-limits an action to at most once per period.
+///
+/// A new cooldown is ready immediately. [`trigger()`](Self::trigger)
+/// returns `true` and rearms only if the previous trigger was at least
+/// one period ago, which makes it suitable for weapon fire rates, key
+/// repeat, or toast notifications.
+///
+/// # Example
+///
+/// ```ignore
+/// let mut fire = Cooldown::new(250);
+/// loop {
+///     if ctrl.is_held(CtrlButtons::CROSS) && fire.trigger() {
+///         spawn_bullet();
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cooldown {
+    period_us: u64,
+    last_us: Option<u64>,
+}
+
+impl Cooldown {
+    /// Create a cooldown with a period of `duration_ms` milliseconds.
+    pub fn new(duration_ms: u32) -> Self {
+        Self {
+            period_us: duration_ms as u64 * 1000,
+            last_us: None,
+        }
+    }
+
+    /// Returns `true` if the period has elapsed since the last trigger.
+    pub fn is_ready(&self) -> bool {
+        self.remaining() == Duration::ZERO
+    }
+
+    /// If ready, rearm the cooldown and return `true`; otherwise leave it
+    /// untouched and return `false`.
+    pub fn trigger(&mut self) -> bool {
+        let now = system_time_us();
+        match self.last_us {
+            Some(last) if now.saturating_sub(last) < self.period_us => false,
+            _ => {
+                self.last_us = Some(now);
+                true
+            },
+        }
+    }
+
+    /// Time left until the cooldown is ready again.
+    pub fn remaining(&self) -> Duration {
+        match self.last_us {
+            Some(last) => {
+                let elapsed = system_time_us().saturating_sub(last);
+                Duration::from_micros(self.period_us.saturating_sub(elapsed))
+            },
+            None => Duration::ZERO,
+        }
+    }
+
+    /// Make the cooldown ready immediately.
+    pub fn reset(&mut self) {
+        self.last_us = None;
+    }
+}
+
+// ── Timeout ─────────────────────────────────────────────────────────
+
+/// A deadline for "give up after N ms" polling loops.
+///
+/// # Example
+///
+/// ```ignore
+/// let timeout = Timeout::from_millis(5000);
+/// while !device_ready() {
+///     if timeout.expired() {
+///         return Err(MyError::TimedOut);
+///     }
+///     psp::thread::sleep_ms(10);
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timeout {
+    deadline_us: u64,
+}
+
+impl Timeout {
+    /// A deadline `ms` milliseconds from now.
+    pub fn from_millis(ms: u32) -> Self {
+        Self::after(Duration::from_millis(ms as u64))
+    }
+
+    /// A deadline `duration` from now.
+    pub fn after(duration: Duration) -> Self {
+        Self {
+            deadline_us: system_time_us().saturating_add(duration.as_micros()),
+        }
+    }
+
+    /// Returns `true` once the deadline has passed.
+    pub fn expired(&self) -> bool {
+        system_time_us() >= self.deadline_us
+    }
+
+    /// Time left before the deadline, or [`Duration::ZERO`] if expired.
+    pub fn remaining(&self) -> Duration {
+        Duration::from_micros(self.deadline_us.saturating_sub(system_time_us()))
+    }
+}
@@ -0,0 +1,149 @@
+//! Helpers for building menus and other pointer-driven UI.
+//!
+//! [`Cursor`] turns the analog stick into an on-screen pointer with click
+//! detection, for interfaces that would otherwise need a touch screen or
+//! a mouse.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use psp::input::Controller;
+//! use psp::ui::Cursor;
+//!
+//! psp::input::enable_analog();
+//! let mut ctrl = Controller::new();
+//! let mut cursor = Cursor::new();
+//!
+//! loop {
+//!     ctrl.update();
+//!     cursor.update(&ctrl, 1.0 / 60.0);
+//!     let (x, y) = cursor.position();
+//!     if cursor.clicked() {
+//!         menu.click_at(x, y);
+//!     }
+//!     draw_pointer(x, y);
+//! }
+//! ```
+
+use crate::constants::{SCREEN_HEIGHT, SCREEN_WIDTH};
+use crate::input::{Controller, apply_radial_deadzone};
+use crate::sys::CtrlButtons;
+
+/// Default cursor speed at full stick deflection, in pixels per second.
+pub const DEFAULT_CURSOR_SPEED: f32 = 240.0;
+
+/// An analog-stick-driven pointer clamped to the screen.
+///
+/// Call [`update()`](Self::update) once per frame. The stick moves the
+/// cursor at up to [`speed()`](Self::speed) pixels per second, through a
+/// radial deadzone so diagonal movement isn't snapped to the axes. The
+/// click button defaults to CROSS.
+pub struct Cursor {
+    x: f32,
+    y: f32,
+    speed: f32,
+    deadzone: f32,
+    button: CtrlButtons,
+    held: bool,
+    was_held: bool,
+}
+
+impl Cursor {
+    /// Create a cursor at the center of the screen.
+    pub fn new() -> Self {
+        Self {
+            x: SCREEN_WIDTH as f32 / 2.0,
+            y: SCREEN_HEIGHT as f32 / 2.0,
+            speed: DEFAULT_CURSOR_SPEED,
+            deadzone: 0.2,
+            button: CtrlButtons::CROSS,
+            held: false,
+            was_held: false,
+        }
+    }
+
+    /// Move the cursor by this frame's stick position and update the
+    /// click state. `dt` is the frame time in seconds.
+    pub fn update(&mut self, ctrl: &Controller, dt: f32) {
+        // The cursor applies its own deadzone; this only limits the
+        // stick vector to length 1.0.
+        let (x, y) = ctrl.analog_radial(0.0);
+        self.feed(x, y, ctrl.raw().buttons, dt);
+    }
+
+    /// Like [`update()`](Self::update), with an explicit stick position
+    /// in -1.0..=1.0 (before the deadzone) and held buttons.
+    pub fn feed(&mut self, stick_x: f32, stick_y: f32, held: CtrlButtons, dt: f32) {
+        let (dx, dy) = apply_radial_deadzone(stick_x, stick_y, self.deadzone);
+        self.set_position(self.x + dx * self.speed * dt, self.y + dy * self.speed * dt);
+        self.was_held = self.held;
+        self.held = held.contains(self.button);
+    }
+
+    /// The cursor position in screen pixels.
+    pub fn position(&self) -> (f32, f32) {
+        (self.x, self.y)
+    }
+
+    /// Move the cursor, clamped to the screen.
+    pub fn set_position(&mut self, x: f32, y: f32) {
+        self.x = x.clamp(0.0, (SCREEN_WIDTH - 1) as f32);
+        self.y = y.clamp(0.0, (SCREEN_HEIGHT - 1) as f32);
+    }
+
+    /// Whether the click button went down this frame.
+    pub fn clicked(&self) -> bool {
+        self.held && !self.was_held
+    }
+
+    /// Whether the click button is held.
+    pub fn held(&self) -> bool {
+        self.held
+    }
+
+    /// Whether the click button was let go this frame.
+    pub fn released(&self) -> bool {
+        !self.held && self.was_held
+    }
+
+    /// Set the speed at full stick deflection, in pixels per second.
+    pub fn set_speed(&mut self, pixels_per_second: f32) {
+        self.speed = pixels_per_second;
+    }
+
+    /// The speed at full stick deflection, in pixels per second.
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    /// Set the radial deadzone, as a fraction of stick travel.
+    pub fn set_deadzone(&mut self, deadzone: f32) {
+        self.deadzone = deadzone.clamp(0.0, 0.99);
+    }
+
+    /// The radial deadzone.
+    pub fn deadzone(&self) -> f32 {
+        self.deadzone
+    }
+
+    /// Set the button (or chord) that clicks.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `button` is empty.
+    pub fn set_button(&mut self, button: CtrlButtons) {
+        assert!(!button.is_empty(), "cursor needs a click button");
+        self.button = button;
+    }
+
+    /// The button that clicks.
+    pub fn button(&self) -> CtrlButtons {
+        self.button
+    }
+}
+
+impl Default for Cursor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
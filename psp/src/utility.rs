@@ -0,0 +1,283 @@
+//! Refcounted loading of firmware utility modules.
+//!
+//! Many system libraries live in PRX modules that a user-mode app must load
+//! with `sceUtilityLoadModule` before calling into them: `sceMp3*` needs
+//! [`UtilityModule::AvMp3`], `sceNetInet*` needs
+//! [`UtilityModule::NetInet`], and so on. Calling a library whose module
+//! isn't loaded fails with an opaque error code.
+//!
+//! [`load_module()`] loads a module together with the modules it depends
+//! on, and counts references so independent users of the same module can
+//! coexist: the module is only unloaded once every [`load_module()`] has
+//! been matched by an [`unload_module()`]. Modules that were already
+//! loaded by someone else (the firmware, a plugin, or direct syscalls) are
+//! used as-is and never unloaded by this module.
+//!
+//! [`crate::mp3::Mp3Decoder`], [`crate::net::init()`] and
+//! [`crate::http::HttpClient`] load what they need automatically.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use psp::utility::{self, UtilityModule};
+//!
+//! utility::load_module(UtilityModule::AvAtrac3Plus)?; // also loads AvCodec
+//! // ... sceAtrac* calls ...
+//! utility::unload_module(UtilityModule::AvAtrac3Plus)?;
+//! ```
+
+use crate::sync::SpinMutex;
+use crate::sys::{self, Module};
+
+/// A firmware module loadable with `sceUtilityLoadModule`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UtilityModule {
+    NetCommon,
+    NetAdhoc,
+    NetInet,
+    NetParseUri,
+    NetParseHttp,
+    NetHttp,
+    NetSsl,
+    UsbPspCm,
+    UsbAcc,
+    UsbMic,
+    UsbCam,
+    UsbGps,
+    AvCodec,
+    AvSascore,
+    AvAtrac3Plus,
+    AvMpegBase,
+    AvMp3,
+    AvVaudio,
+    AvAac,
+    AvG729,
+    NpCommon,
+    NpService,
+    NpMatching2,
+    NpDrm,
+    Irda,
+}
+
+const MODULE_COUNT: usize = UtilityModule::Irda as usize + 1;
+
+impl UtilityModule {
+    /// The `sceUtilityLoadModule` id.
+    pub fn raw(self) -> Module {
+        match self {
+            Self::NetCommon => Module::NetCommon,
+            Self::NetAdhoc => Module::NetAdhoc,
+            Self::NetInet => Module::NetInet,
+            Self::NetParseUri => Module::NetParseUri,
+            Self::NetParseHttp => Module::NetParseHttp,
+            Self::NetHttp => Module::NetHttp,
+            Self::NetSsl => Module::NetSsl,
+            Self::UsbPspCm => Module::UsbPspCm,
+            Self::UsbAcc => Module::UsbAcc,
+            Self::UsbMic => Module::UsbMic,
+            Self::UsbCam => Module::UsbCam,
+            Self::UsbGps => Module::UsbGps,
+            Self::AvCodec => Module::AvCodec,
+            Self::AvSascore => Module::AvSascore,
+            Self::AvAtrac3Plus => Module::AvAtrac3Plus,
+            Self::AvMpegBase => Module::AvMpegBase,
+            Self::AvMp3 => Module::AvMp3,
+            Self::AvVaudio => Module::AvVaudio,
+            Self::AvAac => Module::AvAac,
+            Self::AvG729 => Module::AvG729,
+            Self::NpCommon => Module::NpCommon,
+            Self::NpService => Module::NpService,
+            Self::NpMatching2 => Module::NpMatching2,
+            Self::NpDrm => Module::NpDrm,
+            Self::Irda => Module::Irda,
+        }
+    }
+
+    /// Modules that must be loaded first, in load order.
+    pub fn dependencies(self) -> &'static [UtilityModule] {
+        match self {
+            Self::NetAdhoc | Self::NetInet | Self::NetParseUri => &[Self::NetCommon],
+            Self::NetParseHttp => &[Self::NetParseUri],
+            Self::NetHttp => &[Self::NetInet, Self::NetParseHttp],
+            Self::NetSsl => &[Self::NetInet],
+            Self::UsbMic | Self::UsbCam | Self::UsbGps => &[Self::UsbAcc],
+            Self::AvAtrac3Plus | Self::AvMpegBase | Self::AvMp3 | Self::AvAac => &[Self::AvCodec],
+            Self::NpService | Self::NpMatching2 => &[Self::NpCommon],
+            _ => &[],
+        }
+    }
+}
+
+/// Error from loading or unloading a utility module.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct ModuleError {
+    /// The module whose load or unload failed (possibly a dependency of
+    /// the one requested).
+    pub module: UtilityModule,
+    /// The SCE error code.
+    pub code: i32,
+}
+
+impl core::fmt::Debug for ModuleError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "ModuleError({:?}, {:#010x})",
+            self.module, self.code as u32
+        )
+    }
+}
+
+impl core::fmt::Display for ModuleError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "utility module {:?} failed: {:#010x}",
+            self.module, self.code as u32
+        )
+    }
+}
+
+/// `sceUtilityLoadModule`: the module is already loaded.
+const ERROR_ALREADY_LOADED: i32 = 0x8011_1102_u32 as i32;
+/// `sceUtilityUnloadModule`: the module is not loaded.
+const ERROR_NOT_LOADED: i32 = 0x8011_1103_u32 as i32;
+/// The module was loaded by other means (e.g. `sceKernelLoadModule`).
+const ERROR_EXCLUSIVE_LOAD: i32 = 0x8002_0139_u32 as i32;
+
+#[derive(Clone, Copy)]
+struct Slot {
+    refs: u32,
+    /// Whether the module was loaded here, and so should be unloaded here.
+    owned: bool,
+    /// A thread is loading or unloading the module with `SLOTS` released.
+    busy: bool,
+}
+
+/// Reference counts. Only held to read and update them: module loads and
+/// unloads block for a long time, so they run with the lock released and
+/// the slot marked [`busy`](Slot::busy).
+static SLOTS: SpinMutex<[Slot; MODULE_COUNT]> = SpinMutex::new(
+    [Slot {
+        refs: 0,
+        owned: false,
+        busy: false,
+    }; MODULE_COUNT],
+);
+
+/// How long to sleep while another thread loads or unloads a module.
+const BUSY_WAIT_US: u32 = 1000;
+
+/// Load `module` and its dependencies, or add a reference if already
+/// loaded.
+pub fn load_module(module: UtilityModule) -> Result<(), ModuleError> {
+    let deps = module.dependencies();
+    for (i, &dep) in deps.iter().enumerate() {
+        if let Err(e) = load_module(dep) {
+            release_deps(&deps[..i]);
+            return Err(e);
+        }
+    }
+    if let Err(e) = load_one(module) {
+        release_deps(deps);
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// Drop a reference to `module`, unloading it and its dependencies once
+/// unused.
+///
+/// Fails with the firmware's "not loaded" error if `module` has no
+/// references left.
+pub fn unload_module(module: UtilityModule) -> Result<(), ModuleError> {
+    match unload_one(module) {
+        Err(e) if e.code == ERROR_NOT_LOADED => Err(e),
+        result => {
+            release_deps(module.dependencies());
+            result
+        },
+    }
+}
+
+/// Number of outstanding [`load_module()`] calls for `module`, including
+/// those made implicitly for modules that depend on it.
+pub fn ref_count(module: UtilityModule) -> u32 {
+    SLOTS.lock()[module as usize].refs
+}
+
+/// Lock `SLOTS` once no other thread is loading or unloading `module`.
+fn lock_idle(module: UtilityModule) -> crate::sync::SpinGuard<'static, [Slot; MODULE_COUNT]> {
+    loop {
+        let slots = SLOTS.lock();
+        if !slots[module as usize].busy {
+            return slots;
+        }
+        drop(slots);
+        unsafe { sys::sceKernelDelayThread(BUSY_WAIT_US) };
+    }
+}
+
+/// Add a reference to `module` alone, loading it for the first one.
+fn load_one(module: UtilityModule) -> Result<(), ModuleError> {
+    {
+        let mut slots = lock_idle(module);
+        let slot = &mut slots[module as usize];
+        if slot.refs > 0 {
+            slot.refs += 1;
+            return Ok(());
+        }
+        slot.busy = true;
+    }
+
+    let ret = unsafe { sys::sceUtilityLoadModule(module.raw()) };
+
+    let mut slots = SLOTS.lock();
+    let slot = &mut slots[module as usize];
+    slot.busy = false;
+    if ret < 0 && ret != ERROR_ALREADY_LOADED && ret != ERROR_EXCLUSIVE_LOAD {
+        return Err(ModuleError { module, code: ret });
+    }
+    slot.owned = ret >= 0;
+    slot.refs = 1;
+    Ok(())
+}
+
+/// Drop a reference to `module` alone, unloading it after the last one if
+/// it was loaded here.
+fn unload_one(module: UtilityModule) -> Result<(), ModuleError> {
+    {
+        let mut slots = lock_idle(module);
+        let slot = &mut slots[module as usize];
+        if slot.refs == 0 {
+            return Err(ModuleError {
+                module,
+                code: ERROR_NOT_LOADED,
+            });
+        }
+        if slot.refs > 1 || !slot.owned {
+            slot.refs -= 1;
+            return Ok(());
+        }
+        slot.busy = true;
+    }
+
+    let ret = unsafe { sys::sceUtilityUnloadModule(module.raw()) };
+
+    let mut slots = SLOTS.lock();
+    let slot = &mut slots[module as usize];
+    slot.busy = false;
+    slot.refs = 0;
+    slot.owned = false;
+    if ret < 0 {
+        return Err(ModuleError { module, code: ret });
+    }
+    Ok(())
+}
+
+/// Release references taken on `deps`, in reverse load order.
+fn release_deps(deps: &[UtilityModule]) {
+    for &dep in deps.iter().rev() {
+        let _ = unload_module(dep);
+    }
+}
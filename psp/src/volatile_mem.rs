@@ -0,0 +1,237 @@
+//! Volatile memory: the extra 4 MB RAM region as a scratch arena.
+//!
+//! The system reserves a 4 MB region for suspend/resume and game sharing,
+//! which an application may lock with `sceKernelVolatileMemLock` and use as
+//! extra RAM for caches and decoded assets. [`lock()`] returns a
+//! [`VolatileRegion`] that hands out memory from the region with a bump
+//! allocator and unlocks it on drop. To use the region as one raw buffer
+//! without suspend tracking, see [`crate::mem::VolatileMem`].
+//!
+//! # Lifetimes
+//!
+//! Allocations borrow the region, so none can outlive the unlock. Memory
+//! is only reclaimed all at once via [`VolatileRegion::reset()`], which
+//! takes `&mut self` and therefore requires every allocation to be gone.
+//!
+//! # Suspend and resume
+//!
+//! The contents of volatile memory are lost when the PSP suspends. The
+//! region registers a power callback, and once a suspend has been seen it
+//! is *stale*: allocation and [`VolatileRegion::check()`] return
+//! [`VolatileError::IsStale`] until [`reset()`](VolatileRegion::reset) is
+//! called. Data read from a stale region is garbage and must be rebuilt,
+//! which is why the allocator only hands out plain bytes.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use psp::volatile_mem;
+//!
+//! let mut region = volatile_mem::lock()?;
+//! let cache = region.alloc_bytes(1024 * 1024, 16)?;
+//! decode_into(cache);
+//! // ... later, before reusing `cache` ...
+//! if region.check().is_err() {
+//!     region.reset();
+//!     // re-decode
+//! }
+//! ```
+
+use crate::power::{PowerCallbackHandle, PowerError};
+use crate::sys::PowerInfo;
+use core::cell::Cell;
+use core::ffi::c_void;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+/// Error from a volatile memory operation.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum VolatileError {
+    /// The lock syscall failed (e.g. the region is already locked).
+    Kernel(i32),
+    /// Registering the suspend/resume callback failed.
+    Power(PowerError),
+    /// Not enough space left in the region.
+    OutOfMemory,
+    /// The PSP suspended since the region was locked or last reset, so its
+    /// contents are gone.
+    IsStale,
+}
+
+impl core::fmt::Debug for VolatileError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Kernel(code) => write!(f, "VolatileError::Kernel({:#010x})", *code as u32),
+            Self::Power(e) => write!(f, "VolatileError::Power({:?})", e),
+            Self::OutOfMemory => f.write_str("VolatileError::OutOfMemory"),
+            Self::IsStale => f.write_str("VolatileError::IsStale"),
+        }
+    }
+}
+
+impl core::fmt::Display for VolatileError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Kernel(code) => write!(f, "volatile memory lock failed: {:#010x}", *code as u32),
+            Self::Power(e) => write!(f, "power callback registration failed: {}", e),
+            Self::OutOfMemory => f.write_str("volatile memory region exhausted"),
+            Self::IsStale => f.write_str("volatile memory lost on suspend"),
+        }
+    }
+}
+
+impl From<PowerError> for VolatileError {
+    fn from(e: PowerError) -> Self {
+        Self::Power(e)
+    }
+}
+
+/// Incremented by the power callback on every suspend or resume.
+static SUSPEND_GENERATION: AtomicU32 = AtomicU32::new(0);
+
+unsafe extern "C" fn power_handler(_count: i32, power_info: i32, _common: *mut c_void) -> i32 {
+    let info = PowerInfo::from_bits_retain(power_info as u32);
+    if info.intersects(PowerInfo::SUSPENDING | PowerInfo::RESUMING | PowerInfo::POWER_SWITCH) {
+        SUSPEND_GENERATION.fetch_add(1, Ordering::AcqRel);
+    }
+    0
+}
+
+/// Lock the volatile memory region, waiting if another user holds it.
+pub fn lock() -> Result<VolatileRegion, VolatileError> {
+    lock_with(crate::sys::sceKernelVolatileMemLock)
+}
+
+/// Lock the volatile memory region, failing immediately if it is in use.
+pub fn try_lock() -> Result<VolatileRegion, VolatileError> {
+    lock_with(crate::sys::sceKernelVolatileMemTryLock)
+}
+
+/// Signature of `sceKernelVolatileMemLock` and `sceKernelVolatileMemTryLock`.
+pub(crate) type LockFn = unsafe extern "C" fn(i32, *mut *mut c_void, *mut i32) -> i32;
+
+/// Lock the region with `lock_fn` and return its base and size, or the SCE
+/// error code. The caller must [`unlock`] it.
+pub(crate) fn lock_raw(lock_fn: LockFn) -> Result<(*mut u8, usize), i32> {
+    let mut base: *mut c_void = core::ptr::null_mut();
+    let mut size: i32 = 0;
+    let ret = unsafe { lock_fn(0, &mut base, &mut size) };
+    if ret < 0 {
+        return Err(ret);
+    }
+    Ok((base as *mut u8, size as usize))
+}
+
+/// Unlock the region taken by [`lock_raw`].
+pub(crate) fn unlock() {
+    unsafe {
+        crate::sys::sceKernelVolatileMemUnlock(0);
+    }
+}
+
+fn lock_with(lock_fn: LockFn) -> Result<VolatileRegion, VolatileError> {
+    let (base, size) = lock_raw(lock_fn).map_err(VolatileError::Kernel)?;
+    // Register before sampling the generation so no suspend is missed.
+    let power_cb = match crate::power::on_power_event(power_handler) {
+        Ok(handle) => handle,
+        Err(e) => {
+            unlock();
+            return Err(e.into());
+        },
+    };
+    Ok(VolatileRegion {
+        base,
+        size,
+        offset: Cell::new(0),
+        generation: SUSPEND_GENERATION.load(Ordering::Acquire),
+        _power_cb: power_cb,
+    })
+}
+
+/// The locked volatile memory region, with a bump allocator over it.
+///
+/// Unlocks the region on drop. See the [module documentation](self) for
+/// the lifetime and suspend/resume rules.
+pub struct VolatileRegion {
+    base: *mut u8,
+    size: usize,
+    offset: Cell<usize>,
+    generation: u32,
+    _power_cb: PowerCallbackHandle,
+}
+
+impl VolatileRegion {
+    /// Allocate `len` zeroed bytes aligned to `align` (a power of two).
+    ///
+    /// The slice borrows the region, so it cannot outlive the unlock.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `align` is not a power of two.
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_bytes(&self, len: usize, align: usize) -> Result<&mut [u8], VolatileError> {
+        assert!(align.is_power_of_two(), "align must be a power of two");
+        self.check()?;
+        let addr = self.base as usize + self.offset.get();
+        let start = (addr + align - 1) & !(align - 1);
+        let end = start
+            .checked_add(len)
+            .filter(|&end| end <= self.base as usize + self.size)
+            .ok_or(VolatileError::OutOfMemory)?;
+        self.offset.set(end - self.base as usize);
+        // SAFETY: `start..end` lies inside the locked region and the bump
+        // pointer never hands out the same bytes twice until `reset`,
+        // which requires `&mut self` and so no live borrows.
+        unsafe {
+            let ptr = start as *mut u8;
+            core::ptr::write_bytes(ptr, 0, len);
+            Ok(core::slice::from_raw_parts_mut(ptr, len))
+        }
+    }
+
+    /// Returns `Err(IsStale)` if the PSP has suspended since the region
+    /// was locked or last [`reset()`](Self::reset).
+    pub fn check(&self) -> Result<(), VolatileError> {
+        if SUSPEND_GENERATION.load(Ordering::Acquire) != self.generation {
+            Err(VolatileError::IsStale)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Whether the region's contents have been lost to a suspend.
+    pub fn is_stale(&self) -> bool {
+        self.check().is_err()
+    }
+
+    /// Free every allocation and clear the stale state.
+    pub fn reset(&mut self) {
+        self.offset.set(0);
+        self.generation = SUSPEND_GENERATION.load(Ordering::Acquire);
+    }
+
+    /// Start address of the region.
+    pub fn base(&self) -> *mut u8 {
+        self.base
+    }
+
+    /// Total size of the region in bytes.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Bytes allocated so far, including alignment padding.
+    pub fn used(&self) -> usize {
+        self.offset.get()
+    }
+
+    /// Bytes still available (before alignment padding).
+    pub fn remaining(&self) -> usize {
+        self.size - self.offset.get()
+    }
+}
+
+impl Drop for VolatileRegion {
+    fn drop(&mut self) {
+        unlock();
+    }
+}
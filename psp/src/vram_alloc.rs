@@ -53,6 +53,20 @@ impl core::fmt::Display for VramAllocError {
 /// Replaces the previous `static mut` singleton with a safe atomic pattern.
 static VRAM_TAKEN: AtomicBool = AtomicBool::new(false);
 
+/// Bytes handed out by the allocator, mirrored for [`vram_used()`].
+static VRAM_USED: AtomicU32 = AtomicU32::new(0);
+
+/// Bytes of VRAM currently allocated by the allocator from
+/// [`get_vram_allocator()`].
+pub fn vram_used() -> u32 {
+    VRAM_USED.load(Ordering::Relaxed)
+}
+
+/// Total VRAM size in bytes.
+pub fn vram_total() -> u32 {
+    total_vram_size()
+}
+
 pub fn get_vram_allocator() -> Result<VramAllocator, VramAllocatorInUseError> {
     if VRAM_TAKEN
         .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
@@ -118,6 +132,7 @@ impl SimpleVramAllocator {
     /// `&Self` that allocated them.
     pub fn free_all(&mut self) {
         self.offset.store(0, Ordering::Relaxed);
+        VRAM_USED.store(0, Ordering::Relaxed);
     }
 
     /// Allocates `size` bytes of VRAM.
@@ -140,6 +155,7 @@ impl SimpleVramAllocator {
         }
 
         self.offset.store(new_offset, Ordering::Relaxed);
+        VRAM_USED.store(new_offset, Ordering::Relaxed);
         Ok(VramMemChunk::new(old_offset, size))
     }
 